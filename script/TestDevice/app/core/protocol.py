@@ -6,7 +6,11 @@ Only the current protocol is supported.
 
 CMD_QUERY_INFO = 0x10
 CMD_FRAGMENT_PIXELS = 0x12
+# Sent by the device in ACK mode for every frame it received completely: [cmd, frame_id]
+CMD_FRAME_ACK = 0x13
 CMD_QUERY_CONFIG = 0x14
+# Host turns ACK mode on or off: [cmd, enabled]; the device replies with its new state
+CMD_SET_ACK_MODE = 0x15
 
 PROTOCOL_VERSION = 4
 
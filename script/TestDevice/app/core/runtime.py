@@ -171,7 +171,8 @@ class DeviceRuntime:
         total_fragments: int,
         fragment_index: int,
         updates: list[tuple[int, int, int, int]],
-    ) -> None:
+    ) -> bool:
+        """Apply one fragment; returns True when it completed the frame."""
         with self.buffer_lock:
             if self.current_frame_id != frame_id:
                 self.current_frame_id = frame_id
@@ -193,6 +194,8 @@ class DeviceRuntime:
                 self.front_buffer, self.back_buffer = self.back_buffer, self.front_buffer
                 self.dirty = True
                 self.frame_fragments_received.clear()
+                return True
+            return False
 
     def apply_frame_end(self, frame_id: int) -> None:
         with self.buffer_lock:
@@ -8,8 +8,10 @@ from typing import Callable, Optional
 from ..core.config import DeviceConfig, build_config_payload
 from ..core.protocol import (
     CMD_FRAGMENT_PIXELS,
+    CMD_FRAME_ACK,
     CMD_QUERY_CONFIG,
     CMD_QUERY_INFO,
+    CMD_SET_ACK_MODE,
     MAX_UDP_PAYLOAD,
     PROTOCOL_VERSION,
 )
@@ -29,6 +31,8 @@ class VirtualDeviceServer:
         self.runtime = DeviceRuntime(config)
         self._config_payload = build_config_payload(config)
         self._config_msg_id = 0
+        # ACK mode: acknowledge every complete frame to the host that turned it on.
+        self._ack_mode = False
 
         self._on_log = on_log
         self._udp_socket: Optional[socket.socket] = None
@@ -170,6 +174,14 @@ class VirtualDeviceServer:
             self._send_device_config(addr)
             return
 
+        if cmd == CMD_SET_ACK_MODE:
+            if len(payload) < 1:
+                return
+            self._ack_mode = payload[0] != 0
+            self._log(f"ACK mode {'enabled' if self._ack_mode else 'disabled'} by {addr[0]}")
+            self._send(bytes([CMD_SET_ACK_MODE, int(self._ack_mode)]), addr)
+            return
+
         if cmd == CMD_FRAGMENT_PIXELS:
             if len(payload) < 5:
                 return
@@ -179,7 +191,17 @@ class VirtualDeviceServer:
             count = payload[3] | (payload[4] << 8)
             updates = self._parse_updates(payload[5:], count)
             if updates:
-                self.runtime.apply_fragment_updates(frame_id, total_fragments, fragment_index, updates)
+                completed = self.runtime.apply_fragment_updates(frame_id, total_fragments, fragment_index, updates)
+                if completed and self._ack_mode:
+                    self._send(bytes([CMD_FRAME_ACK, frame_id]), addr)
+            return
+
+    def _send(self, packet: bytes, addr: tuple[str, int]) -> None:
+        if self._udp_socket is None:
+            return
+        try:
+            self._udp_socket.sendto(packet, addr)
+        except Exception:
             return
 
     def _parse_updates(self, payload: bytes, count_hint: Optional[int] = None) -> list[tuple[int, int, int, int]]:
@@ -1,32 +1,90 @@
 use tauri::State;
-use crate::manager::{Device, LightingManager};
-use crate::manager::inventory::list_effects;
-use crate::api::dto::{AppConfigDto, EffectInfo, EffectParamInfo, SystemInfoResponse};
+use crate::manager::{Device, LightingManager, OutputFilter, OverlaySettings, SceneSummary};
+use crate::manager::inventory::{
+    get_effect_metadata, list_controller_drivers, list_effects, preferred_drivers,
+    set_preferred_driver, set_preferred_drivers,
+};
+use crate::api::dto::{AppConfigDto, DrgbKeepaliveConfigDto, EffectGroupInfo, EffectInfo, SystemInfoResponse};
+use crate::api::i18n::{self, Locale};
 use crate::api::config_store;
-use crate::manager::PersistedDeviceConfig;
+use crate::api::schema;
+use crate::api::hotkeys::{self, HotkeyBinding, HotkeyRegistrationFailure};
+use crate::manager::{PersistedDeviceConfig, PersistedFrameSnapshot};
 
 #[cfg(any(target_os = "windows", target_os = "macos"))]
 use once_cell::sync::Lazy;
 #[cfg(any(target_os = "windows", target_os = "macos"))]
 use std::sync::Mutex;
 
+use std::collections::BTreeMap;
+use std::net::IpAddr;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
-#[cfg(any(target_os = "windows", target_os = "macos"))]
-use tauri::Manager;
+use tauri::{Emitter, Manager as _};
 
+
+use crate::resource::controller::drgb_hid::{
+    get_keepalive_settings, normalize_keepalive_settings, set_keepalive_settings,
+};
+use crate::resource::controller::led_matrix_udp::{
+    frame_acks_enabled, get_udp_payload, normalize_udp_payload, set_frame_acks_enabled,
+    set_udp_payload,
+};
+use crate::resource::controller::sacn::{
+    configured_nodes as configured_sacn_nodes, forget_node as forget_sacn_node,
+    remember_node as remember_sacn_node, set_configured_nodes as set_configured_sacn_nodes,
+    SacnController, SacnNode,
+};
+use crate::resource::controller::wiz::{known_bulbs, set_known_bulbs};
+use crate::resource::audio::brightness::AudioBrightnessSettings;
+use crate::resource::color::vision::{color_vision_mode, set_color_vision_mode, ColorVisionMode};
+use crate::resource::effect::screen_mirror::benchmark::{self, CaptureBenchmarkReport};
+use crate::resource::effect::screen_mirror::preview::{self, PreviewImage};
+use crate::manager::runner::{
+    adaptive_frame_rate_enabled, ensure_runner_state, set_adaptive_frame_rate_enabled,
+    set_soft_start_ms, soft_start_ms,
+};
+use crate::resource::driver::frame_diff::{partial_updates_enabled, set_partial_updates_enabled};
+use crate::resource::driver::net_bind::{
+    self, get_discovery_timeout_ms, normalize_discovery_timeout_ms, set_discovery_timeout_ms,
+};
+use crate::resource::driver::write_retry;
+use crate::resource::input::{self, InputKind};
 use crate::resource::screen::{
+    configure_capture as configure_screen_capture,
+    get_capture_crop as get_screen_capture_crop,
+    get_capture_target_nits as get_screen_capture_target_nits,
     get_capture_fps as get_screen_capture_fps,
     get_capture_method as get_screen_capture_method,
     get_capture_max_pixels as get_screen_capture_max_pixels,
+    get_high_bit_depth as get_screen_capture_high_bit_depth,
+    get_sample_ratio as get_screen_capture_sample_ratio,
+    get_show_cursor as get_screen_capture_show_cursor,
+    get_tone_mapping as get_screen_capture_tone_mapping,
+    has_screen_permission,
+    is_capture_paused as is_screen_capture_paused,
     list_displays as list_screen_displays,
+    open_screen_permission_settings as open_screen_permission_pane,
+    pause_capture as pause_screen_capture,
+    resume_capture as resume_screen_capture,
+    set_capture_crop as set_screen_capture_crop,
+    set_capture_target_nits as set_screen_capture_target_nits,
     set_capture_fps as set_screen_capture_fps,
     set_capture_method as set_screen_capture_method,
     set_capture_max_pixels as set_screen_capture_max_pixels,
+    set_high_bit_depth as set_screen_capture_high_bit_depth,
+    set_sample_ratio as set_screen_capture_sample_ratio,
+    set_show_cursor as set_screen_capture_show_cursor,
+    set_tone_mapping as set_screen_capture_tone_mapping,
     normalize_capture_max_pixels,
+    normalize_target_nits,
     CaptureMethod,
+    CaptureSettings,
+    CropRect,
     DisplayInfo,
     ScreenSubscription,
+    ToneMapping,
 };
 
 #[cfg(target_os = "windows")]
@@ -75,6 +133,103 @@ pub fn set_minimize_to_tray(enabled: bool, app_handle: tauri::AppHandle) {
     save_runtime_app_config_best_effort(&app_handle);
 }
 
+static CLEAR_LEDS_ON_EXIT: AtomicBool = AtomicBool::new(true);
+
+/// Upper bound for stopping runners and blanking devices on exit.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[tauri::command]
+pub fn get_clear_leds_on_exit() -> bool {
+    CLEAR_LEDS_ON_EXIT.load(Ordering::Relaxed)
+}
+
+#[tauri::command]
+pub fn set_clear_leds_on_exit(enabled: bool, app_handle: tauri::AppHandle) {
+    CLEAR_LEDS_ON_EXIT.store(enabled, Ordering::Relaxed);
+    save_runtime_app_config_best_effort(&app_handle);
+}
+
+#[tauri::command]
+pub fn get_partial_updates() -> bool {
+    partial_updates_enabled()
+}
+
+#[tauri::command]
+pub fn set_partial_updates(enabled: bool, app_handle: tauri::AppHandle) {
+    set_partial_updates_enabled(enabled);
+    save_runtime_app_config_best_effort(&app_handle);
+}
+
+#[tauri::command]
+pub fn get_adaptive_frame_rate() -> bool {
+    adaptive_frame_rate_enabled()
+}
+
+#[tauri::command]
+pub fn set_adaptive_frame_rate(enabled: bool, app_handle: tauri::AppHandle) {
+    set_adaptive_frame_rate_enabled(enabled);
+    save_runtime_app_config_best_effort(&app_handle);
+}
+
+#[tauri::command]
+pub fn get_soft_start() -> u32 {
+    soft_start_ms()
+}
+
+#[tauri::command]
+pub fn set_soft_start(ms: u32, app_handle: tauri::AppHandle) {
+    set_soft_start_ms(ms);
+    save_runtime_app_config_best_effort(&app_handle);
+}
+
+#[tauri::command]
+pub fn get_color_vision() -> ColorVisionMode {
+    color_vision_mode()
+}
+
+#[tauri::command]
+pub fn set_color_vision(mode: ColorVisionMode, app_handle: tauri::AppHandle) {
+    set_color_vision_mode(mode);
+    save_runtime_app_config_best_effort(&app_handle);
+}
+
+#[tauri::command]
+pub fn get_write_retries() -> u32 {
+    write_retry::write_retries()
+}
+
+#[tauri::command]
+pub fn set_write_retries(retries: u32, app_handle: tauri::AppHandle) {
+    write_retry::set_write_retries(retries);
+    save_runtime_app_config_best_effort(&app_handle);
+}
+
+#[tauri::command]
+pub fn get_controller_preferences() -> BTreeMap<String, String> {
+    preferred_drivers()
+}
+
+/// Takes effect on the next scan.
+#[tauri::command]
+pub fn set_controller_preference(
+    serial_id: String,
+    driver: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    set_preferred_driver(&serial_id, driver.as_deref())?;
+    save_runtime_app_config_best_effort(&app_handle);
+    Ok(())
+}
+
+/// Called once from the app exit hook.
+pub fn shutdown_devices(app_handle: &tauri::AppHandle) {
+    let clear_leds = CLEAR_LEDS_ON_EXIT.load(Ordering::Relaxed);
+    log::info!(clear_leds; "[shutdown] Stopping all devices");
+    if let Some(manager) = app_handle.try_state::<LightingManager>() {
+        manager.shutdown_all(clear_leds, SHUTDOWN_TIMEOUT);
+    }
+}
+
 // ============================================================================
 // Persisted App Config (app.json)
 // ============================================================================
@@ -90,9 +245,30 @@ fn runtime_app_config_snapshot(app_handle: &tauri::AppHandle) -> AppConfigDto {
     let mut cfg = AppConfigDto::default_for_platform();
     cfg.window_effect = window_effect;
     cfg.minimize_to_tray = get_minimize_to_tray();
+    cfg.clear_leds_on_exit = get_clear_leds_on_exit();
+    cfg.partial_updates = partial_updates_enabled();
+    cfg.adaptive_frame_rate = adaptive_frame_rate_enabled();
+    cfg.soft_start_ms = soft_start_ms();
+    cfg.color_vision = color_vision_mode();
+    cfg.write_retries = write_retry::write_retries();
+    cfg.controller_preferences = preferred_drivers();
+    cfg.hotkeys = hotkeys::bindings();
     cfg.screen_capture.max_pixels = get_screen_capture_max_pixels();
     cfg.screen_capture.fps = get_capture_fps();
     cfg.screen_capture.method = capture_method;
+    cfg.screen_capture.high_bit_depth = get_screen_capture_high_bit_depth();
+    cfg.screen_capture.show_cursor = get_screen_capture_show_cursor();
+    cfg.screen_capture.sample_ratio = get_screen_capture_sample_ratio();
+    cfg.screen_capture.tone_mapping = get_screen_capture_tone_mapping();
+    cfg.screen_capture.target_nits = get_screen_capture_target_nits();
+    cfg.screen_capture.crop = get_screen_capture_crop();
+    cfg.device_discovery.mdns_timeout_ms = get_discovery_timeout_ms();
+    cfg.device_discovery.frame_acks = frame_acks_enabled();
+    cfg.device_discovery.udp_payload = get_udp_payload();
+    cfg.device_discovery.wiz_bulbs = known_bulbs();
+    cfg.device_discovery.sacn_nodes = configured_sacn_nodes();
+    cfg.device_discovery.source_address = net_bind::source_address().map(|addr| addr.to_string());
+    (cfg.drgb_keepalive.interval_ms, cfg.drgb_keepalive.idle_threshold_ms) = get_keepalive_settings();
 
     // Ensure platform default effect is never persisted as empty string.
     if cfg.window_effect.is_empty() {
@@ -106,10 +282,36 @@ fn runtime_app_config_snapshot(app_handle: &tauri::AppHandle) -> AppConfigDto {
 pub fn apply_app_config_to_runtime(cfg: &AppConfigDto, app_handle: &tauri::AppHandle) {
     // Minimize-to-tray
     MINIMIZE_TO_TRAY.store(cfg.minimize_to_tray, Ordering::Relaxed);
+    CLEAR_LEDS_ON_EXIT.store(cfg.clear_leds_on_exit, Ordering::Relaxed);
+    set_partial_updates_enabled(cfg.partial_updates);
+    set_adaptive_frame_rate_enabled(cfg.adaptive_frame_rate);
+    set_soft_start_ms(cfg.soft_start_ms);
+    set_color_vision_mode(cfg.color_vision);
+    write_retry::set_write_retries(cfg.write_retries);
+    set_preferred_drivers(cfg.controller_preferences.clone());
+    hotkeys::set_bindings(app_handle, cfg.hotkeys.clone());
+
+    // Device discovery
+    set_discovery_timeout_ms(cfg.device_discovery.mdns_timeout_ms);
+    set_frame_acks_enabled(cfg.device_discovery.frame_acks);
+    set_udp_payload(cfg.device_discovery.udp_payload);
+    set_known_bulbs(&cfg.device_discovery.wiz_bulbs);
+    set_configured_sacn_nodes(&cfg.device_discovery.sacn_nodes);
+    match net_bind::parse_source_address(cfg.device_discovery.source_address.as_deref().unwrap_or_default()) {
+        Ok(addr) => net_bind::set_source_address(addr),
+        Err(err) => log::warn!(err:display = err; "[config] Ignoring network source address"),
+    }
+    set_keepalive_settings(cfg.drgb_keepalive.interval_ms, cfg.drgb_keepalive.idle_threshold_ms);
 
     // Screen capture
     set_screen_capture_max_pixels(cfg.screen_capture.max_pixels);
     set_screen_capture_fps(cfg.screen_capture.fps);
+    set_screen_capture_high_bit_depth(cfg.screen_capture.high_bit_depth);
+    set_screen_capture_show_cursor(cfg.screen_capture.show_cursor);
+    set_screen_capture_sample_ratio(cfg.screen_capture.sample_ratio);
+    set_screen_capture_tone_mapping(cfg.screen_capture.tone_mapping);
+    set_screen_capture_target_nits(cfg.screen_capture.target_nits);
+    set_screen_capture_crop(cfg.screen_capture.crop);
     if let Ok(requested) = cfg.screen_capture.method.parse::<CaptureMethod>() {
         set_screen_capture_method(requested);
 
@@ -180,6 +382,19 @@ pub fn set_app_config(config: AppConfigDto, app_handle: tauri::AppHandle) -> Res
     // Clamp numeric values defensively.
     cfg.screen_capture.max_pixels = normalize_capture_max_pixels(cfg.screen_capture.max_pixels);
     cfg.screen_capture.fps = cfg.screen_capture.fps.clamp(1, 60);
+    cfg.screen_capture.tone_mapping = cfg.screen_capture.tone_mapping.normalized();
+    cfg.screen_capture.target_nits = normalize_target_nits(cfg.screen_capture.target_nits);
+    cfg.screen_capture.crop = cfg.screen_capture.crop.and_then(CropRect::normalized);
+    cfg.device_discovery.mdns_timeout_ms =
+        normalize_discovery_timeout_ms(cfg.device_discovery.mdns_timeout_ms);
+    cfg.device_discovery.udp_payload = normalize_udp_payload(cfg.device_discovery.udp_payload);
+    cfg.device_discovery.source_address =
+        net_bind::parse_source_address(cfg.device_discovery.source_address.as_deref().unwrap_or_default())?
+            .map(|addr| addr.to_string());
+    (cfg.drgb_keepalive.interval_ms, cfg.drgb_keepalive.idle_threshold_ms) = normalize_keepalive_settings(
+        cfg.drgb_keepalive.interval_ms,
+        cfg.drgb_keepalive.idle_threshold_ms,
+    );
 
     // Normalize windowEffect.
     if cfg.window_effect.is_empty() {
@@ -210,7 +425,7 @@ pub struct DeviceConfigResponse {
     pub config: Option<PersistedDeviceConfig>,
 }
 
-fn save_device_config_best_effort(
+pub(crate) fn save_device_config_best_effort(
     manager: &LightingManager,
     port: &str,
     app_handle: &tauri::AppHandle,
@@ -227,6 +442,19 @@ fn save_device_config_best_effort(
     }
 }
 
+/// Start or stop the runners of `ports` after a manager call that only changed their config.
+pub(crate) fn ensure_runners_best_effort(
+    manager: &LightingManager,
+    ports: &[String],
+    app_handle: &tauri::AppHandle,
+) {
+    for port in ports {
+        if let Err(err) = ensure_runner_state(manager, port, app_handle.clone()) {
+            log::warn!(port = port.as_str(), err:display = err; "[runner] Failed to update runner state");
+        }
+    }
+}
+
 #[tauri::command]
 pub fn get_device_config(
     port: String,
@@ -249,28 +477,380 @@ pub async fn scan_devices(
     manager: State<'_, LightingManager>,
     app_handle: tauri::AppHandle,
 ) -> Result<Vec<Device>, String> {
-    // 1) Probe hardware.
-    let _ = manager.scan_devices();
-
-    // 2) Restore per-device persisted configs (best-effort) and start runners if needed.
-    let devices = manager.get_devices();
-    for d in &devices {
-        match config_store::load_device_config(&app_handle, &d.id) {
-            Ok(Some(persisted)) => {
-                if let Err(err) = manager.apply_persisted_device_config(&d.port, &persisted, app_handle.clone()) {
-                    log::warn!(port = d.port.as_str(), device_id = d.id.as_str(), err:display = err; "[config] Failed to apply persisted device config");
+    // Probe hardware. Each device is restored from its persisted config (best-effort, starting
+    // runners if needed) and announced to the frontend as soon as its driver finds it.
+    let wiz_bulbs = known_bulbs();
+    let devices = manager.scan_devices(|device| {
+        restore_persisted_device_config(&manager, &device, &app_handle);
+        let device = manager.get_device(&device.port).unwrap_or(device);
+        let _ = app_handle.emit("device-discovered", &device);
+    });
+
+    if known_bulbs() != wiz_bulbs {
+        save_runtime_app_config_best_effort(&app_handle);
+    }
+
+    Ok(devices)
+}
+
+/// Names of the registered controller drivers, as accepted by `rescan_controller_kind`.
+#[tauri::command]
+pub fn list_controller_kinds() -> Vec<String> {
+    let mut names: Vec<String> = list_controller_drivers()
+        .into_iter()
+        .map(|driver| driver.name.to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Like `scan_devices`, but probes only the named driver.
+#[tauri::command]
+pub async fn rescan_controller_kind(
+    name: String,
+    manager: State<'_, LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<Device>, String> {
+    let wiz_bulbs = known_bulbs();
+    let devices = manager.rescan_controller_kind(&name, |device| {
+        restore_persisted_device_config(&manager, &device, &app_handle);
+        let device = manager.get_device(&device.port).unwrap_or(device);
+        let _ = app_handle.emit("device-discovered", &device);
+    })?;
+
+    if known_bulbs() != wiz_bulbs {
+        save_runtime_app_config_best_effort(&app_handle);
+    }
+
+    Ok(devices)
+}
+
+/// Add an E1.31 (sACN) node by hand, since sACN can't be discovered. It is remembered in
+/// the app config and set up again by later scans. A blank `ip` sends each universe to its
+/// multicast group.
+#[tauri::command]
+pub fn add_sacn_device(
+    ip: Option<String>,
+    universe: u16,
+    led_count: usize,
+    manager: State<'_, LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<Device, String> {
+    let ip = match ip.as_deref().map(str::trim).filter(|ip| !ip.is_empty()) {
+        Some(ip) => Some(
+            ip.parse::<IpAddr>()
+                .map_err(|e| format!("Invalid IP address '{}': {}", ip, e))?,
+        ),
+        None => None,
+    };
+    let node = SacnNode { ip, universe, led_count };
+    let controller = SacnController::new(node.clone())?;
+    let device = manager.add_device(Box::new(controller))?;
+    remember_sacn_node(node);
+    save_runtime_app_config_best_effort(&app_handle);
+
+    restore_persisted_device_config(&manager, &device, &app_handle);
+    let device = manager.get_device(&device.port).unwrap_or(device);
+    let _ = app_handle.emit("device-discovered", &device);
+    Ok(device)
+}
+
+/// Remove an sACN node added with [`add_sacn_device`]: it is blanked, released and no longer
+/// set up by scans.
+#[tauri::command]
+pub fn remove_sacn_device(
+    port: String,
+    manager: State<'_, LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    if !forget_sacn_node(&port) {
+        return Err(format!("'{}' is not an added sACN node", port));
+    }
+    save_runtime_app_config_best_effort(&app_handle);
+    // A node whose socket couldn't be opened at startup was never registered.
+    if let Err(err) = manager.remove_device(&port) {
+        log::debug!(port = port.as_str(), err:display = err; "[sacn] Removed node had no device");
+    }
+    Ok(())
+}
+
+fn restore_persisted_device_config(
+    manager: &LightingManager,
+    d: &Device,
+    app_handle: &tauri::AppHandle,
+) {
+    match config_store::load_device_config(app_handle, &d.id) {
+        Ok(Some(persisted)) => {
+            if let Err(err) = manager.apply_persisted_device_config(&d.port, &persisted, app_handle.clone()) {
+                log::warn!(port = d.port.as_str(), device_id = d.id.as_str(), err:display = err; "[config] Failed to apply persisted device config");
+            }
+        }
+        Ok(None) => {}
+        Err(err) => {
+            log::warn!(port = d.port.as_str(), device_id = d.id.as_str(), err:display = err; "[config] Failed to load persisted device config");
+        }
+    }
+}
+
+/// Feed key presses or clicks to input-reactive effects, for platforms without a
+/// system-wide input source or for activity outside the computer.
+#[tauri::command]
+pub fn report_input_activity(kind: InputKind, count: Option<u64>) {
+    input::record(kind, count.unwrap_or(1));
+}
+
+#[tauri::command]
+pub fn get_hotkeys() -> Vec<HotkeyBinding> {
+    hotkeys::bindings()
+}
+
+/// Add or replace a binding (matched by id). Returns the bindings that could not be
+/// registered with the OS, e.g. because another app already owns the shortcut.
+#[tauri::command]
+pub fn set_hotkey(
+    binding: HotkeyBinding,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<HotkeyRegistrationFailure>, String> {
+    let failures = hotkeys::upsert_binding(&app_handle, binding)?;
+    save_runtime_app_config_best_effort(&app_handle);
+    Ok(failures)
+}
+
+#[tauri::command]
+pub fn delete_hotkey(
+    id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<HotkeyRegistrationFailure>, String> {
+    let failures = hotkeys::remove_binding(&app_handle, &id)?;
+    save_runtime_app_config_best_effort(&app_handle);
+    Ok(failures)
+}
+
+// ============================================================================
+// Frame snapshots (frames.json)
+// ============================================================================
+
+/// How often the last written frames are checked for changes to save for
+/// `restore_frame_snapshots`.
+const FRAME_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5);
+/// Unchanged frames are still rewritten this often, so a static scene stays recent enough
+/// to be re-sent.
+const FRAME_SNAPSHOT_REFRESH: Duration = Duration::from_secs(60);
+
+/// Queue the frames saved before the last exit (or crash), so the next scan can re-send
+/// them before the restored effects produce their first frame.
+pub fn restore_frame_snapshots(manager: &LightingManager, app_handle: &tauri::AppHandle) {
+    match config_store::load_frame_snapshots(app_handle) {
+        Ok(snapshots) => manager.set_pending_frame_snapshots(snapshots),
+        Err(err) => log::warn!(err:display = err; "[config] Failed to load frame snapshots"),
+    }
+}
+
+/// Periodically save what every device shows, for the app's whole lifetime. The file is
+/// only rewritten when a frame changed or the saved one is getting old.
+pub fn start_frame_snapshot_saver(app_handle: tauri::AppHandle) {
+    let spawned = std::thread::Builder::new()
+        .name("frame-snapshots".to_string())
+        .spawn(move || {
+            let mut saved: Option<(std::time::Instant, BTreeMap<String, PersistedFrameSnapshot>)> =
+                None;
+            loop {
+                std::thread::sleep(FRAME_SNAPSHOT_INTERVAL);
+                let snapshots = app_handle.state::<LightingManager>().frame_snapshots();
+                let unchanged = match &saved {
+                    Some((at, previous)) => {
+                        same_frames(previous, &snapshots)
+                            && (snapshots.is_empty() || at.elapsed() < FRAME_SNAPSHOT_REFRESH)
+                    }
+                    // Nothing shown yet; keep the file around for the devices still to be found.
+                    None => snapshots.is_empty(),
+                };
+                if unchanged {
+                    continue;
                 }
+                match config_store::save_frame_snapshots(&app_handle, &snapshots) {
+                    Ok(()) => saved = Some((std::time::Instant::now(), snapshots)),
+                    Err(err) => {
+                        log::warn!(err:display = err; "[config] Failed to persist frame snapshots")
+                    }
+                }
+            }
+        });
+    if let Err(err) = spawned {
+        log::warn!(err:display = err; "[config] Failed to start frame snapshot saver");
+    }
+}
+
+/// Whether both hold the same colors for the same devices, whenever each was taken.
+fn same_frames(
+    a: &BTreeMap<String, PersistedFrameSnapshot>,
+    b: &BTreeMap<String, PersistedFrameSnapshot>,
+) -> bool {
+    a.len() == b.len()
+        && a
+            .iter()
+            .zip(b)
+            .all(|((ka, va), (kb, vb))| ka == kb && va.outputs == vb.outputs)
+}
+
+// ============================================================================
+// Device health checks
+// ============================================================================
+
+/// How often network controllers are pinged; a device is marked disconnected after
+/// `MAX_MISSED_PINGS` of these go unanswered.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Ping every device that supports it for the app's whole lifetime, report the ones that
+/// stop or start answering, and bring back devices their runner gave up on.
+pub fn start_health_checker(app_handle: tauri::AppHandle) {
+    let spawned = std::thread::Builder::new()
+        .name("health-check".to_string())
+        .spawn(move || loop {
+            std::thread::sleep(HEALTH_CHECK_INTERVAL);
+            let manager = app_handle.state::<LightingManager>();
+            for (port, status) in manager.ping_devices() {
+                crate::manager::runner::emit_status(&app_handle, &port, &status);
             }
-            Ok(None) => {}
-            Err(err) => {
-                log::warn!(port = d.port.as_str(), device_id = d.id.as_str(), err:display = err; "[config] Failed to load persisted device config");
+
+            let reconnected = manager.reconnect_lost_devices();
+            let ports: Vec<String> = reconnected.iter().map(|device| device.port.clone()).collect();
+            ensure_runners_best_effort(&manager, &ports, &app_handle);
+            for device in reconnected {
+                let port = device.port.as_str();
+                if let Ok(snapshot) = manager.get_device(port) {
+                    crate::manager::runner::emit_status(&app_handle, port, &snapshot.status);
+                }
+                crate::manager::runner::emit_reconnected(&app_handle, &device);
             }
-        }
+        });
+    if let Err(err) = spawned {
+        log::warn!(err:display = err; "[health] Failed to start health checker");
+    }
+}
+
+// ============================================================================
+// Scenes (scenes.json)
+// ============================================================================
+
+fn save_scenes_best_effort(manager: &LightingManager, app_handle: &tauri::AppHandle) {
+    if let Err(err) = config_store::save_scenes(app_handle, &manager.scenes()) {
+        log::warn!(err:display = err; "[config] Failed to persist scenes");
+    }
+}
+
+pub fn restore_scenes(manager: &LightingManager, app_handle: &tauri::AppHandle) {
+    match config_store::load_scenes(app_handle) {
+        Ok(scenes) => manager.set_scenes(scenes),
+        Err(err) => log::warn!(err:display = err; "[config] Failed to load scenes"),
     }
+}
+
+#[tauri::command]
+pub fn save_scene(
+    name: String,
+    manager: State<'_, LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.save_scene(&name)?;
+    save_scenes_best_effort(&manager, &app_handle);
+    Ok(())
+}
 
+#[tauri::command]
+pub fn list_scenes(manager: State<'_, LightingManager>) -> Vec<SceneSummary> {
+    manager.list_scenes()
+}
+
+/// Returns the updated device list.
+#[tauri::command]
+pub fn apply_scene(
+    name: String,
+    manager: State<'_, LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<Device>, String> {
+    let applied = manager.apply_scene(&name)?;
+    ensure_runners_best_effort(&manager, &applied, &app_handle);
+    for port in &applied {
+        save_device_config_best_effort(&manager, port, &app_handle);
+    }
     Ok(manager.get_devices())
 }
 
+#[tauri::command]
+pub fn delete_scene(
+    name: String,
+    manager: State<'_, LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.delete_scene(&name)?;
+    save_scenes_best_effort(&manager, &app_handle);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_mdns_timeout_ms(timeout_ms: u32, app_handle: tauri::AppHandle) {
+    set_discovery_timeout_ms(timeout_ms);
+    save_runtime_app_config_best_effort(&app_handle);
+}
+
+#[tauri::command]
+pub fn get_mdns_timeout_ms() -> u32 {
+    get_discovery_timeout_ms()
+}
+
+#[tauri::command]
+pub fn get_frame_acks() -> bool {
+    frame_acks_enabled()
+}
+
+#[tauri::command]
+pub fn set_frame_acks(enabled: bool, app_handle: tauri::AppHandle) {
+    set_frame_acks_enabled(enabled);
+    save_runtime_app_config_best_effort(&app_handle);
+}
+
+#[tauri::command]
+pub fn get_udp_payload_bytes() -> u32 {
+    get_udp_payload()
+}
+
+/// Takes effect on the next scan; out-of-range sizes are clamped.
+#[tauri::command]
+pub fn set_udp_payload_bytes(bytes: u32, app_handle: tauri::AppHandle) {
+    set_udp_payload(bytes);
+    save_runtime_app_config_best_effort(&app_handle);
+}
+
+#[tauri::command]
+pub fn get_network_source_address() -> Option<String> {
+    net_bind::source_address().map(|addr| addr.to_string())
+}
+
+/// Takes effect on the next scan; `None` or a blank string restores OS routing.
+#[tauri::command]
+pub fn set_network_source_address(address: Option<String>, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let addr = net_bind::parse_source_address(address.as_deref().unwrap_or_default())?;
+    net_bind::set_source_address(addr);
+    save_runtime_app_config_best_effort(&app_handle);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_drgb_keepalive(interval_ms: u32, idle_threshold_ms: u32, app_handle: tauri::AppHandle) {
+    set_keepalive_settings(interval_ms, idle_threshold_ms);
+    save_runtime_app_config_best_effort(&app_handle);
+}
+
+#[tauri::command]
+pub fn get_drgb_keepalive() -> DrgbKeepaliveConfigDto {
+    let (interval_ms, idle_threshold_ms) = get_keepalive_settings();
+    DrgbKeepaliveConfigDto {
+        interval_ms,
+        idle_threshold_ms,
+    }
+}
+
 #[tauri::command]
 pub fn get_devices(manager: State<'_, LightingManager>) -> Result<Vec<Device>, String> {
     Ok(manager.get_devices())
@@ -282,20 +862,76 @@ pub fn get_device(port: String, manager: State<'_, LightingManager>) -> Result<D
 }
 
 #[tauri::command]
-pub fn get_effects() -> Vec<EffectInfo> {
+pub fn get_effects(locale: Option<String>) -> Vec<EffectInfo> {
+    let locale = locale.as_deref().and_then(Locale::parse);
     list_effects()
         .into_iter()
-        .map(|e| EffectInfo {
-            id: e.id,
-            name: e.name,
-            description: e.description,
-            group: e.group,
-            icon: e.icon,
-            params: e.params.iter().map(EffectParamInfo::from).collect(),
-        })
+        .map(|e| EffectInfo::from_metadata(e, locale))
         .collect()
 }
 
+/// JSON Schema of `effect_id`'s params, for generated UIs and external control payloads.
+#[tauri::command]
+pub fn get_effect_schema(
+    effect_id: String,
+    locale: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let meta = get_effect_metadata(&effect_id)
+        .ok_or_else(|| format!("Effect '{}' not found", effect_id))?;
+    let locale = locale.as_deref().and_then(Locale::parse);
+    Ok(schema::effect_schema(meta, locale))
+}
+
+/// Effects grouped by their `group`, optionally filtered by a case-insensitive `query`
+/// matched against name and description (both built-in and localized).
+#[tauri::command]
+pub fn get_effect_groups(query: Option<String>, locale: Option<String>) -> Vec<EffectGroupInfo> {
+    let locale = locale.as_deref().and_then(Locale::parse);
+    let query = query
+        .map(|q| q.trim().to_lowercase())
+        .filter(|q| !q.is_empty());
+
+    let mut groups: Vec<EffectGroupInfo> = Vec::new();
+    for meta in list_effects() {
+        let info = EffectInfo::from_metadata(meta, locale);
+
+        if let Some(query) = &query {
+            let haystack = [
+                Some(meta.name),
+                meta.description,
+                Some(info.name),
+                info.description,
+            ];
+            let matches = haystack
+                .iter()
+                .flatten()
+                .any(|text| text.to_lowercase().contains(query.as_str()));
+            if !matches {
+                continue;
+            }
+        }
+
+        match groups.iter_mut().find(|g| g.id == meta.group) {
+            Some(group) => group.effects.push(info),
+            None => groups.push(EffectGroupInfo {
+                id: meta.group,
+                name: meta
+                    .group
+                    .map(|group| i18n::translate(locale, &format!("group.{}", group), group))
+                    .unwrap_or(""),
+                effects: vec![info],
+            }),
+        }
+    }
+
+    // Stable output regardless of inventory registration order; ungrouped effects go last.
+    groups.sort_by(|a, b| a.id.is_none().cmp(&b.id.is_none()).then(a.name.cmp(b.name)));
+    for group in &mut groups {
+        group.effects.sort_by(|a, b| a.name.cmp(b.name));
+    }
+    groups
+}
+
 #[tauri::command]
 pub fn get_displays() -> Vec<DisplayInfoResponse> {
     match list_screen_displays() {
@@ -307,6 +943,34 @@ pub fn get_displays() -> Vec<DisplayInfoResponse> {
     }
 }
 
+/// Measure achievable capture (+ render) FPS on a display without touching running effects.
+#[tauri::command]
+pub async fn run_capture_benchmark(
+    display_index: usize,
+    seconds: f64,
+    include_render: Option<bool>,
+) -> Result<CaptureBenchmarkReport, String> {
+    if !seconds.is_finite() {
+        return Err(format!("Invalid benchmark duration: {}", seconds));
+    }
+    let duration = Duration::from_secs_f64(seconds.clamp(1.0, 30.0));
+    let include_render = include_render.unwrap_or(true);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        benchmark::run_capture_benchmark(display_index, duration, include_render)
+    })
+    .await
+    .map_err(|e| format!("Benchmark task failed: {}", e))?
+}
+
+/// One downscaled frame of a display for the UI, without starting an effect.
+#[tauri::command]
+pub async fn capture_preview(display_index: usize) -> Result<PreviewImage, String> {
+    tauri::async_runtime::spawn_blocking(move || preview::capture_preview(display_index))
+        .await
+        .map_err(|e| format!("Preview task failed: {}", e))?
+}
+
 #[tauri::command]
 pub fn set_effect(
     port: String,
@@ -359,19 +1023,63 @@ pub fn set_scope_effect(
     Ok(())
 }
 
+/// Fill the whole device with one color; returns the device as it ends up.
 #[tauri::command]
-pub fn update_scope_effect_params(
+pub fn set_device_solid_color(
     port: String,
-    output_id: Option<String>,
-    segment_id: Option<String>,
-    params: serde_json::Value,
+    color: crate::interface::controller::Color,
     manager: State<LightingManager>,
     app_handle: tauri::AppHandle,
-) -> Result<(), String> {
-    manager.update_scope_effect_params(
-        &port,
-        output_id.as_deref(),
-        segment_id.as_deref(),
+) -> Result<Device, String> {
+    let device = manager.set_device_solid_color(&port, color)?;
+    ensure_runner_state(&manager, &port, app_handle.clone())?;
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(device)
+}
+
+/// Turn the whole device off; returns the device as it ends up.
+#[tauri::command]
+pub fn clear_device(
+    port: String,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<Device, String> {
+    let device = manager.clear_device(&port)?;
+    ensure_runner_state(&manager, &port, app_handle.clone())?;
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(device)
+}
+
+/// Select `effect_id` on every output of `port` matching `filter`; returns the changed
+/// output ids. Fails without changing anything if one output can't take the effect.
+#[tauri::command]
+pub fn apply_effect_to_outputs(
+    port: String,
+    filter: OutputFilter,
+    effect_id: String,
+    params: Option<serde_json::Value>,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<String>, String> {
+    let applied = manager.apply_effect_to_outputs(&port, &filter, &effect_id, params)?;
+    ensure_runner_state(&manager, &port, app_handle.clone())?;
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(applied)
+}
+
+#[tauri::command]
+pub fn update_scope_effect_params(
+    port: String,
+    output_id: Option<String>,
+    segment_id: Option<String>,
+    params: serde_json::Value,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.update_scope_effect_params(
+        &port,
+        output_id.as_deref(),
+        segment_id.as_deref(),
         params,
     )?;
 
@@ -379,6 +1087,87 @@ pub fn update_scope_effect_params(
     Ok(())
 }
 
+#[tauri::command]
+pub fn set_scope_overlay(
+    port: String,
+    output_id: Option<String>,
+    segment_id: Option<String>,
+    overlay: Option<OverlaySettings>,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.set_scope_overlay(
+        &port,
+        output_id.as_deref(),
+        segment_id.as_deref(),
+        overlay,
+    )?;
+
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_scope_overlay_params(
+    port: String,
+    output_id: Option<String>,
+    segment_id: Option<String>,
+    params: serde_json::Value,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.update_scope_overlay_params(
+        &port,
+        output_id.as_deref(),
+        segment_id.as_deref(),
+        params,
+    )?;
+
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_scope_phase_offset(
+    port: String,
+    output_id: Option<String>,
+    segment_id: Option<String>,
+    offset_ms: u32,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.set_scope_phase_offset(
+        &port,
+        output_id.as_deref(),
+        segment_id.as_deref(),
+        offset_ms,
+    )?;
+
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
+/// Modulate a scope's brightness with audio loudness; `settings: null` turns it off.
+#[tauri::command]
+pub fn set_scope_audio_brightness(
+    port: String,
+    output_id: Option<String>,
+    segment_id: Option<String>,
+    settings: Option<AudioBrightnessSettings>,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.set_scope_audio_brightness(
+        &port,
+        output_id.as_deref(),
+        segment_id.as_deref(),
+        settings,
+    )?;
+
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
 #[tauri::command]
 pub fn set_output_segments(
     port: String,
@@ -392,6 +1181,233 @@ pub fn set_output_segments(
     Ok(())
 }
 
+#[tauri::command]
+pub fn set_output_leds_count(
+    port: String,
+    output_id: String,
+    leds_count: usize,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.set_output_leds_count(&port, &output_id, leds_count)?;
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_output_led_positions(
+    port: String,
+    output_id: String,
+    positions: Option<Vec<crate::interface::controller::LedPosition>>,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.set_output_led_positions(&port, &output_id, positions)?;
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
+/// `None` makes the output follow the app-wide soft-start again.
+#[tauri::command]
+pub fn set_output_soft_start(
+    port: String,
+    output_id: String,
+    soft_start_ms: Option<u32>,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.set_output_soft_start(&port, &output_id, soft_start_ms)?;
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
+/// The device's setup as a versioned JSON document to share with identical rigs.
+#[tauri::command]
+pub fn export_device_layout(
+    port: String,
+    manager: State<LightingManager>,
+) -> Result<serde_json::Value, String> {
+    manager.export_layout(&port)
+}
+
+/// Rejects layouts that don't fit the device's outputs without changing anything.
+#[tauri::command]
+pub fn import_device_layout(
+    port: String,
+    layout: serde_json::Value,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.import_layout(&port, layout)?;
+    ensure_runner_state(&manager, &port, app_handle.clone())?;
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
+/// `rotation` is clockwise; the flips apply after it.
+#[tauri::command]
+pub fn set_output_transform(
+    port: String,
+    output_id: String,
+    rotation: crate::interface::controller::Rotation,
+    flip_h: bool,
+    flip_v: bool,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.set_output_transform(&port, &output_id, rotation, flip_h, flip_v)?;
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
+/// Snapshot every connected device's config to `state.json`. Returns how many were saved.
+#[tauri::command]
+pub fn persist_state(
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<usize, String> {
+    let path = config_store::state_file_path(&app_handle)?;
+    manager.save_state(&path)
+}
+
+/// Apply `state.json` to the connected devices. Returns the ports that were restored.
+#[tauri::command]
+pub fn restore_state(
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<String>, String> {
+    let path = config_store::state_file_path(&app_handle)?;
+    let restored = manager.load_state(&path)?;
+    ensure_runners_best_effort(&manager, &restored, &app_handle);
+    // Keep the per-device files in step, so the next start loads the restored config.
+    for port in &restored {
+        save_device_config_best_effort(&manager, port, &app_handle);
+    }
+    Ok(restored)
+}
+
+/// Record the frames written to a device under `<log dir>/frames` (see
+/// `manager::recorder` for the file format). Returns the file being written.
+#[tauri::command]
+pub fn start_frame_recording(
+    port: String,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let dir = app_handle
+        .path()
+        .app_log_dir()
+        .map_err(|e| format!("Failed to resolve app log dir: {e}"))?
+        .join("frames");
+    let path = manager.start_frame_recording(&port, &dir)?;
+    log::info!(port = port.as_str(), path:display = path.display(); "[recorder] Recording frames");
+    Ok(path.display().to_string())
+}
+
+/// Returns the last file written, or `None` if the device wasn't recording.
+#[tauri::command]
+pub fn stop_frame_recording(
+    port: String,
+    manager: State<LightingManager>,
+) -> Result<Option<String>, String> {
+    let path = manager.stop_frame_recording(&port)?;
+    Ok(path.map(|p| p.display().to_string()))
+}
+
+/// A disabled output stays black but keeps its effects and layout.
+#[tauri::command]
+pub fn set_output_enabled(
+    port: String,
+    output_id: String,
+    enabled: bool,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.set_output_enabled(&port, &output_id, enabled)?;
+    ensure_runner_state(&manager, &port, app_handle.clone())?;
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
+/// `None` makes the device follow the app-wide color-blindness correction again.
+#[tauri::command]
+pub fn set_device_color_vision(
+    port: String,
+    mode: Option<ColorVisionMode>,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.set_device_color_vision(&port, mode)?;
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
+/// Blend each frame sent to the device with the one before; 0 turns it off.
+#[tauri::command]
+pub fn set_device_smoothing(
+    port: String,
+    smoothing: f32,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.set_device_smoothing(&port, smoothing)?;
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
+/// Cap the current the device's LEDs may draw, in mA; `None` removes the cap.
+#[tauri::command]
+pub fn set_device_power_limit(
+    port: String,
+    max_milliamps: Option<u32>,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.set_device_power_limit(&port, max_milliamps)?;
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
+/// Set how the device's LEDs are wired, for drivers that can't detect it.
+#[tauri::command]
+pub fn set_device_color_order(
+    port: String,
+    order: crate::interface::controller::ColorOrder,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.set_device_color_order(&port, order)?;
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
+/// Move an output's segments into `new_order` (every segment id once).
+#[tauri::command]
+pub fn reorder_segments(
+    port: String,
+    output_id: String,
+    new_order: Vec<String>,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.reorder_segments(&port, &output_id, new_order)?;
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
+/// List the device's outputs in `order` (every output id once). Display only.
+#[tauri::command]
+pub fn set_output_order(
+    port: String,
+    order: Vec<String>,
+    manager: State<LightingManager>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    manager.set_output_order(&port, order)?;
+    save_device_config_best_effort(&manager, &port, &app_handle);
+    Ok(())
+}
+
 #[tauri::command]
 pub fn set_brightness(
     port: String,
@@ -457,11 +1473,129 @@ pub fn set_capture_method(method: String, app_handle: tauri::AppHandle) {
     save_runtime_app_config_best_effort(&app_handle);
 }
 
+/// Set the pixel budget, frame rate and (optionally) method in one step and report the
+/// values that took effect. `method` keeps the current one when omitted.
+#[tauri::command]
+pub fn configure_capture(
+    max_pixels: u32,
+    fps: u8,
+    method: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<CaptureSettings, String> {
+    let method = match method {
+        Some(method) => method.parse::<CaptureMethod>()?,
+        None => get_screen_capture_method(),
+    };
+    let settings = configure_screen_capture(max_pixels, fps, method);
+    save_runtime_app_config_best_effort(&app_handle);
+    Ok(settings)
+}
+
 #[tauri::command]
 pub fn get_capture_method() -> String {
     get_screen_capture_method().to_string()
 }
 
+#[tauri::command]
+pub fn set_capture_high_bit_depth(enabled: bool, app_handle: tauri::AppHandle) {
+    set_screen_capture_high_bit_depth(enabled);
+    save_runtime_app_config_best_effort(&app_handle);
+}
+
+#[tauri::command]
+pub fn get_capture_high_bit_depth() -> bool {
+    get_screen_capture_high_bit_depth()
+}
+
+#[tauri::command]
+pub fn set_capture_show_cursor(enabled: bool, app_handle: tauri::AppHandle) {
+    set_screen_capture_show_cursor(enabled);
+    save_runtime_app_config_best_effort(&app_handle);
+}
+
+#[tauri::command]
+pub fn get_capture_show_cursor() -> bool {
+    get_screen_capture_show_cursor()
+}
+
+#[tauri::command]
+pub fn set_capture_sample_ratio(percent: u8, app_handle: tauri::AppHandle) {
+    set_screen_capture_sample_ratio(percent);
+    save_runtime_app_config_best_effort(&app_handle);
+}
+
+#[tauri::command]
+pub fn get_capture_sample_ratio() -> u8 {
+    get_screen_capture_sample_ratio()
+}
+
+/// Affects HDR displays captured through DXGI; other backends ignore it.
+#[tauri::command]
+pub fn set_capture_tone_mapping(tone_mapping: ToneMapping, app_handle: tauri::AppHandle) {
+    set_screen_capture_tone_mapping(tone_mapping);
+    save_runtime_app_config_best_effort(&app_handle);
+}
+
+#[tauri::command]
+pub fn get_capture_tone_mapping() -> ToneMapping {
+    get_screen_capture_tone_mapping()
+}
+
+/// Nits that light the LEDs fully when capturing an HDR display through DXGI, clamped to
+/// 100–4000. Returns the value as applied; other backends stay at the default.
+#[tauri::command]
+pub fn set_capture_target_nits(nits: u32, app_handle: tauri::AppHandle) -> u32 {
+    set_screen_capture_target_nits(nits);
+    save_runtime_app_config_best_effort(&app_handle);
+    get_screen_capture_target_nits()
+}
+
+#[tauri::command]
+pub fn get_capture_target_nits() -> u32 {
+    get_screen_capture_target_nits()
+}
+
+/// `None` captures the whole display. Returns the crop as applied, after clamping; a crop
+/// covering the whole display comes back as `None`.
+#[tauri::command]
+pub fn set_capture_crop(crop: Option<CropRect>, app_handle: tauri::AppHandle) -> Option<CropRect> {
+    set_screen_capture_crop(crop);
+    save_runtime_app_config_best_effort(&app_handle);
+    get_screen_capture_crop()
+}
+
+#[tauri::command]
+pub fn get_capture_crop() -> Option<CropRect> {
+    get_screen_capture_crop()
+}
+
+/// Runtime-only (not persisted): pausing frees every capturer, and screen effects show
+/// no new frames until capture is resumed.
+#[tauri::command]
+pub fn set_capture_paused(paused: bool) {
+    if paused {
+        pause_screen_capture();
+    } else {
+        resume_screen_capture();
+    }
+}
+
+#[tauri::command]
+pub fn get_capture_paused() -> bool {
+    is_screen_capture_paused()
+}
+
+/// Whether screen capture is allowed. Only macOS gates it (Screen Recording permission).
+#[tauri::command]
+pub fn check_screen_permission() -> bool {
+    has_screen_permission()
+}
+
+#[tauri::command]
+pub fn open_screen_permission_settings() -> Result<(), String> {
+    open_screen_permission_pane()
+}
+
 // ============================================================================
 // Window background effects - shared API
 // ============================================================================
@@ -1,8 +1,9 @@
+use std::collections::BTreeMap;
 use std::io::Write;
 use tauri::Manager;
 
 use crate::api::dto::AppConfigDto;
-use crate::manager::PersistedDeviceConfig;
+use crate::manager::{PersistedDeviceConfig, PersistedFrameSnapshot, PersistedScene};
 
 fn app_config_file_path(app_handle: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
     let base = app_handle
@@ -14,6 +15,37 @@ fn app_config_file_path(app_handle: &tauri::AppHandle) -> Result<std::path::Path
     Ok(base.join("app.json"))
 }
 
+fn scenes_file_path(app_handle: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let base = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to resolve app config dir: {e}"))?;
+    std::fs::create_dir_all(&base)
+        .map_err(|e| format!("Failed to create app config dir '{base:?}': {e}"))?;
+    Ok(base.join("scenes.json"))
+}
+
+fn frames_file_path(app_handle: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let base = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to resolve app config dir: {e}"))?;
+    std::fs::create_dir_all(&base)
+        .map_err(|e| format!("Failed to create app config dir '{base:?}': {e}"))?;
+    Ok(base.join("frames.json"))
+}
+
+/// Where `persist_state`/`restore_state` keep the whole-setup snapshot.
+pub fn state_file_path(app_handle: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let base = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to resolve app config dir: {e}"))?;
+    std::fs::create_dir_all(&base)
+        .map_err(|e| format!("Failed to create app config dir '{base:?}': {e}"))?;
+    Ok(base.join("state.json"))
+}
+
 fn devices_dir_path(app_handle: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
     let base = app_handle
         .path()
@@ -116,3 +148,82 @@ pub fn save_device_config(
 
     Ok(())
 }
+
+pub fn load_scenes(app_handle: &tauri::AppHandle) -> Result<BTreeMap<String, PersistedScene>, String> {
+    let path = scenes_file_path(app_handle)?;
+
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let raw = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read scenes '{path:?}': {e}"))?;
+
+    serde_json::from_str::<BTreeMap<String, PersistedScene>>(&raw)
+        .map_err(|e| format!("Failed to parse scenes '{path:?}': {e}"))
+}
+
+pub fn save_scenes(
+    app_handle: &tauri::AppHandle,
+    scenes: &BTreeMap<String, PersistedScene>,
+) -> Result<(), String> {
+    let path = scenes_file_path(app_handle)?;
+
+    let json = serde_json::to_string_pretty(scenes)
+        .map_err(|e| format!("Failed to serialize scenes: {e}"))?;
+
+    let tmp = path.with_extension("json.tmp");
+    {
+        let mut f = std::fs::File::create(&tmp)
+            .map_err(|e| format!("Failed to create scenes '{tmp:?}': {e}"))?;
+        f.write_all(json.as_bytes())
+            .map_err(|e| format!("Failed to write scenes '{tmp:?}': {e}"))?;
+        f.flush()
+            .map_err(|e| format!("Failed to flush scenes '{tmp:?}': {e}"))?;
+    }
+    std::fs::rename(&tmp, &path)
+        .map_err(|e| format!("Failed to move scenes '{tmp:?}' -> '{path:?}': {e}"))?;
+
+    Ok(())
+}
+
+pub fn load_frame_snapshots(
+    app_handle: &tauri::AppHandle,
+) -> Result<BTreeMap<String, PersistedFrameSnapshot>, String> {
+    let path = frames_file_path(app_handle)?;
+
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let raw = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read frame snapshots '{path:?}': {e}"))?;
+
+    serde_json::from_str::<BTreeMap<String, PersistedFrameSnapshot>>(&raw)
+        .map_err(|e| format!("Failed to parse frame snapshots '{path:?}': {e}"))
+}
+
+pub fn save_frame_snapshots(
+    app_handle: &tauri::AppHandle,
+    snapshots: &BTreeMap<String, PersistedFrameSnapshot>,
+) -> Result<(), String> {
+    let path = frames_file_path(app_handle)?;
+
+    // Rewritten every few seconds and never edited by hand, so not pretty-printed.
+    let json = serde_json::to_string(snapshots)
+        .map_err(|e| format!("Failed to serialize frame snapshots: {e}"))?;
+
+    let tmp = path.with_extension("json.tmp");
+    {
+        let mut f = std::fs::File::create(&tmp)
+            .map_err(|e| format!("Failed to create frame snapshots '{tmp:?}': {e}"))?;
+        f.write_all(json.as_bytes())
+            .map_err(|e| format!("Failed to write frame snapshots '{tmp:?}': {e}"))?;
+        f.flush()
+            .map_err(|e| format!("Failed to flush frame snapshots '{tmp:?}': {e}"))?;
+    }
+    std::fs::rename(&tmp, &path)
+        .map_err(|e| format!("Failed to move frame snapshots '{tmp:?}' -> '{path:?}': {e}"))?;
+
+    Ok(())
+}
@@ -1,8 +1,20 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::api::hotkeys::HotkeyBinding;
+use crate::api::i18n::{self, Locale};
+use crate::resource::color::vision::ColorVisionMode;
 use crate::interface::effect::{
-    DependencyBehavior, EffectParam, EffectParamDependency, EffectParamKind,
+    DependencyBehavior, EffectLayout, EffectMetadata, EffectParam, EffectParamDependency,
+    EffectParamKind, SelectOptions,
+};
+use crate::resource::controller::drgb_hid::{DEFAULT_KEEPALIVE_IDLE_MS, DEFAULT_KEEPALIVE_INTERVAL_MS};
+use crate::resource::controller::led_matrix_udp::DEFAULT_UDP_PAYLOAD;
+use crate::resource::driver::net_bind::DEFAULT_DISCOVERY_TIMEOUT_MS;
+use crate::resource::controller::sacn::SacnNode;
+use crate::resource::driver::write_retry::DEFAULT_WRITE_RETRIES;
+use crate::resource::screen::{
+    CropRect, ToneMapping, DEFAULT_CAPTURE_MAX_PIXELS, DEFAULT_TARGET_NITS,
 };
-use crate::resource::screen::DEFAULT_CAPTURE_MAX_PIXELS;
 
 // ============================================================================
 // App config DTOs (persisted via tauri-plugin-store)
@@ -16,6 +28,82 @@ pub struct ScreenCaptureConfigDto {
     pub fps: u8,
     /// Capture backend/method identifier (e.g. "dxgi", "gdi", "graphics", "xcap").
     pub method: String,
+    /// Request 10-bit desktop surfaces on SDR displays (DXGI only); falls back to 8-bit.
+    #[serde(default)]
+    pub high_bit_depth: bool,
+    /// Include the mouse pointer in captured frames.
+    #[serde(default)]
+    pub show_cursor: bool,
+    /// Percentage of each zone's pixels screen effects average; lower is cheaper.
+    #[serde(default = "default_sample_ratio")]
+    pub sample_ratio: u8,
+    /// How HDR highlights are mapped to LED range (DXGI HDR capture only).
+    #[serde(default)]
+    pub tone_mapping: ToneMapping,
+    /// HDR brightness in nits mapped to full LED output (DXGI HDR capture only).
+    #[serde(default = "default_target_nits")]
+    pub target_nits: u32,
+    /// Part of the display captured, as fractions of its size; unset captures all of it.
+    /// Ignored when capturing all displays at once.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crop: Option<CropRect>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceDiscoveryConfigDto {
+    /// How long network drivers wait for discovery replies (mDNS, broadcast or multicast)
+    /// during a scan.
+    pub mdns_timeout_ms: u32,
+    /// WiZ bulb addresses from earlier scans, asked directly in case broadcasts are dropped.
+    #[serde(default)]
+    pub wiz_bulbs: Vec<String>,
+    /// E1.31 nodes added by hand; sACN has no discovery.
+    #[serde(default)]
+    pub sacn_nodes: Vec<SacnNode>,
+    /// Local IPv4/IPv6 address whose adapter network controllers send from; unset lets the
+    /// OS route.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_address: Option<String>,
+    /// Ask UDP LED matrices to confirm received frames, to measure loss and resend the first
+    /// frame after a pause. Applies to devices connected by later scans.
+    #[serde(default)]
+    pub frame_acks: bool,
+    /// Largest UDP payload sent to LED matrices, in bytes. Applies to devices connected by
+    /// later scans.
+    #[serde(default = "default_udp_payload")]
+    pub udp_payload: u32,
+}
+
+impl Default for DeviceDiscoveryConfigDto {
+    fn default() -> Self {
+        Self {
+            mdns_timeout_ms: DEFAULT_DISCOVERY_TIMEOUT_MS,
+            wiz_bulbs: Vec::new(),
+            sacn_nodes: Vec::new(),
+            source_address: None,
+            frame_acks: false,
+            udp_payload: default_udp_payload(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrgbKeepaliveConfigDto {
+    /// How often the keepalive thread checks the device.
+    pub interval_ms: u32,
+    /// Keepalives are only sent after this long without a frame.
+    pub idle_threshold_ms: u32,
+}
+
+impl Default for DrgbKeepaliveConfigDto {
+    fn default() -> Self {
+        Self {
+            interval_ms: DEFAULT_KEEPALIVE_INTERVAL_MS,
+            idle_threshold_ms: DEFAULT_KEEPALIVE_IDLE_MS,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +113,62 @@ pub struct AppConfigDto {
     pub window_effect: String,
     pub minimize_to_tray: bool,
     pub screen_capture: ScreenCaptureConfigDto,
+    #[serde(default)]
+    pub device_discovery: DeviceDiscoveryConfigDto,
+    #[serde(default)]
+    pub drgb_keepalive: DrgbKeepaliveConfigDto,
+    /// Turn all LEDs off when the app exits (otherwise they keep their last frame).
+    #[serde(default = "default_clear_leds_on_exit")]
+    pub clear_leds_on_exit: bool,
+    /// Send only changed LED ranges to controllers whose firmware supports it.
+    #[serde(default = "default_partial_updates")]
+    pub partial_updates: bool,
+    /// Slow a device's runner down to the frame rate its transport accepts.
+    #[serde(default = "default_adaptive_frame_rate")]
+    pub adaptive_frame_rate: bool,
+    /// Brightness ramp (ms) when an effect lights an output up from dark; 0 cuts straight in.
+    #[serde(default)]
+    pub soft_start_ms: u32,
+    /// Color-blindness correction applied to every device without its own setting.
+    #[serde(default)]
+    pub color_vision: ColorVisionMode,
+    /// Extra attempts for serial/HID writes that fail transiently.
+    #[serde(default = "default_write_retries")]
+    pub write_retries: u32,
+    /// Driver to keep, by device serial id, when several drivers detect the same device.
+    #[serde(default)]
+    pub controller_preferences: BTreeMap<String, String>,
+    /// Global keyboard shortcuts.
+    #[serde(default)]
+    pub hotkeys: Vec<HotkeyBinding>,
+}
+
+fn default_clear_leds_on_exit() -> bool {
+    true
+}
+
+fn default_partial_updates() -> bool {
+    true
+}
+
+fn default_adaptive_frame_rate() -> bool {
+    true
+}
+
+fn default_sample_ratio() -> u8 {
+    100
+}
+
+fn default_target_nits() -> u32 {
+    DEFAULT_TARGET_NITS
+}
+
+fn default_udp_payload() -> u32 {
+    DEFAULT_UDP_PAYLOAD as u32
+}
+
+fn default_write_retries() -> u32 {
+    DEFAULT_WRITE_RETRIES
 }
 
 impl AppConfigDto {
@@ -46,7 +190,23 @@ impl AppConfigDto {
                 max_pixels: DEFAULT_CAPTURE_MAX_PIXELS,
                 fps: 30,
                 method: default_method.to_string(),
+                high_bit_depth: false,
+                show_cursor: false,
+                sample_ratio: default_sample_ratio(),
+                tone_mapping: ToneMapping::default(),
+                target_nits: default_target_nits(),
+                crop: None,
             },
+            device_discovery: DeviceDiscoveryConfigDto::default(),
+            drgb_keepalive: DrgbKeepaliveConfigDto::default(),
+            clear_leds_on_exit: default_clear_leds_on_exit(),
+            partial_updates: default_partial_updates(),
+            adaptive_frame_rate: default_adaptive_frame_rate(),
+            soft_start_ms: 0,
+            color_vision: ColorVisionMode::Off,
+            write_retries: default_write_retries(),
+            controller_preferences: BTreeMap::new(),
+            hotkeys: Vec::new(),
         }
     }
 }
@@ -139,6 +299,43 @@ pub enum EffectParamInfo {
         #[serde(skip_serializing_if = "Option::is_none")]
         dependency: Option<ParamDependencyInfo>,
     },
+    #[serde(rename = "text")]
+    Text {
+        key: &'static str,
+        label: &'static str,
+        default: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        placeholder: Option<&'static str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        dependency: Option<ParamDependencyInfo>,
+    },
+    #[serde(rename = "hsv")]
+    Hsv {
+        key: &'static str,
+        label: &'static str,
+        default: HsvInfo,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        dependency: Option<ParamDependencyInfo>,
+    },
+    /// In Kelvin.
+    #[serde(rename = "temperature")]
+    Temperature {
+        key: &'static str,
+        label: &'static str,
+        min: f64,
+        max: f64,
+        default: f64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        dependency: Option<ParamDependencyInfo>,
+    },
+}
+
+/// Value of an `hsv` param: hue in degrees, saturation and value 0.0–1.0.
+#[derive(Serialize)]
+pub struct HsvInfo {
+    h: f64,
+    s: f64,
+    v: f64,
 }
 
 #[derive(Serialize)]
@@ -147,9 +344,13 @@ pub struct SelectOptionInfo {
     value: f64,
 }
 
-impl From<&'static EffectParam> for EffectParamInfo {
-    fn from(param: &'static EffectParam) -> Self {
+impl EffectParamInfo {
+    /// Build the frontend view of `param` for effect `effect_id`, translating its label and
+    /// static option labels when `locale` has entries for them.
+    pub fn localized(effect_id: &str, param: &'static EffectParam, locale: Option<Locale>) -> Self {
         let dependency = param.dependency.as_ref().map(ParamDependencyInfo::from);
+        let label_key = format!("effect.{}.param.{}", effect_id, param.key);
+        let label = i18n::translate(locale, &label_key, param.label);
 
         match &param.kind {
             EffectParamKind::Slider {
@@ -159,7 +360,7 @@ impl From<&'static EffectParam> for EffectParamInfo {
                 default,
             } => EffectParamInfo::Slider {
                 key: param.key,
-                label: param.label,
+                label,
                 min: *min,
                 max: *max,
                 step: *step,
@@ -188,17 +389,25 @@ impl From<&'static EffectParam> for EffectParamInfo {
                     default_value = resolved[0].value;
                 }
 
+                // Dynamic options (displays, audio devices) come from the OS and stay as-is.
+                let option_locale = locale.filter(|_| matches!(options, SelectOptions::Static(_)));
                 let options = resolved
                     .into_iter()
-                    .map(|option| SelectOptionInfo {
-                        label: option.label,
-                        value: option.value,
+                    .map(|option| {
+                        let translated = option_locale.and_then(|locale| {
+                            let key = format!("{}.option.{}", label_key, option.value as i64);
+                            i18n::lookup(locale, &key)
+                        });
+                        SelectOptionInfo {
+                            label: translated.map(str::to_string).unwrap_or(option.label),
+                            value: option.value,
+                        }
                     })
                     .collect();
 
                 EffectParamInfo::Select {
                     key: param.key,
-                    label: param.label,
+                    label,
                     default: default_value,
                     options,
                     dependency,
@@ -206,16 +415,52 @@ impl From<&'static EffectParam> for EffectParamInfo {
             }
             EffectParamKind::Toggle { default } => EffectParamInfo::Toggle {
                 key: param.key,
-                label: param.label,
+                label,
                 default: *default,
                 dependency,
             },
             EffectParamKind::Color { default } => EffectParamInfo::Color {
                 key: param.key,
-                label: param.label,
+                label,
                 default,
                 dependency,
             },
+            EffectParamKind::Text {
+                default,
+                placeholder,
+            } => EffectParamInfo::Text {
+                key: param.key,
+                label,
+                default,
+                placeholder: *placeholder,
+                dependency,
+            },
+            EffectParamKind::Hsv {
+                default_h,
+                default_s,
+                default_v,
+            } => EffectParamInfo::Hsv {
+                key: param.key,
+                label,
+                default: HsvInfo {
+                    h: *default_h,
+                    s: *default_s,
+                    v: *default_v,
+                },
+                dependency,
+            },
+            EffectParamKind::Temperature {
+                min_k,
+                max_k,
+                default_k,
+            } => EffectParamInfo::Temperature {
+                key: param.key,
+                label,
+                min: *min_k,
+                max: *max_k,
+                default: *default_k,
+                dependency,
+            },
         }
     }
 }
@@ -227,9 +472,43 @@ pub struct EffectInfo {
     pub description: Option<&'static str>,
     pub group: Option<&'static str>,
     pub icon: Option<&'static str>,
+    pub layout: &'static str,
     pub params: Vec<EffectParamInfo>,
 }
 
+impl EffectInfo {
+    /// Build the frontend view of an effect; `None` keeps the built-in labels.
+    pub fn from_metadata(meta: &'static EffectMetadata, locale: Option<Locale>) -> Self {
+        EffectInfo {
+            id: meta.id,
+            name: i18n::translate(locale, &format!("effect.{}.name", meta.id), meta.name),
+            description: meta.description.map(|description| {
+                i18n::translate(locale, &format!("effect.{}.description", meta.id), description)
+            }),
+            group: meta.group,
+            icon: meta.icon,
+            layout: match meta.layout {
+                EffectLayout::Any => "any",
+                EffectLayout::Matrix => "matrix",
+            },
+            params: meta
+                .params
+                .iter()
+                .map(|param| EffectParamInfo::localized(meta.id, param, locale))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct EffectGroupInfo {
+    /// Raw group id as declared by the effects (`None` for ungrouped effects).
+    pub id: Option<&'static str>,
+    /// Display name, localized when a locale was requested.
+    pub name: &'static str,
+    pub effects: Vec<EffectInfo>,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SystemInfoResponse {
@@ -0,0 +1,162 @@
+//! Global keyboard shortcuts bound to whole-setup actions (scenes, brightness, on/off).
+//!
+//! Bindings live in `app.json` and are re-registered with the OS whenever they change. A
+//! shortcut that fails to register (bad accelerator, or already taken by another app) is
+//! reported back and logged, the remaining bindings still work.
+
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+use crate::api::commands::{ensure_runners_best_effort, save_device_config_best_effort};
+use crate::manager::LightingManager;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum HotkeyAction {
+    ApplyScene { name: String },
+    CycleEffect,
+    /// Change every device's brightness by `delta` percentage points.
+    AdjustBrightness { delta: i16 },
+    ToggleAll,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyBinding {
+    pub id: String,
+    /// Accelerator such as `CommandOrControl+Shift+L`.
+    pub shortcut: String,
+    pub action: HotkeyAction,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyRegistrationFailure {
+    pub id: String,
+    pub shortcut: String,
+    pub error: String,
+}
+
+static BINDINGS: RwLock<Vec<HotkeyBinding>> = RwLock::new(Vec::new());
+
+pub fn bindings() -> Vec<HotkeyBinding> {
+    BINDINGS.read().map(|b| b.clone()).unwrap_or_default()
+}
+
+/// Replace all bindings and re-register them with the OS.
+pub fn set_bindings(
+    app_handle: &AppHandle,
+    bindings: Vec<HotkeyBinding>,
+) -> Vec<HotkeyRegistrationFailure> {
+    if let Ok(mut guard) = BINDINGS.write() {
+        *guard = bindings.clone();
+    }
+    register_all(app_handle, &bindings)
+}
+
+/// Add a binding, or replace the one with the same id.
+pub fn upsert_binding(
+    app_handle: &AppHandle,
+    binding: HotkeyBinding,
+) -> Result<Vec<HotkeyRegistrationFailure>, String> {
+    if binding.id.trim().is_empty() {
+        return Err("Hotkey id must not be empty".to_string());
+    }
+    binding
+        .shortcut
+        .parse::<Shortcut>()
+        .map_err(|e| format!("Invalid shortcut '{}': {}", binding.shortcut, e))?;
+
+    let mut next = bindings();
+    match next.iter_mut().find(|b| b.id == binding.id) {
+        Some(existing) => *existing = binding,
+        None => next.push(binding),
+    }
+    Ok(set_bindings(app_handle, next))
+}
+
+pub fn remove_binding(
+    app_handle: &AppHandle,
+    id: &str,
+) -> Result<Vec<HotkeyRegistrationFailure>, String> {
+    let mut next = bindings();
+    let before = next.len();
+    next.retain(|b| b.id != id);
+    if next.len() == before {
+        return Err(format!("Hotkey '{}' not found", id));
+    }
+    Ok(set_bindings(app_handle, next))
+}
+
+fn register_all(app_handle: &AppHandle, bindings: &[HotkeyBinding]) -> Vec<HotkeyRegistrationFailure> {
+    let shortcuts = app_handle.global_shortcut();
+    if let Err(err) = shortcuts.unregister_all() {
+        log::warn!(err:display = err; "[hotkey] Failed to unregister shortcuts");
+    }
+
+    let mut failures = Vec::new();
+    for binding in bindings {
+        let result = binding
+            .shortcut
+            .parse::<Shortcut>()
+            .map_err(|e| e.to_string())
+            .and_then(|shortcut| shortcuts.register(shortcut).map_err(|e| e.to_string()));
+        if let Err(error) = result {
+            log::warn!(id = binding.id.as_str(), shortcut = binding.shortcut.as_str(), err:display = error; "[hotkey] Failed to register shortcut");
+            failures.push(HotkeyRegistrationFailure {
+                id: binding.id.clone(),
+                shortcut: binding.shortcut.clone(),
+                error,
+            });
+        }
+    }
+    failures
+}
+
+/// Handler installed on the global-shortcut plugin.
+pub fn handle_shortcut(app_handle: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+
+    let action = BINDINGS.read().ok().and_then(|bindings| {
+        bindings
+            .iter()
+            .find(|b| b.shortcut.parse::<Shortcut>().is_ok_and(|s| s == *shortcut))
+            .map(|b| b.action.clone())
+    });
+    if let Some(action) = action {
+        run_action(app_handle, &action);
+    }
+}
+
+fn run_action(app_handle: &AppHandle, action: &HotkeyAction) {
+    let manager = app_handle.state::<LightingManager>();
+    let changed = match action {
+        HotkeyAction::ApplyScene { name } => match manager.apply_scene(name) {
+            Ok(ports) => ports,
+            Err(err) => {
+                log::warn!(scene = name.as_str(), err:display = err; "[hotkey] Failed to apply scene");
+                return;
+            }
+        },
+        HotkeyAction::CycleEffect => manager.cycle_effect_all(),
+        HotkeyAction::AdjustBrightness { delta } => manager.adjust_brightness_all(*delta),
+        HotkeyAction::ToggleAll => manager.toggle_all().1,
+    };
+    ensure_runners_best_effort(&manager, &changed, app_handle);
+
+    // Turning everything off is temporary, so the saved device configs keep what was running.
+    let persist = *action != HotkeyAction::ToggleAll;
+    for port in changed {
+        if persist {
+            save_device_config_best_effort(&manager, &port, app_handle);
+        }
+        // The frontend upserts devices from this event.
+        if let Ok(device) = manager.get_device(&port) {
+            let _ = app_handle.emit("device-discovered", &device);
+        }
+    }
+}
@@ -0,0 +1,442 @@
+//! Translation tables for user-facing effect metadata.
+//!
+//! Effects keep their built-in strings as the fallback; a locale only replaces the ones
+//! that have an entry here. Keys are derived from metadata instead of being stored on it:
+//!
+//! - `effect.<id>.name` / `effect.<id>.description`
+//! - `effect.<id>.param.<key>` for parameter labels
+//! - `effect.<id>.param.<key>.option.<value>` for static select options (value as integer)
+//! - `group.<group>`
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    Zh,
+}
+
+impl Locale {
+    /// Accepts BCP 47-ish tags such as `en`, `en-US`, `zh-CN`, `zh_Hans`.
+    pub fn parse(tag: &str) -> Option<Self> {
+        let lang = tag
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match lang.as_str() {
+            "en" => Some(Locale::En),
+            "zh" => Some(Locale::Zh),
+            _ => None,
+        }
+    }
+
+    fn table(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::Zh => ZH,
+        }
+    }
+}
+
+pub fn lookup(locale: Locale, key: &str) -> Option<&'static str> {
+    locale
+        .table()
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| *v)
+}
+
+/// Resolve `key` for `locale`, falling back to the built-in string.
+pub fn translate(locale: Option<Locale>, key: &str, fallback: &'static str) -> &'static str {
+    locale
+        .and_then(|locale| lookup(locale, key))
+        .unwrap_or(fallback)
+}
+
+const EN: &[(&str, &str)] = &[
+    ("group.Basic", "Basic"),
+    ("group.Dynamic", "Dynamic"),
+    ("group.Audio", "Audio"),
+    ("group.Screen Sync", "Screen Sync"),
+    ("group.Test", "Test"),
+    ("effect.ambient_image.name", "Ambient Image"),
+    ("effect.ambient_image.description", "Fill with the color of an image from a URL or file, e.g. album art"),
+    ("effect.audio_bars.name", "Audio Bars"),
+    ("effect.audio_bars.description", "Spectrum analyzer bars with falling peak caps"),
+    ("effect.audio_star.name", "Audio Star"),
+    ("effect.audio_star.description", "Star-shaped audio visualizer with frequency-based colors"),
+    ("effect.color_wipe.name", "Color Wipe"),
+    ("effect.color_wipe.description", "Fill the strip end to end, or show a progress level"),
+    ("effect.comet.name", "Comet"),
+    ("effect.comet.description", "Bright heads chasing along with fading tails"),
+    ("effect.fire.name", "Fire"),
+    ("effect.fire.description", "Flickering flames rising up a matrix"),
+    ("effect.gradient.name", "Gradient"),
+    ("effect.gradient.description", "Static blend through two to five colors"),
+    ("effect.plasma.name", "Plasma"),
+    ("effect.plasma.description", "Slowly drifting blobs of color"),
+    ("effect.matrix_test.name", "Matrix Test"),
+    ("effect.matrix_test.description", "Matrix alignment test pattern"),
+    ("effect.monochrome.name", "Monochrome"),
+    ("effect.monochrome.description", "Solid color fill"),
+    ("effect.rainbow.name", "Rainbow"),
+    ("effect.rainbow.description", "Cycling rainbow colors"),
+    ("effect.ripple.name", "Ripple"),
+    ("effect.ripple.description", "Expanding rings of color on a timer or audio beat"),
+    ("effect.screen_mirror.name", "Screen Mirror"),
+    ("effect.screen_mirror.description", "Mirror the desktop colors onto matrices or strips"),
+    ("effect.system_monitor.name", "System Monitor"),
+    ("effect.system_monitor.description", "Show CPU, memory, GPU or network usage as a bar or a color"),
+    ("effect.theater_chase.name", "Theater Chase"),
+    ("effect.theater_chase.description", "Marquee of evenly spaced lights stepping along"),
+    ("effect.turn_off.name", "Turn Off"),
+    ("effect.turn_off.description", "Turn off all LEDs"),
+    ("effect.twinkle.name", "Twinkle"),
+    ("effect.twinkle.description", "Stars fading in and out over a dim background"),
+    ("effect.typing_pulse.name", "Typing Pulse"),
+    ("effect.typing_pulse.description", "Glow on key presses and clicks, fading while idle"),
+    ("effect.ambient_image.param.source", "Image source"),
+    ("effect.ambient_image.param.refreshSecs", "Refresh interval (s)"),
+    ("effect.ambient_image.param.colorMode", "Color mode"),
+    ("effect.ambient_image.param.colorMode.option.0", "Average"),
+    ("effect.ambient_image.param.colorMode.option.1", "Dominant"),
+    ("effect.ambient_image.param.smoothness", "Smoothness"),
+    ("effect.system_monitor.param.metric", "Metric"),
+    ("effect.system_monitor.param.metric.option.0", "CPU"),
+    ("effect.system_monitor.param.metric.option.1", "Memory"),
+    ("effect.system_monitor.param.metric.option.2", "GPU"),
+    ("effect.system_monitor.param.metric.option.3", "Network"),
+    ("effect.system_monitor.param.display", "Display"),
+    ("effect.system_monitor.param.display.option.0", "Bar"),
+    ("effect.system_monitor.param.display.option.1", "Color"),
+    ("effect.system_monitor.param.lowColor", "Low usage color"),
+    ("effect.system_monitor.param.highColor", "High usage color"),
+    ("effect.system_monitor.param.maxMbps", "Full scale (Mbit/s)"),
+    ("effect.system_monitor.param.interval", "Update interval (s)"),
+    ("effect.audio_bars.param.audioDevice", "Audio device"),
+    ("effect.audio_bars.param.bands", "Bands"),
+    ("effect.audio_bars.param.smoothing", "Smoothing (ms)"),
+    ("effect.audio_bars.param.peakHoldMs", "Peak hold (ms)"),
+    ("effect.audio_bars.param.gradient", "Gradient"),
+    ("effect.audio_bars.param.gradient.option.0", "Classic"),
+    ("effect.audio_bars.param.gradient.option.1", "Rainbow"),
+    ("effect.audio_bars.param.gradient.option.2", "Ocean"),
+    ("effect.audio_star.param.audioDevice", "Audio device"),
+    ("effect.audio_star.param.speed", "Speed"),
+    ("effect.audio_star.param.fftSize", "FFT window"),
+    ("effect.audio_star.param.avgSize", "Smoothing"),
+    ("effect.audio_star.param.amplitude", "Gain"),
+    ("effect.audio_star.param.decay", "Decay"),
+    ("effect.audio_star.param.filterConstant", "Filter constant"),
+    ("effect.audio_star.param.attack", "Attack (ms)"),
+    ("effect.audio_star.param.release", "Release (ms)"),
+    ("effect.audio_star.param.edgeBeat", "Edge beat"),
+    ("effect.audio_star.param.edgeBeatHue", "Edge hue"),
+    ("effect.audio_star.param.edgeBeatSaturation", "Edge saturation"),
+    ("effect.audio_star.param.edgeBeatSensitivity", "Edge sensitivity"),
+    ("effect.audio_star.param._deviceKind", "Device kind"),
+    ("effect.color_wipe.param.color", "Color"),
+    ("effect.color_wipe.param.speed", "Speed"),
+    ("effect.color_wipe.param.bounce", "Bounce"),
+    ("effect.color_wipe.param.progress", "Progress"),
+    ("effect.comet.param.count", "Count"),
+    ("effect.comet.param.speed", "Speed"),
+    ("effect.comet.param.tailLength", "Tail length"),
+    ("effect.comet.param.rainbow", "Rainbow"),
+    ("effect.comet.param.color", "Color"),
+    ("effect.comet.param.bounce", "Bounce"),
+    ("effect.fire.param.intensity", "Intensity"),
+    ("effect.fire.param.cooling", "Cooling"),
+    ("effect.fire.param.speed", "Speed"),
+    ("effect.fire.param.palette", "Palette"),
+    ("effect.fire.param.palette.option.0", "Fire"),
+    ("effect.fire.param.palette.option.1", "Ice"),
+    ("effect.fire.param.palette.option.2", "Toxic"),
+    ("effect.gradient.param.stops", "Color stops"),
+    ("effect.gradient.param.direction", "Direction"),
+    ("effect.gradient.param.direction.option.0", "Horizontal"),
+    ("effect.gradient.param.direction.option.1", "Vertical"),
+    ("effect.gradient.param.direction.option.2", "Radial"),
+    ("effect.gradient.param.angle", "Angle"),
+    ("effect.gradient.param.mirror", "Mirror"),
+    ("effect.plasma.param.speed", "Speed"),
+    ("effect.plasma.param.scale", "Scale"),
+    ("effect.plasma.param.hueShift", "Hue shift"),
+    ("effect.plasma.param.colorMode", "Color mode"),
+    ("effect.plasma.param.colorMode.option.0", "Rainbow"),
+    ("effect.plasma.param.colorMode.option.1", "Duotone"),
+    ("effect.plasma.param.colorMode.option.2", "Mono hue"),
+    ("effect.monochrome.param.color", "Color"),
+    ("effect.rainbow.param.speed", "Speed"),
+    ("effect.ripple.param.color", "Color"),
+    ("effect.ripple.param.rainbow", "Rainbow"),
+    ("effect.ripple.param.speed", "Speed"),
+    ("effect.ripple.param.decay", "Decay"),
+    ("effect.ripple.param.maxRipples", "Max ripples"),
+    ("effect.ripple.param.origin", "Origin"),
+    ("effect.ripple.param.origin.option.0", "Center"),
+    ("effect.ripple.param.origin.option.1", "Random"),
+    ("effect.ripple.param.trigger", "Trigger"),
+    ("effect.ripple.param.trigger.option.0", "Auto"),
+    ("effect.ripple.param.trigger.option.1", "Audio beat"),
+    ("effect.ripple.param.interval", "Interval (s)"),
+    ("effect.ripple.param.audioDevice", "Audio device"),
+    ("effect.screen_mirror.param.displayIndex", "Screen source"),
+    ("effect.screen_mirror.param.smoothness", "Smoothness"),
+    ("effect.screen_mirror.param.brightness", "Brightness gain"),
+    ("effect.screen_mirror.param.saturation", "Saturation gain"),
+    ("effect.screen_mirror.param.gamma", "Gamma correction"),
+    ("effect.screen_mirror.param.gammaR", "Red gamma"),
+    ("effect.screen_mirror.param.gammaG", "Green gamma"),
+    ("effect.screen_mirror.param.gammaB", "Blue gamma"),
+    ("effect.screen_mirror.param.whiteR", "White point red"),
+    ("effect.screen_mirror.param.whiteG", "White point green"),
+    ("effect.screen_mirror.param.whiteB", "White point blue"),
+    ("effect.screen_mirror.param.mapping", "Screen mapping"),
+    ("effect.screen_mirror.param.mapping.option.0", "Uniform"),
+    ("effect.screen_mirror.param.mapping.option.1", "Edge zones"),
+    ("effect.screen_mirror.param.mapping.option.2", "Custom region"),
+    ("effect.screen_mirror.param.zoneDepth", "Zone depth (%)"),
+    ("effect.screen_mirror.param.regionLeft", "Region left (%)"),
+    ("effect.screen_mirror.param.regionTop", "Region top (%)"),
+    ("effect.screen_mirror.param.regionRight", "Region right (%)"),
+    ("effect.screen_mirror.param.regionBottom", "Region bottom (%)"),
+    ("effect.screen_mirror.param.staleTimeout", "Stall timeout (s, 0 = off)"),
+    ("effect.screen_mirror.param.staleFallback", "When capture stalls"),
+    ("effect.screen_mirror.param.staleFallback.option.0", "Hold last frame"),
+    ("effect.screen_mirror.param.staleFallback.option.1", "Fade to black"),
+    ("effect.screen_mirror.param.autoCrop", "Black border crop"),
+    ("effect.screen_mirror.param.bbThreshold", "Black border threshold (%)"),
+    ("effect.screen_mirror.param.bbUnknownFrameCnt", "Unknown border switch frames"),
+    ("effect.screen_mirror.param.bbBorderFrameCnt", "Stable border switch frames"),
+    ("effect.screen_mirror.param.bbMaxInconsistentCnt", "Max inconsistent frames"),
+    ("effect.screen_mirror.param.bbBlurRemoveCnt", "Blur safety margin (px)"),
+    ("effect.screen_mirror.param.bbMode", "Black border detection mode"),
+    ("effect.screen_mirror.param.bbMode.option.0", "Default"),
+    ("effect.screen_mirror.param.bbMode.option.1", "Classic"),
+    ("effect.screen_mirror.param.bbMode.option.2", "OSD"),
+    ("effect.screen_mirror.param.bbMode.option.3", "Letterbox"),
+    ("effect.theater_chase.param.color", "Color"),
+    ("effect.theater_chase.param.twoColor", "Alternate colors"),
+    ("effect.theater_chase.param.secondColor", "Second color"),
+    ("effect.theater_chase.param.spacing", "Spacing"),
+    ("effect.theater_chase.param.speed", "Speed"),
+    ("effect.twinkle.param.density", "Density"),
+    ("effect.twinkle.param.fadeSpeed", "Fade speed"),
+    ("effect.twinkle.param.backgroundColor", "Background"),
+    ("effect.twinkle.param.randomHue", "Random hue"),
+    ("effect.twinkle.param.twinkleColor", "Twinkle color"),
+    ("effect.twinkle.param.colorTemperature", "Color temperature"),
+    ("effect.typing_pulse.param.color", "Color"),
+    ("effect.typing_pulse.param.decay", "Fade (ms)"),
+    ("effect.typing_pulse.param.source", "Source"),
+    ("effect.typing_pulse.param.source.option.0", "Keyboard"),
+    ("effect.typing_pulse.param.source.option.1", "Mouse"),
+    ("effect.typing_pulse.param.source.option.2", "Keyboard and mouse"),
+];
+
+const ZH: &[(&str, &str)] = &[
+    ("group.Basic", "基础"),
+    ("group.Dynamic", "动态"),
+    ("group.Audio", "音频"),
+    ("group.Screen Sync", "屏幕同步"),
+    ("group.Test", "测试"),
+    ("effect.ambient_image.name", "图片氛围色"),
+    ("effect.ambient_image.description", "用网络或本地图片（如专辑封面）的颜色填充"),
+    ("effect.audio_bars.name", "频谱柱"),
+    ("effect.audio_bars.description", "带下落峰值帽的频谱分析柱"),
+    ("effect.audio_star.name", "音频星光"),
+    ("effect.audio_star.description", "按频率着色的星形音频可视化"),
+    ("effect.color_wipe.name", "颜色擦除"),
+    ("effect.color_wipe.description", "从一端逐步填满灯带，或显示进度"),
+    ("effect.comet.name", "彗星"),
+    ("effect.comet.description", "拖着渐隐尾巴追逐的亮点"),
+    ("effect.fire.name", "火焰"),
+    ("effect.fire.description", "沿矩阵向上蹿动的闪烁火焰"),
+    ("effect.gradient.name", "渐变"),
+    ("effect.gradient.description", "由两到五种颜色组成的静态渐变"),
+    ("effect.plasma.name", "等离子"),
+    ("effect.plasma.description", "缓慢漂移的色块"),
+    ("effect.matrix_test.name", "矩阵测试"),
+    ("effect.matrix_test.description", "矩阵对齐测试图案"),
+    ("effect.monochrome.name", "单色"),
+    ("effect.monochrome.description", "纯色填充"),
+    ("effect.rainbow.name", "彩虹"),
+    ("effect.rainbow.description", "循环变换的彩虹色"),
+    ("effect.ripple.name", "涟漪"),
+    ("effect.ripple.description", "按定时或音频节拍扩散的彩色光环"),
+    ("effect.screen_mirror.name", "屏幕镜像"),
+    ("effect.screen_mirror.description", "将桌面颜色映射到矩阵或灯带"),
+    ("effect.system_monitor.name", "系统监视"),
+    ("effect.system_monitor.description", "以进度条或颜色显示CPU、内存、GPU或网络占用"),
+    ("effect.theater_chase.name", "剧场追逐"),
+    ("effect.theater_chase.description", "等间距灯光逐步跑动的跑马灯"),
+    ("effect.turn_off.name", "关闭"),
+    ("effect.turn_off.description", "关闭所有 LED"),
+    ("effect.twinkle.name", "星光"),
+    ("effect.twinkle.description", "在暗色背景上随机明灭的星点"),
+    ("effect.typing_pulse.name", "打字脉冲"),
+    ("effect.typing_pulse.description", "按键和点击时亮起，空闲时渐暗"),
+    ("effect.ambient_image.param.source", "图片来源"),
+    ("effect.ambient_image.param.refreshSecs", "刷新间隔 (秒)"),
+    ("effect.ambient_image.param.colorMode", "取色方式"),
+    ("effect.ambient_image.param.colorMode.option.0", "平均色"),
+    ("effect.ambient_image.param.colorMode.option.1", "主色调"),
+    ("effect.ambient_image.param.smoothness", "平滑度"),
+    ("effect.system_monitor.param.metric", "指标"),
+    ("effect.system_monitor.param.metric.option.0", "CPU"),
+    ("effect.system_monitor.param.metric.option.1", "内存"),
+    ("effect.system_monitor.param.metric.option.2", "GPU"),
+    ("effect.system_monitor.param.metric.option.3", "网络"),
+    ("effect.system_monitor.param.display", "显示方式"),
+    ("effect.system_monitor.param.display.option.0", "进度条"),
+    ("effect.system_monitor.param.display.option.1", "颜色"),
+    ("effect.system_monitor.param.lowColor", "低占用颜色"),
+    ("effect.system_monitor.param.highColor", "高占用颜色"),
+    ("effect.system_monitor.param.maxMbps", "满量程 (Mbit/s)"),
+    ("effect.system_monitor.param.interval", "更新间隔 (秒)"),
+    ("effect.audio_bars.param.audioDevice", "音频设备"),
+    ("effect.audio_bars.param.bands", "频段数"),
+    ("effect.audio_bars.param.smoothing", "平滑 (毫秒)"),
+    ("effect.audio_bars.param.peakHoldMs", "峰值保持 (毫秒)"),
+    ("effect.audio_bars.param.gradient", "渐变"),
+    ("effect.audio_bars.param.gradient.option.0", "经典"),
+    ("effect.audio_bars.param.gradient.option.1", "彩虹"),
+    ("effect.audio_bars.param.gradient.option.2", "海洋"),
+    ("effect.audio_star.param.audioDevice", "音频设备"),
+    ("effect.audio_star.param.speed", "速度"),
+    ("effect.audio_star.param.fftSize", "FFT 窗口"),
+    ("effect.audio_star.param.avgSize", "平滑度"),
+    ("effect.audio_star.param.amplitude", "增益"),
+    ("effect.audio_star.param.decay", "衰减"),
+    ("effect.audio_star.param.filterConstant", "滤波系数"),
+    ("effect.audio_star.param.attack", "起音时间（毫秒）"),
+    ("effect.audio_star.param.release", "释音时间（毫秒）"),
+    ("effect.audio_star.param.edgeBeat", "边缘节拍"),
+    ("effect.audio_star.param.edgeBeatHue", "边缘色相"),
+    ("effect.audio_star.param.edgeBeatSaturation", "边缘饱和度"),
+    ("effect.audio_star.param.edgeBeatSensitivity", "边缘灵敏度"),
+    ("effect.audio_star.param._deviceKind", "设备类型"),
+    ("effect.color_wipe.param.color", "颜色"),
+    ("effect.color_wipe.param.speed", "速度"),
+    ("effect.color_wipe.param.bounce", "往返"),
+    ("effect.color_wipe.param.progress", "进度"),
+    ("effect.comet.param.count", "数量"),
+    ("effect.comet.param.speed", "速度"),
+    ("effect.comet.param.tailLength", "尾巴长度"),
+    ("effect.comet.param.rainbow", "彩虹"),
+    ("effect.comet.param.color", "颜色"),
+    ("effect.comet.param.bounce", "往返"),
+    ("effect.fire.param.intensity", "强度"),
+    ("effect.fire.param.cooling", "冷却"),
+    ("effect.fire.param.speed", "速度"),
+    ("effect.fire.param.palette", "配色"),
+    ("effect.fire.param.palette.option.0", "火焰"),
+    ("effect.fire.param.palette.option.1", "冰焰"),
+    ("effect.fire.param.palette.option.2", "毒焰"),
+    ("effect.gradient.param.stops", "颜色节点"),
+    ("effect.gradient.param.direction", "方向"),
+    ("effect.gradient.param.direction.option.0", "水平"),
+    ("effect.gradient.param.direction.option.1", "垂直"),
+    ("effect.gradient.param.direction.option.2", "径向"),
+    ("effect.gradient.param.angle", "角度"),
+    ("effect.gradient.param.mirror", "镜像"),
+    ("effect.plasma.param.speed", "速度"),
+    ("effect.plasma.param.scale", "尺度"),
+    ("effect.plasma.param.hueShift", "色相偏移"),
+    ("effect.plasma.param.colorMode", "颜色模式"),
+    ("effect.plasma.param.colorMode.option.0", "彩虹"),
+    ("effect.plasma.param.colorMode.option.1", "双色"),
+    ("effect.plasma.param.colorMode.option.2", "单色"),
+    ("effect.monochrome.param.color", "颜色"),
+    ("effect.rainbow.param.speed", "速度"),
+    ("effect.ripple.param.color", "颜色"),
+    ("effect.ripple.param.rainbow", "彩虹色"),
+    ("effect.ripple.param.speed", "速度"),
+    ("effect.ripple.param.decay", "衰减"),
+    ("effect.ripple.param.maxRipples", "最大涟漪数"),
+    ("effect.ripple.param.origin", "起点"),
+    ("effect.ripple.param.origin.option.0", "中心"),
+    ("effect.ripple.param.origin.option.1", "随机"),
+    ("effect.ripple.param.trigger", "触发方式"),
+    ("effect.ripple.param.trigger.option.0", "自动"),
+    ("effect.ripple.param.trigger.option.1", "音频节拍"),
+    ("effect.ripple.param.interval", "间隔 (秒)"),
+    ("effect.ripple.param.audioDevice", "音频设备"),
+    ("effect.screen_mirror.param.displayIndex", "屏幕来源"),
+    ("effect.screen_mirror.param.smoothness", "平滑度"),
+    ("effect.screen_mirror.param.brightness", "亮度增益"),
+    ("effect.screen_mirror.param.saturation", "饱和度增益"),
+    ("effect.screen_mirror.param.gamma", "Gamma 校正"),
+    ("effect.screen_mirror.param.gammaR", "红色通道 Gamma"),
+    ("effect.screen_mirror.param.gammaG", "绿色通道 Gamma"),
+    ("effect.screen_mirror.param.gammaB", "蓝色通道 Gamma"),
+    ("effect.screen_mirror.param.whiteR", "白平衡 红"),
+    ("effect.screen_mirror.param.whiteG", "白平衡 绿"),
+    ("effect.screen_mirror.param.whiteB", "白平衡 蓝"),
+    ("effect.screen_mirror.param.mapping", "映射方式"),
+    ("effect.screen_mirror.param.mapping.option.0", "均匀平铺"),
+    ("effect.screen_mirror.param.mapping.option.1", "边缘分区"),
+    ("effect.screen_mirror.param.mapping.option.2", "自定义区域"),
+    ("effect.screen_mirror.param.zoneDepth", "分区深度 (%)"),
+    ("effect.screen_mirror.param.regionLeft", "区域左边界 (%)"),
+    ("effect.screen_mirror.param.regionTop", "区域上边界 (%)"),
+    ("effect.screen_mirror.param.regionRight", "区域右边界 (%)"),
+    ("effect.screen_mirror.param.regionBottom", "区域下边界 (%)"),
+    ("effect.screen_mirror.param.staleTimeout", "画面停滞判定 (秒, 0 为关闭)"),
+    ("effect.screen_mirror.param.staleFallback", "画面停滞时"),
+    ("effect.screen_mirror.param.staleFallback.option.0", "保持最后画面"),
+    ("effect.screen_mirror.param.staleFallback.option.1", "渐隐至黑色"),
+    ("effect.screen_mirror.param.autoCrop", "黑边裁剪"),
+    ("effect.screen_mirror.param.bbThreshold", "黑边判定阈值 (%)"),
+    ("effect.screen_mirror.param.bbUnknownFrameCnt", "未知边框切换帧数"),
+    ("effect.screen_mirror.param.bbBorderFrameCnt", "稳定边框切换帧数"),
+    ("effect.screen_mirror.param.bbMaxInconsistentCnt", "最大允许不一致帧数"),
+    ("effect.screen_mirror.param.bbBlurRemoveCnt", "模糊安全边界 (像素)"),
+    ("effect.screen_mirror.param.bbMode", "黑边检测模式"),
+    ("effect.screen_mirror.param.bbMode.option.0", "默认模式"),
+    ("effect.screen_mirror.param.bbMode.option.1", "经典模式"),
+    ("effect.screen_mirror.param.bbMode.option.2", "OSD 模式"),
+    ("effect.screen_mirror.param.bbMode.option.3", "信箱模式"),
+    ("effect.theater_chase.param.color", "颜色"),
+    ("effect.theater_chase.param.twoColor", "双色交替"),
+    ("effect.theater_chase.param.secondColor", "第二颜色"),
+    ("effect.theater_chase.param.spacing", "间距"),
+    ("effect.theater_chase.param.speed", "速度"),
+    ("effect.twinkle.param.density", "密度"),
+    ("effect.twinkle.param.fadeSpeed", "明灭速度"),
+    ("effect.twinkle.param.backgroundColor", "背景色"),
+    ("effect.twinkle.param.randomHue", "随机色相"),
+    ("effect.twinkle.param.twinkleColor", "星光颜色"),
+    ("effect.twinkle.param.colorTemperature", "色温"),
+    ("effect.typing_pulse.param.color", "颜色"),
+    ("effect.typing_pulse.param.decay", "渐暗时间 (毫秒)"),
+    ("effect.typing_pulse.param.source", "来源"),
+    ("effect.typing_pulse.param.source.option.0", "键盘"),
+    ("effect.typing_pulse.param.source.option.1", "鼠标"),
+    ("effect.typing_pulse.param.source.option.2", "键盘和鼠标"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::inventory::list_effects;
+
+    #[test]
+    fn parses_locale_tags() {
+        assert_eq!(Locale::parse("en-US"), Some(Locale::En));
+        assert_eq!(Locale::parse("zh_Hans"), Some(Locale::Zh));
+        assert_eq!(Locale::parse("de"), None);
+    }
+
+    #[test]
+    fn every_effect_param_has_translations() {
+        for locale in [Locale::En, Locale::Zh] {
+            for meta in list_effects() {
+                for param in meta.params {
+                    let key = format!("effect.{}.param.{}", meta.id, param.key);
+                    assert!(lookup(locale, &key).is_some(), "{:?} is missing {}", locale, key);
+                }
+            }
+        }
+    }
+}
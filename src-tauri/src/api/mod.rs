@@ -1,4 +1,7 @@
 pub mod commands;
 pub mod dto;
 pub mod config_store;
+pub mod hotkeys;
+pub mod i18n;
+pub mod schema;
 
@@ -0,0 +1,192 @@
+//! JSON Schema (draft 2020-12) for effect parameters.
+//!
+//! The effect list returned by `get_effects` is shaped for this app's own settings panel;
+//! this is the same information in a form generic tooling understands, for generated UIs
+//! and for validating params that arrive over external control channels. A params payload
+//! may set any subset of the properties, so none are required, but unknown keys are
+//! rejected.
+//!
+//! Beyond standard keywords, every property carries `x-kind` (the `EffectParamKind`), and
+//! where relevant `x-step` and `x-dependency`, which describe UI behavior rather than
+//! validity: a param whose dependency isn't met is still accepted.
+
+use serde_json::{json, Map, Value};
+
+use crate::api::i18n::{self, Locale};
+use crate::interface::effect::{
+    DependencyBehavior, EffectMetadata, EffectParam, EffectParamDependency, EffectParamKind,
+};
+
+pub const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Schema of the params object accepted by `meta`'s effect. Titles are localized when
+/// `locale` has entries for them; select options are resolved at call time.
+pub fn effect_schema(meta: &'static EffectMetadata, locale: Option<Locale>) -> Value {
+    let properties: Map<String, Value> = meta
+        .params
+        .iter()
+        .map(|param| (param.key.to_string(), param_schema(meta.id, param, locale)))
+        .collect();
+
+    let mut schema = json!({
+        "$schema": SCHEMA_DIALECT,
+        "$id": format!("light:effect/{}", meta.id),
+        "title": i18n::translate(locale, &format!("effect.{}.name", meta.id), meta.name),
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false,
+    });
+    if let Some(description) = meta.description {
+        schema["description"] = Value::from(i18n::translate(
+            locale,
+            &format!("effect.{}.description", meta.id),
+            description,
+        ));
+    }
+    schema
+}
+
+fn param_schema(effect_id: &str, param: &'static EffectParam, locale: Option<Locale>) -> Value {
+    let label_key = format!("effect.{}.param.{}", effect_id, param.key);
+    let title = i18n::translate(locale, &label_key, param.label);
+
+    let mut schema = match &param.kind {
+        EffectParamKind::Slider {
+            min,
+            max,
+            step,
+            default,
+        } => json!({
+            "x-kind": "slider",
+            "type": "number",
+            "minimum": min,
+            "maximum": max,
+            "x-step": step,
+            "default": default,
+        }),
+        EffectParamKind::Select { default, options } => {
+            let choices = match options.resolve() {
+                Ok(choices) => choices,
+                Err(err) => {
+                    log::warn!(
+                        param = param.key,
+                        err:display = err;
+                        "[effects] Failed to resolve select options for schema"
+                    );
+                    Vec::new()
+                }
+            };
+            // Same fallback as the settings panel when the default isn't offered (yet).
+            let default = match choices.first() {
+                Some(first) if !choices.iter().any(|c| (c.value - default).abs() < f64::EPSILON) => {
+                    first.value
+                }
+                _ => *default,
+            };
+            let mut schema = json!({
+                "x-kind": "select",
+                "type": "number",
+                "default": default,
+            });
+            // Without options (e.g. no audio device right now) any number is let through.
+            if !choices.is_empty() {
+                schema["oneOf"] = choices
+                    .into_iter()
+                    .map(|choice| {
+                        let key = format!("{}.option.{}", label_key, choice.value as i64);
+                        let label = locale
+                            .and_then(|locale| i18n::lookup(locale, &key))
+                            .map(str::to_string)
+                            .unwrap_or(choice.label);
+                        json!({ "const": choice.value, "title": label })
+                    })
+                    .collect();
+            }
+            schema
+        }
+        EffectParamKind::Toggle { default } => json!({
+            "x-kind": "toggle",
+            "type": "boolean",
+            "default": default,
+        }),
+        // Hex (`#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`), `rgb()`/`rgba()` or a CSS color name.
+        EffectParamKind::Color { default } => json!({
+            "x-kind": "color",
+            "type": "string",
+            "format": "color",
+            "default": default,
+        }),
+        EffectParamKind::Text {
+            default,
+            placeholder,
+        } => {
+            let mut schema = json!({
+                "x-kind": "text",
+                "type": "string",
+                "default": default,
+            });
+            if let Some(placeholder) = placeholder {
+                schema["examples"] = json!([placeholder]);
+            }
+            schema
+        }
+        EffectParamKind::Hsv {
+            default_h,
+            default_s,
+            default_v,
+        } => json!({
+            "x-kind": "hsv",
+            "type": "object",
+            "properties": {
+                "h": { "type": "number", "minimum": 0.0, "maximum": 360.0 },
+                "s": { "type": "number", "minimum": 0.0, "maximum": 1.0 },
+                "v": { "type": "number", "minimum": 0.0, "maximum": 1.0 },
+            },
+            "additionalProperties": false,
+            "default": { "h": default_h, "s": default_s, "v": default_v },
+        }),
+        // Kelvin.
+        EffectParamKind::Temperature {
+            min_k,
+            max_k,
+            default_k,
+        } => json!({
+            "x-kind": "temperature",
+            "type": "number",
+            "minimum": min_k,
+            "maximum": max_k,
+            "default": default_k,
+        }),
+    };
+
+    schema["title"] = Value::from(title);
+    if let Some(dependency) = &param.dependency {
+        schema["x-dependency"] = dependency_schema(dependency);
+    }
+    schema
+}
+
+fn dependency_schema(dependency: &EffectParamDependency) -> Value {
+    let behavior = |behavior: &DependencyBehavior| match behavior {
+        DependencyBehavior::Hide => "hide",
+        DependencyBehavior::Disable => "disable",
+    };
+    match dependency {
+        EffectParamDependency::Dependency {
+            key,
+            equals,
+            not_equals,
+            behavior: when_unmet,
+        } => {
+            let mut rule = json!({ "key": key, "behavior": behavior(when_unmet) });
+            if let Some(equals) = equals {
+                rule["equals"] = Value::from(*equals);
+            }
+            if let Some(not_equals) = not_equals {
+                rule["notEquals"] = Value::from(*not_equals);
+            }
+            rule
+        }
+        EffectParamDependency::Always(always) => json!({ "behavior": behavior(always) }),
+    }
+}
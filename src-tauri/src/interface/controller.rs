@@ -1,12 +1,99 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
     pub b: u8,
 }
 
+/// Color of a black body at `k` Kelvin (1000–40000), after Tanner Helland's fit of the CIE
+/// color matching functions. 6600 K is white.
+pub fn kelvin_to_rgb(k: f32) -> Color {
+    let t = k.clamp(1000.0, 40000.0) / 100.0;
+    let channel = |v: f32| v.clamp(0.0, 255.0).round() as u8;
+
+    let r = if t <= 66.0 {
+        255.0
+    } else {
+        329.698_73 * (t - 60.0).powf(-0.133_204_76)
+    };
+    let g = if t <= 66.0 {
+        99.470_8 * t.ln() - 161.119_57
+    } else {
+        288.122_16 * (t - 60.0).powf(-0.075_514_846)
+    };
+    let b = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_73 * (t - 10.0).ln() - 305.044_8
+    };
+
+    Color {
+        r: channel(r),
+        g: channel(g),
+        b: channel(b),
+    }
+}
+
+/// Order a strip takes its color channels in on the wire. The `W` orders have a white
+/// channel: the white shared by r, g and b (their minimum) is moved into it, so the white
+/// LED does the work the three colors would otherwise have to mix. `Rgbww` drives its warm
+/// and cold white channels equally.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorOrder {
+    #[default]
+    Rgb,
+    Rbg,
+    Grb,
+    Gbr,
+    Brg,
+    Bgr,
+    Rgbw,
+    Grbw,
+    Bgrw,
+    Rgbww,
+}
+
+impl ColorOrder {
+    /// Bytes one LED takes.
+    pub const fn channels(self) -> usize {
+        match self {
+            Self::Rgb | Self::Rbg | Self::Grb | Self::Gbr | Self::Brg | Self::Bgr => 3,
+            Self::Rgbw | Self::Grbw | Self::Bgrw => 4,
+            Self::Rgbww => 5,
+        }
+    }
+
+    /// Append `color` to `out` in this order.
+    pub fn push(self, color: Color, out: &mut Vec<u8>) {
+        let Color { r, g, b } = color;
+        let white_channels = self.channels() - 3;
+        let w = if white_channels > 0 { r.min(g).min(b) } else { 0 };
+        let (r, g, b) = (r - w, g - w, b - w);
+        let rgb = match self {
+            Self::Rgb | Self::Rgbw | Self::Rgbww => [r, g, b],
+            Self::Rbg => [r, b, g],
+            Self::Grb | Self::Grbw => [g, r, b],
+            Self::Gbr => [g, b, r],
+            Self::Brg => [b, r, g],
+            Self::Bgr | Self::Bgrw => [b, g, r],
+        };
+        out.extend_from_slice(&rgb);
+        out.extend(std::iter::repeat_n(w, white_channels));
+    }
+
+    /// Append every color of `colors` to `out` in this order.
+    pub fn extend(self, colors: &[Color], out: &mut Vec<u8>) {
+        out.reserve(colors.len() * self.channels());
+        for &color in colors {
+            self.push(color, out);
+        }
+    }
+}
+
 /// High-level device classification (inspired by OpenRGB).
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub enum DeviceType {
@@ -43,6 +130,82 @@ pub struct MatrixMap {
     pub map: Vec<Option<usize>>,
 }
 
+impl MatrixMap {
+    /// The map as seen once the panel is turned `rotation` clockwise and then mirrored
+    /// left-right (`flip_h`) and/or top-bottom (`flip_v`). Quarter turns swap width and
+    /// height.
+    pub fn transformed(&self, rotation: Rotation, flip_h: bool, flip_v: bool) -> MatrixMap {
+        let (w, h) = (self.width, self.height);
+        let (width, height) = match rotation {
+            Rotation::Deg90 | Rotation::Deg270 => (h, w),
+            Rotation::Deg0 | Rotation::Deg180 => (w, h),
+        };
+
+        let map = (0..width * height)
+            .map(|i| {
+                let (mut x, mut y) = (i % width, i / width);
+                if flip_h {
+                    x = width - 1 - x;
+                }
+                if flip_v {
+                    y = height - 1 - y;
+                }
+                // The cell of the untransformed map that ends up at (x, y).
+                let (sx, sy) = match rotation {
+                    Rotation::Deg0 => (x, y),
+                    Rotation::Deg90 => (y, h - 1 - x),
+                    Rotation::Deg180 => (w - 1 - x, h - 1 - y),
+                    Rotation::Deg270 => (w - 1 - y, x),
+                };
+                self.map.get(sy * w + sx).copied().flatten()
+            })
+            .collect();
+
+        MatrixMap { width, height, map }
+    }
+}
+
+/// Clockwise quarter turns a matrix panel is mounted with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Rotation {
+    #[default]
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+/// How a matrix output is mounted relative to its map: rotated first, then mirrored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatrixTransform {
+    #[serde(default)]
+    pub rotation: Rotation,
+    #[serde(default)]
+    pub flip_h: bool,
+    #[serde(default)]
+    pub flip_v: bool,
+}
+
+impl MatrixTransform {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn apply(&self, matrix: &MatrixMap) -> MatrixMap {
+        matrix.transformed(self.rotation, self.flip_h, self.flip_v)
+    }
+}
+
+/// Physical position of one LED, for strips laid out in arbitrary shapes.
+///
+/// Units match matrix cells: one unit is roughly the spacing between neighbouring LEDs.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LedPosition {
+    pub x: f32,
+    pub y: f32,
+}
+
 /// Segment layout type for a region of LEDs.
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub enum SegmentType {
@@ -62,6 +225,10 @@ pub struct SegmentDefinition {
     pub leds_count: usize,
     /// Optional 2D matrix layout for this segment.
     pub matrix: Option<MatrixMap>,
+    /// The segment's LEDs are wired end to start, so its first virtual LED is its last
+    /// physical one.
+    #[serde(default)]
+    pub reversed: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -110,12 +277,33 @@ pub trait Controller: Send {
     /// Outputs exposed by this device.
     fn outputs(&self) -> Vec<OutputPortDefinition>;
 
+    /// How the device's LEDs are wired; encoders write each color in this order.
+    fn color_order(&self) -> ColorOrder {
+        ColorOrder::Rgb
+    }
+
+    /// Encode for a strip the user says is wired in `order`. Drivers whose protocol fixes
+    /// the order keep this default and refuse.
+    fn set_color_order(&mut self, order: ColorOrder) -> Result<(), String> {
+        let _ = order;
+        Err("This device's color order can't be changed".to_string())
+    }
+
     /// Update the device with a flattened frame of colors in **physical order**.
     ///
     /// The physical order is defined as: outputs in `outputs()` order, and
     /// within each output, LEDs in the driver's physical order (0..leds_count).
     fn update(&mut self, colors: &[Color]) -> Result<(), String>;
 
+    /// Change the physical LED count of an editable output (e.g. a user-defined strip length).
+    ///
+    /// Only drivers whose hardware takes the count from the host implement this; the
+    /// manager validates against `OutputCapabilities` before calling it.
+    fn set_output_leds_count(&mut self, output_id: &str, leds_count: usize) -> Result<(), String> {
+        let _ = leds_count;
+        Err(format!("Output '{}' does not support changing its LED count", output_id))
+    }
+
     fn clear(&mut self) -> Result<(), String> {
         // Best-effort default: clear the sum of output lengths.
         let len: usize = self.outputs().iter().map(|o| o.leds_count).sum();
@@ -123,15 +311,115 @@ pub trait Controller: Send {
         self.update(&black)
     }
 
+    /// Release the transport. The LEDs keep what they last showed; callers that want them
+    /// dark call `clear` first.
     fn disconnect(&mut self) -> Result<(), String> {
         Ok(())
     }
+
+    /// Whether the device dims itself through `set_brightness`. The runner then sends such
+    /// controllers unscaled frames whenever one level covers the whole device.
+    fn has_hardware_brightness(&self) -> bool {
+        false
+    }
+
+    /// Set the device's own brightness (0..=100), applied on top of the frames it is sent.
+    fn set_brightness(&mut self, _brightness: u8) -> Result<(), String> {
+        Err("Hardware brightness is not supported".to_string())
+    }
+
+    /// Frame counters for transports that skip frames arriving faster than they can carry
+    /// them. The runner uses them to slow down to the rate the hardware accepts.
+    fn write_metrics(&self) -> Option<WriteMetrics> {
+        None
+    }
+
+    /// Fastest rate the transport can carry frames at. The runner doesn't tick faster than
+    /// this, so it doesn't render frames the device would only drop. `None` for no limit.
+    fn max_update_hz(&self) -> Option<f32> {
+        None
+    }
+
+    /// Current the LEDs may draw. The runner dims frames that would draw more, so a long
+    /// strip at full white doesn't brown out its supply. `None` for no limit.
+    fn power_budget(&self) -> Option<PowerBudget> {
+        None
+    }
+
+    /// Fraction (0.0–1.0) of recent frames the device reported not receiving, for transports
+    /// that get delivery confirmations. `None` when the controller can't tell.
+    fn frame_loss(&self) -> Option<f32> {
+        None
+    }
+
+    /// Health check for transports where writes succeed whether or not anything receives
+    /// them (e.g. UDP). Sends the next ping and returns whether the previous one was
+    /// answered; must not block. `None` when the protocol has nothing to ping with.
+    fn ping(&mut self) -> Option<bool> {
+        None
+    }
+}
+
+/// Supply limit for a controller's LEDs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PowerBudget {
+    pub max_milliamps: u32,
+    /// Draw of one LED at full white, split evenly over its three channels.
+    pub ma_per_led_full_white: f32,
+}
+
+/// Frames a rate-limited transport sent or skipped since it was opened.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WriteMetrics {
+    pub sent: u64,
+    pub dropped: u64,
+}
+
+/// How sure a probe is about what it found. When several probes report the same physical
+/// device in one scan, the controller from the most specific probe is kept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProbeSpecificity {
+    /// Accepts any device of a transport class (e.g. every USB serial port).
+    Generic,
+    /// Matched by identifiers such as VID/PID or an mDNS service type.
+    Matched,
+    /// Confirmed by a protocol handshake with the device.
+    Verified,
 }
 
 pub struct ControllerMetadata {
     pub name: &'static str,
     pub description: &'static str,
+    /// Decides between drivers that can drive the same hardware before `specificity` does;
+    /// higher wins. Native drivers use 0, bridges/proxies to other software go negative.
+    pub priority: i32,
+    pub specificity: ProbeSpecificity,
     pub probe: fn() -> Vec<Box<dyn Controller>>,
 }
 
 inventory::collect!(ControllerMetadata);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daylight_is_close_to_white() {
+        let c = kelvin_to_rgb(6500.0);
+        assert_eq!(c.r, 255);
+        assert!(c.g >= 245 && c.b >= 245, "{c:?}");
+    }
+
+    #[test]
+    fn candlelight_is_red_dominant() {
+        let c = kelvin_to_rgb(1000.0);
+        assert_eq!((c.r, c.b), (255, 0));
+        assert!(c.g < c.r / 2, "{c:?}");
+    }
+
+    #[test]
+    fn temperatures_outside_the_fit_are_clamped() {
+        assert_eq!(kelvin_to_rgb(200.0), kelvin_to_rgb(1000.0));
+        assert_eq!(kelvin_to_rgb(100_000.0), kelvin_to_rgb(40000.0));
+    }
+}
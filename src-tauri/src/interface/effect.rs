@@ -1,14 +1,22 @@
-use super::controller::Color;
+use super::controller::{Color, LedPosition};
+use serde::Serialize;
 use serde_json::Value;
 use std::time::Duration;
 
 pub trait Effect: Send {
     fn id(&self) -> String;
     fn name(&self) -> String;
-    fn tick(&mut self, elapsed: Duration, buffer: &mut [Color]);
+    /// Render one frame. `time` is the shared animation clock (plus the scope's phase
+    /// offset), the same for every device, so effects that derive their phase from it stay
+    /// in sync across devices. It is not reset when the effect is (re)selected.
+    fn tick(&mut self, time: Duration, buffer: &mut [Color]);
     /// Called when the virtual device layout (width/height) changes.
     /// Default implementation ignores the size, which is fine for 1D effects.
     fn resize(&mut self, _width: usize, _height: usize) {}
+    /// Called with the physical position of every LED in the buffer when the output has a
+    /// spatial layout, and with `None` when it is removed. Effects that don't map by position
+    /// can ignore it.
+    fn set_positions(&mut self, _positions: Option<&[LedPosition]>) {}
     fn update_params(&mut self, _params: Value) {}
     /// Returns whether the effect is ready to be displayed.
     ///
@@ -18,6 +26,27 @@ pub trait Effect: Send {
     fn is_ready(&self) -> bool {
         true
     }
+    /// Why the effect's screen capture is currently failing, if it captures at all. The
+    /// runner reports changes to the frontend as `capture-error` events.
+    fn capture_error(&self) -> Option<CaptureFailure> {
+        None
+    }
+    /// Frames per second the effect needs to look right. The runner renders a device only
+    /// as often as its most demanding target asks, never faster than its base rate; `None`
+    /// means the base rate.
+    fn preferred_fps(&self) -> Option<u32> {
+        None
+    }
+}
+
+/// A failing screen capture, as shown to the user.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureFailure {
+    pub display_index: usize,
+    /// Stable identifier such as `permission_denied` or `no_frame`.
+    pub code: &'static str,
+    pub message: String,
 }
 
 /// How the frontend should treat a parameter when its dependency condition is not met.
@@ -74,6 +103,42 @@ pub enum EffectParamKind {
     Color {
         default: &'static str,
     },
+    /// Free-form single-line text such as a URL or file path.
+    Text {
+        default: &'static str,
+        placeholder: Option<&'static str>,
+    },
+    /// Hue, saturation and value, as an object `{ "h", "s", "v" }`: hue in degrees
+    /// (0–360), saturation and value 0.0–1.0. Read with [`params_get_hsv`].
+    Hsv {
+        default_h: f64,
+        default_s: f64,
+        default_v: f64,
+    },
+    /// Color temperature in Kelvin. Read with [`params_get_kelvin`], convert with
+    /// [`kelvin_to_rgb`](super::controller::kelvin_to_rgb).
+    Temperature {
+        min_k: f64,
+        max_k: f64,
+        default_k: f64,
+    },
+}
+
+/// Hue (degrees, `0.0..360.0`), saturation and value (`0.0..=1.0`) of the `Hsv` param `key`
+/// in an `update_params` payload, if it's there. Missing components count as 0.
+pub fn params_get_hsv(params: &Value, key: &str) -> Option<(f32, f32, f32)> {
+    let hsv = params.get(key)?.as_object()?;
+    let component = |name: &str| hsv.get(name).and_then(Value::as_f64).unwrap_or(0.0) as f32;
+    Some((
+        component("h").rem_euclid(360.0),
+        component("s").clamp(0.0, 1.0),
+        component("v").clamp(0.0, 1.0),
+    ))
+}
+
+/// The `Temperature` param `key` in an `update_params` payload, in Kelvin, if it's there.
+pub fn params_get_kelvin(params: &Value, key: &str) -> Option<f32> {
+    params.get(key)?.as_f64().map(|k| (k as f32).clamp(1000.0, 40000.0))
 }
 
 pub enum SelectOptions {
@@ -109,14 +174,52 @@ impl SelectOptions {
     }
 }
 
+/// Segment layout an effect needs in order to render something meaningful.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EffectLayout {
+    /// Works on single LEDs, strips and matrices alike.
+    Any,
+    /// Only makes sense on 2D matrix segments.
+    Matrix,
+}
+
 pub struct EffectMetadata {
     pub id: &'static str,
     pub name: &'static str,
     pub description: Option<&'static str>,
     pub group: Option<&'static str>,
     pub icon: Option<&'static str>,
+    pub layout: EffectLayout,
     pub params: &'static [EffectParam],
     pub factory: fn() -> Box<dyn Effect>,
 }
 
 inventory::collect!(EffectMetadata);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn hsv_params_are_wrapped_clamped_and_default_to_zero() {
+        let params = json!({
+            "color": { "h": 420.0, "s": 1.5, "v": 0.25 },
+            "partial": { "h": 90.0 },
+        });
+        assert_eq!(params_get_hsv(&params, "color"), Some((60.0, 1.0, 0.25)));
+        assert_eq!(params_get_hsv(&params, "partial"), Some((90.0, 0.0, 0.0)));
+        assert_eq!(params_get_hsv(&params, "missing"), None);
+        assert_eq!(params_get_hsv(&json!({ "color": "#ff0000" }), "color"), None);
+    }
+
+    #[test]
+    fn kelvin_params_are_clamped_to_the_supported_range() {
+        let params = json!({ "warm": 2700.0, "low": 10.0, "high": 1e6, "text": "2700" });
+        assert_eq!(params_get_kelvin(&params, "warm"), Some(2700.0));
+        assert_eq!(params_get_kelvin(&params, "low"), Some(1000.0));
+        assert_eq!(params_get_kelvin(&params, "high"), Some(40000.0));
+        assert_eq!(params_get_kelvin(&params, "text"), None);
+        assert_eq!(params_get_kelvin(&params, "missing"), None);
+    }
+}
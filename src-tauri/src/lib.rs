@@ -6,6 +6,7 @@ pub mod api;
 use crate::manager::LightingManager;
 use crate::api::commands;
 use crate::api::config_store;
+use crate::api::hotkeys;
 use log::LevelFilter;
 use tauri_plugin_log::{RotationStrategy, Target, TargetKind, TimezoneStrategy, WEBVIEW_TARGET};
 use tauri::Manager;
@@ -154,18 +155,65 @@ pub fn run() {
         .plugin(log_plugin)
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_os::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(hotkeys::handle_shortcut)
+                .build(),
+        )
         .manage(LightingManager::new())
         .invoke_handler(tauri::generate_handler![
             commands::scan_devices,
+            commands::list_controller_kinds,
+            commands::rescan_controller_kind,
+            commands::add_sacn_device,
+            commands::remove_sacn_device,
             commands::get_devices,
             commands::get_device,
+            commands::set_mdns_timeout_ms,
+            commands::get_mdns_timeout_ms,
+            commands::get_frame_acks,
+            commands::set_frame_acks,
+            commands::get_udp_payload_bytes,
+            commands::set_udp_payload_bytes,
+            commands::get_network_source_address,
+            commands::set_network_source_address,
+            commands::set_drgb_keepalive,
+            commands::get_drgb_keepalive,
             commands::get_effects,
+            commands::get_effect_schema,
+            commands::get_effect_groups,
             commands::get_displays,
+            commands::run_capture_benchmark,
+            commands::capture_preview,
             commands::set_effect,
             commands::update_effect_params,
             commands::set_scope_effect,
+            commands::set_device_solid_color,
+            commands::clear_device,
+            commands::apply_effect_to_outputs,
             commands::update_scope_effect_params,
+            commands::set_scope_overlay,
+            commands::update_scope_overlay_params,
+            commands::set_scope_phase_offset,
+            commands::set_scope_audio_brightness,
             commands::set_output_segments,
+            commands::reorder_segments,
+            commands::set_output_leds_count,
+            commands::set_output_led_positions,
+            commands::set_output_order,
+            commands::set_output_soft_start,
+            commands::set_output_transform,
+            commands::export_device_layout,
+            commands::import_device_layout,
+            commands::set_output_enabled,
+            commands::persist_state,
+            commands::restore_state,
+            commands::start_frame_recording,
+            commands::stop_frame_recording,
+            commands::set_device_color_vision,
+            commands::set_device_smoothing,
+            commands::set_device_power_limit,
+            commands::set_device_color_order,
             commands::set_brightness,
             commands::set_scope_brightness,
             commands::set_capture_max_pixels,
@@ -173,16 +221,55 @@ pub fn run() {
             commands::set_capture_fps,
             commands::get_capture_fps,
             commands::set_capture_method,
+            commands::configure_capture,
             commands::get_capture_method,
+            commands::set_capture_high_bit_depth,
+            commands::get_capture_high_bit_depth,
+            commands::set_capture_show_cursor,
+            commands::get_capture_show_cursor,
+            commands::set_capture_sample_ratio,
+            commands::get_capture_sample_ratio,
+            commands::set_capture_tone_mapping,
+            commands::get_capture_tone_mapping,
+            commands::set_capture_target_nits,
+            commands::get_capture_target_nits,
+            commands::set_capture_crop,
+            commands::get_capture_crop,
+            commands::set_capture_paused,
+            commands::get_capture_paused,
+            commands::check_screen_permission,
+            commands::open_screen_permission_settings,
             commands::get_window_effects,
             commands::get_window_effect,
             commands::set_window_effect,
             commands::get_system_info,
             commands::get_minimize_to_tray,
             commands::set_minimize_to_tray,
+            commands::get_clear_leds_on_exit,
+            commands::set_clear_leds_on_exit,
+            commands::get_partial_updates,
+            commands::set_partial_updates,
+            commands::get_adaptive_frame_rate,
+            commands::set_adaptive_frame_rate,
+            commands::get_soft_start,
+            commands::set_soft_start,
+            commands::get_color_vision,
+            commands::set_color_vision,
+            commands::get_write_retries,
+            commands::set_write_retries,
+            commands::get_controller_preferences,
+            commands::set_controller_preference,
             commands::get_app_config,
             commands::set_app_config,
             commands::get_device_config,
+            commands::save_scene,
+            commands::list_scenes,
+            commands::apply_scene,
+            commands::delete_scene,
+            commands::report_input_activity,
+            commands::get_hotkeys,
+            commands::set_hotkey,
+            commands::delete_hotkey,
         ])
         .on_window_event(|window, event| {
             // 只处理主窗口
@@ -231,6 +318,10 @@ pub fn run() {
                 if let Ok(cfg) = config_store::load_app_config(handle) {
                     commands::apply_app_config_to_runtime(&cfg, handle);
                 }
+                commands::restore_scenes(&handle.state::<LightingManager>(), handle);
+                commands::restore_frame_snapshots(&handle.state::<LightingManager>(), handle);
+                commands::start_frame_snapshot_saver(handle.clone());
+                commands::start_health_checker(handle.clone());
             }
 
             #[cfg(any(target_os = "windows", target_os = "macos"))]
@@ -288,6 +379,11 @@ pub fn run() {
             }
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                commands::shutdown_devices(app_handle);
+            }
+        });
 }
@@ -1,18 +1,222 @@
-use crate::interface::controller::{Controller, ControllerMetadata};
+use crate::interface::controller::{Controller, ControllerMetadata, ProbeSpecificity};
 use crate::interface::effect::{Effect, EffectMetadata, EffectParamKind};
 use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, RwLock};
+use std::time::Instant;
+
+/// Driver chosen by the user per device serial id, for hardware several drivers can drive.
+static PREFERRED_DRIVERS: RwLock<BTreeMap<String, String>> = RwLock::new(BTreeMap::new());
 
 pub fn list_controller_drivers() -> Vec<&'static ControllerMetadata> {
     inventory::iter::<ControllerMetadata>.into_iter().collect()
 }
 
-pub fn scan_controllers() -> Vec<Box<dyn Controller>> {
-    let mut controllers = Vec::new();
-    for driver in inventory::iter::<ControllerMetadata> {
-        log::debug!(driver = driver.name; "Probing controller driver");
-        controllers.extend((driver.probe)());
+pub fn preferred_drivers() -> BTreeMap<String, String> {
+    PREFERRED_DRIVERS.read().map(|p| p.clone()).unwrap_or_default()
+}
+
+pub fn set_preferred_drivers(preferences: BTreeMap<String, String>) {
+    if let Ok(mut guard) = PREFERRED_DRIVERS.write() {
+        *guard = preferences;
+    }
+}
+
+/// Prefer `driver` for the device with `serial_id` on the next scan; `None` clears it.
+pub fn set_preferred_driver(serial_id: &str, driver: Option<&str>) -> Result<(), String> {
+    let serial_id = serial_id.trim();
+    if serial_id.is_empty() {
+        return Err("Serial id must not be empty".to_string());
+    }
+    if let Some(driver) = driver {
+        if !list_controller_drivers().iter().any(|d| d.name == driver) {
+            return Err(format!("Unknown controller driver: {}", driver));
+        }
+    }
+
+    let mut guard = PREFERRED_DRIVERS
+        .write()
+        .map_err(|_| "Driver preferences lock poisoned".to_string())?;
+    match driver {
+        Some(driver) => guard.insert(serial_id.to_string(), driver.to_string()),
+        None => guard.remove(serial_id),
+    };
+    Ok(())
+}
+
+/// A controller reported by a probe during [`scan_controllers_with`].
+pub struct FoundController {
+    pub controller: Box<dyn Controller>,
+    /// Driver whose probe found it.
+    pub driver: &'static ControllerMetadata,
+    /// Port of a controller reported earlier in this scan for the same physical device by
+    /// a less specific probe. The caller should drop that device in favour of this one.
+    pub replaces: Option<String>,
+}
+
+/// Run every driver probe on its own thread and hand each controller to `on_found`
+/// as soon as its probe returns. Slow probes (e.g. mDNS discovery) no longer delay
+/// the others; returns once all probes have finished.
+///
+/// `on_found` runs under the dedup lock, one controller at a time, so the caller has
+/// registered a controller before a later one can replace it.
+///
+/// When probes from different drivers report the same device (same serial number), only
+/// one controller is kept: the user's preferred driver for that serial, then the highest
+/// [`ControllerMetadata::priority`], then the most specific probe ([`ProbeSpecificity`]).
+pub fn scan_controllers_with<F>(on_found: F)
+where
+    F: Fn(FoundController) + Sync,
+{
+    scan_drivers_with(inventory::iter::<ControllerMetadata>, on_found);
+}
+
+/// [`scan_controllers_with`] over a chosen set of drivers. Dedup only sees the controllers
+/// these drivers report.
+pub(crate) fn scan_drivers_with<I, F>(drivers: I, on_found: F)
+where
+    I: IntoIterator<Item = &'static ControllerMetadata>,
+    F: Fn(FoundController) + Sync,
+{
+    let on_found = &on_found;
+    let dedup = &Mutex::new(ControllerDedup::new(preferred_drivers()));
+    std::thread::scope(|scope| {
+        for driver in drivers {
+            scope.spawn(move || {
+                log::debug!(driver = driver.name; "Probing controller driver");
+                let started = Instant::now();
+                let found = (driver.probe)();
+                log::debug!(
+                    driver = driver.name,
+                    count = found.len(),
+                    elapsed_ms = started.elapsed().as_millis() as u64;
+                    "Controller driver probe finished"
+                );
+                for controller in found {
+                    let port = controller.port_name();
+                    // Held through `on_found`: deciding and registering must not interleave
+                    // with another driver's replacement of the same device.
+                    let mut dedup = dedup.lock().unwrap();
+                    let decision = dedup.offer(
+                        driver.name,
+                        driver.priority,
+                        driver.specificity,
+                        &controller.serial_id(),
+                        &port,
+                    );
+                    match decision {
+                        DedupDecision::Accept { replaces } => on_found(FoundController {
+                            controller,
+                            driver,
+                            replaces,
+                        }),
+                        DedupDecision::Reject { kept_driver } => {
+                            log::info!(
+                                driver = driver.name,
+                                port = port.as_str(),
+                                kept_driver;
+                                "Skipping controller already provided by another driver"
+                            );
+                        }
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// Some firmware reports this instead of leaving the serial empty.
+const PLACEHOLDER_SERIAL: &str = "unknown";
+
+fn real_serial(serial_id: &str) -> Option<&str> {
+    let serial = serial_id.trim();
+    (!serial.is_empty() && !serial.eq_ignore_ascii_case(PLACEHOLDER_SERIAL)).then_some(serial)
+}
+
+/// Key identifying a physical device across drivers: the serial number when the device has
+/// a real one, otherwise the port.
+pub(crate) fn device_identity(serial_id: &str, port_name: &str) -> String {
+    match real_serial(serial_id) {
+        Some(serial) => format!("serial:{}", serial),
+        None => format!("port:{}", port_name),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum DedupDecision {
+    Accept { replaces: Option<String> },
+    Reject { kept_driver: &'static str },
+}
+
+/// Ordering used to pick between drivers; compared field by field, higher wins.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct ClaimRank {
+    preferred: bool,
+    priority: i32,
+    specificity: ProbeSpecificity,
+}
+
+struct Claim {
+    driver: &'static str,
+    rank: ClaimRank,
+    port: String,
+}
+
+/// Keeps one controller per physical device across the drivers of a single scan.
+#[derive(Default)]
+struct ControllerDedup {
+    preferences: BTreeMap<String, String>,
+    claims: HashMap<String, Claim>,
+}
+
+impl ControllerDedup {
+    fn new(preferences: BTreeMap<String, String>) -> Self {
+        Self {
+            preferences,
+            claims: HashMap::new(),
+        }
+    }
+
+    fn offer(
+        &mut self,
+        driver: &'static str,
+        priority: i32,
+        specificity: ProbeSpecificity,
+        serial_id: &str,
+        port_name: &str,
+    ) -> DedupDecision {
+        let preferred = real_serial(serial_id)
+            .and_then(|serial| self.preferences.get(serial))
+            .is_some_and(|preferred| preferred == driver);
+        let claim = Claim {
+            driver,
+            rank: ClaimRank {
+                preferred,
+                priority,
+                specificity,
+            },
+            port: port_name.to_string(),
+        };
+        let Some(existing) = self.claims.get_mut(&device_identity(serial_id, port_name)) else {
+            self.claims.insert(device_identity(serial_id, port_name), claim);
+            return DedupDecision::Accept { replaces: None };
+        };
+
+        if existing.driver == driver {
+            // One driver reporting the same serial twice means two devices with cloned
+            // serial numbers; the driver knows its hardware better than we do.
+            DedupDecision::Accept { replaces: None }
+        } else if claim.rank > existing.rank {
+            let previous = std::mem::replace(existing, claim);
+            DedupDecision::Accept {
+                replaces: Some(previous.port),
+            }
+        } else {
+            DedupDecision::Reject {
+                kept_driver: existing.driver,
+            }
+        }
     }
-    controllers
 }
 
 pub fn list_effects() -> Vec<&'static EffectMetadata> {
@@ -35,6 +239,13 @@ pub fn default_params_for_effect(id: &str) -> Option<Map<String, Value>> {
             EffectParamKind::Select { default, .. } => Value::from(*default),
             EffectParamKind::Toggle { default } => Value::from(*default),
             EffectParamKind::Color { default } => Value::from(*default),
+            EffectParamKind::Text { default, .. } => Value::from(*default),
+            EffectParamKind::Hsv {
+                default_h,
+                default_s,
+                default_v,
+            } => serde_json::json!({ "h": default_h, "s": default_s, "v": default_v }),
+            EffectParamKind::Temperature { default_k, .. } => Value::from(*default_k),
         };
         map.insert(param.key.to_string(), value);
     }
@@ -48,3 +259,177 @@ pub fn create_effect(id: &str) -> Option<Box<dyn Effect>> {
         .find(|effect| effect.id == id)
         .map(|effect| (effect.factory)())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::controller::{Color, OutputPortDefinition};
+
+    struct StubController {
+        port: &'static str,
+        serial: &'static str,
+    }
+
+    impl Controller for StubController {
+        fn port_name(&self) -> String {
+            self.port.to_string()
+        }
+        fn model(&self) -> String {
+            "Stub".to_string()
+        }
+        fn description(&self) -> String {
+            "Stub".to_string()
+        }
+        fn serial_id(&self) -> String {
+            self.serial.to_string()
+        }
+        fn outputs(&self) -> Vec<OutputPortDefinition> {
+            Vec::new()
+        }
+        fn update(&mut self, _colors: &[Color]) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    static GENERIC: ControllerMetadata = ControllerMetadata {
+        name: "Generic serial",
+        description: "",
+        priority: 0,
+        specificity: ProbeSpecificity::Generic,
+        probe: || {
+            vec![Box::new(StubController {
+                port: "COM3",
+                serial: "SK-1",
+            })]
+        },
+    };
+
+    static NETWORK: ControllerMetadata = ControllerMetadata {
+        name: "Network",
+        description: "",
+        priority: 0,
+        specificity: ProbeSpecificity::Verified,
+        probe: || {
+            vec![Box::new(StubController {
+                port: "192.168.1.20",
+                serial: "SK-1",
+            })]
+        },
+    };
+
+    #[test]
+    fn same_serial_from_two_drivers_yields_one_survivor() {
+        let found = Mutex::new(Vec::new());
+        scan_drivers_with([&GENERIC, &NETWORK], |f| {
+            found.lock().unwrap().push((f.controller.port_name(), f.replaces));
+        });
+
+        // Probe threads race, so the generic one may or may not be reported first; either
+        // way the verified controller is the only one left once replacements are applied.
+        let mut survivors: Vec<String> = Vec::new();
+        for (port, replaces) in found.into_inner().unwrap() {
+            if let Some(old) = replaces {
+                survivors.retain(|p| *p != old);
+            }
+            survivors.push(port);
+        }
+        assert_eq!(survivors, vec!["192.168.1.20".to_string()]);
+    }
+
+    static SLOW_NETWORK: ControllerMetadata = ControllerMetadata {
+        name: "Slow network",
+        description: "",
+        priority: 0,
+        specificity: ProbeSpecificity::Verified,
+        probe: || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            vec![Box::new(StubController {
+                port: "192.168.1.20",
+                serial: "SK-1",
+            })]
+        },
+    };
+
+    #[test]
+    fn a_replacement_waits_for_the_replaced_device_to_be_registered() {
+        // The generic controller is accepted first but takes a while to register; the
+        // verified one is decided meanwhile on the other probe thread and retires it.
+        let registered = Mutex::new(Vec::new());
+        scan_drivers_with([&GENERIC, &SLOW_NETWORK], |f| {
+            let port = f.controller.port_name();
+            if port == "COM3" {
+                std::thread::sleep(std::time::Duration::from_millis(150));
+            }
+            let mut registered = registered.lock().unwrap();
+            if let Some(old) = f.replaces {
+                registered.retain(|p| *p != old);
+            }
+            registered.push(port);
+        });
+        assert_eq!(registered.into_inner().unwrap(), ["192.168.1.20"]);
+    }
+
+    #[test]
+    fn placeholder_serials_do_not_collide() {
+        let mut dedup = ControllerDedup::default();
+        let a = dedup.offer("A", 0, ProbeSpecificity::Matched, "unknown", "hid-1");
+        let b = dedup.offer("B", 0, ProbeSpecificity::Matched, "unknown", "hid-2");
+        assert_eq!(a, DedupDecision::Accept { replaces: None });
+        assert_eq!(b, DedupDecision::Accept { replaces: None });
+    }
+
+    #[test]
+    fn equal_specificity_keeps_the_first_driver() {
+        let mut dedup = ControllerDedup::default();
+        dedup.offer("A", 0, ProbeSpecificity::Matched, "X", "p1");
+        assert_eq!(
+            dedup.offer("B", 0, ProbeSpecificity::Matched, "X", "p2"),
+            DedupDecision::Reject { kept_driver: "A" }
+        );
+        // The same driver reporting a cloned serial is still accepted.
+        assert_eq!(
+            dedup.offer("A", 0, ProbeSpecificity::Matched, "X", "p3"),
+            DedupDecision::Accept { replaces: None }
+        );
+    }
+
+    #[test]
+    fn ties_break_by_preference_then_priority_then_specificity() {
+        use ProbeSpecificity::*;
+
+        // Priority beats specificity.
+        let mut dedup = ControllerDedup::default();
+        dedup.offer("proxy", -10, Verified, "X", "proxy:1");
+        assert_eq!(
+            dedup.offer("native", 0, Generic, "X", "COM3"),
+            DedupDecision::Accept {
+                replaces: Some("proxy:1".to_string())
+            }
+        );
+
+        // Specificity decides between equal priorities.
+        let mut dedup = ControllerDedup::default();
+        dedup.offer("generic", 0, Generic, "X", "COM3");
+        assert_eq!(
+            dedup.offer("udp", 0, Matched, "X", "10.0.0.2"),
+            DedupDecision::Accept {
+                replaces: Some("COM3".to_string())
+            }
+        );
+
+        // A user preference beats both, and the losing driver is rejected afterwards.
+        let preferences = BTreeMap::from([("X".to_string(), "proxy".to_string())]);
+        let mut dedup = ControllerDedup::new(preferences);
+        dedup.offer("native", 10, Verified, "X", "COM3");
+        assert_eq!(
+            dedup.offer("proxy", -10, Generic, "X", "proxy:1"),
+            DedupDecision::Accept {
+                replaces: Some("COM3".to_string())
+            }
+        );
+        assert_eq!(
+            dedup.offer("other", 50, Verified, "X", "10.0.0.2"),
+            DedupDecision::Reject { kept_driver: "proxy" }
+        );
+    }
+}
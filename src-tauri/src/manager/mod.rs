@@ -1,22 +1,47 @@
 pub mod inventory;
+pub mod recorder;
 pub mod runner;
 
 use serde_json::{Map, Value};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tauri::AppHandle;
 
 use crate::interface::controller::{
-    Controller, DeviceType, MatrixMap, OutputCapabilities, OutputPortDefinition, SegmentDefinition,
-    SegmentType,
+    Color, ColorOrder, Controller, ControllerMetadata, DeviceType, LedPosition, MatrixMap, MatrixTransform,
+    OutputCapabilities, OutputPortDefinition, Rotation,
+    SegmentDefinition, SegmentType,
 };
+use crate::interface::effect::EffectLayout;
+use crate::resource::audio::brightness::AudioBrightnessSettings;
+use crate::resource::color::vision::ColorVisionMode;
 
-use self::inventory::{default_params_for_effect, scan_controllers};
-use self::runner::DeviceRunner;
+use self::inventory::{
+    default_params_for_effect, device_identity, list_controller_drivers, scan_controllers_with,
+    scan_drivers_with, FoundController,
+};
+use self::recorder::{FrameRecorder, RecordedOutput};
+use self::runner::{record_ping, DeviceRunner, MAX_SMOOTHING, MAX_SOFT_START_MS};
 
 type ControllerRef = Arc<Mutex<Box<dyn Controller>>>;
 
+/// `(port, device_identity)` of a device waiting to be re-probed.
+type LostDevice = (String, String);
+
+const TURN_OFF_EFFECT_ID: &str = "turn_off";
+const MONOCHROME_EFFECT_ID: &str = "monochrome";
+
+/// Wait before the first re-probe for a device its runner gave up on; doubled after each
+/// attempt that doesn't find it, up to `RECONNECT_BACKOFF_MAX`.
+const RECONNECT_BACKOFF_MIN: Duration = Duration::from_secs(2);
+const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// Upper bound for a scope's phase offset on the shared animation clock.
+pub const MAX_PHASE_OFFSET_MS: u32 = 60_000;
+
 fn default_brightness() -> u8 {
     100
 }
@@ -73,6 +98,66 @@ pub struct ScopeModeState {
     pub effective_params: Option<Map<String, Value>>,
     /// Where `effective_effect_id` is coming from.
     pub effective_from: Option<ScopeRef>,
+    /// Overlay composited over the effective effect (from the same origin scope).
+    pub effective_overlay: Option<ScopeOverlayState>,
+    /// Phase offset of the effective effect on the shared animation clock.
+    pub effective_phase_offset_ms: u32,
+    /// Audio-driven brightness of the effective effect, if enabled at its origin scope.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_audio_brightness: Option<AudioBrightnessSettings>,
+    /// Brightness (0..=100) this scope renders at, inherited along with the mode; the same
+    /// value as `ScopeBrightnessState::effective_value`.
+    pub effective_brightness: u8,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct ScopeOverlayState {
+    pub effect_id: String,
+    pub params: Map<String, Value>,
+    pub opacity: f32,
+    pub blend: BlendMode,
+}
+
+/// Overlay selection sent by the frontend; params are edited separately.
+#[derive(serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlaySettings {
+    pub effect_id: String,
+    pub opacity: f32,
+    #[serde(default)]
+    pub blend: BlendMode,
+}
+
+/// Which outputs of a device a bulk operation applies to. Unset fields match everything,
+/// so the default selects every output.
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputFilter {
+    #[serde(default)]
+    pub output_type: Option<SegmentType>,
+    /// Case-insensitive substring of the output name.
+    #[serde(default)]
+    pub name_contains: Option<String>,
+}
+
+impl OutputFilter {
+    fn matches(&self, out: &OutputConfig) -> bool {
+        self.output_type.is_none_or(|t| t == out.output_type)
+            && self.name_contains.as_deref().is_none_or(|needle| {
+                out.name.to_lowercase().contains(&needle.trim().to_lowercase())
+            })
+    }
+}
+
+/// How an overlay effect is composited over the scope's primary effect.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Add,
+    Screen,
+    Multiply,
 }
 
 #[derive(serde::Serialize, Clone, Debug)]
@@ -98,6 +183,7 @@ pub struct Segment {
     pub leds_count: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub matrix: Option<MatrixMap>,
+    pub reversed: bool,
     pub brightness: ScopeBrightnessState,
     pub mode: ScopeModeState,
 }
@@ -108,12 +194,71 @@ pub struct OutputPort {
     pub name: String,
     pub output_type: SegmentType,
     pub leds_count: usize,
+    /// As the effects see it, i.e. with `transform` applied.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub matrix: Option<MatrixMap>,
+    #[serde(skip_serializing_if = "MatrixTransform::is_identity")]
+    pub transform: MatrixTransform,
+    /// Physical position of each LED, in output order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub positions: Option<Vec<LedPosition>>,
     pub capabilities: OutputCapabilities,
     pub segments: Vec<Segment>,
     pub brightness: ScopeBrightnessState,
     pub mode: ScopeModeState,
+    /// Frames per second the device received over the last second, while its runner is
+    /// active. All outputs of a device are written together, so they share one value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_rate: Option<f32>,
+    /// Brightness ramp in ms when an effect lights this output up from dark; `None` follows
+    /// the app-wide default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub soft_start_ms: Option<u32>,
+    /// Disabled outputs stay dark but keep their effects and layout.
+    pub enabled: bool,
+}
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceHealth {
+    Ok,
+    /// Recent frames failed; the runner keeps retrying.
+    Error,
+    /// Too many consecutive failures; the runner stopped and disconnected the controller.
+    /// Also used while a device doesn't answer health checks, until it answers again.
+    Disconnected,
+}
+
+/// Last known result of writing frames to the hardware, shared with the runner.
+#[derive(serde::Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct DeviceStatus {
+    pub health: DeviceHealth,
+    pub last_error: Option<String>,
+    pub consecutive_failures: u32,
+    /// Recent frames the device reported not receiving, in percent; only for controllers
+    /// that get delivery confirmations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_loss_percent: Option<u8>,
+    /// Brightness in percent frames are dimmed to so the LEDs stay within the controller's
+    /// power budget; `None` while no limiting is needed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power_limit_percent: Option<u8>,
+    /// Health checks in a row the device didn't answer.
+    #[serde(skip)]
+    pub missed_pings: u32,
+}
+
+impl Default for DeviceStatus {
+    fn default() -> Self {
+        Self {
+            health: DeviceHealth::Ok,
+            last_error: None,
+            consecutive_failures: 0,
+            frame_loss_percent: None,
+            power_limit_percent: None,
+            missed_pings: 0,
+        }
+    }
 }
 
 #[derive(serde::Serialize, Clone, Debug)]
@@ -123,9 +268,35 @@ pub struct Device {
     pub description: String,
     pub id: String,
     pub device_type: DeviceType,
+    pub status: DeviceStatus,
     pub brightness: ScopeBrightnessState,
     pub outputs: Vec<OutputPort>,
     pub mode: ScopeModeState,
+    /// Color-blindness correction for this device; `None` follows the app-wide mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_vision: Option<ColorVisionMode>,
+    /// Share of the previous frame kept per 60 Hz frame (0 sends every frame as composed).
+    pub smoothing: f32,
+    /// User supply limit in mA; `None` leaves only the driver's own budget.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power_limit_ma: Option<u32>,
+    /// Channel order the device's frames are encoded in.
+    pub color_order: ColorOrder,
+}
+
+/// A lost device that [`LightingManager::reconnect_lost_devices`] found again.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReconnectedDevice {
+    pub port: String,
+    /// Port the device was managed under, when it came back under another one.
+    pub previous_port: Option<String>,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct SceneSummary {
+    pub name: String,
+    /// Serial ids of the devices the scene covers.
+    pub device_ids: Vec<String>,
 }
 
 // ============================================================================
@@ -138,6 +309,28 @@ struct ActiveEffect {
     started_at: Instant,
 }
 
+/// Secondary effect rendered on top of a scope's primary effect.
+#[derive(Clone, Debug)]
+struct OverlayConfig {
+    effect_id: String,
+    started_at: Instant,
+    params: Map<String, Value>,
+    /// 0.0 shows only the primary effect, 1.0 the fully blended result.
+    opacity: f32,
+    blend: BlendMode,
+}
+
+impl From<&OverlayConfig> for PersistedOverlayConfig {
+    fn from(value: &OverlayConfig) -> Self {
+        PersistedOverlayConfig {
+            effect_id: value.effect_id.clone(),
+            params: value.params.clone(),
+            opacity: value.opacity,
+            blend: value.blend,
+        }
+    }
+}
+
 // ============================================================================
 // Persisted config DTOs (stored under config/devices/*.json)
 // ============================================================================
@@ -148,6 +341,28 @@ pub struct PersistedModeConfig {
     pub selected: Option<String>,
     #[serde(default)]
     pub params: HashMap<String, Map<String, Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlay: Option<PersistedOverlayConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase_offset_ms: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_brightness: Option<AudioBrightnessSettings>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedOverlayConfig {
+    pub effect_id: String,
+    #[serde(default)]
+    pub params: Map<String, Value>,
+    #[serde(default = "default_overlay_opacity")]
+    pub opacity: f32,
+    #[serde(default)]
+    pub blend: BlendMode,
+}
+
+fn default_overlay_opacity() -> f32 {
+    1.0
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
@@ -157,6 +372,22 @@ pub struct PersistedDeviceConfig {
     pub effects: PersistedEffectsSection,
 }
 
+/// Version `export_layout` writes; layouts with any other version are rejected on import.
+const LAYOUT_VERSION: u64 = 1;
+
+/// A device's setup as shared between identical rigs.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct LayoutDocument {
+    version: u64,
+    /// Controller the layout was exported from. Informational: imports are checked against
+    /// the target's outputs instead.
+    #[serde(default)]
+    model: String,
+    #[serde(flatten)]
+    config: PersistedDeviceConfig,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PersistedDeviceSection {
@@ -165,16 +396,46 @@ pub struct PersistedDeviceSection {
     /// Keyed by `output_id`.
     #[serde(default)]
     pub layout: HashMap<String, PersistedOutputLayout>,
+    /// Output ids in the order the UI lists them; empty keeps the driver's order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub output_order: Vec<String>,
+    /// Per-device color-blindness correction override.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_vision: Option<ColorVisionMode>,
+    /// Frame smoothing; omitted while off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smoothing: Option<f32>,
+    /// Supply limit in mA; omitted when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub power_limit_ma: Option<u32>,
+    /// Wiring chosen by the user; omitted while the driver's own order applies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_order: Option<ColorOrder>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PersistedOutputLayout {
+    /// User-configured physical LED count, for drivers that let the host choose it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leds_count: Option<usize>,
     /// Ordered segment list for this output.
     ///
     /// Order matters for linear outputs because we derive physical offsets by accumulation.
     #[serde(default)]
     pub segments: Vec<SegmentDefinition>,
+    /// User-placed LED coordinates for outputs arranged in a free-form shape.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub positions: Option<Vec<LedPosition>>,
+    /// Per-output soft-start override, in ms.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub soft_start_ms: Option<u32>,
+    /// Only stored when the output has been disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Only stored for panels mounted rotated or mirrored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<MatrixTransform>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
@@ -184,6 +445,12 @@ pub struct PersistedEffectsSection {
     pub selected: Option<String>,
     #[serde(default)]
     pub params: HashMap<String, Map<String, Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlay: Option<PersistedOverlayConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase_offset_ms: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_brightness: Option<AudioBrightnessSettings>,
     /// Device-scope brightness (0..=100).
     #[serde(default = "default_brightness")]
     pub brightness: u8,
@@ -192,6 +459,47 @@ pub struct PersistedEffectsSection {
     pub outputs: Vec<PersistedOutputEffectsConfig>,
 }
 
+/// Effect selections for a set of devices, switched between as a whole (stored in scenes.json).
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedScene {
+    /// Keyed by device serial id.
+    #[serde(default)]
+    pub devices: BTreeMap<String, PersistedEffectsSection>,
+}
+
+/// Every connected device's config (stored in state.json), written on demand rather than on
+/// each change like the per-device files.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedState {
+    /// Keyed by device serial id, so a device found on another port keeps its config.
+    #[serde(default)]
+    pub devices: BTreeMap<String, PersistedDeviceConfig>,
+}
+
+/// The last frame written to a device (stored in frames.json), shown again when the device
+/// is found after a restart so it doesn't go dark while its effects start up.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedFrameSnapshot {
+    /// When the frame was taken, in ms since the Unix epoch.
+    pub saved_at_ms: u64,
+    /// Physical colors of each output, keyed by output id.
+    pub outputs: BTreeMap<String, Vec<Color>>,
+}
+
+/// Snapshots older than this are dropped instead of re-sent; the lights may have been
+/// changed by something else by then.
+const FRAME_SNAPSHOT_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+
+fn unix_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PersistedOutputEffectsConfig {
@@ -202,6 +510,12 @@ pub struct PersistedOutputEffectsConfig {
     pub selected: Option<String>,
     #[serde(default)]
     pub params: HashMap<String, Map<String, Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlay: Option<PersistedOverlayConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase_offset_ms: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_brightness: Option<AudioBrightnessSettings>,
     #[serde(default)]
     pub segments: Vec<PersistedSegmentEffectsConfig>,
 }
@@ -216,12 +530,23 @@ pub struct PersistedSegmentEffectsConfig {
     pub selected: Option<String>,
     #[serde(default)]
     pub params: HashMap<String, Map<String, Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlay: Option<PersistedOverlayConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase_offset_ms: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_brightness: Option<AudioBrightnessSettings>,
 }
 
 #[derive(Clone, Debug, Default)]
 struct ModeConfig {
     active_effect: Option<ActiveEffect>,
     params_by_effect: HashMap<String, Map<String, Value>>,
+    overlay: Option<OverlayConfig>,
+    /// Shift on the shared animation clock, for staggering scopes that run the same effect.
+    phase_offset_ms: u32,
+    /// Scale the output by audio loudness.
+    audio_brightness: Option<AudioBrightnessSettings>,
     rev: u64,
 }
 
@@ -231,10 +556,22 @@ impl ModeConfig {
     }
 
     fn set_inherit(&mut self) {
-        if self.active_effect.is_some() {
+        if self.active_effect.is_some()
+            || self.overlay.is_some()
+            || self.phase_offset_ms != 0
+            || self.audio_brightness.is_some()
+        {
             self.rev = self.rev.wrapping_add(1);
         }
         self.active_effect = None;
+        // Overlay, phase offset and audio brightness only apply to this scope's own effect.
+        self.overlay = None;
+        self.phase_offset_ms = 0;
+        self.audio_brightness = None;
+    }
+
+    fn persisted_phase_offset(&self) -> Option<u32> {
+        (self.phase_offset_ms != 0).then_some(self.phase_offset_ms)
     }
 
     fn ensure_params_entry(&mut self, effect_id: &str) -> Result<(), String> {
@@ -264,6 +601,16 @@ impl ModeConfig {
         Ok(())
     }
 
+    /// Make an inheriting scope explicit with exactly what it inherits (effect, overlay,
+    /// phase and audio brightness), so the switch is invisible.
+    fn adopt(&mut self, resolved: &ResolvedEffect) -> Result<(), String> {
+        self.set_effect(&resolved.effect_id, resolved.started_at)?;
+        self.overlay = resolved.overlay.clone();
+        self.phase_offset_ms = resolved.phase_offset.as_millis() as u32;
+        self.audio_brightness = resolved.audio_brightness;
+        Ok(())
+    }
+
     fn merge_params(&mut self, effect_id: &str, params: &Map<String, Value>) -> Result<(), String> {
         self.ensure_params_entry(effect_id)?;
         let entry = self.params_by_effect.entry(effect_id.to_string()).or_default();
@@ -273,6 +620,59 @@ impl ModeConfig {
         self.rev = self.rev.wrapping_add(1);
         Ok(())
     }
+
+    /// Set or replace the overlay. Switching to another effect resets its params to the
+    /// defaults; keeping the same effect keeps its params and start time.
+    fn set_overlay(
+        &mut self,
+        effect_id: &str,
+        opacity: f32,
+        blend: BlendMode,
+        started_at: Instant,
+    ) -> Result<(), String> {
+        if !opacity.is_finite() {
+            return Err("Overlay opacity must be a number".to_string());
+        }
+        let opacity = opacity.clamp(0.0, 1.0);
+
+        match &mut self.overlay {
+            Some(overlay) if overlay.effect_id == effect_id => {
+                overlay.opacity = opacity;
+                overlay.blend = blend;
+            }
+            _ => {
+                let params = default_params_for_effect(effect_id)
+                    .ok_or_else(|| format!("Effect '{}' not found", effect_id))?;
+                self.overlay = Some(OverlayConfig {
+                    effect_id: effect_id.to_string(),
+                    started_at,
+                    params,
+                    opacity,
+                    blend,
+                });
+            }
+        }
+        self.rev = self.rev.wrapping_add(1);
+        Ok(())
+    }
+
+    fn clear_overlay(&mut self) {
+        if self.overlay.take().is_some() {
+            self.rev = self.rev.wrapping_add(1);
+        }
+    }
+
+    fn merge_overlay_params(&mut self, params: &Map<String, Value>) -> Result<(), String> {
+        let overlay = self
+            .overlay
+            .as_mut()
+            .ok_or_else(|| "No overlay in this scope".to_string())?;
+        for (k, v) in params {
+            overlay.params.insert(k.clone(), v.clone());
+        }
+        self.rev = self.rev.wrapping_add(1);
+        Ok(())
+    }
 }
 
 impl From<&ModeConfig> for PersistedModeConfig {
@@ -280,6 +680,9 @@ impl From<&ModeConfig> for PersistedModeConfig {
         PersistedModeConfig {
             selected: value.selected_effect_id(),
             params: value.params_by_effect.clone(),
+            overlay: value.overlay.as_ref().map(PersistedOverlayConfig::from),
+            phase_offset_ms: value.persisted_phase_offset(),
+            audio_brightness: value.audio_brightness,
         }
     }
 }
@@ -297,10 +700,170 @@ fn apply_persisted_mode(mode: &mut ModeConfig, persisted: &PersistedModeConfig)
         mode.active_effect = None;
     }
 
+    // A stored overlay whose effect no longer exists is dropped rather than failing the load.
+    mode.overlay = persisted
+        .overlay
+        .as_ref()
+        .filter(|_| mode.active_effect.is_some())
+        .filter(|o| default_params_for_effect(&o.effect_id).is_some())
+        .map(|o| OverlayConfig {
+            effect_id: o.effect_id.clone(),
+            started_at: Instant::now(),
+            params: o.params.clone(),
+            opacity: if o.opacity.is_finite() {
+                o.opacity.clamp(0.0, 1.0)
+            } else {
+                default_overlay_opacity()
+            },
+            blend: o.blend,
+        });
+    mode.phase_offset_ms = persisted
+        .phase_offset_ms
+        .filter(|_| mode.active_effect.is_some())
+        .unwrap_or(0)
+        .min(MAX_PHASE_OFFSET_MS);
+    mode.audio_brightness = persisted
+        .audio_brightness
+        .filter(|_| mode.active_effect.is_some())
+        .map(AudioBrightnessSettings::normalized);
+
     mode.rev = mode.rev.wrapping_add(1);
     Ok(())
 }
 
+fn export_persisted_effects(cfg: &DeviceConfig) -> PersistedEffectsSection {
+    // Scope brightness is only worth persisting when it differs from the default or the
+    // scope has its own mode (otherwise it follows the parent anyway).
+    let scope_brightness = |mode: &ModeConfig, brightness: u8| {
+        (mode.selected_effect_id().is_some() || brightness != 100).then_some(brightness)
+    };
+
+    let outputs = cfg
+        .outputs
+        .iter()
+        .map(|out| PersistedOutputEffectsConfig {
+            id: out.id.clone(),
+            brightness: scope_brightness(&out.mode, out.brightness),
+            selected: out.mode.selected_effect_id(),
+            params: out.mode.params_by_effect.clone(),
+            overlay: out.mode.overlay.as_ref().map(PersistedOverlayConfig::from),
+            phase_offset_ms: out.mode.persisted_phase_offset(),
+            audio_brightness: out.mode.audio_brightness,
+            segments: out
+                .segments
+                .iter()
+                .map(|s| PersistedSegmentEffectsConfig {
+                    id: s.id.clone(),
+                    brightness: scope_brightness(&s.mode, s.brightness),
+                    selected: s.mode.selected_effect_id(),
+                    params: s.mode.params_by_effect.clone(),
+                    overlay: s.mode.overlay.as_ref().map(PersistedOverlayConfig::from),
+                    phase_offset_ms: s.mode.persisted_phase_offset(),
+                    audio_brightness: s.mode.audio_brightness,
+                })
+                .collect(),
+        })
+        .collect();
+
+    PersistedEffectsSection {
+        selected: cfg.mode.selected_effect_id(),
+        params: cfg.mode.params_by_effect.clone(),
+        overlay: cfg.mode.overlay.as_ref().map(PersistedOverlayConfig::from),
+        phase_offset_ms: cfg.mode.persisted_phase_offset(),
+        audio_brightness: cfg.mode.audio_brightness,
+        brightness: cfg.brightness,
+        outputs,
+    }
+}
+
+/// Restore device, output and segment modes. Outputs/segments that no longer exist are ignored.
+fn apply_persisted_effects(
+    cfg: &mut DeviceConfig,
+    effects: &PersistedEffectsSection,
+) -> Result<(), String> {
+    cfg.brightness = effects.brightness;
+
+    let device_mode = PersistedModeConfig {
+        selected: effects.selected.clone(),
+        params: effects.params.clone(),
+        overlay: effects.overlay.clone(),
+        phase_offset_ms: effects.phase_offset_ms,
+        audio_brightness: effects.audio_brightness,
+    };
+    apply_persisted_mode(&mut cfg.mode, &device_mode)?;
+
+    for out_persisted in &effects.outputs {
+        let Some(out) = cfg.output_mut(&out_persisted.id) else {
+            continue;
+        };
+
+        // Brightness (optional per-scope).
+        out.brightness = out_persisted.brightness.unwrap_or(100);
+
+        let out_mode = PersistedModeConfig {
+            selected: out_persisted.selected.clone(),
+            params: out_persisted.params.clone(),
+            overlay: out_persisted.overlay.clone(),
+            phase_offset_ms: out_persisted.phase_offset_ms,
+            audio_brightness: out_persisted.audio_brightness,
+        };
+        apply_persisted_mode(&mut out.mode, &out_mode)?;
+
+        for seg_persisted in &out_persisted.segments {
+            if let Some(seg) = out
+                .segments
+                .iter_mut()
+                .find(|s| s.id == seg_persisted.id)
+            {
+                seg.brightness = seg_persisted.brightness.unwrap_or(100);
+                let seg_mode = PersistedModeConfig {
+                    selected: seg_persisted.selected.clone(),
+                    params: seg_persisted.params.clone(),
+                    overlay: seg_persisted.overlay.clone(),
+                    phase_offset_ms: seg_persisted.phase_offset_ms,
+                    audio_brightness: seg_persisted.audio_brightness,
+                };
+                let _ = apply_persisted_mode(&mut seg.mode, &seg_mode);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Forget selections and params of effects that are no longer registered, so a config saved
+/// by another build still loads.
+fn drop_unregistered_effects(effects: &mut PersistedEffectsSection) {
+    fn retain_known(
+        selected: &mut Option<String>,
+        params: &mut HashMap<String, Map<String, Value>>,
+    ) {
+        if selected
+            .as_deref()
+            .is_some_and(|id| default_params_for_effect(id).is_none())
+        {
+            *selected = None;
+        }
+        params.retain(|id, _| default_params_for_effect(id).is_some());
+    }
+
+    retain_known(&mut effects.selected, &mut effects.params);
+    for out in &mut effects.outputs {
+        retain_known(&mut out.selected, &mut out.params);
+        for seg in &mut out.segments {
+            retain_known(&mut seg.selected, &mut seg.params);
+        }
+    }
+}
+
+fn normalize_scene_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Scene name must not be empty".to_string());
+    }
+    Ok(name.to_string())
+}
+
 #[derive(Clone, Debug)]
 struct SegmentConfig {
     id: String,
@@ -308,6 +871,7 @@ struct SegmentConfig {
     segment_type: SegmentType,
     leds_count: usize,
     matrix: Option<MatrixMap>,
+    reversed: bool,
     brightness: u8,
     mode: ModeConfig,
 }
@@ -319,10 +883,18 @@ struct OutputConfig {
     output_type: SegmentType,
     leds_count: usize,
     matrix: Option<MatrixMap>,
+    /// One entry per LED when set; only kept for linear outputs.
+    positions: Option<Vec<LedPosition>>,
     capabilities: OutputCapabilities,
     brightness: u8,
     mode: ModeConfig,
     segments: Vec<SegmentConfig>,
+    /// Overrides the global soft-start duration (ms) for every target on this output.
+    soft_start_ms: Option<u32>,
+    /// When false the runner leaves this output's LEDs black; its config is kept.
+    enabled: bool,
+    /// Orientation correction for `matrix`, applied when the runner maps effects onto it.
+    transform: MatrixTransform,
 }
 
 #[derive(Clone, Debug)]
@@ -332,6 +904,19 @@ struct DeviceConfig {
     outputs: Vec<OutputConfig>,
     /// Fast lookup table for outputs by id. `outputs` remains the source of truth.
     output_index: HashMap<String, usize>,
+    /// Output ids in presentation order. Only affects the DTO; frames are always laid out
+    /// in `outputs` order. Empty means driver order.
+    display_order: Vec<String>,
+    /// Overrides the app-wide color-blindness correction for the whole device.
+    color_vision: Option<ColorVisionMode>,
+    /// How much of the previously sent frame the runner blends into each new one,
+    /// `0.0..=MAX_SMOOTHING`.
+    smoothing: f32,
+    /// Current the user's supply can deliver; the runner dims frames to stay under it.
+    power_limit_ma: Option<u32>,
+    /// The user's wiring override, already applied to the controller. `None` keeps the
+    /// driver's order.
+    color_order: Option<ColorOrder>,
 }
 
 #[derive(Clone, Debug)]
@@ -474,6 +1059,9 @@ struct ResolvedEffect {
     started_at: Instant,
     params: Map<String, Value>,
     origin_rev: u64,
+    overlay: Option<OverlayConfig>,
+    phase_offset: Duration,
+    audio_brightness: Option<AudioBrightnessSettings>,
 }
 
 const EFFECT_READY_TIMEOUT: Duration = Duration::from_secs(5);
@@ -543,6 +1131,75 @@ fn mode_for_scope_mut<'a>(
     }
 }
 
+fn validate_output_leds_count(out: &OutputConfig, leds_count: usize) -> Result<(), String> {
+    let caps = &out.capabilities;
+    if !caps.editable {
+        return Err(format!("Output '{}' is not editable", out.id));
+    }
+    if leds_count < caps.min_total_leds.max(1) || leds_count > caps.max_total_leds {
+        return Err(format!(
+            "LED count {} out of range {}..={} for output '{}'",
+            leds_count,
+            caps.min_total_leds.max(1),
+            caps.max_total_leds,
+            out.id
+        ));
+    }
+    if let Some(allowed) = &caps.allowed_total_leds {
+        if !allowed.contains(&leds_count) {
+            return Err(format!(
+                "LED count {} not allowed for output '{}' (allowed: {:?})",
+                leds_count, out.id, allowed
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn validate_led_positions(out: &OutputConfig, positions: &[LedPosition]) -> Result<(), String> {
+    if out.output_type != SegmentType::Linear {
+        return Err(format!(
+            "Output '{}' is {:?}; LED positions are only supported for Linear outputs",
+            out.id, out.output_type
+        ));
+    }
+    if positions.len() != out.leds_count {
+        return Err(format!(
+            "Got {} LED positions for output '{}' with {} LEDs",
+            positions.len(),
+            out.id,
+            out.leds_count
+        ));
+    }
+    if positions.iter().any(|p| !p.x.is_finite() || !p.y.is_finite()) {
+        return Err("LED positions must be finite numbers".to_string());
+    }
+    Ok(())
+}
+
+/// Resize an output on the driver, then resync the config (user segments that no longer
+/// add up are dropped by `sync_with_output_defs`).
+fn apply_output_leds_count(md: &ManagedDevice, output_id: &str, leds_count: usize) -> Result<(), String> {
+    {
+        let cfg = md.config.lock().unwrap();
+        let out = cfg
+            .output(output_id)
+            .ok_or_else(|| format!("Output '{}' not found", output_id))?;
+        if out.leds_count == leds_count {
+            return Ok(());
+        }
+        validate_output_leds_count(out, leds_count)?;
+    }
+
+    let defs = {
+        let mut controller = md.controller.lock().unwrap();
+        controller.set_output_leds_count(output_id, leds_count)?;
+        controller.outputs()
+    };
+    md.config.lock().unwrap().sync_with_output_defs(defs);
+    Ok(())
+}
+
 fn replace_segments_for_output(
     out: &mut OutputConfig,
     output_id: &str,
@@ -614,6 +1271,7 @@ fn replace_segments_for_output(
                 existing.segment_type = seg.segment_type;
                 existing.leds_count = seg.leds_count;
                 existing.matrix = seg.matrix;
+                existing.reversed = seg.reversed;
                 existing
             } else {
                 SegmentConfig {
@@ -622,6 +1280,7 @@ fn replace_segments_for_output(
                     segment_type: seg.segment_type,
                     leds_count: seg.leds_count,
                     matrix: seg.matrix,
+                    reversed: seg.reversed,
                     brightness: 100,
                     mode: ModeConfig::default(),
                 }
@@ -632,6 +1291,188 @@ fn replace_segments_for_output(
     Ok(())
 }
 
+fn parse_layout_document(document: Value) -> Result<PersistedDeviceConfig, String> {
+    let version = document
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| "Layout has no version".to_string())?;
+    if version != LAYOUT_VERSION {
+        return Err(format!(
+            "Unsupported layout version {} (expected {})",
+            version, LAYOUT_VERSION
+        ));
+    }
+    let document: LayoutDocument =
+        serde_json::from_value(document).map_err(|e| format!("Invalid layout: {}", e))?;
+    Ok(document.config)
+}
+
+/// `cfg` with a shared layout applied, or why it doesn't fit the device. Outputs the layout
+/// doesn't mention go back to the driver's defaults. Output lengths are taken as given; the
+/// caller resizes the driver to match.
+fn stage_layout(cfg: &DeviceConfig, config: &PersistedDeviceConfig) -> Result<DeviceConfig, String> {
+    let mut staged = cfg.clone();
+    if let Some(output_id) = config
+        .device
+        .layout
+        .keys()
+        .find(|id| staged.output(id).is_none())
+    {
+        return Err(format!("Output '{}' not found on this device", output_id));
+    }
+
+    for out in &mut staged.outputs {
+        let Some((output_id, layout)) = config.device.layout.get_key_value(&out.id) else {
+            out.segments.clear();
+            out.positions = None;
+            out.soft_start_ms = None;
+            out.enabled = true;
+            out.transform = MatrixTransform::default();
+            continue;
+        };
+
+        if let Some(leds_count) = layout.leds_count.filter(|n| *n != out.leds_count) {
+            if out.output_type != SegmentType::Linear {
+                return Err(format!("Output '{}' has a fixed LED count", output_id));
+            }
+            validate_output_leds_count(out, leds_count)?;
+            out.leds_count = leds_count;
+        }
+
+        if layout.segments.is_empty() {
+            out.segments.clear();
+        } else if out.output_type != SegmentType::Linear || !out.capabilities.editable {
+            return Err(format!("Output '{}' does not support segments", output_id));
+        } else {
+            replace_segments_for_output(out, output_id, layout.segments.clone())?;
+        }
+
+        if let Some(positions) = &layout.positions {
+            validate_led_positions(out, positions)?;
+        }
+        out.positions = layout.positions.clone();
+
+        if layout.soft_start_ms.is_some_and(|ms| ms > MAX_SOFT_START_MS) {
+            return Err(format!(
+                "Soft-start must be at most {} ms",
+                MAX_SOFT_START_MS
+            ));
+        }
+        out.soft_start_ms = layout.soft_start_ms;
+        out.enabled = layout.enabled.unwrap_or(true);
+
+        let transform = layout.transform.unwrap_or_default();
+        if !transform.is_identity() && out.matrix.is_none() {
+            return Err(format!("Output '{}' has no matrix layout", output_id));
+        }
+        out.transform = transform;
+    }
+
+    if config.device.output_order.is_empty() {
+        staged.display_order.clear();
+    } else {
+        staged.validate_display_order(&config.device.output_order)?;
+        staged.display_order = config.device.output_order.clone();
+    }
+    staged.color_vision = config.device.color_vision;
+    let smoothing = config.device.smoothing.unwrap_or(0.0);
+    if !(0.0..=MAX_SMOOTHING).contains(&smoothing) {
+        return Err(format!("Smoothing must be between 0 and {}", MAX_SMOOTHING));
+    }
+    staged.smoothing = smoothing;
+    if config.device.power_limit_ma == Some(0) {
+        return Err("Power limit must be above 0 mA".to_string());
+    }
+    staged.power_limit_ma = config.device.power_limit_ma;
+    staged.color_order = config.device.color_order;
+
+    validate_layout_effects(&staged, &config.effects)?;
+    apply_persisted_effects(&mut staged, &config.effects)?;
+    Ok(staged)
+}
+
+/// Every scope an imported layout sets an effect for must exist, and so must the effect.
+fn validate_layout_effects(cfg: &DeviceConfig, effects: &PersistedEffectsSection) -> Result<(), String> {
+    let registered = |selected: &Option<String>| match selected {
+        Some(id) if default_params_for_effect(id).is_none() => {
+            Err(format!("Effect '{}' is not available", id))
+        }
+        _ => Ok(()),
+    };
+
+    registered(&effects.selected)?;
+    for out_effects in &effects.outputs {
+        let out = cfg
+            .output(&out_effects.id)
+            .ok_or_else(|| format!("Output '{}' not found on this device", out_effects.id))?;
+        registered(&out_effects.selected)?;
+        for seg_effects in &out_effects.segments {
+            if !out.segments.iter().any(|s| s.id == seg_effects.id) {
+                return Err(format!(
+                    "Segment '{}' not found on output '{}'",
+                    seg_effects.id, out.id
+                ));
+            }
+            registered(&seg_effects.selected)?;
+        }
+    }
+    Ok(())
+}
+
+/// Apply a shared layout, checking all of it before anything is changed. If the hardware
+/// refuses part of it, what was already applied is undone.
+fn apply_layout(md: &ManagedDevice, config: &PersistedDeviceConfig) -> Result<(), String> {
+    let previous = md.config.lock().unwrap().clone();
+    stage_layout(&previous, config)?;
+    let previous_order = md.controller.lock().unwrap().color_order();
+
+    let mut resized = Vec::new();
+    let result = apply_layout_changes(md, config, &mut resized);
+    if result.is_err() {
+        let mut controller = md.controller.lock().unwrap();
+        for (output_id, leds_count) in resized.into_iter().rev() {
+            if let Err(err) = controller.set_output_leds_count(output_id, leds_count) {
+                log::warn!(
+                    output = output_id,
+                    err:display = err;
+                    "[layout] Failed to restore LED count"
+                );
+            }
+        }
+        if config.device.color_order.is_some() {
+            let _ = controller.set_color_order(previous_order);
+        }
+        *md.config.lock().unwrap() = previous;
+    }
+    result
+}
+
+/// The part of [`apply_layout`] that touches the hardware, recording each output it resized
+/// with the count it had before.
+fn apply_layout_changes<'a>(
+    md: &ManagedDevice,
+    config: &'a PersistedDeviceConfig,
+    resized: &mut Vec<(&'a str, usize)>,
+) -> Result<(), String> {
+    for (output_id, layout) in &config.device.layout {
+        let Some(leds_count) = layout.leds_count else {
+            continue;
+        };
+        let before = md.config.lock().unwrap().output(output_id).map(|out| out.leds_count);
+        apply_output_leds_count(md, output_id, leds_count)?;
+        if let Some(before) = before.filter(|&before| before != leds_count) {
+            resized.push((output_id.as_str(), before));
+        }
+    }
+    if let Some(order) = config.device.color_order {
+        md.controller.lock().unwrap().set_color_order(order)?;
+    }
+
+    let mut cfg = md.config.lock().unwrap();
+    *cfg = stage_layout(&cfg, config)?;
+    Ok(())
+}
+
 fn force_children_inherit(cfg: &mut DeviceConfig, scope: Scope<'_>) {
     match scope {
         Scope::Device => {
@@ -653,13 +1494,140 @@ fn force_children_inherit(cfg: &mut DeviceConfig, scope: Scope<'_>) {
     }
 }
 
-/// Resolve the effective effect for a scope by applying inheritance:
-/// segment -> output -> device.
-fn resolve_effect_for_scope(cfg: &DeviceConfig, port: &str, scope: Scope<'_>) -> Option<ResolvedEffect> {
-    match scope {
-        Scope::Device => cfg.mode.active_effect.as_ref().and_then(|active| {
-            let params = cfg.mode.params_for_effect(&active.effect_id)?;
-            Some(ResolvedEffect {
+/// Select `effect_id` at device scope with `params` merged in and make every output and
+/// segment inherit it. A running `effect_id` keeps its start time.
+fn fill_device(cfg: &mut DeviceConfig, effect_id: &str, params: &Map<String, Value>) -> Result<(), String> {
+    let started_at = cfg
+        .mode
+        .active_effect
+        .as_ref()
+        .filter(|active| active.effect_id == effect_id)
+        .map_or_else(Instant::now, |active| active.started_at);
+    cfg.mode.set_effect(effect_id, started_at)?;
+    if !params.is_empty() {
+        cfg.mode.merge_params(effect_id, params)?;
+    }
+    force_children_inherit(cfg, Scope::Device);
+    Ok(())
+}
+
+/// Whether `fill_device` left `effect_id` on the whole device and nothing below overrides it.
+fn is_filled_with(cfg: &DeviceConfig, effect_id: &str) -> bool {
+    cfg.mode.selected_effect_id().as_deref() == Some(effect_id)
+        && cfg.outputs.iter().all(|out| {
+            out.mode.selected_effect_id().is_none()
+                && out.segments.iter().all(|seg| seg.mode.selected_effect_id().is_none())
+        })
+}
+
+/// Stop the runner of a device taken out of the manager and disconnect it, blanking it
+/// first with `clear_leds`.
+fn release_device(port: &str, mut md: ManagedDevice, clear_leds: bool) {
+    if let Some(runner) = md.runner.take() {
+        runner.stop();
+    }
+    let mut controller = md.controller.lock().unwrap();
+    if clear_leds {
+        if let Err(err) = controller.clear() {
+            log::warn!(port, err:display = err; "Failed to clear removed device");
+        }
+    }
+    if let Err(err) = controller.disconnect() {
+        log::warn!(port, err:display = err; "Failed to disconnect removed device");
+    }
+}
+
+/// Put `found` in place of the controller of a device that was lost, e.g. a strip that was
+/// unplugged and plugged back in. Outputs resized in the config are resized on the new
+/// handle, and the finished runner is dropped so the next one starts from a clean status.
+fn adopt_controller(md: &mut ManagedDevice, mut found: Box<dyn Controller>) {
+    let counts: Vec<(String, usize)> = md
+        .config
+        .lock()
+        .unwrap()
+        .outputs
+        .iter()
+        .map(|out| (out.id.clone(), out.leds_count))
+        .collect();
+    for def in found.outputs() {
+        let Some(&(_, leds_count)) = counts.iter().find(|(id, _)| *id == def.id) else {
+            continue;
+        };
+        if def.leds_count == leds_count {
+            continue;
+        }
+        if let Err(err) = found.set_output_leds_count(&def.id, leds_count) {
+            log::warn!(
+                output_id = def.id.as_str(),
+                err:display = err;
+                "[reconnect] Failed to restore output size"
+            );
+        }
+    }
+    let defs = found.outputs();
+
+    *md.controller.lock().unwrap() = found;
+    md.config.lock().unwrap().sync_with_output_defs(defs);
+    if let Some(runner) = md.runner.take() {
+        runner.stop();
+    }
+    *md.status.lock().unwrap() = DeviceStatus::default();
+    md.reconnect_at = None;
+    md.reconnect_backoff = RECONNECT_BACKOFF_MIN;
+}
+
+/// Select `effect_id` (with `params` merged in) on every output `filter` matches.
+///
+/// All or nothing: the outputs are changed on a copy, which only replaces `cfg` once every
+/// one succeeded. Returns the ids of the changed outputs.
+fn apply_effect_to_outputs(
+    cfg: &mut DeviceConfig,
+    port: &str,
+    filter: &OutputFilter,
+    effect_id: &str,
+    params: Option<&Map<String, Value>>,
+) -> Result<Vec<String>, String> {
+    let targets: Vec<String> = cfg
+        .outputs
+        .iter()
+        .filter(|out| filter.matches(out))
+        .map(|out| out.id.clone())
+        .collect();
+    if targets.is_empty() {
+        return Err("No outputs match".to_string());
+    }
+
+    let mut next = cfg.clone();
+    let now = Instant::now();
+    for output_id in &targets {
+        let scope = Scope::Output {
+            output_id: output_id.as_str(),
+        };
+        // Keep the animation running on outputs that already show this effect.
+        let started_at = resolve_effect_for_scope(&next, port, scope)
+            .filter(|res| res.effect_id == effect_id)
+            .map_or(now, |res| res.started_at);
+
+        let mode = mode_for_scope_mut(&mut next, scope)?;
+        let applied = mode.set_effect(effect_id, started_at).and_then(|()| match params {
+            Some(params) => mode.merge_params(effect_id, params),
+            None => Ok(()),
+        });
+        applied.map_err(|err| format!("Output '{}': {}", output_id, err))?;
+        force_children_inherit(&mut next, scope);
+    }
+
+    *cfg = next;
+    Ok(targets)
+}
+
+/// Resolve the effective effect for a scope by applying inheritance:
+/// segment -> output -> device.
+fn resolve_effect_for_scope(cfg: &DeviceConfig, port: &str, scope: Scope<'_>) -> Option<ResolvedEffect> {
+    match scope {
+        Scope::Device => cfg.mode.active_effect.as_ref().and_then(|active| {
+            let params = cfg.mode.params_for_effect(&active.effect_id)?;
+            Some(ResolvedEffect {
                 effect_id: active.effect_id.clone(),
                 from: ScopeRef {
                     port: port.to_string(),
@@ -669,6 +1637,9 @@ fn resolve_effect_for_scope(cfg: &DeviceConfig, port: &str, scope: Scope<'_>) ->
                 started_at: active.started_at,
                 params,
                 origin_rev: cfg.mode.rev,
+                overlay: cfg.mode.overlay.clone(),
+                phase_offset: Duration::from_millis(cfg.mode.phase_offset_ms as u64),
+                audio_brightness: cfg.mode.audio_brightness,
             })
         }),
         Scope::Output { output_id } => {
@@ -685,6 +1656,9 @@ fn resolve_effect_for_scope(cfg: &DeviceConfig, port: &str, scope: Scope<'_>) ->
                     started_at: active.started_at,
                     params,
                     origin_rev: out.mode.rev,
+                    overlay: out.mode.overlay.clone(),
+                    phase_offset: Duration::from_millis(out.mode.phase_offset_ms as u64),
+                    audio_brightness: out.mode.audio_brightness,
                 })
             } else {
                 resolve_effect_for_scope(cfg, port, Scope::Device)
@@ -709,6 +1683,9 @@ fn resolve_effect_for_scope(cfg: &DeviceConfig, port: &str, scope: Scope<'_>) ->
                     started_at: active.started_at,
                     params,
                     origin_rev: seg.mode.rev,
+                    overlay: seg.mode.overlay.clone(),
+                    phase_offset: Duration::from_millis(seg.mode.phase_offset_ms as u64),
+                    audio_brightness: seg.mode.audio_brightness,
                 })
             } else {
                 resolve_effect_for_scope(cfg, port, Scope::Output { output_id })
@@ -744,11 +1721,15 @@ impl DeviceConfig {
                 output_type: def.output_type,
                 leds_count: def.leds_count.max(1),
                 matrix: def.matrix,
+                positions: None,
                 capabilities: def.capabilities,
                 brightness: 100,
                 mode: ModeConfig::default(),
                 // Segments are user-defined and only meaningful for linear outputs (future).
                 segments: Vec::new(),
+                soft_start_ms: None,
+                enabled: true,
+                transform: MatrixTransform::default(),
             })
             .collect();
 
@@ -757,11 +1738,37 @@ impl DeviceConfig {
             mode: ModeConfig::default(),
             outputs,
             output_index: HashMap::new(),
+            display_order: Vec::new(),
+            color_vision: None,
+            smoothing: 0.0,
+            power_limit_ma: None,
+            color_order: None,
         };
         cfg.rebuild_output_index();
         cfg
     }
 
+    /// Check that `order` names every output exactly once.
+    fn validate_display_order(&self, order: &[String]) -> Result<(), String> {
+        if order.len() != self.outputs.len() {
+            return Err(format!(
+                "Output order lists {} outputs, device has {}",
+                order.len(),
+                self.outputs.len()
+            ));
+        }
+        let mut seen = HashSet::new();
+        for id in order {
+            if self.output(id).is_none() {
+                return Err(format!("Output '{}' not found", id));
+            }
+            if !seen.insert(id.as_str()) {
+                return Err(format!("Output '{}' is listed twice", id));
+            }
+        }
+        Ok(())
+    }
+
     fn sync_with_output_defs(&mut self, defs: Vec<OutputPortDefinition>) {
         let mut old_by_id: HashMap<String, OutputConfig> =
             self.outputs.drain(..).map(|o| (o.id.clone(), o)).collect();
@@ -782,6 +1789,13 @@ impl DeviceConfig {
                     o.segments.clear();
                 }
 
+                // Coordinates are per LED, so they only survive if the length is unchanged.
+                if o.output_type != SegmentType::Linear
+                    || o.positions.as_ref().is_some_and(|p| p.len() != o.leds_count)
+                {
+                    o.positions = None;
+                }
+
                 // Ensure existing user segments still match the driver's LED count.
                 if o.output_type == SegmentType::Linear && !o.segments.is_empty() {
                     let total = o.segments.iter().map(|s| s.leds_count).sum::<usize>();
@@ -798,10 +1812,14 @@ impl DeviceConfig {
                     output_type: def.output_type,
                     leds_count: def.leds_count.max(1),
                     matrix: def.matrix,
+                    positions: None,
                     capabilities: def.capabilities,
                     brightness: 100,
                     mode: ModeConfig::default(),
                     segments: Vec::new(),
+                    soft_start_ms: None,
+                    enabled: true,
+                    transform: MatrixTransform::default(),
                 }
             };
 
@@ -818,16 +1836,86 @@ impl DeviceConfig {
     }
 }
 
+/// Split a device frame into its outputs, in the runner's physical layout.
+fn frame_snapshot_outputs(cfg: &DeviceConfig, frame: &[Color]) -> BTreeMap<String, Vec<Color>> {
+    let mut offset = 0;
+    cfg.outputs
+        .iter()
+        .map(|out| {
+            let start = offset.min(frame.len());
+            offset = offset.saturating_add(out.leds_count.max(1));
+            (out.id.clone(), frame[start..offset.min(frame.len())].to_vec())
+        })
+        .collect()
+}
+
+/// Reassemble a saved frame for the device's current outputs. Outputs whose length changed
+/// are cut or padded with black; `None` if an output isn't in the snapshot at all.
+fn frame_from_snapshot(cfg: &DeviceConfig, snapshot: &PersistedFrameSnapshot) -> Option<Vec<Color>> {
+    let mut frame = Vec::new();
+    for out in &cfg.outputs {
+        let saved = snapshot.outputs.get(&out.id)?;
+        let len = out.leds_count.max(1);
+        frame.extend(saved.iter().copied().take(len));
+        frame.resize(frame.len() + len.saturating_sub(saved.len()), Color::default());
+    }
+    (!frame.is_empty()).then_some(frame)
+}
+
 struct ManagedDevice {
     controller: ControllerRef,
     config: Arc<Mutex<DeviceConfig>>,
+    status: Arc<Mutex<DeviceStatus>>,
+    /// Delivered frame rate measured by the runner, as `f32` bits.
+    frame_rate: Arc<AtomicU32>,
+    /// Last frame the runner wrote successfully.
+    last_frame: Arc<Mutex<Vec<Color>>>,
+    /// Frame recording in progress, fed by the runner.
+    recorder: Arc<Mutex<Option<FrameRecorder>>>,
     runner: Option<DeviceRunner>,
+    /// Driver that probed the controller, re-probed by the manager when the device is lost.
+    /// `None` for devices the user added.
+    driver: Option<&'static ControllerMetadata>,
+    /// Earliest time the next re-probe for this device may run and the wait after that one,
+    /// while its runner has given up on it.
+    reconnect_at: Option<Instant>,
+    reconnect_backoff: Duration,
     switch_tx: flume::Sender<SwitchEvent>,
     switch_rx: Option<flume::Receiver<SwitchEvent>>,
 }
 
+impl ManagedDevice {
+    fn new(controller: Box<dyn Controller>) -> Self {
+        let controller_ref: ControllerRef = Arc::new(Mutex::new(controller));
+        let output_defs = controller_ref.lock().unwrap().outputs();
+        let config = DeviceConfig::from_output_defs(output_defs);
+        let (switch_tx, switch_rx) = flume::unbounded();
+
+        ManagedDevice {
+            controller: controller_ref,
+            config: Arc::new(Mutex::new(config)),
+            status: Arc::new(Mutex::new(DeviceStatus::default())),
+            frame_rate: Arc::new(AtomicU32::new(0)),
+            last_frame: Arc::new(Mutex::new(Vec::new())),
+            recorder: Arc::new(Mutex::new(None)),
+            runner: None,
+            driver: None,
+            reconnect_at: None,
+            reconnect_backoff: RECONNECT_BACKOFF_MIN,
+            switch_tx,
+            switch_rx: Some(switch_rx),
+        }
+    }
+}
+
 pub struct LightingManager {
     devices: Mutex<HashMap<String, ManagedDevice>>,
+    scenes: Mutex<BTreeMap<String, PersistedScene>>,
+    /// Effects to restore when `toggle_all` turns the setup back on.
+    suspended: Mutex<Option<PersistedScene>>,
+    /// Frames saved before the last restart, keyed by device identity, until each device
+    /// is found.
+    pending_frames: Mutex<HashMap<String, PersistedFrameSnapshot>>,
 }
 
 impl Default for LightingManager {
@@ -840,37 +1928,309 @@ impl LightingManager {
     pub fn new() -> Self {
         Self {
             devices: Mutex::new(HashMap::new()),
+            scenes: Mutex::new(BTreeMap::new()),
+            suspended: Mutex::new(None),
+            pending_frames: Mutex::new(HashMap::new()),
         }
     }
 
     /// Probe hardware and merge newly discovered controllers into the manager.
-    pub fn scan_devices(&self) -> Vec<Device> {
-        let found = scan_controllers();
+    ///
+    /// Drivers are probed in parallel and each device is reported through `on_device` as soon
+    /// as it is registered, instead of waiting for the slowest probe.
+    ///
+    /// `on_device` runs on probe threads without the device map locked, so it may call back
+    /// into the manager.
+    pub fn scan_devices<F>(&self, on_device: F) -> Vec<Device>
+    where
+        F: Fn(Device) + Sync,
+    {
+        self.scan_with(|on_found| scan_controllers_with(on_found), on_device);
+        self.get_devices()
+    }
+
+    /// [`Self::scan_devices`] over the probes `scan` runs.
+    fn scan_with<S, F>(&self, scan: S, on_device: F)
+    where
+        S: FnOnce(&(dyn Fn(FoundController) + Sync)),
+        F: Fn(Device) + Sync,
+    {
+        scan(&|found| {
+            if let Some(old_port) = &found.replaces {
+                self.retire_device(old_port);
+            }
+            // A device that came back under a new port is re-keyed by the reconnect check,
+            // which keeps its config; registering it here would manage it twice.
+            if self.managed_elsewhere(found.controller.as_ref()).is_some() {
+                return;
+            }
+            on_device(self.register_controller(found.controller, Some(found.driver)));
+        });
+
+        // Always sync output definitions after scan, in case a driver changed its capabilities.
+        self.sync_all_output_defs();
+    }
+
+    /// Run only the probe of the controller driver called `name`, e.g. the USB serial one
+    /// right after a strip is plugged in, without waiting for mDNS and the other probes.
+    /// Devices other drivers found earlier are kept. Returns every device afterwards.
+    pub fn rescan_controller_kind<F>(&self, name: &str, on_device: F) -> Result<Vec<Device>, String>
+    where
+        F: Fn(Device) + Sync,
+    {
+        let driver = list_controller_drivers()
+            .into_iter()
+            .find(|driver| driver.name == name)
+            .ok_or_else(|| format!("Unknown controller driver: {}", name))?;
+        self.rescan_driver(driver, on_device);
+        Ok(self.get_devices())
+    }
+
+    fn rescan_driver<F>(&self, driver: &'static ControllerMetadata, on_device: F)
+    where
+        F: Fn(Device) + Sync,
+    {
+        // The other drivers aren't probed, so their devices can't take part in the scan's
+        // own dedup; a device already managed under another port keeps its driver.
+        self.scan_with(|on_found| scan_drivers_with(std::iter::once(driver), on_found), on_device);
+    }
+
+    /// Port of the entry that already manages the device `controller` drives, if it isn't
+    /// the controller's own port.
+    fn managed_elsewhere(&self, controller: &dyn Controller) -> Option<String> {
+        let port = controller.port_name();
+        let identity = device_identity(&controller.serial_id(), &port);
+        let devices = self.devices.lock().unwrap();
+        let (existing, _) = devices.iter().find(|(existing, md)| {
+            **existing != port
+                && device_identity(&md.controller.lock().unwrap().serial_id(), existing) == identity
+        })?;
+        log::info!(
+            port = port.as_str(),
+            existing = existing.as_str();
+            "[scan] Device is already managed under another port"
+        );
+        Some(existing.clone())
+    }
+
+    /// Add a probed controller unless its port is already managed, and return its DTO.
+    ///
+    /// A newly added device is sent the frame it showed before the restart, if one is pending.
+    fn register_controller(
+        &self,
+        controller: Box<dyn Controller>,
+        driver: Option<&'static ControllerMetadata>,
+    ) -> Device {
+        use std::collections::hash_map::Entry;
+
+        let port = controller.port_name();
+        let identity = device_identity(&controller.serial_id(), &port);
+        let mut devices = self.devices.lock().unwrap();
+        let (dto, resend) = match devices.entry(port.clone()) {
+            Entry::Occupied(entry) => (self.build_device_dto(&port, entry.get()), None),
+            Entry::Vacant(entry) => {
+                let md = entry.insert(ManagedDevice::new(controller));
+                md.driver = driver;
+                let resend = self
+                    .take_pending_frame(&port, &identity, md)
+                    .map(|frame| (md.controller.clone(), frame));
+                (self.build_device_dto(&port, md), resend)
+            }
+        };
+        drop(devices);
+
+        // Written without the devices lock; a slow transport mustn't hold up the others.
+        if let Some((controller, frame)) = resend {
+            if let Err(err) = controller.lock().unwrap().update(&frame) {
+                log::warn!(port = port.as_str(), err:display = err; "[frames] Failed to re-send saved frame");
+            }
+        }
+        dto
+    }
+
+    /// Add a controller no probe can find, e.g. a node the user typed in. Fails if its port
+    /// is already managed.
+    pub fn add_device(&self, controller: Box<dyn Controller>) -> Result<Device, String> {
+        let port = controller.port_name();
+        if self.devices.lock().unwrap().contains_key(&port) {
+            return Err(format!("Device '{}' is already added", port));
+        }
+        Ok(self.register_controller(controller, None))
+    }
+
+    /// The saved frame for a newly added device, if one is pending and still recent.
+    fn take_pending_frame(&self, port: &str, identity: &str, md: &ManagedDevice) -> Option<Vec<Color>> {
+        let snapshot = self.pending_frames.lock().unwrap().remove(identity)?;
+        let age = Duration::from_millis(unix_time_ms().saturating_sub(snapshot.saved_at_ms));
+        if age > FRAME_SNAPSHOT_MAX_AGE {
+            return None;
+        }
+        let frame = frame_from_snapshot(&md.config.lock().unwrap(), &snapshot);
+        if frame.is_none() {
+            log::debug!(port; "[frames] Outputs changed since the frame was saved, not re-sending");
+        }
+        frame
+    }
+
+    /// Re-probe the drivers of devices whose runner gave up on them, and put a controller
+    /// found for the same device in place of the dead one. Returns the devices that are back;
+    /// the caller restarts their runners with [`runner::ensure_runner_state`].
+    ///
+    /// Each device waits an exponentially growing time between attempts, and the probe goes
+    /// through the same dedup and driver preferences as a scan.
+    pub fn reconnect_lost_devices(&self) -> Vec<ReconnectedDevice> {
+        let now = Instant::now();
+        let mut lost: HashMap<&'static str, (&'static ControllerMetadata, Vec<LostDevice>)> =
+            HashMap::new();
         {
             let mut devices = self.devices.lock().unwrap();
-            for controller in found {
-                let port = controller.port_name();
-                devices.entry(port.clone()).or_insert_with(|| {
-                    let controller_ref: ControllerRef = Arc::new(Mutex::new(controller));
-                    let output_defs = controller_ref.lock().unwrap().outputs();
-                    let config = DeviceConfig::from_output_defs(output_defs);
-                    let (switch_tx, switch_rx) = flume::unbounded();
-
-                    ManagedDevice {
-                        controller: controller_ref,
-                        config: Arc::new(Mutex::new(config)),
-                        runner: None,
-                        switch_tx,
-                        switch_rx: Some(switch_rx),
-                    }
-                });
+            for (port, md) in devices.iter_mut() {
+                let Some(driver) = md.driver else {
+                    continue;
+                };
+                if !md.runner.as_ref().is_some_and(DeviceRunner::is_finished) {
+                    continue;
+                }
+                let due = md.reconnect_at.get_or_insert(now + md.reconnect_backoff);
+                if *due > now {
+                    continue;
+                }
+                md.reconnect_backoff = (md.reconnect_backoff * 2).min(RECONNECT_BACKOFF_MAX);
+                *due = now + md.reconnect_backoff;
+
+                let identity = device_identity(&md.controller.lock().unwrap().serial_id(), port);
+                lost.entry(driver.name)
+                    .or_insert_with(|| (driver, Vec::new()))
+                    .1
+                    .push((port.clone(), identity));
             }
         }
 
-        // Always sync output definitions after scan, in case a driver changed its capabilities.
-        self.sync_all_output_defs();
+        lost.into_values()
+            .flat_map(|(driver, lost)| self.reconnect_with_driver(driver, &lost))
+            .collect()
+    }
 
-        self.get_devices()
+    /// Probe `driver` once for the devices in `lost`. Devices are matched by
+    /// identity, so one that comes back under another port is found too and moved to that
+    /// port; everything else the probe reports is dropped.
+    fn reconnect_with_driver(
+        &self,
+        driver: &'static ControllerMetadata,
+        lost: &[LostDevice],
+    ) -> Vec<ReconnectedDevice> {
+        let preferences = inventory::preferred_drivers();
+        let reconnected = Mutex::new(Vec::new());
+        scan_drivers_with(std::iter::once(driver), |found| {
+            let serial = found.controller.serial_id();
+            let identity = device_identity(&serial, &found.controller.port_name());
+            let Some((port, _)) = lost.iter().find(|(_, lost)| *lost == identity) else {
+                return;
+            };
+            if preferences.get(serial.trim()).is_some_and(|preferred| preferred != driver.name) {
+                // The user wants another driver for it; the next scan finds it there.
+                return;
+            }
+
+            let mut devices = self.devices.lock().unwrap();
+            let new_port = found.controller.port_name();
+            if new_port != *port && devices.contains_key(&new_port) {
+                log::warn!(
+                    port = port.as_str(),
+                    new_port = new_port.as_str();
+                    "[reconnect] Device is back under a port another device uses"
+                );
+                return;
+            }
+            let Some(mut md) = devices.remove(port) else {
+                return;
+            };
+            adopt_controller(&mut md, found.controller);
+            devices.insert(new_port.clone(), md);
+            log::info!(port = new_port.as_str(), driver = driver.name; "[reconnect] Device is back");
+            reconnected.lock().unwrap().push(ReconnectedDevice {
+                previous_port: (new_port != *port).then(|| port.clone()),
+                port: new_port,
+            });
+        });
+        reconnected.into_inner().unwrap()
+    }
+
+    /// Drop a device that a more specific driver now represents.
+    fn retire_device(&self, port: &str) {
+        let Some(md) = self.devices.lock().unwrap().remove(port) else {
+            return;
+        };
+        // The more specific driver takes over the LEDs right away.
+        release_device(port, md, false);
+        log::info!(port; "[scan] Device replaced by a more specific driver");
+    }
+
+    /// Stop driving a device added with [`Self::add_device`] and disconnect it.
+    pub fn remove_device(&self, port: &str) -> Result<(), String> {
+        let md = self
+            .devices
+            .lock()
+            .unwrap()
+            .remove(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+        release_device(port, md, true);
+        log::info!(port; "[device] Device removed");
+        Ok(())
+    }
+
+    /// Stop every runner and release all devices, e.g. when the app exits.
+    ///
+    /// With `clear_leds` set, each controller is blanked before `disconnect()`. Devices are
+    /// handled in parallel; any that haven't finished within `timeout` are logged and left
+    /// behind so a hung transport cannot block exit.
+    pub fn shutdown_all(&self, clear_leds: bool, timeout: Duration) {
+        let drained: Vec<(String, ManagedDevice)> = self.devices.lock().unwrap().drain().collect();
+        if drained.is_empty() {
+            return;
+        }
+
+        let (done_tx, done_rx) = flume::unbounded::<String>();
+        let mut pending: HashSet<String> = HashSet::new();
+
+        for (port, mut md) in drained {
+            pending.insert(port.clone());
+            let done_tx = done_tx.clone();
+            std::thread::spawn(move || {
+                if let Some(runner) = md.runner.take() {
+                    runner.stop();
+                }
+
+                let mut controller = md.controller.lock().unwrap();
+                if clear_leds {
+                    if let Err(err) = controller.clear() {
+                        log::warn!(port = port.as_str(), err:display = err; "[shutdown] Failed to clear device");
+                    }
+                }
+                if let Err(err) = controller.disconnect() {
+                    log::warn!(port = port.as_str(), err:display = err; "[shutdown] Failed to disconnect device");
+                }
+                drop(controller);
+
+                let _ = done_tx.send(port);
+            });
+        }
+        drop(done_tx);
+
+        let deadline = Instant::now() + timeout;
+        while !pending.is_empty() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match done_rx.recv_timeout(remaining) {
+                Ok(port) => {
+                    pending.remove(&port);
+                }
+                Err(_) => break,
+            }
+        }
+
+        for port in &pending {
+            log::warn!(port = port.as_str(), timeout_ms = timeout.as_millis() as u64; "[shutdown] Device did not shut down in time");
+        }
     }
 
     /// Return current devices without probing.
@@ -944,6 +2304,38 @@ impl LightingManager {
         Ok(())
     }
 
+    /// Fill the whole device with `color`: `monochrome` at device scope, inherited by every
+    /// output and segment. Returns the device as it ends up; the caller starts its runner
+    /// with [`runner::ensure_runner_state`].
+    pub fn set_device_solid_color(&self, port: &str, color: Color) -> Result<Device, String> {
+        let mut params = Map::new();
+        params.insert(
+            "color".to_string(),
+            Value::String(format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)),
+        );
+        self.fill_device_by_port(port, MONOCHROME_EFFECT_ID, &params)
+    }
+
+    /// Turn the whole device off: `turn_off` at device scope, inherited by every output and
+    /// segment. Returns the device as it ends up.
+    pub fn clear_device(&self, port: &str) -> Result<Device, String> {
+        self.fill_device_by_port(port, TURN_OFF_EFFECT_ID, &Map::new())
+    }
+
+    fn fill_device_by_port(
+        &self,
+        port: &str,
+        effect_id: &str,
+        params: &Map<String, Value>,
+    ) -> Result<Device, String> {
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+        fill_device(&mut md.config.lock().unwrap(), effect_id, params)?;
+        Ok(self.build_device_dto(port, md))
+    }
+
     pub fn set_scope_effect_wait_ready(
         &self,
         port: &str,
@@ -1023,6 +2415,8 @@ impl LightingManager {
                 }
             }
 
+            // Disabled outputs render nothing, so there is no ready signal to wait for.
+            targets.retain(|t| cfg.output(&t.output_id).is_some_and(|out| out.enabled));
             Ok(targets)
         }
 
@@ -1266,7 +2660,7 @@ impl LightingManager {
             if let Some(active) = &mode.active_effect {
                 return Ok(active.effect_id.clone());
             }
-            mode.set_effect(&resolved.effect_id, resolved.started_at)?;
+            mode.adopt(&resolved)?;
             Ok(resolved.effect_id.clone())
         };
 
@@ -1284,6 +2678,147 @@ impl LightingManager {
         Ok(())
     }
 
+    /// Select one effect on every output of a device that `filter` matches, e.g. all the
+    /// identical channels of a multi-channel controller. Nothing changes if any output fails.
+    pub fn apply_effect_to_outputs(
+        &self,
+        port: &str,
+        filter: &OutputFilter,
+        effect_id: &str,
+        params: Option<Value>,
+    ) -> Result<Vec<String>, String> {
+        let params = match &params {
+            Some(Value::Object(map)) => Some(map),
+            Some(_) => return Err("Params must be a JSON object".to_string()),
+            None => None,
+        };
+
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        let mut cfg = md.config.lock().unwrap();
+        apply_effect_to_outputs(&mut cfg, port, filter, effect_id, params)
+    }
+
+    /// Set (or with `None`, remove) the overlay effect of a scope.
+    ///
+    /// An inheriting scope is first made explicit with its current effect, so the overlay
+    /// doesn't spill over to sibling scopes that inherit from the same parent.
+    pub fn set_scope_overlay(
+        &self,
+        port: &str,
+        output_id: Option<&str>,
+        segment_id: Option<&str>,
+        overlay: Option<OverlaySettings>,
+    ) -> Result<(), String> {
+        let scope = Scope::from_options(output_id, segment_id)?;
+
+        let mut devices = self.devices.lock().unwrap();
+        let md = devices
+            .get_mut(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        let mut cfg = md.config.lock().unwrap();
+
+        let Some(overlay) = overlay else {
+            mode_for_scope_mut(&mut cfg, scope)?.clear_overlay();
+            return Ok(());
+        };
+
+        let resolved = resolve_effect_for_scope(&cfg, port, scope)
+            .ok_or_else(|| "No active effect in this scope hierarchy".to_string())?;
+        let mode = mode_for_scope_mut(&mut cfg, scope)?;
+        if mode.active_effect.is_none() {
+            mode.adopt(&resolved)?;
+        }
+        mode.set_overlay(
+            &overlay.effect_id,
+            overlay.opacity,
+            overlay.blend,
+            Instant::now(),
+        )
+    }
+
+    /// Shift a scope's effect on the shared animation clock, e.g. to stagger several
+    /// devices running the same effect. Inheriting scopes are made explicit first.
+    pub fn set_scope_phase_offset(
+        &self,
+        port: &str,
+        output_id: Option<&str>,
+        segment_id: Option<&str>,
+        offset_ms: u32,
+    ) -> Result<(), String> {
+        let scope = Scope::from_options(output_id, segment_id)?;
+
+        let mut devices = self.devices.lock().unwrap();
+        let md = devices
+            .get_mut(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        let mut cfg = md.config.lock().unwrap();
+        let resolved = resolve_effect_for_scope(&cfg, port, scope)
+            .ok_or_else(|| "No active effect in this scope hierarchy".to_string())?;
+        let mode = mode_for_scope_mut(&mut cfg, scope)?;
+        if mode.active_effect.is_none() {
+            mode.adopt(&resolved)?;
+        }
+        mode.phase_offset_ms = offset_ms.min(MAX_PHASE_OFFSET_MS);
+        mode.rev = mode.rev.wrapping_add(1);
+        Ok(())
+    }
+
+    /// Modulate a scope's brightness with audio loudness; `None` turns it off. Inheriting
+    /// scopes are made explicit first.
+    pub fn set_scope_audio_brightness(
+        &self,
+        port: &str,
+        output_id: Option<&str>,
+        segment_id: Option<&str>,
+        settings: Option<AudioBrightnessSettings>,
+    ) -> Result<(), String> {
+        let scope = Scope::from_options(output_id, segment_id)?;
+
+        let mut devices = self.devices.lock().unwrap();
+        let md = devices
+            .get_mut(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        let mut cfg = md.config.lock().unwrap();
+        let resolved = resolve_effect_for_scope(&cfg, port, scope)
+            .ok_or_else(|| "No active effect in this scope hierarchy".to_string())?;
+        let mode = mode_for_scope_mut(&mut cfg, scope)?;
+        if mode.active_effect.is_none() {
+            mode.adopt(&resolved)?;
+        }
+        mode.audio_brightness = settings.map(AudioBrightnessSettings::normalized);
+        mode.rev = mode.rev.wrapping_add(1);
+        Ok(())
+    }
+
+    pub fn update_scope_overlay_params(
+        &self,
+        port: &str,
+        output_id: Option<&str>,
+        segment_id: Option<&str>,
+        params: Value,
+    ) -> Result<(), String> {
+        let scope = Scope::from_options(output_id, segment_id)?;
+
+        let params_obj = params
+            .as_object()
+            .ok_or_else(|| "Params must be a JSON object".to_string())?;
+
+        let mut devices = self.devices.lock().unwrap();
+        let md = devices
+            .get_mut(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        let mut cfg = md.config.lock().unwrap();
+        mode_for_scope_mut(&mut cfg, scope)?.merge_overlay_params(params_obj)
+    }
+
     pub fn set_scope_brightness(
         &self,
         port: &str,
@@ -1308,6 +2843,16 @@ impl LightingManager {
 
         let target = brightness_for_scope_mut(&mut cfg, scope)?;
         *target = brightness;
+        drop(cfg);
+
+        // A running runner routes brightness itself on its next tick; an idle device has no
+        // runner to do it, so the change goes straight to controllers that dim in hardware.
+        if matches!(scope, Scope::Device) && md.runner.is_none() {
+            let mut controller = md.controller.lock().unwrap();
+            if controller.has_hardware_brightness() {
+                controller.set_brightness(brightness.min(100))?;
+            }
+        }
         Ok(())
     }
 
@@ -1348,6 +2893,287 @@ impl LightingManager {
         Ok(())
     }
 
+    /// Put an output's segments in `new_order` (every segment id once). Each segment keeps
+    /// its effect, brightness and direction; only where its LEDs start changes.
+    pub fn reorder_segments(
+        &self,
+        port: &str,
+        output_id: &str,
+        new_order: Vec<String>,
+    ) -> Result<(), String> {
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        let mut cfg = md.config.lock().unwrap();
+        let out = cfg
+            .output_mut(output_id)
+            .ok_or_else(|| format!("Output '{}' not found", output_id))?;
+
+        if !out.capabilities.editable {
+            return Err(format!("Output '{}' is not editable", output_id));
+        }
+        if new_order.len() != out.segments.len() {
+            return Err(format!(
+                "Segment order lists {} segments, output '{}' has {}",
+                new_order.len(),
+                output_id,
+                out.segments.len()
+            ));
+        }
+
+        let mut by_id: HashMap<String, SegmentConfig> =
+            out.segments.iter().map(|s| (s.id.clone(), s.clone())).collect();
+        let mut reordered = Vec::with_capacity(new_order.len());
+        for id in &new_order {
+            let Some(seg) = by_id.remove(id) else {
+                return Err(if out.segments.iter().any(|s| &s.id == id) {
+                    format!("Segment '{}' is listed twice", id)
+                } else {
+                    format!("Segment '{}' not found", id)
+                });
+            };
+            reordered.push(seg);
+        }
+        out.segments = reordered;
+        Ok(())
+    }
+
+    pub fn set_output_leds_count(
+        &self,
+        port: &str,
+        output_id: &str,
+        leds_count: usize,
+    ) -> Result<(), String> {
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        apply_output_leds_count(md, output_id, leds_count)
+    }
+
+    /// Set or clear (`None`) the physical coordinates of every LED on a linear output.
+    pub fn set_output_led_positions(
+        &self,
+        port: &str,
+        output_id: &str,
+        positions: Option<Vec<LedPosition>>,
+    ) -> Result<(), String> {
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        let mut cfg = md.config.lock().unwrap();
+        let out = cfg
+            .output_mut(output_id)
+            .ok_or_else(|| format!("Output '{}' not found", output_id))?;
+        if let Some(positions) = &positions {
+            validate_led_positions(out, positions)?;
+        }
+        out.positions = positions;
+        Ok(())
+    }
+
+    /// Override (or with `None`, stop overriding) the soft-start ramp for one output.
+    pub fn set_output_soft_start(
+        &self,
+        port: &str,
+        output_id: &str,
+        soft_start_ms: Option<u32>,
+    ) -> Result<(), String> {
+        if soft_start_ms.is_some_and(|ms| ms > MAX_SOFT_START_MS) {
+            return Err(format!(
+                "Soft-start must be at most {} ms",
+                MAX_SOFT_START_MS
+            ));
+        }
+
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        let mut cfg = md.config.lock().unwrap();
+        let out = cfg
+            .output_mut(output_id)
+            .ok_or_else(|| format!("Output '{}' not found", output_id))?;
+        out.soft_start_ms = soft_start_ms;
+        Ok(())
+    }
+
+    /// Correct the orientation of a matrix output mounted rotated (clockwise) and/or
+    /// mirrored. Effects see the output with the transform applied.
+    pub fn set_output_transform(
+        &self,
+        port: &str,
+        output_id: &str,
+        rotation: Rotation,
+        flip_h: bool,
+        flip_v: bool,
+    ) -> Result<(), String> {
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        let mut cfg = md.config.lock().unwrap();
+        let out = cfg
+            .output_mut(output_id)
+            .ok_or_else(|| format!("Output '{}' not found", output_id))?;
+        if out.matrix.is_none() {
+            return Err(format!("Output '{}' has no matrix layout", output_id));
+        }
+        out.transform = MatrixTransform {
+            rotation,
+            flip_h,
+            flip_v,
+        };
+        Ok(())
+    }
+
+    /// Turn one output off (black) or back on, keeping its effects and layout. The runner
+    /// stops (on the next [`runner::ensure_runner_state`]) once every output with an effect
+    /// is disabled.
+    pub fn set_output_enabled(&self, port: &str, output_id: &str, enabled: bool) -> Result<(), String> {
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        let mut cfg = md.config.lock().unwrap();
+        let out = cfg
+            .output_mut(output_id)
+            .ok_or_else(|| format!("Output '{}' not found", output_id))?;
+        out.enabled = enabled;
+        Ok(())
+    }
+
+    /// Set the order this device's outputs are listed in. Cosmetic only: it doesn't change
+    /// which LEDs an output drives.
+    pub fn set_output_order(&self, port: &str, order: Vec<String>) -> Result<(), String> {
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        let mut cfg = md.config.lock().unwrap();
+        cfg.validate_display_order(&order)?;
+        cfg.display_order = order;
+        Ok(())
+    }
+
+    /// Override (or with `None`, stop overriding) the color-blindness correction for one
+    /// device. The runner picks it up on its next frame.
+    pub fn set_device_color_vision(
+        &self,
+        port: &str,
+        mode: Option<ColorVisionMode>,
+    ) -> Result<(), String> {
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        md.config.lock().unwrap().color_vision = mode;
+        Ok(())
+    }
+
+    /// Blend each frame sent to a device with the one before: `smoothing` is the share of
+    /// the previous frame kept per 60 Hz frame, 0 turns it off. The runner picks it up on
+    /// its next frame.
+    pub fn set_device_smoothing(&self, port: &str, smoothing: f32) -> Result<(), String> {
+        if !(0.0..=MAX_SMOOTHING).contains(&smoothing) {
+            return Err(format!("Smoothing must be between 0 and {}", MAX_SMOOTHING));
+        }
+
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        md.config.lock().unwrap().smoothing = smoothing;
+        Ok(())
+    }
+
+    /// Cap the current a device's LEDs may draw, on top of any budget its driver reports;
+    /// `None` removes the cap. The runner picks it up on its next frame.
+    pub fn set_device_power_limit(
+        &self,
+        port: &str,
+        max_milliamps: Option<u32>,
+    ) -> Result<(), String> {
+        if max_milliamps == Some(0) {
+            return Err("Power limit must be above 0 mA".to_string());
+        }
+
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        md.config.lock().unwrap().power_limit_ma = max_milliamps;
+        Ok(())
+    }
+
+    /// Tell the driver how a device's LEDs are wired, for strips whose channel order or
+    /// white channel the driver can't detect.
+    pub fn set_device_color_order(&self, port: &str, order: ColorOrder) -> Result<(), String> {
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        md.controller.lock().unwrap().set_color_order(order)?;
+        md.config.lock().unwrap().color_order = Some(order);
+        Ok(())
+    }
+
+    /// Start recording the frames written to a device into `dir`, replacing a recording
+    /// already in progress. Returns the file the first frames go to.
+    pub fn start_frame_recording(&self, port: &str, dir: &Path) -> Result<PathBuf, String> {
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        let outputs = md
+            .config
+            .lock()
+            .unwrap()
+            .outputs
+            .iter()
+            .map(|out| RecordedOutput {
+                id: out.id.clone(),
+                leds_count: out.leds_count.max(1),
+            })
+            .collect();
+        let name = format!("{}-{}", md.controller.lock().unwrap().serial_id(), unix_time_ms());
+        let recorder = FrameRecorder::create(dir, &name, outputs, Instant::now())
+            .map_err(|e| format!("Failed to start frame recording in '{}': {}", dir.display(), e))?;
+        let path = recorder.path();
+        *md.recorder.lock().unwrap() = Some(recorder);
+        Ok(path)
+    }
+
+    /// Stop recording a device's frames. Returns the last file written, if it was recording.
+    pub fn stop_frame_recording(&self, port: &str) -> Result<Option<PathBuf>, String> {
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+
+        let Some(mut recorder) = md.recorder.lock().unwrap().take() else {
+            return Ok(None);
+        };
+        recorder
+            .flush()
+            .map_err(|e| format!("Failed to finish frame recording: {}", e))?;
+        Ok(Some(recorder.path()))
+    }
+
     /// Export a device config snapshot for persistence.
     /// Returns `(device_id, config)` where `device_id` is the controller serial id.
     pub fn export_persisted_device_config(
@@ -1363,11 +3189,20 @@ impl LightingManager {
         let cfg = md.config.lock().unwrap();
 
         let mut layout: HashMap<String, PersistedOutputLayout> = HashMap::new();
-        let mut outputs: Vec<PersistedOutputEffectsConfig> = Vec::with_capacity(cfg.outputs.len());
-
         for out in &cfg.outputs {
-            // Layout: persist only if user-defined segments exist.
-            if !out.segments.is_empty() {
+            // Layout: persist only if user-defined segments, a user-chosen length, LED
+            // coordinates, a soft-start override, a disabled flag or a transform exist.
+            let leds_count = (out.output_type == SegmentType::Linear
+                && out.capabilities.editable
+                && out.capabilities.min_total_leds != out.capabilities.max_total_leds)
+                .then_some(out.leds_count);
+            if !out.segments.is_empty()
+                || leds_count.is_some()
+                || out.positions.is_some()
+                || out.soft_start_ms.is_some()
+                || !out.enabled
+                || !out.transform.is_identity()
+            {
                 let segments = out
                     .segments
                     .iter()
@@ -1377,45 +3212,22 @@ impl LightingManager {
                         segment_type: s.segment_type,
                         leds_count: s.leds_count,
                         matrix: s.matrix.clone(),
+                        reversed: s.reversed,
                     })
                     .collect::<Vec<_>>();
 
-                layout.insert(out.id.clone(), PersistedOutputLayout { segments });
-            }
-
-            // Effects: persist mode state for each scope.
-            let segments = out
-                .segments
-                .iter()
-                .map(|s| PersistedSegmentEffectsConfig {
-                    id: s.id.clone(),
-                    brightness: {
-                        let explicit = s.mode.selected_effect_id().is_some();
-                        if explicit || s.brightness != 100 {
-                            Some(s.brightness)
-                        } else {
-                            None
-                        }
+                layout.insert(
+                    out.id.clone(),
+                    PersistedOutputLayout {
+                        leds_count,
+                        segments,
+                        positions: out.positions.clone(),
+                        soft_start_ms: out.soft_start_ms,
+                        enabled: (!out.enabled).then_some(false),
+                        transform: (!out.transform.is_identity()).then_some(out.transform),
                     },
-                    selected: s.mode.selected_effect_id(),
-                    params: s.mode.params_by_effect.clone(),
-                })
-                .collect::<Vec<_>>();
-
-            outputs.push(PersistedOutputEffectsConfig {
-                id: out.id.clone(),
-                brightness: {
-                    let explicit = out.mode.selected_effect_id().is_some();
-                    if explicit || out.brightness != 100 {
-                        Some(out.brightness)
-                    } else {
-                        None
-                    }
-                },
-                selected: out.mode.selected_effect_id(),
-                params: out.mode.params_by_effect.clone(),
-                segments,
-            });
+                );
+            }
         }
 
         Ok((
@@ -1423,17 +3235,50 @@ impl LightingManager {
             PersistedDeviceConfig {
                 device: PersistedDeviceSection {
                     layout,
+                    output_order: cfg.display_order.clone(),
+                    color_vision: cfg.color_vision,
+                    smoothing: (cfg.smoothing > 0.0).then_some(cfg.smoothing),
+                    power_limit_ma: cfg.power_limit_ma,
+                    color_order: cfg.color_order,
                 },
-                effects: PersistedEffectsSection {
-                    selected: cfg.mode.selected_effect_id(),
-                    params: cfg.mode.params_by_effect.clone(),
-                    brightness: cfg.brightness,
-                    outputs,
-                },
+                effects: export_persisted_effects(&cfg),
             },
         ))
     }
 
+    /// A device's segments, transforms, effects and params as a versioned JSON document,
+    /// for sharing with someone running the same hardware.
+    pub fn export_layout(&self, port: &str) -> Result<Value, String> {
+        let model = {
+            let devices = self.devices.lock().unwrap();
+            let md = devices
+                .get(port)
+                .ok_or_else(|| "Device not found".to_string())?;
+            let model = md.controller.lock().unwrap().model();
+            model
+        };
+        let (_, config) = self.export_persisted_device_config(port)?;
+
+        serde_json::to_value(LayoutDocument {
+            version: LAYOUT_VERSION,
+            model,
+            config,
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// Replace a device's setup with a document from `export_layout`. Fails without changing
+    /// anything if the layout doesn't fit the device's outputs.
+    pub fn import_layout(&self, port: &str, document: Value) -> Result<(), String> {
+        let config = parse_layout_document(document)?;
+
+        let devices = self.devices.lock().unwrap();
+        let md = devices
+            .get(port)
+            .ok_or_else(|| "Device not found".to_string())?;
+        apply_layout(md, &config)
+    }
+
     /// Apply a persisted device config to a live device instance.
     ///
     /// Best-effort: unknown outputs/segments are ignored; invalid segments are skipped.
@@ -1443,16 +3288,50 @@ impl LightingManager {
         persisted: &PersistedDeviceConfig,
         app_handle: AppHandle,
     ) -> Result<(), String> {
+        self.restore_persisted_device_config(port, persisted)?;
+        // Ensure runner state matches restored modes.
         let mut devices = self.devices.lock().unwrap();
+        self.ensure_runner_state_locked(&mut devices, port, app_handle)
+    }
+
+    /// The config half of [`Self::apply_persisted_device_config`], which leaves the runner
+    /// alone.
+    fn restore_persisted_device_config(
+        &self,
+        port: &str,
+        persisted: &PersistedDeviceConfig,
+    ) -> Result<(), String> {
+        let devices = self.devices.lock().unwrap();
         let md = devices
-            .get_mut(port)
+            .get(port)
             .ok_or_else(|| "Device not found".to_string())?;
 
+        // 0) Restore user-configured output lengths so persisted segments validate against them.
+        for (output_id, layout) in &persisted.device.layout {
+            if let Some(leds_count) = layout.leds_count {
+                if let Err(err) = apply_output_leds_count(md, output_id, leds_count) {
+                    log::warn!(
+                        port,
+                        output = output_id.as_str(),
+                        err:display = err;
+                        "[config] Skip invalid persisted LED count"
+                    );
+                }
+            }
+        }
+        let color_order = persisted.device.color_order.filter(|&order| {
+            match md.controller.lock().unwrap().set_color_order(order) {
+                Ok(()) => true,
+                Err(err) => {
+                    log::warn!(port, err:display = err; "[config] Skip persisted color order");
+                    false
+                }
+            }
+        });
+
         {
             let mut cfg = md.config.lock().unwrap();
 
-            cfg.brightness = persisted.effects.brightness;
-
             // 1) Apply layout first so segments exist before applying segment modes.
             for (output_id, layout) in &persisted.device.layout {
                 let Some(out) = cfg.output_mut(output_id) else {
@@ -1475,56 +3354,376 @@ impl LightingManager {
                         );
                     }
                 }
+
+                if let Some(positions) = &layout.positions {
+                    match validate_led_positions(out, positions) {
+                        Ok(()) => out.positions = Some(positions.clone()),
+                        Err(err) => log::warn!(
+                            port,
+                            output = output_id.as_str(),
+                            err:display = err;
+                            "[config] Skip invalid persisted LED positions"
+                        ),
+                    }
+                }
+
+                if let Some(ms) = layout.soft_start_ms {
+                    out.soft_start_ms = Some(ms.min(MAX_SOFT_START_MS));
+                }
+                out.enabled = layout.enabled.unwrap_or(true);
+                if out.matrix.is_some() {
+                    out.transform = layout.transform.unwrap_or_default();
+                }
+            }
+
+            if !persisted.device.output_order.is_empty() {
+                match cfg.validate_display_order(&persisted.device.output_order) {
+                    Ok(()) => cfg.display_order = persisted.device.output_order.clone(),
+                    Err(err) => log::warn!(
+                        port,
+                        err:display = err;
+                        "[config] Skip stale persisted output order"
+                    ),
+                }
+            }
+            cfg.color_vision = persisted.device.color_vision;
+            cfg.smoothing = persisted
+                .device
+                .smoothing
+                .filter(|s| s.is_finite())
+                .map_or(0.0, |s| s.clamp(0.0, MAX_SMOOTHING));
+            cfg.power_limit_ma = persisted.device.power_limit_ma.filter(|&ma| ma > 0);
+            cfg.color_order = color_order;
+
+            // 2) Apply device, output and segment effects.
+            apply_persisted_effects(&mut cfg, &persisted.effects)?;
+        }
+        Ok(())
+    }
+
+    // =========================================================================
+    // Scenes
+    // =========================================================================
+
+    /// Capture the effect selections of every connected device under `name`, replacing any
+    /// scene with the same name.
+    pub fn save_scene(&self, name: &str) -> Result<(), String> {
+        let name = normalize_scene_name(name)?;
+        let scene = self.snapshot_scene();
+        self.scenes.lock().unwrap().insert(name, scene);
+        Ok(())
+    }
+
+    pub fn list_scenes(&self) -> Vec<SceneSummary> {
+        self.scenes
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, scene)| SceneSummary {
+                name: name.clone(),
+                device_ids: scene.devices.keys().cloned().collect(),
+            })
+            .collect()
+    }
+
+    pub fn delete_scene(&self, name: &str) -> Result<(), String> {
+        self.scenes
+            .lock()
+            .unwrap()
+            .remove(name.trim())
+            .map(|_| ())
+            .ok_or_else(|| format!("Scene '{}' not found", name))
+    }
+
+    /// Apply a saved scene to every connected device it covers.
+    ///
+    /// Devices in the scene that are not connected are skipped. Returns the ports that were
+    /// updated; their runners follow with [`runner::ensure_runner_state`].
+    pub fn apply_scene(&self, name: &str) -> Result<Vec<String>, String> {
+        let scene = self
+            .scenes
+            .lock()
+            .unwrap()
+            .get(name.trim())
+            .cloned()
+            .ok_or_else(|| format!("Scene '{}' not found", name))?;
+
+        Ok(self.apply_scene_to_devices(name, &scene))
+    }
+
+    pub fn scenes(&self) -> BTreeMap<String, PersistedScene> {
+        self.scenes.lock().unwrap().clone()
+    }
+
+    pub fn set_scenes(&self, scenes: BTreeMap<String, PersistedScene>) {
+        *self.scenes.lock().unwrap() = scenes;
+    }
+
+    fn snapshot_scene(&self) -> PersistedScene {
+        let devices = self.devices.lock().unwrap();
+        let devices = devices
+            .values()
+            .map(|md| {
+                let device_id = md.controller.lock().unwrap().serial_id();
+                let effects = export_persisted_effects(&md.config.lock().unwrap());
+                (device_id, effects)
+            })
+            .collect();
+        PersistedScene { devices }
+    }
+
+    fn apply_scene_to_devices(&self, name: &str, scene: &PersistedScene) -> Vec<String> {
+        let devices = self.devices.lock().unwrap();
+        let ports_by_id: HashMap<String, String> = devices
+            .iter()
+            .map(|(port, md)| (md.controller.lock().unwrap().serial_id(), port.clone()))
+            .collect();
+
+        let mut applied = Vec::new();
+        for (device_id, effects) in &scene.devices {
+            let Some(port) = ports_by_id.get(device_id) else {
+                log::warn!(scene = name, device_id = device_id.as_str(); "[scene] Device not connected, skipping");
+                continue;
+            };
+            let Some(md) = devices.get(port) else {
+                continue;
+            };
+
+            if let Err(err) = apply_persisted_effects(&mut md.config.lock().unwrap(), effects) {
+                log::warn!(scene = name, port = port.as_str(), err:display = err; "[scene] Failed to apply scene to device");
+                continue;
+            }
+            applied.push(port.clone());
+        }
+
+        applied
+    }
+
+    // =========================================================================
+    // State snapshots
+    // =========================================================================
+
+    /// Write the config of every connected device to `path`. Returns how many were saved.
+    pub fn save_state(&self, path: &Path) -> Result<usize, String> {
+        let ports: Vec<String> = self.devices.lock().unwrap().keys().cloned().collect();
+        let devices: BTreeMap<String, PersistedDeviceConfig> = ports
+            .iter()
+            .filter_map(|port| self.export_persisted_device_config(port).ok())
+            .collect();
+        let count = devices.len();
+
+        let json = serde_json::to_string_pretty(&PersistedState { devices })
+            .map_err(|e| format!("Failed to serialize state: {e}"))?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json).map_err(|e| format!("Failed to write state '{tmp:?}': {e}"))?;
+        std::fs::rename(&tmp, path)
+            .map_err(|e| format!("Failed to move state '{tmp:?}' -> '{path:?}': {e}"))?;
+        Ok(count)
+    }
+
+    /// Apply the configs saved by [`Self::save_state`] to the devices connected now, matched
+    /// by serial id. Saved devices that aren't connected and effects that no longer exist are
+    /// skipped. Returns the ports that were restored, whose runners follow with
+    /// [`runner::ensure_runner_state`]; a missing file restores nothing.
+    pub fn load_state(&self, path: &Path) -> Result<Vec<String>, String> {
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read state '{path:?}': {e}"))?;
+        let state = serde_json::from_str::<PersistedState>(&raw)
+            .map_err(|e| format!("Failed to parse state '{path:?}': {e}"))?;
+
+        let ports_by_id: HashMap<String, String> = self
+            .devices
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(port, md)| (md.controller.lock().unwrap().serial_id(), port.clone()))
+            .collect();
+
+        let mut restored = Vec::new();
+        for (device_id, mut config) in state.devices {
+            let Some(port) = ports_by_id.get(&device_id) else {
+                continue;
+            };
+            drop_unregistered_effects(&mut config.effects);
+            // Effects restart from now: instants from another process mean nothing here.
+            match self.restore_persisted_device_config(port, &config) {
+                Ok(()) => restored.push(port.clone()),
+                Err(err) => log::warn!(
+                    port = port.as_str(),
+                    err:display = err;
+                    "[state] Failed to restore device config"
+                ),
             }
+        }
+        Ok(restored)
+    }
+
+    // =========================================================================
+    // Whole-setup actions (hotkeys)
+    // =========================================================================
 
-            // 2) Apply device-scope effects.
-            let device_mode = PersistedModeConfig {
-                selected: persisted.effects.selected.clone(),
-                params: persisted.effects.params.clone(),
-            };
-            apply_persisted_mode(&mut cfg.mode, &device_mode)?;
+    /// Change the device-scope brightness of every device by `delta`, clamped to 0..=100.
+    /// Returns the ports whose brightness changed.
+    pub fn adjust_brightness_all(&self, delta: i16) -> Vec<String> {
+        let devices = self.devices.lock().unwrap();
+        let mut changed = Vec::new();
+        for (port, md) in devices.iter() {
+            let mut cfg = md.config.lock().unwrap();
+            let next = (cfg.brightness as i16).saturating_add(delta).clamp(0, 100) as u8;
+            if next != cfg.brightness {
+                cfg.brightness = next;
+                changed.push(port.clone());
+            }
+        }
+        changed
+    }
 
-            // 3) Apply output/segment effects.
-            for out_persisted in &persisted.effects.outputs {
-                let Some(out) = cfg.output_mut(&out_persisted.id) else {
-                    continue;
-                };
+    /// Switch every device to the effect after its current device-scope effect.
+    ///
+    /// Only effects that work on any layout take part, and `turn_off` is skipped. Returns the
+    /// ports that were switched; their runners follow with [`runner::ensure_runner_state`].
+    pub fn cycle_effect_all(&self) -> Vec<String> {
+        let mut cycle: Vec<&'static str> = inventory::list_effects()
+            .into_iter()
+            .filter(|meta| meta.layout == EffectLayout::Any && meta.id != TURN_OFF_EFFECT_ID)
+            .map(|meta| meta.id)
+            .collect();
+        cycle.sort_unstable();
+        if cycle.is_empty() {
+            return Vec::new();
+        }
 
-                // Brightness (optional per-scope).
-                out.brightness = out_persisted.brightness.unwrap_or(100);
+        let devices = self.devices.lock().unwrap();
+        let mut switched = Vec::new();
+        for (port, md) in devices.iter() {
+            let mut cfg = md.config.lock().unwrap();
+            let next = cfg
+                .mode
+                .selected_effect_id()
+                .and_then(|id| cycle.iter().position(|candidate| *candidate == id))
+                .map_or(0, |index| (index + 1) % cycle.len());
+            match fill_device(&mut cfg, cycle[next], &Map::new()) {
+                Ok(()) => switched.push(port.clone()),
+                Err(err) => {
+                    log::warn!(port = port.as_str(), effect = cycle[next], err:display = err; "[hotkey] Failed to cycle effect");
+                }
+            }
+        }
+        switched
+    }
 
-                let out_mode = PersistedModeConfig {
-                    selected: out_persisted.selected.clone(),
-                    params: out_persisted.params.clone(),
-                };
-                apply_persisted_mode(&mut out.mode, &out_mode)?;
+    /// Turn every device off, or restore what was running before the last toggle.
+    ///
+    /// Only devices still off as the toggle left them are restored; one switched on by hand
+    /// meanwhile keeps what it shows. Once none is left off, the toggle turns everything off
+    /// again instead. Returns whether the setup is now off, and the ports that were changed; their runners
+    /// follow with [`runner::ensure_runner_state`].
+    pub fn toggle_all(&self) -> (bool, Vec<String>) {
+        let suspended = self.suspended.lock().unwrap().take();
+        if let Some(mut previous) = suspended {
+            {
+                let devices = self.devices.lock().unwrap();
+                let still_off: HashSet<String> = devices
+                    .values()
+                    .filter(|md| is_filled_with(&md.config.lock().unwrap(), TURN_OFF_EFFECT_ID))
+                    .map(|md| md.controller.lock().unwrap().serial_id())
+                    .collect();
+                previous.devices.retain(|device_id, _| still_off.contains(device_id));
+            }
+            if !previous.devices.is_empty() {
+                return (false, self.apply_scene_to_devices("(toggle)", &previous));
+            }
+        }
 
-                for seg_persisted in &out_persisted.segments {
-                    if let Some(seg) = out
-                        .segments
-                        .iter_mut()
-                        .find(|s| s.id == seg_persisted.id)
-                    {
-                        seg.brightness = seg_persisted.brightness.unwrap_or(100);
-                        let seg_mode = PersistedModeConfig {
-                            selected: seg_persisted.selected.clone(),
-                            params: seg_persisted.params.clone(),
-                        };
-                        let _ = apply_persisted_mode(&mut seg.mode, &seg_mode);
-                    }
+        let snapshot = self.snapshot_scene();
+        let devices = self.devices.lock().unwrap();
+        let mut changed = Vec::new();
+        for (port, md) in devices.iter() {
+            match fill_device(&mut md.config.lock().unwrap(), TURN_OFF_EFFECT_ID, &Map::new()) {
+                Ok(()) => changed.push(port.clone()),
+                Err(err) => {
+                    log::warn!(port = port.as_str(), err:display = err; "[hotkey] Failed to turn off device");
                 }
             }
         }
-
-        // Ensure runner state matches restored modes.
-        self.ensure_runner_state_for_device(md, port, app_handle)?;
-        Ok(())
+        *self.suspended.lock().unwrap() = Some(snapshot);
+        (true, changed)
     }
 
     // =========================================================================
     // Internal helpers
     // =========================================================================
 
+    /// Ping every controller that supports health checks and fold the answers into the
+    /// device status. Returns the devices that went offline or came back.
+    pub fn ping_devices(&self) -> Vec<(String, DeviceStatus)> {
+        let targets: Vec<(String, ControllerRef, Arc<Mutex<DeviceStatus>>)> = {
+            let devices = self.devices.lock().unwrap();
+            devices
+                .iter()
+                .map(|(port, md)| (port.clone(), md.controller.clone(), md.status.clone()))
+                .collect()
+        };
+
+        targets
+            .into_iter()
+            .filter_map(|(port, controller, status)| {
+                let answered = controller.lock().unwrap().ping()?;
+                let changed = record_ping(&status, answered)?;
+                match changed.health {
+                    DeviceHealth::Ok => log::info!(port = port.as_str(); "[health] Device answers again"),
+                    _ => log::warn!(port = port.as_str(); "[health] Device stopped answering health checks"),
+                }
+                Some((port, changed))
+            })
+            .collect()
+    }
+
+    // =========================================================================
+    // Frame snapshots
+    // =========================================================================
+
+    /// The frame each device shows, keyed by device identity. Devices showing nothing
+    /// (not written yet, cleared, turned off or without a runner) are left out.
+    pub fn frame_snapshots(&self) -> BTreeMap<String, PersistedFrameSnapshot> {
+        let saved_at_ms = unix_time_ms();
+        // Copied out first, so controllers busy with a write don't hold the devices lock.
+        let shown: Vec<_> = self
+            .devices
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(port, md)| {
+                (
+                    port.clone(),
+                    md.controller.clone(),
+                    md.config.clone(),
+                    md.last_frame.clone(),
+                )
+            })
+            .collect();
+        shown
+            .into_iter()
+            .filter_map(|(port, controller, config, last_frame)| {
+                let frame = last_frame.lock().unwrap().clone();
+                if frame.is_empty() {
+                    return None;
+                }
+                let outputs = frame_snapshot_outputs(&config.lock().unwrap(), &frame);
+                let identity = device_identity(&controller.lock().unwrap().serial_id(), &port);
+                Some((identity, PersistedFrameSnapshot { saved_at_ms, outputs }))
+            })
+            .collect()
+    }
+
+    /// Frames to re-send once, as each device is found by the next scan.
+    pub fn set_pending_frame_snapshots(&self, snapshots: BTreeMap<String, PersistedFrameSnapshot>) {
+        *self.pending_frames.lock().unwrap() = snapshots.into_iter().collect();
+    }
+
     fn sync_all_output_defs(&self) {
         let devices = self.devices.lock().unwrap();
         for (_port, md) in devices.iter() {
@@ -1535,16 +3734,21 @@ impl LightingManager {
     }
 
     fn build_device_dto(&self, port: &str, md: &ManagedDevice) -> Device {
-        let (model, description, serial_id, device_type) = {
+        let (model, description, serial_id, device_type, color_order) = {
             let c = md.controller.lock().unwrap();
-            (c.model(), c.description(), c.serial_id(), c.device_type())
+            (c.model(), c.description(), c.serial_id(), c.device_type(), c.color_order())
         };
 
         let cfg = md.config.lock().unwrap();
 
         let device_mode = self.build_mode_state_for_device(&cfg, port);
+        let frame_rate = md
+            .runner
+            .as_ref()
+            .filter(|runner| !runner.is_finished())
+            .map(|_| f32::from_bits(md.frame_rate.load(Ordering::Relaxed)));
 
-        let outputs = cfg
+        let mut outputs: Vec<OutputPort> = cfg
             .outputs
             .iter()
             .map(|out| {
@@ -1558,6 +3762,7 @@ impl LightingManager {
                         segment_type: seg.segment_type,
                         leds_count: seg.leds_count,
                         matrix: seg.matrix.clone(),
+                        reversed: seg.reversed,
                         brightness: self.build_brightness_state_for_segment(
                             &cfg,
                             port,
@@ -1573,14 +3778,28 @@ impl LightingManager {
                     name: out.name.clone(),
                     output_type: out.output_type,
                     leds_count: out.leds_count,
-                    matrix: out.matrix.clone(),
+                    matrix: out.matrix.as_ref().map(|m| out.transform.apply(m)),
+                    transform: out.transform,
+                    positions: out.positions.clone(),
                     capabilities: out.capabilities.clone(),
                     segments,
                     brightness: self.build_brightness_state_for_output(&cfg, port, &out.id),
                     mode: out_mode,
+                    frame_rate,
+                    soft_start_ms: out.soft_start_ms,
+                    enabled: out.enabled,
                 }
             })
             .collect();
+        if !cfg.display_order.is_empty() {
+            // Outputs missing from the order (added by a driver update) go last.
+            outputs.sort_by_key(|out| {
+                cfg.display_order
+                    .iter()
+                    .position(|id| *id == out.id)
+                    .unwrap_or(usize::MAX)
+            });
+        }
 
         Device {
             port: port.to_string(),
@@ -1588,9 +3807,14 @@ impl LightingManager {
             description,
             id: serial_id,
             device_type,
+            status: md.status.lock().unwrap().clone(),
             brightness: self.build_brightness_state_for_device(&cfg, port),
             outputs,
             mode: device_mode,
+            color_vision: cfg.color_vision,
+            smoothing: cfg.smoothing,
+            power_limit_ma: cfg.power_limit_ma,
+            color_order,
         }
     }
 
@@ -1602,6 +3826,21 @@ impl LightingManager {
             effective_effect_id: resolved.as_ref().map(|r| r.effect_id.clone()),
             effective_params: resolved.as_ref().map(|r| r.params.clone()),
             effective_from: resolved.as_ref().map(|r| r.from.clone()),
+            effective_overlay: resolved
+                .as_ref()
+                .and_then(|r| r.overlay.as_ref())
+                .map(|o| ScopeOverlayState {
+                    effect_id: o.effect_id.clone(),
+                    params: o.params.clone(),
+                    opacity: o.opacity,
+                    blend: o.blend,
+                }),
+            effective_phase_offset_ms: resolved
+                .as_ref()
+                .map_or(0, |r| r.phase_offset.as_millis() as u32),
+            effective_audio_brightness: resolved.as_ref().and_then(|r| r.audio_brightness),
+            effective_brightness: resolve_brightness_for_scope(cfg, port, scope)
+                .map_or(100, |b| b.value),
         }
     }
 
@@ -1672,13 +3911,10 @@ impl LightingManager {
         self.build_brightness_state(cfg, port, Scope::Segment { output_id, segment_id })
     }
 
+    /// Whether any enabled output has something to render.
     fn device_has_any_effect(&self, cfg: &DeviceConfig, _port: &str) -> bool {  
-        if cfg.mode.active_effect.is_some() {
-            return true;
-        }
-
-        for out in &cfg.outputs {
-            if out.mode.active_effect.is_some() {
+        for out in cfg.outputs.iter().filter(|out| out.enabled) {
+            if cfg.mode.active_effect.is_some() || out.mode.active_effect.is_some() {
                 return true;
             }
             for seg in &out.segments {
@@ -1701,12 +3937,25 @@ impl LightingManager {
         let should_run = self.device_has_any_effect(&cfg, port);
         drop(cfg);
 
+        // A runner gives up after persistent write failures. Devices a driver found are
+        // re-probed by `reconnect_lost_devices`; for the rest the next config change, which
+        // restarts the runner, doubles as the reconnect attempt.
+        if md.runner.as_ref().is_some_and(DeviceRunner::is_finished) {
+            if let Some(runner) = md.runner.take() {
+                runner.stop();
+            }
+        }
+
         match (should_run, md.runner.is_some()) {
             (true, false) => {
                 md.runner = Some(DeviceRunner::start(
                     port.to_string(),
                     md.controller.clone(),
                     md.config.clone(),
+                    md.status.clone(),
+                    md.frame_rate.clone(),
+                    md.last_frame.clone(),
+                    md.recorder.clone(),
                     app_handle,
                     md.switch_tx.clone(),
                 )?);
@@ -1736,3 +3985,954 @@ impl LightingManager {
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::controller::{Color, ProbeSpecificity};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Calls {
+        update: AtomicUsize,
+        clear: AtomicUsize,
+        disconnect: AtomicUsize,
+        /// Whether the mock claims hardware brightness.
+        hardware_brightness: AtomicBool,
+        /// Levels passed to `set_brightness`.
+        brightness: Mutex<Vec<u8>>,
+        /// What `outputs()` reports.
+        outputs: Mutex<Vec<OutputPortDefinition>>,
+        /// Frames passed to `update`.
+        frames: Mutex<Vec<Vec<Color>>>,
+        /// What `ping` reports; `None` for a controller without health checks.
+        ping_answer: Mutex<Option<bool>>,
+        /// Order passed to `set_color_order`.
+        color_order: Mutex<Option<ColorOrder>>,
+        /// Output whose resize the mock refuses.
+        resize_error: Mutex<Option<String>>,
+        /// What `serial_id` reports; the port when unset.
+        serial: Mutex<Option<String>>,
+    }
+
+    struct MockController {
+        port: String,
+        calls: Arc<Calls>,
+        hang: Option<Duration>,
+        /// Updates start failing after this many successful ones.
+        fail_after: Option<usize>,
+    }
+
+    impl Controller for MockController {
+        fn port_name(&self) -> String {
+            self.port.clone()
+        }
+
+        fn model(&self) -> String {
+            "Mock".to_string()
+        }
+
+        fn description(&self) -> String {
+            String::new()
+        }
+
+        fn serial_id(&self) -> String {
+            let serial = self.calls.serial.lock().unwrap();
+            serial.clone().unwrap_or_else(|| self.port.clone())
+        }
+
+        fn outputs(&self) -> Vec<OutputPortDefinition> {
+            self.calls.outputs.lock().unwrap().clone()
+        }
+
+        fn update(&mut self, colors: &[Color]) -> Result<(), String> {
+            self.calls.frames.lock().unwrap().push(colors.to_vec());
+            let done = self.calls.update.fetch_add(1, Ordering::SeqCst);
+            match self.fail_after {
+                Some(limit) if done >= limit => Err("write failed".to_string()),
+                _ => Ok(()),
+            }
+        }
+
+        fn clear(&mut self) -> Result<(), String> {
+            if let Some(hang) = self.hang {
+                std::thread::sleep(hang);
+            }
+            self.calls.clear.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn disconnect(&mut self) -> Result<(), String> {
+            self.calls.disconnect.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn has_hardware_brightness(&self) -> bool {
+            self.calls.hardware_brightness.load(Ordering::SeqCst)
+        }
+
+        fn set_brightness(&mut self, brightness: u8) -> Result<(), String> {
+            self.calls.brightness.lock().unwrap().push(brightness);
+            Ok(())
+        }
+
+        fn ping(&mut self) -> Option<bool> {
+            *self.calls.ping_answer.lock().unwrap()
+        }
+
+        fn set_output_leds_count(&mut self, output_id: &str, leds_count: usize) -> Result<(), String> {
+            if self.calls.resize_error.lock().unwrap().as_deref() == Some(output_id) {
+                return Err("resize failed".to_string());
+            }
+            let mut outputs = self.calls.outputs.lock().unwrap();
+            let out = outputs
+                .iter_mut()
+                .find(|out| out.id == output_id)
+                .ok_or_else(|| "not found".to_string())?;
+            out.leds_count = leds_count;
+            Ok(())
+        }
+
+        fn color_order(&self) -> ColorOrder {
+            self.calls.color_order.lock().unwrap().unwrap_or_default()
+        }
+
+        fn set_color_order(&mut self, order: ColorOrder) -> Result<(), String> {
+            if order == ColorOrder::Rgbww {
+                return Err("unsupported".to_string());
+            }
+            *self.calls.color_order.lock().unwrap() = Some(order);
+            Ok(())
+        }
+    }
+
+    fn manager_with(port: &str, hang: Option<Duration>) -> (LightingManager, Arc<Calls>) {
+        let calls = Arc::new(Calls::default());
+        let controller = MockController {
+            port: port.to_string(),
+            calls: calls.clone(),
+            hang,
+            fail_after: None,
+        };
+        let manager = LightingManager::new();
+        manager
+            .devices
+            .lock()
+            .unwrap()
+            .insert(port.to_string(), ManagedDevice::new(Box::new(controller)));
+        (manager, calls)
+    }
+
+    /// A fixed-size linear output named after its id.
+    pub(super) fn linear_output(id: &str, leds_count: usize) -> OutputPortDefinition {
+        OutputPortDefinition {
+            id: id.to_string(),
+            name: id.to_string(),
+            output_type: SegmentType::Linear,
+            leds_count,
+            matrix: None,
+            capabilities: OutputCapabilities {
+                editable: false,
+                min_total_leds: leds_count,
+                max_total_leds: leds_count,
+                allowed_total_leds: None,
+                allowed_segment_types: vec![SegmentType::Linear],
+            },
+        }
+    }
+
+    /// [`linear_output`] that can be resized to anything from 1 to `max_total_leds` LEDs.
+    fn resizable_output(
+        id: &str,
+        leds_count: usize,
+        max_total_leds: usize,
+    ) -> OutputPortDefinition {
+        let mut output = linear_output(id, leds_count);
+        output.capabilities.editable = true;
+        output.capabilities.min_total_leds = 1;
+        output.capabilities.max_total_leds = max_total_leds;
+        output
+    }
+
+    fn mock_controller(port: &str) -> Box<dyn Controller> {
+        Box::new(MockController {
+            port: port.to_string(),
+            calls: Arc::new(Calls::default()),
+            hang: None,
+            fail_after: None,
+        })
+    }
+
+    #[test]
+    fn rescanning_one_driver_keeps_devices_from_the_others() {
+        fn probe() -> Vec<Box<dyn Controller>> {
+            // One new device, plus one the manager already has.
+            vec![mock_controller("usb-new"), mock_controller("usb-known")]
+        }
+        static USB_DRIVER: ControllerMetadata = ControllerMetadata {
+            name: "mock_usb",
+            description: "",
+            priority: 0,
+            specificity: ProbeSpecificity::Generic,
+            probe,
+        };
+
+        let (manager, calls) = manager_with("lan-bulb", None);
+        manager
+            .devices
+            .lock()
+            .unwrap()
+            .insert("usb-known".to_string(), ManagedDevice::new(mock_controller("usb-known")));
+
+        let announced = Mutex::new(Vec::new());
+        manager.rescan_driver(&USB_DRIVER, |device| announced.lock().unwrap().push(device.port));
+
+        let mut ports: Vec<String> = manager.get_devices().into_iter().map(|d| d.port).collect();
+        ports.sort();
+        assert_eq!(ports, ["lan-bulb", "usb-known", "usb-new"]);
+        assert_eq!(calls.disconnect.load(Ordering::SeqCst), 0);
+        announced.lock().unwrap().sort();
+        assert_eq!(*announced.lock().unwrap(), ["usb-known", "usb-new"]);
+    }
+
+    #[test]
+    fn saved_frame_is_resent_when_the_device_is_found_after_a_restart() {
+        let controller = |calls: &Arc<Calls>| {
+            *calls.outputs.lock().unwrap() = vec![linear_output("a", 2), linear_output("b", 1)];
+            Box::new(MockController {
+                port: "strip".to_string(),
+                calls: calls.clone(),
+                hang: None,
+                fail_after: None,
+            })
+        };
+        let rgb = |frame: &[Color]| frame.iter().map(|c| (c.r, c.g, c.b)).collect::<Vec<_>>();
+        let shown = vec![
+            Color { r: 255, g: 0, b: 0 },
+            Color { r: 0, g: 255, b: 0 },
+            Color { r: 0, g: 0, b: 255 },
+        ];
+
+        // Before the restart: the runner wrote `shown`, and the saver persisted it.
+        let before = LightingManager::new();
+        before.register_controller(controller(&Arc::new(Calls::default())), None);
+        *before.devices.lock().unwrap()["strip"].last_frame.lock().unwrap() = shown.clone();
+        let saved = serde_json::to_string(&before.frame_snapshots()).unwrap();
+        // Once the runner clears it, there is nothing left to save.
+        before.devices.lock().unwrap()["strip"].last_frame.lock().unwrap().clear();
+        assert!(before.frame_snapshots().is_empty());
+
+        let after = LightingManager::new();
+        after.set_pending_frame_snapshots(serde_json::from_str(&saved).unwrap());
+        let calls = Arc::new(Calls::default());
+        after.register_controller(controller(&calls), None);
+        after.register_controller(controller(&calls), None);
+        let frames = calls.frames.lock().unwrap();
+        assert_eq!(frames.len(), 1, "re-sent once, on the first scan only");
+        assert_eq!(rgb(&frames[0]), rgb(&shown));
+        drop(frames);
+
+        // Too old to be what the lights still show.
+        let mut stale: BTreeMap<String, PersistedFrameSnapshot> =
+            serde_json::from_str(&saved).unwrap();
+        let identity = device_identity("strip", "strip");
+        stale.get_mut(&identity).unwrap().saved_at_ms -= FRAME_SNAPSHOT_MAX_AGE.as_millis() as u64 + 1;
+        let restarted = LightingManager::new();
+        restarted.set_pending_frame_snapshots(stale);
+        let calls = Arc::new(Calls::default());
+        restarted.register_controller(controller(&calls), None);
+        assert!(calls.frames.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn state_is_keyed_by_serial_and_drops_unregistered_effects() {
+        let (manager, _calls) = manager_with("usb-1", None);
+        manager.devices.lock().unwrap()["usb-1"]
+            .config
+            .lock()
+            .unwrap()
+            .mode
+            .set_effect("rainbow", Instant::now())
+            .unwrap();
+
+        let path = std::env::temp_dir().join(format!("light-state-{}.json", std::process::id()));
+        assert_eq!(manager.save_state(&path), Ok(1));
+        let mut state: PersistedState =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(state.devices["usb-1"].effects.selected.as_deref(), Some("rainbow"));
+
+        // Saved by a build that had an effect this one doesn't.
+        let effects = &mut state.devices.get_mut("usb-1").unwrap().effects;
+        effects.selected = Some("retired_effect".to_string());
+        effects.params.insert("retired_effect".to_string(), Map::new());
+        drop_unregistered_effects(effects);
+        assert_eq!(effects.selected, None);
+        assert_eq!(effects.params.keys().collect::<Vec<_>>(), ["rainbow"]);
+
+        let mut cfg = DeviceConfig::from_output_defs(Vec::new());
+        assert!(apply_persisted_effects(&mut cfg, effects).is_ok());
+    }
+
+    #[test]
+    fn shutdown_clears_and_disconnects_devices() {
+        let (manager, calls) = manager_with("mock", None);
+        manager.shutdown_all(true, Duration::from_secs(1));
+
+        assert_eq!(calls.clear.load(Ordering::SeqCst), 1);
+        assert_eq!(calls.disconnect.load(Ordering::SeqCst), 1);
+        assert!(manager.get_devices().is_empty());
+    }
+
+    #[test]
+    fn removed_devices_are_disconnected_and_forgotten() {
+        let (manager, calls) = manager_with("mock", None);
+        manager.remove_device("mock").unwrap();
+
+        assert_eq!(calls.clear.load(Ordering::SeqCst), 1);
+        assert_eq!(calls.disconnect.load(Ordering::SeqCst), 1);
+        assert!(manager.get_devices().is_empty());
+        assert!(manager.remove_device("mock").is_err());
+    }
+
+    #[test]
+    fn shutdown_can_leave_leds_on() {
+        let (manager, calls) = manager_with("mock", None);
+        manager.shutdown_all(false, Duration::from_secs(1));
+
+        assert_eq!(calls.clear.load(Ordering::SeqCst), 0);
+        assert_eq!(calls.disconnect.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn shutdown_gives_up_on_unresponsive_device() {
+        let (manager, _calls) = manager_with("mock", Some(Duration::from_secs(5)));
+        let started = Instant::now();
+        manager.shutdown_all(true, Duration::from_millis(100));
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn scene_restores_saved_effects() {
+        let (manager, _calls) = manager_with("mock", None);
+        let set_device_effect = |effect_id: Option<&str>| {
+            let devices = manager.devices.lock().unwrap();
+            let mut cfg = devices["mock"].config.lock().unwrap();
+            match effect_id {
+                Some(id) => cfg.mode.set_effect(id, Instant::now()).unwrap(),
+                None => cfg.mode.set_inherit(),
+            }
+        };
+
+        set_device_effect(Some("rainbow"));
+        manager.save_scene(" Movie ").unwrap();
+        set_device_effect(None);
+
+        let summaries = manager.list_scenes();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].name, "Movie");
+        assert_eq!(summaries[0].device_ids, vec!["mock".to_string()]);
+
+        let scene = manager.scenes().remove("Movie").unwrap();
+        let devices = manager.devices.lock().unwrap();
+        let mut cfg = devices["mock"].config.lock().unwrap();
+        apply_persisted_effects(&mut cfg, &scene.devices["mock"]).unwrap();
+        assert_eq!(cfg.mode.selected_effect_id().as_deref(), Some("rainbow"));
+        drop(cfg);
+        drop(devices);
+
+        manager.delete_scene("Movie").unwrap();
+        assert!(manager.delete_scene("Movie").is_err());
+    }
+
+    #[test]
+    fn runner_reports_failures_and_gives_up_after_persistent_errors() {
+        use super::runner::{write_frame, WriteOutcome, MAX_CONSECUTIVE_FAILURES};
+
+        const K: usize = 3;
+        let calls = Arc::new(Calls::default());
+        let md = ManagedDevice::new(Box::new(MockController {
+            port: "flaky".to_string(),
+            calls: calls.clone(),
+            hang: None,
+            fail_after: Some(K),
+        }));
+        let frame = [Color::default(); 4];
+
+        for _ in 0..K {
+            assert_eq!(write_frame(&md.controller, &frame, &md.status), WriteOutcome::Unchanged);
+        }
+
+        // First failure flips the status, later ones are only counted.
+        match write_frame(&md.controller, &frame, &md.status) {
+            WriteOutcome::StatusChanged(status) => {
+                assert_eq!(status.health, DeviceHealth::Error);
+                assert_eq!(status.last_error.as_deref(), Some("write failed"));
+            }
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+        for _ in 2..MAX_CONSECUTIVE_FAILURES {
+            assert_eq!(write_frame(&md.controller, &frame, &md.status), WriteOutcome::Unchanged);
+        }
+        assert_eq!(calls.disconnect.load(Ordering::SeqCst), 0);
+
+        match write_frame(&md.controller, &frame, &md.status) {
+            WriteOutcome::GaveUp(status) => {
+                assert_eq!(status.health, DeviceHealth::Disconnected);
+                assert_eq!(status.consecutive_failures, MAX_CONSECUTIVE_FAILURES);
+            }
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+        assert_eq!(calls.disconnect.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn lost_device_is_reconnected_by_serial_id() {
+        fn probe() -> Vec<Box<dyn Controller>> {
+            // Back under its own serial, plus a device that isn't the lost one.
+            vec![mock_controller("other"), mock_controller("strip")]
+        }
+        static SERIAL_DRIVER: ControllerMetadata = ControllerMetadata {
+            name: "mock_serial",
+            description: "",
+            priority: 0,
+            specificity: ProbeSpecificity::Generic,
+            probe,
+        };
+        fn empty_probe() -> Vec<Box<dyn Controller>> {
+            Vec::new()
+        }
+        static EMPTY_DRIVER: ControllerMetadata = ControllerMetadata {
+            name: "mock_empty",
+            description: "",
+            priority: 0,
+            specificity: ProbeSpecificity::Generic,
+            probe: empty_probe,
+        };
+
+        let (manager, calls) = manager_with("strip", None);
+        let lost = [("strip".to_string(), device_identity("strip", "strip"))];
+        manager.devices.lock().unwrap().get_mut("strip").unwrap().reconnect_backoff =
+            RECONNECT_BACKOFF_MAX;
+
+        assert!(manager.reconnect_with_driver(&EMPTY_DRIVER, &lost).is_empty());
+        assert_eq!(
+            manager.reconnect_with_driver(&SERIAL_DRIVER, &lost),
+            [ReconnectedDevice { port: "strip".to_string(), previous_port: None }]
+        );
+
+        let devices = manager.devices.lock().unwrap();
+        assert_eq!(devices.len(), 1, "devices that weren't lost are left to scans");
+        let md = &devices["strip"];
+        assert_eq!(md.controller.lock().unwrap().serial_id(), "strip");
+        assert_eq!(md.reconnect_backoff, RECONNECT_BACKOFF_MIN);
+        // The new handle is written to, not the old one.
+        md.controller.lock().unwrap().update(&[]).unwrap();
+        assert_eq!(calls.update.load(Ordering::SeqCst), 0);
+    }
+
+    /// A mock on `port` whose serial is `serial`.
+    fn controller_with_serial(port: &str, serial: &str) -> Box<dyn Controller> {
+        let calls = Calls::default();
+        *calls.serial.lock().unwrap() = Some(serial.to_string());
+        Box::new(MockController {
+            port: port.to_string(),
+            calls: Arc::new(calls),
+            hang: None,
+            fail_after: None,
+        })
+    }
+
+    #[test]
+    fn device_back_under_another_port_moves_there() {
+        fn probe() -> Vec<Box<dyn Controller>> {
+            vec![controller_with_serial("COM7", "SN-1")]
+        }
+        static SERIAL_DRIVER: ControllerMetadata = ControllerMetadata {
+            name: "mock_serial",
+            description: "",
+            priority: 0,
+            specificity: ProbeSpecificity::Generic,
+            probe,
+        };
+
+        let manager = LightingManager::new();
+        manager
+            .devices
+            .lock()
+            .unwrap()
+            .insert("COM3".to_string(), ManagedDevice::new(controller_with_serial("COM3", "SN-1")));
+        let lost = [("COM3".to_string(), device_identity("SN-1", "COM3"))];
+
+        assert_eq!(
+            manager.reconnect_with_driver(&SERIAL_DRIVER, &lost),
+            [ReconnectedDevice { port: "COM7".to_string(), previous_port: Some("COM3".to_string()) }]
+        );
+        assert_eq!(manager.get_device("COM7").unwrap().port, "COM7");
+        assert!(manager.get_device("COM3").is_err());
+
+        // A full scan doesn't add it a second time.
+        manager.scan_with(
+            |on_found| scan_drivers_with(std::iter::once(&SERIAL_DRIVER), on_found),
+            |_| {},
+        );
+        assert_eq!(manager.get_devices().len(), 1);
+    }
+
+    #[test]
+    fn full_scan_skips_a_device_managed_under_another_port() {
+        fn probe() -> Vec<Box<dyn Controller>> {
+            vec![controller_with_serial("COM7", "SN-1"), controller_with_serial("COM8", "SN-2")]
+        }
+        static SERIAL_DRIVER: ControllerMetadata = ControllerMetadata {
+            name: "mock_serial",
+            description: "",
+            priority: 0,
+            specificity: ProbeSpecificity::Generic,
+            probe,
+        };
+
+        let manager = LightingManager::new();
+        manager
+            .devices
+            .lock()
+            .unwrap()
+            .insert("COM3".to_string(), ManagedDevice::new(controller_with_serial("COM3", "SN-1")));
+        manager.scan_with(
+            |on_found| scan_drivers_with(std::iter::once(&SERIAL_DRIVER), on_found),
+            |_| {},
+        );
+
+        let mut ports: Vec<String> = manager.get_devices().into_iter().map(|d| d.port).collect();
+        ports.sort();
+        assert_eq!(ports, ["COM3", "COM8"]);
+    }
+
+    #[test]
+    fn runner_status_recovers_after_transient_error() {
+        use super::runner::{write_frame, WriteOutcome};
+
+        let md = ManagedDevice::new(Box::new(MockController {
+            port: "mock".to_string(),
+            calls: Arc::new(Calls::default()),
+            hang: None,
+            fail_after: None,
+        }));
+        *md.status.lock().unwrap() = DeviceStatus {
+            health: DeviceHealth::Error,
+            last_error: Some("timeout".to_string()),
+            consecutive_failures: 2,
+            frame_loss_percent: None,
+            power_limit_percent: None,
+            missed_pings: 0,
+        };
+
+        match write_frame(&md.controller, &[Color::default()], &md.status) {
+            WriteOutcome::StatusChanged(status) => {
+                assert_eq!(status.health, DeviceHealth::Ok);
+                assert_eq!(status.consecutive_failures, 0);
+            }
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unanswered_pings_disconnect_the_device_until_it_answers() {
+        use super::runner::{write_frame, MAX_MISSED_PINGS};
+
+        let (manager, calls) = manager_with("udp", None);
+        let health = |manager: &LightingManager| manager.get_device("udp").unwrap().status.health;
+
+        // Controllers without health checks are left alone.
+        assert!(manager.ping_devices().is_empty());
+
+        *calls.ping_answer.lock().unwrap() = Some(false);
+        for _ in 1..MAX_MISSED_PINGS {
+            assert!(manager.ping_devices().is_empty());
+        }
+        let changed = manager.ping_devices();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].1.health, DeviceHealth::Disconnected);
+        assert!(manager.ping_devices().is_empty(), "reported once");
+
+        // UDP writes keep succeeding, which doesn't bring the device back.
+        let status = manager.devices.lock().unwrap()["udp"].status.clone();
+        let controller = manager.devices.lock().unwrap()["udp"].controller.clone();
+        write_frame(&controller, &[Color::default()], &status);
+        assert_eq!(health(&manager), DeviceHealth::Disconnected);
+
+        *calls.ping_answer.lock().unwrap() = Some(true);
+        let changed = manager.ping_devices();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].1.health, DeviceHealth::Ok);
+        assert_eq!(health(&manager), DeviceHealth::Ok);
+    }
+
+    #[test]
+    fn output_order_only_changes_presentation() {
+        let (manager, _calls) = manager_with("mock", None);
+        *manager.devices.lock().unwrap()["mock"].config.lock().unwrap() =
+            DeviceConfig::from_output_defs(vec![
+                linear_output("center", 4),
+                linear_output("left", 4),
+                linear_output("right", 4),
+            ]);
+        let listed = |manager: &LightingManager| {
+            manager
+                .get_device("mock")
+                .unwrap()
+                .outputs
+                .iter()
+                .map(|o| o.id.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let order = vec!["left".to_string(), "center".to_string(), "right".to_string()];
+        manager.set_output_order("mock", order.clone()).unwrap();
+        assert_eq!(listed(&manager), order);
+
+        let devices = manager.devices.lock().unwrap();
+        let cfg = devices["mock"].config.lock().unwrap();
+        let physical = cfg.outputs.iter().map(|o| o.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(physical, ["center", "left", "right"], "frame layout keeps driver order");
+        drop(cfg);
+        drop(devices);
+
+        for invalid in [
+            vec!["left".to_string(), "center".to_string()],
+            vec!["left".to_string(), "left".to_string(), "right".to_string()],
+            vec!["left".to_string(), "center".to_string(), "top".to_string()],
+        ] {
+            assert!(manager.set_output_order("mock", invalid).is_err());
+        }
+        assert_eq!(listed(&manager), order);
+    }
+
+    #[test]
+    fn reordering_segments_keeps_their_settings() {
+        let (manager, _calls) = manager_with("mock", None);
+        *manager.devices.lock().unwrap()["mock"].config.lock().unwrap() =
+            DeviceConfig::from_output_defs(vec![resizable_output("strip", 6, 6)]);
+        let segment = |id: &str, leds_count: usize, reversed: bool| SegmentDefinition {
+            id: id.to_string(),
+            name: id.to_string(),
+            segment_type: SegmentType::Linear,
+            leds_count,
+            matrix: None,
+            reversed,
+        };
+        manager
+            .set_output_segments(
+                "mock",
+                "strip",
+                vec![segment("a", 2, false), segment("b", 4, true)],
+            )
+            .unwrap();
+        manager
+            .set_scope_brightness("mock", Some("strip"), Some("b"), 40)
+            .unwrap();
+
+        manager
+            .reorder_segments("mock", "strip", vec!["b".to_string(), "a".to_string()])
+            .unwrap();
+        let segments = manager.get_device("mock").unwrap().outputs[0].segments.clone();
+        let summary = segments
+            .iter()
+            .map(|s| (s.id.as_str(), s.leds_count, s.reversed, s.brightness.value))
+            .collect::<Vec<_>>();
+        assert_eq!(summary, [("b", 4, true, 40), ("a", 2, false, 100)]);
+
+        for invalid in [
+            vec!["b".to_string()],
+            vec!["b".to_string(), "b".to_string()],
+            vec!["b".to_string(), "c".to_string()],
+        ] {
+            assert!(manager.reorder_segments("mock", "strip", invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn layouts_round_trip_and_mismatches_change_nothing() {
+        let strip = resizable_output("strip", 6, 6);
+        let segment = |id: &str, leds_count: usize| SegmentDefinition {
+            id: id.to_string(),
+            name: id.to_string(),
+            segment_type: SegmentType::Linear,
+            leds_count,
+            matrix: None,
+            reversed: false,
+        };
+
+        let (source, _calls) = manager_with("source", None);
+        *source.devices.lock().unwrap()["source"].config.lock().unwrap() =
+            DeviceConfig::from_output_defs(vec![strip.clone()]);
+        source
+            .set_output_segments("source", "strip", vec![segment("a", 2), segment("b", 4)])
+            .unwrap();
+        source.devices.lock().unwrap()["source"]
+            .config
+            .lock()
+            .unwrap()
+            .output_mut("strip")
+            .unwrap()
+            .segments[1]
+            .mode
+            .set_effect("rainbow", Instant::now())
+            .unwrap();
+        let document = source.export_layout("source").unwrap();
+        assert_eq!(document["version"], LAYOUT_VERSION);
+
+        let (target, _calls) = manager_with("target", None);
+        *target.devices.lock().unwrap()["target"].config.lock().unwrap() =
+            DeviceConfig::from_output_defs(vec![strip]);
+        let import = |document: Value| {
+            let devices = target.devices.lock().unwrap();
+            parse_layout_document(document).and_then(|config| apply_layout(&devices["target"], &config))
+        };
+        let segments = || {
+            target.get_device("target").unwrap().outputs[0]
+                .segments
+                .iter()
+                .map(|s| (s.id.clone(), s.mode.selected_effect_id.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        import(document.clone()).unwrap();
+        let imported = vec![
+            ("a".to_string(), None),
+            ("b".to_string(), Some("rainbow".to_string())),
+        ];
+        assert_eq!(segments(), imported);
+
+        // Segments that don't add up to the strip, then a layout from a newer build.
+        let mut mismatched = document.clone();
+        mismatched["device"]["layout"]["strip"]["segments"][1]["leds_count"] = 3.into();
+        assert!(import(mismatched).unwrap_err().contains("Segment total"));
+        let mut newer = document;
+        newer["version"] = (LAYOUT_VERSION + 1).into();
+        assert!(import(newer).unwrap_err().contains("version"));
+        assert_eq!(segments(), imported);
+    }
+
+    #[test]
+    fn a_refused_resize_undoes_the_rest_of_the_layout() {
+        let strip = |id: &str| resizable_output(id, 6, 12);
+        let segment = |id: &str| SegmentDefinition {
+            id: id.to_string(),
+            name: id.to_string(),
+            segment_type: SegmentType::Linear,
+            leds_count: 3,
+            matrix: None,
+            reversed: false,
+        };
+
+        let (manager, calls) = manager_with("rig", None);
+        *calls.outputs.lock().unwrap() = vec![strip("a"), strip("b")];
+        *manager.devices.lock().unwrap()["rig"].config.lock().unwrap() =
+            DeviceConfig::from_output_defs(vec![strip("a"), strip("b")]);
+        manager
+            .set_output_segments("rig", "a", vec![segment("a1"), segment("a2")])
+            .unwrap();
+        *calls.resize_error.lock().unwrap() = Some("b".to_string());
+
+        let mut config = PersistedDeviceConfig::default();
+        for id in ["a", "b"] {
+            let layout = PersistedOutputLayout {
+                leds_count: Some(12),
+                ..Default::default()
+            };
+            config.device.layout.insert(id.to_string(), layout);
+        }
+        config.device.color_order = Some(ColorOrder::Grb);
+        {
+            let devices = manager.devices.lock().unwrap();
+            assert_eq!(apply_layout(&devices["rig"], &config).unwrap_err(), "resize failed");
+        }
+
+        assert!(calls.outputs.lock().unwrap().iter().all(|out| out.leds_count == 6));
+        let device = manager.get_device("rig").unwrap();
+        assert!(device.outputs.iter().all(|out| out.leds_count == 6));
+        assert_eq!(device.outputs[0].segments.len(), 2);
+        assert_eq!(device.color_order, ColorOrder::Rgb);
+    }
+
+    #[test]
+    fn applying_to_linear_outputs_skips_matrix_outputs() {
+        let output = |id: &str, output_type: SegmentType| OutputPortDefinition {
+            id: id.to_string(),
+            name: format!("Channel {}", id),
+            output_type,
+            leds_count: 4,
+            matrix: None,
+            capabilities: OutputCapabilities {
+                editable: false,
+                min_total_leds: 4,
+                max_total_leds: 4,
+                allowed_total_leds: None,
+                allowed_segment_types: vec![output_type],
+            },
+        };
+        let mut cfg = DeviceConfig::from_output_defs(vec![
+            output("1", SegmentType::Linear),
+            output("2", SegmentType::Matrix),
+            output("3", SegmentType::Linear),
+        ]);
+        let linear = OutputFilter {
+            output_type: Some(SegmentType::Linear),
+            name_contains: None,
+        };
+        let params = serde_json::json!({ "speed": 7 });
+
+        let applied =
+            apply_effect_to_outputs(&mut cfg, "mock", &linear, "rainbow", params.as_object())
+                .unwrap();
+        assert_eq!(applied, ["1", "3"]);
+        for id in ["1", "3"] {
+            let mode = &cfg.output(id).unwrap().mode;
+            assert_eq!(mode.selected_effect_id().as_deref(), Some("rainbow"));
+            assert_eq!(mode.params_for_effect("rainbow").unwrap()["speed"], 7);
+        }
+        assert_eq!(cfg.output("2").unwrap().mode.selected_effect_id(), None);
+
+        // A failure leaves every output as it was.
+        let all = OutputFilter::default();
+        assert!(apply_effect_to_outputs(&mut cfg, "mock", &all, "no_such_effect", None).is_err());
+        let selected = |cfg: &DeviceConfig, id: &str| cfg.output(id).unwrap().mode.selected_effect_id();
+        assert_eq!(selected(&cfg, "1").as_deref(), Some("rainbow"));
+        assert_eq!(selected(&cfg, "2"), None);
+    }
+
+    #[test]
+    fn filling_the_device_makes_every_output_inherit() {
+        let mut cfg =
+            DeviceConfig::from_output_defs(vec![linear_output("1", 4), linear_output("2", 4)]);
+        cfg.output_mut("2")
+            .unwrap()
+            .mode
+            .set_effect("rainbow", Instant::now())
+            .unwrap();
+
+        let mut params = Map::new();
+        params.insert("color".to_string(), Value::from("#ff8000"));
+        fill_device(&mut cfg, MONOCHROME_EFFECT_ID, &params).unwrap();
+        let started_at = cfg.mode.active_effect.as_ref().unwrap().started_at;
+        assert_eq!(cfg.mode.params_for_effect(MONOCHROME_EFFECT_ID).unwrap()["color"], "#ff8000");
+        assert!(cfg.outputs.iter().all(|out| out.mode.selected_effect_id().is_none()));
+
+        // Another color keeps the effect running; turning off replaces it.
+        params.insert("color".to_string(), Value::from("#0000ff"));
+        fill_device(&mut cfg, MONOCHROME_EFFECT_ID, &params).unwrap();
+        assert_eq!(cfg.mode.active_effect.as_ref().unwrap().started_at, started_at);
+        fill_device(&mut cfg, TURN_OFF_EFFECT_ID, &Map::new()).unwrap();
+        assert_eq!(cfg.mode.selected_effect_id().as_deref(), Some(TURN_OFF_EFFECT_ID));
+    }
+
+    #[test]
+    fn hotkey_actions_cover_every_device() {
+        let (manager, _calls) = manager_with("a", None);
+        manager
+            .devices
+            .lock()
+            .unwrap()
+            .insert("b".to_string(), ManagedDevice::new(mock_controller("b")));
+        let device_effect = |port: &str| {
+            manager.devices.lock().unwrap()[port].config.lock().unwrap().mode.selected_effect_id()
+        };
+        let sorted = |mut ports: Vec<String>| {
+            ports.sort();
+            ports
+        };
+
+        // Huge deltas saturate instead of overflowing.
+        assert_eq!(sorted(manager.adjust_brightness_all(-20)), ["a", "b"]);
+        assert_eq!(manager.adjust_brightness_all(i16::MAX).len(), 2);
+        assert!(manager.adjust_brightness_all(i16::MAX).is_empty(), "already at 100");
+        manager.adjust_brightness_all(i16::MIN);
+        assert_eq!(manager.devices.lock().unwrap()["a"].config.lock().unwrap().brightness, 0);
+
+        assert_eq!(sorted(manager.cycle_effect_all()), ["a", "b"]);
+        let first = device_effect("a").unwrap();
+        manager.cycle_effect_all();
+        let second = device_effect("a").unwrap();
+        assert_ne!(first, second);
+        assert_ne!(second, TURN_OFF_EFFECT_ID);
+
+        assert!(manager.toggle_all().0);
+        assert_eq!(device_effect("b").as_deref(), Some(TURN_OFF_EFFECT_ID));
+        assert!(!manager.toggle_all().0);
+        assert_eq!(device_effect("a"), Some(second.clone()));
+
+        // A device switched on by hand while the setup is off keeps what it shows.
+        manager.toggle_all();
+        {
+            let devices = manager.devices.lock().unwrap();
+            fill_device(&mut devices["b"].config.lock().unwrap(), &first, &Map::new()).unwrap();
+        }
+        assert_eq!(manager.toggle_all(), (false, vec!["a".to_string()]));
+        assert_eq!(device_effect("b"), Some(first.clone()));
+        // With nothing left off, toggling turns everything off again.
+        assert!(manager.toggle_all().0);
+        assert_eq!(device_effect("a").as_deref(), Some(TURN_OFF_EFFECT_ID));
+    }
+
+    #[test]
+    fn brightness_goes_to_hardware_when_the_controller_supports_it() {
+        use super::runner::HardwareBrightness;
+
+        let (manager, calls) = manager_with("mock", None);
+        manager.set_brightness("mock", 70).unwrap();
+        assert!(calls.brightness.lock().unwrap().is_empty(), "RGB-only controller");
+
+        calls.hardware_brightness.store(true, Ordering::SeqCst);
+        manager.set_brightness("mock", 40).unwrap();
+        assert_eq!(*calls.brightness.lock().unwrap(), vec![40]);
+
+        let controller = manager.devices.lock().unwrap()["mock"].controller.clone();
+        let mut routing = HardwareBrightness::default();
+        assert!(routing.route(&controller, Some(40)), "one shared level is dimmed in hardware");
+        assert!(routing.route(&controller, Some(40)));
+        // Mixed levels: hardware back to full, the runner scales each target itself.
+        assert!(!routing.route(&controller, None));
+        assert_eq!(*calls.brightness.lock().unwrap(), vec![40, 40, 100]);
+
+        calls.hardware_brightness.store(false, Ordering::SeqCst);
+        assert!(!HardwareBrightness::default().route(&controller, Some(40)));
+        assert_eq!(calls.brightness.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn color_order_reaches_the_controller_and_is_restored() {
+        let (manager, calls) = manager_with("strip", None);
+        assert_eq!(manager.get_device("strip").unwrap().color_order, ColorOrder::Rgb);
+
+        manager.set_device_color_order("strip", ColorOrder::Grbw).unwrap();
+        assert!(manager.set_device_color_order("strip", ColorOrder::Rgbww).is_err());
+        assert_eq!(*calls.color_order.lock().unwrap(), Some(ColorOrder::Grbw));
+        assert_eq!(manager.get_device("strip").unwrap().color_order, ColorOrder::Grbw);
+
+        let (_, persisted) = manager.export_persisted_device_config("strip").unwrap();
+        assert_eq!(persisted.device.color_order, Some(ColorOrder::Grbw));
+        let (restarted, restarted_calls) = manager_with("strip", None);
+        restarted
+            .restore_persisted_device_config("strip", &persisted)
+            .unwrap();
+        assert_eq!(*restarted_calls.color_order.lock().unwrap(), Some(ColorOrder::Grbw));
+    }
+
+    #[test]
+    fn overlay_opacity_mixes_between_base_and_overlay() {
+        use super::runner::blend_color;
+
+        let base = Color { r: 200, g: 40, b: 0 };
+        let overlay = Color { r: 10, g: 120, b: 255 };
+
+        for mode in [BlendMode::Normal, BlendMode::Add, BlendMode::Screen, BlendMode::Multiply] {
+            let out = blend_color(base, overlay, mode, 0.0);
+            assert_eq!((out.r, out.g, out.b), (base.r, base.g, base.b), "{:?}", mode);
+        }
+
+        let out = blend_color(base, overlay, BlendMode::Normal, 1.0);
+        assert_eq!((out.r, out.g, out.b), (overlay.r, overlay.g, overlay.b));
+    }
+}
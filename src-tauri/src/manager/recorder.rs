@@ -0,0 +1,278 @@
+//! Records the frames a device runner writes, for reproducing effect bugs elsewhere.
+//!
+//! A recording is a series of files `<name>-<part>.lfr`, each decodable on its own. When one
+//! reaches [`MAX_FILE_BYTES`] the next part is started, and only the newest [`KEEP_FILES`]
+//! parts are kept, the same budget the log files get.
+//!
+//! File format, all integers little-endian:
+//!
+//! ```text
+//! header:  b"LFRM"  version: u8 (= 1)  outputs: u16
+//!          per output: id_len: u16  id: [u8; id_len] (UTF-8)  leds_count: u32
+//! frame:   at_us: u64 (since the recording started)  leds: u32  rgb: [u8; leds * 3]
+//! ```
+//!
+//! Frames are the device buffer in physical order, after brightness and color correction,
+//! so an output's LEDs start where the lengths of the outputs before it add up to. Only
+//! rendered frames are recorded: a static effect that renders once a second shows up as
+//! one frame a second.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::interface::controller::Color;
+
+const MAGIC: &[u8; 4] = b"LFRM";
+const VERSION: u8 = 1;
+
+pub const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+pub const KEEP_FILES: u32 = 10;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedOutput {
+    pub id: String,
+    pub leds_count: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct RecordedFrame {
+    /// Time since the recording started.
+    pub at: Duration,
+    pub colors: Vec<Color>,
+}
+
+/// One decoded file.
+#[derive(Clone, Debug)]
+pub struct Recording {
+    pub outputs: Vec<RecordedOutput>,
+    pub frames: Vec<RecordedFrame>,
+}
+
+pub struct FrameRecorder {
+    dir: PathBuf,
+    name: String,
+    outputs: Vec<RecordedOutput>,
+    started_at: Instant,
+    writer: BufWriter<File>,
+    part: u32,
+    file_bytes: u64,
+    max_file_bytes: u64,
+}
+
+impl FrameRecorder {
+    /// Start recording into `dir`, which is created if needed. `name` is made
+    /// filesystem-friendly.
+    pub fn create(
+        dir: &Path,
+        name: &str,
+        outputs: Vec<RecordedOutput>,
+        now: Instant,
+    ) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let name = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect::<String>();
+        let path = part_path(dir, &name, 0);
+        let mut recorder = Self {
+            dir: dir.to_path_buf(),
+            name,
+            outputs,
+            started_at: now,
+            writer: BufWriter::new(File::create(&path)?),
+            part: 0,
+            file_bytes: 0,
+            max_file_bytes: MAX_FILE_BYTES,
+        };
+        recorder.write_header()?;
+        Ok(recorder)
+    }
+
+    /// File currently written to.
+    pub fn path(&self) -> PathBuf {
+        part_path(&self.dir, &self.name, self.part)
+    }
+
+    pub fn record(&mut self, now: Instant, frame: &[Color]) -> io::Result<()> {
+        let frame_bytes = 12 + frame.len() as u64 * 3;
+        if self.file_bytes + frame_bytes > self.max_file_bytes {
+            self.rotate()?;
+        }
+
+        let at = now.saturating_duration_since(self.started_at).as_micros() as u64;
+        let mut buf = Vec::with_capacity(frame_bytes as usize);
+        buf.extend_from_slice(&at.to_le_bytes());
+        buf.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        for color in frame {
+            buf.extend_from_slice(&[color.r, color.g, color.b]);
+        }
+        self.writer.write_all(&buf)?;
+        self.file_bytes += frame_bytes;
+        Ok(())
+    }
+
+    /// Flush buffered frames to disk.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(MAGIC);
+        buf.push(VERSION);
+        buf.extend_from_slice(&(self.outputs.len() as u16).to_le_bytes());
+        for output in &self.outputs {
+            let id = output.id.as_bytes();
+            buf.extend_from_slice(&(id.len() as u16).to_le_bytes());
+            buf.extend_from_slice(id);
+            buf.extend_from_slice(&(output.leds_count as u32).to_le_bytes());
+        }
+        self.writer.write_all(&buf)?;
+        self.file_bytes = buf.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        self.part += 1;
+        self.writer = BufWriter::new(File::create(self.path())?);
+        self.write_header()?;
+        if let Some(expired) = self.part.checked_sub(KEEP_FILES) {
+            // Already gone is fine.
+            let _ = fs::remove_file(part_path(&self.dir, &self.name, expired));
+        }
+        Ok(())
+    }
+}
+
+fn part_path(dir: &Path, name: &str, part: u32) -> PathBuf {
+    dir.join(format!("{}-{:03}.lfr", name, part))
+}
+
+/// Decode one recording file. A frame cut off at the end (the app quit mid-write) is dropped.
+pub fn decode(mut reader: impl Read) -> io::Result<Recording> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+    let mut magic = [0u8; 5];
+    reader.read_exact(&mut magic)?;
+    if &magic[..4] != MAGIC {
+        return Err(invalid("not a frame recording"));
+    }
+    if magic[4] != VERSION {
+        return Err(invalid("unsupported frame recording version"));
+    }
+
+    let outputs = (0..read_u16(&mut reader)?)
+        .map(|_| {
+            let mut id = vec![0u8; read_u16(&mut reader)? as usize];
+            reader.read_exact(&mut id)?;
+            Ok(RecordedOutput {
+                id: String::from_utf8(id).map_err(|_| invalid("output id is not UTF-8"))?,
+                leds_count: read_u32(&mut reader)? as usize,
+            })
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let mut frames = Vec::new();
+    loop {
+        let mut at = [0u8; 8];
+        match reader.read_exact(&mut at) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        }
+        let mut frame = || -> io::Result<RecordedFrame> {
+            let mut rgb = vec![0u8; read_u32(&mut reader)? as usize * 3];
+            reader.read_exact(&mut rgb)?;
+            Ok(RecordedFrame {
+                at: Duration::from_micros(u64::from_le_bytes(at)),
+                colors: rgb
+                    .chunks_exact(3)
+                    .map(|c| Color { r: c[0], g: c[1], b: c[2] })
+                    .collect(),
+            })
+        };
+        match frame() {
+            Ok(frame) => frames.push(frame),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(Recording { outputs, frames })
+}
+
+fn read_u16(reader: &mut impl Read) -> io::Result<u16> {
+    let mut buf = [0u8; 2];
+    reader.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("light-recorder-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn outputs() -> Vec<RecordedOutput> {
+        vec![
+            RecordedOutput { id: "a".to_string(), leds_count: 2 },
+            RecordedOutput { id: "b".to_string(), leds_count: 1 },
+        ]
+    }
+
+    #[test]
+    fn one_second_at_30_fps_records_30_frames() {
+        let dir = scratch_dir("fps");
+        let start = Instant::now();
+        let mut recorder = FrameRecorder::create(&dir, "usb:1", outputs(), start).unwrap();
+        for i in 0..30u32 {
+            let level = (i * 8) as u8;
+            let frame = [Color { r: level, g: 0, b: 255 - level }; 3];
+            recorder.record(start + Duration::from_secs(1) * i / 30, &frame).unwrap();
+        }
+        recorder.flush().unwrap();
+
+        let recording = decode(File::open(recorder.path()).unwrap()).unwrap();
+        assert_eq!(recording.outputs, outputs());
+        assert_eq!(recording.frames.len(), 30);
+        let last = recording.frames.last().unwrap();
+        // Timestamps are kept to the microsecond.
+        assert_eq!(last.at.as_micros(), 966_666);
+        assert_eq!((last.colors[2].r, last.colors[2].b), (232, 23));
+        assert!(recorder.path().ends_with("usb_1-000.lfr"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn full_files_rotate_and_old_parts_are_removed() {
+        let dir = scratch_dir("rotate");
+        let start = Instant::now();
+        let mut recorder = FrameRecorder::create(&dir, "dev", outputs(), start).unwrap();
+        // Room for the header plus two 3-LED frames per file.
+        recorder.max_file_bytes = recorder.file_bytes + 2 * 21;
+        for i in 0..2 * (KEEP_FILES as u64 + 3) {
+            recorder.record(start + Duration::from_millis(i), &[Color::default(); 3]).unwrap();
+        }
+        recorder.flush().unwrap();
+
+        let parts = fs::read_dir(&dir).unwrap().count();
+        assert_eq!(parts, KEEP_FILES as usize);
+        let newest = decode(File::open(recorder.path()).unwrap()).unwrap();
+        assert_eq!(newest.outputs, outputs());
+        assert_eq!(newest.frames.len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
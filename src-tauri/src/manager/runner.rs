@@ -1,22 +1,349 @@
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 
-use crate::interface::controller::{Color, MatrixMap, SegmentType};
-use crate::interface::effect::Effect;
+use crate::interface::controller::{
+    Color, LedPosition, MatrixMap, MatrixTransform, PowerBudget, SegmentType,
+    WriteMetrics,
+};
+use crate::interface::effect::{CaptureFailure, Effect};
+use crate::resource::audio::brightness::{AudioBrightness, AudioBrightnessSettings};
+use crate::resource::color::vision::{color_vision_mode, correct_frame};
+use crate::resource::effect::audio_star::screen_blend;
 
 use super::inventory::create_effect;
+use super::recorder::FrameRecorder;
 use super::{
-    resolve_brightness_for_scope, resolve_effect_for_scope, DeviceConfig, ResolvedEffect, Scope,
+    resolve_brightness_for_scope, resolve_effect_for_scope, BlendMode, DeviceConfig,
+    DeviceHealth, DeviceStatus, LightingManager, OverlayConfig, ReconnectedDevice, ResolvedEffect, Scope,
     EFFECT_READY_TIMEOUT,
 };
 
 type ControllerRef = Arc<Mutex<Box<dyn crate::interface::controller::Controller>>>;
 
+/// Consecutive failed writes (~1s at 60 FPS) before the runner gives up on the device.
+pub(super) const MAX_CONSECUTIVE_FAILURES: u32 = 60;
+
+/// Unanswered health checks in a row before a device counts as disconnected.
+pub(super) const MAX_MISSED_PINGS: u32 = 3;
+
+/// Runner tick interval when the device keeps up (~60 FPS).
+const BASE_FRAME_INTERVAL: Duration = Duration::from_micros(16666);
+/// Slowest the adaptive throttle goes.
+const MAX_FRAME_INTERVAL: Duration = Duration::from_secs(1);
+/// Transport writes per throttle decision.
+const THROTTLE_WINDOW: u64 = 30;
+/// Drop-free windows before the throttle tries a faster rate again.
+const THROTTLE_PROBE_WINDOWS: u32 = 10;
+
+/// Lower the runner's tick rate to what rate-limited transports accept.
+static ADAPTIVE_FRAME_RATE: AtomicBool = AtomicBool::new(true);
+
+pub fn set_adaptive_frame_rate_enabled(enabled: bool) {
+    ADAPTIVE_FRAME_RATE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn adaptive_frame_rate_enabled() -> bool {
+    ADAPTIVE_FRAME_RATE.load(Ordering::Relaxed)
+}
+
+/// Longest soft-start ramp accepted.
+pub const MAX_SOFT_START_MS: u32 = 10_000;
+
+/// Strongest frame smoothing accepted; at 1.0 the frame would never change.
+pub const MAX_SMOOTHING: f32 = 0.98;
+
+/// Longest gap between frames that smoothing eases over, so a change after a pause (static
+/// effects send a frame a second) is still eased rather than jumped to.
+const MAX_SMOOTHING_STEP: Duration = Duration::from_millis(100);
+
+/// Full-white draw of a WS2812-class LED, for user power limits on drivers that don't
+/// report their own.
+const DEFAULT_MA_PER_LED_FULL_WHITE: f32 = 60.0;
+
+/// Default brightness ramp for effects that start on a dark output, in ms. 0 disables it.
+static SOFT_START_MS: AtomicU32 = AtomicU32::new(0);
+
+/// Applies to targets activated from now on; a ramp in progress keeps its duration.
+pub fn set_soft_start_ms(ms: u32) {
+    SOFT_START_MS.store(ms.min(MAX_SOFT_START_MS), Ordering::Relaxed);
+}
+
+pub fn soft_start_ms() -> u32 {
+    SOFT_START_MS.load(Ordering::Relaxed)
+}
+
+/// What the runner should do after a frame write.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum WriteOutcome {
+    Unchanged,
+    /// Health flipped (ok → error or error → ok); announce it.
+    StatusChanged(DeviceStatus),
+    /// Failure persisted; the controller has been disconnected and the runner must stop.
+    GaveUp(DeviceStatus),
+    /// The reported frame loss moved by at least `PERCENT_REPORT_STEP` points.
+    LossChanged(DeviceStatus),
+}
+
+/// Loss and power limit changes smaller than this (in percentage points) aren't recorded,
+/// so a noisy link or a flickering effect doesn't emit a status event every frame.
+const PERCENT_REPORT_STEP: u8 = 5;
+
+fn percent_change_is_notable(previous: Option<u8>, current: Option<u8>) -> bool {
+    match (previous, current) {
+        (Some(previous), Some(current)) => previous.abs_diff(current) >= PERCENT_REPORT_STEP,
+        (previous, current) => previous != current,
+    }
+}
+
+/// Write one frame and fold the result into the shared device status.
+///
+/// Transient errors are only recorded; after `MAX_CONSECUTIVE_FAILURES` in a row the
+/// controller is blanked and disconnected so its resources are released before the runner exits.
+pub(super) fn write_frame(
+    controller: &ControllerRef,
+    colors: &[Color],
+    status: &Mutex<DeviceStatus>,
+) -> WriteOutcome {
+    let mut c = controller.lock().unwrap();
+    let result = c.update(colors);
+    let frame_loss = c.frame_loss().map(|loss| (loss.clamp(0.0, 1.0) * 100.0).round() as u8);
+
+    let mut st = status.lock().unwrap();
+    match result {
+        Ok(()) => {
+            // A write reaching the socket says nothing about a device that stopped answering pings.
+            let recovered = st.health != DeviceHealth::Ok && st.missed_pings < MAX_MISSED_PINGS;
+            st.consecutive_failures = 0;
+            let loss_changed = percent_change_is_notable(st.frame_loss_percent, frame_loss);
+            if loss_changed {
+                st.frame_loss_percent = frame_loss;
+            }
+            if recovered {
+                st.health = DeviceHealth::Ok;
+                st.last_error = None;
+                return WriteOutcome::StatusChanged(st.clone());
+            }
+            if loss_changed {
+                return WriteOutcome::LossChanged(st.clone());
+            }
+            WriteOutcome::Unchanged
+        }
+        Err(err) => {
+            st.consecutive_failures = st.consecutive_failures.saturating_add(1);
+            let changed = st.health == DeviceHealth::Ok;
+            st.last_error = Some(err);
+
+            if st.consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+                st.health = DeviceHealth::Disconnected;
+                let snapshot = st.clone();
+                drop(st);
+                // Best effort: the link is most likely gone, but a flaky one may still take it.
+                let _ = c.clear();
+                if let Err(err) = c.disconnect() {
+                    log::warn!(err:display = err; "[runner] Disconnect after persistent failures failed");
+                }
+                return WriteOutcome::GaveUp(snapshot);
+            }
+
+            if changed {
+                st.health = DeviceHealth::Error;
+                return WriteOutcome::StatusChanged(st.clone());
+            }
+            WriteOutcome::Unchanged
+        }
+    }
+}
+
+/// Fold a health check answer into the device status. Returns the new status when the
+/// device went offline or came back.
+///
+/// Only devices that still count as connected are marked offline, so a runner that gave up
+/// on its device isn't undone by a later answer.
+pub(super) fn record_ping(status: &Mutex<DeviceStatus>, answered: bool) -> Option<DeviceStatus> {
+    let mut st = status.lock().unwrap();
+    let offline = st.missed_pings >= MAX_MISSED_PINGS;
+    if answered {
+        st.missed_pings = 0;
+        if !offline {
+            return None;
+        }
+        st.health = DeviceHealth::Ok;
+        st.last_error = None;
+        st.consecutive_failures = 0;
+        return Some(st.clone());
+    }
+    if offline || st.health == DeviceHealth::Disconnected {
+        return None;
+    }
+    st.missed_pings += 1;
+    if st.missed_pings < MAX_MISSED_PINGS {
+        return None;
+    }
+    st.health = DeviceHealth::Disconnected;
+    st.last_error = Some(format!("No answer to {} health checks", MAX_MISSED_PINGS));
+    Some(st.clone())
+}
+
+/// Fraction of its brightness `frame` can be shown at within `budget`; 1.0 when it fits.
+fn power_limit_scale(frame: &[Color], budget: PowerBudget) -> f32 {
+    let ma_per_channel = budget.ma_per_led_full_white / 3.0;
+    let levels: u64 = frame
+        .iter()
+        .map(|c| c.r as u64 + c.g as u64 + c.b as u64)
+        .sum();
+    let estimate = levels as f32 / 255.0 * ma_per_channel;
+    let max = budget.max_milliamps as f32;
+    if estimate > max {
+        max / estimate
+    } else {
+        1.0
+    }
+}
+
+/// The tighter of the driver's budget and the user's limit. A user limit on a driver that
+/// reports nothing assumes typical 5 V addressable LEDs.
+fn effective_power_budget(
+    driver: Option<PowerBudget>,
+    user_limit_ma: Option<u32>,
+) -> Option<PowerBudget> {
+    let Some(limit) = user_limit_ma else {
+        return driver;
+    };
+    let ma_per_led_full_white = driver.map_or(DEFAULT_MA_PER_LED_FULL_WHITE, |d| {
+        d.ma_per_led_full_white
+    });
+    Some(PowerBudget {
+        max_milliamps: driver.map_or(limit, |d| d.max_milliamps.min(limit)),
+        ma_per_led_full_white,
+    })
+}
+
+/// Dim `frame` to fit `budget`, rounding down so it never ends up just over. Returns the
+/// scale applied, in percent, while limiting.
+fn apply_power_budget(frame: &mut [Color], budget: Option<PowerBudget>) -> Option<u8> {
+    let scale = budget.map_or(1.0, |budget| power_limit_scale(frame, budget));
+    if scale >= 1.0 {
+        return None;
+    }
+    let dim = |c: u8| (c as f32 * scale) as u8;
+    for led in frame.iter_mut() {
+        *led = Color {
+            r: dim(led.r),
+            g: dim(led.g),
+            b: dim(led.b),
+        };
+    }
+    Some((scale * 100.0).round() as u8)
+}
+
+/// Record the power limit a frame was sent with. Returns the new status when the change is
+/// worth announcing.
+fn record_power_limit(status: &Mutex<DeviceStatus>, percent: Option<u8>) -> Option<DeviceStatus> {
+    let mut st = status.lock().unwrap();
+    if !percent_change_is_notable(st.power_limit_percent, percent) {
+        return None;
+    }
+    st.power_limit_percent = percent;
+    Some(st.clone())
+}
+
+/// Eases the composed device frame towards each new one.
+///
+/// The previous frame is kept unrounded, so a slow approach doesn't stall a level short of
+/// its target, and is blended out by time rather than by frame so a link that takes fewer
+/// frames eases just as long.
+#[derive(Default)]
+struct FrameSmoother {
+    previous: Vec<[f32; 3]>,
+    last: Option<Instant>,
+    settling: bool,
+}
+
+impl FrameSmoother {
+    /// Blend `frame` in place with the frame before it, keeping `smoothing` of the old one
+    /// per 60 Hz frame. A frame of another length starts over from itself.
+    fn apply(&mut self, frame: &mut [Color], smoothing: f32, now: Instant) {
+        let step = self.last.replace(now).map(|last| now.saturating_duration_since(last));
+        let channels = |c: &Color| [c.r as f32, c.g as f32, c.b as f32];
+        self.settling = false;
+        if smoothing <= 0.0 {
+            self.previous.clear();
+            return;
+        }
+        let Some(step) = step.filter(|_| self.previous.len() == frame.len()) else {
+            self.previous.clear();
+            self.previous.extend(frame.iter().map(channels));
+            return;
+        };
+
+        let steps =
+            step.min(MAX_SMOOTHING_STEP).as_secs_f32() / BASE_FRAME_INTERVAL.as_secs_f32();
+        let keep = smoothing.min(MAX_SMOOTHING).powf(steps);
+        for (led, previous) in frame.iter_mut().zip(&mut self.previous) {
+            for (level, target) in previous.iter_mut().zip(channels(led)) {
+                *level = target + (*level - target) * keep;
+                if (*level - target).abs() < 0.5 {
+                    *level = target;
+                } else {
+                    self.settling = true;
+                }
+            }
+            let [r, g, b] = previous.map(|level| level.round() as u8);
+            *led = Color { r, g, b };
+        }
+    }
+
+    /// Whether the last frame was still short of its target, so the next one shouldn't wait
+    /// for the effects' own frame rate.
+    fn is_settling(&self) -> bool {
+        self.settling
+    }
+}
+
+/// The level every rendering target shares, `None` when they differ or nothing renders.
+fn uniform_brightness(levels: impl IntoIterator<Item = u8>) -> Option<u8> {
+    let mut levels = levels.into_iter().map(|level| level.min(100));
+    let first = levels.next()?;
+    levels.all(|level| level == first).then_some(first)
+}
+
+/// Hands brightness to the controller's own dimming instead of scaling RGB, when it can.
+#[derive(Default)]
+pub(super) struct HardwareBrightness {
+    /// Level the controller was last set to.
+    applied: Option<u8>,
+}
+
+impl HardwareBrightness {
+    /// Bring the controller's brightness in line with `uniform` (the level shared by every
+    /// target, if any). Returns whether the hardware now handles dimming, so frames should
+    /// go out unscaled; otherwise the hardware is held at 100 and the caller scales.
+    pub(super) fn route(&mut self, controller: &ControllerRef, uniform: Option<u8>) -> bool {
+        let mut c = controller.lock().unwrap();
+        if !c.has_hardware_brightness() {
+            return false;
+        }
+
+        let level = uniform.unwrap_or(100);
+        if self.applied != Some(level) {
+            match c.set_brightness(level) {
+                Ok(()) => self.applied = Some(level),
+                Err(err) => {
+                    log::warn!(level, err:display = err; "[runner] Hardware brightness failed, scaling in software");
+                    self.applied = None;
+                    return false;
+                }
+            }
+        }
+        uniform.is_some()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 struct TargetKey {
     output_id: String,
@@ -29,6 +356,8 @@ struct TargetRuntime {
     origin_rev: u64,
     width: usize,
     height: usize,
+    /// Spatial layout last handed to the effect (and to `pending`).
+    positions: Option<Vec<LedPosition>>,
     effect: Box<dyn Effect>,
     /// Final rendered buffer (after optional transitions), in virtual order.   
     buffer: Vec<Color>,
@@ -38,6 +367,36 @@ struct TargetRuntime {
     pending: Option<PendingEffect>,
     ready_wait: Option<ReadyWait>,
     blocked: Option<BlockedSpec>,
+    phase_offset: Duration,
+    overlay: Option<OverlayRuntime>,
+    /// Overlay (effect id, start) that failed to initialize; not retried until it changes.
+    blocked_overlay: Option<(String, Instant)>,
+    /// Capture failure last announced to the frontend for this target.
+    reported_capture_error: Option<CaptureFailure>,
+    /// Ramp length used when this target lights up from dark.
+    soft_start: Duration,
+    /// Start of the brightness ramp in progress, if any.
+    soft_start_from: Option<Instant>,
+    /// Loudness envelope scaling the output, when the scope has audio brightness on.
+    audio_brightness: Option<AudioBrightness>,
+}
+
+/// Secondary effect composited over the primary one.
+///
+/// The overlay takes no part in the ready/transition handshake: it starts rendering as soon
+/// as it's created, and the primary's switch fade applies underneath it.
+struct OverlayRuntime {
+    effect_id: String,
+    origin_started_at: Instant,
+    origin_rev: u64,
+    width: usize,
+    height: usize,
+    opacity: f32,
+    blend: BlendMode,
+    effect: Box<dyn Effect>,
+    buffer: Vec<Color>,
+    /// Primary frame with the overlay blended on top; what actually gets output.
+    composite: Vec<Color>,
 }
 
 struct PendingEffect {
@@ -77,34 +436,58 @@ struct TargetSpec<'a> {
     origin_started_at: Instant,
     origin_rev: u64,
     params: &'a serde_json::Map<String, Value>,
+    positions: Option<&'a [LedPosition]>,
+    overlay: Option<&'a OverlayConfig>,
+    phase_offset: Duration,
+    soft_start: Duration,
+    audio_brightness: Option<&'a AudioBrightnessSettings>,
+}
+
+/// Time base shared by every runner. Effects render from `now - epoch` rather than from when
+/// they were selected, so devices showing the same effect stay in phase.
+static ANIMATION_EPOCH: OnceLock<Instant> = OnceLock::new();
+
+fn animation_time(now: Instant, phase_offset: Duration) -> Duration {
+    let epoch = *ANIMATION_EPOCH.get_or_init(Instant::now);
+    now.saturating_duration_since(epoch) + phase_offset
 }
 
 const EFFECT_SWITCH_FADE_DURATION: Duration = Duration::from_millis(120);
 
+/// Switching away from this effect counts as lighting up from dark for the soft-start.
+const TURN_OFF_EFFECT_ID: &str = "turn_off";
+
 impl TargetRuntime {
     fn create_configured_effect(
         effect_id: &str,
         width: usize,
         height: usize,
         params: &serde_json::Map<String, Value>,
+        positions: Option<&[LedPosition]>,
     ) -> Result<Box<dyn Effect>, String> {
         let mut effect = create_effect(effect_id)
             .ok_or_else(|| format!("Effect '{}' not found", effect_id))?;
         effect.resize(width, height);
+        effect.set_positions(positions);
         effect.update_params(Value::Object(params.clone()));
         Ok(effect)
     }
 
-    fn new(
-        effect_id: &str,
-        width: usize,
-        height: usize,
-        origin_started_at: Instant,
-        origin_rev: u64,
-        params: &serde_json::Map<String, Value>,
-        now: Instant,
-    ) -> Result<Self, String> {
-        let effect = Self::create_configured_effect(effect_id, width, height, params)?;
+    fn new(spec: TargetSpec<'_>, now: Instant) -> Result<Self, String> {
+        let TargetSpec {
+            effect_id,
+            width,
+            height,
+            origin_started_at,
+            origin_rev,
+            params,
+            positions,
+            overlay,
+            phase_offset,
+            soft_start,
+            audio_brightness,
+        } = spec;
+        let effect = Self::create_configured_effect(effect_id, width, height, params, positions)?;
 
         let len = width.checked_mul(height).unwrap_or(0).max(1);
         let fade_from_black = EffectTransition {
@@ -113,12 +496,13 @@ impl TargetRuntime {
             from: vec![Color::default(); len],
         };
 
-        Ok(Self {
+        let mut runtime = Self {
             effect_id: effect_id.to_string(),
             origin_started_at,
             origin_rev,
             width,
             height,
+            positions: positions.map(<[LedPosition]>::to_vec),
             effect,
             buffer: vec![Color::default(); len],
             effect_buffer: Vec::new(),
@@ -126,7 +510,167 @@ impl TargetRuntime {
             pending: None,
             ready_wait: None,
             blocked: None,
-        })
+            phase_offset,
+            overlay: None,
+            blocked_overlay: None,
+            reported_capture_error: None,
+            soft_start,
+            // A new target was dark before (app start, device plugged in, first effect).
+            soft_start_from: (!soft_start.is_zero()).then_some(now),
+            audio_brightness: None,
+        };
+        runtime.sync_overlay(overlay, origin_rev, width, height);
+        runtime.sync_audio_brightness(audio_brightness);
+        Ok(runtime)
+    }
+
+    /// Bring the overlay runtime in line with the resolved overlay config.
+    fn sync_overlay(
+        &mut self,
+        config: Option<&OverlayConfig>,
+        origin_rev: u64,
+        width: usize,
+        height: usize,
+    ) {
+        let Some(config) = config else {
+            self.overlay = None;
+            self.blocked_overlay = None;
+            return;
+        };
+
+        if let Some(overlay) = &mut self.overlay {
+            if overlay.effect_id == config.effect_id
+                && overlay.origin_started_at == config.started_at
+            {
+                if overlay.width != width || overlay.height != height {
+                    overlay.width = width;
+                    overlay.height = height;
+                    overlay.effect.resize(width, height);
+                }
+                if overlay.origin_rev != origin_rev {
+                    overlay.origin_rev = origin_rev;
+                    overlay.opacity = config.opacity;
+                    overlay.blend = config.blend;
+                    overlay
+                        .effect
+                        .update_params(Value::Object(config.params.clone()));
+                }
+                return;
+            }
+        }
+
+        let key = (config.effect_id.clone(), config.started_at);
+        if self.blocked_overlay.as_ref() == Some(&key) {
+            return;
+        }
+
+        match Self::create_configured_effect(
+            &config.effect_id,
+            width,
+            height,
+            &config.params,
+            self.positions.as_deref(),
+        ) {
+            Ok(effect) => {
+                self.blocked_overlay = None;
+                self.overlay = Some(OverlayRuntime {
+                    effect_id: config.effect_id.clone(),
+                    origin_started_at: config.started_at,
+                    origin_rev,
+                    width,
+                    height,
+                    opacity: config.opacity,
+                    blend: config.blend,
+                    effect,
+                    buffer: Vec::new(),
+                    composite: Vec::new(),
+                });
+            }
+            Err(err) => {
+                log::warn!(
+                    effect_id = config.effect_id.as_str(),
+                    err:display = err;
+                    "[runner] Failed to create overlay effect"
+                );
+                self.overlay = None;
+                self.blocked_overlay = Some(key);
+            }
+        }
+    }
+
+    fn sync_audio_brightness(&mut self, settings: Option<&AudioBrightnessSettings>) {
+        match (settings, &mut self.audio_brightness) {
+            (None, _) => self.audio_brightness = None,
+            (Some(settings), Some(current)) => {
+                if current.settings() != settings {
+                    current.set_settings(*settings);
+                }
+            }
+            (Some(settings), None) => self.audio_brightness = Some(AudioBrightness::new(*settings)),
+        }
+    }
+
+    /// The effect's frame rate hint. While a switch, fade, soft start, overlay or audio
+    /// brightness is in progress the target needs the full rate, whatever the effect itself
+    /// asks for.
+    fn preferred_fps(&self) -> Option<u32> {
+        let animating = self.transition.is_some()
+            || self.pending.is_some()
+            || self.ready_wait.is_some()
+            || self.soft_start_from.is_some()
+            || self.overlay.is_some()
+            || self.audio_brightness.is_some();
+        if animating {
+            return None;
+        }
+        self.effect.preferred_fps()
+    }
+
+    /// Rendered frame for this target: the primary effect, with the overlay on top if any.
+    fn output(&self) -> &[Color] {
+        self.overlay
+            .as_ref()
+            .map_or(&self.buffer, |overlay| &overlay.composite)
+    }
+
+    /// The current capture failure if it differs from the one last reported. The outer
+    /// `Some(None)` means the capture has recovered.
+    fn capture_error_change(&mut self) -> Option<Option<CaptureFailure>> {
+        let current = self.effect.capture_error().or_else(|| {
+            self.overlay
+                .as_ref()
+                .and_then(|overlay| overlay.effect.capture_error())
+        });
+        if current == self.reported_capture_error {
+            return None;
+        }
+        self.reported_capture_error = current.clone();
+        Some(current)
+    }
+
+    fn tick_overlay(&mut self, now: Instant) {
+        let Some(overlay) = &mut self.overlay else {
+            return;
+        };
+
+        let len = self.buffer.len();
+        if overlay.buffer.len() != len {
+            overlay.buffer.resize(len, Color::default());
+            overlay.composite.resize(len, Color::default());
+        }
+
+        overlay
+            .effect
+            .tick(animation_time(now, self.phase_offset), &mut overlay.buffer);
+
+        for ((out, base), top) in overlay
+            .composite
+            .iter_mut()
+            .zip(&self.buffer)
+            .zip(&overlay.buffer)
+        {
+            *out = blend_color(*base, *top, overlay.blend, overlay.opacity);
+        }
     }
 
     fn ensure_updated(
@@ -136,6 +680,22 @@ impl TargetRuntime {
         target: &TargetKey,
         switch_tx: &flume::Sender<super::SwitchEvent>,
     ) -> Result<(), String> {
+        if self.positions.as_deref() != spec.positions {
+            self.positions = spec.positions.map(<[LedPosition]>::to_vec);
+            self.effect.set_positions(spec.positions);
+            if let Some(pending) = &mut self.pending {
+                pending.effect.set_positions(spec.positions);
+            }
+            if let Some(overlay) = &mut self.overlay {
+                overlay.effect.set_positions(spec.positions);
+            }
+        }
+
+        self.phase_offset = spec.phase_offset;
+        self.soft_start = spec.soft_start;
+        self.sync_overlay(spec.overlay, spec.origin_rev, spec.width, spec.height);
+        self.sync_audio_brightness(spec.audio_brightness);
+
         let current_matches = self.effect_id == spec.effect_id
             && self.origin_started_at == spec.origin_started_at
             && self.width == spec.width
@@ -179,8 +739,13 @@ impl TargetRuntime {
         });
 
         if !pending_matches {
-            match Self::create_configured_effect(spec.effect_id, spec.width, spec.height, spec.params)
-            {
+            match Self::create_configured_effect(
+                spec.effect_id,
+                spec.width,
+                spec.height,
+                spec.params,
+                spec.positions,
+            ) {
                 Ok(effect) => {
                     let len = spec.width.checked_mul(spec.height).unwrap_or(0).max(1);
                     self.pending = Some(PendingEffect {
@@ -240,6 +805,9 @@ impl TargetRuntime {
         ) {
             let pending = self.pending.take().unwrap();
             let mut from = std::mem::take(&mut self.buffer);
+            if self.effect_id == TURN_OFF_EFFECT_ID && !self.soft_start.is_zero() {
+                self.soft_start_from = Some(now);
+            }
 
             let commit_len = pending.width.checked_mul(pending.height).unwrap_or(0).max(1);
             if from.len() != commit_len {
@@ -264,10 +832,11 @@ impl TargetRuntime {
             self.buffer.resize(len, Color::default());
         }
 
-        let elapsed = now.duration_since(self.origin_started_at);
+        let time = animation_time(now, self.phase_offset);
 
         let Some(transition) = &mut self.transition else {
-            self.effect.tick(elapsed, &mut self.buffer);
+            self.effect.tick(time, &mut self.buffer);
+            self.tick_overlay(now);
 
             self.process_ready_events(now, target, switch_tx);
             self.tick_pending(now, target, switch_tx);
@@ -279,7 +848,7 @@ impl TargetRuntime {
         }
 
         // Produce the new effect frame.
-        self.effect.tick(elapsed, &mut self.effect_buffer);
+        self.effect.tick(time, &mut self.effect_buffer);
 
         // Blend with the previous frame.
         let t = if transition.duration.is_zero() {
@@ -303,11 +872,33 @@ impl TargetRuntime {
             self.transition = None;
             std::mem::swap(&mut self.buffer, &mut self.effect_buffer);
         }
+        self.tick_overlay(now);
 
         self.process_ready_events(now, target, switch_tx);
         self.tick_pending(now, target, switch_tx);
     }
 
+    /// Brightness multiplier of the soft-start ramp at `now`, 1.0 once it is over.
+    fn soft_start_level(&mut self, now: Instant) -> f32 {
+        let Some(from) = self.soft_start_from else {
+            return 1.0;
+        };
+        let t = now.saturating_duration_since(from).as_secs_f32()
+            / self.soft_start.as_secs_f32().max(f32::EPSILON);
+        if t >= 1.0 {
+            self.soft_start_from = None;
+            return 1.0;
+        }
+        t
+    }
+
+    /// Brightness multiplier from the audio envelope at `now`, 1.0 when it's off.
+    fn audio_level(&mut self, now: Instant) -> f32 {
+        self.audio_brightness
+            .as_mut()
+            .map_or(1.0, |audio| audio.multiplier(now))
+    }
+
     fn tick_pending(
         &mut self,
         now: Instant,
@@ -350,8 +941,9 @@ impl TargetRuntime {
             pending.buffer.resize(len, Color::default());
         }
 
-        let elapsed = now.duration_since(pending.origin_started_at);
-        pending.effect.tick(elapsed, &mut pending.buffer);
+        pending
+            .effect
+            .tick(animation_time(now, self.phase_offset), &mut pending.buffer);
 
         if pending.effect.is_ready() {
             pending.ready_to_commit = true;
@@ -402,6 +994,60 @@ impl TargetRuntime {
     }
 }
 
+/// Start, restart or stop the device's runner to match its config.
+///
+/// Manager methods that only edit config leave the runner to this, so the api layer calls
+/// it after them instead of each handing an `AppHandle` down.
+pub fn ensure_runner_state(
+    manager: &LightingManager,
+    port: &str,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let mut devices = manager.devices.lock().unwrap();
+    manager.ensure_runner_state_locked(&mut devices, port, app_handle)
+}
+
+/// `device-lost`; see [`emit_reconnected`] for the way back.
+pub(crate) fn emit_connection(app_handle: &AppHandle, event: &str, port: &str) {
+    let _ = app_handle.emit(event, serde_json::json!({ "port": port }));
+}
+
+/// `device-reconnected` for a device that may be back under another port, with the port the
+/// UI knew it by.
+pub(crate) fn emit_reconnected(app_handle: &AppHandle, device: &ReconnectedDevice) {
+    let _ = app_handle.emit(
+        "device-reconnected",
+        serde_json::json!({ "port": device.port, "previousPort": device.previous_port }),
+    );
+}
+
+pub(crate) fn emit_status(app_handle: &AppHandle, port: &str, status: &DeviceStatus) {
+    let _ = app_handle.emit(
+        "device-status-changed",
+        serde_json::json!({
+            "port": port,
+            "status": status,
+        }),
+    );
+}
+
+fn emit_capture_error(
+    app_handle: &AppHandle,
+    port: &str,
+    key: &TargetKey,
+    error: Option<&CaptureFailure>,
+) {
+    let _ = app_handle.emit(
+        "capture-error",
+        serde_json::json!({
+            "port": port,
+            "outputId": key.output_id,
+            "segmentId": key.segment_id,
+            "error": error,
+        }),
+    );
+}
+
 fn lerp_color(from: Color, to: Color, t: f32) -> Color {
     fn lerp_u8(a: u8, b: u8, t: f32) -> u8 {
         let a = a as f32;
@@ -416,16 +1062,185 @@ fn lerp_color(from: Color, to: Color, t: f32) -> Color {
     }
 }
 
+/// Blend `overlay` onto `base` per channel, then mix the result in by `opacity`.
+pub(super) fn blend_color(base: Color, overlay: Color, mode: BlendMode, opacity: f32) -> Color {
+    let channel = |a: u8, b: u8| match mode {
+        BlendMode::Normal => b,
+        BlendMode::Add => a.saturating_add(b),
+        BlendMode::Screen => screen_blend(a, b),
+        BlendMode::Multiply => ((a as u16 * b as u16 + 127) / 255) as u8,
+    };
+    let blended = Color {
+        r: channel(base.r, overlay.r),
+        g: channel(base.g, overlay.g),
+        b: channel(base.b, overlay.b),
+    };
+    lerp_color(base, blended, opacity.clamp(0.0, 1.0))
+}
+
+/// Interval between frames for targets with these frame rate hints: as short as the most
+/// demanding target needs, and the base interval as soon as one target has no hint.
+fn hinted_frame_interval(hints: impl IntoIterator<Item = Option<u32>>) -> Duration {
+    let mut fastest: Option<u32> = None;
+    for hint in hints {
+        let Some(fps) = hint else {
+            return BASE_FRAME_INTERVAL;
+        };
+        fastest = fastest.max(Some(fps));
+    }
+    fastest.map_or(BASE_FRAME_INTERVAL, |fps| {
+        (Duration::from_secs(1) / fps.max(1)).clamp(BASE_FRAME_INTERVAL, MAX_FRAME_INTERVAL)
+    })
+}
+
+/// Interval between frames a controller that takes at most `max_hz` can keep up with.
+fn device_frame_interval(max_hz: Option<f32>) -> Duration {
+    max_hz.filter(|hz| *hz > 0.0).map_or(BASE_FRAME_INTERVAL, |hz| {
+        Duration::from_secs_f32(1.0 / hz).clamp(BASE_FRAME_INTERVAL, MAX_FRAME_INTERVAL)
+    })
+}
+
+/// What a target's frame depends on besides time. A change renders the next frame right
+/// away instead of waiting out a slow effect's frame interval.
+#[derive(PartialEq)]
+struct TaskInputs {
+    key: TargetKey,
+    effect: Option<(String, u64)>,
+    brightness: u8,
+    leds_count: usize,
+    physical_offset: usize,
+    reversed: bool,
+    transform: MatrixTransform,
+}
+
+impl From<&TargetTask> for TaskInputs {
+    fn from(task: &TargetTask) -> Self {
+        Self {
+            key: task.key.clone(),
+            effect: task
+                .resolved
+                .as_ref()
+                .map(|resolved| (resolved.effect_id.clone(), resolved.origin_rev)),
+            brightness: task.brightness,
+            leds_count: task.leds_count,
+            physical_offset: task.physical_offset,
+            reversed: task.reversed,
+            transform: task.transform,
+        }
+    }
+}
+
+/// Measures the frame rate a device actually receives and, when adaptive throttling is on,
+/// stretches the tick interval while the transport keeps dropping frames.
+///
+/// Once throttled there are no drops left to measure, so after a quiet stretch the
+/// interval is shortened a little; if the hardware can't take it the next window with
+/// drops throttles it back.
+struct FramePacer {
+    interval: Duration,
+    last_metrics: Option<WriteMetrics>,
+    window: WriteMetrics,
+    window_started: Instant,
+    quiet_windows: u32,
+    delivered: u32,
+    rate_started: Instant,
+    frame_rate: f32,
+}
+
+impl FramePacer {
+    fn new(now: Instant) -> Self {
+        Self {
+            interval: BASE_FRAME_INTERVAL,
+            last_metrics: None,
+            window: WriteMetrics::default(),
+            window_started: now,
+            quiet_windows: 0,
+            delivered: 0,
+            rate_started: now,
+            frame_rate: 0.0,
+        }
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Frames per second that reached the device over the last second.
+    fn frame_rate(&self) -> f32 {
+        self.frame_rate
+    }
+
+    /// Account for one tick. `metrics` are the controller's cumulative counters, `written`
+    /// whether its update call succeeded.
+    fn record(&mut self, now: Instant, metrics: Option<WriteMetrics>, written: bool, adaptive: bool) {
+        let step = match (metrics, self.last_metrics) {
+            (Some(current), Some(last)) => WriteMetrics {
+                sent: current.sent.saturating_sub(last.sent),
+                dropped: current.dropped.saturating_sub(last.dropped),
+            },
+            (Some(current), None) => current,
+            // Without counters every successful update counts as delivered.
+            (None, _) => WriteMetrics {
+                sent: written as u64,
+                dropped: 0,
+            },
+        };
+        self.last_metrics = metrics;
+
+        self.delivered += step.sent as u32;
+        let elapsed = now.duration_since(self.rate_started);
+        if elapsed >= Duration::from_secs(1) {
+            self.frame_rate = self.delivered as f32 / elapsed.as_secs_f32();
+            self.delivered = 0;
+            self.rate_started = now;
+        }
+
+        if !adaptive || metrics.is_none() {
+            self.interval = BASE_FRAME_INTERVAL;
+            return;
+        }
+
+        self.window.sent += step.sent;
+        self.window.dropped += step.dropped;
+        if self.window.sent + self.window.dropped < THROTTLE_WINDOW {
+            return;
+        }
+
+        let window = std::mem::take(&mut self.window);
+        let span = now.duration_since(self.window_started);
+        self.window_started = now;
+
+        if window.dropped * 4 >= window.sent + window.dropped {
+            // A quarter or more dropped: tick at the rate the transport accepted, plus some
+            // slack for sleep jitter.
+            let accepted = span.mul_f64(1.05) / window.sent.max(1) as u32;
+            self.interval = accepted.clamp(self.interval, MAX_FRAME_INTERVAL);
+            self.quiet_windows = 0;
+        } else if window.dropped == 0 && self.interval > BASE_FRAME_INTERVAL {
+            self.quiet_windows += 1;
+            if self.quiet_windows >= THROTTLE_PROBE_WINDOWS {
+                self.interval = self.interval.mul_f64(0.9).max(BASE_FRAME_INTERVAL);
+                self.quiet_windows = 0;
+            }
+        }
+    }
+}
+
 pub struct DeviceRunner {
     running: Arc<AtomicBool>,
     thread: Option<JoinHandle<()>>,
 }
 
 impl DeviceRunner {
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn start(
         port: String,
         controller: ControllerRef,
         config: Arc<Mutex<DeviceConfig>>,
+        status: Arc<Mutex<DeviceStatus>>,
+        frame_rate: Arc<AtomicU32>,
+        last_frame: Arc<Mutex<Vec<Color>>>,
+        recorder: Arc<Mutex<Option<FrameRecorder>>>,
         app_handle: AppHandle,
         switch_tx: flume::Sender<super::SwitchEvent>,
     ) -> Result<Self, String> {
@@ -433,133 +1248,25 @@ impl DeviceRunner {
         let running_thread = running.clone();
 
         let thread = thread::spawn(move || {
-            let frame_duration = Duration::from_micros(16666); // ~60 FPS
             let mut next_frame = Instant::now();
+            let mut pacer = FramePacer::new(next_frame);
 
             let mut target_runtimes: HashMap<TargetKey, TargetRuntime> = HashMap::new();
             let mut device_buffer: Vec<Color> = Vec::new();
+            let mut hardware_brightness = HardwareBrightness::default();
+            let mut smoother = FrameSmoother::default();
+            let mut last_inputs: Vec<TaskInputs> = Vec::new();
+            let mut last_color_vision = color_vision_mode();
 
             while running_thread.load(Ordering::Relaxed) {
                 let now = Instant::now();
 
                 // Snapshot config for this tick.
-                let (tasks, total_len) = {
+                let (tasks, total_len, color_vision, smoothing, power_limit_ma) = {
                     let cfg = config.lock().unwrap();
-                    let mut tasks = Vec::new();
-
-                    let mut offset: usize = 0;
-                    for out in &cfg.outputs {
-                        let out_len = out.leds_count.max(1);
-
-                        // Segments are user-defined and only meaningful for linear outputs.
-                        // If there are no segments, render the output as a whole.
-                        let use_segments =
-                            out.output_type == SegmentType::Linear && !out.segments.is_empty();
-
-                        if use_segments {
-                            let seg_total =
-                                out.segments.iter().map(|s| s.leds_count).sum::<usize>();
-
-                            // Safety fallback: if segments don't cover the output, ignore them.
-                            if seg_total != out_len {
-                                let resolved = resolve_effect_for_scope(
-                                    &cfg,
-                                    &port,
-                                    Scope::Output {
-                                        output_id: out.id.as_str(),
-                                    },
-                                );
-                                tasks.push(TargetTask {
-                                    key: TargetKey {
-                                        output_id: out.id.clone(),
-                                        segment_id: None,
-                                    },
-                                    layout_type: out.output_type,
-                                    leds_count: out_len,
-                                    matrix: out.matrix.clone(),
-                                    physical_offset: offset,
-                                    brightness: resolve_brightness_for_scope(
-                                        &cfg,
-                                        &port,
-                                        Scope::Output {
-                                            output_id: out.id.as_str(),
-                                        },
-                                    )
-                                    .map(|b| b.value)
-                                    .unwrap_or(100),
-                                    resolved,
-                                });
-                                offset = offset.saturating_add(out_len);
-                            } else {
-                                for seg in &out.segments {
-                                    let resolved = resolve_effect_for_scope(
-                                        &cfg,
-                                        &port,
-                                        Scope::Segment {
-                                            output_id: out.id.as_str(),
-                                            segment_id: seg.id.as_str(),
-                                        },
-                                    );
-
-                                    tasks.push(TargetTask {
-                                        key: TargetKey {
-                                            output_id: out.id.clone(),
-                                            segment_id: Some(seg.id.clone()),
-                                        },
-                                        layout_type: seg.segment_type,
-                                        leds_count: seg.leds_count.max(1),
-                                        matrix: seg.matrix.clone(),
-                                        physical_offset: offset,
-                                        brightness: resolve_brightness_for_scope(
-                                            &cfg,
-                                            &port,
-                                            Scope::Segment {
-                                                output_id: out.id.as_str(),
-                                                segment_id: seg.id.as_str(),
-                                            },
-                                        )
-                                        .map(|b| b.value)
-                                        .unwrap_or(100),
-                                        resolved,
-                                    });
-
-                                    offset = offset.saturating_add(seg.leds_count.max(1));
-                                }
-                            }
-                        } else {
-                            let resolved = resolve_effect_for_scope(
-                                &cfg,
-                                &port,
-                                Scope::Output {
-                                    output_id: out.id.as_str(),
-                                },
-                            );
-                            tasks.push(TargetTask {
-                                key: TargetKey {
-                                    output_id: out.id.clone(),
-                                    segment_id: None,
-                                },
-                                layout_type: out.output_type,
-                                leds_count: out_len,
-                                matrix: out.matrix.clone(),
-                                physical_offset: offset,
-                                brightness: resolve_brightness_for_scope(
-                                    &cfg,
-                                    &port,
-                                    Scope::Output {
-                                        output_id: out.id.as_str(),
-                                    },
-                                )
-                                .map(|b| b.value)
-                                .unwrap_or(100),
-                                resolved,
-                            });
-
-                            offset = offset.saturating_add(out_len);
-                        }
-                    }
-
-                    (tasks, offset)
+                    let (tasks, total_len) = collect_tasks(&cfg, &port);
+                    let color_vision = cfg.color_vision.unwrap_or_else(color_vision_mode);
+                    (tasks, total_len, color_vision, cfg.smoothing, cfg.power_limit_ma)
                 };
 
                 // Prune runtimes for removed targets (config edits).
@@ -567,15 +1274,31 @@ impl DeviceRunner {
                     tasks.iter().map(|t| t.key.clone()).collect();
                 target_runtimes.retain(|k, _| task_keys.contains(k));
 
+                // Between the frames of a slow effect, only a config change renders early.
+                let inputs: Vec<TaskInputs> = tasks.iter().map(TaskInputs::from).collect();
+                if now < next_frame && inputs == last_inputs && color_vision == last_color_vision {
+                    thread::sleep((next_frame - now).min(BASE_FRAME_INTERVAL));
+                    continue;
+                }
+                last_inputs = inputs;
+                last_color_vision = color_vision;
+
                 // Prepare device buffer in physical order.
                 if device_buffer.len() != total_len {
                     device_buffer.resize(total_len, Color::default());
                 }
                 device_buffer.fill(Color::default());
 
+                let hardware_dims = hardware_brightness.route(
+                    &controller,
+                    uniform_brightness(
+                        tasks.iter().filter(|t| t.resolved.is_some()).map(|t| t.brightness),
+                    ),
+                );
+
                 // Render all targets.
-                for task in tasks {
-                    let Some(resolved) = task.resolved else {
+                for mut task in tasks {
+                    let Some(resolved) = task.resolved.take() else {
                         target_runtimes.remove(&task.key);
                         continue;
                     };
@@ -587,17 +1310,22 @@ impl DeviceRunner {
                     }
 
                     let params = resolved.params.clone();
+                    let spec = TargetSpec {
+                        effect_id: &resolved.effect_id,
+                        width,
+                        height,
+                        origin_started_at: resolved.started_at,
+                        origin_rev: resolved.origin_rev,
+                        params: &params,
+                        positions: task.positions.as_deref(),
+                        overlay: resolved.overlay.as_ref(),
+                        phase_offset: resolved.phase_offset,
+                        soft_start: task.soft_start,
+                        audio_brightness: resolved.audio_brightness.as_ref(),
+                    };
                     let entry = target_runtimes.entry(task.key.clone());
                     let runtime = match entry {
                         std::collections::hash_map::Entry::Occupied(mut e) => {
-                            let spec = TargetSpec {
-                                effect_id: &resolved.effect_id,
-                                width,
-                                height,
-                                origin_started_at: resolved.started_at,
-                                origin_rev: resolved.origin_rev,
-                                params: &params,
-                            };
                             if let Err(err) = e
                                 .get_mut()
                                 .ensure_updated(spec, now, &task.key, &switch_tx)
@@ -620,15 +1348,7 @@ impl DeviceRunner {
                             e.into_mut()
                         }
                         std::collections::hash_map::Entry::Vacant(v) => {
-                            match TargetRuntime::new(
-                                &resolved.effect_id,
-                                width,
-                                height,
-                                resolved.started_at,
-                                resolved.origin_rev,
-                                &params,
-                                now,
-                            ) {
+                            match TargetRuntime::new(spec, now) {
                                 Ok(mut rt) => {
                                     if rt.effect.is_ready() {
                                         let _ = switch_tx.send(super::SwitchEvent::Ready {
@@ -671,26 +1391,100 @@ impl DeviceRunner {
 
                     runtime.tick(now, &task.key, &switch_tx);
 
-                    map_segment_into_physical(
-                        &runtime.buffer,
-                        task.layout_type,
-                        task.leds_count,
-                        &task.matrix,
-                        task.physical_offset,
-                        task.brightness,
-                        &mut device_buffer,
+                    if let Some(change) = runtime.capture_error_change() {
+                        let seg = task.key.segment_id.as_deref().unwrap_or("<output>");
+                        match &change {
+                            Some(failure) => log::warn!(
+                                port = port.as_str(),
+                                output_id = task.key.output_id.as_str(),
+                                segment_id = seg,
+                                display_index = failure.display_index,
+                                code = failure.code;
+                                "[runner] Screen capture failing: {}", failure.message
+                            ),
+                            None => log::info!(
+                                port = port.as_str(),
+                                output_id = task.key.output_id.as_str(),
+                                segment_id = seg;
+                                "[runner] Screen capture recovered"
+                            ),
+                        }
+                        emit_capture_error(&app_handle, &port, &task.key, change.as_ref());
+                    }
+
+                    let brightness = if hardware_dims { 100 } else { task.brightness.min(100) };
+                    let scale = brightness as f32 / 100.0
+                        * runtime.soft_start_level(now)
+                        * runtime.audio_level(now);
+                    write_task_into_physical(&task, runtime.output(), scale, &mut device_buffer);
+                }
+
+                // Correct the composed frame, so overlays and brightness are corrected too.
+                correct_frame(&mut device_buffer, color_vision);
+                // Ease over what the device was sent, before the power budget: dimming a
+                // frame that is already dimmed would keep it dark.
+                smoother.apply(&mut device_buffer, smoothing, now);
+
+                // Dim last: the budget is about what the LEDs finally draw.
+                let driver_budget = controller.lock().unwrap().power_budget();
+                let budget = effective_power_budget(driver_budget, power_limit_ma);
+                let power_limit = apply_power_budget(&mut device_buffer, budget);
+                if let Some(status) = record_power_limit(&status, power_limit) {
+                    log::debug!(
+                        port = port.as_str(),
+                        percent = power_limit;
+                        "[runner] Power limit changed"
                     );
+                    emit_status(&app_handle, &port, &status);
                 }
 
-                // Write to hardware.
                 {
-                    let mut c = controller.lock().unwrap();
-                    if let Err(err) = c.update(&device_buffer) {
+                    let mut recording = recorder.lock().unwrap();
+                    if let Some(Err(err)) =
+                        recording.as_mut().map(|r| r.record(now, &device_buffer))
+                    {
                         log::warn!(
                             port = port.as_str(),
                             err:display = err;
-                            "[runner] Controller update failed"
+                            "[runner] Frame recording failed, stopping it"
+                        );
+                        *recording = None;
+                    }
+                }
+
+                // Write to hardware.
+                match write_frame(&controller, &device_buffer, &status) {
+                    WriteOutcome::Unchanged => {}
+                    WriteOutcome::StatusChanged(status) => {
+                        match status.last_error.as_deref().filter(|_| status.health != DeviceHealth::Ok) {
+                            Some(err) => log::warn!(
+                                port = port.as_str(),
+                                err;
+                                "[runner] Controller update failed"
+                            ),
+                            None => log::info!(port = port.as_str(); "[runner] Controller recovered"),
+                        }
+                        emit_status(&app_handle, &port, &status);
+                    }
+                    WriteOutcome::LossChanged(status) => {
+                        log::debug!(
+                            port = port.as_str(),
+                            percent = status.frame_loss_percent;
+                            "[runner] Device frame loss changed"
+                        );
+                        emit_status(&app_handle, &port, &status);
+                    }
+                    WriteOutcome::GaveUp(lost) => {
+                        log::error!(
+                            port = port.as_str(),
+                            failures = lost.consecutive_failures,
+                            err = lost.last_error.as_deref().unwrap_or_default();
+                            "[runner] Controller keeps failing, disconnecting"
                         );
+                        emit_status(&app_handle, &port, &lost);
+                        emit_connection(&app_handle, "device-lost", &port);
+                        // The manager re-probes the driver and starts a new runner once
+                        // the device is back (`LightingManager::reconnect_lost_devices`).
                         break;
                     }
                 }
@@ -704,16 +1498,50 @@ impl DeviceRunner {
                     }),
                 );
 
-                // Timing.
-                next_frame += frame_duration;
+                let (metrics, max_hz) = {
+                    let controller = controller.lock().unwrap();
+                    (controller.write_metrics(), controller.max_update_hz())
+                };
+                let written = status.lock().unwrap().consecutive_failures == 0;
+                if written {
+                    // A dark frame (cleared, turned off) is nothing to bring back.
+                    let mut last = last_frame.lock().unwrap();
+                    if device_buffer.iter().all(|&c| c == Color::default()) {
+                        last.clear();
+                    } else {
+                        last.clone_from(&device_buffer);
+                    }
+                }
+                let previous_interval = pacer.interval();
+                pacer.record(Instant::now(), metrics, written, adaptive_frame_rate_enabled());
+                frame_rate.store(pacer.frame_rate().to_bits(), Ordering::Relaxed);
+                if pacer.interval() != previous_interval {
+                    log::debug!(
+                        port = port.as_str(),
+                        fps = 1.0 / pacer.interval().as_secs_f32();
+                        "[runner] Adjusted tick rate to what the device accepts"
+                    );
+                }
+
+                // Timing: the slowest of what the device accepts, what its transport can
+                // carry and what the effects need.
+                let hinted = hinted_frame_interval(
+                    target_runtimes.values().map(TargetRuntime::preferred_fps),
+                );
+                let hinted = if smoother.is_settling() { BASE_FRAME_INTERVAL } else { hinted };
+                let interval = pacer.interval().max(hinted).max(device_frame_interval(max_hz));
+                // An early frame (config change) restarts the cadence from now.
+                next_frame = next_frame.min(now) + interval;
                 let after = Instant::now();
                 if next_frame > after {
-                    thread::sleep(next_frame - after);
+                    thread::sleep((next_frame - after).min(BASE_FRAME_INTERVAL));
                 } else {
                     next_frame = after;
                     thread::yield_now();
                 }
             }
+            // Without a runner the device no longer shows that frame.
+            last_frame.lock().unwrap().clear();
         });
 
         Ok(Self {
@@ -722,6 +1550,11 @@ impl DeviceRunner {
         })
     }
 
+    /// True once the render thread has exited on its own (e.g. after giving up on the device).
+    pub(super) fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
     pub(super) fn stop(mut self) {
         self.running.store(false, Ordering::Relaxed);
         if let Some(handle) = self.thread.take() {
@@ -739,10 +1572,166 @@ struct TargetTask {
     key: TargetKey,
     layout_type: SegmentType,
     leds_count: usize,
+    /// With `transform` applied, so effects draw in the panel's mounted orientation.
     matrix: Option<MatrixMap>,
+    transform: MatrixTransform,
+    positions: Option<Vec<LedPosition>>,
     physical_offset: usize,
+    /// Written into its physical range back to front.
+    reversed: bool,
     brightness: u8,
     resolved: Option<ResolvedEffect>,
+    soft_start: Duration,
+}
+
+/// One task per render target of `cfg`, in physical order, plus the device's total LED
+/// count. Disabled outputs get no task: their range stays black.
+fn collect_tasks(cfg: &DeviceConfig, port: &str) -> (Vec<TargetTask>, usize) {
+    let mut tasks = Vec::new();
+
+    let mut offset: usize = 0;
+    for out in &cfg.outputs {
+        let out_len = out.leds_count.max(1);
+        if !out.enabled {
+            offset = offset.saturating_add(out_len);
+            continue;
+        }
+        let soft_start = Duration::from_millis(
+            out.soft_start_ms.unwrap_or_else(soft_start_ms).into(),
+        );
+
+        // Segments are user-defined and only meaningful for linear outputs.
+        // If there are no segments, render the output as a whole.
+        let use_segments =
+            out.output_type == SegmentType::Linear && !out.segments.is_empty();
+
+        if use_segments {
+            let seg_total =
+                out.segments.iter().map(|s| s.leds_count).sum::<usize>();
+
+            // Safety fallback: if segments don't cover the output, ignore them.
+            if seg_total != out_len {
+                let resolved = resolve_effect_for_scope(
+                    cfg,
+                    port,
+                    Scope::Output {
+                        output_id: out.id.as_str(),
+                    },
+                );
+                tasks.push(TargetTask {
+                    key: TargetKey {
+                        output_id: out.id.clone(),
+                        segment_id: None,
+                    },
+                    layout_type: out.output_type,
+                    leds_count: out_len,
+                    matrix: out.matrix.as_ref().map(|m| out.transform.apply(m)),
+                    transform: out.transform,
+                    positions: out.positions.clone(),
+                    physical_offset: offset,
+                    reversed: false,
+                    brightness: resolve_brightness_for_scope(
+                        cfg,
+                        port,
+                        Scope::Output {
+                            output_id: out.id.as_str(),
+                        },
+                    )
+                    .map(|b| b.value)
+                    .unwrap_or(100),
+                    resolved,
+                    soft_start,
+                });
+                offset = offset.saturating_add(out_len);
+            } else {
+                let out_offset = offset;
+                for seg in &out.segments {
+                    let resolved = resolve_effect_for_scope(
+                        cfg,
+                        port,
+                        Scope::Segment {
+                            output_id: out.id.as_str(),
+                            segment_id: seg.id.as_str(),
+                        },
+                    );
+
+                    tasks.push(TargetTask {
+                        key: TargetKey {
+                            output_id: out.id.clone(),
+                            segment_id: Some(seg.id.clone()),
+                        },
+                        layout_type: seg.segment_type,
+                        leds_count: seg.leds_count.max(1),
+                        matrix: seg.matrix.clone(),
+                        transform: MatrixTransform::default(),
+                        // Segments see their slice of the output's layout, in the order
+                        // their virtual LEDs land on it.
+                        positions: out.positions.as_ref().and_then(|p| {
+                            let start = offset - out_offset;
+                            let mut slice =
+                                p.get(start..start + seg.leds_count.max(1))?.to_vec();
+                            if seg.reversed {
+                                slice.reverse();
+                            }
+                            Some(slice)
+                        }),
+                        physical_offset: offset,
+                        reversed: seg.reversed,
+                        brightness: resolve_brightness_for_scope(
+                            cfg,
+                            port,
+                            Scope::Segment {
+                                output_id: out.id.as_str(),
+                                segment_id: seg.id.as_str(),
+                            },
+                        )
+                        .map(|b| b.value)
+                        .unwrap_or(100),
+                        resolved,
+                        soft_start,
+                    });
+
+                    offset = offset.saturating_add(seg.leds_count.max(1));
+                }
+            }
+        } else {
+            let resolved = resolve_effect_for_scope(
+                cfg,
+                port,
+                Scope::Output {
+                    output_id: out.id.as_str(),
+                },
+            );
+            tasks.push(TargetTask {
+                key: TargetKey {
+                    output_id: out.id.clone(),
+                    segment_id: None,
+                },
+                layout_type: out.output_type,
+                leds_count: out_len,
+                matrix: out.matrix.as_ref().map(|m| out.transform.apply(m)),
+                transform: out.transform,
+                positions: out.positions.clone(),
+                physical_offset: offset,
+                reversed: false,
+                brightness: resolve_brightness_for_scope(
+                    cfg,
+                    port,
+                    Scope::Output {
+                        output_id: out.id.as_str(),
+                    },
+                )
+                .map(|b| b.value)
+                .unwrap_or(100),
+                resolved,
+                soft_start,
+            });
+
+            offset = offset.saturating_add(out_len);
+        }
+    }
+
+    (tasks, offset)
 }
 
 fn virtual_layout_for_segment(
@@ -764,20 +1753,44 @@ fn virtual_layout_for_segment(
     }
 }
 
+/// Map a task's rendered frame into its physical range, back to front when the task is
+/// `reversed`.
+fn write_task_into_physical(
+    task: &TargetTask,
+    virtual_buffer: &[Color],
+    scale: f32,
+    physical_out: &mut [Color],
+) {
+    map_segment_into_physical(
+        virtual_buffer,
+        task.layout_type,
+        task.leds_count,
+        &task.matrix,
+        task.physical_offset,
+        scale,
+        physical_out,
+    );
+    if task.reversed {
+        let end = (task.physical_offset + task.leds_count).min(physical_out.len());
+        if let Some(range) = physical_out.get_mut(task.physical_offset..end) {
+            range.reverse();
+        }
+    }
+}
+
 fn map_segment_into_physical(
     virtual_buffer: &[Color],
     segment_type: SegmentType,
     leds_count: usize,
     matrix: &Option<MatrixMap>,
     physical_offset: usize,
-    brightness: u8,
+    scale: f32,
     physical_out: &mut [Color],
 ) {
-    let brightness = brightness.min(100);
-    let factor = (brightness as f32 / 100.0).clamp(0.0, 1.0);
+    let factor = scale.clamp(0.0, 1.0);
 
     let apply = |c: Color| -> Color {
-        if brightness >= 100 {
+        if factor >= 1.0 {
             return c;
         }
         Color {
@@ -798,7 +1811,7 @@ fn map_segment_into_physical(
             let end = (physical_offset + len).min(physical_out.len());
             let write_len = end.saturating_sub(physical_offset);
             if write_len > 0 {
-                if brightness >= 100 {
+                if factor >= 1.0 {
                     physical_out[physical_offset..physical_offset + write_len]
                         .copy_from_slice(&virtual_buffer[..write_len]);
                 } else {
@@ -815,7 +1828,7 @@ fn map_segment_into_physical(
                 let end = (physical_offset + len).min(physical_out.len());
                 let write_len = end.saturating_sub(physical_offset);
                 if write_len > 0 {
-                    if brightness >= 100 {
+                    if factor >= 1.0 {
                         physical_out[physical_offset..physical_offset + write_len]
                             .copy_from_slice(&virtual_buffer[..write_len]);
                     } else {
@@ -846,3 +1859,402 @@ fn map_segment_into_physical(
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::inventory::default_params_for_effect;
+    use crate::manager::tests::linear_output;
+
+    fn rgb(colors: &[Color]) -> Vec<(u8, u8, u8)> {
+        colors.iter().map(|c| (c.r, c.g, c.b)).collect()
+    }
+
+    #[test]
+    fn dropped_frames_slow_the_runner_down() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(start);
+        let mut now = start;
+        let mut metrics = WriteMetrics::default();
+
+        // The transport accepts every other frame at 60 FPS, i.e. ~30 FPS.
+        for tick in 0..(THROTTLE_WINDOW * 3) {
+            now += BASE_FRAME_INTERVAL;
+            if tick % 2 == 0 {
+                metrics.sent += 1;
+            } else {
+                metrics.dropped += 1;
+            }
+            pacer.record(now, Some(metrics), true, true);
+        }
+        let throttled = pacer.interval();
+        assert!(
+            throttled >= BASE_FRAME_INTERVAL * 2,
+            "tick interval {:?} should match the ~30 FPS the device accepts",
+            throttled
+        );
+        assert!(pacer.frame_rate() > 25.0 && pacer.frame_rate() < 35.0);
+
+        // Switching the throttle off restores the full rate.
+        pacer.record(now + throttled, Some(metrics), true, false);
+        assert_eq!(pacer.interval(), BASE_FRAME_INTERVAL);
+    }
+
+    #[test]
+    fn slow_transports_cap_the_tick_rate() {
+        use crate::resource::driver::serail_port::RateLimitedSerialPort;
+
+        // 500 LEDs at 115200 baud: 6 + 500 * 3 bytes a frame, ~6 FPS.
+        let serial = RateLimitedSerialPort::compute_min_interval(115_200, 6 + 500 * 3);
+        let max_hz = 1.0 / serial.as_secs_f32();
+        let interval = device_frame_interval(Some(max_hz));
+        let hz = 1.0 / interval.as_secs_f32();
+        assert!((hz - 6.0).abs() < 0.1, "expected ~6 Hz, got {}", hz);
+        // Even for effects that want 60.
+        assert_eq!(hinted_frame_interval([Some(60)]).max(interval), interval);
+
+        assert_eq!(device_frame_interval(None), BASE_FRAME_INTERVAL);
+        assert_eq!(device_frame_interval(Some(1000.0)), BASE_FRAME_INTERVAL);
+        assert_eq!(device_frame_interval(Some(0.0)), BASE_FRAME_INTERVAL);
+    }
+
+    #[test]
+    fn smoothing_eases_frames_at_any_frame_rate() {
+        let white = Color { r: 255, g: 255, b: 255 };
+        let start = Instant::now();
+        // Black, then white for 100 ms sent every `step`.
+        let run = |smoothing: f32, step: Duration| {
+            let mut smoother = FrameSmoother::default();
+            smoother.apply(&mut [Color::default(); 4], smoothing, start);
+            let mut frame = [white; 4];
+            let mut now = start;
+            while now < start + Duration::from_millis(100) {
+                now += step;
+                frame = [white; 4];
+                smoother.apply(&mut frame, smoothing, now);
+            }
+            (frame[0].r, smoother.is_settling())
+        };
+
+        assert_eq!(run(0.0, BASE_FRAME_INTERVAL), (255, false));
+        let (fast, settling) = run(0.9, Duration::from_millis(10));
+        let (slow, _) = run(0.9, Duration::from_millis(50));
+        assert!(settling && fast < 255);
+        assert!(fast.abs_diff(slow) <= 2, "{} vs {}", fast, slow);
+
+        // A frame of another length is sent as it is.
+        let mut smoother = FrameSmoother::default();
+        smoother.apply(&mut [Color::default(); 4], 0.9, start);
+        let mut longer = [white; 6];
+        smoother.apply(&mut longer, 0.9, start + BASE_FRAME_INTERVAL);
+        assert_eq!(rgb(&longer), vec![(255, 255, 255); 6]);
+    }
+
+    #[test]
+    fn frames_over_the_power_budget_are_dimmed() {
+        // 10 LEDs at 60 mA full white draw 600 mA; half of it fits.
+        let budget = PowerBudget {
+            max_milliamps: 300,
+            ma_per_led_full_white: 60.0,
+        };
+        let white = Color {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+        let mut frame = vec![white; 10];
+        assert_eq!(apply_power_budget(&mut frame, Some(budget)), Some(50));
+        assert!(frame.iter().all(|c| c.r == 127 && c.g == 127 && c.b == 127));
+        assert!(power_limit_scale(&frame, budget) >= 1.0);
+
+        let mut dim = vec![white; 5];
+        assert_eq!(apply_power_budget(&mut dim, Some(budget)), None);
+        assert_eq!(apply_power_budget(&mut frame, None), None);
+
+        let status = Mutex::new(DeviceStatus::default());
+        assert_eq!(
+            record_power_limit(&status, Some(50)).map(|s| s.power_limit_percent),
+            Some(Some(50))
+        );
+        assert!(record_power_limit(&status, Some(52)).is_none());
+        assert!(record_power_limit(&status, None).is_some());
+    }
+
+    #[test]
+    fn the_user_power_limit_tightens_the_driver_budget() {
+        let driver = PowerBudget {
+            max_milliamps: 2000,
+            ma_per_led_full_white: 55.0,
+        };
+        assert_eq!(effective_power_budget(Some(driver), None), Some(driver));
+        assert_eq!(effective_power_budget(None, None), None);
+        assert_eq!(
+            effective_power_budget(Some(driver), Some(500)),
+            Some(PowerBudget {
+                max_milliamps: 500,
+                ma_per_led_full_white: 55.0,
+            })
+        );
+        assert_eq!(effective_power_budget(Some(driver), Some(4000)), Some(driver));
+        assert_eq!(
+            effective_power_budget(None, Some(500)),
+            Some(PowerBudget {
+                max_milliamps: 500,
+                ma_per_led_full_white: DEFAULT_MA_PER_LED_FULL_WHITE,
+            })
+        );
+    }
+
+    #[test]
+    fn soft_start_ramps_a_new_target_up_from_black() {
+        let params = default_params_for_effect("monochrome").unwrap();
+        let start = Instant::now();
+        let spec = TargetSpec {
+            effect_id: "monochrome",
+            width: 4,
+            height: 1,
+            origin_started_at: start,
+            origin_rev: 0,
+            params: &params,
+            positions: None,
+            overlay: None,
+            phase_offset: Duration::ZERO,
+            soft_start: Duration::from_millis(500),
+            audio_brightness: None,
+        };
+        let mut runtime = TargetRuntime::new(spec, start).unwrap();
+
+        let (switch_tx, _switch_rx) = flume::unbounded();
+        let key = TargetKey {
+            output_id: "out".to_string(),
+            segment_id: None,
+        };
+        let mut levels = Vec::new();
+        for frame in 0..7 {
+            let now = start + Duration::from_millis(100 * frame);
+            runtime.tick(now, &key, &switch_tx);
+            let mut physical = vec![Color::default(); 4];
+            let scale = runtime.soft_start_level(now);
+            map_segment_into_physical(
+                runtime.output(),
+                SegmentType::Linear,
+                4,
+                &None,
+                0,
+                scale,
+                &mut physical,
+            );
+            levels.push(physical[0].r);
+        }
+
+        assert_eq!(levels[0], 0);
+        assert!(
+            levels[..6].windows(2).all(|pair| pair[0] < pair[1]),
+            "ramp should brighten every frame: {:?}",
+            levels
+        );
+        assert_eq!(levels[5..], [255, 255]);
+    }
+
+    #[test]
+    fn runtimes_on_the_shared_clock_render_identical_frames() {
+        let params = default_params_for_effect("rainbow").unwrap();
+        let spec = |origin_started_at, phase_offset| TargetSpec {
+            effect_id: "rainbow",
+            width: 30,
+            height: 1,
+            origin_started_at,
+            origin_rev: 0,
+            params: &params,
+            positions: None,
+            overlay: None,
+            phase_offset,
+            soft_start: Duration::ZERO,
+            audio_brightness: None,
+        };
+
+        // Two devices selecting the same effect 1.5s apart, plus one deliberately staggered.
+        let first = Instant::now();
+        let second = first + Duration::from_millis(1500);
+        let mut a = TargetRuntime::new(spec(first, Duration::ZERO), first).unwrap();
+        let mut b = TargetRuntime::new(spec(second, Duration::ZERO), second).unwrap();
+        let mut staggered =
+            TargetRuntime::new(spec(second, Duration::from_millis(250)), second).unwrap();
+
+        let (switch_tx, _switch_rx) = flume::unbounded();
+        let key = TargetKey {
+            output_id: "out".to_string(),
+            segment_id: None,
+        };
+        // Well past both fade-ins.
+        let now = second + Duration::from_secs(1);
+        for runtime in [&mut a, &mut b, &mut staggered] {
+            runtime.tick(now, &key, &switch_tx);
+        }
+
+        assert_eq!(rgb(a.output()), rgb(b.output()));
+        assert_ne!(rgb(a.output()), rgb(staggered.output()));
+    }
+
+    #[test]
+    fn static_effects_are_rendered_at_their_hinted_rate() {
+        let params = default_params_for_effect("monochrome").unwrap();
+        let start = Instant::now();
+        let spec = TargetSpec {
+            effect_id: "monochrome",
+            width: 4,
+            height: 1,
+            origin_started_at: start,
+            origin_rev: 0,
+            params: &params,
+            positions: None,
+            overlay: None,
+            phase_offset: Duration::ZERO,
+            soft_start: Duration::ZERO,
+            audio_brightness: None,
+        };
+        let mut runtime = TargetRuntime::new(spec, start).unwrap();
+        let (switch_tx, _switch_rx) = flume::unbounded();
+        let key = TargetKey {
+            output_id: "out".to_string(),
+            segment_id: None,
+        };
+
+        // The fade-in runs at the full rate.
+        runtime.tick(start, &key, &switch_tx);
+        assert_eq!(runtime.preferred_fps(), None);
+        assert_eq!(hinted_frame_interval([runtime.preferred_fps()]), BASE_FRAME_INTERVAL);
+
+        runtime.tick(start + Duration::from_secs(2), &key, &switch_tx);
+        assert_eq!(runtime.preferred_fps(), Some(1));
+        assert_eq!(hinted_frame_interval([runtime.preferred_fps()]), Duration::from_secs(1));
+
+        // Another target's needs win, and an effect without a hint gets the base rate.
+        assert_eq!(hinted_frame_interval([Some(1), Some(30)]), Duration::from_secs(1) / 30);
+        assert_eq!(hinted_frame_interval([Some(1), None]), BASE_FRAME_INTERVAL);
+        assert_eq!(hinted_frame_interval([Some(1000)]), BASE_FRAME_INTERVAL);
+    }
+
+    #[test]
+    fn disabled_output_is_left_black_while_the_others_keep_running() {
+        use crate::manager::LightingManager;
+
+        let mut cfg =
+            DeviceConfig::from_output_defs(vec![linear_output("a", 2), linear_output("b", 1)]);
+        cfg.mode.set_effect("monochrome", Instant::now()).unwrap();
+        cfg.output_mut("a").unwrap().enabled = false;
+
+        let manager = LightingManager::new();
+        assert!(manager.device_has_any_effect(&cfg, "strip"), "runner stays up for 'b'");
+
+        // Nothing renders into a's range (0..2), so it stays at the buffer's black.
+        let (tasks, total_len) = collect_tasks(&cfg, "strip");
+        assert_eq!(total_len, 3);
+        let ranges: Vec<_> = tasks
+            .iter()
+            .map(|t| (t.key.output_id.as_str(), t.physical_offset, t.leds_count))
+            .collect();
+        assert_eq!(ranges, [("b", 2, 1)]);
+        assert!(tasks[0].resolved.is_some());
+
+        cfg.output_mut("b").unwrap().enabled = false;
+        assert!(!manager.device_has_any_effect(&cfg, "strip"));
+    }
+
+    #[test]
+    fn transformed_matrix_outputs_map_effects_in_the_mounted_orientation() {
+        use crate::interface::controller::{OutputCapabilities, OutputPortDefinition, Rotation};
+
+        // 0 1 2
+        // 5 _ 3
+        let panel = MatrixMap {
+            width: 3,
+            height: 2,
+            map: vec![Some(0), Some(1), Some(2), Some(5), None, Some(3)],
+        };
+        let rotated = panel.transformed(Rotation::Deg90, false, false);
+        assert_eq!((rotated.width, rotated.height), (2, 3));
+        assert_eq!(rotated.map, [Some(5), Some(0), None, Some(1), Some(3), Some(2)]);
+        let mirrored = panel.transformed(Rotation::Deg90, true, false);
+        assert_eq!(mirrored.map, [Some(0), Some(5), Some(1), None, Some(2), Some(3)]);
+        let upside_down = panel.transformed(Rotation::Deg180, false, false);
+        assert_eq!(upside_down.map, [Some(3), None, Some(5), Some(2), Some(1), Some(0)]);
+        assert_eq!(
+            panel.transformed(Rotation::Deg270, false, false).map,
+            [Some(2), Some(3), Some(1), None, Some(0), Some(5)]
+        );
+
+        let mut cfg = DeviceConfig::from_output_defs(vec![OutputPortDefinition {
+            id: "panel".to_string(),
+            name: "Panel".to_string(),
+            output_type: SegmentType::Matrix,
+            leds_count: 6,
+            matrix: Some(panel),
+            capabilities: OutputCapabilities {
+                editable: false,
+                min_total_leds: 6,
+                max_total_leds: 6,
+                allowed_total_leds: None,
+                allowed_segment_types: vec![SegmentType::Matrix],
+            },
+        }]);
+        cfg.output_mut("panel").unwrap().transform = MatrixTransform {
+            rotation: Rotation::Deg90,
+            flip_h: false,
+            flip_v: false,
+        };
+        let (tasks, _) = collect_tasks(&cfg, "panel");
+        let task = &tasks[0];
+        assert_eq!(
+            virtual_layout_for_segment(task.layout_type, task.leds_count, &task.matrix),
+            (2, 3)
+        );
+
+        // The top-left of what the effect draws lands on LED 5.
+        let mut virtual_buffer = vec![Color::default(); 6];
+        virtual_buffer[0] = Color { r: 255, g: 0, b: 0 };
+        let mut physical = vec![Color::default(); 6];
+        map_segment_into_physical(
+            &virtual_buffer,
+            task.layout_type,
+            task.leds_count,
+            &task.matrix,
+            0,
+            1.0,
+            &mut physical,
+        );
+        assert_eq!(physical[5].r, 255);
+        assert!(physical[..5].iter().all(|c| c.r == 0));
+    }
+
+    #[test]
+    fn reversed_segments_are_written_back_to_front() {
+        use super::super::SegmentConfig;
+
+        let mut cfg = DeviceConfig::from_output_defs(vec![linear_output("strip", 5)]);
+        let segment = |id: &str, leds_count: usize, reversed: bool| SegmentConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            segment_type: SegmentType::Linear,
+            leds_count,
+            matrix: None,
+            reversed,
+            brightness: 100,
+            mode: Default::default(),
+        };
+        cfg.output_mut("strip").unwrap().segments =
+            vec![segment("a", 2, false), segment("b", 3, true)];
+
+        let (tasks, _) = collect_tasks(&cfg, "strip");
+        let mut physical = vec![Color::default(); 5];
+        for task in &tasks {
+            // Every effect draws a ramp starting at its first LED.
+            let ramp: Vec<Color> = (1..=task.leds_count as u8)
+                .map(|i| Color { r: i * 10, g: 0, b: 0 })
+                .collect();
+            write_task_into_physical(task, &ramp, 1.0, &mut physical);
+        }
+        let reds: Vec<u8> = physical.iter().map(|c| c.r).collect();
+        assert_eq!(reds, [10, 20, 30, 20, 10]);
+    }
+}
@@ -1,6 +1,9 @@
 pub mod audio;
+pub mod color;
 pub mod controller;
 pub mod driver;
 pub mod effect;
+pub mod input;
 pub mod lut;
 pub mod screen;
+pub mod trig;
@@ -177,6 +177,13 @@ impl SystemAudioCapture {
             .unwrap_or(48000)
     }
 
+    /// Keep at least `samples` of history.
+    pub fn reserve(&self, samples: usize) {
+        if let Ok(mut buf) = self.buffer.lock() {
+            buf.ensure_capacity(samples);
+        }
+    }
+
     /// Read the most recent audio samples into the destination buffer.
     /// Returns the number of samples actually read.
     pub fn read_samples(&self, dest: &mut [f32]) -> usize {
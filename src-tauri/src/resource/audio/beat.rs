@@ -0,0 +1,245 @@
+//! Beat detection on FFT magnitudes, for effects that want to pulse on the music.
+//!
+//! Each frame the spectrum is split into bass, mid and high bands. A beat is an onset: a
+//! band's energy jumping above [`BeatDetector::threshold`] times its average over the last
+//! second. The tempo comes from autocorrelating the times of the beats found in the last
+//! few seconds, so a missed or extra beat only nudges it.
+
+use std::collections::VecDeque;
+
+/// Band edges as fractions of the spectrum: bass below the first, high above the second.
+const BAND_SPLITS: (f32, f32) = (1.0 / 16.0, 1.0 / 4.0);
+
+/// Energy below this is silence, where any ratio would be noise.
+const MIN_ENERGY: f32 = 1e-6;
+
+pub const MIN_BPM: f32 = 60.0;
+pub const MAX_BPM: f32 = 200.0;
+
+/// Seconds of energy the instantaneous energy is compared against.
+const HISTORY_SECS: f32 = 1.0;
+/// Seconds of beats the tempo is estimated from.
+const TEMPO_WINDOW_SECS: f32 = 8.0;
+
+/// Standard deviations above the average energy an onset has to clear as well.
+const SPREAD_DEVIATIONS: f32 = 2.0;
+
+/// Energy ratio that counts as the strongest possible beat.
+const FULL_STRENGTH_RATIO: f32 = 3.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BeatEvent {
+    pub is_beat: bool,
+    /// 0.0 (barely over the threshold) to 1.0; 0.0 when there's no beat.
+    pub strength: f32,
+    /// Tempo of the recent beats, once there have been enough of them.
+    pub bpm_estimate: Option<f32>,
+}
+
+pub struct BeatDetector {
+    /// How often `process` is called, in Hz.
+    frame_rate: f32,
+    /// Ratio to the average energy a band has to reach to count as a beat.
+    pub threshold: f32,
+    /// Bass/mid/high energy of recent frames, oldest first.
+    history: VecDeque<[f32; 3]>,
+    history_len: usize,
+    /// Frame numbers of the beats within the tempo window.
+    beats: VecDeque<u64>,
+    frame: u64,
+    bpm: Option<f32>,
+}
+
+impl BeatDetector {
+    pub fn new(frame_rate: f32) -> Self {
+        let frame_rate = frame_rate.max(1.0);
+        let history_len = (frame_rate * HISTORY_SECS).round().max(1.0) as usize;
+        Self {
+            frame_rate,
+            threshold: 1.4,
+            history: VecDeque::with_capacity(history_len),
+            history_len,
+            beats: VecDeque::new(),
+            frame: 0,
+            bpm: None,
+        }
+    }
+
+    /// Feed one frame of FFT magnitudes (low to high frequency).
+    pub fn process(&mut self, fft: &[f32]) -> BeatEvent {
+        self.frame += 1;
+        let energies = band_energies(fft);
+
+        // Compare against the history before this frame joins it, and only once a quarter
+        // of it has been seen so the first frames don't all look like onsets. A band that
+        // merely reaches the top of its usual spread isn't an onset, however quiet it is.
+        let ratio = if self.history.len() * 4 >= self.history_len {
+            let count = self.history.len() as f32;
+            (0..3)
+                .map(|band| {
+                    let average = self.history.iter().map(|e| e[band]).sum::<f32>() / count;
+                    let variance = self
+                        .history
+                        .iter()
+                        .map(|e| (e[band] - average).powi(2))
+                        .sum::<f32>()
+                        / count;
+                    let spread = average + SPREAD_DEVIATIONS * variance.sqrt();
+                    if energies[band] < MIN_ENERGY || energies[band] <= spread {
+                        0.0
+                    } else {
+                        energies[band] / average.max(MIN_ENERGY)
+                    }
+                })
+                .fold(0.0, f32::max)
+        } else {
+            0.0
+        };
+
+        if self.history.len() == self.history_len {
+            self.history.pop_front();
+        }
+        self.history.push_back(energies);
+
+        let min_gap = (self.frame_rate * 60.0 / MAX_BPM).floor() as u64;
+        let is_beat = ratio >= self.threshold
+            && self
+                .beats
+                .back()
+                .is_none_or(|&last| self.frame - last >= min_gap);
+
+        if is_beat {
+            let window = (self.frame_rate * TEMPO_WINDOW_SECS) as u64;
+            self.beats.push_back(self.frame);
+            while self.beats.front().is_some_and(|&f| self.frame - f > window) {
+                self.beats.pop_front();
+            }
+            self.bpm = self.estimate_bpm();
+        }
+
+        BeatEvent {
+            is_beat,
+            strength: if is_beat {
+                ((ratio - 1.0) / (FULL_STRENGTH_RATIO - 1.0)).clamp(0.0, 1.0)
+            } else {
+                0.0
+            },
+            bpm_estimate: self.bpm,
+        }
+    }
+
+    /// Autocorrelate the beat times: every pair of beats votes for the lag between them
+    /// (and half a vote for its neighbours, for jitter), and the best lag in the BPM range
+    /// is refined with a parabola through its neighbours.
+    fn estimate_bpm(&self) -> Option<f32> {
+        if self.beats.len() < 3 {
+            return None;
+        }
+        let min_lag = (self.frame_rate * 60.0 / MAX_BPM).floor() as usize;
+        let max_lag = (self.frame_rate * 60.0 / MIN_BPM).ceil() as usize;
+
+        let mut scores = vec![0.0f32; max_lag + 2];
+        let beats: Vec<u64> = self.beats.iter().copied().collect();
+        for (i, &a) in beats.iter().enumerate() {
+            for &b in &beats[i + 1..] {
+                let lag = (b - a) as usize;
+                if lag > max_lag + 1 {
+                    break;
+                }
+                scores[lag] += 1.0;
+                scores[lag - 1] += 0.5;
+                if let Some(next) = scores.get_mut(lag + 1) {
+                    *next += 0.5;
+                }
+            }
+        }
+
+        let best = (min_lag.max(1)..=max_lag)
+            .filter(|&lag| scores[lag] > 0.0)
+            .fold(None, |best: Option<usize>, lag| match best {
+                Some(b) if scores[b] >= scores[lag] => Some(b),
+                _ => Some(lag),
+            })?;
+
+        let (before, at, after) = (scores[best - 1], scores[best], scores[best + 1]);
+        let curvature = before - 2.0 * at + after;
+        let offset = if curvature.abs() > f32::EPSILON {
+            (0.5 * (before - after) / curvature).clamp(-0.5, 0.5)
+        } else {
+            0.0
+        };
+        Some(60.0 * self.frame_rate / (best as f32 + offset))
+    }
+}
+
+/// Mean squared magnitude of the bass, mid and high bands.
+fn band_energies(fft: &[f32]) -> [f32; 3] {
+    let len = fft.len();
+    if len == 0 {
+        return [0.0; 3];
+    }
+    let bass_end = ((len as f32 * BAND_SPLITS.0).ceil() as usize).clamp(1, len);
+    let mid_end = ((len as f32 * BAND_SPLITS.1).ceil() as usize).clamp(bass_end, len);
+    let energy = |bins: &[f32]| {
+        if bins.is_empty() {
+            0.0
+        } else {
+            bins.iter().map(|v| v * v).sum::<f32>() / bins.len() as f32
+        }
+    };
+    [
+        energy(&fft[..bass_end]),
+        energy(&fft[bass_end..mid_end]),
+        energy(&fft[mid_end..]),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FPS: f32 = 60.0;
+
+    /// A kick every `60 / bpm` seconds over a quiet, slightly varying spectrum.
+    fn run(detector: &mut BeatDetector, bpm: f32, seconds: f32) -> (usize, BeatEvent) {
+        let period = FPS * 60.0 / bpm;
+        let mut next_kick = period;
+        let mut beats = 0;
+        let mut last = BeatEvent::default();
+        for frame in 0..(FPS * seconds) as usize {
+            let mut fft = vec![0.02 + 0.005 * (frame % 7) as f32; 256];
+            if frame as f32 >= next_kick {
+                next_kick += period;
+                fft[..16].fill(0.8);
+            }
+            last = detector.process(&fft);
+            beats += usize::from(last.is_beat);
+        }
+        (beats, last)
+    }
+
+    #[test]
+    fn a_120_bpm_impulse_train_is_detected_and_timed() {
+        let mut detector = BeatDetector::new(FPS);
+        let (beats, last) = run(&mut detector, 120.0, 10.0);
+        // 2 kicks a second, give or take one at the edges.
+        assert!((19..=20).contains(&beats), "{} beats", beats);
+        let bpm = last.bpm_estimate.unwrap();
+        assert!((bpm - 120.0).abs() <= 5.0, "{}", bpm);
+    }
+
+    #[test]
+    fn tempo_off_the_frame_grid_is_still_close() {
+        let mut detector = BeatDetector::new(FPS);
+        let (_, last) = run(&mut detector, 128.0, 12.0);
+        let bpm = last.bpm_estimate.unwrap();
+        assert!((bpm - 128.0).abs() <= 5.0, "{}", bpm);
+    }
+
+    #[test]
+    fn steady_noise_has_no_beats() {
+        let mut detector = BeatDetector::new(FPS);
+        let fft = vec![0.3; 256];
+        assert!((0..300).all(|_| !detector.process(&fft).is_beat));
+    }
+}
@@ -0,0 +1,256 @@
+//! Audio-driven brightness: scales a scope's output by how loud the captured audio is.
+//!
+//! Loudness is the RMS of the most recent samples, optionally band-limited with one-pole
+//! filters, and is smoothed by an attack/release envelope measured in milliseconds so the
+//! response doesn't depend on the frame rate. Silence settles at `floor` rather than black.
+
+use std::time::Instant;
+
+use super::AudioManager;
+
+/// Part of the spectrum that drives the envelope.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AudioBand {
+    #[default]
+    Full,
+    /// Below ~250 Hz (kick, bass).
+    Bass,
+    /// ~250 Hz to ~4 kHz (vocals, most instruments).
+    Mid,
+    /// Above ~4 kHz (hi-hats, sibilance).
+    Treble,
+}
+
+impl AudioBand {
+    /// `(highpass, lowpass)` cutoffs in Hz.
+    fn cutoffs(self) -> (Option<f32>, Option<f32>) {
+        match self {
+            Self::Full => (None, None),
+            Self::Bass => (None, Some(250.0)),
+            Self::Mid => (Some(250.0), Some(4000.0)),
+            Self::Treble => (Some(4000.0), None),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AudioBrightnessSettings {
+    /// Capture device, as listed by `get_audio_devices`.
+    pub device_index: usize,
+    pub band: AudioBand,
+    /// Gain applied to the loudness before it's clamped, in percent.
+    pub sensitivity: u32,
+    /// Time to rise towards a louder level, in ms.
+    pub attack_ms: u32,
+    /// Time to fall towards a quieter level, in ms.
+    pub release_ms: u32,
+    /// Brightness kept during silence, in percent.
+    pub floor: u8,
+}
+
+pub const MAX_SENSITIVITY: u32 = 1000;
+pub const MAX_ENVELOPE_MS: u32 = 5000;
+
+impl Default for AudioBrightnessSettings {
+    fn default() -> Self {
+        Self {
+            device_index: 0,
+            band: AudioBand::Full,
+            sensitivity: 100,
+            attack_ms: 30,
+            release_ms: 300,
+            floor: 20,
+        }
+    }
+}
+
+impl AudioBrightnessSettings {
+    pub fn normalized(mut self) -> Self {
+        self.sensitivity = self.sensitivity.min(MAX_SENSITIVITY);
+        self.attack_ms = self.attack_ms.min(MAX_ENVELOPE_MS);
+        self.release_ms = self.release_ms.min(MAX_ENVELOPE_MS);
+        self.floor = self.floor.min(100);
+        self
+    }
+}
+
+/// RMS treated as full brightness at 100% sensitivity; typical loud music sits around here.
+const FULL_SCALE_RMS: f32 = 0.25;
+const WINDOW_SAMPLES: usize = 1024;
+const FALLBACK_SAMPLE_RATE: u32 = 48_000;
+
+/// Envelope state for one scope. Shares the audio capture from first use until it is
+/// dropped or switched to another device.
+pub struct AudioBrightness {
+    settings: AudioBrightnessSettings,
+    level: f32,
+    last_update: Option<Instant>,
+    samples: Vec<f32>,
+    capturing: bool,
+}
+
+impl AudioBrightness {
+    pub fn new(settings: AudioBrightnessSettings) -> Self {
+        Self {
+            settings: settings.normalized(),
+            level: 0.0,
+            last_update: None,
+            samples: vec![0.0; WINDOW_SAMPLES],
+            capturing: false,
+        }
+    }
+
+    pub fn settings(&self) -> &AudioBrightnessSettings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: AudioBrightnessSettings) {
+        let settings = settings.normalized();
+        if settings.device_index != self.settings.device_index && self.capturing {
+            self.release_capture();
+        }
+        self.settings = settings;
+    }
+
+    /// Brightness multiplier (floor..=1.0) at `now`.
+    pub fn multiplier(&mut self, now: Instant) -> f32 {
+        let dt_ms = self
+            .last_update
+            .replace(now)
+            .map_or(0.0, |last| now.saturating_duration_since(last).as_secs_f32() * 1000.0);
+        let loudness = self.read_loudness();
+        self.advance(loudness, dt_ms)
+    }
+
+    /// Move the envelope towards `loudness` (an RMS) over `dt_ms`.
+    fn advance(&mut self, loudness: f32, dt_ms: f32) -> f32 {
+        let gain = self.settings.sensitivity as f32 / 100.0;
+        let target = (loudness / FULL_SCALE_RMS * gain).clamp(0.0, 1.0);
+        let tau = if target > self.level {
+            self.settings.attack_ms
+        } else {
+            self.settings.release_ms
+        };
+        let rate = if tau == 0 {
+            1.0
+        } else {
+            1.0 - (-dt_ms / tau as f32).exp()
+        };
+        self.level += (target - self.level) * rate;
+
+        let floor = self.settings.floor as f32 / 100.0;
+        floor + (1.0 - floor) * self.level
+    }
+
+    fn read_loudness(&mut self) -> f32 {
+        let manager = AudioManager::get();
+        if !self.capturing {
+            if let Err(e) = manager.acquire_capture(self.settings.device_index) {
+                log::error!(device_index = self.settings.device_index, err:display = e; "[audio] Failed to start capture for audio brightness");
+                return 0.0;
+            }
+            self.capturing = true;
+        }
+
+        let read = manager.read_samples(&mut self.samples);
+        let sample_rate = manager.sample_rate().unwrap_or(FALLBACK_SAMPLE_RATE);
+        band_rms(&self.samples[..read], sample_rate, self.settings.band)
+    }
+
+    fn release_capture(&mut self) {
+        if self.capturing {
+            AudioManager::get().release_capture();
+            self.capturing = false;
+        }
+    }
+}
+
+impl Drop for AudioBrightness {
+    fn drop(&mut self) {
+        self.release_capture();
+    }
+}
+
+/// RMS of `samples` after restricting them to `band`.
+fn band_rms(samples: &[f32], sample_rate: u32, band: AudioBand) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let coefficient = |cutoff: f32| {
+        1.0 - (-2.0 * std::f32::consts::PI * cutoff / sample_rate.max(1) as f32).exp()
+    };
+    let (highpass, lowpass) = band.cutoffs();
+    let highpass = highpass.map(coefficient);
+    let lowpass = lowpass.map(coefficient);
+
+    let (mut high_state, mut low_state) = (0.0f32, 0.0f32);
+    let sum: f32 = samples
+        .iter()
+        .map(|&sample| {
+            let mut value = sample;
+            if let Some(a) = highpass {
+                high_state += (value - high_state) * a;
+                value -= high_state;
+            }
+            if let Some(a) = lowpass {
+                low_state += (value - low_state) * a;
+                value = low_state;
+            }
+            value * value
+        })
+        .sum();
+    (sum / samples.len() as f32).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME_MS: f32 = 16.0;
+
+    fn run(envelope: &mut AudioBrightness, loudness: f32, ms: f32) -> f32 {
+        let mut multiplier = 0.0;
+        for _ in 0..(ms / FRAME_MS) as usize {
+            multiplier = envelope.advance(loudness, FRAME_MS);
+        }
+        multiplier
+    }
+
+    #[test]
+    fn loud_audio_brightens_and_silence_settles_at_the_floor() {
+        let mut envelope = AudioBrightness::new(AudioBrightnessSettings {
+            floor: 30,
+            ..Default::default()
+        });
+        let quiet = run(&mut envelope, 0.0, 200.0);
+        assert!((quiet - 0.3).abs() < 1e-3, "{}", quiet);
+
+        let loud = run(&mut envelope, 0.5, 1000.0);
+        assert!(loud > 0.95, "{}", loud);
+
+        let settled = run(&mut envelope, 0.0, 3000.0);
+        assert!((settled - 0.3).abs() < 0.02, "{}", settled);
+    }
+
+    #[test]
+    fn bands_pick_their_part_of_the_spectrum() {
+        let sample_rate = 48_000;
+        let sine = |hz: f32| -> Vec<f32> {
+            (0..WINDOW_SAMPLES * 4)
+                .map(|i| (2.0 * std::f32::consts::PI * hz * i as f32 / sample_rate as f32).sin())
+                .collect()
+        };
+        let bass = sine(60.0);
+        assert!(
+            band_rms(&bass, sample_rate, AudioBand::Bass)
+                > 4.0 * band_rms(&bass, sample_rate, AudioBand::Treble)
+        );
+        let treble = sine(10_000.0);
+        assert!(
+            band_rms(&treble, sample_rate, AudioBand::Treble)
+                > 4.0 * band_rms(&treble, sample_rate, AudioBand::Bass)
+        );
+    }
+}
@@ -6,11 +6,20 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Device, Host, SampleFormat, Stream, StreamConfig};
 use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
 
 #[cfg(target_os = "macos")]
 use super::macos::SystemAudioCapture;
 
+/// Window readers ask for unless they call [`AudioManager::set_frame_size`].
+pub const DEFAULT_FRAME_SIZE: usize = 1024;
+pub const MAX_FRAME_SIZE: usize = 16_384;
+
+/// Captures keep enough history to resample a full frame down from this many times the
+/// rate it's read at (e.g. 192 kHz read at 48 kHz).
+const MAX_RESAMPLE_RATIO: usize = 4;
+
 /// Global audio manager singleton.
 static AUDIO_MANAGER: Lazy<AudioManager> = Lazy::new(AudioManager::new);
 
@@ -34,6 +43,8 @@ pub(crate) struct AudioRingBuffer {
     buffer: Vec<f32>,
     write_pos: usize,
     capacity: usize,
+    /// Samples written so far, up to `capacity`.
+    filled: usize,
 }
 
 impl AudioRingBuffer {
@@ -42,6 +53,7 @@ impl AudioRingBuffer {
             buffer: vec![0.0; capacity],
             write_pos: 0,
             capacity,
+            filled: 0,
         }
     }
 
@@ -50,21 +62,34 @@ impl AudioRingBuffer {
             self.buffer[self.write_pos] = sample;
             self.write_pos = (self.write_pos + 1) % self.capacity;
         }
+        self.filled = (self.filled + samples.len()).min(self.capacity);
     }
 
-    /// Read the most recent `count` samples into the destination buffer.
+    /// Read the most recent samples into the destination buffer, oldest first. When less
+    /// has been captured than `dest` holds, the start is zero-padded.
     pub(crate) fn read_recent(&self, dest: &mut [f32]) {
-        let count = dest.len().min(self.capacity);
-        let start = if self.write_pos >= count {
-            self.write_pos - count
-        } else {
-            self.capacity - (count - self.write_pos)
-        };
+        let count = dest.len().min(self.filled);
+        let (padding, recent) = dest.split_at_mut(dest.len() - count);
+        padding.fill(0.0);
 
-        for (i, sample) in dest.iter_mut().enumerate().take(count) {
+        let start = (self.write_pos + self.capacity - count) % self.capacity;
+        for (i, sample) in recent.iter_mut().enumerate() {
             *sample = self.buffer[(start + i) % self.capacity];
         }
     }
+
+    /// Grow to hold at least `capacity` samples, keeping what was captured.
+    pub(crate) fn ensure_capacity(&mut self, capacity: usize) {
+        if capacity <= self.capacity {
+            return;
+        }
+        let mut recent = vec![0.0; self.filled];
+        self.read_recent(&mut recent);
+        self.buffer = vec![0.0; capacity];
+        self.buffer[..recent.len()].copy_from_slice(&recent);
+        self.write_pos = recent.len();
+        self.capacity = capacity;
+    }
 }
 
 /// Active audio capture state using cpal.
@@ -83,12 +108,22 @@ enum CaptureState {
     ScreenCaptureKit(SystemAudioCapture),
 }
 
+/// Who is holding the capture open. One device is captured at a time; acquiring another
+/// one switches it for every user.
+#[derive(Default)]
+struct CaptureUsers {
+    count: usize,
+    device_index: Option<usize>,
+}
+
 /// The main audio manager responsible for device enumeration and capture.
 pub struct AudioManager {
     _host: Host,
     input_devices: Vec<Device>,
     output_devices: Vec<Device>,
     active_capture: RwLock<Option<CaptureState>>,
+    users: Mutex<CaptureUsers>,
+    frame_size: AtomicUsize,
 }
 
 // SAFETY: cpal::Host and cpal::Device are Send (they manage internal handles).
@@ -115,6 +150,8 @@ impl AudioManager {
             input_devices,
             output_devices,
             active_capture: RwLock::new(None),
+            users: Mutex::new(CaptureUsers::default()),
+            frame_size: AtomicUsize::new(DEFAULT_FRAME_SIZE),
         }
     }
 
@@ -165,8 +202,43 @@ impl AudioManager {
         }
     }
 
+    /// Count one more user of the capture, starting it on `device_index` unless that
+    /// device is already being captured. Every successful call needs a matching
+    /// [`Self::release_capture`]; failed ones aren't counted.
+    pub fn acquire_capture(&self, device_index: usize) -> Result<(), String> {
+        let mut users = self
+            .users
+            .lock()
+            .map_err(|_| "Audio capture users lock poisoned".to_string())?;
+        if users.device_index != Some(device_index) || !self.is_capturing() {
+            if users.count > 0 {
+                log::info!(
+                    device_index = device_index,
+                    users = users.count;
+                    "[audio] Switching the shared capture to another device"
+                );
+            }
+            self.start_capture(device_index)?;
+            users.device_index = Some(device_index);
+        }
+        users.count += 1;
+        Ok(())
+    }
+
+    /// Drop one user of the capture; the last one stops it.
+    pub fn release_capture(&self) {
+        let Ok(mut users) = self.users.lock() else {
+            return;
+        };
+        users.count = users.count.saturating_sub(1);
+        if users.count == 0 {
+            users.device_index = None;
+            self.stop_capture();
+        }
+    }
+
     /// Start capturing audio from the specified device.
-    pub fn start_capture(&self, device_index: usize) -> Result<(), String> {
+    fn start_capture(&self, device_index: usize) -> Result<(), String> {
         // Stop any existing capture first.
         self.stop_capture();
 
@@ -189,6 +261,7 @@ impl AudioManager {
     fn start_screencapturekit_capture(&self) -> Result<(), String> {
         let mut capture = SystemAudioCapture::new()?;
         capture.start()?;
+        capture.reserve(self.history_len());
 
         let capture_state = CaptureState::ScreenCaptureKit(capture);
 
@@ -214,8 +287,9 @@ impl AudioManager {
         let channels = config.channels() as usize;
         let sample_format = config.sample_format();
 
-        // Allocate buffer for ~100ms of audio at the given sample rate (mono).
-        let buffer_size = (sample_rate as usize / 10).max(4096);
+        // Allocate buffer for ~100ms of audio at the given sample rate (mono), or more if
+        // readers want longer frames.
+        let buffer_size = (sample_rate as usize / 10).max(4096).max(self.history_len());
         let buffer = Arc::new(Mutex::new(AudioRingBuffer::new(buffer_size)));
         let buffer_clone = Arc::clone(&buffer);
 
@@ -343,7 +417,7 @@ impl AudioManager {
     }
 
     /// Stop the current audio capture.
-    pub fn stop_capture(&self) {
+    fn stop_capture(&self) {
         if let Ok(mut guard) = self.active_capture.write() {
             *guard = None;
         }
@@ -360,6 +434,35 @@ impl AudioManager {
         })
     }
 
+    /// Set the longest window (in samples, at the rate it's read at) that readers ask for.
+    /// The active capture's history grows to fit right away; later captures start with it.
+    pub fn set_frame_size(&self, samples: usize) {
+        self.frame_size
+            .store(samples.clamp(1, MAX_FRAME_SIZE), Ordering::Relaxed);
+        let history = self.history_len();
+        if let Ok(guard) = self.active_capture.read() {
+            match guard.as_ref() {
+                Some(CaptureState::Cpal(cpal_state)) => {
+                    if let Ok(mut buf) = cpal_state.buffer.lock() {
+                        buf.ensure_capacity(history);
+                    }
+                }
+                #[cfg(target_os = "macos")]
+                Some(CaptureState::ScreenCaptureKit(sck_state)) => sck_state.reserve(history),
+                None => {}
+            }
+        }
+    }
+
+    pub fn frame_size(&self) -> usize {
+        self.frame_size.load(Ordering::Relaxed)
+    }
+
+    /// Samples of history to keep so a full frame can still be resampled.
+    fn history_len(&self) -> usize {
+        self.frame_size() * MAX_RESAMPLE_RATIO
+    }
+
     /// Read the most recent audio samples.
     /// Returns the number of samples actually read.
     pub fn read_samples(&self, dest: &mut [f32]) -> usize {
@@ -384,6 +487,28 @@ impl AudioManager {
         0
     }
 
+    /// Read the most recent audio as if it had been captured at `target_rate`, linearly
+    /// interpolating between the device's samples. Like [`Self::read_samples`], history
+    /// shorter than `dest` is zero-padded instead of waited for.
+    pub fn read_samples_resampled(&self, dest: &mut [f32], target_rate: u32) -> usize {
+        let source_rate = match self.sample_rate() {
+            Some(rate) if rate > 0 && target_rate > 0 && rate != target_rate => rate,
+            _ => return self.read_samples(dest),
+        };
+        if dest.is_empty() {
+            return 0;
+        }
+
+        let ratio = source_rate as f64 / target_rate as f64;
+        let mut source = vec![0.0; ((dest.len() - 1) as f64 * ratio).ceil() as usize + 1];
+        if self.read_samples(&mut source) == 0 {
+            dest.fill(0.0);
+            return 0;
+        }
+        resample_linear(&source, dest, ratio);
+        dest.len()
+    }
+
     /// Check if capture is currently active.
     pub fn is_capturing(&self) -> bool {
         self.active_capture
@@ -394,8 +519,60 @@ impl AudioManager {
     }
 }
 
+/// Resample `source` into `dest`, `ratio` source samples per destination sample, with the
+/// last samples of both lined up so `dest` ends at the newest audio.
+fn resample_linear(source: &[f32], dest: &mut [f32], ratio: f64) {
+    let (Some(last), Some(dest_last)) = (source.len().checked_sub(1), dest.len().checked_sub(1))
+    else {
+        dest.fill(0.0);
+        return;
+    };
+    for (i, out) in dest.iter_mut().enumerate() {
+        let pos = (last as f64 - (dest_last - i) as f64 * ratio).max(0.0);
+        let index = pos as usize;
+        let frac = (pos - index as f64) as f32;
+        let a = source[index];
+        let b = source.get(index + 1).copied().unwrap_or(a);
+        *out = a + (b - a) * frac;
+    }
+}
+
 /// Get a list of audio devices for the frontend.
 pub fn list_audio_devices() -> Vec<AudioDevice> {
     AudioManager::get().list_devices()
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_history_is_zero_padded_and_survives_growing() {
+        let mut ring = AudioRingBuffer::new(8);
+        ring.write(&[1.0, 2.0, 3.0]);
+        let mut dest = [9.0; 5];
+        ring.read_recent(&mut dest);
+        assert_eq!(dest, [0.0, 0.0, 1.0, 2.0, 3.0]);
+
+        // Only the newest 8 of 1..=10 are kept; growing keeps them in order.
+        ring.write(&[4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0]);
+        ring.ensure_capacity(16);
+        ring.write(&[11.0]);
+        let mut dest = [9.0; 12];
+        ring.read_recent(&mut dest);
+        assert_eq!(dest, [0.0, 0.0, 0.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0]);
+    }
+
+    #[test]
+    fn resampling_lines_up_the_newest_samples() {
+        let source: Vec<f32> = (0..9).map(|i| i as f32).collect();
+        let mut half = [0.0; 5];
+        resample_linear(&source, &mut half, 2.0);
+        assert_eq!(half, [0.0, 2.0, 4.0, 6.0, 8.0]);
+
+        let mut double = [0.0; 5];
+        resample_linear(&source[6..], &mut double, 0.5);
+        assert_eq!(double, [6.0, 6.5, 7.0, 7.5, 8.0]);
+    }
+}
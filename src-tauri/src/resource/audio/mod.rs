@@ -1,4 +1,7 @@
+pub mod beat;
+pub mod brightness;
 pub mod manager;
+pub mod spectrum;
 
 #[cfg(target_os = "macos")]
 #[path = "MacOS/mod.rs"]
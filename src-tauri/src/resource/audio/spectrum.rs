@@ -0,0 +1,337 @@
+//! FFT processing shared by the audio visualizers.
+//!
+//! Matches the OpenRGBEffectsPlugin AudioSignalProcessor: captured samples are amplified
+//! (AGC), windowed and transformed, each bin is frequency-compensated, log-filtered and
+//! peak-held with a per-frame decay, then averaged over neighbouring bins and low-pass
+//! filtered. Effects draw from [`AudioSpectrum::filtered`], usually through an [`Envelope`].
+
+use crate::resource::audio::AudioManager;
+use spectrum_analyzer::scaling::divide_by_N_sqrt;
+use spectrum_analyzer::windows::hann_window;
+use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
+
+pub const DEFAULT_FFT_SIZE: usize = 1024;
+pub const FFT_SIZES: [usize; 4] = [512, 1024, 2048, 4096];
+
+/// Filtered FFT bins per FFT sample; 1024 samples give the C++ 256 bins.
+const FFT_BINS_DIVISOR: usize = 4;
+
+/// Audio captured faster than this is resampled down before the FFT, so a 96 kHz loopback
+/// device gets the same low-frequency resolution as a 48 kHz one.
+const MAX_ANALYSIS_RATE: u32 = 48_000;
+
+/// Frame rate the per-frame decay and envelope coefficients are tuned for.
+pub const TARGET_FPS: f32 = 60.0;
+
+/// Fill `samples` with the latest `len` captured samples, resampled to at most
+/// `MAX_ANALYSIS_RATE`, and return the rate they are at. The buffer is reused from frame
+/// to frame, so it is only reallocated when `len` grows.
+pub fn latest_samples(samples: &mut Vec<f32>, len: usize) -> u32 {
+    let manager = AudioManager::get();
+
+    // Make sure the capture keeps a whole window of history.
+    if manager.frame_size() < len {
+        manager.set_frame_size(len);
+    }
+    let sample_rate = manager.sample_rate().unwrap_or(44100).min(MAX_ANALYSIS_RATE);
+    samples.resize(len, 0.0);
+    manager.read_samples_resampled(samples, sample_rate);
+    sample_rate
+}
+
+pub struct AudioSpectrum {
+    // AGC (Auto Gain Control) settings - matches C++ AudioSettingsStruct.
+    pub amplitude: f32,       // Gain multiplier (default 100)
+    pub decay: f32,           // Decay rate percentage (default 80)
+    pub filter_constant: f32, // Low-pass filter constant (default 1.0)
+    /// Bins averaged together after peak-hold; 1 disables the averaging.
+    pub avg_size: usize,
+
+    fft_size: usize,
+    fft_buffer: Vec<f32>,   // Raw FFT magnitude (with peak-hold and decay)
+    fft_nrml: Vec<f32>,     // Normalization array (frequency compensation)
+    fft_filtered: Vec<f32>, // Low-pass filtered FFT output
+}
+
+impl Default for AudioSpectrum {
+    fn default() -> Self {
+        Self::new(DEFAULT_FFT_SIZE)
+    }
+}
+
+impl AudioSpectrum {
+    pub fn new(fft_size: usize) -> Self {
+        let mut spectrum = Self {
+            amplitude: 100.0,
+            decay: 80.0,
+            filter_constant: 1.0,
+            avg_size: 8, // C++ default is 8
+            fft_size: 0,
+            fft_buffer: Vec::new(),
+            fft_nrml: Vec::new(),
+            fft_filtered: Vec::new(),
+        };
+        spectrum.set_fft_size(fft_size);
+        spectrum
+    }
+
+    pub fn fft_size(&self) -> usize {
+        self.fft_size
+    }
+
+    /// Reallocate the buffers for `fft_size` samples, dropping the analysis so far.
+    pub fn set_fft_size(&mut self, fft_size: usize) {
+        self.fft_size = fft_size;
+        let bins = fft_size / FFT_BINS_DIVISOR;
+
+        // Default AGC settings matching C++ AudioSettingsStruct.
+        // nrml_ofst = 0.04, nrml_scl = 0.5
+        // Higher frequencies get more gain to compensate for typical audio spectrum roll-off.
+        self.fft_nrml = (0..bins)
+            .map(|i| 0.04 + (0.5 * (i as f32 / bins as f32)))
+            .collect();
+        self.fft_buffer = vec![0.0; bins];
+        self.fft_filtered = vec![0.0; bins];
+    }
+
+    pub fn bins(&self) -> usize {
+        self.fft_buffer.len()
+    }
+
+    /// Low-pass filtered bins, 0.0..=1.0 from low to high frequencies.
+    pub fn filtered(&self) -> &[f32] {
+        &self.fft_filtered
+    }
+
+    /// Lets effect tests draw a spectrum without audio.
+    #[cfg(test)]
+    pub(crate) fn filtered_mut(&mut self) -> &mut [f32] {
+        &mut self.fft_filtered
+    }
+
+    /// Decay-and-peak-hold magnitudes; after [`Self::analyze`], before averaging.
+    pub fn peaks(&self) -> &[f32] {
+        &self.fft_buffer
+    }
+
+    /// Fold one window of samples (`fft_size` of them, at `sample_rate`) into the spectrum.
+    /// Matches the C++ AudioSignalProcessor::Process() implementation.
+    pub fn process(&mut self, samples: &[f32], sample_rate: u32) {
+        self.analyze(samples, sample_rate);
+        self.filter();
+    }
+
+    /// First half of [`Self::process`]: decay the held peaks and raise them to the new
+    /// spectrum. Onset detection should look at [`Self::peaks`] before [`Self::filter`]
+    /// blurs the bands.
+    pub fn analyze(&mut self, samples: &[f32], sample_rate: u32) {
+        let bins = self.bins();
+
+        // Apply amplitude gain (AGC) - matches C++ fft_tmp[i] *= settings->amplitude.
+        let amplified_samples: Vec<f32> = samples.iter()
+            .map(|&s| s * self.amplitude)
+            .collect();
+
+        // Apply decay to previous FFT values.
+        // C++: data.fft[i] = data.fft[i] * ((float(settings->decay) / 100.0f / (60 / FPS)));
+        let decay_factor = (self.decay / 100.0) / (60.0 / TARGET_FPS);
+        for i in 0..bins {
+            self.fft_buffer[i] *= decay_factor;
+        }
+
+        // Apply Hann window (C++ window_mode == 1).
+        let windowed = hann_window(&amplified_samples);
+
+        // Compute FFT.
+        if let Ok(spectrum) = samples_fft_to_spectrum(
+            &windowed,
+            sample_rate,
+            FrequencyLimit::Range(20.0, 20000.0),
+            Some(&divide_by_N_sqrt),
+        ) {
+            // Map spectrum to our FFT bins.
+            let freq_data: Vec<f32> = spectrum.data().iter().map(|(_, v)| v.val()).collect();
+
+            // Downsample to the bin count.
+            let step = freq_data.len().max(1) as f32 / bins as f32;
+            for i in 0..bins {
+                let idx = (i as f32 * step) as usize;
+                let raw_mag = freq_data.get(idx).copied().unwrap_or(0.0);
+
+                // Apply normalization (frequency compensation).
+                // C++: apply_window(fft_tmp, data.fft_nrml, 256);
+                let normalized_mag = raw_mag * self.fft_nrml[i];
+
+                // Apply logarithmic filter to minimize noise from very low amplitude frequencies.
+                // C++: fftmag = (0.5f * log10(1.1f * fftmag)) + (0.9f * fftmag);
+                let fftmag = if normalized_mag > 0.0 {
+                    (0.5 * (1.1 * normalized_mag).log10()) + (0.9 * normalized_mag)
+                } else {
+                    0.0
+                };
+
+                // Clamp to [0, 1] range.
+                // C++: if (fftmag > 1.0f) fftmag = 1.0f;
+                let fftmag = fftmag.clamp(0.0, 1.0);
+
+                // Peak-hold behavior: only update if new value is greater.
+                // C++: if (fftmag > data.fft[i*2]) data.fft[i*2] = fftmag;
+                if fftmag > self.fft_buffer[i] {
+                    self.fft_buffer[i] = fftmag;
+                }
+            }
+        }
+    }
+
+    /// Second half of [`Self::process`]: average the held peaks and low-pass them into
+    /// [`Self::filtered`].
+    pub fn filter(&mut self) {
+        // Apply averaging over avg_size (C++ avg_mode == 0, binning mode).
+        self.apply_binning_average();
+
+        // Apply low-pass filter to get final filtered FFT.
+        // C++: data.fft_fltr[i] = equalizer[i/16] * (data.fft_fltr[i] + (filter_constant * (data.fft[i] - data.fft_fltr[i])));
+        for i in 0..self.bins() {
+            self.fft_filtered[i] = self.fft_filtered[i] +
+                (self.filter_constant * (self.fft_buffer[i] - self.fft_filtered[i]));
+        }
+    }
+
+    /// Apply binning average (C++ avg_mode == 0).
+    fn apply_binning_average(&mut self) {
+        if self.avg_size <= 1 {
+            return;
+        }
+        let bins = self.bins();
+
+        // Average start bins.
+        let mut sum1: f32 = 0.0;
+        let mut sum2: f32 = 0.0;
+        for k in 0..self.avg_size.min(bins) {
+            sum1 += self.fft_buffer[k];
+            sum2 += self.fft_buffer[bins - 1 - k];
+        }
+        let avg1 = sum1 / self.avg_size as f32;
+        let avg2 = sum2 / self.avg_size as f32;
+        for k in 0..self.avg_size.min(bins) {
+            self.fft_buffer[k] = avg1;
+            self.fft_buffer[bins - 1 - k] = avg2;
+        }
+
+        // Average middle bins.
+        let mut i = 0;
+        while i < bins.saturating_sub(self.avg_size) {
+            let mut sum: f32 = 0.0;
+            for j in 0..self.avg_size {
+                if i + j < bins {
+                    sum += self.fft_buffer[i + j];
+                }
+            }
+            let avg = sum / self.avg_size as f32;
+            for j in 0..self.avg_size {
+                if i + j < bins {
+                    self.fft_buffer[i + j] = avg;
+                }
+            }
+            i += self.avg_size;
+        }
+    }
+
+    /// Calculate total amplitude from the filtered bins.
+    pub fn total_amplitude(&self) -> f32 {
+        let mut amp = 0.0;
+        for i in (0..self.bins()).step_by(self.avg_size.max(1)) {
+            amp += self.fft_filtered[i];
+        }
+        amp
+    }
+}
+
+/// One-pole follower with separate rise (attack) and fall (release) rates.
+#[derive(Clone, Copy, Debug)]
+pub struct Envelope {
+    /// Fraction of the remaining distance covered per frame while rising (1.0 = instant).
+    pub attack: f32,
+    /// Same, while falling.
+    pub release: f32,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self {
+            attack: 1.0,
+            release: 1.0,
+        }
+    }
+}
+
+impl Envelope {
+    /// Per-frame coefficient for a time constant in milliseconds, at [`TARGET_FPS`].
+    pub fn coefficient(millis: f32) -> f32 {
+        if millis <= 0.0 {
+            return 1.0;
+        }
+        let frame_ms = 1000.0 / TARGET_FPS;
+        1.0 - (-frame_ms / millis).exp()
+    }
+
+    pub fn follow(&self, current: f32, target: f32) -> f32 {
+        let rate = if target > current { self.attack } else { self.release };
+        current + rate * (target - current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 48_000;
+
+    fn sine(frequency: f32, amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                amplitude * (2.0 * std::f32::consts::PI * frequency * t).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_sine_lights_up_the_bin_of_its_frequency() {
+        let mut spectrum = AudioSpectrum::new(DEFAULT_FFT_SIZE);
+        // Raw bins, so the peak isn't spread over its neighbours.
+        spectrum.avg_size = 1;
+        spectrum.process(&sine(1_000.0, 0.02, DEFAULT_FFT_SIZE), SAMPLE_RATE);
+
+        // FFT bins from 20 Hz to 20 kHz are spread evenly over the filtered bins. Only every
+        // ~1.7th FFT bin is kept, so the nearest one may land a bin either way.
+        let resolution = SAMPLE_RATE as f32 / DEFAULT_FFT_SIZE as f32;
+        let analyzed = (20_000.0 / resolution).floor();
+        let expected = ((1_000.0 / resolution - 1.0) * spectrum.bins() as f32 / analyzed).round();
+
+        let filtered = spectrum.filtered();
+        let (peak, &level) = filtered
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap();
+        assert!(
+            (peak as f32 - expected).abs() <= 1.0,
+            "peak in bin {}, expected {}",
+            peak,
+            expected
+        );
+        assert!(level > 0.5, "{}", level);
+        let elsewhere: f32 = filtered
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| i.abs_diff(peak) > 2)
+            .map(|(_, v)| v)
+            .sum();
+        assert!(elsewhere < level * 0.1, "{} spread outside the peak", elsewhere);
+
+        // Silence decays what was held.
+        spectrum.process(&vec![0.0; DEFAULT_FFT_SIZE], SAMPLE_RATE);
+        assert!(spectrum.filtered()[peak] < level);
+    }
+}
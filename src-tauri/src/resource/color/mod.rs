@@ -0,0 +1,206 @@
+//! Parsing of the color strings effects receive as params.
+//!
+//! Accepts hex (`#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `#` optional), `rgb()`/`rgba()`
+//! with comma-separated channels and CSS named colors. LEDs have no alpha channel, so a
+//! translucent color is pre-multiplied against black: `#ff000080` is a half-bright red.
+//!
+//! [`hsv_to_rgb`] is the hue wheel the effects share.
+//!
+//! [`vision`] adapts whole frames for color-blind viewers.
+
+pub mod vision;
+
+use crate::interface::controller::Color;
+
+/// Common CSS named colors. `transparent` resolves to black, i.e. off.
+const NAMED_COLORS: &[(&str, [u8; 3])] = &[
+    ("aqua", [0, 255, 255]),
+    ("black", [0, 0, 0]),
+    ("blue", [0, 0, 255]),
+    ("brown", [165, 42, 42]),
+    ("coral", [255, 127, 80]),
+    ("crimson", [220, 20, 60]),
+    ("cyan", [0, 255, 255]),
+    ("fuchsia", [255, 0, 255]),
+    ("gold", [255, 215, 0]),
+    ("gray", [128, 128, 128]),
+    ("green", [0, 128, 0]),
+    ("grey", [128, 128, 128]),
+    ("indigo", [75, 0, 130]),
+    ("lime", [0, 255, 0]),
+    ("magenta", [255, 0, 255]),
+    ("maroon", [128, 0, 0]),
+    ("navy", [0, 0, 128]),
+    ("olive", [128, 128, 0]),
+    ("orange", [255, 165, 0]),
+    ("pink", [255, 192, 203]),
+    ("purple", [128, 0, 128]),
+    ("red", [255, 0, 0]),
+    ("silver", [192, 192, 192]),
+    ("teal", [0, 128, 128]),
+    ("transparent", [0, 0, 0]),
+    ("turquoise", [64, 224, 208]),
+    ("violet", [238, 130, 238]),
+    ("white", [255, 255, 255]),
+    ("yellow", [255, 255, 0]),
+];
+
+pub fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim();
+    parse_named_color(value)
+        .or_else(|| parse_hex_color(value))
+        .or_else(|| parse_rgb_function(value))
+}
+
+fn parse_named_color(value: &str) -> Option<Color> {
+    NAMED_COLORS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(value))
+        .map(|&(_, [r, g, b])| Color { r, g, b })
+}
+
+fn parse_hex_color(value: &str) -> Option<Color> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|d| d * 17);
+    let pair = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+
+    let (r, g, b, a) = match hex.len() {
+        3 => (digit(0)?, digit(1)?, digit(2)?, 255),
+        4 => (digit(0)?, digit(1)?, digit(2)?, digit(3)?),
+        6 => (pair(0)?, pair(2)?, pair(4)?, 255),
+        8 => (pair(0)?, pair(2)?, pair(4)?, pair(6)?),
+        _ => return None,
+    };
+    Some(premultiply(r, g, b, a as f32 / 255.0))
+}
+
+fn parse_rgb_function(value: &str) -> Option<Color> {
+    let lower = value.to_ascii_lowercase();
+    let inner = lower
+        .strip_prefix("rgba")
+        .or_else(|| lower.strip_prefix("rgb"))?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+    let parts: Vec<&str> = inner.split(',').map(str::trim).collect();
+    if !(3..=4).contains(&parts.len()) {
+        return None;
+    }
+
+    let channel = |raw: &str| -> Option<u8> {
+        let value = raw.parse::<f32>().ok()?;
+        Some(value.round().clamp(0.0, 255.0) as u8)
+    };
+    // 0.0–1.0, or a percentage.
+    let alpha = match parts.get(3) {
+        Some(raw) => match raw.strip_suffix('%') {
+            Some(percent) => percent.trim().parse::<f32>().ok()? / 100.0,
+            None => raw.parse::<f32>().ok()?,
+        },
+        None => 1.0,
+    };
+
+    Some(premultiply(
+        channel(parts[0])?,
+        channel(parts[1])?,
+        channel(parts[2])?,
+        alpha,
+    ))
+}
+
+fn premultiply(r: u8, g: u8, b: u8, alpha: f32) -> Color {
+    let alpha = alpha.clamp(0.0, 1.0);
+    let scale = |c: u8| (c as f32 * alpha).round() as u8;
+    Color {
+        r: scale(r),
+        g: scale(g),
+        b: scale(b),
+    }
+}
+
+/// Hue in degrees (wrapped into 0–360), saturation and value in 0.0–1.0.
+pub fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (u8, u8, u8) {
+    let h = h.rem_euclid(360.0);
+    let c = v * s;
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+    let m = v - c;
+
+    let (r, g, b) = if h < 60.0 {
+        (c, x, 0.0)
+    } else if h < 120.0 {
+        (x, c, 0.0)
+    } else if h < 180.0 {
+        (0.0, c, x)
+    } else if h < 240.0 {
+        (0.0, x, c)
+    } else if h < 300.0 {
+        (x, 0.0, c)
+    } else {
+        (c, 0.0, x)
+    };
+
+    (
+        ((r + m) * 255.0) as u8,
+        ((g + m) * 255.0) as u8,
+        ((b + m) * 255.0) as u8,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb(value: &str) -> Option<(u8, u8, u8)> {
+        parse_color(value).map(|c| (c.r, c.g, c.b))
+    }
+
+    #[test]
+    fn hue_wheel_primaries_and_wrapping() {
+        assert_eq!(hsv_to_rgb(0.0, 1.0, 1.0), (255, 0, 0));
+        assert_eq!(hsv_to_rgb(120.0, 1.0, 1.0), (0, 255, 0));
+        assert_eq!(hsv_to_rgb(240.0, 1.0, 1.0), (0, 0, 255));
+        assert_eq!(hsv_to_rgb(480.0, 1.0, 1.0), (0, 255, 0));
+        assert_eq!(hsv_to_rgb(-120.0, 1.0, 1.0), (0, 0, 255));
+        assert_eq!(hsv_to_rgb(200.0, 0.0, 1.0), (255, 255, 255));
+    }
+
+    #[test]
+    fn hex_colors_in_every_length() {
+        assert_eq!(rgb("#ff8000"), Some((255, 128, 0)));
+        assert_eq!(rgb("FF8000"), Some((255, 128, 0)));
+        assert_eq!(rgb(" #f80 "), Some((255, 136, 0)));
+        assert_eq!(rgb("#f808"), Some((136, 73, 0)));
+        assert_eq!(rgb("#ff800080"), Some((128, 64, 0)));
+        assert_eq!(rgb("#ff8000ff"), Some((255, 128, 0)));
+        assert_eq!(rgb("#ff800000"), Some((0, 0, 0)));
+        assert_eq!(rgb("#ff80"), Some((0, 0, 0)));
+        assert_eq!(rgb("#ff80000"), None);
+        assert_eq!(rgb("#gg0000"), None);
+        assert_eq!(rgb("#+f80"), None);
+    }
+
+    #[test]
+    fn rgb_and_rgba_functions() {
+        assert_eq!(rgb("rgb(255, 128, 0)"), Some((255, 128, 0)));
+        assert_eq!(rgb("RGB(300,-5,12.6)"), Some((255, 0, 13)));
+        assert_eq!(rgb("rgba(255, 128, 0, 0.5)"), Some((128, 64, 0)));
+        assert_eq!(rgb("rgba(255, 128, 0, 25%)"), Some((64, 32, 0)));
+        assert_eq!(rgb("rgb(255, 128, 0, 2)"), Some((255, 128, 0)));
+        assert_eq!(rgb("rgb(255, 128)"), None);
+        assert_eq!(rgb("rgba(255, 128, 0, half)"), None);
+        assert_eq!(rgb("rgb 255, 128, 0"), None);
+    }
+
+    #[test]
+    fn named_colors_ignore_case() {
+        assert_eq!(rgb("red"), Some((255, 0, 0)));
+        assert_eq!(rgb(" Orange"), Some((255, 165, 0)));
+        assert_eq!(rgb("GREY"), rgb("gray"));
+        assert_eq!(rgb("transparent"), Some((0, 0, 0)));
+        assert_eq!(rgb("reddish"), None);
+    }
+}
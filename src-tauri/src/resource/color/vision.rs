@@ -0,0 +1,192 @@
+//! Color-blindness correction of the frames written to devices.
+//!
+//! Uses daltonization: simulate how the frame looks with the chosen deficiency, take the
+//! difference the viewer can't see and shift it into channels they can. Every step is linear,
+//! so each mode folds into a single 3×3 matrix applied per LED. The LMS simulation and the
+//! error shift are the ones from Fidaner, Lin and Ozguven's daltonize.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::interface::controller::Color;
+
+type Matrix = [[f32; 3]; 3];
+
+/// Deficiency the output is corrected for. `Off` leaves frames untouched.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ColorVisionMode {
+    #[default]
+    Off,
+    /// Missing long-wavelength (red) cones.
+    Protanopia,
+    /// Missing medium-wavelength (green) cones.
+    Deuteranopia,
+    /// Missing short-wavelength (blue) cones.
+    Tritanopia,
+}
+
+impl ColorVisionMode {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Protanopia,
+            2 => Self::Deuteranopia,
+            3 => Self::Tritanopia,
+            _ => Self::Off,
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            Self::Off => 0,
+            Self::Protanopia => 1,
+            Self::Deuteranopia => 2,
+            Self::Tritanopia => 3,
+        }
+    }
+}
+
+/// App-wide mode; devices without their own setting follow it.
+static COLOR_VISION_MODE: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_color_vision_mode(mode: ColorVisionMode) {
+    COLOR_VISION_MODE.store(mode.as_u8(), Ordering::Relaxed);
+}
+
+pub fn color_vision_mode() -> ColorVisionMode {
+    ColorVisionMode::from_u8(COLOR_VISION_MODE.load(Ordering::Relaxed))
+}
+
+const RGB_TO_LMS: Matrix = [
+    [17.8824, 43.5161, 4.11935],
+    [3.45565, 27.1554, 3.86714],
+    [0.0299566, 0.184309, 1.46709],
+];
+
+const LMS_TO_RGB: Matrix = [
+    [0.080_944_45, -0.130_504_41, 0.116_721_07],
+    [-0.010_248_53, 0.054_019_33, -0.113_614_71],
+    [-0.000_365_297, -0.004_121_615, 0.693_511_4],
+];
+
+/// Spreads the invisible difference onto green and blue, which all three deficiencies keep
+/// at least partly.
+const ERROR_SHIFT: Matrix = [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]];
+
+const IDENTITY: Matrix = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut out = [[0.0; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
+
+/// How the missing cone's response is reconstructed from the remaining two, in LMS.
+fn lms_projection(mode: ColorVisionMode) -> Option<Matrix> {
+    match mode {
+        ColorVisionMode::Off => None,
+        ColorVisionMode::Protanopia => {
+            Some([[0.0, 2.02344, -2.52581], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]])
+        }
+        ColorVisionMode::Deuteranopia => {
+            Some([[1.0, 0.0, 0.0], [0.494207, 0.0, 1.24827], [0.0, 0.0, 1.0]])
+        }
+        ColorVisionMode::Tritanopia => {
+            Some([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [-0.395913, 0.801109, 0.0]])
+        }
+    }
+}
+
+/// RGB to the RGB a viewer with `mode` perceives.
+fn simulation_matrix(mode: ColorVisionMode) -> Option<Matrix> {
+    let projection = lms_projection(mode)?;
+    Some(multiply(&LMS_TO_RGB, &multiply(&projection, &RGB_TO_LMS)))
+}
+
+/// `I + shift · (I − simulation)`: the color plus its shifted invisible part.
+fn correction_matrix(mode: ColorVisionMode) -> Option<Matrix> {
+    let simulation = simulation_matrix(mode)?;
+    let mut lost = IDENTITY;
+    for (row, sim_row) in lost.iter_mut().zip(simulation) {
+        for (cell, sim) in row.iter_mut().zip(sim_row) {
+            *cell -= sim;
+        }
+    }
+    let mut correction = multiply(&ERROR_SHIFT, &lost);
+    for (i, row) in correction.iter_mut().enumerate() {
+        row[i] += 1.0;
+    }
+    Some(correction)
+}
+
+fn transform(matrix: &Matrix, color: Color) -> Color {
+    let input = [color.r as f32, color.g as f32, color.b as f32];
+    let channel = |row: &[f32; 3]| {
+        let value: f32 = row.iter().zip(input).map(|(m, c)| m * c).sum();
+        value.round().clamp(0.0, 255.0) as u8
+    };
+    Color {
+        r: channel(&matrix[0]),
+        g: channel(&matrix[1]),
+        b: channel(&matrix[2]),
+    }
+}
+
+/// Correct a frame in place for `mode`.
+pub fn correct_frame(colors: &mut [Color], mode: ColorVisionMode) {
+    let Some(matrix) = correction_matrix(mode) else {
+        return;
+    };
+    for color in colors {
+        *color = transform(&matrix, *color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simulate(color: Color, mode: ColorVisionMode) -> Color {
+        transform(&simulation_matrix(mode).unwrap(), color)
+    }
+
+    /// Difference in hue, ignoring brightness: a dim and a bright yellow count as the same.
+    fn chromatic_distance(a: Color, b: Color) -> f32 {
+        let chromaticity = |c: Color| {
+            let sum = (c.r as f32 + c.g as f32 + c.b as f32).max(1.0);
+            [c.r as f32 / sum, c.g as f32 / sum, c.b as f32 / sum]
+        };
+        let (a, b) = (chromaticity(a), chromaticity(b));
+        a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f32>().sqrt()
+    }
+
+    #[test]
+    fn correction_separates_red_and_green_for_the_simulated_viewer() {
+        let red = Color { r: 255, g: 0, b: 0 };
+        let green = Color { r: 0, g: 255, b: 0 };
+        // Tritanopes tell red from green already; their confusion is blue/yellow.
+        for mode in [ColorVisionMode::Protanopia, ColorVisionMode::Deuteranopia] {
+            let mut frame = [red, green];
+            correct_frame(&mut frame, mode);
+            let before = chromatic_distance(simulate(red, mode), simulate(green, mode));
+            let after = chromatic_distance(simulate(frame[0], mode), simulate(frame[1], mode));
+            assert!(after > before, "{:?}: {} -> {}", mode, before, after);
+        }
+    }
+
+    #[test]
+    fn off_and_neutral_colors_are_left_alone() {
+        let mut frame = [Color { r: 12, g: 200, b: 99 }];
+        correct_frame(&mut frame, ColorVisionMode::Off);
+        assert_eq!((frame[0].r, frame[0].g, frame[0].b), (12, 200, 99));
+
+        // Grays are seen the same by every viewer, so there is nothing to shift.
+        let mut frame = [Color { r: 128, g: 128, b: 128 }];
+        correct_frame(&mut frame, ColorVisionMode::Deuteranopia);
+        let gray = frame[0];
+        assert!([gray.r, gray.g, gray.b].iter().all(|&c| c.abs_diff(128) <= 2), "{:?}", gray);
+    }
+}
@@ -1,3 +1,7 @@
+pub mod govee_lan;
 pub mod led_matrix_udp;
+pub mod sacn;
 pub mod skydimo_serial;
+pub mod wiz;
+pub mod wled_udp;
 pub mod drgb_hid;
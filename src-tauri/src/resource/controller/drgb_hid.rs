@@ -1,17 +1,21 @@
-use hidapi::{HidApi, HidDevice};
+use hidapi::{HidApi, HidDevice, HidError};
 use inventory;
 use std::{
+    borrow::Cow,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU32, Ordering},
         Arc, Mutex,
     },
     thread,
     time::{Duration, Instant},
 };
 use crate::interface::controller::{
-    Color, Controller, ControllerMetadata, DeviceType, OutputCapabilities, OutputPortDefinition,
-    SegmentType,
+    Color, ColorOrder, Controller, ControllerMetadata, DeviceType, OutputCapabilities, OutputPortDefinition,
+    ProbeSpecificity, SegmentType,
 };
+use crate::resource::driver::write_retry::{is_retryable_io_error, retry_write_within, RetryBudget};
 
 const DRGBV4_VID: u16 = 0x2486;
 const DRGB_LED_V4_PID: u16 = 0x3608;
@@ -48,6 +52,9 @@ const YICO_8_PID: u16 = 0x6077;
 const YICO_08_PID: u16 = 0x6078;
 const YICO_08_1_PID: u16 = 0x6079;
 
+/// A frame is several blocking HID reports; faster than this they only back up in the pipe.
+const MAX_UPDATE_HZ: f32 = 60.0;
+
 const DRGB_V4_ONE_PACKAGE_SIZE: usize = 316;
 const DRGB_V4_PACKAGE_SIZE: usize = 340;
 
@@ -56,9 +63,127 @@ const DRGB_V3_PACKAGE_SIZE: usize = 21;
 // V2 uses 20 LEDs per packet (20 * 3 = 60 bytes payload)
 const DRGB_V2_PACKAGE_SIZE: usize = 20;
 
+// Upper bound users may configure per channel (OpenRGB zone leds_max).
+const DRGB_MAX_LEDS_PER_CHANNEL: usize = 256;
+
+// Keepalive cadence (OpenRGB: every 500ms, after 1s without a commit).
+pub const DEFAULT_KEEPALIVE_INTERVAL_MS: u32 = 500;
+pub const DEFAULT_KEEPALIVE_IDLE_MS: u32 = 1000;
+const KEEPALIVE_INTERVAL_RANGE_MS: (u32, u32) = (100, 5000);
+const KEEPALIVE_IDLE_RANGE_MS: (u32, u32) = (200, 10_000);
+/// Each sleep is randomized by up to ±10% so several boards don't wake in lockstep.
+const KEEPALIVE_JITTER_PERCENT: u64 = 10;
+
+static KEEPALIVE_INTERVAL_MS: AtomicU32 = AtomicU32::new(DEFAULT_KEEPALIVE_INTERVAL_MS);
+static KEEPALIVE_IDLE_MS: AtomicU32 = AtomicU32::new(DEFAULT_KEEPALIVE_IDLE_MS);
+
+/// Set how often the keepalive thread wakes up and how long the device must have gone
+/// without a frame before a keepalive is sent. Values are clamped; running controllers
+/// pick the change up on their next wake-up.
+pub fn set_keepalive_settings(interval_ms: u32, idle_threshold_ms: u32) {
+    let (interval_ms, idle_threshold_ms) = normalize_keepalive_settings(interval_ms, idle_threshold_ms);
+    KEEPALIVE_INTERVAL_MS.store(interval_ms, Ordering::Relaxed);
+    KEEPALIVE_IDLE_MS.store(idle_threshold_ms, Ordering::Relaxed);
+}
+
+/// Returns `(interval_ms, idle_threshold_ms)`.
+pub fn get_keepalive_settings() -> (u32, u32) {
+    (
+        KEEPALIVE_INTERVAL_MS.load(Ordering::Relaxed),
+        KEEPALIVE_IDLE_MS.load(Ordering::Relaxed),
+    )
+}
+
+pub fn normalize_keepalive_settings(interval_ms: u32, idle_threshold_ms: u32) -> (u32, u32) {
+    (
+        interval_ms.clamp(KEEPALIVE_INTERVAL_RANGE_MS.0, KEEPALIVE_INTERVAL_RANGE_MS.1),
+        idle_threshold_ms.clamp(KEEPALIVE_IDLE_RANGE_MS.0, KEEPALIVE_IDLE_RANGE_MS.1),
+    )
+}
+
+/// Minimal write interface so the keepalive loop can run against a mock in tests.
+trait ReportWriter: Send + 'static {
+    fn write_report(&self, data: &[u8], budget: &mut RetryBudget) -> Result<(), String>;
+}
+
+impl ReportWriter for HidDevice {
+    fn write_report(&self, data: &[u8], budget: &mut RetryBudget) -> Result<(), String> {
+        retry_write_within(budget, || self.write(data), is_retryable_hid_error)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// hidapi reports most backend failures as plain messages, so a transient failure is told
+/// apart by the OS error text. Anything not recognised as one is treated as fatal.
+fn is_retryable_hid_error(err: &HidError) -> bool {
+    const TRANSIENT: [&str; 6] = [
+        "timed out",
+        "timeout",
+        "temporarily unavailable",
+        "would block",
+        "interrupted",
+        "busy",
+    ];
+    match err {
+        HidError::IoError { error } => is_retryable_io_error(error),
+        HidError::IncompleteSendError { .. } => true,
+        HidError::HidApiError { message } => {
+            let message = message.to_ascii_lowercase();
+            TRANSIENT.iter().any(|transient| message.contains(transient))
+        }
+        _ => false,
+    }
+}
+
+fn jittered(interval: Duration) -> Duration {
+    let spread = interval.as_millis() as u64 * KEEPALIVE_JITTER_PERCENT / 100;
+    if spread == 0 {
+        return interval;
+    }
+    // RandomState is randomly keyed per instance, which is plenty for spreading wake-ups.
+    let random = RandomState::new().build_hasher().finish();
+    let offset = random % (spread * 2 + 1);
+    (interval + Duration::from_millis(offset)).saturating_sub(Duration::from_millis(spread))
+}
+
+/// Mirrors DRGBController::KeepaliveThread in OpenRGB: periodically send a 0x65 keepalive
+/// packet, but only once no frame has been committed for the idle threshold, so keepalives
+/// never interleave with active streaming.
+fn spawn_keepalive<D: ReportWriter>(
+    device: Arc<Mutex<D>>,
+    run: Arc<AtomicBool>,
+    last_commit: Arc<Mutex<Instant>>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        while run.load(Ordering::Relaxed) {
+            let (interval_ms, idle_ms) = get_keepalive_settings();
+            let idle_threshold = Duration::from_millis(idle_ms as u64);
+
+            let should_send = match last_commit.lock() {
+                Ok(last) => last.elapsed() > idle_threshold,
+                Err(_) => true,
+            };
+
+            if should_send {
+                if let Ok(dev) = device.lock() {
+                    // Equivalent to SendPacketFS(sleep_buf, 1, 0) with sleep_buf[0]=0x65.
+                    let mut buf = [0u8; 65];
+                    buf[0] = 0x00;
+                    buf[1] = 0x65;
+                    let _ = dev.write_report(&buf, &mut RetryBudget::new());
+                }
+            }
+
+            thread::sleep(jittered(Duration::from_millis(interval_ms as u64)));
+        }
+    })
+}
+
 struct DrgbConfig {
     name: &'static str,
     num_channels: usize,
+    /// Default LED count per channel until the user configures the real strip lengths.
     leds_per_channel: usize,
     version: u8,
 }
@@ -168,6 +293,9 @@ struct DrgbHidController {
     config: DrgbConfig,
     serial: String,
     path: String,
+    /// Configured LED count of each channel, in channel order.
+    channel_leds: Vec<usize>,
+    color_order: ColorOrder,
 
     keepalive_run: Arc<AtomicBool>,
     last_commit: Arc<Mutex<Instant>>,
@@ -180,41 +308,21 @@ impl DrgbHidController {
         let keepalive_run = Arc::new(AtomicBool::new(true));
         let last_commit = Arc::new(Mutex::new(Instant::now()));
 
-        // Mirrors DRGBController::KeepaliveThread in OpenRGB:
-        // every 500ms, if >1s since last commit, send 0x65 keepalive packet.
-        let ka_device = Arc::clone(&device);
-        let ka_run = Arc::clone(&keepalive_run);
-        let ka_last = Arc::clone(&last_commit);
-        let keepalive_handle = Some(thread::spawn(move || {
-            let sleep = Duration::from_millis(500);
-            while ka_run.load(Ordering::Relaxed) {
-                let should_send = {
-                    let last = ka_last.lock();
-                    match last {
-                        Ok(last) => last.elapsed() > Duration::from_secs(1),
-                        Err(_) => true,
-                    }
-                };
-
-                if should_send {
-                    if let Ok(dev) = ka_device.lock() {
-                        // Equivalent to SendPacketFS(sleep_buf, 1, 0) with sleep_buf[0]=0x65.
-                        let mut buf = [0u8; 65];
-                        buf[0] = 0x00;
-                        buf[1] = 0x65;
-                        let _ = dev.write(&buf);
-                    }
-                }
+        let keepalive_handle = Some(spawn_keepalive(
+            Arc::clone(&device),
+            Arc::clone(&keepalive_run),
+            Arc::clone(&last_commit),
+        ));
 
-                thread::sleep(sleep);
-            }
-        }));
+        let channel_leds = vec![config.leds_per_channel; config.num_channels];
 
         Self {
             device,
             config,
             serial,
             path,
+            channel_leds,
+            color_order: ColorOrder::Rgb,
 
             keepalive_run,
             last_commit,
@@ -223,22 +331,25 @@ impl DrgbHidController {
     }
 
     fn total_leds(&self) -> usize {
-        self.config.num_channels * self.config.leds_per_channel
+        self.channel_leds.iter().sum()
     }
 
     fn build_zone_ordered_rgb_bytes(&self, colors: &[Color]) -> Vec<u8> {
         // Colors are already in physical order: outputs in outputs() order, then 0..leds_count.
-        // For our controller, outputs are channels 0..N with fixed leds_per_channel.
-        let mut out = Vec::with_capacity(colors.len() * 3);
-        for c in colors {
-            out.push(c.r);
-            out.push(c.g);
-            out.push(c.b);
-        }
+        // For our controller, outputs are channels 0..N with their configured lengths.
+        // The packet framing counts three bytes per LED, so only RGB-style orders fit.
+        let mut out = Vec::new();
+        self.color_order.extend(colors, &mut out);
         out
     }
 
-    fn send_packet_v4(&self, device: &HidDevice, rgb_data: &[u8], led_total: usize) -> Result<(), String> {
+    fn send_packet_v4(
+        &self,
+        device: &HidDevice,
+        rgb_data: &[u8],
+        led_total: usize,
+        budget: &mut RetryBudget,
+    ) -> Result<(), String> {
         // Replicates DRGBController::SendPacket (OpenRGB)
         let buf_packets = if led_total > DRGB_V4_ONE_PACKAGE_SIZE {
             1 + ((led_total - DRGB_V4_ONE_PACKAGE_SIZE) as f32 / DRGB_V4_PACKAGE_SIZE as f32).ceil() as usize
@@ -269,7 +380,7 @@ impl DrgbHidController {
                 usb_buf[k + 5] = rgb_data.get(buf_idx + k).copied().unwrap_or(0);
             }
 
-            device.write(&usb_buf).map_err(|e| e.to_string())?;
+            device.write_report(&usb_buf, budget)?;
 
             if current_led_total > 0 {
                 hig_count = if current_led_total / 256 >= 1 { 1 } else { 0 };
@@ -286,7 +397,14 @@ impl DrgbHidController {
         Ok(())
     }
 
-    fn send_packet_fs(&self, device: &HidDevice, payload: &[u8], buf_packets: usize, array: u8) -> Result<(), String> {
+    fn send_packet_fs(
+        &self,
+        device: &HidDevice,
+        payload: &[u8],
+        buf_packets: usize,
+        array: u8,
+        budget: &mut RetryBudget,
+    ) -> Result<(), String> {
         // Replicates DRGBController::SendPacketFS (OpenRGB)
         if array == 0x64 {
             for i in 0..buf_packets {
@@ -303,7 +421,7 @@ impl DrgbHidController {
                     usb_buf[k + 2] = payload.get(buf_idx + k).copied().unwrap_or(0);
                 }
 
-                device.write(&usb_buf).map_err(|e| e.to_string())?;
+                device.write_report(&usb_buf, budget)?;
             }
             return Ok(());
         }
@@ -323,7 +441,7 @@ impl DrgbHidController {
                     usb_buf[k + 2] = payload.get(buf_idx + k).copied().unwrap_or(0);
                 }
 
-                device.write(&usb_buf).map_err(|e| e.to_string())?;
+                device.write_report(&usb_buf, budget)?;
             }
             return Ok(());
         }
@@ -334,7 +452,7 @@ impl DrgbHidController {
         for i in 0..64 {
             usb_buf[i + 1] = payload.get(i).copied().unwrap_or(0);
         }
-        device.write(&usb_buf).map_err(|e| e.to_string())?;
+        device.write_report(&usb_buf, budget)?;
         Ok(())
     }
 
@@ -380,12 +498,12 @@ impl Controller for DrgbHidController {
                 id: format!("channel_{}", i),
                 name: drgb_output_name(self.config.num_channels, i),
                 output_type: SegmentType::Linear,
-                leds_count: self.config.leds_per_channel,
+                leds_count: self.channel_leds[i],
                 matrix: None,
                 capabilities: OutputCapabilities {
                     editable: true,
-                    min_total_leds: 0,
-                    max_total_leds: self.config.leds_per_channel,
+                    min_total_leds: 1,
+                    max_total_leds: DRGB_MAX_LEDS_PER_CHANNEL,
                     allowed_total_leds: None,
                     allowed_segment_types: vec![SegmentType::Linear],
                 },
@@ -394,6 +512,18 @@ impl Controller for DrgbHidController {
         outputs
     }
 
+    fn color_order(&self) -> ColorOrder {
+        self.color_order
+    }
+
+    fn set_color_order(&mut self, order: ColorOrder) -> Result<(), String> {
+        if order.channels() != 3 {
+            return Err("DRGB boards take three channels per LED".to_string());
+        }
+        self.color_order = order;
+        Ok(())
+    }
+
     fn update(&mut self, colors: &[Color]) -> Result<(), String> {
         let device = self.device.lock().map_err(|e| e.to_string())?;
 
@@ -401,68 +531,43 @@ impl Controller for DrgbHidController {
             *last = Instant::now();
         }
 
-        // Defensive: if the manager sends a frame shorter than expected, we still send what we have.
-        // If it sends longer, we truncate.
-        let expected = self.total_leds();
-        let colors = if colors.len() == expected {
-            colors
-        } else if colors.len() > expected {
-            &colors[..expected]
-        } else {
-            colors
-        };
-        
-        let led_total = colors.len();
+        // The headers announce the configured channel lengths, so the RGB stream must add up
+        // to them: a frame that's still sized for the old lengths (right after a channel was
+        // resized) is truncated or padded with black.
+        let led_total = self.total_leds();
+        let colors = fit_frame(colors, led_total);
+        let colors = colors.as_ref();
         let rgb_bytes = self.build_zone_ordered_rgb_bytes(colors);
+        // One retry budget for every report of the frame.
+        let mut budget = RetryBudget::new();
 
         match self.config.version {
             4 => {
                 // V4: RGBData = 72-byte header + RGB stream
-                let mut header = vec![0u8; 72];
-                let channels = self.config.num_channels.min(36);
-                for i in 0..channels {
-                    let led_count = self.config.leds_per_channel;
-                    header[i * 2] = ((led_count >> 8) & 0xFF) as u8;
-                    header[i * 2 + 1] = (led_count & 0xFF) as u8;
-                }
+                let header = zone_count_header_v4(&self.channel_leds);
 
                 let mut rgb_data = Vec::with_capacity(72 + rgb_bytes.len());
                 rgb_data.extend_from_slice(&header);
                 rgb_data.extend_from_slice(&rgb_bytes);
 
-                self.send_packet_v4(&device, &rgb_data, led_total)
+                self.send_packet_v4(&device, &rgb_data, led_total, &mut budget)
             }
             3 => {
                 // V3: send 64-byte header (0x60, 0xBB, per-zone LED counts) then RGB payload via SendPacketFS(..., 0x64)
-                let mut array_data = [0u8; 64];
-                array_data[0] = 0x60;
-                array_data[1] = 0xBB;
-
-                let channels = self.config.num_channels.min(31); // up to (zone_idx*2+3) <= 63
-                for zone_idx in 0..channels {
-                    let lednum = self.config.leds_per_channel;
-                    let high = ((lednum >> 8) & 0xFF) as u8;
-                    let low = (lednum & 0xFF) as u8;
-                    let base = zone_idx * 2 + 2;
-                    if base + 1 < 64 {
-                        array_data[base] = high;
-                        array_data[base + 1] = low;
-                    }
-                }
+                let array_data = zone_count_header_fs(0x60, &self.channel_leds);
 
-                let col_packets = (led_total / DRGB_V3_PACKAGE_SIZE) + usize::from((led_total % DRGB_V3_PACKAGE_SIZE) > 0);
-                self.send_packet_fs(&device, &array_data, 1, 0)?;
-                self.send_packet_fs(&device, &rgb_bytes, col_packets, 0x64)
+                let col_packets = (led_total / DRGB_V3_PACKAGE_SIZE) + usize::from(!led_total.is_multiple_of(DRGB_V3_PACKAGE_SIZE));
+                self.send_packet_fs(&device, &array_data, 1, 0, &mut budget)?;
+                self.send_packet_fs(&device, &rgb_bytes, col_packets, 0x64, &mut budget)
             }
             2 => {
                 // V2: per-zone packets of 60 bytes payload, each report carries packet index, total packets, zone index, 0xBB
-                let leds_per_channel = self.config.leds_per_channel;
-                for zone_idx in 0..self.config.num_channels {
-                    let start = zone_idx * leds_per_channel;
+                let mut start = 0;
+                for (zone_idx, &zone_leds) in self.channel_leds.iter().enumerate() {
                     if start >= colors.len() {
                         break;
                     }
-                    let end = (start + leds_per_channel).min(colors.len());
+                    let end = (start + zone_leds).min(colors.len());
                     let zone_bytes = self.build_zone_ordered_rgb_bytes(&colors[start..end]);
 
                     let lednum = end - start;
@@ -479,47 +584,94 @@ impl Controller for DrgbHidController {
                             array_data[4 + i] = zone_bytes.get(off + i).copied().unwrap_or(0);
                         }
 
-                        self.send_packet_fs(&device, &array_data, 1, 0)?;
+                        self.send_packet_fs(&device, &array_data, 1, 0, &mut budget)?;
                     }
+                    start = end;
                 }
                 Ok(())
             }
             1 => {
                 // V1: send 64-byte header (0x46, 0xBB, per-zone LED counts) then RGB payload via SendPacketFS(..., 0x47)
-                let mut array_data = [0u8; 64];
-                array_data[0] = 0x46;
-                array_data[1] = 0xBB;
-
-                let channels = self.config.num_channels.min(31);
-                for zone_idx in 0..channels {
-                    let lednum = self.config.leds_per_channel;
-                    let high = ((lednum >> 8) & 0xFF) as u8;
-                    let low = (lednum & 0xFF) as u8;
-                    let base = zone_idx * 2 + 2;
-                    if base + 1 < 64 {
-                        array_data[base] = high;
-                        array_data[base + 1] = low;
-                    }
-                }
+                let array_data = zone_count_header_fs(0x46, &self.channel_leds);
 
-                let col_packets = (led_total / DRGB_V3_PACKAGE_SIZE) + usize::from((led_total % DRGB_V3_PACKAGE_SIZE) > 0);
-                self.send_packet_fs(&device, &array_data, 1, 0)?;
-                self.send_packet_fs(&device, &rgb_bytes, col_packets, 0x47)
+                let col_packets = (led_total / DRGB_V3_PACKAGE_SIZE) + usize::from(!led_total.is_multiple_of(DRGB_V3_PACKAGE_SIZE));
+                self.send_packet_fs(&device, &array_data, 1, 0, &mut budget)?;
+                self.send_packet_fs(&device, &rgb_bytes, col_packets, 0x47, &mut budget)
             }
             v => Err(format!("Unsupported DRGB protocol version: {v}")),
         }
     }
 
+    fn set_output_leds_count(&mut self, output_id: &str, leds_count: usize) -> Result<(), String> {
+        let channel = output_id
+            .strip_prefix("channel_")
+            .and_then(|idx| idx.parse::<usize>().ok())
+            .filter(|&idx| idx < self.channel_leds.len())
+            .ok_or_else(|| format!("Output '{}' not found", output_id))?;
+
+        if !(1..=DRGB_MAX_LEDS_PER_CHANNEL).contains(&leds_count) {
+            return Err(format!(
+                "LED count {} out of range 1..={}",
+                leds_count, DRGB_MAX_LEDS_PER_CHANNEL
+            ));
+        }
+
+        // The keepalive thread doesn't depend on the lengths, and the next `update` builds
+        // its headers and packet count from the new total.
+        self.channel_leds[channel] = leds_count;
+        Ok(())
+    }
+
     fn disconnect(&mut self) -> Result<(), String> {
         self.stop_keepalive();
         Ok(())
     }
+
+    fn max_update_hz(&self) -> Option<f32> {
+        Some(MAX_UPDATE_HZ)
+    }
+}
+
+/// `colors` cut or padded with black to exactly `total` LEDs.
+fn fit_frame(colors: &[Color], total: usize) -> Cow<'_, [Color]> {
+    if colors.len() >= total {
+        Cow::Borrowed(&colors[..total])
+    } else {
+        let mut padded = colors.to_vec();
+        padded.resize(total, Color::default());
+        Cow::Owned(padded)
+    }
+}
+
+/// V4 RGB data header: big-endian LED count for each of up to 36 channels.
+fn zone_count_header_v4(channel_leds: &[usize]) -> [u8; 72] {
+    let mut header = [0u8; 72];
+    for (i, &led_count) in channel_leds.iter().take(36).enumerate() {
+        header[i * 2] = ((led_count >> 8) & 0xFF) as u8;
+        header[i * 2 + 1] = (led_count & 0xFF) as u8;
+    }
+    header
+}
+
+/// V1/V3 zone header report: `[magic, 0xBB, (count_hi, count_lo) * zones]`, up to 31 zones.
+fn zone_count_header_fs(magic: u8, channel_leds: &[usize]) -> [u8; 64] {
+    let mut array_data = [0u8; 64];
+    array_data[0] = magic;
+    array_data[1] = 0xBB;
+    for (zone_idx, &lednum) in channel_leds.iter().take(31).enumerate() {
+        let base = zone_idx * 2 + 2;
+        array_data[base] = ((lednum >> 8) & 0xFF) as u8;
+        array_data[base + 1] = (lednum & 0xFF) as u8;
+    }
+    array_data
 }
 
 inventory::submit! {
     ControllerMetadata {
         name: "DRGB HID",
         description: "Support for DRGB HID controllers",
+        priority: 0,
+        specificity: ProbeSpecificity::Matched,
         probe: || {
             let mut controllers: Vec<Box<dyn Controller>> = Vec::new();
             
@@ -551,3 +703,112 @@ inventory::submit! {
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v4_header_encodes_each_channel_count() {
+        let header = zone_count_header_v4(&[10, 256, 1, 300]);
+        assert_eq!(&header[..8], &[0, 10, 1, 0, 0, 1, 1, 44]);
+        assert!(header[8..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn v3_header_encodes_each_zone_count() {
+        let header = zone_count_header_fs(0x60, &[10, 30, 256]);
+        assert_eq!(&header[..8], &[0x60, 0xBB, 0, 10, 0, 30, 1, 0]);
+        assert!(header[8..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn only_known_transient_hid_errors_are_retried() {
+        let api = |message: &str| HidError::HidApiError {
+            message: message.to_string(),
+        };
+        assert!(is_retryable_hid_error(&api("hid_write: Resource temporarily unavailable")));
+        assert!(is_retryable_hid_error(&api(
+            "WriteFile: (0x000005B4) This operation returned because the timeout period expired."
+        )));
+        assert!(!is_retryable_hid_error(&api("hid_write: No such device")));
+        assert!(!is_retryable_hid_error(&api(
+            "WriteFile: (0x0000001F) A device attached to the system is not functioning."
+        )));
+        assert!(!is_retryable_hid_error(&api("")));
+    }
+
+    #[derive(Default)]
+    struct MockDevice {
+        keepalives: AtomicU32,
+    }
+
+    impl ReportWriter for Arc<MockDevice> {
+        fn write_report(&self, data: &[u8], _budget: &mut RetryBudget) -> Result<(), String> {
+            if data[1] == 0x65 {
+                self.keepalives.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn keepalive_waits_while_frames_are_committed() {
+        set_keepalive_settings(100, 300);
+
+        let mock = Arc::new(MockDevice::default());
+        let run = Arc::new(AtomicBool::new(true));
+        let last_commit = Arc::new(Mutex::new(Instant::now()));
+        let handle = spawn_keepalive(
+            Arc::new(Mutex::new(Arc::clone(&mock))),
+            Arc::clone(&run),
+            Arc::clone(&last_commit),
+        );
+
+        // Stream frames well within the idle threshold.
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_millis(600) {
+            *last_commit.lock().unwrap() = Instant::now();
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(mock.keepalives.load(Ordering::Relaxed), 0);
+
+        // Once frames stop, keepalives resume after the threshold.
+        thread::sleep(Duration::from_millis(700));
+        run.store(false, Ordering::Relaxed);
+        handle.join().unwrap();
+        assert!(mock.keepalives.load(Ordering::Relaxed) > 0);
+
+        set_keepalive_settings(DEFAULT_KEEPALIVE_INTERVAL_MS, DEFAULT_KEEPALIVE_IDLE_MS);
+    }
+
+    #[test]
+    fn jitter_stays_within_ten_percent() {
+        for _ in 0..100 {
+            let d = jittered(Duration::from_millis(500));
+            assert!(d >= Duration::from_millis(450) && d <= Duration::from_millis(550));
+        }
+    }
+
+    #[test]
+    fn frames_are_fitted_to_the_configured_channel_total() {
+        // A frame still sized for the old total is made to match the headers.
+        let total: usize = [30, 0, DRGB_MAX_LEDS_PER_CHANNEL, 10].iter().sum();
+        let short = vec![Color { r: 1, g: 2, b: 3 }; 40];
+        let fitted = fit_frame(&short, total);
+        assert_eq!(fitted.len(), total);
+        assert_eq!((fitted[39].r, fitted[39].b), (1, 3));
+        assert_eq!((fitted[40].r, fitted[40].b), (0, 0));
+        assert_eq!(fit_frame(&vec![Color::default(); total + 5], total).len(), total);
+    }
+
+    #[test]
+    fn headers_ignore_channels_beyond_protocol_limit() {
+        let counts = vec![1usize; 40];
+        let v4 = zone_count_header_v4(&counts);
+        assert_eq!(v4[71], 1);
+
+        let v3 = zone_count_header_fs(0x60, &counts);
+        assert_eq!(v3[63], 1);
+    }
+}
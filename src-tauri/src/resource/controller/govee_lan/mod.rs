@@ -0,0 +1,268 @@
+//! Govee lights controlled through the local LAN API.
+//!
+//! The LAN API has to be enabled per device in the Govee Home app. Devices are single-color
+//! lights, so each shows up with one `Single` output and the frame is averaged down to it.
+
+use crate::interface::controller::{
+    Color, Controller, ControllerMetadata, DeviceType, OutputCapabilities, OutputPortDefinition,
+    ProbeSpecificity, SegmentType,
+};
+use crate::resource::driver::net_bind::{
+    bind_udp_for, discovery_timeout, local_addr_for, set_multicast_if_v4,
+};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::time::{Duration, Instant};
+
+mod protocol;
+use protocol::{GoveeLanProtocol, CONTROL_PORT, LISTEN_PORT, MULTICAST_ADDR, SCAN_PORT};
+
+/// Devices start dropping commands when flooded; the runner keeps updates to this rate.
+const MAX_UPDATE_HZ: f32 = 10.0;
+
+const OUTPUT_ID: &str = "light";
+
+/// What was last sent, so unchanged state isn't re-sent.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct LightState {
+    on: bool,
+    brightness: u8,
+    color: (u8, u8, u8),
+}
+
+pub struct GoveeLanController {
+    sku: String,
+    device_id: String,
+    addr: SocketAddr,
+    socket: UdpSocket,
+    sent: Option<LightState>,
+    /// Brightness set through `set_brightness`, folded into the API brightness.
+    brightness: u8,
+}
+
+impl GoveeLanController {
+    fn new(device: protocol::ScanResponse) -> Result<Self, String> {
+        let ip: Ipv4Addr = device
+            .ip
+            .parse()
+            .map_err(|e| format!("Invalid address '{}': {}", device.ip, e))?;
+        let socket =
+            bind_udp_for(IpAddr::V4(ip)).map_err(|e| format!("Failed to bind socket: {}", e))?;
+
+        Ok(Self {
+            sku: device.sku,
+            device_id: device.device,
+            addr: SocketAddr::V4(SocketAddrV4::new(ip, CONTROL_PORT)),
+            socket,
+            sent: None,
+            brightness: 100,
+        })
+    }
+
+    fn send(&self, data: &[u8]) -> Result<(), String> {
+        self.socket
+            .send_to(data, self.addr)
+            .map_err(|e| format!("Failed to send Govee command: {}", e))?;
+        Ok(())
+    }
+
+    /// Send the commands needed to get from the last sent state to `target`.
+    fn apply(&mut self, target: LightState) -> Result<(), String> {
+        let previous = self.sent;
+        if previous == Some(target) {
+            return Ok(());
+        }
+
+        if previous.map(|p| p.on) != Some(target.on) {
+            self.send(&GoveeLanProtocol::encode_turn(target.on))?;
+        }
+        if target.on {
+            if previous.map(|p| p.brightness) != Some(target.brightness) {
+                self.send(&GoveeLanProtocol::encode_brightness(target.brightness))?;
+            }
+            if previous.map(|p| p.color) != Some(target.color) {
+                let (r, g, b) = target.color;
+                self.send(&GoveeLanProtocol::encode_color(Color { r, g, b }))?;
+            }
+        }
+
+        // While off, brightness/color weren't sent; keep the old ones so turning back on
+        // re-sends them if they changed.
+        self.sent = Some(if target.on {
+            target
+        } else {
+            LightState {
+                on: false,
+                ..previous.unwrap_or(target)
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Average the frame and split it into a full-scale color plus an API brightness, which
+/// keeps more color resolution at low levels than dimming the RGB value itself. `brightness`
+/// (0..=100) scales the API brightness.
+fn light_state(colors: &[Color], brightness: u8) -> LightState {
+    let count = colors.len().max(1) as u32;
+    let (r, g, b) = colors.iter().fold((0u32, 0u32, 0u32), |(r, g, b), c| {
+        (r + c.r as u32, g + c.g as u32, b + c.b as u32)
+    });
+    let (r, g, b) = (r / count, g / count, b / count);
+
+    let peak = r.max(g).max(b);
+    let level = (peak * brightness.min(100) as u32 + 127) / 255;
+    if peak == 0 || brightness == 0 {
+        return LightState {
+            on: false,
+            brightness: 0,
+            color: (0, 0, 0),
+        };
+    }
+
+    let scale = |v: u32| ((v * 255 + peak / 2) / peak) as u8;
+    LightState {
+        on: true,
+        brightness: level.max(1) as u8,
+        color: (scale(r), scale(g), scale(b)),
+    }
+}
+
+impl Controller for GoveeLanController {
+    fn port_name(&self) -> String {
+        self.addr.ip().to_string()
+    }
+
+    fn model(&self) -> String {
+        format!("Govee {}", self.sku)
+    }
+
+    fn description(&self) -> String {
+        "Govee light (LAN API)".to_string()
+    }
+
+    fn serial_id(&self) -> String {
+        self.device_id.clone()
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Light
+    }
+
+    fn outputs(&self) -> Vec<OutputPortDefinition> {
+        vec![OutputPortDefinition {
+            id: OUTPUT_ID.to_string(),
+            name: "Light".to_string(),
+            output_type: SegmentType::Single,
+            leds_count: 1,
+            matrix: None,
+            capabilities: OutputCapabilities {
+                editable: false,
+                min_total_leds: 1,
+                max_total_leds: 1,
+                allowed_total_leds: Some(vec![1]),
+                allowed_segment_types: vec![SegmentType::Single],
+            },
+        }]
+    }
+
+    fn update(&mut self, colors: &[Color]) -> Result<(), String> {
+        self.apply(light_state(colors, self.brightness))
+    }
+
+    fn clear(&mut self) -> Result<(), String> {
+        self.apply(light_state(&[], self.brightness))
+    }
+
+    fn has_hardware_brightness(&self) -> bool {
+        true
+    }
+
+    fn max_update_hz(&self) -> Option<f32> {
+        Some(MAX_UPDATE_HZ)
+    }
+
+    /// Takes effect with the next frame, which the runner sends every tick.
+    fn set_brightness(&mut self, brightness: u8) -> Result<(), String> {
+        self.brightness = brightness.min(100);
+        Ok(())
+    }
+}
+
+/// Multicast a scan and collect replies until `timeout`, keyed by device id.
+fn discover_devices(timeout: Duration) -> Vec<protocol::ScanResponse> {
+    // Replies always go to the fixed listen port, not back to the sender's port.
+    let socket = match UdpSocket::bind(local_addr_for(IpAddr::V4(MULTICAST_ADDR), LISTEN_PORT)) {
+        Ok(socket) => socket,
+        Err(e) => {
+            log::warn!(port = LISTEN_PORT, err:display = e; "Failed to bind Govee scan port");
+            return Vec::new();
+        }
+    };
+    if let Err(e) = socket
+        .set_read_timeout(Some(Duration::from_millis(100)))
+        .and_then(|_| set_multicast_if_v4(&socket))
+    {
+        log::warn!(err:display = e; "Failed to configure Govee scan socket");
+        return Vec::new();
+    }
+
+    let scan = GoveeLanProtocol::encode_scan();
+    if let Err(e) = socket.send_to(&scan, SocketAddrV4::new(MULTICAST_ADDR, SCAN_PORT)) {
+        log::warn!(err:display = e; "Failed to send Govee scan request");
+        return Vec::new();
+    }
+
+    let mut devices: HashMap<String, protocol::ScanResponse> = HashMap::new();
+    let mut buf = [0u8; 2048];
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        match socket.recv_from(&mut buf) {
+            Ok((len, _)) => {
+                if let Some(device) = GoveeLanProtocol::decode_scan_response(&buf[..len]) {
+                    log::info!(
+                        sku = device.sku.as_str(),
+                        ip = device.ip.as_str();
+                        "Discovered Govee device"
+                    );
+                    devices.insert(device.device.clone(), device);
+                }
+            }
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+                continue;
+            }
+            Err(e) => {
+                log::warn!(err:display = e; "Govee scan receive failed");
+                break;
+            }
+        }
+    }
+
+    devices.into_values().collect()
+}
+
+fn probe() -> Vec<Box<dyn Controller>> {
+    let mut controllers: Vec<Box<dyn Controller>> = Vec::new();
+
+    log::info!("Scanning for Govee LAN devices...");
+    for device in discover_devices(discovery_timeout()) {
+        let ip = device.ip.clone();
+        match GoveeLanController::new(device) {
+            Ok(controller) => controllers.push(Box::new(controller)),
+            Err(e) => {
+                log::warn!(ip = ip.as_str(), err:display = e; "Failed to create Govee controller");
+            }
+        }
+    }
+
+    controllers
+}
+
+inventory::submit!(ControllerMetadata {
+    name: "Govee LAN Controller",
+    description: "Govee lights via the local LAN API (multicast discovery)",
+    priority: 0,
+    specificity: ProbeSpecificity::Verified,
+    probe,
+});
@@ -0,0 +1,130 @@
+//! Govee LAN API messages.
+//!
+//! Every message is a JSON object `{"msg": {"cmd": ..., "data": {...}}}`. Scans go to the
+//! multicast group on `SCAN_PORT`, devices answer on `LISTEN_PORT`, and control commands
+//! are sent to the device's own address on `CONTROL_PORT`.
+
+use crate::interface::controller::Color;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::net::Ipv4Addr;
+
+pub const MULTICAST_ADDR: Ipv4Addr = Ipv4Addr::new(239, 255, 255, 250);
+pub const SCAN_PORT: u16 = 4001;
+pub const LISTEN_PORT: u16 = 4002;
+pub const CONTROL_PORT: u16 = 4003;
+
+/// Device entry from a scan reply.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct ScanResponse {
+    pub ip: String,
+    /// MAC-like identifier, e.g. `1F:80:C5:32:32:36:72:4E`.
+    pub device: String,
+    /// Model number, e.g. `H6199`.
+    pub sku: String,
+}
+
+#[derive(Deserialize)]
+struct Envelope<T> {
+    msg: Message<T>,
+}
+
+#[derive(Deserialize)]
+struct Message<T> {
+    cmd: String,
+    data: T,
+}
+
+pub struct GoveeLanProtocol;
+
+impl GoveeLanProtocol {
+    pub fn encode_scan() -> Vec<u8> {
+        Self::encode("scan", json!({ "account_topic": "reserve" }))
+    }
+
+    pub fn decode_scan_response(bytes: &[u8]) -> Option<ScanResponse> {
+        let envelope: Envelope<ScanResponse> = serde_json::from_slice(bytes).ok()?;
+        (envelope.msg.cmd == "scan").then_some(envelope.msg.data)
+    }
+
+    pub fn encode_turn(on: bool) -> Vec<u8> {
+        Self::encode("turn", json!({ "value": u8::from(on) }))
+    }
+
+    /// `percent` is clamped to the API's 1..=100 range; use `encode_turn` for off.
+    pub fn encode_brightness(percent: u8) -> Vec<u8> {
+        Self::encode("brightness", json!({ "value": percent.clamp(1, 100) }))
+    }
+
+    /// RGB color; a color temperature of 0 tells the device to use the RGB value.
+    pub fn encode_color(color: Color) -> Vec<u8> {
+        Self::encode(
+            "colorwc",
+            json!({
+                "color": { "r": color.r, "g": color.g, "b": color.b },
+                "colorTemInKelvin": 0,
+            }),
+        )
+    }
+
+    fn encode(cmd: &str, data: Value) -> Vec<u8> {
+        json!({ "msg": { "cmd": cmd, "data": data } })
+            .to_string()
+            .into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(bytes: &[u8]) -> Value {
+        serde_json::from_slice(bytes).unwrap()
+    }
+
+    #[test]
+    fn encodes_scan_request() {
+        assert_eq!(
+            parse(&GoveeLanProtocol::encode_scan()),
+            json!({ "msg": { "cmd": "scan", "data": { "account_topic": "reserve" } } })
+        );
+    }
+
+    #[test]
+    fn decodes_scan_response() {
+        let reply = br#"{"msg":{"cmd":"scan","data":{"ip":"192.168.1.23","device":"1F:80:C5:32:32:36:72:4E","sku":"H618E","bleVersionHard":"3.01.01","wifiVersionSoft":"1.02.03"}}}"#;
+        assert_eq!(
+            GoveeLanProtocol::decode_scan_response(reply),
+            Some(ScanResponse {
+                ip: "192.168.1.23".to_string(),
+                device: "1F:80:C5:32:32:36:72:4E".to_string(),
+                sku: "H618E".to_string(),
+            })
+        );
+
+        let status = br#"{"msg":{"cmd":"devStatus","data":{"onOff":1}}}"#;
+        assert_eq!(GoveeLanProtocol::decode_scan_response(status), None);
+    }
+
+    #[test]
+    fn encodes_color_and_brightness_commands() {
+        let color = Color { r: 255, g: 12, b: 7 };
+        assert_eq!(
+            parse(&GoveeLanProtocol::encode_color(color)),
+            json!({
+                "msg": {
+                    "cmd": "colorwc",
+                    "data": { "color": { "r": 255, "g": 12, "b": 7 }, "colorTemInKelvin": 0 }
+                }
+            })
+        );
+        assert_eq!(
+            parse(&GoveeLanProtocol::encode_brightness(0)),
+            json!({ "msg": { "cmd": "brightness", "data": { "value": 1 } } })
+        );
+        assert_eq!(
+            parse(&GoveeLanProtocol::encode_turn(false)),
+            json!({ "msg": { "cmd": "turn", "data": { "value": 0 } } })
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! ACK模式下的丢帧统计
+//!
+//! 设备只确认完整收到的帧，因此确认到某帧时，比它更早发送、仍未确认的帧都按丢失计；
+//! 超过 [`ACK_TIMEOUT`] 仍未确认的帧同样按丢失计。确认包本身丢失与帧丢失无法区分，
+//! 统计结果是链路的整体丢包情况。
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// 统计丢帧率的窗口（最近有结果的帧数）
+const LOSS_WINDOW: usize = 120;
+/// 超过该时间仍未确认的帧视为丢失
+const ACK_TIMEOUT: Duration = Duration::from_millis(500);
+/// 最多同时等待确认的帧数：frame_id 只有8位，再多会与回绕后的新帧混淆
+const MAX_PENDING: usize = 128;
+
+pub struct FrameAcks {
+    /// 已发送、等待确认的帧（按发送顺序）
+    pending: VecDeque<(u8, Instant)>,
+    /// 最近各帧的结果，true 表示丢失
+    outcomes: VecDeque<bool>,
+    lost_in_window: usize,
+}
+
+impl FrameAcks {
+    pub fn new() -> Self {
+        Self {
+            pending: VecDeque::with_capacity(MAX_PENDING),
+            outcomes: VecDeque::with_capacity(LOSS_WINDOW),
+            lost_in_window: 0,
+        }
+    }
+
+    /// 记录一个已发送的帧；返回因此判定丢失的帧数
+    pub fn sent(&mut self, frame_id: u8, now: Instant) -> usize {
+        let mut lost = self.expire(now);
+        if self.pending.len() >= MAX_PENDING {
+            self.pending.pop_front();
+            self.record(true);
+            lost += 1;
+        }
+        self.pending.push_back((frame_id, now));
+        lost
+    }
+
+    /// 处理设备的确认；返回因此判定丢失的更早帧数。未在等待的帧（重复或迟到的确认）被忽略
+    pub fn acked(&mut self, frame_id: u8) -> usize {
+        let Some(pos) = self.pending.iter().position(|(id, _)| *id == frame_id) else {
+            return 0;
+        };
+        self.pending.drain(..=pos);
+        for _ in 0..pos {
+            self.record(true);
+        }
+        self.record(false);
+        pos
+    }
+
+    /// 将超时未确认的帧记为丢失；返回丢失的帧数
+    pub fn expire(&mut self, now: Instant) -> usize {
+        let mut lost = 0;
+        while let Some(&(_, sent_at)) = self.pending.front() {
+            if now.duration_since(sent_at) < ACK_TIMEOUT {
+                break;
+            }
+            self.pending.pop_front();
+            self.record(true);
+            lost += 1;
+        }
+        lost
+    }
+
+    /// 该帧是否仍在等待确认
+    pub fn is_pending(&self, frame_id: u8) -> bool {
+        self.pending.iter().any(|(id, _)| *id == frame_id)
+    }
+
+    /// 最近窗口内的丢帧率（0.0–1.0）；还没有任何帧有结果时为 None
+    pub fn loss_rate(&self) -> Option<f32> {
+        if self.outcomes.is_empty() {
+            return None;
+        }
+        Some(self.lost_in_window as f32 / self.outcomes.len() as f32)
+    }
+
+    fn record(&mut self, lost: bool) {
+        if self.outcomes.len() == LOSS_WINDOW && self.outcomes.pop_front() == Some(true) {
+            self.lost_in_window -= 1;
+        }
+        self.outcomes.push_back(lost);
+        if lost {
+            self.lost_in_window += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skipped_and_timed_out_frames_count_as_lost() {
+        let start = Instant::now();
+        let mut acks = FrameAcks::new();
+        assert_eq!(acks.loss_rate(), None);
+
+        for id in 0..4 {
+            acks.sent(id, start);
+        }
+        // 帧1、2未确认就确认了帧3
+        assert_eq!(acks.acked(0), 0);
+        assert_eq!(acks.acked(3), 2);
+        assert!(!acks.is_pending(3));
+        assert_eq!(acks.loss_rate(), Some(0.5));
+
+        // 迟到的确认不再计入
+        assert_eq!(acks.acked(1), 0);
+
+        acks.sent(4, start);
+        assert_eq!(acks.expire(start + ACK_TIMEOUT / 2), 0);
+        assert_eq!(acks.expire(start + ACK_TIMEOUT), 1);
+        assert_eq!(acks.loss_rate(), Some(3.0 / 5.0));
+    }
+
+    #[test]
+    fn loss_rate_only_reflects_the_recent_window() {
+        let now = Instant::now();
+        let mut acks = FrameAcks::new();
+        for id in 0..10u8 {
+            acks.sent(id, now);
+        }
+        acks.acked(9);
+        assert_eq!(acks.loss_rate(), Some(0.9));
+
+        for i in 0..LOSS_WINDOW {
+            let id = (10 + i) as u8;
+            acks.sent(id, now);
+            acks.acked(id);
+        }
+        assert_eq!(acks.loss_rate(), Some(0.0));
+    }
+}
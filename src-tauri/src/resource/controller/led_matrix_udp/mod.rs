@@ -1,27 +1,83 @@
 use crate::interface::controller::{
     Color, Controller, ControllerMetadata, DeviceType, MatrixMap, OutputCapabilities,
-    OutputPortDefinition, SegmentType,
+    OutputPortDefinition, ProbeSpecificity, SegmentType,
 };
-use mdns_sd::{ServiceDaemon, ServiceEvent};
+use crate::resource::driver::frame_diff::{FrameDiff, FrameDiffConfig, FramePlan};
+use crate::resource::driver::net_bind::{bind_udp_for, discovery_timeout, source_address};
+use mdns_sd::{IfKind, ScopedIp, ServiceDaemon, ServiceEvent};
 use serde::Deserialize;
 use std::collections::{HashMap, HashSet};
 use std::io::ErrorKind;
-use std::net::{SocketAddr, UdpSocket};
+use std::net::{IpAddr, SocketAddr, SocketAddrV6, UdpSocket};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+mod ack;
 mod protocol;
-use protocol::{LedMatrixProtocol, PROTOCOL_VERSION, MAX_UDP_PAYLOAD};
+use ack::FrameAcks;
+use protocol::{
+    LedMatrixProtocol, ProtocolVersion, MAX_UDP_PAYLOAD, MIN_PROTOCOL_VERSION, MIN_UDP_PAYLOAD,
+    PROTOCOL_VERSION,
+};
+pub use protocol::DEFAULT_UDP_PAYLOAD;
 
 /// mDNS服务类型（与虚拟LED矩阵保持一致）
 const SERVICE_TYPE: &str = "_testdevice._udp.local.";
 
+/// 关键帧间隔（帧数）：约每秒发送一次全量帧，防止丢包导致像素长期不同步
+const KEYFRAME_INTERVAL: u32 = 60;
+
+/// 距上一帧超过该间隔后的第一帧视为关键帧，在ACK模式下确认送达
+const FRAME_GAP: Duration = Duration::from_secs(1);
+/// 关键帧首次等待确认的时间，每次重发后翻倍
+const CRITICAL_ACK_TIMEOUT: Duration = Duration::from_millis(15);
+/// 关键帧最多重发次数
+const CRITICAL_RETRIES: u32 = 3;
+
+/// 连续发送失败多少帧后重新通过mDNS解析设备地址（设备可能换了DHCP地址）
+const SEND_FAILURES_BEFORE_RESOLVE: u32 = 3;
+/// 连续多少次心跳未应答后重新解析；发往失效地址的UDP包通常不会报错，只能靠心跳发现
+const MISSED_PINGS_BEFORE_RESOLVE: u32 = 2;
+/// 后台重新解析时mDNS浏览的时长
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(2);
+/// 等待确认时轮询socket的间隔
+const ACK_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+static FRAME_ACKS: AtomicBool = AtomicBool::new(false);
+static UDP_PAYLOAD: AtomicU32 = AtomicU32::new(DEFAULT_UDP_PAYLOAD as u32);
+
+/// 设置是否向设备请求帧确认（ACK模式），对之后连接的设备生效。
+/// 默认关闭：发后即忘延迟最低，ACK模式用于统计丢包并确保关键帧送达
+pub fn set_frame_acks_enabled(enabled: bool) {
+    FRAME_ACKS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn frame_acks_enabled() -> bool {
+    FRAME_ACKS.load(Ordering::Relaxed)
+}
+
+/// 设置单个UDP包的最大负载（字节），对之后连接的设备生效，超出范围的值会被钳制。
+/// 更大的包减少分片数（需网络支持巨帧、设备固件能接收），更小的包适合MTU受限的链路
+pub fn set_udp_payload(bytes: u32) {
+    UDP_PAYLOAD.store(normalize_udp_payload(bytes), Ordering::Relaxed);
+}
+
+pub fn get_udp_payload() -> u32 {
+    UDP_PAYLOAD.load(Ordering::Relaxed)
+}
+
+pub fn normalize_udp_payload(bytes: u32) -> u32 {
+    bytes.clamp(MIN_UDP_PAYLOAD as u32, MAX_UDP_PAYLOAD as u32)
+}
+
 /// 发现的LED矩阵设备信息（仅基于mDNS）
 #[derive(Clone, Debug)]
 pub struct DiscoveredDevice {
     pub name: String,
-    pub ip: String,
-    pub port: u16,
+    pub addr: SocketAddr,
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,8 +97,13 @@ struct OutputPortConfigDto {
     matrix: Option<MatrixMap>,
 }
 
+/// 重新解析设备地址：参数为mDNS名称、序列号和当前地址，返回确认在线的地址
+type Resolver = fn(&str, &str, SocketAddr) -> Result<SocketAddr, String>;
+
 /// LED矩阵UDP控制器
 pub struct LedMatrixUdpController {
+    /// mDNS中公布的名称，用于重新解析地址
+    discovered_name: String,
     device_name: String,
     device_description: String,
     serial: String,
@@ -56,15 +117,31 @@ pub struct LedMatrixUdpController {
     max_pixels_per_fragment: usize,
     /// 当前帧ID（0-255循环）
     frame_id: u8,
+    /// 与设备协商出的协议版本
+    protocol: ProtocolVersion,
+    /// 脏区跟踪，仅发送变化的像素段
+    diff: FrameDiff,
+    /// ACK模式下的丢帧统计；设备未开启ACK模式时为None
+    acks: Option<FrameAcks>,
+    /// 上一帧的发送时间
+    last_sent: Option<Instant>,
+    /// 已发出心跳（查询信息请求）但尚未收到回复
+    ping_pending: bool,
+    /// 连续发送失败的帧数
+    send_failures: u32,
+    /// 连续未应答的心跳数
+    missed_pings: u32,
+    /// 后台进行中的地址解析，完成后给出确认在线的地址
+    resolving: Option<Receiver<Result<SocketAddr, String>>>,
+    /// 后台解析使用的函数，测试中替换为不访问网络的实现
+    resolver: Resolver,
 }
 
 impl LedMatrixUdpController {
     pub fn new(device: DiscoveredDevice) -> Result<Self, String> {
-        let addr: SocketAddr = format!("{}:{}", device.ip, device.port)
-            .parse()
-            .map_err(|e| format!("Invalid address: {}", e))?;
+        let addr = device.addr;
 
-        let socket = UdpSocket::bind("0.0.0.0:0").map_err(|e| format!("Failed to bind socket: {}", e))?;
+        let socket = bind_udp_for(addr.ip()).map_err(|e| format!("Failed to bind socket: {}", e))?;
         socket
             .set_nonblocking(false)
             .map_err(|e| format!("Failed to set socket mode: {}", e))?;
@@ -73,15 +150,14 @@ impl LedMatrixUdpController {
             .map_err(|e| format!("Failed to set socket timeout: {}", e))?;
 
         // 查询设备详细信息（必须成功，否则报错）
-        // TestDevice is intentionally not backward-compatible.
         let info = Self::fetch_device_info(&socket, addr)?;
 
-        if info.version != PROTOCOL_VERSION {
-            return Err(format!(
-                "Unsupported protocol version: device={}, supported={}",
-                info.version, PROTOCOL_VERSION
-            ));
-        }
+        let protocol = ProtocolVersion::negotiate(info.version).ok_or_else(|| {
+            format!(
+                "Unsupported protocol version: device={}, supported={}..={}",
+                info.version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+            )
+        })?;
 
         let device_name = info.name.clone();
         let device_description = info.description.clone();
@@ -107,12 +183,27 @@ impl LedMatrixUdpController {
 
         // 分片参数与缓冲区预分配
         let max_pixels_per_fragment =
-            LedMatrixProtocol::max_pixels_per_fragment(MAX_UDP_PAYLOAD)
+            LedMatrixProtocol::max_pixels_per_fragment(protocol, get_udp_payload() as usize)
                 .map_err(|e| format!("Invalid UDP payload setting: {}", e))?;
-        // 预分配单个分片的最大空间: cmd(1) + header(5) + pixels * 5
-        let frame_buffer = Vec::with_capacity(1 + 5 + max_pixels_per_fragment * 5);
+        // 全量帧必须能放进协议允许的分片数
+        LedMatrixProtocol::calc_total_fragments(led_count, max_pixels_per_fragment)?;
+        // 预分配单个分片的最大空间
+        let frame_buffer = Vec::with_capacity(protocol.fragment_capacity(max_pixels_per_fragment));
+
+        let acks = (frame_acks_enabled() && Self::enable_acks(&socket, addr)).then(FrameAcks::new);
+        if acks.is_none() && frame_acks_enabled() {
+            log::info!(
+                name = device_name.as_str();
+                "LED Matrix does not support frame acknowledgements, sending fire-and-forget"
+            );
+        }
+        // 之后的回包（帧确认、心跳回复）都在发送后非阻塞读取
+        socket
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to set socket mode: {}", e))?;
 
         Ok(Self {
+            discovered_name: device.name,
             device_name,
             device_description,
             serial,
@@ -123,9 +214,40 @@ impl LedMatrixUdpController {
             frame_buffer,
             max_pixels_per_fragment,
             frame_id: 0,
+            protocol,
+            diff: FrameDiff::new(FrameDiffConfig::for_protocol(
+                protocol.fragment_header_len(),
+                protocol.bytes_per_pixel(),
+                KEYFRAME_INTERVAL,
+            )),
+            acks,
+            last_sent: None,
+            ping_pending: false,
+            send_failures: 0,
+            missed_pings: 0,
+            resolving: None,
+            resolver: resolve_device,
         })
     }
 
+    /// 请求设备开启ACK模式；设备无回复（不支持）时返回false
+    fn enable_acks(socket: &UdpSocket, addr: SocketAddr) -> bool {
+        let payload = LedMatrixProtocol::encode_set_ack_mode(true);
+        let mut buf = [0u8; 64];
+
+        for _ in 0..2 {
+            if socket.send_to(&payload, addr).is_err() {
+                return false;
+            }
+            if let Ok((len, _)) = socket.recv_from(&mut buf) {
+                if let Some(enabled) = LedMatrixProtocol::decode_ack_mode_reply(&buf[..len]) {
+                    return enabled;
+                }
+            }
+        }
+        false
+    }
+
     /// 查询设备信息（必须成功）
     fn fetch_device_info(socket: &UdpSocket, addr: SocketAddr) -> Result<protocol::QueryInfo, String> {
         let payload = LedMatrixProtocol::encode_query_info();
@@ -363,16 +485,234 @@ impl LedMatrixUdpController {
         }
     }
 
-    /// 发送UDP数据包
-    fn send(&self, data: &[u8]) -> Result<(), String> {
-        self.socket
-            .send_to(data, self.addr)
-            .map_err(|e| format!("Failed to send UDP packet: {}", e))?;
+    /// 发送UDP数据包；socket为非阻塞，发送缓冲区已满（WouldBlock）时丢弃该包并返回`false`
+    fn send(&self, data: &[u8]) -> Result<bool, String> {
+        match self.socket.send_to(data, self.addr) {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(false),
+            Err(e) => Err(format!("Failed to send UDP packet: {}", e)),
+        }
+    }
+
+    /// 编码并发送一帧的全部分片；有分片因发送缓冲区已满被丢弃时停止并返回`false`
+    fn send_fragments(
+        &mut self,
+        frame_id: u8,
+        total_fragments: u8,
+        fragments: &[Range<usize>],
+        colors: &[Color],
+    ) -> Result<bool, String> {
+        for (fragment_index, fragment) in fragments.iter().enumerate() {
+            LedMatrixProtocol::encode_fragment_into(
+                self.protocol,
+                frame_id,
+                total_fragments,
+                fragment_index as u8,
+                fragment.start,
+                &colors[fragment.clone()],
+                &mut self.frame_buffer,
+            )?;
+
+            if !self.send(&self.frame_buffer)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// 读取已到达的回包（不阻塞）：帧确认计入丢帧统计，查询信息回复视为心跳应答；
+    /// 返回因确认判定丢失的帧数
+    fn read_replies(&mut self) -> usize {
+        let mut buf = [0u8; 512];
+        let mut lost = 0;
+        // WouldBlock 表示已读完；其他错误留给下一次再读
+        while let Ok((len, from)) = self.socket.recv_from(&mut buf) {
+            if from.ip() != self.addr.ip() {
+                continue;
+            }
+            let packet = &buf[..len];
+            if let Some(frame_id) = LedMatrixProtocol::decode_frame_ack(packet) {
+                if let Some(acks) = self.acks.as_mut() {
+                    lost += acks.acked(frame_id);
+                }
+            } else if LedMatrixProtocol::decode_query_response(packet).is_some() {
+                self.ping_pending = false;
+            }
+        }
+        lost
+    }
+
+    /// 读取已到达的帧确认（不阻塞）；返回判定丢失的帧数
+    fn poll_acks(&mut self) -> usize {
+        if self.acks.is_none() {
+            return 0;
+        }
+        let lost = self.read_replies();
+        lost + self.acks.as_mut().map_or(0, |acks| acks.expire(Instant::now()))
+    }
+
+    /// 等待设备确认关键帧，未确认时按指数退避重发；返回期间判定丢失的帧数
+    fn confirm_critical_frame(
+        &mut self,
+        frame_id: u8,
+        total_fragments: u8,
+        fragments: &[Range<usize>],
+        colors: &[Color],
+    ) -> Result<usize, String> {
+        let mut lost = 0;
+        let mut timeout = CRITICAL_ACK_TIMEOUT;
+        for attempt in 0..=CRITICAL_RETRIES {
+            let deadline = Instant::now() + timeout;
+            loop {
+                lost += self.poll_acks();
+                if !self.acks.as_ref().is_some_and(|acks| acks.is_pending(frame_id)) {
+                    return Ok(lost);
+                }
+                if Instant::now() >= deadline {
+                    break;
+                }
+                std::thread::sleep(ACK_POLL_INTERVAL);
+            }
+            if attempt == CRITICAL_RETRIES {
+                break;
+            }
+            log::debug!(
+                addr = self.addr.to_string().as_str(),
+                frame_id,
+                attempt;
+                "LED Matrix did not confirm frame, retransmitting"
+            );
+            // 重发被丢弃时帧仍未确认，由下一轮再重发
+            self.send_fragments(frame_id, total_fragments, fragments, colors)?;
+            timeout *= 2;
+        }
+        Ok(lost)
+    }
+
+    /// 在后台重新解析设备地址；已有解析在进行时不重复发起
+    fn start_resolve(&mut self) {
+        if self.resolving.is_some() {
+            return;
+        }
+        log::info!(
+            name = self.discovered_name.as_str(),
+            addr = self.addr.to_string().as_str();
+            "LED Matrix stopped responding, re-resolving it via mDNS"
+        );
+        let (tx, rx) = mpsc::channel();
+        let name = self.discovered_name.clone();
+        let serial = self.serial.clone();
+        let current = self.addr;
+        let resolver = self.resolver;
+        // 解析和握手都有超时，控制器被丢弃时发送结果会失败，线程随之结束
+        match std::thread::Builder::new()
+            .name("led-matrix-resolve".to_string())
+            .spawn(move || {
+                let _ = tx.send(resolver(&name, &serial, current));
+            }) {
+            Ok(_) => self.resolving = Some(rx),
+            Err(e) => {
+                log::error!(err:display = e; "Failed to spawn LED Matrix resolve thread");
+            }
+        }
+    }
+
+    /// 取回已完成的后台解析结果（不阻塞）：地址变化时切换过去；设备无应答时返回错误
+    fn poll_resolve(&mut self) -> Result<(), String> {
+        let Some(rx) = &self.resolving else {
+            return Ok(());
+        };
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return Ok(()),
+            Err(TryRecvError::Disconnected) => Err("Resolve thread exited".to_string()),
+        };
+        self.resolving = None;
+        self.send_failures = 0;
+        self.missed_pings = 0;
+
+        let addr = result.map_err(|e| format!("LED Matrix is gone: {}", e))?;
+        if addr != self.addr {
+            log::info!(
+                name = self.discovered_name.as_str(),
+                from = self.addr.to_string().as_str(),
+                to = addr.to_string().as_str();
+                "LED Matrix moved to a new address"
+            );
+            if addr.is_ipv6() != self.addr.is_ipv6() {
+                let socket = bind_udp_for(addr.ip())
+                    .and_then(|socket| socket.set_nonblocking(true).map(|_| socket))
+                    .map_err(|e| format!("Failed to bind socket: {}", e))?;
+                self.socket = socket;
+            }
+            self.addr = addr;
+            // 设备多半重启过，ACK模式需要重新开启；回复在read_replies中被忽略
+            if self.acks.is_some() {
+                let _ = self.send(&LedMatrixProtocol::encode_set_ack_mode(true));
+            }
+        }
+        // 期间的帧都已丢失，设备画面与脏区跟踪不再一致
+        self.diff.reset();
         Ok(())
     }
+
+    /// 记录一次发送失败：阈值以内的失败只丢弃该帧；达到阈值后发起重新解析并报错，
+    /// 让运行器知道设备可能已不可达
+    fn send_failed(&mut self, err: String) -> Result<(), String> {
+        self.send_failures += 1;
+        self.diff.reset();
+        log::debug!(
+            addr = self.addr.to_string().as_str(),
+            failures = self.send_failures,
+            err:display = err;
+            "Failed to send LED Matrix frame"
+        );
+        if self.send_failures < SEND_FAILURES_BEFORE_RESOLVE {
+            return Ok(());
+        }
+        self.start_resolve();
+        Err(format!(
+            "Failed to send LED Matrix frame {} times in a row: {}",
+            self.send_failures, err
+        ))
+    }
+}
+
+/// 通过mDNS重新查找名为`name`的设备（找不到时沿用`current`），并用一次查询信息握手确认
+/// 应答的是同一台设备。在后台线程中运行
+fn resolve_device(name: &str, serial: &str, current: SocketAddr) -> Result<SocketAddr, String> {
+    let addr = discover_devices(RESOLVE_TIMEOUT)
+        .into_iter()
+        .find(|device| device.name == name)
+        .map_or(current, |device| device.addr);
+
+    // 独立的socket，避免与渲染线程争抢回包
+    let socket = bind_udp_for(addr.ip()).map_err(|e| format!("Failed to bind socket: {}", e))?;
+    socket
+        .set_read_timeout(Some(Duration::from_millis(500)))
+        .map_err(|e| format!("Failed to set socket timeout: {}", e))?;
+    let info = LedMatrixUdpController::fetch_device_info(&socket, addr)?;
+    if info.serial != serial {
+        return Err(format!("A different device answers at {}", addr));
+    }
+    Ok(addr)
+}
+
+/// 将像素段按单个分片的像素上限切分
+fn split_fragments(ranges: &[Range<usize>], max_pixels: usize) -> Vec<Range<usize>> {
+    ranges
+        .iter()
+        .flat_map(|range| {
+            range
+                .clone()
+                .step_by(max_pixels)
+                .map(move |start| start..(start + max_pixels).min(range.end))
+        })
+        .collect()
 }
 
 impl Controller for LedMatrixUdpController {
+    /// 当前地址；重新解析到新地址后随之变化，管理器仍沿用注册时的键
     fn port_name(&self) -> String {
         self.addr.to_string()
     }
@@ -407,47 +747,139 @@ impl Controller for LedMatrixUdpController {
                 colors.len()
             ));
         }
+        // 解析期间照常发往当前地址
+        self.poll_resolve()?;
+
+        let mut plan = self.diff.plan(colors);
+        let whole = 0..self.led_count;
+        let full_frame = std::slice::from_ref(&whole);
+        let ranges: &[Range<usize>] = match &plan {
+            FramePlan::Unchanged => {
+                self.diff.commit(colors, &plan);
+                return Ok(());
+            }
+            FramePlan::Full => full_frame,
+            FramePlan::Partial(ranges) => ranges,
+        };
 
-        // 使用分片协议，保证UDP包不会超出安全负载
+        // 使用分片协议，保证UDP包不会超出安全负载；每个脏区按分片上限切分
         let max_pixels = self.max_pixels_per_fragment;
-        let total_fragments =
-            LedMatrixProtocol::calc_total_fragments(self.led_count, max_pixels)?;
+        let mut fragments = split_fragments(ranges, max_pixels);
+        if fragments.len() > u8::MAX as usize && plan != FramePlan::Full {
+            // 脏区过于零散，退回全量帧
+            plan = FramePlan::Full;
+            fragments = split_fragments(full_frame, max_pixels);
+        }
+        let total_fragments = u8::try_from(fragments.len())
+            .map_err(|_| "Fragment count exceeds protocol limit (<=255)".to_string())?;
+
         let frame_id = self.frame_id;
         self.frame_id = self.frame_id.wrapping_add(1);
 
-        for fragment_index in 0..total_fragments {
-            let start = fragment_index as usize * max_pixels;
-            let end = (start + max_pixels).min(self.led_count);
-
-            LedMatrixProtocol::encode_fragment_into(
-                frame_id,
-                total_fragments,
-                fragment_index,
-                start,
-                &colors[start..end],
-                &mut self.frame_buffer,
-            )?;
-
-            self.send(&self.frame_buffer)?;
+        let now = Instant::now();
+        let after_gap = self
+            .last_sent
+            .is_none_or(|at| now.duration_since(at) >= FRAME_GAP);
+        self.last_sent = Some(now);
+
+        match self.send_fragments(frame_id, total_fragments, &fragments, colors) {
+            Ok(true) => {}
+            // 负载过高时发送缓冲区满：只丢弃这一帧，不算发送失败；设备可能只收到部分分片，
+            // 下一帧全量发送
+            Ok(false) => {
+                self.diff.reset();
+                return Ok(());
+            }
+            Err(e) => return self.send_failed(e),
         }
+        self.send_failures = 0;
+        self.diff.commit(colors, &plan);
 
+        let Some(acks) = self.acks.as_mut() else {
+            return Ok(());
+        };
+        let mut lost = acks.sent(frame_id, now);
+        if after_gap {
+            lost += self.confirm_critical_frame(frame_id, total_fragments, &fragments, colors)?;
+        }
+        lost += self.poll_acks();
+        if lost > 0 {
+            // 丢失的增量帧会让设备画面与脏区跟踪不一致，下一帧改发全量帧
+            self.diff.reset();
+        }
         Ok(())
     }
 
     fn clear(&mut self) -> Result<(), String> {
-        // 发送全黑帧
+        // 发送全黑帧（强制全量，避免脏区跳过）
+        self.diff.reset();
         let black = vec![Color { r: 0, g: 0, b: 0 }; self.led_count];
         self.update(&black)
     }
 
     fn disconnect(&mut self) -> Result<(), String> {
-        // 断开前清屏
-        self.clear()
+        // 只释放连接；是否清屏由调用方决定
+        if self.acks.take().is_some() {
+            let _ = self.send(&LedMatrixProtocol::encode_set_ack_mode(false));
+        }
+        Ok(())
+    }
+
+    fn frame_loss(&self) -> Option<f32> {
+        self.acks.as_ref().and_then(FrameAcks::loss_rate)
+    }
+
+    /// 以查询信息请求作为心跳
+    fn ping(&mut self) -> Option<bool> {
+        // 渲染中的设备由update取回解析结果并报错；空闲的设备也要能切换到新地址
+        let idle = self
+            .last_sent
+            .is_none_or(|at| at.elapsed() >= FRAME_GAP);
+        if idle {
+            if let Err(e) = self.poll_resolve() {
+                log::warn!(name = self.discovered_name.as_str(), err:display = e; "LED Matrix did not answer after re-resolving");
+            }
+        }
+        if self.read_replies() > 0 {
+            self.diff.reset();
+        }
+        let answered = !self.ping_pending;
+        if answered {
+            self.missed_pings = 0;
+        } else {
+            self.missed_pings += 1;
+            if self.missed_pings >= MISSED_PINGS_BEFORE_RESOLVE {
+                self.start_resolve();
+            }
+        }
+        // 发送失败同样记为未应答
+        let _ = self.send(&LedMatrixProtocol::encode_query_info());
+        self.ping_pending = true;
+        Some(answered)
     }
 }
 
+/// 从mDNS解析出的地址中选择一个：优先与配置的源地址同族，其次IPv4
+fn select_device_addr(addresses: &HashSet<ScopedIp>, port: u16) -> Option<SocketAddr> {
+    let prefer_v6 = matches!(source_address(), Some(IpAddr::V6(_)));
+    addresses
+        .iter()
+        .filter_map(|ip| match ip {
+            ScopedIp::V4(v4) => Some(SocketAddr::from((*v4.addr(), port))),
+            // 链路本地IPv6地址需要带上接口索引才能路由
+            ScopedIp::V6(v6) => Some(SocketAddr::V6(SocketAddrV6::new(
+                *v6.addr(),
+                port,
+                0,
+                v6.scope_id().index,
+            ))),
+            _ => None,
+        })
+        .min_by_key(|addr| (addr.is_ipv6() != prefer_v6, addr.ip()))
+}
+
 /// 通过mDNS发现LED矩阵设备
-fn discover_devices(timeout_secs: u64) -> Vec<DiscoveredDevice> {
+fn discover_devices(timeout: Duration) -> Vec<DiscoveredDevice> {
     let devices: Arc<Mutex<HashMap<String, DiscoveredDevice>>> =
         Arc::new(Mutex::new(HashMap::new()));
 
@@ -460,6 +892,16 @@ fn discover_devices(timeout_secs: u64) -> Vec<DiscoveredDevice> {
         }
     };
 
+    // 配置了源地址时只在该网卡上查询
+    if let Some(source) = source_address() {
+        if let Err(e) = mdns
+            .disable_interface(IfKind::All)
+            .and_then(|_| mdns.enable_interface(IfKind::Addr(source)))
+        {
+            log::warn!(source = source.to_string().as_str(), err:display = e; "Failed to restrict mDNS to the source interface");
+        }
+    }
+
     // 浏览服务
     let receiver = match mdns.browse(SERVICE_TYPE) {
         Ok(r) => r,
@@ -471,7 +913,6 @@ fn discover_devices(timeout_secs: u64) -> Vec<DiscoveredDevice> {
 
     let devices_clone = devices.clone();
     let start = std::time::Instant::now();
-    let timeout = Duration::from_secs(timeout_secs);
 
     // 接收服务事件
     while start.elapsed() < timeout {
@@ -485,24 +926,20 @@ fn discover_devices(timeout_secs: u64) -> Vec<DiscoveredDevice> {
                         .map(|v| v.to_string())
                         .unwrap_or_else(|| info.get_fullname().to_string());
 
-                    // 获取IP地址
-                    let addresses: Vec<_> = info.get_addresses().iter().collect();
-                    if addresses.is_empty() {
+                    // 获取IP地址（IPv4或IPv6）
+                    let Some(addr) = select_device_addr(info.get_addresses(), info.get_port())
+                    else {
                         continue;
-                    }
-                    let ip = addresses[0].to_string();
-                    let port = info.get_port();
+                    };
 
                     let device = DiscoveredDevice {
                         name: name.clone(),
-                        ip,
-                        port,
+                        addr,
                     };
 
                     log::info!(
                         name = name.as_str(),
-                        ip = device.ip.as_str(),
-                        port = port;
+                        addr = addr.to_string().as_str();
                         "Discovered LED Matrix via mDNS"
                     );
 
@@ -538,8 +975,9 @@ fn discover_devices(timeout_secs: u64) -> Vec<DiscoveredDevice> {
 fn probe() -> Vec<Box<dyn Controller>> {
     let mut controllers: Vec<Box<dyn Controller>> = Vec::new();
 
-    log::info!("Scanning for LED Matrix devices via mDNS...");
-    let devices = discover_devices(3); // 3秒超时
+    let timeout = discovery_timeout();
+    log::info!(timeout_ms = timeout.as_millis() as u64; "Scanning for LED Matrix devices via mDNS...");
+    let devices = discover_devices(timeout);
 
     for device in devices {
         match LedMatrixUdpController::new(device.clone()) {
@@ -564,5 +1002,143 @@ fn probe() -> Vec<Box<dyn Controller>> {
     inventory::submit!(ControllerMetadata {
     name: "LED Matrix UDP Controller",
     description: "UDP-based LED Matrix Display with mDNS discovery",
+    priority: 0,
+    specificity: ProbeSpecificity::Matched,
     probe,
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 绑定在本机回环上的控制器，后台解析使用`resolver`
+    fn controller(addr: SocketAddr, resolver: Resolver) -> LedMatrixUdpController {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.set_nonblocking(true).unwrap();
+        let protocol = ProtocolVersion::V4;
+        LedMatrixUdpController {
+            discovered_name: "matrix".to_string(),
+            device_name: "Matrix".to_string(),
+            device_description: String::new(),
+            serial: "serial".to_string(),
+            addr,
+            socket,
+            outputs: Vec::new(),
+            led_count: 4,
+            frame_buffer: Vec::new(),
+            max_pixels_per_fragment: LedMatrixProtocol::max_pixels_per_fragment(
+                protocol,
+                DEFAULT_UDP_PAYLOAD,
+            )
+            .unwrap(),
+            frame_id: 0,
+            protocol,
+            diff: FrameDiff::new(FrameDiffConfig::for_protocol(
+                protocol.fragment_header_len(),
+                protocol.bytes_per_pixel(),
+                KEYFRAME_INTERVAL,
+            )),
+            acks: None,
+            last_sent: None,
+            ping_pending: false,
+            send_failures: 0,
+            missed_pings: 0,
+            resolving: None,
+            resolver,
+        }
+    }
+
+    /// 设置一个手动完成的解析，返回其发送端
+    fn pending_resolve(
+        controller: &mut LedMatrixUdpController,
+    ) -> mpsc::Sender<Result<SocketAddr, String>> {
+        let (tx, rx) = mpsc::channel();
+        controller.resolving = Some(rx);
+        tx
+    }
+
+    fn moved_addr() -> SocketAddr {
+        "127.0.0.2:4210".parse().unwrap()
+    }
+
+    #[test]
+    fn repeated_send_failures_resolve_and_then_report_errors() {
+        // IPv4的socket发不到IPv6地址，每帧都会发送失败
+        let unreachable: SocketAddr = "[::1]:4210".parse().unwrap();
+        let mut matrix = controller(unreachable, |_, _, current| Ok(current));
+        let frame = vec![Color::default(); 4];
+
+        for _ in 1..SEND_FAILURES_BEFORE_RESOLVE {
+            assert!(matrix.update(&frame).is_ok());
+            assert!(matrix.resolving.is_none());
+        }
+        assert!(matrix.update(&frame).unwrap_err().contains("in a row"));
+        assert!(matrix.resolving.is_some());
+
+        // 解析确认设备仍在原地址，计数清零
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while matrix.resolving.is_some() && Instant::now() < deadline {
+            matrix.poll_resolve().unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(matrix.resolving.is_none());
+        assert_eq!(matrix.send_failures, 0);
+        assert_eq!(matrix.addr, unreachable);
+    }
+
+    #[test]
+    fn a_resolved_address_replaces_the_current_one() {
+        let start: SocketAddr = "127.0.0.1:4210".parse().unwrap();
+        let mut matrix = controller(start, |_, _, _| Err("unused".to_string()));
+        matrix.send_failures = 2;
+
+        // 解析未完成时照常使用当前地址
+        let tx = pending_resolve(&mut matrix);
+        matrix.poll_resolve().unwrap();
+        assert!(matrix.resolving.is_some());
+        assert_eq!(matrix.send_failures, 2);
+
+        tx.send(Ok(start)).unwrap();
+        matrix.poll_resolve().unwrap();
+        assert!(matrix.resolving.is_none());
+        assert_eq!(matrix.send_failures, 0);
+        assert_eq!(matrix.port_name(), "127.0.0.1:4210");
+
+        pending_resolve(&mut matrix).send(Ok(moved_addr())).unwrap();
+        matrix.poll_resolve().unwrap();
+        assert_eq!(matrix.addr, moved_addr());
+        assert_eq!(matrix.port_name(), "127.0.0.2:4210");
+    }
+
+    #[test]
+    fn a_failed_or_abandoned_resolve_reports_the_device_gone() {
+        let start: SocketAddr = "127.0.0.1:4210".parse().unwrap();
+        let mut matrix = controller(start, |_, _, _| Err("unused".to_string()));
+
+        pending_resolve(&mut matrix)
+            .send(Err("no answer".to_string()))
+            .unwrap();
+        assert!(matrix.poll_resolve().unwrap_err().contains("gone"));
+        assert!(matrix.resolving.is_none());
+
+        drop(pending_resolve(&mut matrix));
+        assert!(matrix.poll_resolve().unwrap_err().contains("exited"));
+        assert!(matrix.resolving.is_none());
+        assert_eq!(matrix.addr, start);
+    }
+
+    #[test]
+    fn disconnect_leaves_the_last_frame_up() {
+        let device = UdpSocket::bind("127.0.0.1:0").unwrap();
+        device.set_read_timeout(Some(Duration::from_millis(100))).unwrap();
+        let mut matrix = controller(device.local_addr().unwrap(), |_, _, current| Ok(current));
+        let mut buf = [0u8; 1500];
+
+        matrix.update(&[Color { r: 255, g: 0, b: 0 }; 4]).unwrap();
+        assert!(device.recv(&mut buf).is_ok());
+        while device.recv(&mut buf).is_ok() {}
+
+        matrix.disconnect().unwrap();
+        assert!(device.recv(&mut buf).is_err());
+    }
+}
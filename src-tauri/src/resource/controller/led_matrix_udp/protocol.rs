@@ -6,14 +6,69 @@ pub const CMD_QUERY_INFO: u8 = 0x10;
 pub const CMD_QUERY_CONFIG: u8 = 0x14;
 /// 分片帧数据（唯一支持的写入命令）
 pub const CMD_FRAGMENT_PIXELS: u8 = 0x12;
+/// 设备回报最后一个完整收到的帧：[cmd, frame_id]（仅在ACK模式下发送）
+pub const CMD_FRAME_ACK: u8 = 0x13;
+/// 开关ACK模式：[cmd, enabled]；支持的设备以相同格式回复当前状态
+pub const CMD_SET_ACK_MODE: u8 = 0x15;
 
-/// 当前协议版本
+/// 当前（最新）协议版本
 pub const PROTOCOL_VERSION: u8 = 4;
-/// 推荐的最大UDP负载（字节），与虚拟设备保持一致
-pub const MAX_UDP_PAYLOAD: usize = 1400;
+/// 仍然支持的最低协议版本；只有虚拟设备实现的版本才算支持
+pub const MIN_PROTOCOL_VERSION: u8 = 4;
+/// 默认的最大UDP负载（字节），与虚拟设备保持一致
+pub const DEFAULT_UDP_PAYLOAD: usize = 1400;
+/// 可设置的最小UDP负载：IPv4保证可重组的576字节数据报减去IP/UDP头
+pub const MIN_UDP_PAYLOAD: usize = 548;
+/// 可设置的最大UDP负载：9000字节巨帧减去IPv6/UDP头
+pub const MAX_UDP_PAYLOAD: usize = 8952;
 
 use crate::interface::controller::Color;
 
+/// 协商后的协议版本，决定像素分片的编码方式
+///
+/// - v4: 带索引像素 `[cmd, frame_id, total, index, count_lo, count_hi, (index_lo, index_hi, r, g, b) * count]`
+///
+/// 新版本须先有固件规范并在虚拟设备中实现，再在这里加入。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolVersion {
+    V4,
+}
+
+impl ProtocolVersion {
+    /// 根据设备上报的版本选择编码方式
+    ///
+    /// `MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION` 之外的设备视为不兼容：更新的版本
+    /// 格式未知，不能假定它向后兼容。
+    pub fn negotiate(device_version: u8) -> Option<Self> {
+        match device_version {
+            PROTOCOL_VERSION => Some(Self::V4),
+            _ => None,
+        }
+    }
+
+    /// 分片头长度（字节）
+    #[inline]
+    pub fn fragment_header_len(self) -> usize {
+        match self {
+            Self::V4 => 6,
+        }
+    }
+
+    /// 单个像素占用的字节数
+    #[inline]
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            Self::V4 => 5,
+        }
+    }
+
+    /// 单个分片可能占用的最大字节数
+    #[inline]
+    pub fn fragment_capacity(self, max_pixels: usize) -> usize {
+        self.fragment_header_len() + max_pixels * self.bytes_per_pixel()
+    }
+}
+
 /// 设备信息查询结果
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct QueryInfo {
@@ -53,8 +108,30 @@ impl LedMatrixProtocol {
         [CMD_QUERY_CONFIG]
     }
 
+    /// 编码开关ACK模式命令
+    #[inline]
+    pub fn encode_set_ack_mode(enabled: bool) -> [u8; 2] {
+        [CMD_SET_ACK_MODE, enabled as u8]
+    }
+
+    /// 解析ACK模式回复，返回设备当前是否会发送帧确认
+    pub fn decode_ack_mode_reply(data: &[u8]) -> Option<bool> {
+        match data {
+            [CMD_SET_ACK_MODE, enabled, ..] => Some(*enabled != 0),
+            _ => None,
+        }
+    }
+
+    /// 解析帧确认，返回设备最后一个完整收到的 frame_id
+    pub fn decode_frame_ack(data: &[u8]) -> Option<u8> {
+        match data {
+            [CMD_FRAME_ACK, frame_id, ..] => Some(*frame_id),
+            _ => None,
+        }
+    }
+
     /// 解析设备信息响应
-    /// 格式 (strict, v4):
+    /// 格式:
     /// [cmd, version, width_lo, width_hi, height_lo, height_hi, pixel_size_lo, pixel_size_hi,
     ///  name_len, name_bytes,
     ///  desc_len, desc_bytes,
@@ -123,13 +200,14 @@ impl LedMatrixProtocol {
     }
 
     /// 计算单个分片最多可携带的像素数量
-    /// header = cmd(1) + frame_id(1) + total_fragments(1) + fragment_index(1) + count(2) = 6
+    /// v4 header = cmd(1) + frame_id(1) + total_fragments(1) + fragment_index(1) + count(2) = 6
     #[inline]
-    pub fn max_pixels_per_fragment(max_payload: usize) -> Result<usize, String> {
-        if max_payload <= 6 {
+    pub fn max_pixels_per_fragment(version: ProtocolVersion, max_payload: usize) -> Result<usize, String> {
+        let header = version.fragment_header_len();
+        if max_payload <= header {
             return Err("Max UDP payload is too small for fragment header".to_string());
         }
-        Ok((max_payload - 6) / 5)
+        Ok((max_payload - header) / version.bytes_per_pixel())
     }
 
     /// 计算总分片数，限制在协议约定的 u8 范围内
@@ -142,9 +220,9 @@ impl LedMatrixProtocol {
         u8::try_from(total).map_err(|_| "Fragment count exceeds protocol limit (<=255)".to_string())
     }
 
-    /// 编码单个分片命令到缓冲区
-    /// 格式: [cmd, frame_id, total_fragments, fragment_index, count_lo, count_hi, (index_lo, index_hi, r, g, b) * count]
+    /// 编码单个分片命令到缓冲区，格式取决于协商出的协议版本
     pub fn encode_fragment_into(
+        version: ProtocolVersion,
         frame_id: u8,
         total_fragments: u8,
         fragment_index: u8,
@@ -161,26 +239,110 @@ impl LedMatrixProtocol {
         }
 
         buffer.clear();
-        buffer.reserve(1 + 5 + colors.len() * 5);
+        buffer.reserve(version.fragment_capacity(colors.len()));
 
         buffer.push(CMD_FRAGMENT_PIXELS);
         buffer.push(frame_id);
         buffer.push(total_fragments);
         buffer.push(fragment_index);
+
+        match version {
+            ProtocolVersion::V4 => Self::encode_indexed_v4(start_index, colors, buffer),
+        }
+    }
+
+    /// v4: 每个像素携带自身索引
+    fn encode_indexed_v4(start_index: usize, colors: &[Color], buffer: &mut Vec<u8>) -> Result<(), String> {
         buffer.extend_from_slice(&(colors.len() as u16).to_le_bytes());
 
-        let mut index = start_index;
-        for color in colors {
-            let idx: u16 = index
+        for (offset, color) in colors.iter().enumerate() {
+            let idx: u16 = (start_index + offset)
                 .try_into()
                 .map_err(|_| "LED index exceeds u16 range for protocol".to_string())?;
             buffer.extend_from_slice(&idx.to_le_bytes());
             buffer.push(color.r);
             buffer.push(color.g);
             buffer.push(color.b);
-            index += 1;
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colors(n: usize) -> Vec<Color> {
+        (0..n)
+            .map(|i| Color {
+                r: i as u8,
+                g: (i * 2) as u8,
+                b: (i * 3) as u8,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn negotiates_supported_versions() {
+        assert_eq!(ProtocolVersion::negotiate(3), None);
+        assert_eq!(ProtocolVersion::negotiate(4), Some(ProtocolVersion::V4));
+        // 更新的版本格式未知，不按v4发送
+        assert_eq!(ProtocolVersion::negotiate(5), None);
+    }
+
+    #[test]
+    fn decodes_frame_acks_and_ack_mode_replies() {
+        assert_eq!(LedMatrixProtocol::decode_frame_ack(&[CMD_FRAME_ACK, 42]), Some(42));
+        assert_eq!(LedMatrixProtocol::decode_frame_ack(&[CMD_FRAME_ACK]), None);
+        assert_eq!(LedMatrixProtocol::decode_frame_ack(&[CMD_QUERY_INFO, 42]), None);
+
+        let request = LedMatrixProtocol::encode_set_ack_mode(true);
+        assert_eq!(LedMatrixProtocol::decode_ack_mode_reply(&request), Some(true));
+        assert_eq!(LedMatrixProtocol::decode_ack_mode_reply(&[CMD_SET_ACK_MODE, 0]), Some(false));
+        assert_eq!(LedMatrixProtocol::decode_ack_mode_reply(&[CMD_FRAME_ACK, 1]), None);
+    }
+
+    #[test]
+    fn encodes_v4_fragment_with_indices() {
+        let mut buf = Vec::new();
+        LedMatrixProtocol::encode_fragment_into(ProtocolVersion::V4, 7, 2, 1, 300, &colors(2), &mut buf)
+            .unwrap();
+
+        assert_eq!(
+            buf,
+            vec![
+                CMD_FRAGMENT_PIXELS, 7, 2, 1, 2, 0,
+                0x2C, 0x01, 0, 0, 0,
+                0x2D, 0x01, 1, 2, 3,
+            ]
+        );
+    }
+
+    #[test]
+    fn fragments_fit_max_payload_for_each_version() {
+        for version in [ProtocolVersion::V4] {
+            for payload in [MIN_UDP_PAYLOAD, DEFAULT_UDP_PAYLOAD, MAX_UDP_PAYLOAD] {
+                let max_pixels = LedMatrixProtocol::max_pixels_per_fragment(version, payload).unwrap();
+                let mut buf = Vec::new();
+                LedMatrixProtocol::encode_fragment_into(version, 0, 1, 0, 0, &colors(max_pixels), &mut buf)
+                    .unwrap();
+                assert!(buf.len() <= payload);
+                assert_eq!(buf.len(), version.fragment_capacity(max_pixels));
+            }
+        }
+    }
+
+    #[test]
+    fn larger_payloads_need_fewer_fragments() {
+        let fragments = |version, payload| {
+            let max_pixels = LedMatrixProtocol::max_pixels_per_fragment(version, payload).unwrap();
+            LedMatrixProtocol::calc_total_fragments(1000, max_pixels).unwrap()
+        };
+
+        // v4: 5 bytes per pixel after a 6-byte header.
+        assert_eq!(fragments(ProtocolVersion::V4, MIN_UDP_PAYLOAD), 10); // 108 per fragment
+        assert_eq!(fragments(ProtocolVersion::V4, DEFAULT_UDP_PAYLOAD), 4); // 278
+        assert_eq!(fragments(ProtocolVersion::V4, MAX_UDP_PAYLOAD), 1); // 1789
+    }
+}
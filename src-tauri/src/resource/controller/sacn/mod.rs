@@ -0,0 +1,263 @@
+//! DMX-over-Ethernet nodes driven with E1.31 (sACN) on UDP port 5568.
+//!
+//! sACN has no discovery, so nodes are added by hand (`add_sacn_device`) and remembered in
+//! the app config; the probe only sets up the configured ones. A node's LEDs fill
+//! consecutive universes from its start universe, 170 per universe. Without a unicast
+//! address each universe goes to its multicast group.
+
+use crate::interface::controller::{
+    Color, Controller, ControllerMetadata, DeviceType, OutputCapabilities, OutputPortDefinition,
+    ProbeSpecificity, SegmentType,
+};
+use crate::resource::driver::net_bind::{bind_udp_for, set_multicast_if_v4};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::RwLock;
+
+mod protocol;
+use protocol::{LEDS_PER_UNIVERSE, MAX_UNIVERSE, SACN_PORT};
+
+/// Shown by receivers that list their sources.
+const SOURCE_NAME: &str = "Light";
+
+/// Guards against typos in the LED count; 64 universes is more than any single node takes.
+const MAX_UNIVERSES_PER_NODE: usize = 64;
+
+const OUTPUT_ID: &str = "strip";
+
+/// DMX512 refreshes a full universe about 44 times a second, so nodes that drive DMX can't
+/// pass on more than that.
+const MAX_REFRESH_HZ: f32 = 44.0;
+
+/// A node the user added, as persisted in the app config.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SacnNode {
+    /// Unicast target; `None` sends to each universe's multicast group.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<IpAddr>,
+    /// First universe, 1–63999.
+    pub universe: u16,
+    pub led_count: usize,
+}
+
+impl SacnNode {
+    fn port_name(&self) -> String {
+        match self.ip {
+            Some(ip) => format!("sacn://{}/{}", ip, self.universe),
+            None => format!("sacn://multicast/{}", self.universe),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let universes = protocol::universe_count(self.led_count);
+        if universes == 0 || universes > MAX_UNIVERSES_PER_NODE {
+            return Err(format!(
+                "LED count must be between 1 and {}",
+                MAX_UNIVERSES_PER_NODE * LEDS_PER_UNIVERSE
+            ));
+        }
+        let last = self.universe as usize + universes - 1;
+        if self.universe == 0 || last > MAX_UNIVERSE as usize {
+            return Err(format!(
+                "Universes {}–{} are outside 1–{}",
+                self.universe, last, MAX_UNIVERSE
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Nodes added by hand, persisted in the app config.
+static CONFIGURED_NODES: RwLock<Vec<SacnNode>> = RwLock::new(Vec::new());
+
+pub fn configured_nodes() -> Vec<SacnNode> {
+    CONFIGURED_NODES
+        .read()
+        .map(|nodes| nodes.clone())
+        .unwrap_or_default()
+}
+
+/// Replace the configured nodes; invalid entries are dropped.
+pub fn set_configured_nodes(nodes: &[SacnNode]) {
+    let valid = nodes
+        .iter()
+        .filter(|node| match node.validate() {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!(port = node.port_name().as_str(), err:display = e; "Ignoring configured sACN node");
+                false
+            }
+        })
+        .cloned()
+        .collect();
+    if let Ok(mut current) = CONFIGURED_NODES.write() {
+        *current = valid;
+    }
+}
+
+/// Keep `node` configured so later scans set it up again; replaces one on the same port.
+pub fn remember_node(node: SacnNode) {
+    if let Ok(mut nodes) = CONFIGURED_NODES.write() {
+        nodes.retain(|known| known.port_name() != node.port_name());
+        nodes.push(node);
+    }
+}
+
+/// Stop configuring the node on `port`. Returns whether it was configured.
+pub fn forget_node(port: &str) -> bool {
+    let Ok(mut nodes) = CONFIGURED_NODES.write() else {
+        return false;
+    };
+    let before = nodes.len();
+    nodes.retain(|known| known.port_name() != port);
+    nodes.len() != before
+}
+
+pub struct SacnController {
+    node: SacnNode,
+    socket: UdpSocket,
+    /// Destination of each universe, starting with `node.universe`.
+    targets: Vec<SocketAddr>,
+    cid: [u8; 16],
+    sequence: u8,
+    frame: Vec<Color>,
+}
+
+impl SacnController {
+    pub fn new(node: SacnNode) -> Result<Self, String> {
+        node.validate()?;
+        let universes = node.universe..node.universe + protocol::universe_count(node.led_count) as u16;
+        let (socket, targets) = match node.ip {
+            Some(ip) => (
+                bind_udp_for(ip),
+                universes.map(|_| SocketAddr::new(ip, SACN_PORT)).collect(),
+            ),
+            None => (
+                bind_udp_for(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+                    .and_then(|socket| set_multicast_if_v4(&socket).map(|_| socket)),
+                universes
+                    .map(|universe| SocketAddr::new(protocol::multicast_group(universe).into(), SACN_PORT))
+                    .collect(),
+            ),
+        };
+        let socket = socket.map_err(|e| format!("Failed to bind socket: {}", e))?;
+
+        Ok(Self {
+            cid: component_id(&node.port_name()),
+            frame: vec![Color::default(); node.led_count],
+            node,
+            socket,
+            targets,
+            sequence: 0,
+        })
+    }
+
+    fn send_frame(&mut self) -> Result<(), String> {
+        self.sequence = self.sequence.wrapping_add(1);
+        let universes = (self.node.universe..).zip(&self.targets);
+        for ((universe, target), colors) in universes.zip(self.frame.chunks(LEDS_PER_UNIVERSE)) {
+            let packet = protocol::encode_packet(&self.cid, SOURCE_NAME, universe, self.sequence, colors);
+            self.socket
+                .send_to(&packet, target)
+                .map_err(|e| format!("Failed to send sACN packet: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+impl Controller for SacnController {
+    fn port_name(&self) -> String {
+        self.node.port_name()
+    }
+
+    fn model(&self) -> String {
+        "sACN (E1.31)".to_string()
+    }
+
+    fn description(&self) -> String {
+        let last = self.node.universe as usize + self.targets.len() - 1;
+        let target = match self.node.ip {
+            Some(ip) => ip.to_string(),
+            None => "multicast".to_string(),
+        };
+        if last == self.node.universe as usize {
+            format!("E1.31 universe {} ({})", self.node.universe, target)
+        } else {
+            format!("E1.31 universes {}–{} ({})", self.node.universe, last, target)
+        }
+    }
+
+    fn serial_id(&self) -> String {
+        self.node.port_name()
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::LedStrip
+    }
+
+    fn outputs(&self) -> Vec<OutputPortDefinition> {
+        let len = self.node.led_count;
+        vec![OutputPortDefinition {
+            id: OUTPUT_ID.to_string(),
+            name: "Strip".to_string(),
+            output_type: SegmentType::Linear,
+            leds_count: len,
+            matrix: None,
+            // The count is part of the node's configuration.
+            capabilities: OutputCapabilities {
+                editable: false,
+                min_total_leds: len,
+                max_total_leds: len,
+                allowed_total_leds: Some(vec![len]),
+                allowed_segment_types: vec![SegmentType::Linear],
+            },
+        }]
+    }
+
+    fn update(&mut self, colors: &[Color]) -> Result<(), String> {
+        let len = colors.len().min(self.frame.len());
+        self.frame[..len].copy_from_slice(&colors[..len]);
+        self.frame[len..].fill(Color::default());
+        self.send_frame()
+    }
+
+    fn max_update_hz(&self) -> Option<f32> {
+        Some(MAX_REFRESH_HZ)
+    }
+}
+
+/// Stable 16-byte source id (CID) for a node, so receivers see the same source across
+/// restarts. Two FNV-1a hashes of the port name with different offsets.
+fn component_id(port: &str) -> [u8; 16] {
+    let fnv = |offset: u64| {
+        port.bytes().fold(offset, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01B3)
+        })
+    };
+    let mut cid = [0u8; 16];
+    cid[..8].copy_from_slice(&fnv(0xCBF2_9CE4_8422_2325).to_be_bytes());
+    cid[8..].copy_from_slice(&fnv(0x8422_2325_CBF2_9CE4).to_be_bytes());
+    cid
+}
+
+fn probe() -> Vec<Box<dyn Controller>> {
+    let mut controllers: Vec<Box<dyn Controller>> = Vec::new();
+    for node in configured_nodes() {
+        match SacnController::new(node) {
+            Ok(controller) => controllers.push(Box::new(controller)),
+            Err(e) => {
+                log::warn!(err:display = e; "Failed to create sACN controller");
+            }
+        }
+    }
+    controllers
+}
+
+inventory::submit!(ControllerMetadata {
+    name: "sACN Controller",
+    description: "E1.31 (sACN) nodes added by hand",
+    priority: 0,
+    specificity: ProbeSpecificity::Matched,
+    probe,
+});
@@ -0,0 +1,127 @@
+//! E1.31 (Streaming ACN) data packets.
+//!
+//! Each packet carries one universe of up to 512 DMX slots behind three nested layers:
+//!
+//! ```text
+//! root (38 bytes):    preamble, "ASC-E1.17" identifier, flags+length, vector, CID
+//! framing (77 bytes): flags+length, vector, source name, priority, sync, sequence, options, universe
+//! DMP (11 bytes):     flags+length, vector, address type, first address, increment, count, start code
+//! ```
+//!
+//! Every flags+length field is `0x7000 | length`, counting from that field to the end of
+//! the packet. LEDs are packed three slots each and never split across universes, so a
+//! universe holds 170 of them and leaves slots 511–512 unused.
+
+use std::net::Ipv4Addr;
+
+use crate::interface::controller::Color;
+
+pub const SACN_PORT: u16 = 5568;
+
+/// Universes 64000 and up are reserved.
+pub const MAX_UNIVERSE: u16 = 63999;
+
+pub const LEDS_PER_UNIVERSE: usize = 170;
+
+const ACN_IDENTIFIER: &[u8; 12] = b"ASC-E1.17\0\0\0";
+const VECTOR_ROOT_E131_DATA: u32 = 0x0000_0004;
+const VECTOR_E131_DATA_PACKET: u32 = 0x0000_0002;
+const VECTOR_DMP_SET_PROPERTY: u8 = 0x02;
+const DMP_ADDRESS_TYPE: u8 = 0xA1;
+
+const SOURCE_NAME_LEN: usize = 64;
+const DEFAULT_PRIORITY: u8 = 100;
+
+/// Offsets of the flags+length fields, and where the slot data starts.
+const ROOT_PDU_OFFSET: usize = 16;
+const FRAMING_PDU_OFFSET: usize = 38;
+const DMP_PDU_OFFSET: usize = 115;
+const HEADER_LEN: usize = 126;
+
+/// 239.255.<universe high byte>.<universe low byte>.
+pub fn multicast_group(universe: u16) -> Ipv4Addr {
+    let [high, low] = universe.to_be_bytes();
+    Ipv4Addr::new(239, 255, high, low)
+}
+
+/// Universes needed for `led_count` LEDs.
+pub fn universe_count(led_count: usize) -> usize {
+    led_count.div_ceil(LEDS_PER_UNIVERSE)
+}
+
+/// One data packet for `universe` carrying `colors` (at most `LEDS_PER_UNIVERSE`).
+pub fn encode_packet(
+    cid: &[u8; 16],
+    source_name: &str,
+    universe: u16,
+    sequence: u8,
+    colors: &[Color],
+) -> Vec<u8> {
+    let colors = &colors[..colors.len().min(LEDS_PER_UNIVERSE)];
+    let slots = colors.len() * 3;
+    let len = HEADER_LEN + slots;
+    let flags_and_length = |offset: usize| (0x7000 | (len - offset) as u16).to_be_bytes();
+
+    let mut packet = Vec::with_capacity(len);
+    // Root layer
+    packet.extend_from_slice(&0x0010u16.to_be_bytes());
+    packet.extend_from_slice(&0u16.to_be_bytes());
+    packet.extend_from_slice(ACN_IDENTIFIER);
+    packet.extend_from_slice(&flags_and_length(ROOT_PDU_OFFSET));
+    packet.extend_from_slice(&VECTOR_ROOT_E131_DATA.to_be_bytes());
+    packet.extend_from_slice(cid);
+    // Framing layer
+    packet.extend_from_slice(&flags_and_length(FRAMING_PDU_OFFSET));
+    packet.extend_from_slice(&VECTOR_E131_DATA_PACKET.to_be_bytes());
+    let mut name = [0u8; SOURCE_NAME_LEN];
+    // Null-terminated.
+    let name_len = source_name.len().min(SOURCE_NAME_LEN - 1);
+    name[..name_len].copy_from_slice(&source_name.as_bytes()[..name_len]);
+    packet.extend_from_slice(&name);
+    packet.push(DEFAULT_PRIORITY);
+    packet.extend_from_slice(&0u16.to_be_bytes());
+    packet.push(sequence);
+    packet.push(0);
+    packet.extend_from_slice(&universe.to_be_bytes());
+    // DMP layer
+    packet.extend_from_slice(&flags_and_length(DMP_PDU_OFFSET));
+    packet.push(VECTOR_DMP_SET_PROPERTY);
+    packet.push(DMP_ADDRESS_TYPE);
+    packet.extend_from_slice(&0u16.to_be_bytes());
+    packet.extend_from_slice(&1u16.to_be_bytes());
+    packet.extend_from_slice(&(slots as u16 + 1).to_be_bytes());
+    // Start code 0: dimmer data.
+    packet.push(0);
+    packet.extend(colors.iter().flat_map(|c| [c.r, c.g, c.b]));
+    packet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packets_have_e131_layout() {
+        let colors: Vec<Color> = (0..200).map(|i| Color { r: i as u8, g: 1, b: 2 }).collect();
+        let packet = encode_packet(&[7; 16], "Light", 300, 42, &colors);
+        // Capped at one universe: 126 + 170 * 3.
+        assert_eq!(packet.len(), 636);
+
+        assert_eq!(&packet[4..16], ACN_IDENTIFIER);
+        assert_eq!(&packet[16..18], &(0x7000u16 | 620).to_be_bytes());
+        assert_eq!(&packet[22..38], &[7; 16]);
+        assert_eq!(&packet[38..40], &(0x7000u16 | 598).to_be_bytes());
+        assert_eq!(&packet[44..50], b"Light\0");
+        assert_eq!(packet[108], 100);
+        assert_eq!(packet[111], 42);
+        assert_eq!(&packet[113..115], &300u16.to_be_bytes());
+        assert_eq!(&packet[115..117], &(0x7000u16 | 521).to_be_bytes());
+        assert_eq!(&packet[123..125], &511u16.to_be_bytes());
+        assert_eq!(&packet[125..129], &[0, 0, 1, 2]);
+        assert_eq!(&packet[633..636], &[169, 1, 2]);
+
+        assert_eq!(multicast_group(300), Ipv4Addr::new(239, 255, 1, 44));
+        assert_eq!(universe_count(170), 1);
+        assert_eq!(universe_count(171), 2);
+    }
+}
@@ -1,20 +1,30 @@
 use crate::interface::controller::{
-    Color, Controller, ControllerMetadata, DeviceType, OutputCapabilities, OutputPortDefinition,
-    SegmentType,
+    Color, ColorOrder, Controller, ControllerMetadata, DeviceType, OutputCapabilities, OutputPortDefinition,
+    ProbeSpecificity, SegmentType, WriteMetrics,
 };
+use crate::resource::driver::frame_diff::{FrameDiff, FrameDiffConfig, FramePlan};
 use crate::resource::driver::serail_port::RateLimitedSerialPort;
 use inventory;
 use serialport::SerialPortType;
 use std::time::Duration;
 
 mod protocol;
-use protocol::SkydimoSerialProtocol;
+use protocol::{HandshakeInfo, SkydimoSerialProtocol, RANGE_HEADER_LEN};
 mod config;
 use config::build_layout_from_device_name;
 
 /// Baud rate used for Skydimo serial devices.
 const BAUD_RATE: u32 = 115_200;
 
+/// Resend the whole strip every ~5s at 60 FPS in case the device rebooted in between.
+const KEYFRAME_INTERVAL: u32 = 300;
+
+/// LED count assumed for models missing from the layout table until the user sets one.
+const FALLBACK_LED_COUNT: usize = 100;
+
+/// Upper bound for user-configured strips; beyond this 115200 baud drops below ~7 FPS.
+const MAX_CONFIGURABLE_LEDS: usize = 512;
+
 pub struct SkydimoSerialController {
     pub port_name: String,
     model: String,
@@ -22,8 +32,13 @@ pub struct SkydimoSerialController {
     port: RateLimitedSerialPort,
     outputs: Vec<OutputPortDefinition>,
     led_count: usize,
+    /// Unknown models get a host-chosen length; known models keep the table's count.
+    configurable_length: bool,
+    color_order: ColorOrder,
     buffer_cache: Vec<Color>,
     packet_cache: Vec<u8>,
+    /// Present only when the firmware advertised range packet support.
+    diff: Option<FrameDiff>,
 }
 
 impl SkydimoSerialController {
@@ -32,13 +47,16 @@ impl SkydimoSerialController {
         model: String,
         id: String,
         port: RateLimitedSerialPort,
+        supports_ranges: bool,
+        color_order: ColorOrder,
     ) -> Self {
         // Try to build a default layout from the reported model name.
-        let (output_type, led_count, matrix) = if let Some(layout) = build_layout_from_device_name(&model) {
-            (layout.segment_type, layout.total_leds, layout.matrix)
-        } else {
-            // Fallback: treat as a simple linear strip of 100 LEDs.
-            (SegmentType::Linear, 100, None)
+        let layout = build_layout_from_device_name(&model);
+        let configurable_length = layout.is_none();
+        let (output_type, led_count, matrix) = match layout {
+            Some(layout) => (layout.segment_type, layout.total_leds, layout.matrix),
+            // Fallback: treat as a simple linear strip until the user sets the real length.
+            None => (SegmentType::Linear, FALLBACK_LED_COUNT, None),
         };
 
         let capabilities = match output_type {
@@ -49,18 +67,7 @@ impl SkydimoSerialController {
                 allowed_total_leds: Some(vec![led_count]),
                 allowed_segment_types: vec![SegmentType::Matrix],
             },
-            SegmentType::Linear | SegmentType::Single => OutputCapabilities {
-                // Allow segment editing, but keep total LED count fixed for this controller.
-                editable: true,
-                min_total_leds: led_count,
-                max_total_leds: led_count,
-                allowed_total_leds: Some(vec![led_count]),
-                allowed_segment_types: vec![
-                    SegmentType::Single,
-                    SegmentType::Linear,
-                    SegmentType::Matrix,
-                ],
-            },
+            SegmentType::Linear | SegmentType::Single => linear_capabilities(led_count, configurable_length),
         };
 
         let outputs = vec![OutputPortDefinition {
@@ -79,12 +86,41 @@ impl SkydimoSerialController {
             port,
             outputs,
             led_count,
+            configurable_length,
+            color_order,
             buffer_cache: Vec::with_capacity(led_count),
-            packet_cache: Vec::with_capacity(led_count * 3 + 10),
+            packet_cache: Vec::with_capacity(SkydimoSerialProtocol::frame_size(led_count, color_order)),
+            diff: supports_ranges.then(|| range_diff(color_order)),
         }
     }
 }
 
+/// Change tracking for range packets carrying `color_order.channels()` bytes per LED.
+fn range_diff(color_order: ColorOrder) -> FrameDiff {
+    FrameDiff::new(FrameDiffConfig::for_protocol(
+        RANGE_HEADER_LEN,
+        color_order.channels(),
+        KEYFRAME_INTERVAL,
+    ))
+}
+
+fn linear_capabilities(led_count: usize, configurable_length: bool) -> OutputCapabilities {
+    let (min_total_leds, max_total_leds, allowed_total_leds) = if configurable_length {
+        (1, MAX_CONFIGURABLE_LEDS, None)
+    } else {
+        // Known model: allow segment editing, but keep the total LED count fixed.
+        (led_count, led_count, Some(vec![led_count]))
+    };
+
+    OutputCapabilities {
+        editable: true,
+        min_total_leds,
+        max_total_leds,
+        allowed_total_leds,
+        allowed_segment_types: vec![SegmentType::Single, SegmentType::Linear, SegmentType::Matrix],
+    }
+}
+
 impl Controller for SkydimoSerialController {
     fn port_name(&self) -> String {
         self.port_name.clone()
@@ -110,6 +146,20 @@ impl Controller for SkydimoSerialController {
         self.outputs.clone()
     }
 
+    fn color_order(&self) -> ColorOrder {
+        self.color_order
+    }
+
+    fn set_color_order(&mut self, order: ColorOrder) -> Result<(), String> {
+        self.color_order = order;
+        self.port
+            .set_frame_size(SkydimoSerialProtocol::frame_size(self.led_count, order));
+        if self.diff.is_some() {
+            self.diff = Some(range_diff(order));
+        }
+        Ok(())
+    }
+
     fn update(&mut self, colors: &[Color]) -> Result<(), String> {
         // Ensure buffer cache is sized correctly
         if self.buffer_cache.len() != self.led_count {
@@ -123,13 +173,69 @@ impl Controller for SkydimoSerialController {
             self.buffer_cache[len..].fill(Color::default());
         }
 
-        SkydimoSerialProtocol::encode_into(&self.buffer_cache, &mut self.packet_cache);
+        let plan = match &self.diff {
+            Some(diff) => diff.plan(&self.buffer_cache),
+            None => FramePlan::Full,
+        };
+        match &plan {
+            FramePlan::Unchanged => {}
+            FramePlan::Full => SkydimoSerialProtocol::encode_into(
+                &self.buffer_cache,
+                self.color_order,
+                &mut self.packet_cache,
+            ),
+            FramePlan::Partial(ranges) => SkydimoSerialProtocol::encode_ranges_into(
+                &self.buffer_cache,
+                ranges,
+                self.color_order,
+                &mut self.packet_cache,
+            ),
+        }
+
         // Use rate-limited write; returns Ok(false) if frame was dropped due to throttling.
+        let delivered = plan == FramePlan::Unchanged
+            || self
+                .port
+                .write_all_throttled(&self.packet_cache)
+                .map_err(|e| e.to_string())?;
+
+        if let (Some(diff), true) = (&mut self.diff, delivered) {
+            diff.commit(&self.buffer_cache, &plan);
+        }
+        Ok(())
+    }
+
+    fn set_output_leds_count(&mut self, output_id: &str, leds_count: usize) -> Result<(), String> {
+        let Some(output) = self.outputs.iter_mut().find(|o| o.id == output_id) else {
+            return Err(format!("Output '{}' not found", output_id));
+        };
+        if !self.configurable_length {
+            return Err(format!("{} has a fixed LED count of {}", self.model, self.led_count));
+        }
+        if !(1..=MAX_CONFIGURABLE_LEDS).contains(&leds_count) {
+            return Err(format!(
+                "LED count {} out of range 1..={}",
+                leds_count, MAX_CONFIGURABLE_LEDS
+            ));
+        }
+
+        output.leds_count = leds_count;
+        self.led_count = leds_count;
         self.port
-            .write_all_throttled(&self.packet_cache)
-            .map_err(|e| e.to_string())?;
+            .set_frame_size(SkydimoSerialProtocol::frame_size(leds_count, self.color_order));
+        if let Some(diff) = &mut self.diff {
+            diff.reset();
+        }
         Ok(())
     }
+
+    fn write_metrics(&self) -> Option<WriteMetrics> {
+        Some(self.port.metrics())
+    }
+
+    fn max_update_hz(&self) -> Option<f32> {
+        Some(self.port.safe_fps() as f32)
+    }
 }
 
 fn probe() -> Vec<Box<dyn Controller>> {
@@ -150,7 +256,11 @@ fn probe() -> Vec<Box<dyn Controller>> {
             .open()
         {
             match SkydimoSerialProtocol::handshake(&mut port) {
-                Ok((model, id)) => {
+                Ok(HandshakeInfo {
+                    model,
+                    serial: id,
+                    supports_ranges,
+                }) => {
                     // Prepend "Skydimo" if not present, to match C++ "Skydimo " + model
                     let full_model = if !model.starts_with("Skydimo") {
                         format!("Skydimo {}", model)
@@ -159,12 +269,12 @@ fn probe() -> Vec<Box<dyn Controller>> {
                     };
 
                     // Compute frame size for rate limiting based on LED count.
-                    let led_count = if let Some(layout) = build_layout_from_device_name(&full_model) {
-                        layout.total_leds
-                    } else {
-                        100 // Fallback
-                    };
-                    let frame_size = 6 + led_count * 3;
+                    let led_count = build_layout_from_device_name(&full_model)
+                        .map(|layout| layout.total_leds)
+                        .unwrap_or(FALLBACK_LED_COUNT);
+                    // Every model so far is wired RGB.
+                    let color_order = ColorOrder::Rgb;
+                    let frame_size = SkydimoSerialProtocol::frame_size(led_count, color_order);
 
                     // Wrap the port in a rate-limited driver.
                     let rate_limited_port =
@@ -175,6 +285,8 @@ fn probe() -> Vec<Box<dyn Controller>> {
                         full_model,
                         id,
                         rate_limited_port,
+                        supports_ranges,
+                        color_order,
                     )));
                 }
                 Err(_) => {
@@ -189,5 +301,7 @@ fn probe() -> Vec<Box<dyn Controller>> {
     inventory::submit!(ControllerMetadata {
     name: "Skydimo Serial Controller",
     description: "Skydimo Serial LED Strip Driver",
+    priority: 0,
+    specificity: ProbeSpecificity::Verified,
     probe,
 });
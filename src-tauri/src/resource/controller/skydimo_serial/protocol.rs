@@ -1,15 +1,37 @@
-use crate::interface::controller::Color;
+use crate::interface::controller::{Color, ColorOrder};
 use serialport::SerialPort;
 use std::io::{Read, Write};
+use std::ops::Range;
 use std::time::Duration;
 
+/// Full frame header: "Ada" + 0x00, count (hi, lo).
+pub const FRAME_HEADER_LEN: usize = 6;
+
+/// Range packet header: "Adr" + 0x00, start (hi, lo), count (hi, lo).
+pub const RANGE_HEADER_LEN: usize = 8;
+
+/// Handshake suffix advertising firmware features, e.g. `Model,Serial,caps=R`.
+const CAPS_PREFIX: &str = ",caps=";
+
+pub struct HandshakeInfo {
+    pub model: String,
+    pub serial: String,
+    /// Firmware accepts "Adr" range packets and keeps LEDs outside them untouched.
+    pub supports_ranges: bool,
+}
+
 pub struct SkydimoSerialProtocol;
 
 impl SkydimoSerialProtocol {
-    pub fn encode_into(colors: &[Color], buffer: &mut Vec<u8>) {
+    /// Size of a full frame on the wire, used to derive the safe frame rate.
+    pub fn frame_size(led_count: usize, order: ColorOrder) -> usize {
+        FRAME_HEADER_LEN + led_count * order.channels()
+    }
+
+    pub fn encode_into(colors: &[Color], order: ColorOrder, buffer: &mut Vec<u8>) {
         let count = colors.len();
         buffer.clear();
-        buffer.reserve(6 + count * 3);
+        buffer.reserve(Self::frame_size(count, order));
 
         // Header: Ada (0x41, 0x64, 0x61, 0x00)
         buffer.extend_from_slice(&[0x41, 0x64, 0x61, 0x00]);
@@ -17,14 +39,34 @@ impl SkydimoSerialProtocol {
         buffer.push(((count >> 8) & 0xFF) as u8);
         buffer.push((count & 0xFF) as u8);
 
-        for color in colors {
-            buffer.push(color.r);
-            buffer.push(color.g);
-            buffer.push(color.b);
+        order.extend(colors, buffer);
+    }
+
+    /// Encode one "Adr" packet per range, back to back, so a partial frame is a single write.
+    pub fn encode_ranges_into(
+        colors: &[Color],
+        ranges: &[Range<usize>],
+        order: ColorOrder,
+        buffer: &mut Vec<u8>,
+    ) {
+        buffer.clear();
+        let payload: usize = ranges.iter().map(|r| r.len() * order.channels()).sum();
+        buffer.reserve(ranges.len() * RANGE_HEADER_LEN + payload);
+
+        for range in ranges {
+            let start = range.start;
+            let count = range.len();
+            buffer.extend_from_slice(&[0x41, 0x64, 0x72, 0x00]);
+            buffer.push(((start >> 8) & 0xFF) as u8);
+            buffer.push((start & 0xFF) as u8);
+            buffer.push(((count >> 8) & 0xFF) as u8);
+            buffer.push((count & 0xFF) as u8);
+
+            order.extend(&colors[range.clone()], buffer);
         }
     }
 
-    pub fn handshake(port: &mut Box<dyn SerialPort>) -> Result<(String, String), String> {
+    pub fn handshake(port: &mut Box<dyn SerialPort>) -> Result<HandshakeInfo, String> {
         port.write_all(b"Moni-A").map_err(|e| e.to_string())?;
 
         // Wait for response
@@ -32,34 +74,98 @@ impl SkydimoSerialProtocol {
 
         let mut serial_buf: Vec<u8> = vec![0; 1024];
         match port.read(&mut serial_buf) {
-            Ok(t) if t > 0 => {
-                let response = &serial_buf[..t];
-                let response_str = String::from_utf8_lossy(response);
-
-                // Expected format: "Model,Serial\r\n"
-                if let Some(comma_pos) = response_str.find(',') {
-                    let model = response_str[..comma_pos].to_string();
-
-                    // Extract serial (after comma, before newline)
-                    let after_comma = &response_str[comma_pos + 1..];
-                    let serial_part = after_comma.trim(); // Remove \r\n
-
-                    // Convert serial to hex string to match C++ behavior if needed,
-                    // or just use it as is if it's already readable.
-                    // The C++ code converts the raw bytes of the serial part to hex.
-                    // "std::string serial_raw = response.substr(comma_pos + 1, ...)"
-                    // "oss << hex << (int)ch"
-                    // So we should probably hex encode the serial part bytes.
-
-                    let serial_hex = hex::encode(serial_part);
-
-                    Ok((model, serial_hex.to_uppercase()))
-                } else {
-                    Err("Invalid response format".to_string())
-                }
-            }
+            Ok(t) if t > 0 => Self::parse_handshake(&String::from_utf8_lossy(&serial_buf[..t])),
             Ok(_) => Err("No data received".to_string()),
             Err(e) => Err(e.to_string()),
         }
     }
+
+    /// Parse the reply to "Moni-A": "Model,Serial\r\n", optionally followed by ",caps=<flags>".
+    pub fn parse_handshake(response: &str) -> Result<HandshakeInfo, String> {
+        let response_str = response.trim_end();
+
+        // Newer firmware appends ",caps=<flags>"; older firmware sends only "Model,Serial".
+        let (response_str, caps) = match response_str.rfind(CAPS_PREFIX) {
+            Some(pos) => (&response_str[..pos], &response_str[pos + CAPS_PREFIX.len()..]),
+            None => (response_str, ""),
+        };
+
+        let Some(comma_pos) = response_str.find(',') else {
+            return Err("Invalid response format".to_string());
+        };
+        let model = response_str[..comma_pos].to_string();
+        let serial_part = response_str[comma_pos + 1..].trim();
+
+        // The C++ driver hex-encodes the raw serial bytes
+        // ("oss << hex << (int)ch"), so do the same to keep device ids stable.
+        Ok(HandshakeInfo {
+            model,
+            serial: hex::encode(serial_part).to_uppercase(),
+            supports_ranges: caps.contains('R'),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colors(n: usize) -> Vec<Color> {
+        (0..n)
+            .map(|i| Color {
+                r: i as u8,
+                g: 0x80,
+                b: 0xFF - i as u8,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn full_frame_header_carries_big_endian_count() {
+        let mut buffer = Vec::new();
+        SkydimoSerialProtocol::encode_into(&colors(300), ColorOrder::Rgb, &mut buffer);
+
+        assert_eq!(&buffer[..FRAME_HEADER_LEN], &[0x41, 0x64, 0x61, 0x00, 0x01, 0x2C]);
+        assert_eq!(buffer.len(), SkydimoSerialProtocol::frame_size(300, ColorOrder::Rgb));
+        assert_eq!(&buffer[FRAME_HEADER_LEN..FRAME_HEADER_LEN + 6], &[0, 0x80, 0xFF, 1, 0x80, 0xFE]);
+    }
+
+    #[test]
+    fn rgbw_frames_move_shared_white_into_its_channel() {
+        let mut buffer = Vec::new();
+        SkydimoSerialProtocol::encode_into(&colors(2)[1..], ColorOrder::Grbw, &mut buffer);
+
+        assert_eq!(buffer.len(), SkydimoSerialProtocol::frame_size(1, ColorOrder::Grbw));
+        // (1, 0x80, 0xFE) has 1 of white in it.
+        assert_eq!(&buffer[FRAME_HEADER_LEN..], &[0x7F, 0, 0xFD, 1]);
+    }
+
+    #[test]
+    fn range_packets_carry_start_and_count() {
+        let mut buffer = Vec::new();
+        let ranges = [2..4, 260..261];
+        SkydimoSerialProtocol::encode_ranges_into(&colors(300), &ranges, ColorOrder::Rgb, &mut buffer);
+
+        assert_eq!(&buffer[..RANGE_HEADER_LEN], &[0x41, 0x64, 0x72, 0x00, 0x00, 0x02, 0x00, 0x02]);
+        let second = RANGE_HEADER_LEN + 2 * 3;
+        assert_eq!(
+            &buffer[second..second + RANGE_HEADER_LEN],
+            &[0x41, 0x64, 0x72, 0x00, 0x01, 0x04, 0x00, 0x01]
+        );
+        assert_eq!(buffer.len(), 2 * RANGE_HEADER_LEN + 3 * 3);
+    }
+
+    #[test]
+    fn parses_handshake_with_and_without_caps() {
+        let legacy = SkydimoSerialProtocol::parse_handshake("SK0201,AB\r\n").unwrap();
+        assert_eq!(legacy.model, "SK0201");
+        assert_eq!(legacy.serial, "4142");
+        assert!(!legacy.supports_ranges);
+
+        let ranged = SkydimoSerialProtocol::parse_handshake("SK0201,AB,caps=R\r\n").unwrap();
+        assert_eq!(ranged.serial, "4142");
+        assert!(ranged.supports_ranges);
+
+        assert!(SkydimoSerialProtocol::parse_handshake("garbage").is_err());
+    }
 }
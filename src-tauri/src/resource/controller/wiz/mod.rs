@@ -0,0 +1,334 @@
+//! WiZ smart bulbs over their local UDP JSON-RPC API (port 38899).
+//!
+//! Bulbs answer a broadcast `getPilot`, but plenty of routers drop broadcasts on Wi-Fi, so
+//! every bulb found is remembered and asked directly on the next scan as well.
+
+use crate::interface::controller::{
+    Color, Controller, ControllerMetadata, DeviceType, OutputCapabilities, OutputPortDefinition,
+    ProbeSpecificity, SegmentType,
+};
+use crate::resource::driver::net_bind::{bind_udp_for, discovery_timeout};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{BTreeSet, HashMap};
+use std::io::ErrorKind;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+const WIZ_PORT: u16 = 38899;
+
+/// Bulbs start dropping or lagging behind commands above ~10-20 Hz; the runner keeps
+/// updates to this rate.
+const MAX_UPDATE_HZ: f32 = 10.0;
+
+/// The API rejects dimming below 10%.
+const MIN_DIMMING: u8 = 10;
+
+const OUTPUT_ID: &str = "bulb";
+
+/// Bulb addresses found by earlier scans, persisted in the app config.
+static KNOWN_BULBS: RwLock<BTreeSet<Ipv4Addr>> = RwLock::new(BTreeSet::new());
+
+pub fn known_bulbs() -> Vec<String> {
+    KNOWN_BULBS
+        .read()
+        .map(|bulbs| bulbs.iter().map(Ipv4Addr::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Replace the remembered bulbs; entries that aren't IPv4 addresses are ignored.
+pub fn set_known_bulbs(ips: &[String]) {
+    let parsed = ips.iter().filter_map(|ip| ip.trim().parse().ok()).collect();
+    if let Ok(mut bulbs) = KNOWN_BULBS.write() {
+        *bulbs = parsed;
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PilotReply {
+    result: PilotResult,
+}
+
+#[derive(Debug, Deserialize)]
+struct PilotResult {
+    mac: String,
+}
+
+fn encode_get_pilot() -> Vec<u8> {
+    json!({ "method": "getPilot", "params": {} })
+        .to_string()
+        .into_bytes()
+}
+
+/// `setPilot` for a color, or `state: false` when `color` is `None`.
+fn encode_set_pilot(color: Option<(Color, u8)>) -> Vec<u8> {
+    let params = match color {
+        Some((color, dimming)) => json!({
+            "r": color.r,
+            "g": color.g,
+            "b": color.b,
+            "dimming": dimming.clamp(MIN_DIMMING, 100),
+        }),
+        None => json!({ "state": false }),
+    };
+    json!({ "method": "setPilot", "params": params })
+        .to_string()
+        .into_bytes()
+}
+
+/// Reduce a frame to the color covering most of it, as a full-scale color plus dimming
+/// percentage. `None` means the frame is black and the bulb should turn off.
+///
+/// LEDs are binned by coarse color and weighted by brightness, so a mostly dark frame with
+/// one bright area picks that area rather than a muddy average of everything.
+fn dominant_color(colors: &[Color]) -> Option<(Color, u8)> {
+    // Per bin: weight, and weighted channel sums.
+    let mut bins: HashMap<(u8, u8, u8), (u64, [u64; 3])> = HashMap::new();
+    for c in colors {
+        let weight = c.r.max(c.g).max(c.b) as u64;
+        if weight == 0 {
+            continue;
+        }
+        let bin = bins
+            .entry((c.r >> 5, c.g >> 5, c.b >> 5))
+            .or_insert((0, [0; 3]));
+        bin.0 += weight;
+        bin.1[0] += c.r as u64 * weight;
+        bin.1[1] += c.g as u64 * weight;
+        bin.1[2] += c.b as u64 * weight;
+    }
+
+    let (weight, sums) = bins
+        .into_values()
+        .max_by_key(|(weight, _)| *weight)?;
+    let [r, g, b] = sums.map(|sum| sum / weight);
+
+    let peak = r.max(g).max(b).max(1);
+    let scale = |v: u64| ((v * 255 + peak / 2) / peak) as u8;
+    let dimming = ((peak * 100 + 127) / 255) as u8;
+    Some((
+        Color {
+            r: scale(r),
+            g: scale(g),
+            b: scale(b),
+        },
+        dimming.max(MIN_DIMMING),
+    ))
+}
+
+pub struct WizController {
+    mac: String,
+    addr: SocketAddr,
+    socket: UdpSocket,
+    /// Last sent pilot, `Some(None)` meaning off.
+    sent: Option<Option<(Color, u8)>>,
+}
+
+impl WizController {
+    fn new(ip: Ipv4Addr, mac: String) -> Result<Self, String> {
+        let socket =
+            bind_udp_for(IpAddr::V4(ip)).map_err(|e| format!("Failed to bind socket: {}", e))?;
+        Ok(Self {
+            mac,
+            addr: SocketAddr::V4(SocketAddrV4::new(ip, WIZ_PORT)),
+            socket,
+            sent: None,
+        })
+    }
+
+    fn set_pilot(&mut self, pilot: Option<(Color, u8)>) -> Result<(), String> {
+        let unchanged = self.sent.is_some_and(|sent| match (sent, pilot) {
+            (Some((a, da)), Some((b, db))) => (a.r, a.g, a.b, da) == (b.r, b.g, b.b, db),
+            (None, None) => true,
+            _ => false,
+        });
+        if unchanged {
+            return Ok(());
+        }
+
+        self.socket
+            .send_to(&encode_set_pilot(pilot), self.addr)
+            .map_err(|e| format!("Failed to send WiZ command: {}", e))?;
+        self.sent = Some(pilot);
+        Ok(())
+    }
+}
+
+impl Controller for WizController {
+    fn port_name(&self) -> String {
+        self.addr.ip().to_string()
+    }
+
+    fn model(&self) -> String {
+        "WiZ Bulb".to_string()
+    }
+
+    fn description(&self) -> String {
+        "WiZ smart bulb (local UDP)".to_string()
+    }
+
+    fn serial_id(&self) -> String {
+        self.mac.clone()
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::Light
+    }
+
+    fn outputs(&self) -> Vec<OutputPortDefinition> {
+        vec![OutputPortDefinition {
+            id: OUTPUT_ID.to_string(),
+            name: "Bulb".to_string(),
+            output_type: SegmentType::Single,
+            leds_count: 1,
+            matrix: None,
+            capabilities: OutputCapabilities {
+                editable: false,
+                min_total_leds: 1,
+                max_total_leds: 1,
+                allowed_total_leds: Some(vec![1]),
+                allowed_segment_types: vec![SegmentType::Single],
+            },
+        }]
+    }
+
+    fn update(&mut self, colors: &[Color]) -> Result<(), String> {
+        self.set_pilot(dominant_color(colors))
+    }
+
+    fn clear(&mut self) -> Result<(), String> {
+        self.set_pilot(None)
+    }
+
+    fn max_update_hz(&self) -> Option<f32> {
+        Some(MAX_UPDATE_HZ)
+    }
+}
+
+/// Ask every bulb on the broadcast address plus the remembered ones, keyed by MAC.
+fn discover_bulbs(timeout: Duration) -> HashMap<String, Ipv4Addr> {
+    let mut found = HashMap::new();
+
+    let socket = match bind_udp_for(IpAddr::V4(Ipv4Addr::BROADCAST)) {
+        Ok(socket) => socket,
+        Err(e) => {
+            log::warn!(err:display = e; "Failed to bind WiZ discovery socket");
+            return found;
+        }
+    };
+    if let Err(e) = socket
+        .set_broadcast(true)
+        .and_then(|_| socket.set_read_timeout(Some(Duration::from_millis(100))))
+    {
+        log::warn!(err:display = e; "Failed to configure WiZ discovery socket");
+        return found;
+    }
+
+    let request = encode_get_pilot();
+    let known: Vec<Ipv4Addr> = KNOWN_BULBS
+        .read()
+        .map(|bulbs| bulbs.iter().copied().collect())
+        .unwrap_or_default();
+    for ip in std::iter::once(Ipv4Addr::BROADCAST).chain(known) {
+        if let Err(e) = socket.send_to(&request, SocketAddrV4::new(ip, WIZ_PORT)) {
+            log::warn!(ip = ip.to_string().as_str(), err:display = e; "Failed to send WiZ getPilot");
+        }
+    }
+
+    let mut buf = [0u8; 1024];
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        match socket.recv_from(&mut buf) {
+            Ok((len, SocketAddr::V4(from))) => {
+                let Ok(reply) = serde_json::from_slice::<PilotReply>(&buf[..len]) else {
+                    continue;
+                };
+                log::info!(mac = reply.result.mac.as_str(), ip = from.ip().to_string().as_str(); "Discovered WiZ bulb");
+                found.insert(reply.result.mac, *from.ip());
+            }
+            Ok(_) => continue,
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+                continue;
+            }
+            Err(e) => {
+                log::warn!(err:display = e; "WiZ discovery receive failed");
+                break;
+            }
+        }
+    }
+
+    found
+}
+
+fn probe() -> Vec<Box<dyn Controller>> {
+    let mut controllers: Vec<Box<dyn Controller>> = Vec::new();
+
+    log::info!("Scanning for WiZ bulbs...");
+    let bulbs = discover_bulbs(discovery_timeout());
+
+    // Bulbs that didn't answer stay remembered; they may just be switched off at the wall.
+    if let Ok(mut known) = KNOWN_BULBS.write() {
+        known.extend(bulbs.values().copied());
+    }
+
+    for (mac, ip) in bulbs {
+        match WizController::new(ip, mac) {
+            Ok(controller) => controllers.push(Box::new(controller)),
+            Err(e) => {
+                log::warn!(ip = ip.to_string().as_str(), err:display = e; "Failed to create WiZ controller");
+            }
+        }
+    }
+
+    controllers
+}
+
+inventory::submit!(ControllerMetadata {
+    name: "WiZ Controller",
+    description: "WiZ smart bulbs via local UDP (broadcast discovery)",
+    priority: 0,
+    specificity: ProbeSpecificity::Verified,
+    probe,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b }
+    }
+
+    #[test]
+    fn set_pilot_payload() {
+        let payload: Value =
+            serde_json::from_slice(&encode_set_pilot(Some((rgb(255, 40, 0), 5)))).unwrap();
+        assert_eq!(
+            payload,
+            json!({
+                "method": "setPilot",
+                "params": { "r": 255, "g": 40, "b": 0, "dimming": MIN_DIMMING },
+            })
+        );
+
+        let off: Value = serde_json::from_slice(&encode_set_pilot(None)).unwrap();
+        assert_eq!(off, json!({ "method": "setPilot", "params": { "state": false } }));
+    }
+
+    #[test]
+    fn dominant_color_picks_the_largest_bright_area() {
+        // Six dim-ish red LEDs outweigh three blue ones; black LEDs don't count.
+        let mut frame = vec![rgb(128, 0, 0); 6];
+        frame.extend([rgb(0, 0, 200); 3]);
+        frame.extend([rgb(0, 0, 0); 20]);
+
+        let (color, dimming) = dominant_color(&frame).unwrap();
+        assert_eq!((color.r, color.g, color.b), (255, 0, 0));
+        assert_eq!(dimming, 50);
+
+        assert!(dominant_color(&[rgb(0, 0, 0); 8]).is_none());
+        assert!(dominant_color(&[]).is_none());
+    }
+}
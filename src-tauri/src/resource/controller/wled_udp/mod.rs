@@ -0,0 +1,273 @@
+//! WLED nodes, found over mDNS and driven with DDP on UDP port 4048.
+//!
+//! Each node is asked for its LED count and segments over the JSON API; every segment shows
+//! up as a `Linear` output. Nodes that don't answer the HTTP query in time are skipped. WLED
+//! enters realtime mode on the first DDP packet and goes back to its own effects a few
+//! seconds after the last one.
+
+use crate::interface::controller::{
+    Color, ColorOrder, Controller, ControllerMetadata, DeviceType, OutputCapabilities, OutputPortDefinition,
+    PowerBudget, ProbeSpecificity, SegmentType,
+};
+use crate::resource::driver::net_bind::{bind_udp_for, discovery_timeout, source_address};
+use mdns_sd::{IfKind, ScopedIp, ServiceDaemon, ServiceEvent};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+mod protocol;
+use protocol::{Info, SegmentRange, State, DDP_PORT};
+
+const SERVICE_TYPE: &str = "_wled._tcp.local.";
+
+/// Per request; a node that hasn't answered by then is treated as offline.
+const HTTP_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// WLED's default per-LED current for its own limiter.
+const MA_PER_LED_FULL_WHITE: f32 = 55.0;
+
+pub struct WledUdpController {
+    name: String,
+    version: String,
+    mac: String,
+    addr: SocketAddr,
+    socket: UdpSocket,
+    segments: Vec<SegmentRange>,
+    /// `Rgb` or `Rgbw`: DDP carries one of the two, and the node handles its strip's byte
+    /// order itself.
+    color_order: ColorOrder,
+    /// The node's configured supply limit, in mA.
+    max_milliamps: Option<u32>,
+    sequence: u8,
+    /// The node's whole LED buffer, which DDP offsets index into. LEDs outside any segment
+    /// stay black.
+    frame: Vec<Color>,
+}
+
+impl WledUdpController {
+    fn new(ip: IpAddr, info: Info, state: State) -> Result<Self, String> {
+        let segments = protocol::segment_ranges(&info, &state);
+        if segments.is_empty() {
+            return Err("Node reports no LEDs".to_string());
+        }
+        let socket = bind_udp_for(ip).map_err(|e| format!("Failed to bind socket: {}", e))?;
+
+        Ok(Self {
+            name: info.name,
+            version: info.ver,
+            mac: info.mac,
+            addr: SocketAddr::new(ip, DDP_PORT),
+            socket,
+            segments,
+            color_order: if info.leds.rgbw { ColorOrder::Rgbw } else { ColorOrder::Rgb },
+            max_milliamps: (info.leds.maxpwr > 0).then_some(info.leds.maxpwr),
+            sequence: 0,
+            frame: vec![Color::default(); info.leds.count],
+        })
+    }
+
+    fn send_frame(&mut self) -> Result<(), String> {
+        self.sequence = protocol::next_sequence(self.sequence);
+        for packet in protocol::encode_frame(&self.frame, self.color_order, self.sequence) {
+            self.socket
+                .send_to(&packet, self.addr)
+                .map_err(|e| format!("Failed to send DDP packet: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+impl Controller for WledUdpController {
+    fn port_name(&self) -> String {
+        self.addr.ip().to_string()
+    }
+
+    fn model(&self) -> String {
+        if self.name.is_empty() {
+            "WLED".to_string()
+        } else {
+            format!("WLED {}", self.name)
+        }
+    }
+
+    fn description(&self) -> String {
+        format!("WLED {} node (DDP)", self.version)
+    }
+
+    fn serial_id(&self) -> String {
+        self.mac.clone()
+    }
+
+    fn device_type(&self) -> DeviceType {
+        DeviceType::LedStrip
+    }
+
+    fn outputs(&self) -> Vec<OutputPortDefinition> {
+        self.segments
+            .iter()
+            .map(|seg| OutputPortDefinition {
+                id: format!("seg{}", seg.id),
+                name: seg.name.clone(),
+                output_type: SegmentType::Linear,
+                leds_count: seg.len,
+                matrix: None,
+                // Segment lengths are configured on the node itself.
+                capabilities: OutputCapabilities {
+                    editable: false,
+                    min_total_leds: seg.len,
+                    max_total_leds: seg.len,
+                    allowed_total_leds: Some(vec![seg.len]),
+                    allowed_segment_types: vec![SegmentType::Linear],
+                },
+            })
+            .collect()
+    }
+
+    fn color_order(&self) -> ColorOrder {
+        self.color_order
+    }
+
+    fn set_color_order(&mut self, order: ColorOrder) -> Result<(), String> {
+        match order {
+            ColorOrder::Rgb | ColorOrder::Rgbw => {
+                self.color_order = order;
+                Ok(())
+            }
+            _ => Err(
+                "WLED takes RGB or RGBW; set the strip's wiring in the node's LED preferences"
+                    .to_string(),
+            ),
+        }
+    }
+
+    fn update(&mut self, colors: &[Color]) -> Result<(), String> {
+        let mut offset = 0;
+        for seg in &self.segments {
+            let end = (offset + seg.len).min(colors.len());
+            let src = &colors[offset.min(end)..end];
+            self.frame[seg.start..seg.start + src.len()].copy_from_slice(src);
+            offset += seg.len;
+        }
+        self.send_frame()
+    }
+
+    fn clear(&mut self) -> Result<(), String> {
+        self.frame.fill(Color::default());
+        self.send_frame()
+    }
+
+    fn power_budget(&self) -> Option<PowerBudget> {
+        self.max_milliamps.map(|max_milliamps| PowerBudget {
+            max_milliamps,
+            ma_per_led_full_white: MA_PER_LED_FULL_WHITE,
+        })
+    }
+}
+
+/// GET a JSON API path from the node, `None` on timeout or a malformed reply.
+fn fetch_json<T: serde::de::DeserializeOwned>(ip: IpAddr, path: &str) -> Option<T> {
+    let url = format!("http://{}{}", SocketAddr::new(ip, 80), path);
+    let body = match ureq::get(&url).timeout(HTTP_TIMEOUT).call() {
+        Ok(response) => response.into_string(),
+        Err(e) => {
+            log::warn!(url = url.as_str(), err:display = e; "WLED node did not answer");
+            return None;
+        }
+    };
+    match body.map_err(|e| e.to_string()).and_then(|body| {
+        serde_json::from_str(&body).map_err(|e| e.to_string())
+    }) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            log::warn!(url = url.as_str(), err:display = e; "Invalid WLED JSON reply");
+            None
+        }
+    }
+}
+
+/// Browse for `_wled._tcp` until `timeout`, returning one IPv4 address per node. WLED
+/// doesn't listen on IPv6.
+fn discover_nodes(timeout: Duration) -> Vec<IpAddr> {
+    let mdns = match ServiceDaemon::new() {
+        Ok(d) => d,
+        Err(e) => {
+            log::error!(err:display = e; "Failed to create mDNS daemon");
+            return Vec::new();
+        }
+    };
+
+    if let Some(source) = source_address() {
+        if let Err(e) = mdns
+            .disable_interface(IfKind::All)
+            .and_then(|_| mdns.enable_interface(IfKind::Addr(source)))
+        {
+            log::warn!(source = source.to_string().as_str(), err:display = e; "Failed to restrict mDNS to the source interface");
+        }
+    }
+
+    let receiver = match mdns.browse(SERVICE_TYPE) {
+        Ok(r) => r,
+        Err(e) => {
+            log::error!(err:display = e; "Failed to browse mDNS services");
+            return Vec::new();
+        }
+    };
+
+    let mut nodes: HashMap<String, IpAddr> = HashMap::new();
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        match receiver.recv_timeout(Duration::from_millis(100)) {
+            Ok(ServiceEvent::ServiceResolved(info)) => {
+                let Some(ip) = info.get_addresses().iter().find_map(|ip| match ip {
+                    ScopedIp::V4(v4) => Some(IpAddr::V4(*v4.addr())),
+                    _ => None,
+                }) else {
+                    continue;
+                };
+                log::info!(
+                    name = info.get_fullname(),
+                    ip = ip.to_string().as_str();
+                    "Discovered WLED node via mDNS"
+                );
+                nodes.insert(info.get_fullname().to_string(), ip);
+            }
+            Ok(ServiceEvent::ServiceRemoved(_, name)) => {
+                nodes.remove(&name);
+            }
+            Ok(_) => {}
+            Err(_) => continue,
+        }
+    }
+
+    let _ = mdns.shutdown();
+    nodes.into_values().collect()
+}
+
+fn probe() -> Vec<Box<dyn Controller>> {
+    let mut controllers: Vec<Box<dyn Controller>> = Vec::new();
+
+    log::info!("Scanning for WLED nodes via mDNS...");
+    for ip in discover_nodes(discovery_timeout()) {
+        let Some(info) = fetch_json::<Info>(ip, "/json/info") else {
+            continue;
+        };
+        // Without segments the whole strip is still usable as one output.
+        let state = fetch_json::<State>(ip, "/json/state").unwrap_or_default();
+        match WledUdpController::new(ip, info, state) {
+            Ok(controller) => controllers.push(Box::new(controller)),
+            Err(e) => {
+                log::warn!(ip = ip.to_string().as_str(), err:display = e; "Failed to create WLED controller");
+            }
+        }
+    }
+
+    controllers
+}
+
+inventory::submit!(ControllerMetadata {
+    name: "WLED UDP Controller",
+    description: "WLED nodes via DDP (mDNS discovery)",
+    priority: 0,
+    specificity: ProbeSpecificity::Verified,
+    probe,
+});
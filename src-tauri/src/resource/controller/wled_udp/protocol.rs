@@ -0,0 +1,228 @@
+//! WLED JSON API replies and DDP (Distributed Display Protocol) packets.
+//!
+//! A DDP packet is a 10-byte header followed by RGB or RGBW data:
+//!
+//! ```text
+//! flags: u8  sequence: u8  data type: u8  destination: u8  offset: u32 BE  length: u16 BE
+//! ```
+//!
+//! `offset` is the byte offset of the data into the node's whole LED buffer. A frame is split
+//! into packets sharing one sequence number; only the last sets PUSH, which makes the node
+//! show what it received.
+
+use serde::Deserialize;
+
+use crate::interface::controller::{Color, ColorOrder};
+
+pub const DDP_PORT: u16 = 4048;
+
+const HEADER_LEN: usize = 10;
+const VERSION_1: u8 = 0x40;
+const FLAG_PUSH: u8 = 0x01;
+/// RGB, 8 bits per channel.
+const DATA_TYPE_RGB24: u8 = 0x0B;
+/// RGBW, 8 bits per channel.
+const DATA_TYPE_RGBW32: u8 = 0x1B;
+/// The node's default output.
+const DESTINATION_DEFAULT: u8 = 0x01;
+
+/// 480 RGB or 360 RGBW LEDs; keeps packets under a 1500-byte MTU.
+pub const MAX_DATA_LEN: usize = 1440;
+
+/// `/json/info`, the fields that are used.
+#[derive(Debug, Deserialize)]
+pub struct Info {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub ver: String,
+    /// Lowercase hex without separators, e.g. `a4cf12fdaea8`.
+    pub mac: String,
+    pub leds: LedsInfo,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LedsInfo {
+    pub count: usize,
+    /// Present on 2D setups.
+    #[serde(default)]
+    pub matrix: Option<serde_json::Value>,
+    /// Whether the strip has a white channel.
+    #[serde(default)]
+    pub rgbw: bool,
+    /// Supply limit set in the node's LED preferences, in mA; 0 when it's turned off.
+    #[serde(default)]
+    pub maxpwr: u32,
+}
+
+/// `/json/state`, the fields that are used.
+#[derive(Debug, Default, Deserialize)]
+pub struct State {
+    #[serde(default)]
+    pub seg: Vec<Segment>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Segment {
+    #[serde(default)]
+    pub id: usize,
+    pub start: usize,
+    /// Exclusive.
+    pub stop: usize,
+    #[serde(default)]
+    pub n: Option<String>,
+}
+
+/// A range of the node's LED buffer shown as one output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SegmentRange {
+    pub id: usize,
+    pub name: String,
+    pub start: usize,
+    pub len: usize,
+}
+
+/// Turn the node's segments into non-overlapping ranges in buffer order. Segments that
+/// overlap an earlier one or fall outside `count` are left out; with none left, or on 2D
+/// setups where start/stop only cover one row, the whole buffer is one range.
+pub fn segment_ranges(info: &Info, state: &State) -> Vec<SegmentRange> {
+    let count = info.leds.count;
+    let mut segments: Vec<&Segment> = if info.leds.matrix.is_some() {
+        Vec::new()
+    } else {
+        state.seg.iter().collect()
+    };
+    segments.sort_by_key(|seg| seg.start);
+
+    let mut ranges: Vec<SegmentRange> = Vec::new();
+    let mut covered = 0;
+    for seg in segments {
+        let stop = seg.stop.min(count);
+        if seg.start < covered || seg.start >= stop {
+            continue;
+        }
+        ranges.push(SegmentRange {
+            id: seg.id,
+            name: seg
+                .n
+                .clone()
+                .filter(|n| !n.is_empty())
+                .unwrap_or_else(|| format!("Segment {}", seg.id)),
+            start: seg.start,
+            len: stop - seg.start,
+        });
+        covered = stop;
+    }
+
+    if ranges.is_empty() && count > 0 {
+        ranges.push(SegmentRange {
+            id: 0,
+            name: "Strip".to_string(),
+            start: 0,
+            len: count,
+        });
+    }
+    ranges
+}
+
+/// Next DDP sequence number; 0 means "unused", so it cycles through 1..=15.
+pub fn next_sequence(sequence: u8) -> u8 {
+    sequence % 15 + 1
+}
+
+/// Encode one frame for the whole LED buffer as DDP packets of at most `MAX_DATA_LEN`
+/// data bytes each. `order` is `Rgb` or `Rgbw`; the node maps those to its strip's own
+/// wiring.
+pub fn encode_frame(colors: &[Color], order: ColorOrder, sequence: u8) -> Vec<Vec<u8>> {
+    let data_type = if order.channels() == 4 { DATA_TYPE_RGBW32 } else { DATA_TYPE_RGB24 };
+    let mut data = Vec::new();
+    order.extend(colors, &mut data);
+    let chunks: Vec<&[u8]> = if data.is_empty() {
+        vec![&[]]
+    } else {
+        data.chunks(MAX_DATA_LEN).collect()
+    };
+    let last = chunks.len() - 1;
+
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let offset = (i * MAX_DATA_LEN) as u32;
+            let mut packet = Vec::with_capacity(HEADER_LEN + chunk.len());
+            packet.push(if i == last { VERSION_1 | FLAG_PUSH } else { VERSION_1 });
+            packet.push(sequence & 0x0F);
+            packet.push(data_type);
+            packet.push(DESTINATION_DEFAULT);
+            packet.extend_from_slice(&offset.to_be_bytes());
+            packet.extend_from_slice(&(chunk.len() as u16).to_be_bytes());
+            packet.extend_from_slice(chunk);
+            packet
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_strips_are_split_with_push_on_the_last_packet() {
+        let colors: Vec<Color> = (0..1000)
+            .map(|i| Color { r: (i % 256) as u8, g: 1, b: 2 })
+            .collect();
+        let packets = encode_frame(&colors, ColorOrder::Rgb, 7);
+        assert_eq!(packets.len(), 3);
+
+        assert_eq!(&packets[0][..HEADER_LEN], &[0x40, 7, 0x0B, 1, 0, 0, 0, 0, 0x05, 0xA0]);
+        assert_eq!(&packets[1][4..HEADER_LEN], &[0, 0, 0x05, 0xA0, 0x05, 0xA0]);
+        // 40 LEDs left, with PUSH.
+        assert_eq!(&packets[2][..HEADER_LEN], &[0x41, 7, 0x0B, 1, 0, 0, 0x0B, 0x40, 0, 120]);
+        // LED 960 starts the last packet.
+        assert_eq!(&packets[2][HEADER_LEN..HEADER_LEN + 3], &[192, 1, 2]);
+
+        let rgbw = encode_frame(&[Color { r: 200, g: 50, b: 60 }; 400], ColorOrder::Rgbw, 1);
+        assert_eq!(rgbw.len(), 2);
+        assert_eq!(&rgbw[0][..HEADER_LEN], &[0x40, 1, 0x1B, 1, 0, 0, 0, 0, 0x05, 0xA0]);
+        assert_eq!(&rgbw[0][HEADER_LEN..HEADER_LEN + 4], &[150, 0, 10, 50]);
+        // 40 LEDs of 4 bytes, starting at LED 360.
+        assert_eq!(&rgbw[1][4..HEADER_LEN], &[0, 0, 0x05, 0xA0, 0, 160]);
+
+        assert_eq!(next_sequence(15), 1);
+        assert_eq!(next_sequence(0), 1);
+    }
+
+    #[test]
+    fn segments_map_to_ranges_of_the_buffer() {
+        let info: Info = serde_json::from_str(
+            r#"{"name":"Desk","ver":"0.14.0","mac":"a4cf12fdaea8","leds":{"count":150,"fps":42}}"#,
+        )
+        .unwrap();
+        let state: State = serde_json::from_str(
+            r#"{"on":true,"seg":[
+                {"id":1,"start":90,"stop":200,"n":"Shelf"},
+                {"id":0,"start":0,"stop":60},
+                {"id":2,"start":30,"stop":90}
+            ]}"#,
+        )
+        .unwrap();
+        let ranges = segment_ranges(&info, &state);
+        assert_eq!(
+            ranges,
+            vec![
+                SegmentRange { id: 0, name: "Segment 0".to_string(), start: 0, len: 60 },
+                SegmentRange { id: 1, name: "Shelf".to_string(), start: 90, len: 60 },
+            ]
+        );
+
+        let whole = segment_ranges(&info, &State::default());
+        assert_eq!(whole, vec![SegmentRange { id: 0, name: "Strip".to_string(), start: 0, len: 150 }]);
+        assert_eq!(info.leds.maxpwr, 0);
+
+        let limited: Info = serde_json::from_str(
+            r#"{"mac":"a4cf12fdaea8","leds":{"count":150,"maxpwr":850}}"#,
+        )
+        .unwrap();
+        assert_eq!(limited.leds.maxpwr, 850);
+    }
+}
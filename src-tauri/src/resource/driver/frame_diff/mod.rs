@@ -0,0 +1,223 @@
+//! Dirty-range tracking for partial LED frame updates.
+//!
+//! Controllers whose wire protocol can address a sub-range of LEDs keep a `FrameDiff`
+//! next to their transport. Each frame is compared with the last frame that actually
+//! reached the device and only the changed ranges are sent. Full frames are still sent
+//! when most of the strip changed, and periodically as keyframes so a lost packet cannot
+//! leave stale pixels behind forever.
+
+use crate::interface::controller::Color;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Global switch so users can fall back to full frames if a firmware misbehaves.
+static PARTIAL_UPDATES_ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub fn set_partial_updates_enabled(enabled: bool) {
+    PARTIAL_UPDATES_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn partial_updates_enabled() -> bool {
+    PARTIAL_UPDATES_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Tuning knobs, expressed in terms of the protocol's own framing costs.
+#[derive(Clone, Copy, Debug)]
+pub struct FrameDiffConfig {
+    /// Unchanged LEDs between two dirty ranges are sent anyway when re-sending them is
+    /// cheaper than opening a new range (i.e. `gap * bytes_per_led <= range_header_bytes`).
+    pub max_merge_gap: usize,
+    /// Send a full frame once this fraction of LEDs (by coalesced range length) changed.
+    pub full_frame_ratio: f32,
+    /// Force a full frame after this many partial/skipped frames. 0 disables keyframes.
+    pub keyframe_interval: u32,
+}
+
+impl FrameDiffConfig {
+    pub fn for_protocol(range_header_bytes: usize, bytes_per_led: usize, keyframe_interval: u32) -> Self {
+        Self {
+            max_merge_gap: range_header_bytes / bytes_per_led.max(1),
+            full_frame_ratio: 0.5,
+            keyframe_interval,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FramePlan {
+    /// Nothing changed since the last delivered frame.
+    Unchanged,
+    Full,
+    /// Coalesced, sorted, non-overlapping LED ranges that need to be re-sent.
+    Partial(Vec<Range<usize>>),
+}
+
+pub struct FrameDiff {
+    config: FrameDiffConfig,
+    last_sent: Vec<Color>,
+    frames_since_full: u32,
+}
+
+impl FrameDiff {
+    pub fn new(config: FrameDiffConfig) -> Self {
+        Self {
+            config,
+            last_sent: Vec::new(),
+            frames_since_full: 0,
+        }
+    }
+
+    /// Decide what to send for `next` without changing any state.
+    pub fn plan(&self, next: &[Color]) -> FramePlan {
+        if !partial_updates_enabled() || self.last_sent.len() != next.len() {
+            return FramePlan::Full;
+        }
+        if self.config.keyframe_interval > 0 && self.frames_since_full >= self.config.keyframe_interval {
+            return FramePlan::Full;
+        }
+
+        let ranges = changed_ranges(&self.last_sent, next, self.config.max_merge_gap);
+        if ranges.is_empty() {
+            return FramePlan::Unchanged;
+        }
+
+        let dirty: usize = ranges.iter().map(|r| r.len()).sum();
+        if dirty as f32 > next.len() as f32 * self.config.full_frame_ratio {
+            FramePlan::Full
+        } else {
+            FramePlan::Partial(ranges)
+        }
+    }
+
+    /// Record that `plan` for `frame` reached the device. Frames dropped by the transport
+    /// (e.g. rate limiting) must not be committed, otherwise their changes would be lost.
+    pub fn commit(&mut self, frame: &[Color], plan: &FramePlan) {
+        match plan {
+            FramePlan::Full => {
+                self.last_sent.clear();
+                self.last_sent.extend_from_slice(frame);
+                self.frames_since_full = 0;
+            }
+            FramePlan::Partial(ranges) => {
+                for range in ranges {
+                    self.last_sent[range.clone()].copy_from_slice(&frame[range.clone()]);
+                }
+                self.frames_since_full += 1;
+            }
+            FramePlan::Unchanged => {
+                self.frames_since_full += 1;
+            }
+        }
+    }
+
+    /// Forget the device state so the next frame is sent in full (e.g. after reconnect).
+    pub fn reset(&mut self) {
+        self.last_sent.clear();
+        self.frames_since_full = 0;
+    }
+}
+
+fn same(a: Color, b: Color) -> bool {
+    a.r == b.r && a.g == b.g && a.b == b.b
+}
+
+/// Changed index ranges between `prev` and `next`, merging ranges separated by at most
+/// `max_gap` unchanged LEDs. Both slices must have the same length.
+pub fn changed_ranges(prev: &[Color], next: &[Color], max_gap: usize) -> Vec<Range<usize>> {
+    debug_assert_eq!(prev.len(), next.len());
+
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut i = 0;
+    let len = prev.len().min(next.len());
+
+    while i < len {
+        if same(prev[i], next[i]) {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < len && !same(prev[i], next[i]) {
+            i += 1;
+        }
+
+        match ranges.last_mut() {
+            Some(last) if start - last.end <= max_gap => last.end = i,
+            _ => ranges.push(start..i),
+        }
+    }
+
+    ranges
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+
+    const OFF: Color = Color { r: 0, g: 0, b: 0 };
+    const ON: Color = Color { r: 255, g: 0, b: 0 };
+
+    fn frame(lit: &[usize], len: usize) -> Vec<Color> {
+        let mut f = vec![OFF; len];
+        for &i in lit {
+            f[i] = ON;
+        }
+        f
+    }
+
+    #[test]
+    fn coalesces_ranges_within_gap() {
+        let prev = frame(&[], 20);
+        let next = frame(&[2, 3, 5, 12], 20);
+
+        assert_eq!(changed_ranges(&prev, &next, 0), vec![2..4, 5..6, 12..13]);
+        assert_eq!(changed_ranges(&prev, &next, 1), vec![2..6, 12..13]);
+        assert_eq!(changed_ranges(&prev, &next, 6), vec![2..13]);
+    }
+
+    #[test]
+    fn unchanged_frame_produces_no_ranges() {
+        let f = frame(&[1, 2], 10);
+        assert!(changed_ranges(&f, &f, 3).is_empty());
+    }
+
+    #[test]
+    fn plans_full_frame_when_most_leds_changed() {
+        let mut diff = FrameDiff::new(FrameDiffConfig::for_protocol(8, 3, 0));
+        let first = frame(&[], 10);
+        assert_eq!(diff.plan(&first), FramePlan::Full);
+        diff.commit(&first, &FramePlan::Full);
+
+        let small = frame(&[4], 10);
+        assert_eq!(diff.plan(&small), FramePlan::Partial(vec![4..5]));
+
+        let large = frame(&[0, 1, 2, 3, 4, 5], 10);
+        assert_eq!(diff.plan(&large), FramePlan::Full);
+    }
+
+    #[test]
+    fn uncommitted_frames_are_resent() {
+        let mut diff = FrameDiff::new(FrameDiffConfig::for_protocol(8, 3, 0));
+        let base = frame(&[], 10);
+        diff.commit(&base, &FramePlan::Full);
+
+        // First attempt dropped by the transport: nothing committed.
+        let next = frame(&[7], 10);
+        assert_eq!(diff.plan(&next), FramePlan::Partial(vec![7..8]));
+
+        let plan = diff.plan(&next);
+        diff.commit(&next, &plan);
+        assert_eq!(diff.plan(&next), FramePlan::Unchanged);
+    }
+
+    #[test]
+    fn forces_keyframe_after_interval() {
+        let mut diff = FrameDiff::new(FrameDiffConfig::for_protocol(8, 3, 2));
+        let f = frame(&[], 10);
+        diff.commit(&f, &FramePlan::Full);
+        diff.commit(&f, &FramePlan::Unchanged);
+        diff.commit(&f, &FramePlan::Unchanged);
+        assert_eq!(diff.plan(&f), FramePlan::Full);
+    }
+}
@@ -1 +1,4 @@
+pub mod frame_diff;
+pub mod net_bind;
 pub mod serail_port;
+pub mod write_retry;
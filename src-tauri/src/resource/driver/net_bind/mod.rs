@@ -0,0 +1,140 @@
+//! Local address selection for the network controllers.
+//!
+//! A socket bound to `0.0.0.0:0` leaves the choice of adapter to the routing table, which on
+//! machines with VPNs or virtual adapters regularly picks one the lights aren't reachable
+//! from. When a source address is configured, sockets for targets of the same IP family are
+//! bound to it and multicast leaves through the adapter that owns it. Targets of the other
+//! family still get the unspecified address, so an IPv4 source doesn't cut off IPv6 devices.
+//!
+//! Controllers bind when they are created, so a change applies from the next scan on. The
+//! time a scan waits for replies is shared by the network drivers too.
+
+use socket2::SockRef;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// How long network drivers wait for discovery replies during a scan, in ms.
+pub const DEFAULT_DISCOVERY_TIMEOUT_MS: u32 = 3000;
+const MIN_DISCOVERY_TIMEOUT_MS: u32 = 500;
+const MAX_DISCOVERY_TIMEOUT_MS: u32 = 15_000;
+
+static SOURCE_ADDRESS: RwLock<Option<IpAddr>> = RwLock::new(None);
+static DISCOVERY_TIMEOUT_MS: AtomicU32 = AtomicU32::new(DEFAULT_DISCOVERY_TIMEOUT_MS);
+
+/// Out-of-range values are clamped.
+pub fn set_discovery_timeout_ms(timeout_ms: u32) {
+    DISCOVERY_TIMEOUT_MS.store(normalize_discovery_timeout_ms(timeout_ms), Ordering::Relaxed);
+}
+
+pub fn get_discovery_timeout_ms() -> u32 {
+    DISCOVERY_TIMEOUT_MS.load(Ordering::Relaxed)
+}
+
+pub fn normalize_discovery_timeout_ms(timeout_ms: u32) -> u32 {
+    timeout_ms.clamp(MIN_DISCOVERY_TIMEOUT_MS, MAX_DISCOVERY_TIMEOUT_MS)
+}
+
+pub fn discovery_timeout() -> Duration {
+    Duration::from_millis(get_discovery_timeout_ms() as u64)
+}
+
+pub fn source_address() -> Option<IpAddr> {
+    SOURCE_ADDRESS.read().map(|addr| *addr).unwrap_or(None)
+}
+
+/// `None` lets the OS pick the adapter per destination.
+pub fn set_source_address(addr: Option<IpAddr>) {
+    if let Ok(mut current) = SOURCE_ADDRESS.write() {
+        *current = addr;
+    }
+}
+
+/// Parse a configured source address; a blank value means no override.
+pub fn parse_source_address(value: &str) -> Result<Option<IpAddr>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value
+        .parse()
+        .map(Some)
+        .map_err(|e| format!("Invalid source address '{}': {}", value, e))
+}
+
+/// Local address to bind on `port` for talking to `target`.
+pub fn local_addr_for(target: IpAddr, port: u16) -> SocketAddr {
+    match (source_address(), target) {
+        (Some(source @ IpAddr::V4(_)), IpAddr::V4(_))
+        | (Some(source @ IpAddr::V6(_)), IpAddr::V6(_)) => SocketAddr::new(source, port),
+        (_, IpAddr::V4(_)) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)),
+        (_, IpAddr::V6(_)) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, port)),
+    }
+}
+
+/// UDP socket on an ephemeral port that can reach `target`.
+pub fn bind_udp_for(target: IpAddr) -> io::Result<UdpSocket> {
+    UdpSocket::bind(local_addr_for(target, 0))
+}
+
+/// The configured IPv4 adapter address, or `UNSPECIFIED` for the OS default.
+pub fn multicast_interface_v4() -> Ipv4Addr {
+    match source_address() {
+        Some(IpAddr::V4(addr)) => addr,
+        _ => Ipv4Addr::UNSPECIFIED,
+    }
+}
+
+/// Send the socket's IPv4 multicast out of the configured adapter. Broadcasts follow the
+/// bound address instead. Does nothing without an IPv4 source address.
+pub fn set_multicast_if_v4(socket: &UdpSocket) -> io::Result<()> {
+    let interface = multicast_interface_v4();
+    if interface.is_unspecified() {
+        return Ok(());
+    }
+    SockRef::from(socket).set_multicast_if_v4(&interface)
+}
+
+/// Join `group` on the configured adapter (or the OS default one) and send through it too.
+pub fn join_multicast_v4(socket: &UdpSocket, group: Ipv4Addr) -> io::Result<()> {
+    socket.join_multicast_v4(&group, &multicast_interface_v4())?;
+    set_multicast_if_v4(socket)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn sockets_bind_to_the_configured_source_address() {
+        let loopback = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        set_source_address(Some(loopback));
+
+        let receiver = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        let target = receiver.local_addr().unwrap();
+
+        let socket = bind_udp_for(target.ip()).unwrap();
+        assert_eq!(socket.local_addr().unwrap().ip(), loopback);
+        set_multicast_if_v4(&socket).unwrap();
+
+        socket.send_to(b"ping", target).unwrap();
+        let mut buf = [0u8; 8];
+        let (len, from) = receiver.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"ping");
+        assert_eq!(from, socket.local_addr().unwrap());
+
+        // IPv6 targets aren't forced onto an IPv4 source.
+        assert_eq!(
+            local_addr_for(IpAddr::V6(Ipv6Addr::LOCALHOST), 0),
+            SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0))
+        );
+
+        set_source_address(None);
+    }
+}
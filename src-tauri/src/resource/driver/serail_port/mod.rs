@@ -4,6 +4,8 @@
 //! throttles writes based on baud rate and payload size, preventing buffer overflow
 //! issues on macOS and other platforms where the OS serial buffer is less forgiving.
 
+use crate::interface::controller::WriteMetrics;
+use crate::resource::driver::write_retry::{is_retryable_io_error, retry_write, write_all_with_retry};
 use serialport::SerialPort;
 use std::io::{self, Write};
 use std::time::{Duration, Instant};
@@ -28,6 +30,7 @@ pub struct RateLimitedSerialPort {
     baud_rate: u32,
     min_interval: Duration,
     last_send: Option<Instant>,
+    metrics: WriteMetrics,
 }
 
 impl RateLimitedSerialPort {
@@ -51,6 +54,7 @@ impl RateLimitedSerialPort {
             baud_rate,
             min_interval,
             last_send: None,
+            metrics: WriteMetrics::default(),
         }
     }
 
@@ -59,7 +63,7 @@ impl RateLimitedSerialPort {
     /// Uses a conservative calculation:
     /// - Each byte on UART is ~10 bits (1 start + 8 data + 1 stop)
     /// - Safe FPS = floor(theoretical_fps) - 1, minimum 1 FPS
-    fn compute_min_interval(baud_rate: u32, frame_size: usize) -> Duration {
+    pub(crate) fn compute_min_interval(baud_rate: u32, frame_size: usize) -> Duration {
         const BITS_PER_BYTE: f64 = 10.0;
         let bytes_per_second = baud_rate as f64 / BITS_PER_BYTE;
         let theoretical_fps = bytes_per_second / frame_size as f64;
@@ -84,6 +88,11 @@ impl RateLimitedSerialPort {
         self.min_interval
     }
 
+    /// Frames written and dropped by the throttled writes so far.
+    pub fn metrics(&self) -> WriteMetrics {
+        self.metrics
+    }
+
     /// Writes data to the serial port with rate limiting.
     ///
     /// If called within the minimum interval since the last successful write,
@@ -99,19 +108,22 @@ impl RateLimitedSerialPort {
         if let Some(last) = self.last_send {
             if now.duration_since(last) < self.min_interval {
                 // Drop this frame - we're sending too fast
+                self.metrics.dropped += 1;
                 return Ok(0);
             }
         }
 
         // Write the data
-        let bytes_written = self.port.write(data)?;
+        let bytes_written = retry_write(|| self.port.write(data), is_retryable_io_error)?;
         self.last_send = Some(now);
+        self.metrics.sent += 1;
         Ok(bytes_written)
     }
 
     /// Writes all data to the serial port with rate limiting.
     ///
     /// Similar to `write_throttled`, but ensures all data is written if not rate-limited.
+    /// Transient errors are retried (see `write_retry`), fatal ones are returned as-is.
     ///
     /// # Returns
     /// - `Ok(true)` - Data was written successfully
@@ -124,13 +136,15 @@ impl RateLimitedSerialPort {
         if let Some(last) = self.last_send {
             if now.duration_since(last) < self.min_interval {
                 // Drop this frame - we're sending too fast
+                self.metrics.dropped += 1;
                 return Ok(false);
             }
         }
 
         // Write all data
-        self.port.write_all(data)?;
+        write_all_with_retry(&mut *self.port, data)?;
         self.last_send = Some(now);
+        self.metrics.sent += 1;
         Ok(true)
     }
 
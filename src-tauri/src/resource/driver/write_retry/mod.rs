@@ -0,0 +1,258 @@
+//! Retry of transient controller write failures.
+//!
+//! USB serial and HID writes occasionally fail for a moment (`WouldBlock`, a timed-out
+//! transfer, a bus hiccup) and succeed again right away. Left alone, each of these costs a
+//! frame and counts towards the runner giving up on the device. Writes are retried a few
+//! times with a short exponential backoff; errors that mean the device is gone are returned
+//! immediately. A frame's retries share one budget that also ends after `RETRY_WINDOW`, so
+//! a write that blocks until its own timeout (200 ms on the Skydimo port) isn't retried
+//! into several frames' worth of stall.
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_WRITE_RETRIES: u32 = 3;
+pub const MAX_WRITE_RETRIES: u32 = 5;
+
+/// Backoff before the first retry; doubles on every further attempt (1, 2, 4, 8, 16 ms).
+const INITIAL_BACKOFF: Duration = Duration::from_millis(1);
+
+/// How long after a budget is created its retries may still start: one frame at 60 Hz,
+/// counted from before the first attempt.
+const RETRY_WINDOW: Duration = Duration::from_millis(16);
+
+/// Extra attempts after a failed write; 0 disables retrying.
+static WRITE_RETRIES: AtomicU32 = AtomicU32::new(DEFAULT_WRITE_RETRIES);
+
+pub fn write_retries() -> u32 {
+    WRITE_RETRIES.load(Ordering::Relaxed)
+}
+
+/// Values above `MAX_WRITE_RETRIES` are clamped so retries can't eat several frames.
+pub fn set_write_retries(retries: u32) {
+    WRITE_RETRIES.store(retries.min(MAX_WRITE_RETRIES), Ordering::Relaxed);
+}
+
+/// IO errors worth retrying. Anything else (`NotFound`, `BrokenPipe`, permission errors...)
+/// means the device went away or can't be used.
+pub fn is_retryable_io_error(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::Interrupted
+    )
+}
+
+/// Retries left for a group of writes. A frame sent as several reports or chunks shares
+/// one budget, so it can't spend more than one write's worth of backoff however many of
+/// them fail, nor retry past the end of its `RETRY_WINDOW`.
+pub struct RetryBudget {
+    retries_left: u32,
+    backoff: Duration,
+    deadline: Instant,
+}
+
+impl RetryBudget {
+    pub fn new() -> Self {
+        Self {
+            retries_left: write_retries(),
+            backoff: INITIAL_BACKOFF,
+            deadline: Instant::now() + RETRY_WINDOW,
+        }
+    }
+
+    /// Take one retry, if any are left and its backoff ends before the deadline.
+    fn take(&mut self) -> Option<Duration> {
+        if self.retries_left == 0 || Instant::now() + self.backoff > self.deadline {
+            return None;
+        }
+        self.retries_left -= 1;
+        let backoff = self.backoff;
+        self.backoff *= 2;
+        Some(backoff)
+    }
+}
+
+impl Default for RetryBudget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Run `write`, retrying with backoff while it fails with an error `retryable` accepts.
+pub fn retry_write<T, E>(
+    write: impl FnMut() -> Result<T, E>,
+    retryable: impl Fn(&E) -> bool,
+) -> Result<T, E> {
+    retry_write_within(&mut RetryBudget::new(), write, retryable)
+}
+
+/// [`retry_write`] drawing its retries from `budget`.
+pub fn retry_write_within<T, E>(
+    budget: &mut RetryBudget,
+    mut write: impl FnMut() -> Result<T, E>,
+    retryable: impl Fn(&E) -> bool,
+) -> Result<T, E> {
+    loop {
+        match write() {
+            Err(err) if retryable(&err) => match budget.take() {
+                Some(backoff) => thread::sleep(backoff),
+                None => return Err(err),
+            },
+            result => return result,
+        }
+    }
+}
+
+/// `Write::write_all` that retries transient errors and resumes after the bytes already
+/// written, so a retry never re-sends the start of a packet. All chunks share one budget.
+pub fn write_all_with_retry<W: Write + ?Sized>(writer: &mut W, mut data: &[u8]) -> io::Result<()> {
+    let mut budget = RetryBudget::new();
+    while !data.is_empty() {
+        let written = retry_write_within(&mut budget, || writer.write(data), is_retryable_io_error)?;
+        if written == 0 {
+            return Err(io::Error::from(io::ErrorKind::WriteZero));
+        }
+        data = &data[written..];
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fails the first `failures` writes with `kind`, then accepts at most `chunk` bytes.
+    struct FlakyWriter {
+        failures: u32,
+        kind: io::ErrorKind,
+        chunk: usize,
+        written: Vec<u8>,
+    }
+
+    impl Write for FlakyWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(io::Error::from(self.kind));
+            }
+            let n = buf.len().min(self.chunk);
+            self.written.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn transient_failure_still_delivers_the_frame() {
+        let mut writer = FlakyWriter {
+            failures: 1,
+            kind: io::ErrorKind::WouldBlock,
+            chunk: 4,
+            written: Vec::new(),
+        };
+        let frame = [0xAAu8, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+
+        write_all_with_retry(&mut writer, &frame).unwrap();
+        assert_eq!(writer.written, frame);
+    }
+
+    #[test]
+    fn fatal_errors_are_not_retried() {
+        let mut writer = FlakyWriter {
+            failures: 1,
+            kind: io::ErrorKind::BrokenPipe,
+            chunk: usize::MAX,
+            written: Vec::new(),
+        };
+
+        let err = write_all_with_retry(&mut writer, &[1, 2, 3]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+        assert!(writer.written.is_empty());
+    }
+
+    #[test]
+    fn writes_sharing_a_budget_stop_retrying_once_it_is_spent() {
+        let mut budget = RetryBudget {
+            retries_left: 2,
+            backoff: Duration::ZERO,
+            deadline: Instant::now() + Duration::from_secs(60),
+        };
+        let mut attempts = 0;
+        let mut flaky = || {
+            attempts += 1;
+            if attempts % 2 == 1 {
+                Err(io::ErrorKind::TimedOut)
+            } else {
+                Ok(())
+            }
+        };
+        let retryable = |_: &io::ErrorKind| true;
+
+        // Two reports that each fail once use up the budget between them.
+        assert!(retry_write_within(&mut budget, &mut flaky, retryable).is_ok());
+        assert!(retry_write_within(&mut budget, &mut flaky, retryable).is_ok());
+        assert_eq!(
+            retry_write_within(&mut budget, &mut flaky, retryable),
+            Err(io::ErrorKind::TimedOut)
+        );
+        assert_eq!(attempts, 5);
+    }
+
+    #[test]
+    fn chunks_of_one_write_share_a_budget() {
+        // Every chunk fails once; a budget per chunk would deliver all of them.
+        struct FailsEveryOther {
+            fail: bool,
+            written: usize,
+        }
+        impl Write for FailsEveryOther {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.fail = !self.fail;
+                if self.fail {
+                    return Err(io::Error::from(io::ErrorKind::WouldBlock));
+                }
+                self.written += 1;
+                Ok(buf.len().min(1))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut writer = FailsEveryOther {
+            fail: false,
+            written: 0,
+        };
+        let err = write_all_with_retry(&mut writer, &[0; 8]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+        assert!(writer.written <= DEFAULT_WRITE_RETRIES as usize);
+    }
+
+    #[test]
+    fn no_retry_starts_after_the_deadline() {
+        let mut budget = RetryBudget {
+            retries_left: 5,
+            backoff: Duration::ZERO,
+            deadline: Instant::now(),
+        };
+        let mut attempts = 0;
+        // A write that blocked until its own timeout leaves no time for another one.
+        let result = retry_write_within(
+            &mut budget,
+            || {
+                attempts += 1;
+                thread::sleep(Duration::from_millis(2));
+                Err::<(), _>(io::ErrorKind::TimedOut)
+            },
+            |_| true,
+        );
+        assert_eq!(result, Err(io::ErrorKind::TimedOut));
+        assert_eq!(attempts, 1);
+    }
+}
@@ -1,6 +1,19 @@
+pub mod ambient_image;
+pub mod audio_bars;
 pub mod audio_star;
+pub mod color_wipe;
+pub mod comet;
+pub mod fire;
+pub mod gradient;
 pub mod matrix_test;
 pub mod monochrome;
+pub mod parallel;
+pub mod plasma;
 pub mod rainbow;
+pub mod ripple;
 pub mod screen_mirror;
+pub mod system_monitor;
+pub mod theater_chase;
 pub mod turn_off;
+pub mod twinkle;
+pub mod typing_pulse;
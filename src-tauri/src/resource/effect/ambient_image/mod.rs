@@ -0,0 +1,435 @@
+use crate::interface::controller::Color;
+use crate::interface::effect::{
+    Effect, EffectLayout, EffectMetadata, EffectParam, EffectParamKind, SelectOptions,
+    StaticSelectOption,
+};
+use image::GenericImageView;
+use serde_json::Value;
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Largest image we are willing to download (album art is usually far smaller).
+const MAX_IMAGE_BYTES: u64 = 16 * 1024 * 1024;
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+/// Images are shrunk to this size before color extraction.
+const SAMPLE_SIZE: u32 = 64;
+
+const COLOR_MODE_OPTIONS: [StaticSelectOption; 2] = [
+    StaticSelectOption {
+        label: "Average",
+        value: 0.0,
+    },
+    StaticSelectOption {
+        label: "Dominant",
+        value: 1.0,
+    },
+];
+
+const AMBIENT_IMAGE_PARAMS: [EffectParam; 4] = [
+    EffectParam {
+        key: "source",
+        label: "Image source",
+        kind: EffectParamKind::Text {
+            default: "",
+            placeholder: Some("https://example.com/cover.jpg"),
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "refreshSecs",
+        label: "Refresh interval (s)",
+        kind: EffectParamKind::Slider {
+            min: 1.0,
+            max: 60.0,
+            step: 1.0,
+            default: 5.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "colorMode",
+        label: "Color mode",
+        kind: EffectParamKind::Select {
+            default: 0.0,
+            options: SelectOptions::Static(&COLOR_MODE_OPTIONS),
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "smoothness",
+        label: "Smoothness",
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 100.0,
+            step: 1.0,
+            default: 80.0,
+        },
+        dependency: None,
+    },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ColorMode {
+    Average,
+    Dominant,
+}
+
+/// Settings and results shared between the effect and its fetch thread.
+struct Shared {
+    source: String,
+    refresh: Duration,
+    mode: ColorMode,
+    /// Bumped whenever `source` or `mode` changes so the worker refetches immediately.
+    revision: u64,
+    /// Latest extracted color; kept on fetch failures so the LEDs hold the last good value.
+    color: Option<Color>,
+}
+
+/// Validators from the last successful fetch, used to skip unchanged images.
+#[derive(Default)]
+struct FetchCache {
+    revision: u64,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    modified: Option<SystemTime>,
+}
+
+pub struct AmbientImageEffect {
+    shared: Arc<Mutex<Shared>>,
+    running: Arc<AtomicBool>,
+    current: [f32; 3],
+    smoothness: f32,
+    last_time: Option<Duration>,
+}
+
+impl AmbientImageEffect {
+    fn new() -> Self {
+        let shared = Arc::new(Mutex::new(Shared {
+            source: String::new(),
+            refresh: Duration::from_secs(5),
+            mode: ColorMode::Average,
+            revision: 0,
+            color: None,
+        }));
+        let running = Arc::new(AtomicBool::new(true));
+
+        // Detached on purpose: a fetch in flight is bounded by HTTP_TIMEOUT and the loop
+        // checks `running` every 100ms, so dropping the effect never stalls the runner.
+        {
+            let shared = shared.clone();
+            let running = running.clone();
+            if let Err(err) = thread::Builder::new()
+                .name("ambient-image".to_string())
+                .spawn(move || fetch_loop(shared, running))
+            {
+                log::error!(err:display = err; "[ambient_image] Failed to spawn fetch thread");
+            }
+        }
+
+        Self {
+            shared,
+            running,
+            current: [0.0; 3],
+            smoothness: 80.0,
+            last_time: None,
+        }
+    }
+}
+
+impl Drop for AmbientImageEffect {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+impl Effect for AmbientImageEffect {
+    fn id(&self) -> String {
+        "ambient_image".to_string()
+    }
+
+    fn name(&self) -> String {
+        "Ambient Image".to_string()
+    }
+
+    fn tick(&mut self, time: Duration, buffer: &mut [Color]) {
+        let target = self.shared.lock().unwrap().color;
+        let dt = self
+            .last_time
+            .map(|last| time.saturating_sub(last))
+            .unwrap_or_default();
+        self.last_time = Some(time);
+
+        if let Some(target) = target {
+            // Exponential approach; smoothness 0 snaps, 100 takes a couple of seconds.
+            let time_constant = self.smoothness / 100.0 * 1.5;
+            let alpha = if time_constant <= f32::EPSILON {
+                1.0
+            } else {
+                1.0 - (-dt.as_secs_f32() / time_constant).exp()
+            };
+            let target = [target.r as f32, target.g as f32, target.b as f32];
+            for (channel, goal) in self.current.iter_mut().zip(target) {
+                *channel += (goal - *channel) * alpha;
+            }
+        }
+
+        buffer.fill(Color {
+            r: self.current[0].round() as u8,
+            g: self.current[1].round() as u8,
+            b: self.current[2].round() as u8,
+        });
+    }
+
+    fn update_params(&mut self, params: Value) {
+        if let Some(smoothness) = params.get("smoothness").and_then(|v| v.as_f64()) {
+            self.smoothness = (smoothness as f32).clamp(0.0, 100.0);
+        }
+
+        let mut shared = self.shared.lock().unwrap();
+        if let Some(source) = params.get("source").and_then(|v| v.as_str()) {
+            let source = source.trim();
+            if source != shared.source {
+                shared.source = source.to_string();
+                shared.revision += 1;
+            }
+        }
+        if let Some(secs) = params.get("refreshSecs").and_then(|v| v.as_f64()) {
+            shared.refresh = Duration::from_secs_f64(secs.clamp(1.0, 60.0));
+        }
+        if let Some(mode) = params.get("colorMode").and_then(|v| v.as_f64()) {
+            let mode = if mode.round() as i64 == 1 {
+                ColorMode::Dominant
+            } else {
+                ColorMode::Average
+            };
+            if mode != shared.mode {
+                shared.mode = mode;
+                shared.revision += 1;
+            }
+        }
+    }
+}
+
+fn fetch_loop(shared: Arc<Mutex<Shared>>, running: Arc<AtomicBool>) {
+    let mut cache = FetchCache::default();
+    let mut next_fetch = std::time::Instant::now();
+
+    while running.load(Ordering::Relaxed) {
+        let (source, refresh, mode, revision) = {
+            let s = shared.lock().unwrap();
+            (s.source.clone(), s.refresh, s.mode, s.revision)
+        };
+
+        let now = std::time::Instant::now();
+        if !source.is_empty() && (revision != cache.revision || now >= next_fetch) {
+            if revision != cache.revision {
+                cache = FetchCache {
+                    revision,
+                    ..FetchCache::default()
+                };
+            }
+
+            match fetch_color(&source, mode, &mut cache) {
+                Ok(Some(color)) => shared.lock().unwrap().color = Some(color),
+                Ok(None) => {}
+                Err(err) => {
+                    log::warn!(source = source.as_str(), err:display = err; "[ambient_image] Failed to refresh image color");
+                }
+            }
+            next_fetch = std::time::Instant::now() + refresh;
+        }
+
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Returns `Ok(None)` when the source is unchanged since the last fetch.
+fn fetch_color(source: &str, mode: ColorMode, cache: &mut FetchCache) -> Result<Option<Color>, String> {
+    let bytes = if source.starts_with("http://") || source.starts_with("https://") {
+        match fetch_http(source, cache)? {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        }
+    } else {
+        let path = source.strip_prefix("file://").unwrap_or(source);
+        let modified = std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map_err(|e| format!("Failed to stat {}: {}", path, e))?;
+        if cache.modified == Some(modified) {
+            return Ok(None);
+        }
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        cache.modified = Some(modified);
+        bytes
+    };
+
+    let image = image::load_from_memory(&bytes).map_err(|e| format!("Failed to decode image: {}", e))?;
+    let thumb = image.thumbnail(SAMPLE_SIZE, SAMPLE_SIZE);
+    let pixels: Vec<[u8; 3]> = thumb
+        .pixels()
+        .filter(|(_, _, p)| p[3] > 0)
+        .map(|(_, _, p)| [p[0], p[1], p[2]])
+        .collect();
+
+    Ok(match mode {
+        ColorMode::Average => average_color(&pixels),
+        ColorMode::Dominant => dominant_color(&pixels),
+    })
+}
+
+fn fetch_http(url: &str, cache: &mut FetchCache) -> Result<Option<Vec<u8>>, String> {
+    let mut request = ureq::get(url).timeout(HTTP_TIMEOUT);
+    if let Some(etag) = &cache.etag {
+        request = request.set("If-None-Match", etag);
+    }
+    if let Some(last_modified) = &cache.last_modified {
+        request = request.set("If-Modified-Since", last_modified);
+    }
+
+    let response = request.call().map_err(|e| format!("HTTP request failed: {}", e))?;
+    if response.status() == 304 {
+        return Ok(None);
+    }
+
+    let etag = response.header("ETag").map(str::to_string);
+    let last_modified = response.header("Last-Modified").map(str::to_string);
+
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_IMAGE_BYTES)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read HTTP body: {}", e))?;
+
+    cache.etag = etag;
+    cache.last_modified = last_modified;
+    Ok(Some(bytes))
+}
+
+fn average_color(pixels: &[[u8; 3]]) -> Option<Color> {
+    if pixels.is_empty() {
+        return None;
+    }
+    let mut sum = [0u64; 3];
+    for p in pixels {
+        for (acc, v) in sum.iter_mut().zip(p) {
+            *acc += *v as u64;
+        }
+    }
+    let n = pixels.len() as u64;
+    Some(Color {
+        r: (sum[0] / n) as u8,
+        g: (sum[1] / n) as u8,
+        b: (sum[2] / n) as u8,
+    })
+}
+
+/// Most common color bucket (4 bits per channel), preferring saturated colors so that
+/// black/white borders on cover art don't win.
+fn dominant_color(pixels: &[[u8; 3]]) -> Option<Color> {
+    if pixels.is_empty() {
+        return None;
+    }
+
+    let mut weights = vec![0f32; 4096];
+    let mut buckets = vec![([0u64; 3], 0u64); 4096];
+    for p in pixels {
+        let bucket = ((p[0] as usize >> 4) << 8) | ((p[1] as usize >> 4) << 4) | (p[2] as usize >> 4);
+        let max = p[0].max(p[1]).max(p[2]) as f32;
+        let min = p[0].min(p[1]).min(p[2]) as f32;
+        let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+        weights[bucket] += 0.1 + saturation;
+
+        let (sum, count) = &mut buckets[bucket];
+        for (acc, v) in sum.iter_mut().zip(p) {
+            *acc += *v as u64;
+        }
+        *count += 1;
+    }
+
+    let (best, _) = weights
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))?;
+    let (sum, count) = buckets[best];
+    Some(Color {
+        r: (sum[0] / count) as u8,
+        g: (sum[1] / count) as u8,
+        b: (sum[2] / count) as u8,
+    })
+}
+
+fn factory() -> Box<dyn Effect> {
+    Box::new(AmbientImageEffect::new())
+}
+
+inventory::submit!(EffectMetadata {
+    id: "ambient_image",
+    name: "Ambient Image",
+    description: Some("Fill with the color of an image from a URL or file, e.g. album art"),
+    group: Some("Dynamic"),
+    icon: Some("Image"),
+    layout: EffectLayout::Any,
+    params: &AMBIENT_IMAGE_PARAMS,
+    factory,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [u8; 3] = [255, 0, 0];
+    const BLUE: [u8; 3] = [0, 0, 255];
+    const WHITE: [u8; 3] = [255, 255, 255];
+
+    fn rgb(color: Option<Color>) -> Option<(u8, u8, u8)> {
+        color.map(|c| (c.r, c.g, c.b))
+    }
+
+    #[test]
+    fn average_mixes_every_pixel() {
+        assert_eq!(rgb(average_color(&[RED, BLUE])), Some((127, 0, 127)));
+        assert_eq!(average_color(&[]), None);
+    }
+
+    #[test]
+    fn dominant_prefers_saturated_colors_over_a_white_border() {
+        let mut pixels = vec![WHITE; 6];
+        pixels.extend([RED; 4]);
+        assert_eq!(rgb(dominant_color(&pixels)), Some((255, 0, 0)));
+        assert_eq!(dominant_color(&[]), None);
+    }
+
+    #[test]
+    fn files_are_read_once_until_they_change() {
+        let path = std::env::temp_dir().join(format!("light-ambient-{}.png", std::process::id()));
+        image::RgbImage::from_pixel(80, 80, image::Rgb(BLUE)).save(&path).unwrap();
+        let source = path.to_str().unwrap();
+
+        let mut cache = FetchCache::default();
+        let color = fetch_color(source, ColorMode::Average, &mut cache).unwrap();
+        assert_eq!(rgb(color), Some((0, 0, 255)));
+        assert_eq!(fetch_color(source, ColorMode::Average, &mut cache).unwrap(), None);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(fetch_color(source, ColorMode::Average, &mut cache).is_err());
+    }
+
+    #[test]
+    fn changing_the_source_or_mode_requests_a_refetch() {
+        let mut effect = AmbientImageEffect::new();
+        let revision = |effect: &AmbientImageEffect| effect.shared.lock().unwrap().revision;
+
+        effect.update_params(serde_json::json!({ "source": " cover.png ", "colorMode": 0.0 }));
+        assert_eq!(effect.shared.lock().unwrap().source, "cover.png");
+        assert_eq!(revision(&effect), 1);
+
+        effect.update_params(serde_json::json!({ "source": "cover.png", "refreshSecs": 30.0 }));
+        assert_eq!(revision(&effect), 1);
+        effect.update_params(serde_json::json!({ "colorMode": 1.0 }));
+        assert_eq!(revision(&effect), 2);
+    }
+}
@@ -0,0 +1,429 @@
+//! Audio Bars Effect
+//!
+//! A classic spectrum analyzer: the spectrum is split into log-spaced bands laid out left to
+//! right. On a matrix every band is a bar as tall as the band is loud, topped by a peak cap
+//! that holds for a moment and then falls; on a strip every LED shows a band, as bright as
+//! the band is loud. Colors follow the gradient from the bottom of a bar to its top.
+
+use crate::interface::controller::Color;
+use crate::interface::effect::{
+    Effect, EffectLayout, EffectMetadata, EffectParam, EffectParamKind, SelectOptions,
+    StaticSelectOption,
+};
+use crate::resource::audio::spectrum::{latest_samples, AudioSpectrum, Envelope, DEFAULT_FFT_SIZE};
+use crate::resource::audio::AudioManager;
+use crate::resource::color::hsv_to_rgb;
+use crate::resource::effect::audio_star::load_audio_devices;
+use inventory;
+use serde_json::Value;
+use std::time::Duration;
+
+const MIN_BANDS: usize = 4;
+const MAX_BANDS: usize = 64;
+
+/// Bar heights per second a cap falls once its hold is over.
+const CAP_FALL_SPEED: f32 = 1.5;
+
+/// Caps below this are not drawn, so silence leaves the bottom row dark.
+const MIN_CAP_LEVEL: f32 = 0.02;
+
+/// Longest gap between ticks that is simulated, so a stalled runner doesn't drop the caps
+/// all at once.
+const MAX_TICK: f32 = 0.25;
+
+/// Hue at the bottom and at the top of a bar, per `gradient`.
+const GRADIENTS: [(f32, f32); 3] = [(120.0, 0.0), (270.0, 0.0), (190.0, 250.0)];
+
+const CAP_COLOR: Color = Color {
+    r: 255,
+    g: 255,
+    b: 255,
+};
+
+const GRADIENT_OPTIONS: [StaticSelectOption; 3] = [
+    StaticSelectOption {
+        label: "Classic",
+        value: 0.0,
+    },
+    StaticSelectOption {
+        label: "Rainbow",
+        value: 1.0,
+    },
+    StaticSelectOption {
+        label: "Ocean",
+        value: 2.0,
+    },
+];
+
+const AUDIO_BARS_PARAMS: [EffectParam; 5] = [
+    EffectParam {
+        key: "audioDevice",
+        label: "Audio device",
+        kind: EffectParamKind::Select {
+            default: 0.0,
+            options: SelectOptions::Dynamic(load_audio_devices),
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "bands",
+        label: "Bands",
+        kind: EffectParamKind::Slider {
+            min: MIN_BANDS as f64,
+            max: MAX_BANDS as f64,
+            step: 1.0,
+            default: 16.0,
+        },
+        dependency: None,
+    },
+    // Release time of the bars, in ms; they rise instantly.
+    EffectParam {
+        key: "smoothing",
+        label: "Smoothing (ms)",
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 1000.0,
+            step: 10.0,
+            default: 150.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "peakHoldMs",
+        label: "Peak hold (ms)",
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 2000.0,
+            step: 50.0,
+            default: 500.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "gradient",
+        label: "Gradient",
+        kind: EffectParamKind::Select {
+            default: 0.0,
+            options: SelectOptions::Static(&GRADIENT_OPTIONS),
+        },
+        dependency: None,
+    },
+];
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Cap {
+    /// 0.0..=1.0, like the bars.
+    level: f32,
+    /// Seconds left before it starts to fall.
+    hold: f32,
+}
+
+pub struct AudioBarsEffect {
+    width: usize,
+    height: usize,
+    audio_device_index: Option<usize>,
+    /// Whether this effect holds a share of the audio capture.
+    capturing: bool,
+    spectrum: AudioSpectrum,
+    /// Captured audio for the current frame, kept to avoid reallocating it every tick.
+    samples: Vec<f32>,
+    envelope: Envelope,
+    bands: usize,
+    /// Seconds.
+    peak_hold: f32,
+    gradient: (f32, f32),
+    /// Per band, 0.0..=1.0 after the envelope.
+    levels: Vec<f32>,
+    caps: Vec<Cap>,
+    /// [`band_edges`] for the `(bins, bands)` they were computed for; only a band count or
+    /// FFT size change recomputes them.
+    edges: Vec<usize>,
+    edges_for: (usize, usize),
+    last_time: Option<Duration>,
+}
+
+impl AudioBarsEffect {
+    pub fn new() -> Self {
+        let mut spectrum = AudioSpectrum::new(DEFAULT_FFT_SIZE);
+        // Bins are grouped into bands instead.
+        spectrum.avg_size = 1;
+        Self {
+            width: 0,
+            height: 0,
+            audio_device_index: None,
+            capturing: false,
+            spectrum,
+            samples: Vec::new(),
+            envelope: Envelope {
+                attack: 1.0,
+                release: Envelope::coefficient(150.0),
+            },
+            bands: 16,
+            peak_hold: 0.5,
+            gradient: GRADIENTS[0],
+            levels: Vec::new(),
+            caps: Vec::new(),
+            edges: Vec::new(),
+            edges_for: (0, 0),
+            last_time: None,
+        }
+    }
+
+    /// Share the audio capture once a device is selected.
+    fn ensure_capture(&mut self) {
+        let Some(device_index) = self.audio_device_index else {
+            return;
+        };
+        if self.capturing {
+            return;
+        }
+        if let Err(e) = AudioManager::get().acquire_capture(device_index) {
+            log::error!(device_index = device_index, err:display = e; "[audio_bars] Failed to start audio capture");
+            return;
+        }
+        self.capturing = true;
+    }
+
+    fn release_capture(&mut self) {
+        if self.capturing {
+            AudioManager::get().release_capture();
+            self.capturing = false;
+        }
+    }
+
+    /// Follow the filtered spectrum with the bars and let the caps hold and fall over `dt`
+    /// seconds.
+    fn update_levels(&mut self, dt: f32) {
+        if self.levels.len() != self.bands {
+            self.levels = vec![0.0; self.bands];
+            self.caps = vec![Cap::default(); self.bands];
+        }
+        let edges_for = (self.spectrum.bins(), self.bands);
+        if self.edges_for != edges_for {
+            self.edges = band_edges(edges_for.0, edges_for.1);
+            self.edges_for = edges_for;
+        }
+        for (band, range) in self.edges.windows(2).enumerate() {
+            let energy = self.spectrum.filtered()[range[0]..range[1]]
+                .iter()
+                .fold(0.0f32, |max, &v| max.max(v))
+                .clamp(0.0, 1.0);
+            let level = self.envelope.follow(self.levels[band], energy);
+            self.levels[band] = level;
+
+            let cap = &mut self.caps[band];
+            if level >= cap.level {
+                *cap = Cap {
+                    level,
+                    hold: self.peak_hold,
+                };
+            } else if cap.hold > 0.0 {
+                cap.hold -= dt;
+            } else {
+                cap.level = (cap.level - CAP_FALL_SPEED * dt).max(level);
+            }
+        }
+    }
+
+    /// Color at `t` (0.0 bottom, 1.0 top) of the gradient.
+    fn gradient_color(&self, t: f32, value: f32) -> Color {
+        let (bottom, top) = self.gradient;
+        let (r, g, b) = hsv_to_rgb(bottom + (top - bottom) * t.clamp(0.0, 1.0), 1.0, value);
+        Color { r, g, b }
+    }
+
+    fn draw_strip(&self, buffer: &mut [Color]) {
+        let len = buffer.len();
+        for (i, led) in buffer.iter_mut().enumerate() {
+            let level = self.levels[i * self.bands / len];
+            *led = self.gradient_color(level, level);
+        }
+    }
+
+    fn draw_matrix(&self, width: usize, height: usize, buffer: &mut [Color]) {
+        buffer.fill(Color::default());
+        for x in 0..width {
+            let band = x * self.bands / width;
+            let bar = self.levels[band] * height as f32;
+            let cap = self.caps[band].level;
+            let cap_row = ((cap * height as f32) as usize).min(height - 1);
+            for row in 0..height {
+                let Some(led) = buffer.get_mut((height - 1 - row) * width + x) else {
+                    continue;
+                };
+                // The top LED of a bar is lit by how much of it the bar covers.
+                let fill = (bar - row as f32).clamp(0.0, 1.0);
+                if fill > 0.0 {
+                    *led = self.gradient_color((row as f32 + 0.5) / height as f32, fill);
+                }
+                if row == cap_row && cap >= MIN_CAP_LEVEL && bar < (row + 1) as f32 {
+                    *led = CAP_COLOR;
+                }
+            }
+        }
+    }
+}
+
+impl Default for AudioBarsEffect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// First spectrum bin of each of `bands` bands, then `bins`. Log-spaced, so the bass gets as
+/// many bands as the treble, but every band has at least one bin.
+fn band_edges(bins: usize, bands: usize) -> Vec<usize> {
+    let bands = bands.min(bins);
+    let mut edges = Vec::with_capacity(bands + 1);
+    edges.push(0);
+    for band in 1..bands {
+        let log_edge = (bins as f32).powf(band as f32 / bands as f32).round() as usize - 1;
+        // Leave a bin for each band still to come.
+        let edge = log_edge.max(edges[band - 1] + 1).min(bins - (bands - band));
+        edges.push(edge);
+    }
+    edges.push(bins);
+    edges
+}
+
+impl Effect for AudioBarsEffect {
+    fn id(&self) -> String {
+        "audio_bars".to_string()
+    }
+
+    fn name(&self) -> String {
+        "Audio Bars".to_string()
+    }
+
+    fn preferred_fps(&self) -> Option<u32> {
+        Some(60)
+    }
+
+    fn tick(&mut self, time: Duration, buffer: &mut [Color]) {
+        if buffer.is_empty() {
+            return;
+        }
+
+        self.ensure_capture();
+
+        let dt = self
+            .last_time
+            .and_then(|last| time.checked_sub(last))
+            .map_or(0.0, |d| d.as_secs_f32().min(MAX_TICK));
+        self.last_time = Some(time);
+
+        let sample_rate = latest_samples(&mut self.samples, self.spectrum.fft_size());
+        self.spectrum.process(&self.samples, sample_rate);
+        self.update_levels(dt);
+
+        if self.width == 0 || self.height <= 1 {
+            self.draw_strip(buffer);
+        } else {
+            self.draw_matrix(self.width, self.height, buffer);
+        }
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn update_params(&mut self, params: Value) {
+        if let Some(device_index) = params.get("audioDevice").and_then(|v| v.as_f64()) {
+            let new_index = Some(device_index as usize);
+            if new_index != self.audio_device_index {
+                self.release_capture();
+                self.audio_device_index = new_index;
+                self.ensure_capture();
+            }
+        }
+
+        if let Some(bands) = params.get("bands").and_then(|v| v.as_f64()) {
+            self.bands = (bands as usize).clamp(MIN_BANDS, MAX_BANDS);
+        }
+
+        if let Some(smoothing) = params.get("smoothing").and_then(|v| v.as_f64()) {
+            self.envelope.release = Envelope::coefficient(smoothing as f32);
+        }
+
+        if let Some(hold) = params.get("peakHoldMs").and_then(|v| v.as_f64()) {
+            self.peak_hold = (hold as f32).clamp(0.0, 2000.0) / 1000.0;
+        }
+
+        if let Some(gradient) = params.get("gradient").and_then(|v| v.as_f64()) {
+            self.gradient = GRADIENTS
+                .get(gradient as usize)
+                .copied()
+                .unwrap_or(GRADIENTS[0]);
+        }
+    }
+}
+
+impl Drop for AudioBarsEffect {
+    fn drop(&mut self) {
+        self.release_capture();
+    }
+}
+
+fn factory() -> Box<dyn Effect> {
+    Box::new(AudioBarsEffect::new())
+}
+
+inventory::submit!(EffectMetadata {
+    id: "audio_bars",
+    name: "Audio Bars",
+    description: Some("Spectrum analyzer bars with falling peak caps"),
+    group: Some("Audio"),
+    icon: Some("ChartColumn"),
+    layout: EffectLayout::Any,
+    params: &AUDIO_BARS_PARAMS,
+    factory,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bands_are_log_spaced_and_never_empty() {
+        for (bins, bands) in [(256, 16), (128, 64), (1024, 4)] {
+            let edges = band_edges(bins, bands);
+            assert_eq!(edges.len(), bands + 1);
+            assert_eq!((edges[0], edges[bands]), (0, bins));
+            assert!(edges.windows(2).all(|w| w[0] < w[1]), "{:?}", edges);
+        }
+        // The bass gets narrower bands than the treble.
+        let edges = band_edges(256, 16);
+        assert!(edges[2] - edges[1] < edges[16] - edges[15]);
+    }
+
+    #[test]
+    fn caps_hold_then_fall_onto_the_bars() {
+        let mut effect = AudioBarsEffect::new();
+        effect.update_params(serde_json::json!({ "bands": 4, "smoothing": 0, "peakHoldMs": 200 }));
+        effect.resize(4, 10);
+
+        effect.spectrum.filtered_mut().fill(1.0);
+        effect.update_levels(0.0);
+        effect.spectrum.filtered_mut().fill(0.0);
+        effect.update_levels(0.1);
+        assert_eq!(effect.levels[0], 0.0);
+        assert_eq!(effect.caps[0].level, 1.0);
+
+        let mut buffer = vec![Color::default(); 40];
+        effect.draw_matrix(4, 10, &mut buffer);
+        // Cap on the top row, nothing below it.
+        assert_eq!(buffer[0].r, CAP_COLOR.r);
+        assert!(buffer[4..].iter().all(|c| c.r == 0 && c.g == 0 && c.b == 0));
+
+        // Held for 200 ms, then it falls at `CAP_FALL_SPEED`.
+        effect.update_levels(0.15);
+        assert_eq!(effect.caps[0].level, 1.0);
+        effect.update_levels(0.2);
+        assert!(effect.caps[0].level < 1.0);
+        for _ in 0..10 {
+            effect.update_levels(0.1);
+        }
+        assert_eq!(effect.caps[0].level, 0.0);
+    }
+}
@@ -5,24 +5,22 @@
 
 use crate::interface::controller::Color;
 use crate::interface::effect::{
-    DependencyBehavior, Effect, EffectMetadata, EffectParam, EffectParamDependency,
-    EffectParamKind, SelectOption, SelectOptions,
+    DependencyBehavior, Effect, EffectLayout, EffectMetadata, EffectParam, EffectParamDependency,
+    EffectParamKind, SelectOption, SelectOptions, StaticSelectOption,
+};
+use crate::resource::audio::beat::BeatDetector;
+use crate::resource::audio::spectrum::{
+    latest_samples, AudioSpectrum, Envelope, DEFAULT_FFT_SIZE, FFT_SIZES, TARGET_FPS,
 };
 use crate::resource::audio::{AudioDevice, AudioManager};
+use crate::resource::color::hsv_to_rgb;
+use crate::resource::trig;
 use inventory;
 use serde_json::Value;
-use spectrum_analyzer::scaling::divide_by_N_sqrt;
-use spectrum_analyzer::windows::hann_window;
-use spectrum_analyzer::{samples_fft_to_spectrum, FrequencyLimit};
 use std::time::Duration;
 
-const FFT_SIZE: usize = 1024;
-
-/// Number of filtered FFT bins we'll work with (matches C++ 256 bins).
-const FFT_BINS: usize = 256;
-
-/// Target FPS for decay calculation.
-const TARGET_FPS: f32 = 60.0;
+/// Per-frame fade of the edge pulse after a beat.
+const EDGE_PULSE_DECAY: f32 = 0.85;
 
 pub struct AudioStarEffect {
     // Layout dimensions.
@@ -35,26 +33,28 @@ pub struct AudioStarEffect {
 
     // Audio settings.
     audio_device_index: Option<usize>,
-    avg_size: usize,
+    // Whether this effect holds a share of the audio capture.
+    capturing: bool,
+
+    // FFT processing with the AGC settings.
+    spectrum: AudioSpectrum,
+    // Captured audio for the current frame, reused between ticks.
+    samples: Vec<f32>,
 
-    // AGC (Auto Gain Control) settings - matches C++ AudioSettingsStruct.
-    amplitude: f32,          // Gain multiplier (default 100)
-    decay: f32,              // Decay rate percentage (default 80)
-    filter_constant: f32,    // Low-pass filter constant (default 1.0)
+    // Attack/release envelope applied to the bins and the overall amplitude.
+    envelope: Envelope,
 
     // Edge beat settings.
     edge_beat_enabled: bool,
     edge_beat_hue: u16,
     edge_beat_saturation: u8,
     edge_beat_sensitivity: f32,
+    beat_detector: BeatDetector,
+    /// Edge brightness (0.0–1.0), set on each beat and fading between them.
+    edge_pulse: f32,
 
-    // FFT processing buffers.
-    fft_buffer: Vec<f32>,     // Raw FFT magnitude (with peak-hold and decay)
-    fft_nrml: Vec<f32>,       // Normalization array (frequency compensation)
-    fft_filtered: Vec<f32>,   // Final filtered FFT output
-
-    // Audio sample buffer.
-    audio_samples: Vec<f32>,
+    fft_smoothed: Vec<f32>,   // Filtered output after the envelope (what gets drawn)
+    amp: f32,                 // Overall amplitude after the envelope
 }
 
 impl Default for AudioStarEffect {
@@ -65,155 +65,82 @@ impl Default for AudioStarEffect {
 
 impl AudioStarEffect {
     pub fn new() -> Self {
-        // Default AGC settings matching C++ AudioSettingsStruct.
-        // nrml_ofst = 0.04, nrml_scl = 0.5
-        // Initialize normalization array (frequency compensation).
-        // Higher frequencies get more gain to compensate for typical audio spectrum roll-off.
-        let fft_nrml: Vec<f32> = (0..FFT_BINS)
-            .map(|i| 0.04 + (0.5 * (i as f32 / FFT_BINS as f32)))
-            .collect();
-
+        let spectrum = AudioSpectrum::new(DEFAULT_FFT_SIZE);
         Self {
             width: 0,
             height: 0,
             time: 0.0,
             speed: 50.0,
             audio_device_index: None,
-            avg_size: 8, // C++ default is 8
-            amplitude: 100.0,
-            decay: 80.0,
-            filter_constant: 1.0,
+            capturing: false,
+            fft_smoothed: vec![0.0; spectrum.bins()],
+            spectrum,
+            samples: Vec::new(),
+            envelope: Envelope::default(),
             edge_beat_enabled: false,
             edge_beat_hue: 0,
             edge_beat_saturation: 0,
             edge_beat_sensitivity: 100.0,
-            fft_buffer: vec![0.0; FFT_BINS],
-            fft_nrml,
-            fft_filtered: vec![0.0; FFT_BINS],
-            audio_samples: vec![0.0; FFT_SIZE],
+            beat_detector: BeatDetector::new(TARGET_FPS),
+            edge_pulse: 0.0,
+            amp: 0.0,
         }
     }
 
-    /// Process audio samples and update FFT data.
-    /// Matches the C++ AudioSignalProcessor::Process() implementation.
-    fn process_audio(&mut self) {
-        let manager = AudioManager::get();
-
-        // Read raw audio samples.
-        manager.read_samples(&mut self.audio_samples);
-
-        // Apply amplitude gain (AGC) - matches C++ fft_tmp[i] *= settings->amplitude.
-        let amplified_samples: Vec<f32> = self.audio_samples.iter()
-            .map(|&s| s * self.amplitude)
-            .collect();
-
-        // Apply decay to previous FFT values.
-        // C++: data.fft[i] = data.fft[i] * ((float(settings->decay) / 100.0f / (60 / FPS)));
-        let decay_factor = (self.decay / 100.0) / (60.0 / TARGET_FPS);
-        for i in 0..FFT_BINS {
-            self.fft_buffer[i] *= decay_factor;
+    /// Share the audio capture once a device is selected.
+    fn ensure_capture(&mut self) {
+        let Some(device_index) = self.audio_device_index else {
+            return;
+        };
+        if self.capturing {
+            return;
         }
-
-        // Apply Hann window (C++ window_mode == 1).
-        let windowed = hann_window(&amplified_samples);
-
-        // Compute FFT.
-        if let Ok(spectrum) = samples_fft_to_spectrum(
-            &windowed,
-            manager.sample_rate().unwrap_or(44100),
-            FrequencyLimit::Range(20.0, 20000.0),
-            Some(&divide_by_N_sqrt),
-        ) {
-            // Map spectrum to our FFT bins.
-            let freq_data: Vec<f32> = spectrum.data().iter().map(|(_, v)| v.val()).collect();
-
-            // Downsample to FFT_BINS.
-            let step = freq_data.len().max(1) as f32 / FFT_BINS as f32;
-            for i in 0..FFT_BINS {
-                let idx = (i as f32 * step) as usize;
-                let raw_mag = freq_data.get(idx).copied().unwrap_or(0.0);
-
-                // Apply normalization (frequency compensation).
-                // C++: apply_window(fft_tmp, data.fft_nrml, 256);
-                let normalized_mag = raw_mag * self.fft_nrml[i];
-
-                // Apply logarithmic filter to minimize noise from very low amplitude frequencies.
-                // C++: fftmag = (0.5f * log10(1.1f * fftmag)) + (0.9f * fftmag);
-                let fftmag = if normalized_mag > 0.0 {
-                    (0.5 * (1.1 * normalized_mag).log10()) + (0.9 * normalized_mag)
-                } else {
-                    0.0
-                };
-
-                // Clamp to [0, 1] range.
-                // C++: if (fftmag > 1.0f) fftmag = 1.0f;
-                let fftmag = fftmag.clamp(0.0, 1.0);
-
-                // Peak-hold behavior: only update if new value is greater.
-                // C++: if (fftmag > data.fft[i*2]) data.fft[i*2] = fftmag;
-                if fftmag > self.fft_buffer[i] {
-                    self.fft_buffer[i] = fftmag;
-                }
-            }
+        if let Err(e) = AudioManager::get().acquire_capture(device_index) {
+            log::error!(device_index = device_index, err:display = e; "[audio_star] Failed to start audio capture");
+            return;
         }
+        self.capturing = true;
+    }
 
-        // Apply averaging over avg_size (C++ avg_mode == 0, binning mode).
-        self.apply_binning_average();
-
-        // Apply low-pass filter to get final filtered FFT.
-        // C++: data.fft_fltr[i] = equalizer[i/16] * (data.fft_fltr[i] + (filter_constant * (data.fft[i] - data.fft_fltr[i])));
-        for i in 0..FFT_BINS {
-            self.fft_filtered[i] = self.fft_filtered[i] + 
-                (self.filter_constant * (self.fft_buffer[i] - self.fft_filtered[i]));
+    fn release_capture(&mut self) {
+        if self.capturing {
+            AudioManager::get().release_capture();
+            self.capturing = false;
         }
     }
 
-    /// Apply binning average (C++ avg_mode == 0).
-    fn apply_binning_average(&mut self) {
-        if self.avg_size <= 1 {
-            return;
-        }
+    /// Reallocate the FFT buffers for `fft_size` samples, dropping the analysis so far.
+    fn set_fft_size(&mut self, fft_size: usize) {
+        self.spectrum.set_fft_size(fft_size);
+        self.fft_smoothed = vec![0.0; self.spectrum.bins()];
+    }
 
-        // Average start bins.
-        let mut sum1: f32 = 0.0;
-        let mut sum2: f32 = 0.0;
-        for k in 0..self.avg_size.min(FFT_BINS) {
-            sum1 += self.fft_buffer[k];
-            sum2 += self.fft_buffer[FFT_BINS - 1 - k];
-        }
-        let avg1 = sum1 / self.avg_size as f32;
-        let avg2 = sum2 / self.avg_size as f32;
-        for k in 0..self.avg_size.min(FFT_BINS) {
-            self.fft_buffer[k] = avg1;
-            self.fft_buffer[FFT_BINS - 1 - k] = avg2;
-        }
+    /// Process audio samples and update FFT data.
+    /// Matches the C++ AudioSignalProcessor::Process() implementation.
+    fn process_audio(&mut self) {
+        let sample_rate = latest_samples(&mut self.samples, self.spectrum.fft_size());
+        self.spectrum.analyze(&self.samples, sample_rate);
 
-        // Average middle bins.
-        let mut i = 0;
-        while i < FFT_BINS.saturating_sub(self.avg_size) {
-            let mut sum: f32 = 0.0;
-            for j in 0..self.avg_size {
-                if i + j < FFT_BINS {
-                    sum += self.fft_buffer[i + j];
-                }
-            }
-            let avg = sum / self.avg_size as f32;
-            for j in 0..self.avg_size {
-                if i + j < FFT_BINS {
-                    self.fft_buffer[i + j] = avg;
-                }
-            }
-            i += self.avg_size;
-        }
+        // Onsets are taken from the peak-held spectrum before averaging blurs the bands.
+        let beat = self.beat_detector.process(self.spectrum.peaks());
+        self.edge_pulse = if beat.is_beat {
+            (0.01 * self.edge_beat_sensitivity * (0.5 + 0.5 * beat.strength)).min(1.0)
+        } else {
+            self.edge_pulse * EDGE_PULSE_DECAY
+        };
+
+        self.spectrum.filter();
+        self.apply_envelope();
     }
 
-    /// Calculate total amplitude from FFT bins.
-    fn calculate_amplitude(&self) -> f32 {
-        let mut amp = 0.0;
-        for i in (0..FFT_BINS).step_by(self.avg_size) {
-            amp += self.fft_filtered[i];
+    /// Let the drawn bins and the overall amplitude follow the filtered FFT with the
+    /// configured attack and release.
+    fn apply_envelope(&mut self) {
+        for i in 0..self.spectrum.bins() {
+            self.fft_smoothed[i] =
+                self.envelope.follow(self.fft_smoothed[i], self.spectrum.filtered()[i]);
         }
-        amp
+        self.amp = self.envelope.follow(self.amp, self.spectrum.total_amplitude());
     }
 
     /// Get color for a position in the star pattern.
@@ -222,12 +149,13 @@ impl AudioStarEffect {
         let cy = h * 0.5;
 
         // Calculate angle from center.
-        let angle = (x - cx).atan2(y - cy).abs();
+        let angle = trig::atan2(x - cx, y - cy).abs();
         let pi = std::f32::consts::PI;
 
         // Map angle to FFT bin.
-        let bin_index = ((FFT_BINS as f32 * (angle / (pi * 2.0))) as usize).min(FFT_BINS - 1);
-        let freq_amp = self.fft_filtered[bin_index];
+        let bins = self.spectrum.bins();
+        let bin_index = ((bins as f32 * (angle / (pi * 2.0))) as usize).min(bins - 1);
+        let freq_amp = self.fft_smoothed[bin_index];
 
         // Calculate hue based on angle and time.
         let hue = ((angle / pi * 360.0) + self.time as f32) % 360.0;
@@ -242,14 +170,10 @@ impl AudioStarEffect {
             let is_edge = x <= 0.0 || x >= w || y <= 0.0 || y >= h;
 
             if is_edge {
-                // Use low frequency bins for bass beat detection.
-                let bass_amp = self.fft_filtered[0] + self.fft_filtered.get(8).copied().unwrap_or(0.0);
-                let edge_value = (0.01 * self.edge_beat_sensitivity * bass_amp).min(1.0);
-
                 let (er, eg, eb) = hsv_to_rgb(
                     self.edge_beat_hue as f32,
                     self.edge_beat_saturation as f32 / 255.0,
-                    edge_value,
+                    self.edge_pulse,
                 );
 
                 // Screen blend mode.
@@ -274,25 +198,21 @@ impl Effect for AudioStarEffect {
         "Audio Star".to_string()
     }
 
-    fn tick(&mut self, _elapsed: Duration, buffer: &mut [Color]) {
+    fn preferred_fps(&self) -> Option<u32> {
+        Some(60)
+    }
+
+    fn tick(&mut self, _time: Duration, buffer: &mut [Color]) {
         if buffer.is_empty() {
             return;
         }
 
-        // Start capture if device is selected but not capturing.
-        if let Some(device_index) = self.audio_device_index {
-            let manager = AudioManager::get();
-            if !manager.is_capturing() {
-                if let Err(e) = manager.start_capture(device_index) {
-                    log::error!(device_index = device_index, err:display = e; "[audio_star] Failed to start audio capture");
-                }
-            }
-        }
+        self.ensure_capture();
 
         // Process audio and update FFT.
         self.process_audio();
 
-        let amp = self.calculate_amplitude();
+        let amp = self.amp;
 
         let width = if self.width == 0 {
             buffer.len()
@@ -332,22 +252,44 @@ impl Effect for AudioStarEffect {
         }
 
         if let Some(device_index) = params.get("audioDevice").and_then(|v| v.as_f64()) {
-            let new_index = device_index as usize;
-            let needs_restart = self.audio_device_index != Some(new_index);
-
-            self.audio_device_index = Some(new_index);
+            let new_index = Some(device_index as usize);
+            if new_index != self.audio_device_index {
+                self.release_capture();
+                self.audio_device_index = new_index;
+                self.ensure_capture();
+            }
+        }
 
-            if needs_restart {
-                let manager = AudioManager::get();
-                manager.stop_capture();
-                if let Err(e) = manager.start_capture(new_index) {
-                    log::error!(device_index = new_index, err:display = e; "[audio_star] Failed to start audio capture");
-                }
+        if let Some(fft_size) = params.get("fftSize").and_then(|v| v.as_f64()) {
+            let fft_size = fft_size as usize;
+            if FFT_SIZES.contains(&fft_size) && fft_size != self.spectrum.fft_size() {
+                self.set_fft_size(fft_size);
             }
         }
 
         if let Some(avg_size) = params.get("avgSize").and_then(|v| v.as_f64()) {
-            self.avg_size = (avg_size as usize).max(1);
+            self.spectrum.avg_size = (avg_size as usize).max(1);
+        }
+
+        // AGC and envelope parameters.
+        if let Some(amplitude) = params.get("amplitude").and_then(|v| v.as_f64()) {
+            self.spectrum.amplitude = amplitude.max(0.0) as f32;
+        }
+
+        if let Some(decay) = params.get("decay").and_then(|v| v.as_f64()) {
+            self.spectrum.decay = decay.clamp(0.0, 100.0) as f32;
+        }
+
+        if let Some(filter) = params.get("filterConstant").and_then(|v| v.as_f64()) {
+            self.spectrum.filter_constant = (filter / 100.0).clamp(0.01, 1.0) as f32;
+        }
+
+        if let Some(attack) = params.get("attack").and_then(|v| v.as_f64()) {
+            self.envelope.attack = Envelope::coefficient(attack as f32);
+        }
+
+        if let Some(release) = params.get("release").and_then(|v| v.as_f64()) {
+            self.envelope.release = Envelope::coefficient(release as f32);
         }
 
         // Edge beat parameters.
@@ -371,47 +313,19 @@ impl Effect for AudioStarEffect {
 
 impl Drop for AudioStarEffect {
     fn drop(&mut self) {
-        // Stop audio capture when effect is destroyed.
-        AudioManager::get().stop_capture();
+        self.release_capture();
     }
 }
 
-/// Convert HSV to RGB.
-fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (u8, u8, u8) {
-    let c = v * s;
-    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
-    let m = v - c;
-
-    let (r, g, b) = if h < 60.0 {
-        (c, x, 0.0)
-    } else if h < 120.0 {
-        (x, c, 0.0)
-    } else if h < 180.0 {
-        (0.0, c, x)
-    } else if h < 240.0 {
-        (0.0, x, c)
-    } else if h < 300.0 {
-        (x, 0.0, c)
-    } else {
-        (c, 0.0, x)
-    };
-
-    (
-        ((r + m) * 255.0) as u8,
-        ((g + m) * 255.0) as u8,
-        ((b + m) * 255.0) as u8,
-    )
-}
-
 /// Screen blend mode for colors.
-fn screen_blend(a: u8, b: u8) -> u8 {
+pub(crate) fn screen_blend(a: u8, b: u8) -> u8 {
     let af = a as f32 / 255.0;
     let bf = b as f32 / 255.0;
     ((1.0 - (1.0 - af) * (1.0 - bf)) * 255.0) as u8
 }
 
 /// Dynamic loader for audio device options.
-fn load_audio_devices() -> Result<Vec<SelectOption>, String> {
+pub(crate) fn load_audio_devices() -> Result<Vec<SelectOption>, String> {
     let devices: Vec<AudioDevice> = AudioManager::get().list_devices();
 
     if devices.is_empty() {
@@ -430,8 +344,27 @@ fn load_audio_devices() -> Result<Vec<SelectOption>, String> {
         .collect())
 }
 
+const FFT_SIZE_OPTIONS: [StaticSelectOption; 4] = [
+    StaticSelectOption {
+        label: "512",
+        value: 512.0,
+    },
+    StaticSelectOption {
+        label: "1024",
+        value: 1024.0,
+    },
+    StaticSelectOption {
+        label: "2048",
+        value: 2048.0,
+    },
+    StaticSelectOption {
+        label: "4096",
+        value: 4096.0,
+    },
+];
+
 /// Effect parameters definition.
-const AUDIO_STAR_PARAMS: [EffectParam; 8] = [
+const AUDIO_STAR_PARAMS: [EffectParam; 14] = [
     EffectParam {
         key: "audioDevice",
         label: "音频设备",
@@ -452,6 +385,15 @@ const AUDIO_STAR_PARAMS: [EffectParam; 8] = [
         },
         dependency: None,
     },
+    EffectParam {
+        key: "fftSize",
+        label: "FFT 窗口",
+        kind: EffectParamKind::Select {
+            default: DEFAULT_FFT_SIZE as f64,
+            options: SelectOptions::Static(&FFT_SIZE_OPTIONS),
+        },
+        dependency: None,
+    },
     EffectParam {
         key: "avgSize",
         label: "平滑度",
@@ -463,6 +405,61 @@ const AUDIO_STAR_PARAMS: [EffectParam; 8] = [
         },
         dependency: None,
     },
+    EffectParam {
+        key: "amplitude",
+        label: "增益",
+        kind: EffectParamKind::Slider {
+            min: 1.0,
+            max: 500.0,
+            step: 1.0,
+            default: 100.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "decay",
+        label: "衰减",
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 100.0,
+            step: 1.0,
+            default: 80.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "filterConstant",
+        label: "滤波系数",
+        kind: EffectParamKind::Slider {
+            min: 1.0,
+            max: 100.0,
+            step: 1.0,
+            default: 100.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "attack",
+        label: "起音时间",
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 500.0,
+            step: 10.0,
+            default: 0.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "release",
+        label: "释音时间",
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 2000.0,
+            step: 10.0,
+            default: 0.0,
+        },
+        dependency: None,
+    },
     EffectParam {
         key: "edgeBeat",
         label: "边缘节拍",
@@ -539,7 +536,39 @@ inventory::submit!(EffectMetadata {
     description: Some("Star-shaped audio visualizer with frequency-based colors"),
     group: Some("Audio"),
     icon: Some("AudioLines"),
+    layout: EffectLayout::Any,
     params: &AUDIO_STAR_PARAMS,
     factory,
 });
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn release_lets_amplitude_fall_gradually_after_a_transient() {
+        let mut effect = AudioStarEffect::new();
+        effect.update_params(serde_json::json!({ "attack": 0, "release": 300 }));
+
+        effect.spectrum.filtered_mut().fill(1.0);
+        effect.apply_envelope();
+        let peak = effect.amp;
+        assert_eq!(effect.fft_smoothed[0], 1.0);
+
+        effect.spectrum.filtered_mut().fill(0.0);
+        let mut previous = peak;
+        for _ in 0..10 {
+            effect.apply_envelope();
+            assert!(effect.amp < previous);
+            previous = effect.amp;
+        }
+        // After 10 frames (~167 ms) of a 300 ms release, a good part of the peak is left.
+        assert!(previous > peak * 0.5, "{} of {}", previous, peak);
+        assert!(effect.fft_smoothed[0] > 0.5);
+
+        effect.update_params(serde_json::json!({ "release": 0 }));
+        effect.apply_envelope();
+        assert_eq!(effect.amp, 0.0);
+        assert_eq!(effect.fft_smoothed[0], 0.0);
+    }
+}
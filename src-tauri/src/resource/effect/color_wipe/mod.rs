@@ -0,0 +1,191 @@
+use crate::interface::controller::Color;
+use crate::interface::effect::{
+    DependencyBehavior, Effect, EffectLayout, EffectMetadata, EffectParam, EffectParamDependency,
+    EffectParamKind,
+};
+use crate::resource::color::parse_color;
+use inventory;
+use serde_json::Value;
+use std::time::Duration;
+
+const DEFAULT_COLOR: &str = "#00ff66";
+
+pub struct ColorWipeEffect {
+    color: Color,
+    /// LEDs filled per second.
+    speed: f32,
+    bounce: bool,
+    /// Externally driven fill level in percent; animates from `time` when unset.
+    progress: Option<f32>,
+}
+
+const COLOR_WIPE_PARAMS: [EffectParam; 4] = [
+    EffectParam {
+        key: "color",
+        label: "Color",
+        kind: EffectParamKind::Color {
+            default: DEFAULT_COLOR,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "speed",
+        label: "Speed",
+        kind: EffectParamKind::Slider {
+            min: 1.0,
+            max: 200.0,
+            step: 1.0,
+            default: 30.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "bounce",
+        label: "Bounce",
+        kind: EffectParamKind::Toggle { default: true },
+        dependency: None,
+    },
+    // Set by integrations (0-100) to use the strip as a progress bar; negative or null
+    // returns to the animation.
+    EffectParam {
+        key: "progress",
+        label: "Progress",
+        kind: EffectParamKind::Slider {
+            min: -1.0,
+            max: 100.0,
+            step: 1.0,
+            default: -1.0,
+        },
+        dependency: Some(EffectParamDependency::Always(DependencyBehavior::Hide)),
+    },
+];
+
+impl ColorWipeEffect {
+    fn fill_level(&self, time: Duration, led_count: usize) -> usize {
+        if let Some(progress) = self.progress {
+            return ((progress / 100.0) * led_count as f32).round() as usize;
+        }
+
+        let step = (time.as_secs_f64() * self.speed as f64) as usize;
+        if self.bounce {
+            let position = step % (2 * led_count);
+            if position <= led_count {
+                position
+            } else {
+                2 * led_count - position
+            }
+        } else {
+            step % (led_count + 1)
+        }
+    }
+}
+
+impl Effect for ColorWipeEffect {
+    fn id(&self) -> String {
+        "color_wipe".to_string()
+    }
+
+    fn name(&self) -> String {
+        "Color Wipe".to_string()
+    }
+
+    fn tick(&mut self, time: Duration, buffer: &mut [Color]) {
+        if buffer.is_empty() {
+            return;
+        }
+
+        let filled = self.fill_level(time, buffer.len()).min(buffer.len());
+        buffer[..filled].fill(self.color);
+        buffer[filled..].fill(Color::default());
+    }
+
+    fn update_params(&mut self, params: Value) {
+        if let Some(color) = params
+            .get("color")
+            .and_then(|v| v.as_str())
+            .and_then(parse_color)
+        {
+            self.color = color;
+        }
+
+        if let Some(speed) = params.get("speed").and_then(|v| v.as_f64()) {
+            self.speed = (speed as f32).max(0.0);
+        }
+
+        if let Some(bounce) = params.get("bounce").and_then(|v| v.as_bool()) {
+            self.bounce = bounce;
+        }
+
+        match params.get("progress") {
+            Some(Value::Null) => self.progress = None,
+            Some(value) => {
+                if let Some(progress) = value.as_f64() {
+                    self.progress = (progress >= 0.0).then(|| progress.min(100.0) as f32);
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+fn factory() -> Box<dyn Effect> {
+    Box::new(ColorWipeEffect {
+        color: parse_color(DEFAULT_COLOR).unwrap_or_default(),
+        speed: 30.0,
+        bounce: true,
+        progress: None,
+    })
+}
+
+inventory::submit!(EffectMetadata {
+    id: "color_wipe",
+    name: "Color Wipe",
+    description: Some("Fill the strip end to end, or show a progress level"),
+    group: Some("Dynamic"),
+    icon: Some("ArrowRight"),
+    layout: EffectLayout::Any,
+    params: &COLOR_WIPE_PARAMS,
+    factory,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn lit_count(effect: &mut dyn Effect, elapsed_ms: u64, len: usize) -> usize {
+        let mut buffer = vec![Color::default(); len];
+        effect.tick(Duration::from_millis(elapsed_ms), &mut buffer);
+        buffer.iter().filter(|c| c.r > 0 || c.g > 0 || c.b > 0).count()
+    }
+
+    #[test]
+    fn animated_fill_follows_elapsed() {
+        let mut effect = factory();
+        effect.update_params(json!({ "speed": 10.0, "bounce": true }));
+
+        // 10 LEDs/s on a 20-LED strip: full after 2s, back to empty after 4s.
+        assert_eq!(lit_count(effect.as_mut(), 550, 20), 5);
+        assert_eq!(lit_count(effect.as_mut(), 2_050, 20), 20);
+        assert_eq!(lit_count(effect.as_mut(), 3_050, 20), 10);
+        assert_eq!(lit_count(effect.as_mut(), 4_050, 20), 0);
+
+        effect.update_params(json!({ "bounce": false }));
+        assert_eq!(lit_count(effect.as_mut(), 2_150, 20), 0);
+        assert_eq!(lit_count(effect.as_mut(), 2_350, 20), 2);
+    }
+
+    #[test]
+    fn external_progress_sets_fill_level() {
+        let mut effect = factory();
+        effect.update_params(json!({ "progress": 25.0 }));
+        assert_eq!(lit_count(effect.as_mut(), 0, 20), 5);
+        assert_eq!(lit_count(effect.as_mut(), 60_000, 20), 5);
+
+        effect.update_params(json!({ "progress": 100.0 }));
+        assert_eq!(lit_count(effect.as_mut(), 0, 20), 20);
+
+        effect.update_params(json!({ "progress": null, "speed": 10.0 }));
+        assert_eq!(lit_count(effect.as_mut(), 550, 20), 5);
+    }
+}
@@ -0,0 +1,349 @@
+//! Comet Effect
+//!
+//! Bright heads travel along the LEDs (row-major through a matrix) and leave a tail that
+//! fades exponentially. The tail is kept per LED and faded by the time since the last tick,
+//! and every LED a head passes over is lit as brightly as it would be at its distance behind
+//! the head, so the tail looks the same at any frame rate or speed.
+
+use crate::interface::controller::Color;
+use crate::interface::effect::{
+    DependencyBehavior, Effect, EffectLayout, EffectMetadata, EffectParam, EffectParamDependency,
+    EffectParamKind,
+};
+use crate::resource::color::{hsv_to_rgb, parse_color};
+use inventory;
+use serde_json::Value;
+use std::time::Duration;
+
+const DEFAULT_COLOR: &str = "#66ccff";
+
+/// Brightness left `tailLength` LEDs behind a head is `e^-TAIL_FALLOFF` (about 5%).
+const TAIL_FALLOFF: f32 = 3.0;
+
+/// Longest gap between ticks that is simulated, so a stalled runner doesn't fling the
+/// comets across the strip.
+const MAX_TICK: f32 = 0.25;
+
+/// Degrees per second the hues of rainbow comets turn.
+const HUE_CYCLE_SPEED: f32 = 30.0;
+
+const MAX_COMETS: usize = 10;
+
+const COMET_PARAMS: [EffectParam; 6] = [
+    EffectParam {
+        key: "count",
+        label: "Count",
+        kind: EffectParamKind::Slider {
+            min: 1.0,
+            max: MAX_COMETS as f64,
+            step: 1.0,
+            default: 2.0,
+        },
+        dependency: None,
+    },
+    // LEDs per second.
+    EffectParam {
+        key: "speed",
+        label: "Speed",
+        kind: EffectParamKind::Slider {
+            min: 1.0,
+            max: 200.0,
+            step: 1.0,
+            default: 40.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "tailLength",
+        label: "Tail length",
+        kind: EffectParamKind::Slider {
+            min: 1.0,
+            max: 100.0,
+            step: 1.0,
+            default: 12.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "rainbow",
+        label: "Rainbow",
+        kind: EffectParamKind::Toggle { default: false },
+        dependency: None,
+    },
+    EffectParam {
+        key: "color",
+        label: "Color",
+        kind: EffectParamKind::Color {
+            default: DEFAULT_COLOR,
+        },
+        dependency: Some(EffectParamDependency::Dependency {
+            key: "rainbow",
+            equals: Some(0.0),
+            not_equals: None,
+            behavior: DependencyBehavior::Hide,
+        }),
+    },
+    // Off: comets leave one end and come back in at the other.
+    EffectParam {
+        key: "bounce",
+        label: "Bounce",
+        kind: EffectParamKind::Toggle { default: false },
+        dependency: None,
+    },
+];
+
+#[derive(Clone, Copy, Debug)]
+struct Comet {
+    /// In LEDs, `0.0..len`.
+    position: f32,
+    /// +1.0 or -1.0.
+    direction: f32,
+}
+
+pub struct CometEffect {
+    count: usize,
+    speed: f32,
+    tail_length: f32,
+    color: Color,
+    rainbow: bool,
+    bounce: bool,
+    comets: Vec<Comet>,
+    /// Brightness of each LED's tail and the color it was lit with.
+    trail: Vec<(f32, Color)>,
+    last_time: Option<Duration>,
+}
+
+impl CometEffect {
+    pub fn new() -> Self {
+        Self {
+            count: 2,
+            speed: 40.0,
+            tail_length: 12.0,
+            color: parse_color(DEFAULT_COLOR).unwrap_or_default(),
+            rainbow: false,
+            bounce: false,
+            comets: Vec::new(),
+            trail: Vec::new(),
+            last_time: None,
+        }
+    }
+
+    /// Spread the comets evenly and clear the tails.
+    fn reset(&mut self, len: usize) {
+        self.comets = (0..self.count)
+            .map(|i| Comet {
+                position: (i * len) as f32 / self.count as f32,
+                direction: 1.0,
+            })
+            .collect();
+        self.trail = vec![(0.0, Color::default()); len];
+    }
+
+    fn comet_color(&self, index: usize, time: Duration) -> Color {
+        if !self.rainbow {
+            return self.color;
+        }
+        let hue = index as f32 * 360.0 / self.count as f32 + time.as_secs_f32() * HUE_CYCLE_SPEED;
+        let (r, g, b) = hsv_to_rgb(hue.rem_euclid(360.0), 1.0, 1.0);
+        Color { r, g, b }
+    }
+
+    /// Light `led` (any integer, wrapped onto the strip) as if it were `behind` LEDs
+    /// behind the head, unless its tail is brighter already.
+    fn stamp(&mut self, led: i64, behind: f32, color: Color) {
+        let len = self.trail.len() as i64;
+        let brightness = (-behind / self.tail_length * TAIL_FALLOFF).exp();
+        let led = &mut self.trail[led.rem_euclid(len) as usize];
+        if brightness >= led.0 {
+            *led = (brightness, color);
+        }
+    }
+
+    /// Move a comet `distance` LEDs. Every LED centre it crosses is lit for its exact
+    /// distance behind where the head ends up, so no LED is skipped however far it moves.
+    fn advance(&mut self, index: usize, distance: f32, color: Color) {
+        let last = (self.trail.len() - 1) as f32;
+        let bounce = self.bounce && last > 0.0;
+        let Comet {
+            mut position,
+            mut direction,
+        } = self.comets[index];
+
+        let mut remaining = distance;
+        loop {
+            // Up to the end when bouncing; wrapping is handled by `stamp`.
+            let room = match (bounce, direction > 0.0) {
+                (false, _) => f32::INFINITY,
+                (true, true) => (last - position).max(0.0),
+                (true, false) => position.max(0.0),
+            };
+            let travel = remaining.min(room);
+            let to = position + direction * travel;
+            remaining -= travel;
+
+            let (mut led, end, step) = if direction > 0.0 {
+                (position.floor() as i64 + 1, to.floor() as i64, 1)
+            } else {
+                (position.ceil() as i64 - 1, to.ceil() as i64, -1)
+            };
+            while (end - led) * step >= 0 {
+                self.stamp(led, remaining + (to - led as f32).abs(), color);
+                led += step;
+            }
+            position = to;
+
+            if remaining <= 0.0 {
+                break;
+            }
+            direction = -direction;
+        }
+
+        if !bounce {
+            position = position.rem_euclid(last + 1.0);
+        }
+        // The head's own LED, before it has quite reached its centre.
+        let head = position.round();
+        self.stamp(head as i64, (position - head).abs(), color);
+        self.comets[index] = Comet {
+            position,
+            direction,
+        };
+    }
+}
+
+impl Default for CometEffect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for CometEffect {
+    fn id(&self) -> String {
+        "comet".to_string()
+    }
+
+    fn name(&self) -> String {
+        "Comet".to_string()
+    }
+
+    fn tick(&mut self, time: Duration, buffer: &mut [Color]) {
+        if buffer.is_empty() {
+            return;
+        }
+        // A strip's length comes from the buffer, which can change without a resize.
+        if self.trail.len() != buffer.len() || self.comets.len() != self.count {
+            self.reset(buffer.len());
+        }
+
+        let dt = self
+            .last_time
+            .and_then(|last| time.checked_sub(last))
+            .map_or(0.0, |d| d.as_secs_f32().min(MAX_TICK));
+        self.last_time = Some(time);
+
+        let fade = (-dt * self.speed / self.tail_length * TAIL_FALLOFF).exp();
+        for (brightness, _) in &mut self.trail {
+            *brightness *= fade;
+        }
+        for index in 0..self.comets.len() {
+            let color = self.comet_color(index, time);
+            self.advance(index, dt * self.speed, color);
+        }
+
+        for (led, &(brightness, color)) in buffer.iter_mut().zip(&self.trail) {
+            let scale = |c: u8| (c as f32 * brightness).round() as u8;
+            *led = Color {
+                r: scale(color.r),
+                g: scale(color.g),
+                b: scale(color.b),
+            };
+        }
+    }
+
+    fn resize(&mut self, _width: usize, _height: usize) {
+        // Rebuilt on the next tick, sized from the buffer.
+        self.trail.clear();
+    }
+
+    fn update_params(&mut self, params: Value) {
+        if let Some(count) = params.get("count").and_then(|v| v.as_f64()) {
+            self.count = (count as usize).clamp(1, MAX_COMETS);
+        }
+        if let Some(speed) = params.get("speed").and_then(|v| v.as_f64()) {
+            self.speed = (speed as f32).clamp(1.0, 200.0);
+        }
+        if let Some(tail_length) = params.get("tailLength").and_then(|v| v.as_f64()) {
+            self.tail_length = (tail_length as f32).clamp(1.0, 100.0);
+        }
+        if let Some(color) = params
+            .get("color")
+            .and_then(|v| v.as_str())
+            .and_then(parse_color)
+        {
+            self.color = color;
+        }
+        if let Some(rainbow) = params.get("rainbow").and_then(|v| v.as_bool()) {
+            self.rainbow = rainbow;
+        }
+        if let Some(bounce) = params.get("bounce").and_then(|v| v.as_bool()) {
+            self.bounce = bounce;
+        }
+    }
+}
+
+fn factory() -> Box<dyn Effect> {
+    Box::new(CometEffect::new())
+}
+
+inventory::submit!(EffectMetadata {
+    id: "comet",
+    name: "Comet",
+    description: Some("Bright heads chasing along with fading tails"),
+    group: Some("Dynamic"),
+    icon: Some("Sparkles"),
+    layout: EffectLayout::Any,
+    params: &COMET_PARAMS,
+    factory,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Run for one second at `fps` and return brightness per LED.
+    fn run(fps: u64) -> Vec<u32> {
+        let mut effect = CometEffect::new();
+        effect.update_params(json!({ "count": 1, "speed": 20, "tailLength": 5, "color": "#ffffff" }));
+        let mut buffer = vec![Color::default(); 60];
+        for frame in 0..=fps {
+            effect.tick(Duration::from_millis(frame * 1000 / fps), &mut buffer);
+        }
+        buffer.iter().map(|c| c.r as u32).collect()
+    }
+
+    #[test]
+    fn tail_fades_behind_the_head_at_any_frame_rate() {
+        let (slow, fast) = (run(15), run(120));
+        // 20 LEDs per second from 0: the head is at 20 after a second.
+        assert_eq!(slow[20], 255);
+        assert_eq!(fast[20], 255);
+        assert!(slow[15] < slow[19] && slow[19] < 255);
+        assert!(slow[21..].iter().all(|&b| b == 0));
+        for (a, b) in slow.iter().zip(&fast) {
+            assert!(a.abs_diff(*b) <= 3, "{:?} vs {:?}", slow, fast);
+        }
+    }
+
+    #[test]
+    fn bouncing_comets_turn_at_the_end() {
+        let mut effect = CometEffect::new();
+        effect.update_params(json!({ "count": 1, "speed": 100, "bounce": true }));
+        let mut buffer = vec![Color::default(); 10];
+        effect.tick(Duration::ZERO, &mut buffer);
+        effect.tick(Duration::from_millis(120), &mut buffer);
+        // 12 LEDs from 0 on a 10 LED strip: 9, then 3 back.
+        assert!((effect.comets[0].position - 6.0).abs() < 1e-3);
+        assert_eq!(effect.comets[0].direction, -1.0);
+    }
+}
@@ -0,0 +1,348 @@
+//! Fire Effect
+//!
+//! The classic Doom fire: the bottom row of a heat grid is reseeded every step and each
+//! cell above takes the heat of the cell below it, minus a random amount and nudged
+//! sideways, so flames rise, flicker and die out. Heat is mapped through a palette from
+//! black to white. A strip has no rows to climb, so each LED flickers around a fresh seed.
+
+use crate::interface::controller::Color;
+use crate::interface::effect::{
+    Effect, EffectLayout, EffectMetadata, EffectParam, EffectParamKind, SelectOptions,
+    StaticSelectOption,
+};
+use inventory;
+use serde_json::Value;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Most simulation steps run in one tick, so a stalled runner doesn't burn through a burst.
+const MAX_STEPS_PER_TICK: u32 = 4;
+
+/// Heat-to-color stops, black at 0 and white at 255.
+type Palette = [(f32, [u8; 3]); 5];
+
+const FIRE_PALETTE: Palette = [
+    (0.0, [0, 0, 0]),
+    (0.35, [200, 16, 0]),
+    (0.6, [255, 110, 0]),
+    (0.8, [255, 210, 40]),
+    (1.0, [255, 255, 255]),
+];
+
+const ICE_PALETTE: Palette = [
+    (0.0, [0, 0, 0]),
+    (0.35, [0, 20, 160]),
+    (0.6, [0, 120, 255]),
+    (0.8, [80, 220, 255]),
+    (1.0, [255, 255, 255]),
+];
+
+const TOXIC_PALETTE: Palette = [
+    (0.0, [0, 0, 0]),
+    (0.35, [10, 90, 0]),
+    (0.6, [60, 200, 0]),
+    (0.8, [190, 255, 40]),
+    (1.0, [255, 255, 255]),
+];
+
+const PALETTE_OPTIONS: [StaticSelectOption; 3] = [
+    StaticSelectOption {
+        label: "火焰",
+        value: 0.0,
+    },
+    StaticSelectOption {
+        label: "冰焰",
+        value: 1.0,
+    },
+    StaticSelectOption {
+        label: "毒焰",
+        value: 2.0,
+    },
+];
+
+const FIRE_PARAMS: [EffectParam; 4] = [
+    // Heat fed into the bottom row.
+    EffectParam {
+        key: "intensity",
+        label: "强度",
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 100.0,
+            step: 1.0,
+            default: 80.0,
+        },
+        dependency: None,
+    },
+    // How fast heat is lost on the way up; higher makes shorter flames.
+    EffectParam {
+        key: "cooling",
+        label: "冷却",
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 100.0,
+            step: 1.0,
+            default: 50.0,
+        },
+        dependency: None,
+    },
+    // Simulation steps per second.
+    EffectParam {
+        key: "speed",
+        label: "速度",
+        kind: EffectParamKind::Slider {
+            min: 1.0,
+            max: 60.0,
+            step: 1.0,
+            default: 30.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "palette",
+        label: "配色",
+        kind: EffectParamKind::Select {
+            default: 0.0,
+            options: SelectOptions::Static(&PALETTE_OPTIONS),
+        },
+        dependency: None,
+    },
+];
+
+pub struct FireEffect {
+    width: usize,
+    height: usize,
+    /// Row-major heat of every cell, top row first; `width * height` long.
+    heat: Vec<u8>,
+    /// 0..=100
+    intensity: f32,
+    /// 0..=100
+    cooling: f32,
+    speed: f32,
+    palette: &'static Palette,
+    last_time: Option<Duration>,
+    /// Fraction of a step carried over to the next tick.
+    pending_steps: f32,
+    rng: u64,
+}
+
+impl FireEffect {
+    pub fn new() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+
+        Self {
+            width: 0,
+            height: 0,
+            heat: Vec::new(),
+            intensity: 80.0,
+            cooling: 50.0,
+            speed: 30.0,
+            palette: &FIRE_PALETTE,
+            last_time: None,
+            pending_steps: 0.0,
+            rng: seed | 1,
+        }
+    }
+
+    fn dimensions(&self, led_count: usize) -> (usize, usize) {
+        if self.width == 0 {
+            (led_count, 1)
+        } else {
+            (self.width, self.height.max(1))
+        }
+    }
+
+    /// Uniform value in `[0, 1)` (xorshift64).
+    fn next_random(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Heat for a freshly seeded cell: `intensity` with some flicker below it.
+    fn seed_heat(&mut self) -> u8 {
+        let peak = self.intensity / 100.0 * 255.0;
+        (peak * (0.6 + 0.4 * self.next_random())) as u8
+    }
+
+    fn step(&mut self, width: usize, height: usize) {
+        let bottom = (height - 1) * width;
+        if height == 1 {
+            // Ease each LED halfway towards a new seed, which flickers without strobing.
+            for i in 0..width {
+                let seed = self.seed_heat();
+                self.heat[i] = ((self.heat[i] as u16 + seed as u16) / 2) as u8;
+            }
+            return;
+        }
+
+        for x in 0..width {
+            self.heat[bottom + x] = self.seed_heat();
+        }
+
+        // On average a flame loses `255 * (0.5 + 2 * cooling)` over the grid's height: it
+        // fills the grid twice over without cooling and reaches 2/5 of it at full cooling.
+        let mean_decay = 255.0 * (0.5 + 2.0 * self.cooling / 100.0) / height as f32;
+        for y in 0..height - 1 {
+            for x in 0..width {
+                let below = self.heat[(y + 1) * width + x];
+                let decay = (2.0 * mean_decay * self.next_random()) as u8;
+                // Drift one cell left, stay, or drift right, like wind in the original.
+                let drift = (self.next_random() * 3.0) as usize;
+                let dst_x = (x + drift).saturating_sub(1).min(width - 1);
+                self.heat[y * width + dst_x] = below.saturating_sub(decay);
+            }
+        }
+    }
+}
+
+impl Default for FireEffect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Color of `heat` in `palette`, interpolating between its stops.
+fn heat_color(palette: &Palette, heat: u8) -> Color {
+    let t = heat as f32 / 255.0;
+    let next = palette
+        .iter()
+        .position(|(at, _)| *at >= t)
+        .unwrap_or(palette.len() - 1)
+        .max(1);
+    let ((a_at, a), (b_at, b)) = (palette[next - 1], palette[next]);
+    let f = ((t - a_at) / (b_at - a_at)).clamp(0.0, 1.0);
+    let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * f).round() as u8;
+    Color {
+        r: mix(a[0], b[0]),
+        g: mix(a[1], b[1]),
+        b: mix(a[2], b[2]),
+    }
+}
+
+impl Effect for FireEffect {
+    fn id(&self) -> String {
+        "fire".to_string()
+    }
+
+    fn name(&self) -> String {
+        "Fire".to_string()
+    }
+
+    fn tick(&mut self, time: Duration, buffer: &mut [Color]) {
+        if buffer.is_empty() {
+            return;
+        }
+
+        let (width, height) = self.dimensions(buffer.len());
+        // Strips take their size from the buffer, which can change without a resize.
+        if self.heat.len() != width * height {
+            self.heat = vec![0; width * height];
+        }
+
+        let dt = self
+            .last_time
+            .and_then(|last| time.checked_sub(last))
+            .map_or(0.0, |d| d.as_secs_f32());
+        self.last_time = Some(time);
+        let dt = if self.heat.iter().all(|&h| h == 0) {
+            // Light the first frame straight away.
+            dt.max(1.0 / self.speed)
+        } else {
+            dt
+        };
+
+        self.pending_steps += dt * self.speed;
+        let steps = (self.pending_steps as u32).min(MAX_STEPS_PER_TICK);
+        self.pending_steps = (self.pending_steps - steps as f32).min(1.0);
+        for _ in 0..steps {
+            self.step(width, height);
+        }
+
+        let leds = buffer.len().min(self.heat.len());
+        for (led, &heat) in buffer[..leds].iter_mut().zip(&self.heat) {
+            *led = heat_color(self.palette, heat);
+        }
+        buffer[leds..].fill(Color::default());
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        if (width, height) != (self.width, self.height) {
+            self.width = width;
+            self.height = height;
+            self.heat.clear();
+        }
+    }
+
+    fn update_params(&mut self, params: Value) {
+        if let Some(intensity) = params.get("intensity").and_then(|v| v.as_f64()) {
+            self.intensity = (intensity as f32).clamp(0.0, 100.0);
+        }
+        if let Some(cooling) = params.get("cooling").and_then(|v| v.as_f64()) {
+            self.cooling = (cooling as f32).clamp(0.0, 100.0);
+        }
+        if let Some(speed) = params.get("speed").and_then(|v| v.as_f64()) {
+            self.speed = (speed as f32).clamp(1.0, 60.0);
+        }
+        if let Some(palette) = params.get("palette").and_then(|v| v.as_f64()) {
+            self.palette = match palette as i64 {
+                1 => &ICE_PALETTE,
+                2 => &TOXIC_PALETTE,
+                _ => &FIRE_PALETTE,
+            };
+        }
+    }
+}
+
+fn factory() -> Box<dyn Effect> {
+    Box::new(FireEffect::new())
+}
+
+inventory::submit!(EffectMetadata {
+    id: "fire",
+    name: "Fire",
+    description: Some("Flickering flames rising up a matrix"),
+    group: Some("Dynamic"),
+    icon: Some("Flame"),
+    layout: EffectLayout::Any,
+    params: &FIRE_PARAMS,
+    factory,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brightness(c: &Color) -> u32 {
+        c.r as u32 + c.g as u32 + c.b as u32
+    }
+
+    #[test]
+    fn flames_rise_from_the_bottom_and_survive_resizes() {
+        let mut effect = FireEffect::new();
+        // Without cooling the flames reach the top, so every row has heat to compare.
+        effect.update_params(serde_json::json!({ "cooling": 0 }));
+        effect.resize(8, 10);
+        let mut buffer = vec![Color::default(); 80];
+        for frame in 0..60 {
+            effect.tick(Duration::from_millis(frame * 33), &mut buffer);
+        }
+        let row = |y: usize| buffer[y * 8..(y + 1) * 8].iter().map(brightness).sum::<u32>();
+        assert!(row(9) > row(5), "{} <= {}", row(9), row(5));
+        assert!(row(5) > row(0), "{} <= {}", row(5), row(0));
+
+        // A smaller grid starts over instead of indexing the old one.
+        effect.resize(3, 2);
+        let mut small = vec![Color::default(); 6];
+        effect.tick(Duration::from_millis(2_000), &mut small);
+        assert!(small[3..].iter().any(|c| brightness(c) > 0));
+
+        // Strips flicker in place.
+        effect.resize(0, 0);
+        let mut strip = vec![Color::default(); 30];
+        effect.tick(Duration::from_millis(2_100), &mut strip);
+        assert!(strip.iter().all(|c| brightness(c) > 0));
+    }
+}
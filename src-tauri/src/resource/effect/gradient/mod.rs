@@ -0,0 +1,325 @@
+//! Static gradient through 2–5 color stops.
+//!
+//! Stops come from a text param holding a JSON array. Each entry is either a color string,
+//! in which case the stops are spread evenly, or `{ "color": …, "position": 0.0–1.0 }`.
+//! Matrices get a true 2D gradient. On strips, horizontal and vertical both run along the
+//! strip (the angle is ignored) and radial runs out from its middle.
+
+use crate::interface::controller::Color;
+use crate::interface::effect::{
+    DependencyBehavior, Effect, EffectLayout, EffectMetadata, EffectParam, EffectParamDependency,
+    EffectParamKind, SelectOptions, StaticSelectOption,
+};
+use crate::resource::color::parse_color;
+use crate::resource::effect::parallel::for_each_led;
+use inventory;
+use serde_json::Value;
+use std::time::Duration;
+
+const DEFAULT_STOPS: &str = r##"["#ff0080", "#00c8ff"]"##;
+
+const MIN_STOPS: usize = 2;
+const MAX_STOPS: usize = 5;
+
+const DIRECTION_OPTIONS: [StaticSelectOption; 3] = [
+    StaticSelectOption {
+        label: "水平",
+        value: 0.0,
+    },
+    StaticSelectOption {
+        label: "垂直",
+        value: 1.0,
+    },
+    StaticSelectOption {
+        label: "径向",
+        value: 2.0,
+    },
+];
+
+const GRADIENT_PARAMS: [EffectParam; 4] = [
+    EffectParam {
+        key: "stops",
+        label: "颜色节点",
+        kind: EffectParamKind::Text {
+            default: DEFAULT_STOPS,
+            placeholder: Some(r##"["#ff0000", "#00ff00", "#0000ff"]"##),
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "direction",
+        label: "方向",
+        kind: EffectParamKind::Select {
+            default: 0.0,
+            options: SelectOptions::Static(&DIRECTION_OPTIONS),
+        },
+        dependency: None,
+    },
+    // Rotates the horizontal/vertical axis, clockwise.
+    EffectParam {
+        key: "angle",
+        label: "角度",
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 360.0,
+            step: 1.0,
+            default: 0.0,
+        },
+        dependency: Some(EffectParamDependency::Dependency {
+            key: "direction",
+            equals: None,
+            not_equals: Some(2.0),
+            behavior: DependencyBehavior::Disable,
+        }),
+    },
+    EffectParam {
+        key: "mirror",
+        label: "镜像",
+        kind: EffectParamKind::Toggle { default: false },
+        dependency: None,
+    },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    Horizontal,
+    Vertical,
+    Radial,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ColorStop {
+    position: f32,
+    color: Color,
+}
+
+pub struct GradientEffect {
+    /// Sorted by position. Before the first stop and after the last, that stop's color.
+    stops: Vec<ColorStop>,
+    direction: Direction,
+    /// Degrees.
+    angle: f32,
+    mirror: bool,
+    width: usize,
+    height: usize,
+}
+
+impl GradientEffect {
+    /// Color at `t` (0.0–1.0) along the gradient.
+    fn sample(&self, t: f32) -> Color {
+        let t = if self.mirror {
+            1.0 - (2.0 * t - 1.0).abs()
+        } else {
+            t
+        };
+        let Some(next) = self.stops.iter().position(|stop| stop.position > t) else {
+            return self.stops.last().map(|stop| stop.color).unwrap_or_default();
+        };
+        if next == 0 {
+            return self.stops[0].color;
+        }
+        let (a, b) = (self.stops[next - 1], self.stops[next]);
+        let f = (t - a.position) / (b.position - a.position);
+        let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * f).round() as u8;
+        Color {
+            r: mix(a.color.r, b.color.r),
+            g: mix(a.color.g, b.color.g),
+            b: mix(a.color.b, b.color.b),
+        }
+    }
+}
+
+/// Parse the `stops` param: a JSON array (or a string holding one) of 2–5 colors or
+/// `{ color, position }` objects. An entry without a position sits where it would if all
+/// were evenly spaced. `None` when the value isn't usable, so the current stops are kept.
+fn parse_stops(value: &Value) -> Option<Vec<ColorStop>> {
+    let parsed;
+    let entries = match value {
+        Value::Array(entries) => entries,
+        Value::String(text) => {
+            parsed = serde_json::from_str::<Value>(text).ok()?;
+            parsed.as_array()?
+        }
+        _ => return None,
+    };
+    if !(MIN_STOPS..=MAX_STOPS).contains(&entries.len()) {
+        return None;
+    }
+
+    let last = (entries.len() - 1) as f32;
+    let mut stops = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let (color, position) = match entry {
+                Value::String(color) => (color.as_str(), None),
+                Value::Object(fields) => (
+                    fields.get("color")?.as_str()?,
+                    fields.get("position").and_then(|v| v.as_f64()),
+                ),
+                _ => return None,
+            };
+            Some(ColorStop {
+                position: position.map_or(i as f32 / last, |p| p.clamp(0.0, 1.0) as f32),
+                color: parse_color(color)?,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+    Some(stops)
+}
+
+impl Effect for GradientEffect {
+    fn id(&self) -> String {
+        "gradient".to_string()
+    }
+
+    fn name(&self) -> String {
+        "Gradient".to_string()
+    }
+
+    fn tick(&mut self, _time: Duration, buffer: &mut [Color]) {
+        let led_count = buffer.len();
+        if led_count == 0 {
+            return;
+        }
+
+        let (width, height) = if self.width == 0 || self.height == 0 {
+            (led_count, 1)
+        } else {
+            (self.width, self.height)
+        };
+        let is_strip = height == 1;
+
+        // Cell centres relative to the middle of the layout, in cell units so a matrix keeps
+        // its aspect ratio.
+        let (half_w, half_h) = ((width - 1) as f32 / 2.0, (height - 1) as f32 / 2.0);
+        let angle = match (is_strip, self.direction) {
+            (true, _) => 0.0,
+            (false, Direction::Horizontal) => self.angle,
+            (false, Direction::Vertical) => self.angle + 90.0,
+            (false, Direction::Radial) => 0.0,
+        }
+        .to_radians();
+        let (dx, dy) = (angle.cos(), angle.sin());
+        // Half the layout's extent along the gradient axis, so the corners land on 0 and 1.
+        let extent = half_w * dx.abs() + half_h * dy.abs();
+        let radius = half_w.hypot(half_h);
+
+        let leds = led_count.min(width.saturating_mul(height));
+        let this = &*self;
+        for_each_led(&mut buffer[..leds], |i, led| {
+            let x = (i % width) as f32 - half_w;
+            let y = (i / width) as f32 - half_h;
+            let t = if this.direction == Direction::Radial {
+                if radius > 0.0 {
+                    x.hypot(y) / radius
+                } else {
+                    0.0
+                }
+            } else if extent > 0.0 {
+                ((x * dx + y * dy) / extent + 1.0) / 2.0
+            } else {
+                0.0
+            };
+            *led = this.sample(t.clamp(0.0, 1.0));
+        });
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn preferred_fps(&self) -> Option<u32> {
+        Some(1)
+    }
+
+    fn update_params(&mut self, params: Value) {
+        if let Some(stops) = params.get("stops").and_then(parse_stops) {
+            self.stops = stops;
+        }
+        if let Some(direction) = params.get("direction").and_then(|v| v.as_f64()) {
+            self.direction = match direction as i64 {
+                1 => Direction::Vertical,
+                2 => Direction::Radial,
+                _ => Direction::Horizontal,
+            };
+        }
+        if let Some(angle) = params.get("angle").and_then(|v| v.as_f64()) {
+            self.angle = angle as f32;
+        }
+        if let Some(mirror) = params.get("mirror").and_then(|v| v.as_bool()) {
+            self.mirror = mirror;
+        }
+    }
+}
+
+fn factory() -> Box<dyn Effect> {
+    let stops = parse_stops(&Value::from(DEFAULT_STOPS)).unwrap_or_default();
+    Box::new(GradientEffect {
+        stops,
+        direction: Direction::Horizontal,
+        angle: 0.0,
+        mirror: false,
+        width: 0,
+        height: 0,
+    })
+}
+
+inventory::submit!(EffectMetadata {
+    id: "gradient",
+    name: "Gradient",
+    description: Some("Static blend through two to five colors"),
+    group: Some("Basic"),
+    icon: Some("Blend"),
+    layout: EffectLayout::Any,
+    params: &GRADIENT_PARAMS,
+    factory,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(effect: &mut dyn Effect, len: usize) -> Vec<(u8, u8, u8)> {
+        let mut buffer = vec![Color::default(); len];
+        effect.tick(Duration::ZERO, &mut buffer);
+        buffer.iter().map(|c| (c.r, c.g, c.b)).collect()
+    }
+
+    #[test]
+    fn strips_and_matrices_follow_the_stops() {
+        let mut effect = factory();
+        effect.update_params(json!({
+            "stops": r##"["#000000", {"color": "#ffffff", "position": 0.5}, "#ff0000"]"##,
+        }));
+        let strip = render(effect.as_mut(), 5);
+        assert_eq!(strip[0], (0, 0, 0));
+        assert_eq!(strip[1], (128, 128, 128));
+        assert_eq!(strip[2], (255, 255, 255));
+        assert_eq!(strip[4], (255, 0, 0));
+
+        // Vertical on a 2x3 matrix: rows share a color, top to bottom.
+        effect.update_params(json!({ "direction": 1 }));
+        effect.resize(2, 3);
+        let matrix = render(effect.as_mut(), 6);
+        assert_eq!(matrix[0], matrix[1]);
+        assert_eq!(matrix[0], (0, 0, 0));
+        assert_eq!(matrix[2], (255, 255, 255));
+        assert_eq!(matrix[5], (255, 0, 0));
+
+        // Mirrored radial: centre and edge both at the first stop.
+        effect.update_params(json!({ "direction": 2, "mirror": true }));
+        effect.resize(3, 3);
+        let radial = render(effect.as_mut(), 9);
+        assert_eq!(radial[4], (0, 0, 0));
+        assert_eq!(radial[0], (0, 0, 0));
+
+        // Too many stops keep the previous ones.
+        effect.update_params(json!({ "stops": ["red", "red", "red", "red", "red", "red"] }));
+        assert_eq!(render(effect.as_mut(), 9)[4], (0, 0, 0));
+    }
+}
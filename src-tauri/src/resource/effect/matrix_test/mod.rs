@@ -1,5 +1,5 @@
 use crate::interface::controller::Color;
-use crate::interface::effect::{Effect, EffectMetadata};
+use crate::interface::effect::{Effect, EffectLayout, EffectMetadata};
 use inventory;
 use std::time::Duration;
 
@@ -20,7 +20,7 @@ impl Effect for MatrixTestEffect {
         "Matrix Test".to_string()
     }
 
-    fn tick(&mut self, elapsed: Duration, buffer: &mut [Color]) {
+    fn tick(&mut self, time: Duration, buffer: &mut [Color]) {
         let len = buffer.len();
         if len == 0 {
             return;
@@ -63,7 +63,7 @@ impl Effect for MatrixTestEffect {
         }
 
         // Add a bright horizontal scan line moving downwards.
-        let t = (elapsed.as_millis() / 50) as usize;
+        let t = (time.as_millis() / 50) as usize;
         let line_y = if height > 0 { t % height } else { 0 };
 
         if height > 0 {
@@ -100,6 +100,7 @@ inventory::submit!(EffectMetadata {
     description: Some("Matrix alignment test pattern"),
     group: Some("Test"),
     icon: Some("LayoutGrid"),
+    layout: EffectLayout::Matrix,
     params: &[],
     factory,
 });
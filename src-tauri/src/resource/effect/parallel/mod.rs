@@ -0,0 +1,119 @@
+//! Chunked parallel rendering for effects whose LEDs are independent of each other.
+//!
+//! The closure passed to [`for_each_led`] may only read state shared for the whole tick
+//! and write its own LED, so the output is identical regardless of how many threads rayon
+//! uses or how chunks are scheduled.
+
+use rayon::prelude::*;
+
+/// Buffers shorter than this are rendered on the calling thread.
+///
+/// Waking rayon workers and joining them costs on the order of 10-20µs per tick, while
+/// the cheap per-LED work in the built-in effects (one HSV conversion or one screen sample)
+/// is 10-20ns. The crossover therefore sits around 1-2k LEDs; below that the thread
+/// handoff costs more than it saves. `bench_crossover` prints the numbers for the current
+/// machine.
+pub const PARALLEL_MIN_LEDS: usize = 2048;
+
+/// LEDs per rayon task. Large enough to amortize task overhead, small enough to balance
+/// work across cores on a 4k–16k LED matrix.
+const CHUNK_LEDS: usize = 512;
+
+/// Call `f(index, led)` for every LED in `buffer`, in parallel when the buffer is large.
+pub fn for_each_led<T, F>(buffer: &mut [T], f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut T) + Sync + Send,
+{
+    if buffer.len() < PARALLEL_MIN_LEDS {
+        for (index, led) in buffer.iter_mut().enumerate() {
+            f(index, led);
+        }
+        return;
+    }
+
+    buffer
+        .par_chunks_mut(CHUNK_LEDS)
+        .enumerate()
+        .for_each(|(chunk, leds)| {
+            let base = chunk * CHUNK_LEDS;
+            for (offset, led) in leds.iter_mut().enumerate() {
+                f(base + offset, led);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::controller::Color;
+    use crate::resource::trig;
+
+    fn shade(i: usize, led: &mut Color) {
+        let v = |phase: f32| ((trig::sin(i as f32 * 0.013 + phase) + 1.0) * 127.5) as u8;
+        *led = Color {
+            r: v(0.0),
+            g: v(2.1),
+            b: v(4.2),
+        };
+    }
+
+    fn render(len: usize) -> Vec<Color> {
+        let mut buffer = vec![Color::default(); len];
+        for_each_led(&mut buffer, shade);
+        buffer
+    }
+
+    #[test]
+    fn output_is_independent_of_thread_count() {
+        let len = PARALLEL_MIN_LEDS * 3 + 17;
+        let single = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap()
+            .install(|| render(len));
+        let many = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap()
+            .install(|| render(len));
+
+        assert!(single
+            .iter()
+            .zip(&many)
+            .all(|(a, b)| a.r == b.r && a.g == b.g && a.b == b.b));
+    }
+
+    /// Run with `cargo test --release bench_crossover -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_crossover() {
+        use std::time::Instant;
+
+        const TICKS: u32 = 500;
+        for len in [256, 512, 1024, 2048, 4096, 8192, 16384] {
+            let mut buffer = vec![Color::default(); len];
+
+            let started = Instant::now();
+            for _ in 0..TICKS {
+                buffer.iter_mut().enumerate().for_each(|(i, led)| shade(i, led));
+            }
+            let sequential = started.elapsed() / TICKS;
+
+            let started = Instant::now();
+            for _ in 0..TICKS {
+                buffer
+                    .par_chunks_mut(CHUNK_LEDS)
+                    .enumerate()
+                    .for_each(|(chunk, leds)| {
+                        for (offset, led) in leds.iter_mut().enumerate() {
+                            shade(chunk * CHUNK_LEDS + offset, led);
+                        }
+                    });
+            }
+            let parallel = started.elapsed() / TICKS;
+
+            println!("{len:>6} LEDs: sequential {sequential:?}, parallel {parallel:?}");
+        }
+    }
+}
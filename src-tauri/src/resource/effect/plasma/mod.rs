@@ -0,0 +1,324 @@
+//! Plasma Effect
+//!
+//! Slowly drifting color blobs from 3D Perlin noise: the layout is a slice through the noise
+//! field and time moves the slice along the third axis. The field only depends on the
+//! elapsed time, so outputs ticked with the same time show the same frame. A strip is a
+//! horizontal line through the field.
+
+use crate::interface::controller::Color;
+use crate::interface::effect::{
+    Effect, EffectLayout, EffectMetadata, EffectParam, EffectParamKind, SelectOptions,
+    StaticSelectOption,
+};
+use crate::resource::color::hsv_to_rgb;
+use crate::resource::effect::parallel::for_each_led;
+use inventory;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Seeds the permutation table; fixed so every instance builds the same field.
+const NOISE_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Noise units the field moves per second at speed 1.
+const DRIFT_PER_SECOND: f32 = 0.25;
+
+/// Raw noise rarely leaves ±0.7; stretching it uses the whole color range.
+const CONTRAST: f32 = 1.4;
+
+const COLOR_MODE_OPTIONS: [StaticSelectOption; 3] = [
+    StaticSelectOption {
+        label: "彩虹",
+        value: 0.0,
+    },
+    StaticSelectOption {
+        label: "双色",
+        value: 1.0,
+    },
+    StaticSelectOption {
+        label: "单色",
+        value: 2.0,
+    },
+];
+
+const PLASMA_PARAMS: [EffectParam; 4] = [
+    EffectParam {
+        key: "speed",
+        label: "速度",
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 5.0,
+            step: 0.1,
+            default: 1.0,
+        },
+        dependency: None,
+    },
+    // Size of a blob, in LEDs.
+    EffectParam {
+        key: "scale",
+        label: "尺度",
+        kind: EffectParamKind::Slider {
+            min: 1.0,
+            max: 50.0,
+            step: 1.0,
+            default: 8.0,
+        },
+        dependency: None,
+    },
+    // Rotates the colors around the hue wheel; the base hue in duotone and mono-hue.
+    EffectParam {
+        key: "hueShift",
+        label: "色相偏移",
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 360.0,
+            step: 1.0,
+            default: 0.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "colorMode",
+        label: "颜色模式",
+        kind: EffectParamKind::Select {
+            default: 0.0,
+            options: SelectOptions::Static(&COLOR_MODE_OPTIONS),
+        },
+        dependency: None,
+    },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ColorMode {
+    Rainbow,
+    /// Between the base hue and its complement.
+    Duotone,
+    /// The base hue, light and dark.
+    MonoHue,
+}
+
+pub struct PlasmaEffect {
+    /// Shuffled 0..=255 twice over, so lookups never wrap.
+    perm: [u8; 512],
+    speed: f32,
+    /// LEDs per noise cell.
+    scale: f32,
+    /// Degrees.
+    hue_shift: f32,
+    color_mode: ColorMode,
+    width: usize,
+    height: usize,
+}
+
+impl PlasmaEffect {
+    pub fn new() -> Self {
+        let mut table: [u8; 256] = std::array::from_fn(|i| i as u8);
+        // Fisher–Yates with xorshift64.
+        let mut rng = NOISE_SEED;
+        for i in (1..table.len()).rev() {
+            rng ^= rng << 13;
+            rng ^= rng >> 7;
+            rng ^= rng << 17;
+            table.swap(i, (rng % (i as u64 + 1)) as usize);
+        }
+
+        Self {
+            perm: std::array::from_fn(|i| table[i % 256]),
+            speed: 1.0,
+            scale: 8.0,
+            hue_shift: 0.0,
+            color_mode: ColorMode::Rainbow,
+            width: 0,
+            height: 0,
+        }
+    }
+
+    /// Improved Perlin noise, roughly -1.0..=1.0.
+    fn noise(&self, x: f32, y: f32, z: f32) -> f32 {
+        let (xf, yf, zf) = (x.floor(), y.floor(), z.floor());
+        let cell = |f: f32| f as i32 as usize & 255;
+        let (xi, yi, zi) = (cell(xf), cell(yf), cell(zf));
+        let (x, y, z) = (x - xf, y - yf, z - zf);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let p = &self.perm;
+        let a = p[xi] as usize + yi;
+        let (aa, ab) = (p[a] as usize + zi, p[a + 1] as usize + zi);
+        let b = p[xi + 1] as usize + yi;
+        let (ba, bb) = (p[b] as usize + zi, p[b + 1] as usize + zi);
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1.0, y, z)),
+                lerp(u, grad(p[ab], x, y - 1.0, z), grad(p[bb], x - 1.0, y - 1.0, z)),
+            ),
+            lerp(
+                v,
+                lerp(u, grad(p[aa + 1], x, y, z - 1.0), grad(p[ba + 1], x - 1.0, y, z - 1.0)),
+                lerp(
+                    u,
+                    grad(p[ab + 1], x, y - 1.0, z - 1.0),
+                    grad(p[bb + 1], x - 1.0, y - 1.0, z - 1.0),
+                ),
+            ),
+        )
+    }
+
+    /// Color for a field value `t` in 0.0..=1.0.
+    fn color(&self, t: f32) -> Color {
+        let (r, g, b) = match self.color_mode {
+            ColorMode::Rainbow => hsv_to_rgb(self.hue_shift + t * 360.0, 1.0, 1.0),
+            ColorMode::Duotone => {
+                let (a, b) = (
+                    hsv_to_rgb(self.hue_shift, 1.0, 1.0),
+                    hsv_to_rgb(self.hue_shift + 180.0, 1.0, 1.0),
+                );
+                // Smoothstep keeps the two colors apart instead of lingering on the mix.
+                let f = t * t * (3.0 - 2.0 * t);
+                let mix = |x: u8, y: u8| (x as f32 + (y as f32 - x as f32) * f).round() as u8;
+                (mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+            }
+            ColorMode::MonoHue => hsv_to_rgb(self.hue_shift, 1.0, t),
+        };
+        Color { r, g, b }
+    }
+}
+
+impl Default for PlasmaEffect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+/// Dot product with one of the 12 cube-edge gradients picked by `hash`.
+fn grad(hash: u8, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = match h {
+        0..=3 => y,
+        12 | 14 => x,
+        _ => z,
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+impl Effect for PlasmaEffect {
+    fn id(&self) -> String {
+        "plasma".to_string()
+    }
+
+    fn name(&self) -> String {
+        "Plasma".to_string()
+    }
+
+    fn tick(&mut self, time: Duration, buffer: &mut [Color]) {
+        let led_count = buffer.len();
+        if led_count == 0 {
+            return;
+        }
+
+        let (width, height) = if self.width == 0 || self.height == 0 {
+            (led_count, 1)
+        } else {
+            (self.width, self.height)
+        };
+        let is_strip = height == 1;
+
+        let z = time.as_secs_f32() * self.speed * DRIFT_PER_SECOND;
+        let leds = led_count.min(width.saturating_mul(height));
+        let this = &*self;
+        for_each_led(&mut buffer[..leds], |i, led| {
+            let x = (i % width) as f32 / this.scale;
+            // Off the lattice, where a single row of noise would be flat.
+            let y = if is_strip {
+                0.5
+            } else {
+                (i / width) as f32 / this.scale
+            };
+            // Opposite drift in the plane keeps blobs from just pulsing in place.
+            let n = this.noise(x + z * 0.5, y - z * 0.3, z);
+            *led = this.color((n * CONTRAST * 0.5 + 0.5).clamp(0.0, 1.0));
+        });
+        buffer[leds..].fill(Color::default());
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn update_params(&mut self, params: Value) {
+        if let Some(speed) = params.get("speed").and_then(|v| v.as_f64()) {
+            self.speed = (speed as f32).clamp(0.0, 5.0);
+        }
+        if let Some(scale) = params.get("scale").and_then(|v| v.as_f64()) {
+            self.scale = (scale as f32).clamp(1.0, 50.0);
+        }
+        if let Some(hue_shift) = params.get("hueShift").and_then(|v| v.as_f64()) {
+            self.hue_shift = (hue_shift as f32).rem_euclid(360.0);
+        }
+        if let Some(mode) = params.get("colorMode").and_then(|v| v.as_f64()) {
+            self.color_mode = match mode as i64 {
+                1 => ColorMode::Duotone,
+                2 => ColorMode::MonoHue,
+                _ => ColorMode::Rainbow,
+            };
+        }
+    }
+}
+
+fn factory() -> Box<dyn Effect> {
+    Box::new(PlasmaEffect::new())
+}
+
+inventory::submit!(EffectMetadata {
+    id: "plasma",
+    name: "Plasma",
+    description: Some("Slowly drifting blobs of color"),
+    group: Some("Dynamic"),
+    icon: Some("Cloudy"),
+    layout: EffectLayout::Any,
+    params: &PLASMA_PARAMS,
+    factory,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(effect: &mut PlasmaEffect, time: Duration, len: usize) -> Vec<(u8, u8, u8)> {
+        let mut buffer = vec![Color::default(); len];
+        effect.tick(time, &mut buffer);
+        buffer.iter().map(|c| (c.r, c.g, c.b)).collect()
+    }
+
+    #[test]
+    fn frames_depend_only_on_the_elapsed_time() {
+        let (mut a, mut b) = (PlasmaEffect::new(), PlasmaEffect::new());
+        a.resize(16, 8);
+        b.resize(16, 8);
+        let _ = render(&mut a, Duration::from_secs(3), 128);
+        let at = Duration::from_millis(12_345);
+        let frame = render(&mut a, at, 128);
+        assert_eq!(frame, render(&mut b, at, 128));
+        assert_ne!(frame, render(&mut b, at + Duration::from_secs(1), 128));
+        // Blobs, not a flat fill.
+        assert!(frame.iter().any(|&c| c != frame[0]));
+
+        // Mono-hue stays on the hue, only its brightness changes.
+        a.update_params(serde_json::json!({ "colorMode": 2, "hueShift": 240 }));
+        a.resize(0, 0);
+        let strip = render(&mut a, at, 60);
+        assert!(strip.iter().all(|&(r, g, _)| r == 0 && g == 0));
+        assert!(strip.iter().any(|&(_, _, b)| b > 0));
+    }
+}
@@ -1,5 +1,7 @@
 use crate::interface::controller::Color;
-use crate::interface::effect::{Effect, EffectMetadata, EffectParam, EffectParamKind};
+use crate::interface::effect::{Effect, EffectLayout, EffectMetadata, EffectParam, EffectParamKind};
+use crate::resource::color::hsv_to_rgb;
+use crate::resource::effect::parallel::for_each_led;
 use inventory;
 use serde_json::Value;
 use std::time::Duration;
@@ -31,7 +33,7 @@ impl Effect for RainbowEffect {
         "Rainbow".to_string()
     }
 
-    fn tick(&mut self, elapsed: Duration, buffer: &mut [Color]) {
+    fn tick(&mut self, time: Duration, buffer: &mut [Color]) {
         let led_count = buffer.len();
         if led_count == 0 {
             return;
@@ -47,21 +49,16 @@ impl Effect for RainbowEffect {
 
         // Simple animation logic: horizontal rainbow that scrolls over time,
         // with a slight vertical phase so matrix layout is obvious.
-        let offset = (elapsed.as_millis() as f32 * self.speed / 10.0) % 360.0;
+        let offset = (time.as_millis() as f32 * self.speed / 10.0) % 360.0;
 
-        let mut i = 0;
-        for y in 0..height {
-            for x in 0..width {
-                if i >= led_count {
-                    break;
-                }
-                let base = (x as f32 * 360.0 / width as f32) + offset;
-                let hue = (base + (y as f32 * 20.0)) % 360.0;
-                let (r, g, b) = hsv_to_rgb(hue, 1.0, 1.0);
-                buffer[i] = Color { r, g, b };
-                i += 1;
-            }
-        }
+        let leds = led_count.min(width.saturating_mul(height));
+        for_each_led(&mut buffer[..leds], |i, led| {
+            let (x, y) = (i % width, i / width);
+            let base = (x as f32 * 360.0 / width as f32) + offset;
+            let hue = (base + (y as f32 * 20.0)) % 360.0;
+            let (r, g, b) = hsv_to_rgb(hue, 1.0, 1.0);
+            *led = Color { r, g, b };
+        });
     }
 
     fn resize(&mut self, width: usize, height: usize) {
@@ -76,32 +73,6 @@ impl Effect for RainbowEffect {
     }
 }
 
-fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (u8, u8, u8) {
-    let c = v * s;
-    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
-    let m = v - c;
-
-    let (r, g, b) = if h < 60.0 {
-        (c, x, 0.0)
-    } else if h < 120.0 {
-        (x, c, 0.0)
-    } else if h < 180.0 {
-        (0.0, c, x)
-    } else if h < 240.0 {
-        (0.0, x, c)
-    } else if h < 300.0 {
-        (x, 0.0, c)
-    } else {
-        (c, 0.0, x)
-    };
-
-    (
-        ((r + m) * 255.0) as u8,
-        ((g + m) * 255.0) as u8,
-        ((b + m) * 255.0) as u8,
-    )
-}
-
 fn factory() -> Box<dyn Effect> {
     Box::new(RainbowEffect {
         speed: 1.0,
@@ -116,6 +87,7 @@ inventory::submit!(EffectMetadata {
     description: Some("Cycling rainbow colors"),
     group: Some("Dynamic"),
     icon: Some("Waves"),
+    layout: EffectLayout::Any,
     params: &RAINBOW_PARAMS,
     factory,
 });
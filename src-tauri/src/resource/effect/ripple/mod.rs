@@ -0,0 +1,610 @@
+//! Ripple Effect
+//!
+//! Expanding rings of color that fade as they grow. A ripple is emitted on a fixed interval
+//! or on each detected audio beat. On a matrix the ring is a circle around its origin; on a
+//! strip the same distance math makes it travel outward in both directions. Strips with LED
+//! coordinates are treated as points in space, so the ring follows their physical layout.
+
+use crate::interface::controller::{Color, LedPosition};
+use crate::interface::effect::{
+    DependencyBehavior, Effect, EffectLayout, EffectMetadata, EffectParam, EffectParamDependency,
+    EffectParamKind, SelectOptions, StaticSelectOption,
+};
+use crate::resource::audio::AudioManager;
+use crate::resource::color::{hsv_to_rgb, parse_color};
+use crate::resource::effect::audio_star::load_audio_devices;
+use crate::resource::effect::parallel::for_each_led;
+use inventory;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DEFAULT_COLOR: &str = "#00aaff";
+
+/// Hard cap on concurrent ripples regardless of the `maxRipples` param.
+const MAX_RIPPLES: usize = 16;
+
+/// Half-width of the ring, in LEDs.
+const RING_WIDTH: f32 = 1.5;
+
+/// Hue step between consecutive ripples in rainbow mode.
+const RAINBOW_HUE_STEP: f32 = 47.0;
+
+/// Samples read per tick for beat detection.
+const BEAT_WINDOW: usize = 1024;
+/// A beat is a window whose energy exceeds the running average by this factor.
+const BEAT_THRESHOLD: f32 = 1.5;
+/// Windows quieter than this never count as beats.
+const BEAT_MIN_ENERGY: f32 = 1e-4;
+/// Minimum time between two beat-triggered ripples.
+const BEAT_COOLDOWN_SECS: f32 = 0.15;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Trigger {
+    Auto,
+    Beat,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Origin {
+    Center,
+    Random,
+}
+
+struct Ripple {
+    x: f32,
+    y: f32,
+    /// Distance from the origin to the farthest LED; the ripple ends once it passes this.
+    reach: f32,
+    radius: f32,
+    color: Color,
+}
+
+pub struct RippleEffect {
+    width: usize,
+    height: usize,
+    /// Physical LED coordinates; replaces the grid position of each LED when it matches the
+    /// buffer length.
+    positions: Option<Vec<LedPosition>>,
+
+    color: Color,
+    rainbow: bool,
+    /// Ring growth in LEDs per second.
+    speed: f32,
+    /// 0..=100; how much a ripple has faded by the time it reaches its farthest LED.
+    decay: f32,
+    max_ripples: usize,
+    trigger: Trigger,
+    interval_secs: f32,
+    origin: Origin,
+    audio_device_index: Option<usize>,
+
+    ripples: VecDeque<Ripple>,
+    last_time: Option<Duration>,
+    since_spawn: f32,
+    next_hue: f32,
+    rng: u64,
+
+    audio_samples: Vec<f32>,
+    average_energy: f32,
+    capturing: bool,
+}
+
+impl Default for RippleEffect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RippleEffect {
+    pub fn new() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+
+        Self {
+            width: 0,
+            height: 0,
+            positions: None,
+            color: parse_color(DEFAULT_COLOR).unwrap_or_default(),
+            rainbow: false,
+            speed: 20.0,
+            decay: 50.0,
+            max_ripples: 6,
+            trigger: Trigger::Auto,
+            interval_secs: 1.0,
+            origin: Origin::Center,
+            audio_device_index: None,
+            ripples: VecDeque::new(),
+            last_time: None,
+            // Emit the first ripple on the first tick.
+            since_spawn: f32::INFINITY,
+            next_hue: 0.0,
+            rng: seed | 1,
+            audio_samples: vec![0.0; BEAT_WINDOW],
+            average_energy: 0.0,
+            capturing: false,
+        }
+    }
+
+    fn dimensions(&self, led_count: usize) -> (usize, usize) {
+        if self.width == 0 {
+            (led_count, 1)
+        } else {
+            (self.width, self.height.max(1))
+        }
+    }
+
+    /// Uniform value in `[0, 1)` (xorshift64).
+    fn next_random(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn spatial_positions(&self, led_count: usize) -> Option<&[LedPosition]> {
+        self.positions
+            .as_deref()
+            .filter(|p| !p.is_empty() && p.len() == led_count)
+    }
+
+    fn spawn(&mut self, width: usize, height: usize, led_count: usize) {
+        let (pick_x, pick_y) = (self.next_random(), self.next_random());
+        let (x, y, reach) = if let Some(points) = self.spatial_positions(led_count) {
+            let (x, y) = match self.origin {
+                Origin::Center => {
+                    let (min_x, max_x, min_y, max_y) = points.iter().fold(
+                        (f32::MAX, f32::MIN, f32::MAX, f32::MIN),
+                        |(min_x, max_x, min_y, max_y), p| {
+                            (min_x.min(p.x), max_x.max(p.x), min_y.min(p.y), max_y.max(p.y))
+                        },
+                    );
+                    ((min_x + max_x) * 0.5, (min_y + max_y) * 0.5)
+                }
+                Origin::Random => {
+                    let p = points[((pick_x * points.len() as f32) as usize).min(points.len() - 1)];
+                    (p.x, p.y)
+                }
+            };
+            let reach = points
+                .iter()
+                .map(|p| ((p.x - x) * (p.x - x) + (p.y - y) * (p.y - y)).sqrt())
+                .fold(0.0f32, f32::max);
+            (x, y, reach)
+        } else {
+            let (x, y) = match self.origin {
+                Origin::Center => ((width - 1) as f32 * 0.5, (height - 1) as f32 * 0.5),
+                Origin::Random => (
+                    (pick_x * width as f32).floor(),
+                    (pick_y * height as f32).floor(),
+                ),
+            };
+            let far_x = x.max((width - 1) as f32 - x);
+            let far_y = y.max((height - 1) as f32 - y);
+            (x, y, (far_x * far_x + far_y * far_y).sqrt())
+        };
+
+        let color = if self.rainbow {
+            let (r, g, b) = hsv_to_rgb(self.next_hue, 1.0, 1.0);
+            self.next_hue = (self.next_hue + RAINBOW_HUE_STEP) % 360.0;
+            Color { r, g, b }
+        } else {
+            self.color
+        };
+
+        while self.ripples.len() >= self.max_ripples {
+            self.ripples.pop_front();
+        }
+        self.ripples.push_back(Ripple {
+            x,
+            y,
+            reach,
+            radius: 0.0,
+            color,
+        });
+        self.since_spawn = 0.0;
+    }
+
+    fn ensure_capture(&mut self) {
+        let Some(device_index) = self.audio_device_index else {
+            return;
+        };
+        if self.capturing {
+            return;
+        }
+        if let Err(e) = AudioManager::get().acquire_capture(device_index) {
+            log::error!(device_index = device_index, err:display = e; "[ripple] Failed to start audio capture");
+            return;
+        }
+        self.capturing = true;
+    }
+
+    fn beat_detected(&mut self) -> bool {
+        self.ensure_capture();
+        if !self.capturing {
+            return false;
+        }
+
+        let read = AudioManager::get().read_samples(&mut self.audio_samples);
+        if read == 0 {
+            return false;
+        }
+        let energy =
+            self.audio_samples[..read].iter().map(|s| s * s).sum::<f32>() / read as f32;
+        let is_beat = energy > BEAT_MIN_ENERGY
+            && energy > self.average_energy * BEAT_THRESHOLD
+            && self.since_spawn >= BEAT_COOLDOWN_SECS;
+        self.average_energy = self.average_energy * 0.95 + energy * 0.05;
+        is_beat
+    }
+
+    fn release_capture(&mut self) {
+        if self.capturing {
+            AudioManager::get().release_capture();
+            self.capturing = false;
+        }
+    }
+}
+
+impl Effect for RippleEffect {
+    fn id(&self) -> String {
+        "ripple".to_string()
+    }
+
+    fn name(&self) -> String {
+        "Ripple".to_string()
+    }
+
+    fn tick(&mut self, time: Duration, buffer: &mut [Color]) {
+        if buffer.is_empty() {
+            return;
+        }
+
+        let dt = self
+            .last_time
+            .and_then(|last| time.checked_sub(last))
+            .map_or(0.0, |d| d.as_secs_f32());
+        self.last_time = Some(time);
+        self.since_spawn += dt;
+
+        for ripple in &mut self.ripples {
+            ripple.radius += self.speed * dt;
+        }
+        self.ripples
+            .retain(|ripple| ripple.radius <= ripple.reach + RING_WIDTH);
+
+        let (width, height) = self.dimensions(buffer.len());
+        let triggered = match self.trigger {
+            Trigger::Auto => self.since_spawn >= self.interval_secs,
+            Trigger::Beat => self.beat_detected(),
+        };
+        if triggered {
+            self.spawn(width, height, buffer.len());
+        }
+
+        // exp(-4) at the far edge keeps the strongest decay visible all the way out.
+        let fade = self.decay / 25.0;
+        let ripples = &self.ripples;
+        let points = self.spatial_positions(buffer.len());
+        let leds = if points.is_some() {
+            buffer.len()
+        } else {
+            buffer.len().min(width.saturating_mul(height))
+        };
+        buffer[leds..].fill(Color::default());
+        for_each_led(&mut buffer[..leds], |i, led| {
+            let (x, y) = match points {
+                Some(points) => (points[i].x, points[i].y),
+                None => ((i % width) as f32, (i / width) as f32),
+            };
+            let (mut r, mut g, mut b) = (0.0f32, 0.0f32, 0.0f32);
+            for ripple in ripples {
+                let (dx, dy) = (x - ripple.x, y - ripple.y);
+                let ring = 1.0 - ((dx * dx + dy * dy).sqrt() - ripple.radius).abs() / RING_WIDTH;
+                if ring <= 0.0 {
+                    continue;
+                }
+                let progress = ripple.radius / ripple.reach.max(1.0);
+                let intensity = ring * (-fade * progress).exp();
+                r += ripple.color.r as f32 * intensity;
+                g += ripple.color.g as f32 * intensity;
+                b += ripple.color.b as f32 * intensity;
+            }
+            *led = Color {
+                r: r.min(255.0) as u8,
+                g: g.min(255.0) as u8,
+                b: b.min(255.0) as u8,
+            };
+        });
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        if (width, height) != (self.width, self.height) {
+            self.ripples.clear();
+        }
+        self.width = width;
+        self.height = height;
+    }
+
+    fn set_positions(&mut self, positions: Option<&[LedPosition]>) {
+        if self.positions.as_deref() != positions {
+            self.ripples.clear();
+        }
+        self.positions = positions.map(<[LedPosition]>::to_vec);
+    }
+
+    fn update_params(&mut self, params: Value) {
+        if let Some(color) = params
+            .get("color")
+            .and_then(|v| v.as_str())
+            .and_then(parse_color)
+        {
+            self.color = color;
+        }
+
+        if let Some(rainbow) = params.get("rainbow").and_then(|v| v.as_bool()) {
+            self.rainbow = rainbow;
+        }
+
+        if let Some(speed) = params.get("speed").and_then(|v| v.as_f64()) {
+            self.speed = (speed as f32).max(0.0);
+        }
+
+        if let Some(decay) = params.get("decay").and_then(|v| v.as_f64()) {
+            self.decay = (decay as f32).clamp(0.0, 100.0);
+        }
+
+        if let Some(max) = params.get("maxRipples").and_then(|v| v.as_f64()) {
+            self.max_ripples = (max as usize).clamp(1, MAX_RIPPLES);
+            while self.ripples.len() > self.max_ripples {
+                self.ripples.pop_front();
+            }
+        }
+
+        if let Some(trigger) = params.get("trigger").and_then(|v| v.as_f64()) {
+            self.trigger = if trigger as i64 == 1 {
+                Trigger::Beat
+            } else {
+                Trigger::Auto
+            };
+            if self.trigger == Trigger::Auto {
+                self.release_capture();
+            }
+        }
+
+        if let Some(interval) = params.get("interval").and_then(|v| v.as_f64()) {
+            self.interval_secs = (interval as f32).max(0.05);
+        }
+
+        if let Some(origin) = params.get("origin").and_then(|v| v.as_f64()) {
+            self.origin = if origin as i64 == 1 {
+                Origin::Random
+            } else {
+                Origin::Center
+            };
+        }
+
+        if let Some(device_index) = params.get("audioDevice").and_then(|v| v.as_f64()) {
+            let new_index = (device_index >= 0.0).then_some(device_index as usize);
+            if new_index != self.audio_device_index {
+                self.release_capture();
+                self.audio_device_index = new_index;
+            }
+        }
+    }
+}
+
+impl Drop for RippleEffect {
+    fn drop(&mut self) {
+        self.release_capture();
+    }
+}
+
+const TRIGGER_OPTIONS: [StaticSelectOption; 2] = [
+    StaticSelectOption {
+        label: "Auto",
+        value: 0.0,
+    },
+    StaticSelectOption {
+        label: "Audio beat",
+        value: 1.0,
+    },
+];
+
+const ORIGIN_OPTIONS: [StaticSelectOption; 2] = [
+    StaticSelectOption {
+        label: "Center",
+        value: 0.0,
+    },
+    StaticSelectOption {
+        label: "Random",
+        value: 1.0,
+    },
+];
+
+const RIPPLE_PARAMS: [EffectParam; 9] = [
+    EffectParam {
+        key: "color",
+        label: "Color",
+        kind: EffectParamKind::Color {
+            default: DEFAULT_COLOR,
+        },
+        dependency: Some(EffectParamDependency::Dependency {
+            key: "rainbow",
+            equals: Some(0.0),
+            not_equals: None,
+            behavior: DependencyBehavior::Disable,
+        }),
+    },
+    EffectParam {
+        key: "rainbow",
+        label: "Rainbow",
+        kind: EffectParamKind::Toggle { default: false },
+        dependency: None,
+    },
+    EffectParam {
+        key: "speed",
+        label: "Speed",
+        kind: EffectParamKind::Slider {
+            min: 1.0,
+            max: 100.0,
+            step: 1.0,
+            default: 20.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "decay",
+        label: "Decay",
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 100.0,
+            step: 1.0,
+            default: 50.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "maxRipples",
+        label: "Max ripples",
+        kind: EffectParamKind::Slider {
+            min: 1.0,
+            max: MAX_RIPPLES as f64,
+            step: 1.0,
+            default: 6.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "origin",
+        label: "Origin",
+        kind: EffectParamKind::Select {
+            default: 0.0,
+            options: SelectOptions::Static(&ORIGIN_OPTIONS),
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "trigger",
+        label: "Trigger",
+        kind: EffectParamKind::Select {
+            default: 0.0,
+            options: SelectOptions::Static(&TRIGGER_OPTIONS),
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "interval",
+        label: "Interval (s)",
+        kind: EffectParamKind::Slider {
+            min: 0.1,
+            max: 5.0,
+            step: 0.1,
+            default: 1.0,
+        },
+        dependency: Some(EffectParamDependency::Dependency {
+            key: "trigger",
+            equals: Some(0.0),
+            not_equals: None,
+            behavior: DependencyBehavior::Hide,
+        }),
+    },
+    EffectParam {
+        key: "audioDevice",
+        label: "Audio device",
+        kind: EffectParamKind::Select {
+            default: 0.0,
+            options: SelectOptions::Dynamic(load_audio_devices),
+        },
+        dependency: Some(EffectParamDependency::Dependency {
+            key: "trigger",
+            equals: Some(1.0),
+            not_equals: None,
+            behavior: DependencyBehavior::Hide,
+        }),
+    },
+];
+
+fn factory() -> Box<dyn Effect> {
+    Box::new(RippleEffect::new())
+}
+
+inventory::submit!(EffectMetadata {
+    id: "ripple",
+    name: "Ripple",
+    description: Some("Expanding rings of color on a timer or audio beat"),
+    group: Some("Dynamic"),
+    icon: Some("Radar"),
+    layout: EffectLayout::Any,
+    params: &RIPPLE_PARAMS,
+    factory,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn single_ripple_reaches_matrix_corners() {
+        let (width, height) = (9, 5);
+        let mut effect = RippleEffect::new();
+        effect.resize(width, height);
+        effect.update_params(json!({ "speed": 10.0, "decay": 100.0, "interval": 60.0 }));
+
+        let mut buffer = vec![Color::default(); width * height];
+        let corners = [0, width - 1, width * (height - 1), width * height - 1];
+        let mut lit = [false; 4];
+        for frame in 0..120u64 {
+            effect.tick(Duration::from_millis(frame * 16), &mut buffer);
+            assert!(effect.ripples.len() <= 1);
+            for (seen, &corner) in lit.iter_mut().zip(&corners) {
+                let c = buffer[corner];
+                *seen |= c.r > 0 || c.g > 0 || c.b > 0;
+            }
+        }
+
+        assert_eq!(lit, [true; 4]);
+        assert!(effect.ripples.is_empty(), "ripple should end after passing the corners");
+    }
+
+    #[test]
+    fn led_positions_override_strip_order() {
+        // A 12-LED strip bent into a circle: a centered ripple hits every LED at once, while
+        // the index-based strip only lights the ends at that radius.
+        let leds = 12;
+        let positions: Vec<LedPosition> = (0..leds)
+            .map(|i| {
+                let angle = i as f32 / leds as f32 * std::f32::consts::TAU;
+                LedPosition {
+                    x: 5.0 + 5.0 * angle.cos(),
+                    y: 5.0 + 5.0 * angle.sin(),
+                }
+            })
+            .collect();
+        let params = json!({ "speed": 10.0, "interval": 60.0, "decay": 0.0 });
+        let render = |positions: Option<&[LedPosition]>| {
+            let mut effect = RippleEffect::new();
+            effect.resize(leds, 1);
+            effect.set_positions(positions);
+            effect.update_params(params.clone());
+            let mut buffer = vec![Color::default(); leds];
+            effect.tick(Duration::ZERO, &mut buffer);
+            effect.tick(Duration::from_millis(500), &mut buffer);
+            buffer
+        };
+
+        let spatial = render(Some(&positions));
+        let min = spatial.iter().map(|c| c.b).min().unwrap();
+        let max = spatial.iter().map(|c| c.b).max().unwrap();
+        assert!(min > 200, "every LED on the ring should be lit: {:?}", spatial);
+        assert!(max - min <= 2);
+
+        let by_index = render(None);
+        assert!(by_index[0].b > 0 && by_index[leds - 1].b > 0);
+        let middle = by_index[leds / 2];
+        assert_eq!((middle.r, middle.g, middle.b), (0, 0, 0));
+    }
+}
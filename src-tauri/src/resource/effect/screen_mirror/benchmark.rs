@@ -0,0 +1,117 @@
+use super::renderer::{render_frame, CropRegion};
+use crate::interface::controller::Color;
+use crate::resource::screen::{get_capture_method, get_hardware_acceleration, ScreenSubscription};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// LED layout used when the render step is included; roughly a 27" ambilight matrix.
+const BENCHMARK_LAYOUT: (usize, usize) = (32, 18);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureBenchmarkReport {
+    pub display_index: usize,
+    /// Capture backend that was active while measuring (after any fallback).
+    pub backend: String,
+    pub hardware_acceleration: bool,
+    pub duration_ms: f64,
+    pub frames: u32,
+    /// Capture calls that returned without a frame or with an error.
+    pub missed_frames: u32,
+    pub fps: f64,
+    pub avg_frame_ms: f64,
+    pub avg_capture_ms: f64,
+    /// Only present when the render step was included.
+    pub avg_render_ms: Option<f64>,
+    /// Size of the captured frame after downsampling.
+    pub frame_width: u32,
+    pub frame_height: u32,
+}
+
+/// Capture (and optionally render) as fast as possible for `duration`.
+///
+/// Uses its own subscription, so running effects keep their state; they may see a lower
+/// frame rate while the benchmark holds the capture lock.
+pub fn run_capture_benchmark(
+    display_index: usize,
+    duration: Duration,
+    include_render: bool,
+) -> Result<CaptureBenchmarkReport, String> {
+    let mut subscription = ScreenSubscription::new(display_index)
+        .map_err(|e| format!("Failed to subscribe to display {}: {}", display_index, e))?;
+
+    let led_count = BENCHMARK_LAYOUT.0 * BENCHMARK_LAYOUT.1;
+    let mut buffer = vec![Color::default(); led_count];
+    let mut previous = vec![Color::default(); led_count];
+    let crop = CropRegion::default();
+
+    let mut frames = 0u32;
+    let mut missed_frames = 0u32;
+    let mut frame_time = Duration::ZERO;
+    let mut render_time = Duration::ZERO;
+    let mut frame_size = (0, 0);
+
+    let started = Instant::now();
+    while started.elapsed() < duration {
+        let frame_started = Instant::now();
+        let mut render_elapsed = Duration::ZERO;
+
+        let result = subscription.capture_with(|frame| {
+            frame_size = (frame.width, frame.height);
+            if include_render {
+                let render_started = Instant::now();
+                render_frame(BENCHMARK_LAYOUT, None, frame, &mut buffer, &mut previous, 0, &crop, 1.0, 1.0, 1.0, None, 100);
+                render_elapsed = render_started.elapsed();
+            }
+        });
+
+        match result {
+            Ok(true) => {
+                frames += 1;
+                frame_time += frame_started.elapsed();
+                render_time += render_elapsed;
+            }
+            Ok(false) => missed_frames += 1,
+            Err(err) => {
+                missed_frames += 1;
+                log::debug!(display_index, err:display = err; "[benchmark] Capture failed");
+                // Avoid spinning on a display that keeps failing.
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+    }
+    let elapsed = started.elapsed();
+
+    if frames == 0 {
+        return Err(format!(
+            "No frames captured from display {} in {:.1}s",
+            display_index,
+            elapsed.as_secs_f64()
+        ));
+    }
+
+    let per_frame_ms = |total: Duration| total.as_secs_f64() * 1000.0 / frames as f64;
+    let report = CaptureBenchmarkReport {
+        display_index,
+        backend: get_capture_method().to_string(),
+        hardware_acceleration: get_hardware_acceleration(),
+        duration_ms: elapsed.as_secs_f64() * 1000.0,
+        frames,
+        missed_frames,
+        fps: frames as f64 / elapsed.as_secs_f64(),
+        avg_frame_ms: per_frame_ms(frame_time),
+        avg_capture_ms: per_frame_ms(frame_time.saturating_sub(render_time)),
+        avg_render_ms: include_render.then(|| per_frame_ms(render_time)),
+        frame_width: frame_size.0,
+        frame_height: frame_size.1,
+    };
+
+    log::info!(
+        display_index,
+        backend = report.backend.as_str(),
+        fps = report.fps,
+        avg_frame_ms = report.avg_frame_ms;
+        "[benchmark] Capture benchmark finished"
+    );
+    Ok(report)
+}
@@ -1,5 +1,7 @@
 use crate::resource::screen::ScreenFrame;
 use super::renderer::CropRegion;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
 
 #[derive(Clone, Copy, Debug, Default)]
 pub struct BlackBorder {
@@ -22,7 +24,7 @@ impl PartialEq for BlackBorder {
 
 impl Eq for BlackBorder {}
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BlackBorderMode {
     Default,
     Classic,
@@ -324,6 +326,52 @@ impl BlackBorderDetector {
     }
 }
 
+/// Raw detection results for the latest frame of each display.
+///
+/// Every screen mirror on a display sees the same captured frame, so the scan only needs
+/// to run once per frame and (threshold, mode) pair. Hysteresis stays per processor.
+#[derive(Default)]
+struct CachedDetections {
+    frame_id: u64,
+    results: Vec<(u8, BlackBorderMode, BlackBorder)>,
+}
+
+static DETECTION_CACHE: OnceLock<Mutex<HashMap<usize, CachedDetections>>> = OnceLock::new();
+
+fn detect_cached(
+    display_index: usize,
+    detector: &BlackBorderDetector,
+    frame: &ScreenFrame<'_>,
+    mode: BlackBorderMode,
+) -> BlackBorder {
+    if frame.frame_id == 0 {
+        return detector.detect(frame, mode);
+    }
+
+    let cache = DETECTION_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    let Ok(mut cache) = cache.lock() else {
+        return detector.detect(frame, mode);
+    };
+
+    let entry = cache.entry(display_index).or_default();
+    if entry.frame_id != frame.frame_id {
+        entry.frame_id = frame.frame_id;
+        entry.results.clear();
+    }
+
+    if let Some(&(_, _, border)) = entry
+        .results
+        .iter()
+        .find(|(threshold, cached_mode, _)| *threshold == detector.threshold && *cached_mode == mode)
+    {
+        return border;
+    }
+
+    let border = detector.detect(frame, mode);
+    entry.results.push((detector.threshold, mode, border));
+    border
+}
+
 pub struct BlackBorderProcessor {
     pub enabled: bool,
     pub unknown_switch_cnt: u32,
@@ -411,13 +459,13 @@ impl BlackBorderProcessor {
         border_changed
     }
 
-    pub fn process_frame(&mut self, frame: &ScreenFrame<'_>) {
+    pub fn process_frame(&mut self, display_index: usize, frame: &ScreenFrame<'_>) {
         if !self.enabled {
             self.current_border = BlackBorder::default();
             return;
         }
 
-        let mut image_border = self.detector.detect(frame, self.mode);
+        let mut image_border = detect_cached(display_index, &self.detector, frame, self.mode);
 
         if image_border.horizontal_size > 0 {
             image_border.horizontal_size += self.blur_remove_cnt;
@@ -451,3 +499,55 @@ impl BlackBorderProcessor {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::screen::next_frame_id;
+
+    /// 16x16 BGRA frame with `bar` black rows at the top and bottom.
+    fn letterboxed(bar: usize) -> Vec<u8> {
+        let mut pixels = vec![200u8; 16 * 16 * 4];
+        for y in (0..bar).chain(16 - bar..16) {
+            pixels[y * 64..(y + 1) * 64].fill(0);
+        }
+        pixels
+    }
+
+    fn frame(pixels: &[u8], frame_id: u64) -> ScreenFrame<'_> {
+        ScreenFrame {
+            width: 16,
+            height: 16,
+            stride: 64,
+            pixels,
+            dirty_regions: &[],
+            frame_id,
+            captured_at: std::time::Instant::now(),
+        }
+    }
+
+    #[test]
+    fn detection_is_shared_per_frame_and_display() {
+        // Displays far away from anything a real subscription would use.
+        let (display, other_display) = (9_000, 9_001);
+        let detector = BlackBorderDetector::new(5.0);
+        let mode = BlackBorderMode::Default;
+        let bars = letterboxed(4);
+        let clean = letterboxed(0);
+
+        let id = next_frame_id();
+        let first = detect_cached(display, &detector, &frame(&bars, id), mode);
+        assert_eq!(first.horizontal_size, 4);
+
+        // Same id on the same display is served from the cache, even though the pixels
+        // passed in differ; that is what lets other subscribers skip the scan.
+        let cached = detect_cached(display, &detector, &frame(&clean, id), mode);
+        assert_eq!(cached, first);
+
+        // Another display, or a new frame, is scanned again.
+        let elsewhere = detect_cached(other_display, &detector, &frame(&clean, id), mode);
+        assert_eq!(elsewhere.horizontal_size, 0);
+        let next = detect_cached(display, &detector, &frame(&clean, next_frame_id()), mode);
+        assert_eq!(next.horizontal_size, 0);
+    }
+}
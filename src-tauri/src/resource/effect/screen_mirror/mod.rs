@@ -1,20 +1,69 @@
+pub mod benchmark;
+pub mod preview;
 pub mod border;
 pub mod params;
 pub mod renderer;
 
-use crate::interface::controller::Color;
-use crate::interface::effect::{Effect, EffectMetadata};
-use crate::resource::screen::ScreenSubscription;
+use crate::interface::controller::{Color, LedPosition};
+use crate::interface::effect::{CaptureFailure, Effect, EffectLayout, EffectMetadata};
+use crate::resource::screen::{
+    get_capture_fps, get_sample_ratio, ScreenCaptureError, ScreenSubscription, ALL_DISPLAYS,
+};
 use border::{BlackBorderProcessor, BlackBorderMode};
-use renderer::{render_frame, CropRegion};
+use renderer::{
+    perimeter_regions, position_regions, render_frame, split_region, CalibrationTables,
+    ColorCalibration, CropRegion, ScreenRect,
+};
 use std::cell::RefCell;
 use inventory;
 use params::SCREEN_PARAMS;
 use std::time::Duration;
 
+/// How long a faded-out stale frame takes to reach black.
+const STALE_FADE: Duration = Duration::from_secs(2);
+
+/// What to show once the captured frame is older than the staleness threshold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StaleFallback {
+    /// Keep showing the last colors.
+    Hold,
+    /// Fade the last colors out over `STALE_FADE`.
+    FadeToBlack,
+}
+
+impl StaleFallback {
+    /// Brightness to show the last rendered colors at, or `None` while the frame is fresh
+    /// enough to render.
+    fn scale(self, age: Duration, stale_after: Option<Duration>) -> Option<f32> {
+        let overdue = age.checked_sub(stale_after?).filter(|d| !d.is_zero())?;
+        Some(match self {
+            Self::Hold => 1.0,
+            Self::FadeToBlack => 1.0 - (overdue.as_secs_f32() / STALE_FADE.as_secs_f32()).min(1.0),
+        })
+    }
+}
+
+/// How LEDs are assigned to parts of the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mapping {
+    /// Point-sample the layout spread evenly over the screen.
+    Grid,
+    /// Average edge zones: a perimeter around the grid, or around the LED coordinates.
+    Zones,
+    /// Spread this scope's LEDs along one screen rect (e.g. a strip segment on the left edge).
+    Region,
+}
+
 pub struct ScreenMirrorEffect {
     width: usize,
     height: usize,
+    positions: Option<Vec<LedPosition>>,
+    mapping: Mapping,
+    /// Zone depth as a fraction of the screen.
+    zone_depth: f32,
+    region: ScreenRect,
+    /// One screen rect per LED, rebuilt whenever the layout or mapping changes.
+    regions: Option<Vec<ScreenRect>>,
     screen: Option<ScreenSubscription>,
     display_index: usize,
     smoothness: u32,
@@ -22,8 +71,18 @@ pub struct ScreenMirrorEffect {
     brightness: f32,
     saturation: f32,
     gamma: f32,
+    calibration: ColorCalibration,
+    /// Built from `calibration` when it changes; `None` while it's the identity.
+    calibration_tables: Option<CalibrationTables>,
     black_border: RefCell<BlackBorderProcessor>,
+    /// Frames older than this count as a stalled capture; `None` disables the check.
+    stale_after: Option<Duration>,
+    stale_fallback: StaleFallback,
+    /// Whether the last tick showed the stale fallback, so the transition is logged once.
+    stale: bool,
     has_captured_frame: bool,
+    /// Why the last capture attempt failed; cleared by the next captured frame.
+    capture_error: Option<CaptureFailure>,
     previous_buffer: Vec<Color>,
 }
 
@@ -38,6 +97,11 @@ impl ScreenMirrorEffect {
         Self {
             width: 0,
             height: 0,
+            positions: None,
+            mapping: Mapping::Grid,
+            zone_depth: 0.15,
+            region: ScreenRect::FULL,
+            regions: None,
             screen: None,
             display_index: 0,
             smoothness: 80,
@@ -45,12 +109,26 @@ impl ScreenMirrorEffect {
             brightness: 1.0,
             saturation: 1.0,
             gamma: 1.0,
+            calibration: ColorCalibration::default(),
+            calibration_tables: None,
             black_border: RefCell::new(BlackBorderProcessor::new()),
+            stale_after: Some(Duration::from_secs(5)),
+            stale_fallback: StaleFallback::Hold,
+            stale: false,
             has_captured_frame: false,
+            capture_error: None,
             previous_buffer: Vec::new(),
         }
     }
 
+    fn record_failure(&mut self, err: &ScreenCaptureError) {
+        self.capture_error = Some(CaptureFailure {
+            display_index: self.display_index,
+            code: err.code(),
+            message: err.to_string(),
+        });
+    }
+
     fn ensure_subscription(&mut self) -> bool {
         if self.screen.is_none() {
             match ScreenSubscription::new(self.display_index) {
@@ -63,6 +141,7 @@ impl ScreenMirrorEffect {
                         err:display = err;
                         "[screen-mirror] Failed to init screen subscription"
                     );
+                    self.record_failure(&err);
                     self.screen = None;
                 }
             }
@@ -71,6 +150,20 @@ impl ScreenMirrorEffect {
         self.screen.is_some()
     }
 
+    fn rebuild_regions(&mut self) {
+        self.regions = match self.mapping {
+            Mapping::Grid => None,
+            Mapping::Zones => Some(match &self.positions {
+                Some(positions) => position_regions(positions, self.zone_depth),
+                None => perimeter_regions(self.width, self.height, self.zone_depth),
+            }),
+            Mapping::Region => Some(split_region(
+                self.region,
+                self.width.saturating_mul(self.height),
+            )),
+        };
+    }
+
     fn paint_black(&self, buffer: &mut [Color]) {
         buffer.fill(Color::default());
     }
@@ -87,6 +180,7 @@ impl ScreenMirrorEffect {
         }
 
         let prev = &mut self.previous_buffer;
+        let regions = self.regions.as_deref();
         let smoothness = self.smoothness;
         
         if let Some(subscription) = self.screen.as_mut() {
@@ -101,12 +195,31 @@ impl ScreenMirrorEffect {
             let brightness = self.brightness;
             let saturation = self.saturation;
             let gamma = self.gamma;
+            let calibration = self.calibration_tables.as_ref();
+            let sample_ratio = get_sample_ratio();
+            let display_index = subscription.display_index();
+            let (stale_after, stale_fallback) = (self.stale_after, self.stale_fallback);
+            let mut stale = false;
+
+            let result = subscription.capture_with(|frame| {
+                // Re-show the last output rather than the frame: `prev` holds exactly what
+                // the LEDs displayed before the capture stalled.
+                if let Some(scale) = stale_fallback.scale(frame.captured_at.elapsed(), stale_after) {
+                    stale = true;
+                    for (led, last) in buffer.iter_mut().zip(prev.iter()) {
+                        *led = Color {
+                            r: (last.r as f32 * scale) as u8,
+                            g: (last.g as f32 * scale) as u8,
+                            b: (last.b as f32 * scale) as u8,
+                        };
+                    }
+                    return;
+                }
 
-            match subscription.capture_with(|frame| {
                 let crop = if auto_crop_enabled {
                     let mut processor = black_border.borrow_mut();
                     processor.set_enabled(true);
-                    processor.process_frame(frame);
+                    processor.process_frame(display_index, frame);
                     processor.crop_region_for(frame)
                 } else {
                     CropRegion::default()
@@ -114,6 +227,7 @@ impl ScreenMirrorEffect {
 
                 render_frame(
                     layout,
+                    regions,
                     frame,
                     buffer,
                     prev,
@@ -122,17 +236,36 @@ impl ScreenMirrorEffect {
                     brightness,
                     saturation,
                     gamma,
+                    calibration,
+                    sample_ratio,
                 )
-            }) {
+            });
+
+            if stale != self.stale {
+                self.stale = stale;
+                if stale {
+                    log::warn!(display_index = display_index; "[screen-mirror] capture stalled, showing fallback");
+                } else {
+                    log::info!(display_index = display_index; "[screen-mirror] capture resumed");
+                }
+            }
+
+            match result {
                 Ok(true) => {
+                    self.capture_error = None;
                     return true;
                 }
                 Ok(false) => {
                     // No active duplicator for this display yet.
                     return false;
                 }
+                Err(ScreenCaptureError::NoFrame) => {
+                    // The capturer is fine, the first frame just hasn't arrived.
+                    return false;
+                }
                 Err(err) => {
                     log::warn!(err:display = err; "[screen-mirror] capture error");
+                    self.record_failure(&err);
                     // Drop current subscription so that a new one (and duplicator)
                     // will be created on the next tick if needed.
                     self.screen = None;
@@ -155,7 +288,7 @@ impl Effect for ScreenMirrorEffect {
         "Screen Mirror".to_string()
     }
 
-    fn tick(&mut self, _elapsed: Duration, buffer: &mut [Color]) {
+    fn tick(&mut self, _time: Duration, buffer: &mut [Color]) {
         if buffer.is_empty() {
             return;
         }
@@ -172,9 +305,25 @@ impl Effect for ScreenMirrorEffect {
         self.screen.is_some() && self.has_captured_frame
     }
 
+    fn capture_error(&self) -> Option<CaptureFailure> {
+        self.capture_error.clone()
+    }
+
+    /// Rendering faster than frames arrive would only repeat them (and run the smoothing
+    /// more often than intended).
+    fn preferred_fps(&self) -> Option<u32> {
+        Some(get_capture_fps() as u32)
+    }
+
     fn resize(&mut self, width: usize, height: usize) {
         self.width = width;
         self.height = height;
+        self.rebuild_regions();
+    }
+
+    fn set_positions(&mut self, positions: Option<&[LedPosition]>) {
+        self.positions = positions.map(<[LedPosition]>::to_vec);
+        self.rebuild_regions();
     }
 
     fn update_params(&mut self, _params: serde_json::Value) {
@@ -198,6 +347,74 @@ impl Effect for ScreenMirrorEffect {
         if let Some(val) = _params.get("gamma").and_then(|v| v.as_f64()) {
             self.gamma = val as f32;
         }
+        {
+            let gamma = |key: &str, current: f32| {
+                _params
+                    .get(key)
+                    .and_then(|v| v.as_f64())
+                    .map_or(current, |v| v.clamp(0.1, 4.0) as f32)
+            };
+            let white = |key: &str, current: u8| {
+                _params
+                    .get(key)
+                    .and_then(|v| v.as_f64())
+                    .map_or(current, |v| v.clamp(0.0, 255.0).round() as u8)
+            };
+            let current = self.calibration;
+            let calibration = ColorCalibration {
+                gamma_r: gamma("gammaR", current.gamma_r),
+                gamma_g: gamma("gammaG", current.gamma_g),
+                gamma_b: gamma("gammaB", current.gamma_b),
+                white_r: white("whiteR", current.white_r),
+                white_g: white("whiteG", current.white_g),
+                white_b: white("whiteB", current.white_b),
+            };
+            if calibration != current {
+                self.calibration = calibration;
+                self.calibration_tables =
+                    (!calibration.is_identity()).then(|| CalibrationTables::new(&calibration));
+            }
+        }
+
+        if let Some(secs) = _params.get("staleTimeout").and_then(|v| v.as_f64()) {
+            self.stale_after = (secs > 0.0).then(|| Duration::from_secs_f64(secs));
+        }
+        if let Some(mode) = _params.get("staleFallback").and_then(|v| v.as_f64()) {
+            self.stale_fallback = match mode as i64 {
+                1 => StaleFallback::FadeToBlack,
+                _ => StaleFallback::Hold,
+            };
+        }
+
+        if let Some(mapping) = _params.get("mapping").and_then(|v| v.as_f64()) {
+            self.mapping = match mapping as i64 {
+                1 => Mapping::Zones,
+                2 => Mapping::Region,
+                _ => Mapping::Grid,
+            };
+        }
+        if let Some(depth) = _params.get("zoneDepth").and_then(|v| v.as_f64()) {
+            self.zone_depth = (depth / 100.0).clamp(0.01, 0.5) as f32;
+        }
+        {
+            let edge = |key: &str, current: f32| {
+                _params
+                    .get(key)
+                    .and_then(|v| v.as_f64())
+                    .map_or(current, |v| (v / 100.0).clamp(0.0, 1.0) as f32)
+            };
+            let left = edge("regionLeft", self.region.left);
+            let top = edge("regionTop", self.region.top);
+            let right = edge("regionRight", self.region.right);
+            let bottom = edge("regionBottom", self.region.bottom);
+            self.region = ScreenRect {
+                left: left.min(right),
+                top: top.min(bottom),
+                right: left.max(right),
+                bottom: top.max(bottom),
+            };
+        }
+        self.rebuild_regions();
 
         {
             let mut bb = self.black_border.borrow_mut();
@@ -241,16 +458,18 @@ impl Effect for ScreenMirrorEffect {
 
 
         // Display index selection - available on all platforms
-        if let Some(display_index_value) =
-            _params.get("displayIndex").and_then(|value| value.as_u64())
-        {
-            let idx = display_index_value as usize;
+        // -1 selects the whole virtual desktop.
+        if let Some(idx) = _params.get("displayIndex").and_then(|value| match value.as_i64() {
+            Some(-1) => Some(ALL_DISPLAYS),
+            _ => value.as_u64().map(|idx| idx as usize),
+        }) {
             if idx != self.display_index {
                 self.display_index = idx;
                 // Drop existing subscription so that the next capture will
                 // attach to the newly selected display via the manager.
                 self.screen = None;
                 self.has_captured_frame = false;
+                self.capture_error = None;
             }
         }
     }
@@ -266,6 +485,33 @@ inventory::submit!(EffectMetadata {
     description: Some("Mirror the desktop colors onto matrices or strips"),
     group: Some("Screen Sync"),
     icon: Some("Monitor"),
+    layout: EffectLayout::Any,
     params: &SCREEN_PARAMS,
     factory,
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_frames_trigger_the_configured_fallback() {
+        let threshold = Some(Duration::from_secs(5));
+        let fresh = Duration::from_secs(4);
+        let overdue = Duration::from_secs(6);
+
+        assert_eq!(StaleFallback::Hold.scale(fresh, threshold), None);
+        assert_eq!(StaleFallback::FadeToBlack.scale(fresh, threshold), None);
+
+        assert_eq!(StaleFallback::Hold.scale(overdue, threshold), Some(1.0));
+        // Halfway through the fade, then fully black.
+        assert_eq!(StaleFallback::FadeToBlack.scale(overdue, threshold), Some(0.5));
+        assert_eq!(
+            StaleFallback::FadeToBlack.scale(Duration::from_secs(60), threshold),
+            Some(0.0)
+        );
+
+        // Disabled check never reports stale.
+        assert_eq!(StaleFallback::FadeToBlack.scale(Duration::from_secs(60), None), None);
+    }
+}
@@ -22,23 +22,107 @@ const BLACK_BORDER_MODE_OPTIONS: [StaticSelectOption; 4] = [
     },
 ];
 
+const MAPPING_OPTIONS: [StaticSelectOption; 3] = [
+    StaticSelectOption {
+        label: "均匀平铺",
+        value: 0.0,
+    },
+    StaticSelectOption {
+        label: "边缘分区",
+        value: 1.0,
+    },
+    StaticSelectOption {
+        label: "自定义区域",
+        value: 2.0,
+    },
+];
+
+const STALE_FALLBACK_OPTIONS: [StaticSelectOption; 2] = [
+    StaticSelectOption {
+        label: "保持最后画面",
+        value: 0.0,
+    },
+    StaticSelectOption {
+        label: "渐隐至黑色",
+        value: 1.0,
+    },
+];
+
+const fn region_edge_param(key: &'static str, label: &'static str, default: f64) -> EffectParam {
+    EffectParam {
+        key,
+        label,
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 100.0,
+            step: 1.0,
+            default,
+        },
+        dependency: Some(EffectParamDependency::Dependency {
+            key: "mapping",
+            equals: Some(2.0),
+            not_equals: None,
+            behavior: DependencyBehavior::Hide,
+        }),
+    }
+}
+
+const fn channel_gamma_param(key: &'static str, label: &'static str) -> EffectParam {
+    EffectParam {
+        key,
+        label,
+        kind: EffectParamKind::Slider {
+            min: 0.1,
+            max: 4.0,
+            step: 0.05,
+            default: 1.0,
+        },
+        dependency: None,
+    }
+}
+
+const fn white_point_param(key: &'static str, label: &'static str) -> EffectParam {
+    EffectParam {
+        key,
+        label,
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 255.0,
+            step: 1.0,
+            default: 255.0,
+        },
+        dependency: None,
+    }
+}
+
 fn screen_source_options() -> Result<Vec<SelectOption>, String> {
     use crate::resource::screen::list_displays;
 
-    list_displays()
-        .map(|displays| {
-            displays
-                .into_iter()
-                .map(|display| SelectOption {
-                    label: format!("{} ({}x{})", display.name, display.width, display.height),
-                    value: display.index as f64,
-                })
-                .collect()
+    let displays = list_displays().map_err(|err| err.to_string())?;
+    let mut options: Vec<SelectOption> = displays
+        .iter()
+        .map(|display| SelectOption {
+            label: format!("{} ({}x{})", display.name, display.width, display.height),
+            value: display.index as f64,
         })
-        .map_err(|err| err.to_string())
+        .collect();
+
+    if displays.len() > 1 {
+        // Spans the bounding box of every display; costs a capture per display.
+        let left = displays.iter().map(|d| d.x).min().unwrap_or(0);
+        let top = displays.iter().map(|d| d.y).min().unwrap_or(0);
+        let right = displays.iter().map(|d| d.x + d.width as i32).max().unwrap_or(0);
+        let bottom = displays.iter().map(|d| d.y + d.height as i32).max().unwrap_or(0);
+        options.push(SelectOption {
+            label: format!("所有显示器 ({}x{})", right - left, bottom - top),
+            value: -1.0,
+        });
+    }
+
+    Ok(options)
 }
 
-pub const SCREEN_PARAMS: [EffectParam; 12] = [
+pub const SCREEN_PARAMS: [EffectParam; 26] = [
     EffectParam {
         key: "displayIndex",
         label: "屏幕来源",
@@ -92,6 +176,66 @@ pub const SCREEN_PARAMS: [EffectParam; 12] = [
         },
         dependency: None,
     },
+    channel_gamma_param("gammaR", "红色通道 Gamma"),
+    channel_gamma_param("gammaG", "绿色通道 Gamma"),
+    channel_gamma_param("gammaB", "蓝色通道 Gamma"),
+    white_point_param("whiteR", "白平衡 红"),
+    white_point_param("whiteG", "白平衡 绿"),
+    white_point_param("whiteB", "白平衡 蓝"),
+    EffectParam {
+        key: "mapping",
+        label: "映射方式",
+        kind: EffectParamKind::Select {
+            default: 0.0,
+            options: SelectOptions::Static(&MAPPING_OPTIONS),
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "zoneDepth",
+        label: "分区深度 (%)",
+        kind: EffectParamKind::Slider {
+            min: 1.0,
+            max: 50.0,
+            step: 1.0,
+            default: 15.0,
+        },
+        dependency: Some(EffectParamDependency::Dependency {
+            key: "mapping",
+            equals: Some(1.0),
+            not_equals: None,
+            behavior: DependencyBehavior::Hide,
+        }),
+    },
+    region_edge_param("regionLeft", "区域左边界 (%)", 0.0),
+    region_edge_param("regionTop", "区域上边界 (%)", 0.0),
+    region_edge_param("regionRight", "区域右边界 (%)", 100.0),
+    region_edge_param("regionBottom", "区域下边界 (%)", 100.0),
+    EffectParam {
+        key: "staleTimeout",
+        label: "画面停滞判定 (秒, 0 为关闭)",
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 30.0,
+            step: 1.0,
+            default: 5.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "staleFallback",
+        label: "画面停滞时",
+        kind: EffectParamKind::Select {
+            default: 0.0,
+            options: SelectOptions::Static(&STALE_FALLBACK_OPTIONS),
+        },
+        dependency: Some(EffectParamDependency::Dependency {
+            key: "staleTimeout",
+            equals: None,
+            not_equals: Some(0.0),
+            behavior: DependencyBehavior::Hide,
+        }),
+    },
     EffectParam {
         key: "autoCrop",
         label: "黑边裁剪",
@@ -0,0 +1,130 @@
+use crate::resource::screen::{ScreenFrame, ScreenSubscription};
+use base64::Engine;
+use image::codecs::png::PngEncoder;
+use image::{ExtendedColorType, ImageEncoder};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Previews are scaled down to this width; smaller frames keep their size.
+const PREVIEW_WIDTH: u32 = 320;
+
+/// How long to wait for the first frame, which a freshly started capturer can take a moment
+/// to deliver.
+const FRAME_TIMEOUT: Duration = Duration::from_millis(500);
+const RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewImage {
+    /// Base64-encoded PNG.
+    pub png_base64: String,
+    /// Size of the PNG.
+    pub width: u32,
+    pub height: u32,
+    /// Size of the captured frame, before scaling for the preview.
+    pub frame_width: u32,
+    pub frame_height: u32,
+}
+
+/// Grab one frame of a display as a small PNG.
+///
+/// Takes its own subscription, so an effect already capturing the display shares the
+/// capturer and keeps it after the preview is done.
+pub fn capture_preview(display_index: usize) -> Result<PreviewImage, String> {
+    let mut subscription = ScreenSubscription::new(display_index)
+        .map_err(|e| format!("Failed to subscribe to display {}: {}", display_index, e))?;
+
+    let started = Instant::now();
+    let mut preview = None;
+    let mut last_error = None;
+    loop {
+        match subscription.capture_with(|frame| preview = Some(downscale_to_rgba(frame))) {
+            Ok(true) => break,
+            Ok(false) => {}
+            Err(err) => last_error = Some(err),
+        }
+        if started.elapsed() >= FRAME_TIMEOUT {
+            let reason = last_error.map_or_else(String::new, |err| format!(" ({})", err));
+            return Err(format!(
+                "No frame from display {} within {} ms{}",
+                display_index,
+                FRAME_TIMEOUT.as_millis(),
+                reason
+            ));
+        }
+        std::thread::sleep(RETRY_INTERVAL);
+    }
+
+    let (rgba, (width, height), (frame_width, frame_height)) = preview
+        .flatten()
+        .ok_or_else(|| format!("Display {} returned an empty frame", display_index))?;
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png)
+        .write_image(&rgba, width, height, ExtendedColorType::Rgba8)
+        .map_err(|e| format!("Failed to encode preview: {}", e))?;
+
+    Ok(PreviewImage {
+        png_base64: base64::engine::general_purpose::STANDARD.encode(&png),
+        width,
+        height,
+        frame_width,
+        frame_height,
+    })
+}
+
+type Rgba = (Vec<u8>, (u32, u32), (u32, u32));
+
+/// Nearest-neighbour scale of a BGRA frame to at most `PREVIEW_WIDTH` wide, as opaque RGBA;
+/// backends don't all fill in alpha. Returns the pixels, their size and the frame's size.
+fn downscale_to_rgba(frame: &ScreenFrame<'_>) -> Option<Rgba> {
+    if frame.width == 0 || frame.height == 0 || frame.pixels.is_empty() {
+        return None;
+    }
+    let width = frame.width.min(PREVIEW_WIDTH);
+    let height = ((frame.height as u64 * width as u64 + frame.width as u64 / 2)
+        / frame.width as u64)
+        .max(1) as u32;
+
+    let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+    for y in 0..height as usize {
+        let src_y = y * frame.height as usize / height as usize;
+        let row = frame.pixels.get(src_y * frame.stride..)?;
+        for x in 0..width as usize {
+            let src_x = x * frame.width as usize / width as usize;
+            let bgra = row.get(src_x * 4..src_x * 4 + 4)?;
+            rgba.extend_from_slice(&[bgra[2], bgra[1], bgra[0], 255]);
+        }
+    }
+    Some((rgba, (width, height), (frame.width, frame.height)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_are_scaled_down_and_swizzled() {
+        // 640x2 BGRA: left half blue, right half red.
+        let (width, height) = (640u32, 2u32);
+        let pixels: Vec<u8> = (0..height)
+            .flat_map(|_| {
+                (0..width).flat_map(|x| if x < 320 { [255, 0, 0, 0] } else { [0, 0, 255, 0] })
+            })
+            .collect();
+        let frame = ScreenFrame {
+            width,
+            height,
+            stride: width as usize * 4,
+            pixels: &pixels,
+            dirty_regions: &[],
+            frame_id: 1,
+            captured_at: Instant::now(),
+        };
+
+        let (rgba, size, frame_size) = downscale_to_rgba(&frame).unwrap();
+        assert_eq!(size, (320, 1));
+        assert_eq!(frame_size, (640, 2));
+        assert_eq!(&rgba[..4], &[0, 0, 255, 255]);
+        assert_eq!(&rgba[rgba.len() - 4..], &[255, 0, 0, 255]);
+    }
+}
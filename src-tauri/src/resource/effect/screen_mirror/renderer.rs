@@ -1,4 +1,5 @@
-use crate::interface::controller::Color;
+use crate::interface::controller::{Color, LedPosition};
+use crate::resource::effect::parallel::for_each_led;
 use crate::resource::screen::ScreenFrame;
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -9,9 +10,192 @@ pub struct CropRegion {
     pub bottom: f32,
 }
 
+/// Normalized screen area (0..1 on both axes) whose average color drives one LED.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScreenRect {
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+}
+
+impl ScreenRect {
+    pub const FULL: Self = Self {
+        left: 0.0,
+        top: 0.0,
+        right: 1.0,
+        bottom: 1.0,
+    };
+}
+
+/// Zones for a `width` x `height` grid: border cells average a band `depth` deep along their
+/// edge (how bias-light kits split a monitor into zones), interior cells their own tile.
+pub fn perimeter_regions(width: usize, height: usize, depth: f32) -> Vec<ScreenRect> {
+    let (width, height) = (width.max(1), height.max(1));
+    let depth = depth.clamp(0.01, 0.5);
+
+    (0..width * height)
+        .map(|idx| {
+            let (x, y) = (idx % width, idx / width);
+            let mut rect = ScreenRect {
+                left: x as f32 / width as f32,
+                top: y as f32 / height as f32,
+                right: (x + 1) as f32 / width as f32,
+                bottom: (y + 1) as f32 / height as f32,
+            };
+            if width > 1 && x == 0 {
+                rect.right = rect.right.max(depth);
+            }
+            if width > 1 && x == width - 1 {
+                rect.left = rect.left.min(1.0 - depth);
+            }
+            if height > 1 && y == 0 {
+                rect.bottom = rect.bottom.max(depth);
+            }
+            if height > 1 && y == height - 1 {
+                rect.top = rect.top.min(1.0 - depth);
+            }
+            rect
+        })
+        .collect()
+}
+
+/// Zones for LEDs placed by coordinate. Positions are stretched over the screen by their
+/// bounding box, and each LED averages a `depth`-sized square around its spot, kept on screen.
+pub fn position_regions(positions: &[LedPosition], depth: f32) -> Vec<ScreenRect> {
+    let depth = depth.clamp(0.01, 1.0);
+    let (min_x, max_x, min_y, max_y) = positions.iter().fold(
+        (f32::MAX, f32::MIN, f32::MAX, f32::MIN),
+        |(min_x, max_x, min_y, max_y), p| {
+            (min_x.min(p.x), max_x.max(p.x), min_y.min(p.y), max_y.max(p.y))
+        },
+    );
+    let normalize = |v: f32, min: f32, max: f32| {
+        if max - min > f32::EPSILON {
+            (v - min) / (max - min)
+        } else {
+            0.5
+        }
+    };
+
+    positions
+        .iter()
+        .map(|p| {
+            let left = (normalize(p.x, min_x, max_x) - depth * 0.5).clamp(0.0, 1.0 - depth);
+            let top = (normalize(p.y, min_y, max_y) - depth * 0.5).clamp(0.0, 1.0 - depth);
+            ScreenRect {
+                left,
+                top,
+                right: left + depth,
+                bottom: top + depth,
+            }
+        })
+        .collect()
+}
+
+/// Split `rect` into `leds` equal zones along its longer side, in reading order.
+pub fn split_region(rect: ScreenRect, leds: usize) -> Vec<ScreenRect> {
+    let (w, h) = (rect.right - rect.left, rect.bottom - rect.top);
+    (0..leds)
+        .map(|i| {
+            let (from, to) = (i as f32 / leds as f32, (i + 1) as f32 / leds as f32);
+            if w >= h {
+                ScreenRect {
+                    left: rect.left + w * from,
+                    right: rect.left + w * to,
+                    ..rect
+                }
+            } else {
+                ScreenRect {
+                    top: rect.top + h * from,
+                    bottom: rect.top + h * to,
+                    ..rect
+                }
+            }
+        })
+        .collect()
+}
+
+/// Per-channel gamma and white point of the strip, so its white looks white and dim colors
+/// don't drift pink or green. The default changes nothing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorCalibration {
+    pub gamma_r: f32,
+    pub gamma_g: f32,
+    pub gamma_b: f32,
+    /// Level each channel shows for a full-white input.
+    pub white_r: u8,
+    pub white_g: u8,
+    pub white_b: u8,
+}
+
+impl Default for ColorCalibration {
+    fn default() -> Self {
+        Self {
+            gamma_r: 1.0,
+            gamma_g: 1.0,
+            gamma_b: 1.0,
+            white_r: 255,
+            white_g: 255,
+            white_b: 255,
+        }
+    }
+}
+
+impl ColorCalibration {
+    pub fn is_identity(&self) -> bool {
+        let unit = |gamma: f32| (gamma - 1.0).abs() <= 0.01;
+        unit(self.gamma_r)
+            && unit(self.gamma_g)
+            && unit(self.gamma_b)
+            && (self.white_r, self.white_g, self.white_b) == (255, 255, 255)
+    }
+}
+
+/// A [`ColorCalibration`] as one lookup table per channel, `white * (v / 255)^gamma`. Built
+/// when the calibration changes so rendering only indexes.
+pub struct CalibrationTables {
+    r: [u8; 256],
+    g: [u8; 256],
+    b: [u8; 256],
+}
+
+impl CalibrationTables {
+    pub fn new(calibration: &ColorCalibration) -> Self {
+        let table = |gamma: f32, white: u8| {
+            let gamma = gamma.max(0.01);
+            let mut lut = [0u8; 256];
+            for (v, out) in lut.iter_mut().enumerate() {
+                *out = (white as f32 * (v as f32 / 255.0).powf(gamma)).round().clamp(0.0, 255.0) as u8;
+            }
+            lut
+        };
+        Self {
+            r: table(calibration.gamma_r, calibration.white_r),
+            g: table(calibration.gamma_g, calibration.white_g),
+            b: table(calibration.gamma_b, calibration.white_b),
+        }
+    }
+
+    fn apply(&self, colors: &mut [Color]) {
+        for color in colors {
+            *color = Color {
+                r: self.r[color.r as usize],
+                g: self.g[color.g as usize],
+                b: self.b[color.b as usize],
+            };
+        }
+    }
+}
+
+/// Render one frame into `buffer`. `regions` (one per LED) replaces the uniform tiling of
+/// `layout` when it covers the whole buffer; `sample_ratio` is the percentage of each
+/// region's pixels that are averaged. `calibration` is applied after the color adjustments
+/// and before smoothing.
 #[allow(clippy::too_many_arguments)]
 pub fn render_frame(
     layout: (usize, usize),
+    regions: Option<&[ScreenRect]>,
     frame: &ScreenFrame<'_>,
     buffer: &mut [Color],
     previous_buffer: &mut [Color],
@@ -20,11 +204,16 @@ pub fn render_frame(
     brightness: f32,
     saturation: f32,
     gamma: f32,
+    calibration: Option<&CalibrationTables>,
+    sample_ratio: u8,
 ) {
-    if layout.1 <= 1 {
-        render_linear(frame, buffer, previous_buffer, smoothness, crop, brightness, saturation, gamma);
+    if let Some(regions) = regions.filter(|r| r.len() == buffer.len()) {
+        let step = sample_step(sample_ratio);
+        render_regions(regions, frame, buffer, previous_buffer, smoothness, crop, brightness, saturation, gamma, calibration, step);
+    } else if layout.1 <= 1 {
+        render_linear(frame, buffer, previous_buffer, smoothness, crop, brightness, saturation, gamma, calibration);
     } else {
-        render_matrix(layout, frame, buffer, previous_buffer, smoothness, crop, brightness, saturation, gamma);
+        render_matrix(layout, frame, buffer, previous_buffer, smoothness, crop, brightness, saturation, gamma, calibration);
     }
 }
 
@@ -58,29 +247,26 @@ fn render_linear(
     brightness: f32,
     saturation: f32,
     gamma: f32,
+    calibration: Option<&CalibrationTables>,
 ) {
     let leds = buffer.len();
     if leds == 0 {
         return;
     }
 
-    for (index, color) in buffer.iter_mut().enumerate() {
+    for_each_led(buffer, |index, color| {
         let ratio_x = if leds == 1 {
             0.5
         } else {
             (index as f32 + 0.5) / leds as f32
         };
-        let target = sample_pixel(frame, ratio_x, 0.5, crop, brightness, saturation, gamma);
-
-        if index < previous_buffer.len() {
-            let prev = previous_buffer[index];
-            let smoothed = smooth_color(prev, target, smoothness);
-            previous_buffer[index] = smoothed;
-            *color = smoothed;
-        } else {
-            *color = target;
-        }
+        *color = sample_pixel(frame, ratio_x, 0.5, crop);
+    });
+    ColorAdjust::new(brightness, saturation, gamma).apply(buffer);
+    if let Some(tables) = calibration {
+        tables.apply(buffer);
     }
+    smooth_into(previous_buffer, buffer, smoothness);
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -94,57 +280,70 @@ fn render_matrix(
     brightness: f32,
     saturation: f32,
     gamma: f32,
+    calibration: Option<&CalibrationTables>,
 ) {
     let width = layout.0.max(1);
     let height = layout.1.max(1);
     let total = width.saturating_mul(height);
     let max_len = buffer.len().min(total);
+    let buffer = &mut buffer[..max_len];
 
-    for y in 0..height {
-        for x in 0..width {
-            let idx = y * width + x;
-            if idx >= max_len {
-                return;
-            }
-
-            let ratio_x = if width == 1 {
-                0.5
-            } else {
-                (x as f32 + 0.5) / width as f32
-            };
-            let ratio_y = if height == 1 {
-                0.5
-            } else {
-                (y as f32 + 0.5) / height as f32
-            };
-
-            let target = sample_pixel(frame, ratio_x, ratio_y, crop, brightness, saturation, gamma);
-
-            if idx < previous_buffer.len() {
-                let prev = previous_buffer[idx];
-                let smoothed = smooth_color(prev, target, smoothness);
-                previous_buffer[idx] = smoothed;
-                buffer[idx] = smoothed;
-            } else {
-                buffer[idx] = target;
-            }
-        }
+    for_each_led(buffer, |idx, color| {
+        let (x, y) = (idx % width, idx / width);
+        let ratio_x = if width == 1 {
+            0.5
+        } else {
+            (x as f32 + 0.5) / width as f32
+        };
+        let ratio_y = if height == 1 {
+            0.5
+        } else {
+            (y as f32 + 0.5) / height as f32
+        };
+        *color = sample_pixel(frame, ratio_x, ratio_y, crop);
+    });
+    ColorAdjust::new(brightness, saturation, gamma).apply(buffer);
+    if let Some(tables) = calibration {
+        tables.apply(buffer);
     }
+    smooth_into(previous_buffer, buffer, smoothness);
 }
 
 #[allow(clippy::too_many_arguments)]
-fn sample_pixel(
+fn render_regions(
+    regions: &[ScreenRect],
     frame: &ScreenFrame<'_>,
-    ratio_x: f32,
-    ratio_y: f32,
+    buffer: &mut [Color],
+    previous_buffer: &mut [Color],
+    smoothness: u32,
     crop: &CropRegion,
     brightness: f32,
     saturation: f32,
     gamma: f32,
-) -> Color {
-    let width = frame.width.max(1);
-    let height = frame.height.max(1);
+    calibration: Option<&CalibrationTables>,
+    step: usize,
+) {
+    for_each_led(buffer, |idx, color| {
+        *color = sample_region(frame, &regions[idx], crop, step);
+    });
+    ColorAdjust::new(brightness, saturation, gamma).apply(buffer);
+    if let Some(tables) = calibration {
+        tables.apply(buffer);
+    }
+    smooth_into(previous_buffer, buffer, smoothness);
+}
+
+/// Blend `rendered` with the previous frame and remember the result for the next one.
+/// Runs after the (possibly parallel) sampling pass so workers never write shared state.
+fn smooth_into(previous_buffer: &mut [Color], rendered: &mut [Color], smoothness: u32) {
+    for (prev, color) in previous_buffer.iter_mut().zip(rendered.iter_mut()) {
+        *color = smooth_color(*prev, *color, smoothness);
+        *prev = *color;
+    }
+}
 
+/// Maps a position on the cropped screen (0..1 on both axes) onto the whole frame.
+fn crop_ratio(ratio_x: f32, ratio_y: f32, crop: &CropRegion) -> (f32, f32) {
     let crop_left = crop.left.clamp(0.0, 0.45);
     let crop_right = crop.right.clamp(0.0, 0.45);
     let crop_top = crop.top.clamp(0.0, 0.45);
@@ -155,51 +354,524 @@ fn sample_pixel(
 
     let rx = (crop_left + ratio_x.clamp(0.0, 1.0) * roi_width).clamp(0.0, 1.0);
     let ry = (crop_top + ratio_y.clamp(0.0, 1.0) * roi_height).clamp(0.0, 1.0);
+    (rx, ry)
+}
+
+fn pixel_at(frame: &ScreenFrame<'_>, x: usize, y: usize) -> Option<Color> {
+    let offset = y.saturating_mul(frame.stride).saturating_add(x * 4);
+    let bgr = frame.pixels.get(offset..offset + 3)?;
+    Some(Color {
+        r: bgr[2],
+        g: bgr[1],
+        b: bgr[0],
+    })
+}
+
+fn sample_pixel(frame: &ScreenFrame<'_>, ratio_x: f32, ratio_y: f32, crop: &CropRegion) -> Color {
+    let width = frame.width.max(1);
+    let height = frame.height.max(1);
+    let (rx, ry) = crop_ratio(ratio_x, ratio_y, crop);
 
-    let x = ((width - 1) as f32 * rx).round() as u32;
-    let y = ((height - 1) as f32 * ry).round() as u32;
+    let x = ((width - 1) as f32 * rx).round() as usize;
+    let y = ((height - 1) as f32 * ry).round() as usize;
+    pixel_at(frame, x, y).unwrap_or_default()
+}
+
+/// Pixels skipped between two samples of a region for a sample ratio in percent: 100
+/// reads every pixel, 50 every second one, 1 every hundredth.
+pub fn sample_step(sample_ratio: u8) -> usize {
+    (100.0 / sample_ratio.clamp(1, 100) as f32).round() as usize
+}
+
+/// Average of every `step`th pixel inside `rect`, counted in reading order. Consecutive rows
+/// start at different columns unless `step` divides the region width, so thin sampling
+/// still covers the whole region rather than a few columns of it.
+fn sample_region(frame: &ScreenFrame<'_>, rect: &ScreenRect, crop: &CropRegion, step: usize) -> Color {
+    let (width, height) = (frame.width as usize, frame.height as usize);
+    if width == 0 || height == 0 {
+        return Color::default();
+    }
+    let (left, top) = crop_ratio(rect.left, rect.top, crop);
+    let (right, bottom) = crop_ratio(rect.right, rect.bottom, crop);
+    let span = |from: f32, to: f32, extent: usize| {
+        let start = ((from * extent as f32).round() as usize).min(extent - 1);
+        let end = ((to * extent as f32).round() as usize).clamp(start + 1, extent);
+        (start, end - start)
+    };
+    let (x0, w) = span(left, right, width);
+    let (y0, h) = span(top, bottom, height);
+    let step = step.max(1);
 
-    let offset = (y as usize)
-        .saturating_mul(frame.stride)
-        .saturating_add(x as usize * 4);
+    let mut sums = [0u64; 4];
+    for row_index in 0..h {
+        let start = (y0 + row_index).saturating_mul(frame.stride).saturating_add(x0 * 4);
+        let row = frame.pixels.get(start..).unwrap_or_default();
+        let row = &row[..row.len().min(w * 4)];
+        if step == 1 {
+            sum_row(row, &mut sums);
+            continue;
+        }
+        // Column of the first sample in this row, continuing the count from the row above.
+        let first = (step - row_index * w % step) % step;
+        for x in (first..w).step_by(step) {
+            if let Some(&[b, g, r]) = row.get(x * 4..x * 4 + 3) {
+                sums[0] += b as u64;
+                sums[1] += g as u64;
+                sums[2] += r as u64;
+                sums[3] += 1;
+            }
+        }
+    }
 
-    if offset + 3 >= frame.pixels.len() {
+    let [b, g, r, n] = sums;
+    if n == 0 {
         return Color::default();
     }
+    Color {
+        r: (r / n) as u8,
+        g: (g / n) as u8,
+        b: (b / n) as u8,
+    }
+}
 
-    let mut r = frame.pixels[offset + 2];
-    let mut g = frame.pixels[offset + 1];
-    let mut b = frame.pixels[offset];
+/// Add the blue, green and red sums and the pixel count of one row of BGRA pixels to
+/// `sums`. A last pixel cut short after its red byte still counts, as in [`pixel_at`].
+fn sum_row(row: &[u8], sums: &mut [u64; 4]) {
+    let whole = row.len() / 4 * 4;
 
-    // Apply Saturation
-    if (saturation - 1.0).abs() > 0.01 {
-        // Simplified saturation logic
-        let gray = r as f32 * 0.299 + g as f32 * 0.587 + b as f32 * 0.114;
-        let sat_r = gray + (r as f32 - gray) * saturation;
-        let sat_g = gray + (g as f32 - gray) * saturation;
-        let sat_b = gray + (b as f32 - gray) * saturation;
-        
-        r = sat_r.clamp(0.0, 255.0) as u8;
-        g = sat_g.clamp(0.0, 255.0) as u8;
-        b = sat_b.clamp(0.0, 255.0) as u8;
+    #[cfg(target_arch = "x86_64")]
+    {
+        sum_pixels_sse2(&row[..whole], sums);
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        sum_pixels_scalar(&row[..whole], sums);
     }
 
-    // Apply Brightness
-    if (brightness - 1.0).abs() > 0.01 {
-         r = (r as f32 * brightness).clamp(0.0, 255.0) as u8;
-         g = (g as f32 * brightness).clamp(0.0, 255.0) as u8;
-         b = (b as f32 * brightness).clamp(0.0, 255.0) as u8;
+    if let &[b, g, r] = &row[whole..] {
+        sums[0] += b as u64;
+        sums[1] += g as u64;
+        sums[2] += r as u64;
+        sums[3] += 1;
     }
+}
 
-    // Apply Gamma
-    if (gamma - 1.0).abs() > 0.01 {
-        // let inv_gamma = 1.0 / gamma; // Not used currently, assuming direct power mapping
-        
-        r = (255.0 * (r as f32 / 255.0).powf(gamma)).clamp(0.0, 255.0) as u8;
-        g = (255.0 * (g as f32 / 255.0).powf(gamma)).clamp(0.0, 255.0) as u8;
-        b = (255.0 * (b as f32 / 255.0).powf(gamma)).clamp(0.0, 255.0) as u8;
+fn sum_pixels_scalar(pixels: &[u8], sums: &mut [u64; 4]) {
+    for px in pixels.chunks_exact(4) {
+        sums[0] += px[0] as u64;
+        sums[1] += px[1] as u64;
+        sums[2] += px[2] as u64;
+        sums[3] += 1;
     }
+}
+
+/// Four pixels per iteration, widened to 32-bit lanes. A lane gains at most 1020 per
+/// iteration, so a row would need over 16 million pixels to overflow it.
+#[cfg(target_arch = "x86_64")]
+fn sum_pixels_sse2(pixels: &[u8], sums: &mut [u64; 4]) {
+    use std::arch::x86_64::*;
+
+    let mut chunks = pixels.chunks_exact(16);
+    let mut lanes = [0u32; 4];
+    // SAFETY: only SSE2 intrinsics are used, which every x86_64 CPU supports; each load reads
+    // one 16-byte chunk and the store targets a local array of the right size.
+    unsafe {
+        let zero = _mm_setzero_si128();
+        let mut acc = zero;
+        for chunk in chunks.by_ref() {
+            let px = _mm_loadu_si128(chunk.as_ptr() as *const __m128i);
+            // Pixels 0+2 and 1+3 in 16-bit BGRA lanes, then both halves in 32-bit lanes.
+            let pairs = _mm_add_epi16(_mm_unpacklo_epi8(px, zero), _mm_unpackhi_epi8(px, zero));
+            let quad = _mm_add_epi32(_mm_unpacklo_epi16(pairs, zero), _mm_unpackhi_epi16(pairs, zero));
+            acc = _mm_add_epi32(acc, quad);
+        }
+        _mm_storeu_si128(lanes.as_mut_ptr() as *mut __m128i, acc);
+    }
+
+    sums[0] += lanes[0] as u64;
+    sums[1] += lanes[1] as u64;
+    sums[2] += lanes[2] as u64;
+    sums[3] += ((pixels.len() - chunks.remainder().len()) / 4) as u64;
+    sum_pixels_scalar(chunks.remainder(), sums);
+}
+
+/// Saturation, brightness and gamma, resolved once per frame.
+///
+/// Each stage quantizes back to `u8` before the next one, exactly like the original
+/// per-pixel code, so the SIMD path below is bit-identical to [`ColorAdjust::apply_scalar`].
+struct ColorAdjust {
+    saturation: Option<f32>,
+    brightness: Option<f32>,
+    /// `255 * (v / 255)^gamma` for every channel value.
+    gamma_lut: Option<[u8; 256]>,
+}
+
+impl ColorAdjust {
+    fn new(brightness: f32, saturation: f32, gamma: f32) -> Self {
+        let gamma_lut = ((gamma - 1.0).abs() > 0.01).then(|| {
+            let mut lut = [0u8; 256];
+            for (v, out) in lut.iter_mut().enumerate() {
+                *out = (255.0 * (v as f32 / 255.0).powf(gamma)).clamp(0.0, 255.0) as u8;
+            }
+            lut
+        });
+
+        Self {
+            saturation: ((saturation - 1.0).abs() > 0.01).then_some(saturation),
+            brightness: ((brightness - 1.0).abs() > 0.01).then_some(brightness),
+            gamma_lut,
+        }
+    }
+
+    fn is_identity(&self) -> bool {
+        self.saturation.is_none() && self.brightness.is_none() && self.gamma_lut.is_none()
+    }
+
+    fn apply(&self, colors: &mut [Color]) {
+        if self.is_identity() {
+            return;
+        }
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            self.apply_sse2(colors);
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            for color in colors.iter_mut() {
+                *color = self.apply_scalar(*color);
+            }
+        }
+    }
+
+    fn apply_scalar(&self, color: Color) -> Color {
+        let Color { mut r, mut g, mut b } = color;
+
+        if let Some(saturation) = self.saturation {
+            // Simplified saturation logic
+            let gray = r as f32 * 0.299 + g as f32 * 0.587 + b as f32 * 0.114;
+            let sat_r = gray + (r as f32 - gray) * saturation;
+            let sat_g = gray + (g as f32 - gray) * saturation;
+            let sat_b = gray + (b as f32 - gray) * saturation;
+
+            r = sat_r.clamp(0.0, 255.0) as u8;
+            g = sat_g.clamp(0.0, 255.0) as u8;
+            b = sat_b.clamp(0.0, 255.0) as u8;
+        }
+
+        if let Some(brightness) = self.brightness {
+            r = (r as f32 * brightness).clamp(0.0, 255.0) as u8;
+            g = (g as f32 * brightness).clamp(0.0, 255.0) as u8;
+            b = (b as f32 * brightness).clamp(0.0, 255.0) as u8;
+        }
+
+        if let Some(lut) = &self.gamma_lut {
+            r = lut[r as usize];
+            g = lut[g as usize];
+            b = lut[b as usize];
+        }
+
+        Color { r, g, b }
+    }
+
+    /// Four LEDs per iteration in f32 lanes. SSE2 is part of the x86_64 baseline, so no
+    /// runtime detection is needed.
+    #[cfg(target_arch = "x86_64")]
+    fn apply_sse2(&self, colors: &mut [Color]) {
+        use std::arch::x86_64::*;
+
+        let mut chunks = colors.chunks_exact_mut(4);
+        for px in chunks.by_ref() {
+            // SAFETY: only SSE2 intrinsics are used, which every x86_64 CPU supports, and the
+            // store targets a local array of the right size.
+            unsafe {
+                let lanes = |f: fn(&Color) -> u8| {
+                    _mm_set_ps(f(&px[3]) as f32, f(&px[2]) as f32, f(&px[1]) as f32, f(&px[0]) as f32)
+                };
+                let mut r = lanes(|c| c.r);
+                let mut g = lanes(|c| c.g);
+                let mut b = lanes(|c| c.b);
 
-    Color { r, g, b }
+                let zero = _mm_setzero_ps();
+                let max = _mm_set1_ps(255.0);
+                // clamp(0, 255) followed by `as u8` truncation.
+                let quantize = |v: __m128| _mm_cvtepi32_ps(_mm_cvttps_epi32(_mm_min_ps(_mm_max_ps(v, zero), max)));
+
+                if let Some(saturation) = self.saturation {
+                    let gray = _mm_add_ps(
+                        _mm_add_ps(
+                            _mm_mul_ps(r, _mm_set1_ps(0.299)),
+                            _mm_mul_ps(g, _mm_set1_ps(0.587)),
+                        ),
+                        _mm_mul_ps(b, _mm_set1_ps(0.114)),
+                    );
+                    let s = _mm_set1_ps(saturation);
+                    r = quantize(_mm_add_ps(gray, _mm_mul_ps(_mm_sub_ps(r, gray), s)));
+                    g = quantize(_mm_add_ps(gray, _mm_mul_ps(_mm_sub_ps(g, gray), s)));
+                    b = quantize(_mm_add_ps(gray, _mm_mul_ps(_mm_sub_ps(b, gray), s)));
+                }
+
+                if let Some(brightness) = self.brightness {
+                    let k = _mm_set1_ps(brightness);
+                    r = quantize(_mm_mul_ps(r, k));
+                    g = quantize(_mm_mul_ps(g, k));
+                    b = quantize(_mm_mul_ps(b, k));
+                }
+
+                let mut out = [[0i32; 4]; 3];
+                _mm_storeu_si128(out[0].as_mut_ptr() as *mut __m128i, _mm_cvttps_epi32(r));
+                _mm_storeu_si128(out[1].as_mut_ptr() as *mut __m128i, _mm_cvttps_epi32(g));
+                _mm_storeu_si128(out[2].as_mut_ptr() as *mut __m128i, _mm_cvttps_epi32(b));
+
+                for (i, color) in px.iter_mut().enumerate() {
+                    let (r, g, b) = (out[0][i] as u8, out[1][i] as u8, out[2][i] as u8);
+                    *color = match &self.gamma_lut {
+                        Some(lut) => Color {
+                            r: lut[r as usize],
+                            g: lut[g as usize],
+                            b: lut[b as usize],
+                        },
+                        None => Color { r, g, b },
+                    };
+                }
+            }
+        }
+
+        for color in chunks.into_remainder() {
+            *color = self.apply_scalar(*color);
+        }
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic xorshift so the test needs no extra dependencies.
+    fn random_colors(len: usize, mut seed: u32) -> Vec<Color> {
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as u8
+        };
+        (0..len)
+            .map(|_| Color {
+                r: next(),
+                g: next(),
+                b: next(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn simd_matches_scalar() {
+        let input = random_colors(4099, 0x1234_5678);
+        for &(brightness, saturation, gamma) in &[
+            (1.0, 1.0, 1.0),
+            (0.6, 1.0, 1.0),
+            (1.0, 1.8, 1.0),
+            (1.0, 0.3, 2.2),
+            (1.4, 2.0, 0.8),
+            (0.0, 0.0, 3.0),
+        ] {
+            let adjust = ColorAdjust::new(brightness, saturation, gamma);
+            let mut simd = input.clone();
+            adjust.apply(&mut simd);
+
+            for (i, (got, src)) in simd.iter().zip(&input).enumerate() {
+                let want = adjust.apply_scalar(*src);
+                assert!(
+                    got.r == want.r && got.g == want.g && got.b == want.b,
+                    "pixel {i} differs for ({brightness}, {saturation}, {gamma}): {got:?} vs {want:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn row_sums_match_scalar_on_a_random_frame() {
+        let (width, height) = (203usize, 17usize);
+        let bytes: Vec<u8> = random_colors(width * height * 4 / 3 + 1, 0x0bad_f00d)
+            .into_iter()
+            .flat_map(|c| [c.r, c.g, c.b])
+            .take(width * height * 4)
+            .collect();
+
+        for row in bytes.chunks(width * 4) {
+            // Every length, so the SIMD body, its remainder and a cut-short pixel are all hit.
+            for len in 0..row.len() {
+                let mut simd = [0u64; 4];
+                sum_row(&row[..len], &mut simd);
+                let mut scalar = [0u64; 4];
+                sum_pixels_scalar(&row[..len / 4 * 4], &mut scalar);
+                if len % 4 == 3 {
+                    sum_pixels_scalar(&[row[len - 3], row[len - 2], row[len - 1], 0], &mut scalar);
+                }
+                assert_eq!(simd, scalar, "row length {len}");
+            }
+        }
+
+        // The row walk picks the same pixels as counting through the region in reading order.
+        let frame = ScreenFrame {
+            width: width as u32,
+            height: height as u32,
+            stride: width * 4,
+            pixels: &bytes,
+            dirty_regions: &[],
+            frame_id: 0,
+            captured_at: std::time::Instant::now(),
+        };
+        let rect = ScreenRect {
+            left: 0.1,
+            top: 0.2,
+            right: 0.7,
+            bottom: 0.9,
+        };
+        let crop = CropRegion::default();
+        let (x0, y0) = ((0.1 * width as f32).round() as usize, (0.2 * height as f32).round() as usize);
+        let (w, h) = (
+            (0.7 * width as f32).round() as usize - x0,
+            (0.9 * height as f32).round() as usize - y0,
+        );
+        for step in [1, 2, 3, 7, 50] {
+            let (mut r, mut g, mut b, mut n) = (0u64, 0u64, 0u64, 0u64);
+            for i in (0..w * h).step_by(step) {
+                let c = pixel_at(&frame, x0 + i % w, y0 + i / w).unwrap();
+                r += c.r as u64;
+                g += c.g as u64;
+                b += c.b as u64;
+                n += 1;
+            }
+            let got = sample_region(&frame, &rect, &crop, step);
+            assert_eq!(
+                (got.r, got.g, got.b),
+                ((r / n) as u8, (g / n) as u8, (b / n) as u8),
+                "step {step}"
+            );
+        }
+    }
+
+    #[test]
+    fn calibration_tables_apply_gamma_and_white_point_per_channel() {
+        let original = random_colors(64, 0x9e37_79b9);
+        let mut colors = original.clone();
+        assert!(ColorCalibration::default().is_identity());
+        CalibrationTables::new(&ColorCalibration::default()).apply(&mut colors);
+        assert!(colors.iter().zip(&original).all(|(a, b)| (a.r, a.g, a.b) == (b.r, b.g, b.b)));
+
+        let calibration = ColorCalibration {
+            gamma_g: 2.0,
+            white_b: 200,
+            ..Default::default()
+        };
+        let mut colors = [Color { r: 128, g: 128, b: 255 }];
+        CalibrationTables::new(&calibration).apply(&mut colors);
+        assert_eq!((colors[0].r, colors[0].g, colors[0].b), (128, 64, 200));
+    }
+
+    #[test]
+    fn perimeter_zones_sample_their_screen_edge() {
+        // 40x40 BGRA frame: 8px red top band, blue bottom, green left, white right.
+        const SIZE: usize = 40;
+        const BAND: usize = 8;
+        let mut pixels = vec![0u8; SIZE * SIZE * 4];
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let bgr = if y < BAND {
+                    [0, 0, 255]
+                } else if y >= SIZE - BAND {
+                    [255, 0, 0]
+                } else if x < BAND {
+                    [0, 255, 0]
+                } else if x >= SIZE - BAND {
+                    [255, 255, 255]
+                } else {
+                    [0, 0, 0]
+                };
+                let offset = (y * SIZE + x) * 4;
+                pixels[offset..offset + 3].copy_from_slice(&bgr);
+            }
+        }
+        let frame = ScreenFrame {
+            width: SIZE as u32,
+            height: SIZE as u32,
+            stride: SIZE * 4,
+            pixels: &pixels,
+            dirty_regions: &[],
+            frame_id: 0,
+            captured_at: std::time::Instant::now(),
+        };
+
+        let (width, height) = (10, 8);
+        let regions = perimeter_regions(width, height, 0.2);
+        let mut buffer = vec![Color::default(); width * height];
+        let mut previous = buffer.clone();
+        render_frame(
+            (width, height),
+            Some(&regions),
+            &frame,
+            &mut buffer,
+            &mut previous,
+            0,
+            &CropRegion::default(),
+            1.0,
+            1.0,
+            1.0,
+            None,
+            100,
+        );
+
+        let at = |x: usize, y: usize| {
+            let c = buffer[y * width + x];
+            (c.r, c.g, c.b)
+        };
+        assert_eq!(at(4, 0), (255, 0, 0));
+        assert_eq!(at(5, height - 1), (0, 0, 255));
+        assert_eq!(at(0, 3), (0, 255, 0));
+        assert_eq!(at(width - 1, 4), (255, 255, 255));
+        assert_eq!(at(4, 3), (0, 0, 0));
+    }
+
+    #[test]
+    fn uniform_zones_average_to_the_same_color_at_any_sample_ratio() {
+        // Odd sizes so no step divides a zone evenly.
+        let (width, height) = (37usize, 23usize);
+        let pixels = [30u8, 200, 10, 255].repeat(width * height);
+        let frame = ScreenFrame {
+            width: width as u32,
+            height: height as u32,
+            stride: width * 4,
+            pixels: &pixels,
+            dirty_regions: &[],
+            frame_id: 0,
+            captured_at: std::time::Instant::now(),
+        };
+        assert_eq!(sample_step(100), 1);
+
+        let regions = perimeter_regions(6, 4, 0.25);
+        for ratio in [1, 7, 33, 50, 100] {
+            let mut buffer = vec![Color::default(); regions.len()];
+            let mut previous = buffer.clone();
+            render_frame(
+                (6, 4),
+                Some(&regions),
+                &frame,
+                &mut buffer,
+                &mut previous,
+                0,
+                &CropRegion::default(),
+                1.0,
+                1.0,
+                1.0,
+                None,
+                ratio,
+            );
+            assert!(
+                buffer.iter().all(|c| (c.r, c.g, c.b) == (10, 200, 30)),
+                "ratio {}",
+                ratio
+            );
+        }
+    }
+}
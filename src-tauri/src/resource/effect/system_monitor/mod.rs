@@ -0,0 +1,508 @@
+mod source;
+
+use crate::interface::controller::Color;
+use crate::interface::effect::{
+    DependencyBehavior, Effect, EffectLayout, EffectMetadata, EffectParam, EffectParamDependency,
+    EffectParamKind, SelectOptions, StaticSelectOption,
+};
+use crate::resource::color::parse_color;
+use serde_json::Value;
+use source::{Metric, MetricSource, SystemSource};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How quickly the displayed level follows a new reading, in seconds.
+const LEVEL_TIME_CONSTANT: f32 = 0.3;
+const MIN_INTERVAL_SECS: f64 = 0.25;
+const MAX_INTERVAL_SECS: f64 = 5.0;
+
+const METRIC_OPTIONS: [StaticSelectOption; 4] = [
+    StaticSelectOption {
+        label: "CPU",
+        value: 0.0,
+    },
+    StaticSelectOption {
+        label: "Memory",
+        value: 1.0,
+    },
+    StaticSelectOption {
+        label: "GPU",
+        value: 2.0,
+    },
+    StaticSelectOption {
+        label: "Network",
+        value: 3.0,
+    },
+];
+
+const DISPLAY_OPTIONS: [StaticSelectOption; 2] = [
+    StaticSelectOption {
+        label: "Bar",
+        value: 0.0,
+    },
+    StaticSelectOption {
+        label: "Color",
+        value: 1.0,
+    },
+];
+
+const SYSTEM_MONITOR_PARAMS: [EffectParam; 6] = [
+    EffectParam {
+        key: "metric",
+        label: "Metric",
+        kind: EffectParamKind::Select {
+            default: 0.0,
+            options: SelectOptions::Static(&METRIC_OPTIONS),
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "display",
+        label: "Display",
+        kind: EffectParamKind::Select {
+            default: 0.0,
+            options: SelectOptions::Static(&DISPLAY_OPTIONS),
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "lowColor",
+        label: "Low usage color",
+        kind: EffectParamKind::Color { default: "#00ff00" },
+        dependency: None,
+    },
+    EffectParam {
+        key: "highColor",
+        label: "High usage color",
+        kind: EffectParamKind::Color { default: "#ff0000" },
+        dependency: None,
+    },
+    EffectParam {
+        key: "maxMbps",
+        label: "Full scale (Mbit/s)",
+        kind: EffectParamKind::Slider {
+            min: 1.0,
+            max: 1000.0,
+            step: 1.0,
+            default: 100.0,
+        },
+        dependency: Some(EffectParamDependency::Dependency {
+            key: "metric",
+            equals: Some(3.0),
+            not_equals: None,
+            behavior: DependencyBehavior::Hide,
+        }),
+    },
+    EffectParam {
+        key: "interval",
+        label: "Update interval (s)",
+        kind: EffectParamKind::Slider {
+            min: MIN_INTERVAL_SECS,
+            max: MAX_INTERVAL_SECS,
+            step: 0.25,
+            default: 1.0,
+        },
+        dependency: None,
+    },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Display {
+    /// A level meter along the strip; on a matrix, a scrolling history graph.
+    Bar,
+    /// Every LED in the color for the current level.
+    Color,
+}
+
+/// Settings and readings shared between the effect and its sampling thread.
+struct Shared {
+    metric: Metric,
+    interval: Duration,
+    /// Bumped whenever `metric` changes so the worker samples immediately.
+    revision: u64,
+    /// Latest reading in the units of [`MetricSource::sample`].
+    reading: Option<f64>,
+    /// Number of readings taken, so the effect can tell a new sample from a repeated one.
+    samples: u64,
+}
+
+pub struct SystemMonitorEffect {
+    shared: Arc<Mutex<Shared>>,
+    running: Arc<AtomicBool>,
+    display: Display,
+    low: Color,
+    high: Color,
+    max_mbps: f64,
+    width: usize,
+    height: usize,
+    /// Displayed usage fraction, eased towards the latest reading.
+    level: f32,
+    last_time: Option<Duration>,
+    /// Usage fraction of recent samples for the graph, oldest first.
+    history: VecDeque<f32>,
+    seen_samples: u64,
+}
+
+impl SystemMonitorEffect {
+    fn with_source(source: Box<dyn MetricSource>) -> Self {
+        let shared = Arc::new(Mutex::new(Shared {
+            metric: Metric::Cpu,
+            interval: Duration::from_secs(1),
+            revision: 0,
+            reading: None,
+            samples: 0,
+        }));
+        let running = Arc::new(AtomicBool::new(true));
+
+        // Detached like the ambient image fetcher: sampling never blocks, and the loop
+        // notices `running` within one sleep.
+        {
+            let shared = shared.clone();
+            let running = running.clone();
+            if let Err(err) = thread::Builder::new()
+                .name("system-monitor".to_string())
+                .spawn(move || sample_loop(source, shared, running))
+            {
+                log::error!(err:display = err; "[system_monitor] Failed to spawn sampling thread");
+            }
+        }
+
+        Self {
+            shared,
+            running,
+            display: Display::Bar,
+            low: Color { r: 0, g: 255, b: 0 },
+            high: Color { r: 255, g: 0, b: 0 },
+            max_mbps: 100.0,
+            width: 0,
+            height: 1,
+            level: 0.0,
+            last_time: None,
+            history: VecDeque::new(),
+            seen_samples: 0,
+        }
+    }
+}
+
+impl Drop for SystemMonitorEffect {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+impl Effect for SystemMonitorEffect {
+    fn id(&self) -> String {
+        "system_monitor".to_string()
+    }
+
+    fn name(&self) -> String {
+        "System Monitor".to_string()
+    }
+
+    fn tick(&mut self, time: Duration, buffer: &mut [Color]) {
+        let (metric, reading, samples) = {
+            let s = self.shared.lock().unwrap();
+            (s.metric, s.reading, s.samples)
+        };
+        let target = reading.map(|reading| usage_fraction(metric, reading, self.max_mbps));
+
+        if samples != self.seen_samples {
+            self.seen_samples = samples;
+            if let Some(target) = target {
+                self.history.push_back(target);
+                while self.history.len() > self.width.max(1) {
+                    self.history.pop_front();
+                }
+            }
+        }
+
+        let dt = self
+            .last_time
+            .map(|last| time.saturating_sub(last))
+            .unwrap_or_default();
+        self.last_time = Some(time);
+        if let Some(target) = target {
+            let alpha = 1.0 - (-dt.as_secs_f32() / LEVEL_TIME_CONSTANT).exp();
+            self.level += (target - self.level) * alpha;
+        }
+
+        match self.display {
+            Display::Color => buffer.fill(usage_color(self.level, self.low, self.high)),
+            Display::Bar if self.height > 1 && self.width * self.height == buffer.len() => {
+                render_graph(&self.history, self.width, self.low, self.high, buffer)
+            }
+            Display::Bar => render_bar(self.level, self.low, self.high, buffer),
+        }
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        while self.history.len() > width.max(1) {
+            self.history.pop_front();
+        }
+    }
+
+    fn update_params(&mut self, params: Value) {
+        if let Some(display) = params.get("display").and_then(|v| v.as_f64()) {
+            self.display = if display.round() as i64 == 1 {
+                Display::Color
+            } else {
+                Display::Bar
+            };
+        }
+        if let Some(color) = params.get("lowColor").and_then(|v| v.as_str()) {
+            if let Some(color) = parse_color(color) {
+                self.low = color;
+            }
+        }
+        if let Some(color) = params.get("highColor").and_then(|v| v.as_str()) {
+            if let Some(color) = parse_color(color) {
+                self.high = color;
+            }
+        }
+        if let Some(max_mbps) = params.get("maxMbps").and_then(|v| v.as_f64()) {
+            self.max_mbps = max_mbps.max(1.0);
+        }
+
+        let metric_changed = {
+            let mut shared = self.shared.lock().unwrap();
+            if let Some(secs) = params.get("interval").and_then(|v| v.as_f64()) {
+                shared.interval =
+                    Duration::from_secs_f64(secs.clamp(MIN_INTERVAL_SECS, MAX_INTERVAL_SECS));
+            }
+            match params.get("metric").and_then(|v| v.as_f64()).map(Metric::from_param) {
+                Some(metric) if metric != shared.metric => {
+                    shared.metric = metric;
+                    shared.reading = None;
+                    shared.revision += 1;
+                    true
+                }
+                _ => false,
+            }
+        };
+        // The old metric's graph means nothing for the new one.
+        if metric_changed {
+            self.history.clear();
+            self.level = 0.0;
+        }
+    }
+}
+
+fn sample_loop(
+    mut source: Box<dyn MetricSource>,
+    shared: Arc<Mutex<Shared>>,
+    running: Arc<AtomicBool>,
+) {
+    let mut revision = None;
+    let mut next_sample = Instant::now();
+    let mut misses = 0u32;
+
+    while running.load(Ordering::Relaxed) {
+        let (metric, interval, current) = {
+            let s = shared.lock().unwrap();
+            (s.metric, s.interval, s.revision)
+        };
+
+        let now = Instant::now();
+        if revision != Some(current) || now >= next_sample {
+            if revision != Some(current) {
+                revision = Some(current);
+                misses = 0;
+            }
+            let reading = source.sample(metric);
+            // Rate metrics miss their first sample, so only a second miss means "unsupported".
+            match reading {
+                Some(_) => misses = 0,
+                None => {
+                    misses += 1;
+                    if misses == 2 {
+                        log::info!(metric:? = metric; "[system_monitor] Metric is not available on this system");
+                    }
+                }
+            }
+            {
+                let mut s = shared.lock().unwrap();
+                if s.revision == current {
+                    s.reading = reading;
+                    s.samples += 1;
+                }
+            }
+            next_sample = Instant::now() + interval;
+        }
+
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Reading as a fraction of full scale: network throughput against `max_mbps`, everything
+/// else as reported.
+fn usage_fraction(metric: Metric, reading: f64, max_mbps: f64) -> f32 {
+    let fraction = match metric {
+        Metric::Network => reading * 8.0 / 1_000_000.0 / max_mbps,
+        _ => reading,
+    };
+    fraction.clamp(0.0, 1.0) as f32
+}
+
+/// Color for `fraction` between `low` (0.0) and `high` (1.0). A plain mix dims in the middle
+/// (green to red passes through dark olive), so the mix is rescaled to the brightness of the
+/// endpoints and green to red passes through yellow instead.
+fn usage_color(fraction: f32, low: Color, high: Color) -> Color {
+    let t = fraction.clamp(0.0, 1.0);
+    let mix = |a: u8, b: u8| a as f32 + (b as f32 - a as f32) * t;
+    let mixed = [mix(low.r, high.r), mix(low.g, high.g), mix(low.b, high.b)];
+    let peak = |c: Color| c.r.max(c.g).max(c.b) as f32;
+    let target_peak = peak(low) + (peak(high) - peak(low)) * t;
+    let mixed_peak = mixed[0].max(mixed[1]).max(mixed[2]);
+    let scale = if mixed_peak > 0.0 {
+        target_peak / mixed_peak
+    } else {
+        1.0
+    };
+    let channel = |v: f32| (v * scale).round().clamp(0.0, 255.0) as u8;
+    Color {
+        r: channel(mixed[0]),
+        g: channel(mixed[1]),
+        b: channel(mixed[2]),
+    }
+}
+
+/// Lights the first `level` of the strip, each LED in the color of its own position so the
+/// bar runs from the low color to the high color as it fills.
+fn render_bar(level: f32, low: Color, high: Color, buffer: &mut [Color]) {
+    let len = buffer.len();
+    let lit = (level.clamp(0.0, 1.0) * len as f32).round() as usize;
+    let span = len.saturating_sub(1).max(1) as f32;
+    for (i, led) in buffer.iter_mut().enumerate() {
+        *led = if i < lit {
+            usage_color(i as f32 / span, low, high)
+        } else {
+            Color::default()
+        };
+    }
+}
+
+/// One column per sample with the newest on the right, each filled from the bottom. Rows
+/// are colored by height, like the bar.
+fn render_graph(history: &VecDeque<f32>, width: usize, low: Color, high: Color, buffer: &mut [Color]) {
+    buffer.fill(Color::default());
+    if width == 0 {
+        return;
+    }
+    let height = buffer.len() / width;
+    let span = height.saturating_sub(1).max(1) as f32;
+    let offset = width.saturating_sub(history.len());
+    for (column, level) in history.iter().rev().take(width).rev().enumerate() {
+        let x = offset + column;
+        let filled = (level * height as f32).round() as usize;
+        for k in 0..filled.min(height) {
+            let y = height - 1 - k;
+            buffer[y * width + x] = usage_color(k as f32 / span, low, high);
+        }
+    }
+}
+
+fn factory() -> Box<dyn Effect> {
+    Box::new(SystemMonitorEffect::with_source(Box::new(SystemSource::default())))
+}
+
+inventory::submit!(EffectMetadata {
+    id: "system_monitor",
+    name: "System Monitor",
+    description: Some("Show CPU, memory, GPU or network usage as a bar or a color"),
+    group: Some("Dynamic"),
+    icon: Some("Activity"),
+    layout: EffectLayout::Any,
+    params: &SYSTEM_MONITOR_PARAMS,
+    factory,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GREEN: Color = Color { r: 0, g: 255, b: 0 };
+    const RED: Color = Color { r: 255, g: 0, b: 0 };
+
+    fn rgb(color: Color) -> (u8, u8, u8) {
+        (color.r, color.g, color.b)
+    }
+
+    fn lit(color: &Color) -> bool {
+        rgb(*color) != (0, 0, 0)
+    }
+
+    #[test]
+    fn usage_color_passes_through_yellow_from_green_to_red() {
+        assert_eq!(rgb(usage_color(0.0, GREEN, RED)), (0, 255, 0));
+        assert_eq!(rgb(usage_color(1.0, GREEN, RED)), (255, 0, 0));
+        assert_eq!(rgb(usage_color(0.5, GREEN, RED)), (255, 255, 0));
+    }
+
+    #[test]
+    fn network_readings_are_scaled_to_the_full_scale_throughput() {
+        // 6.25 MB/s is 50 Mbit/s.
+        assert_eq!(usage_fraction(Metric::Network, 6_250_000.0, 100.0), 0.5);
+        assert_eq!(usage_fraction(Metric::Network, 1e9, 100.0), 1.0);
+        assert_eq!(usage_fraction(Metric::Cpu, 0.25, 100.0), 0.25);
+    }
+
+    #[test]
+    fn bar_lights_the_used_share_of_the_strip() {
+        let mut buffer = vec![Color::default(); 10];
+        render_bar(0.5, GREEN, RED, &mut buffer);
+        assert_eq!(rgb(buffer[0]), (0, 255, 0));
+        assert!(buffer[..5].iter().all(lit));
+        assert!(!buffer[5..].iter().any(lit));
+    }
+
+    #[test]
+    fn graph_fills_columns_from_the_bottom_with_the_newest_on_the_right() {
+        let (width, height) = (3, 4);
+        let history: VecDeque<f32> = [1.0, 0.5].into_iter().collect();
+        let mut buffer = vec![Color::default(); width * height];
+        render_graph(&history, width, GREEN, RED, &mut buffer);
+
+        let column =
+            |x: usize| -> Vec<bool> { (0..height).map(|y| lit(&buffer[y * width + x])).collect() };
+        assert_eq!(column(0), [false; 4], "no sample yet");
+        assert_eq!(column(1), [true; 4]);
+        assert_eq!(column(2), [false, false, true, true]);
+        assert_eq!(rgb(buffer[(height - 1) * width + 2]), (0, 255, 0));
+        assert_eq!(rgb(buffer[1]), (255, 0, 0));
+    }
+
+    /// Reports half usage for memory only and records what it was asked for.
+    struct MemorySource(Arc<Mutex<Vec<Metric>>>);
+
+    impl MetricSource for MemorySource {
+        fn sample(&mut self, metric: Metric) -> Option<f64> {
+            self.0.lock().unwrap().push(metric);
+            (metric == Metric::Memory).then_some(0.5)
+        }
+    }
+
+    #[test]
+    fn readings_from_an_injected_source_drive_the_display() {
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let mut effect = SystemMonitorEffect::with_source(Box::new(MemorySource(asked.clone())));
+        effect.update_params(serde_json::json!({ "metric": 1.0, "display": 1.0 }));
+
+        // The sampling thread picks up the new metric within one of its 50 ms sleeps.
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while effect.shared.lock().unwrap().reading.is_none() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(asked.lock().unwrap().contains(&Metric::Memory));
+
+        // A long step lets the eased level settle on the reading.
+        let mut buffer = vec![Color::default(); 4];
+        effect.tick(Duration::ZERO, &mut buffer);
+        effect.tick(Duration::from_secs(600), &mut buffer);
+        assert!(buffer.iter().all(|c| rgb(*c) == (255, 255, 0)));
+    }
+}
@@ -0,0 +1,438 @@
+//! Usage readings read straight from the OS.
+//!
+//! CPU load and network throughput are rates, so they need two samples and the first one
+//! reports `None`. GPU load comes from `gpu_busy_percent` on Linux (amdgpu only), the
+//! 3D engine utilization performance counters on Windows and the IOAccelerator
+//! statistics on macOS; network throughput isn't read on macOS.
+
+use std::time::Instant;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Cpu,
+    Memory,
+    Gpu,
+    Network,
+}
+
+impl Metric {
+    pub fn from_param(value: f64) -> Self {
+        match value.round() as i64 {
+            1 => Metric::Memory,
+            2 => Metric::Gpu,
+            3 => Metric::Network,
+            _ => Metric::Cpu,
+        }
+    }
+}
+
+/// Where the effect's readings come from. Polled from a background thread.
+pub trait MetricSource: Send {
+    /// Current value of `metric`: utilization in 0.0–1.0, or bytes per second for
+    /// [`Metric::Network`]. `None` while unknown or when the platform doesn't expose it.
+    fn sample(&mut self, metric: Metric) -> Option<f64>;
+}
+
+/// Cumulative CPU time counters, in platform ticks.
+#[derive(Clone, Copy, Debug)]
+struct CpuTimes {
+    busy: u64,
+    total: u64,
+}
+
+/// Counters from the previous sample of each rate metric.
+#[derive(Default)]
+pub struct SystemSource {
+    cpu: Option<CpuTimes>,
+    network: Option<(u64, Instant)>,
+    gpu: platform::GpuCounter,
+}
+
+impl MetricSource for SystemSource {
+    fn sample(&mut self, metric: Metric) -> Option<f64> {
+        match metric {
+            Metric::Cpu => {
+                let now = platform::cpu_times()?;
+                let previous = self.cpu.replace(now)?;
+                let total = now.total.checked_sub(previous.total).filter(|&t| t > 0)?;
+                Some(now.busy.saturating_sub(previous.busy) as f64 / total as f64)
+            }
+            Metric::Memory => platform::memory_usage(),
+            Metric::Gpu => self.gpu.sample(),
+            Metric::Network => {
+                let bytes = platform::network_bytes()?;
+                let now = Instant::now();
+                let (previous, at) = self.network.replace((bytes, now))?;
+                let secs = now.duration_since(at).as_secs_f64();
+                (secs > 0.0).then(|| bytes.saturating_sub(previous) as f64 / secs)
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::CpuTimes;
+    use std::fs;
+
+    pub fn cpu_times() -> Option<CpuTimes> {
+        // cpu  user nice system idle iowait irq softirq steal guest guest_nice
+        let stat = fs::read_to_string("/proc/stat").ok()?;
+        let fields: Vec<u64> = stat
+            .lines()
+            .next()?
+            .strip_prefix("cpu ")?
+            .split_whitespace()
+            .take(8)
+            .filter_map(|v| v.parse().ok())
+            .collect();
+        if fields.len() < 5 {
+            return None;
+        }
+        let total: u64 = fields.iter().sum();
+        let idle = fields[3] + fields[4];
+        Some(CpuTimes {
+            busy: total - idle,
+            total,
+        })
+    }
+
+    pub fn memory_usage() -> Option<f64> {
+        let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+        let field = |name: &str| -> Option<f64> {
+            meminfo
+                .lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))?
+                .split_whitespace()
+                .next()?
+                .parse()
+                .ok()
+        };
+        let total = field("MemTotal").filter(|&t| t > 0.0)?;
+        Some(1.0 - field("MemAvailable")? / total)
+    }
+
+    /// Reads the driver's own busy percentage, so no state is kept between samples.
+    #[derive(Default)]
+    pub struct GpuCounter;
+
+    impl GpuCounter {
+        pub fn sample(&mut self) -> Option<f64> {
+            gpu_usage()
+        }
+    }
+
+    fn gpu_usage() -> Option<f64> {
+        fs::read_dir("/sys/class/drm")
+            .ok()?
+            .flatten()
+            .filter_map(|entry| {
+                fs::read_to_string(entry.path().join("device/gpu_busy_percent")).ok()
+            })
+            .filter_map(|percent| percent.trim().parse::<f64>().ok())
+            .reduce(f64::max)
+            .map(|percent| percent / 100.0)
+    }
+
+    /// Bytes received plus sent on every interface but loopback.
+    pub fn network_bytes() -> Option<u64> {
+        let dev = fs::read_to_string("/proc/net/dev").ok()?;
+        let total = dev
+            .lines()
+            .skip(2)
+            .filter_map(|line| line.split_once(':'))
+            .filter(|(iface, _)| iface.trim() != "lo")
+            .filter_map(|(_, counters)| {
+                let counters: Vec<u64> = counters
+                    .split_whitespace()
+                    .filter_map(|v| v.parse().ok())
+                    .collect();
+                Some(counters.first()? + counters.get(8)?)
+            })
+            .sum();
+        Some(total)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::CpuTimes;
+    use windows::Win32::Foundation::FILETIME;
+    use windows::Win32::NetworkManagement::IpHelper::{FreeMibTable, GetIfTable2, MIB_IF_TABLE2};
+    use windows::Win32::System::Performance::{
+        PdhAddEnglishCounterW, PdhCloseQuery, PdhCollectQueryData, PdhGetFormattedCounterArrayW,
+        PdhOpenQueryW, PDH_FMT_COUNTERVALUE_ITEM_W, PDH_FMT_DOUBLE, PDH_HCOUNTER, PDH_HQUERY,
+        PDH_MORE_DATA,
+    };
+    use windows::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+    use windows::Win32::System::Threading::GetSystemTimes;
+    use windows::core::{w, PCWSTR};
+
+    const ERROR_SUCCESS: u32 = 0;
+    /// `PDH_CSTATUS_VALID_DATA` and `PDH_CSTATUS_NEW_DATA`.
+    const MAX_VALID_CSTATUS: u32 = 1;
+
+    fn ticks(time: FILETIME) -> u64 {
+        ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64
+    }
+
+    pub fn cpu_times() -> Option<CpuTimes> {
+        let (mut idle, mut kernel, mut user) =
+            (FILETIME::default(), FILETIME::default(), FILETIME::default());
+        // SAFETY: the three pointers come from live locals that outlive the call.
+        unsafe {
+            GetSystemTimes(
+                Some(&mut idle as *mut _),
+                Some(&mut kernel as *mut _),
+                Some(&mut user as *mut _),
+            )
+        }
+        .ok()?;
+        // Kernel time includes idle time.
+        let total = ticks(kernel) + ticks(user);
+        Some(CpuTimes {
+            busy: total.saturating_sub(ticks(idle)),
+            total,
+        })
+    }
+
+    pub fn memory_usage() -> Option<f64> {
+        let mut status = MEMORYSTATUSEX {
+            dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as u32,
+            ..Default::default()
+        };
+        // SAFETY: `status` is a live local with `dwLength` set as the API requires.
+        unsafe { GlobalMemoryStatusEx(&mut status) }.ok()?;
+        Some(status.dwMemoryLoad as f64 / 100.0)
+    }
+
+    /// A PDH query over every 3D engine instance. Utilization is a rate, so the query is
+    /// opened and primed on the first sample, which reports `None`.
+    #[derive(Default)]
+    pub struct GpuCounter {
+        handles: Option<(PDH_HQUERY, PDH_HCOUNTER)>,
+    }
+
+    // SAFETY: PDH handles aren't tied to the thread that opened them, and the counter is
+    // only ever used through `&mut self`.
+    unsafe impl Send for GpuCounter {}
+
+    impl GpuCounter {
+        pub fn sample(&mut self) -> Option<f64> {
+            let Some((query, counter)) = self.handles else {
+                self.handles = open_gpu_query();
+                return None;
+            };
+            // SAFETY: `query` and `counter` stay open until `drop`, and the buffer passed
+            // to the second array call is at least the `size` bytes PDH asked for.
+            unsafe {
+                if PdhCollectQueryData(query) != ERROR_SUCCESS {
+                    return None;
+                }
+                let (mut size, mut count) = (0u32, 0u32);
+                let status = PdhGetFormattedCounterArrayW(
+                    counter,
+                    PDH_FMT_DOUBLE,
+                    &mut size,
+                    &mut count,
+                    None,
+                );
+                if status != PDH_MORE_DATA {
+                    return None;
+                }
+                let item_size = std::mem::size_of::<PDH_FMT_COUNTERVALUE_ITEM_W>();
+                let len = (size as usize).div_ceil(item_size);
+                let mut items = vec![PDH_FMT_COUNTERVALUE_ITEM_W::default(); len];
+                let status = PdhGetFormattedCounterArrayW(
+                    counter,
+                    PDH_FMT_DOUBLE,
+                    &mut size,
+                    &mut count,
+                    Some(items.as_mut_ptr()),
+                );
+                if status != ERROR_SUCCESS {
+                    return None;
+                }
+                // Each process gets its own instance per engine, so the busy share of the
+                // engines is the sum over instances.
+                let percent: f64 = items
+                    .iter()
+                    .take(count as usize)
+                    .filter(|item| item.FmtValue.CStatus <= MAX_VALID_CSTATUS)
+                    .map(|item| item.FmtValue.Anonymous.doubleValue)
+                    .sum();
+                Some((percent / 100.0).clamp(0.0, 1.0))
+            }
+        }
+    }
+
+    impl Drop for GpuCounter {
+        fn drop(&mut self) {
+            if let Some((query, _)) = self.handles.take() {
+                // SAFETY: the query was opened by `open_gpu_query` and is closed once.
+                unsafe { PdhCloseQuery(query) };
+            }
+        }
+    }
+
+    fn open_gpu_query() -> Option<(PDH_HQUERY, PDH_HCOUNTER)> {
+        let (mut query, mut counter) = (PDH_HQUERY::default(), PDH_HCOUNTER::default());
+        // SAFETY: the out pointers come from live locals; on failure the query is closed
+        // before returning so no handle leaks.
+        unsafe {
+            if PdhOpenQueryW(PCWSTR::null(), 0, &mut query) != ERROR_SUCCESS {
+                return None;
+            }
+            let path = w!("\\GPU Engine(*engtype_3D)\\Utilization Percentage");
+            if PdhAddEnglishCounterW(query, path, 0, &mut counter) != ERROR_SUCCESS
+                || PdhCollectQueryData(query) != ERROR_SUCCESS
+            {
+                PdhCloseQuery(query);
+                return None;
+            }
+        }
+        Some((query, counter))
+    }
+
+    /// Bytes received plus sent on physical adapters. Filter drivers show up as extra
+    /// interfaces of the same adapter, so only hardware interfaces are counted.
+    pub fn network_bytes() -> Option<u64> {
+        let mut table: *mut MIB_IF_TABLE2 = std::ptr::null_mut();
+        // SAFETY: on success `GetIfTable2` hands over a table holding `NumEntries` rows,
+        // which are only read before the table is returned to `FreeMibTable`.
+        unsafe {
+            if GetIfTable2(&mut table).is_err() || table.is_null() {
+                return None;
+            }
+            let rows =
+                std::slice::from_raw_parts((*table).Table.as_ptr(), (*table).NumEntries as usize);
+            let total = rows
+                .iter()
+                .filter(|row| row.InterfaceAndOperStatusFlags._bitfield & 1 != 0)
+                .map(|row| row.InOctets + row.OutOctets)
+                .sum();
+            FreeMibTable(table as *const _);
+            Some(total)
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::CpuTimes;
+    use core_foundation::base::{kCFAllocatorDefault, CFType, TCFType};
+    use core_foundation::dictionary::{CFDictionary, CFMutableDictionaryRef};
+    use core_foundation::number::CFNumber;
+    use core_foundation::string::CFString;
+    use io_kit_sys::{
+        kIOMasterPortDefault, IOIteratorNext, IOObjectRelease, IORegistryEntryCreateCFProperties,
+        IOServiceGetMatchingServices, IOServiceMatching,
+    };
+
+    const HOST_VM_INFO: i32 = 2;
+    const HOST_CPU_LOAD_INFO: i32 = 3;
+    /// `vm_statistics` is 15 `natural_t`s.
+    const HOST_VM_INFO_COUNT: usize = 15;
+    /// user, system, idle, nice.
+    const CPU_STATE_MAX: usize = 4;
+    const KERN_SUCCESS: i32 = 0;
+
+    // SAFETY: both are Mach host calls from libSystem with these C signatures.
+    extern "C" {
+        fn mach_host_self() -> u32;
+        fn host_statistics(host: u32, flavor: i32, info: *mut i32, count: *mut u32) -> i32;
+    }
+
+    fn host_stats<const N: usize>(flavor: i32) -> Option<[u64; N]> {
+        let mut info = [0i32; N];
+        let mut count = N as u32;
+        // SAFETY: `info` holds the `count` integers the flavor writes, and the host port
+        // from `mach_host_self` is valid for the life of the process.
+        let result =
+            unsafe { host_statistics(mach_host_self(), flavor, info.as_mut_ptr(), &mut count) };
+        (result == 0).then(|| info.map(|v| v as u32 as u64))
+    }
+
+    pub fn cpu_times() -> Option<CpuTimes> {
+        let [user, system, idle, nice] = host_stats::<CPU_STATE_MAX>(HOST_CPU_LOAD_INFO)?;
+        Some(CpuTimes {
+            busy: user + system + nice,
+            total: user + system + idle + nice,
+        })
+    }
+
+    pub fn memory_usage() -> Option<f64> {
+        let stats = host_stats::<HOST_VM_INFO_COUNT>(HOST_VM_INFO)?;
+        let (free, active, inactive, wired, speculative) =
+            (stats[0], stats[1], stats[2], stats[3], stats[14]);
+        let total = free + active + inactive + wired + speculative;
+        (total > 0).then(|| (active + wired) as f64 / total as f64)
+    }
+
+    /// Reads the utilization each GPU driver publishes, so no state is kept between samples.
+    #[derive(Default)]
+    pub struct GpuCounter;
+
+    impl GpuCounter {
+        pub fn sample(&mut self) -> Option<f64> {
+            gpu_usage()
+        }
+    }
+
+    /// The highest `Device Utilization %` among the IOAccelerator services.
+    fn gpu_usage() -> Option<f64> {
+        let mut iterator = 0;
+        // SAFETY: `IOServiceGetMatchingServices` consumes the matching dictionary, every
+        // object taken from the iterator is released after use, and the properties
+        // dictionary is owned (create rule) while the nested one is borrowed (get rule).
+        unsafe {
+            let matching = IOServiceMatching(c"IOAccelerator".as_ptr());
+            if IOServiceGetMatchingServices(kIOMasterPortDefault, matching as _, &mut iterator)
+                != KERN_SUCCESS
+            {
+                return None;
+            }
+            let mut usage: Option<f64> = None;
+            loop {
+                let service = IOIteratorNext(iterator);
+                if service == 0 {
+                    break;
+                }
+                let mut properties: CFMutableDictionaryRef = std::ptr::null_mut();
+                let result = IORegistryEntryCreateCFProperties(
+                    service,
+                    &mut properties,
+                    kCFAllocatorDefault,
+                    0,
+                );
+                IOObjectRelease(service);
+                if result != KERN_SUCCESS || properties.is_null() {
+                    continue;
+                }
+                let properties: CFDictionary<CFString, CFType> =
+                    CFDictionary::wrap_under_create_rule(properties as _);
+                let percent = properties
+                    .find(CFString::from_static_string("PerformanceStatistics"))
+                    .and_then(|stats| stats.downcast::<CFDictionary>())
+                    .and_then(|stats| {
+                        let stats: CFDictionary<CFString, CFType> =
+                            CFDictionary::wrap_under_get_rule(stats.as_concrete_TypeRef());
+                        stats
+                            .find(CFString::from_static_string("Device Utilization %"))
+                            .and_then(|value| value.downcast::<CFNumber>())
+                            .and_then(|value| value.to_i64())
+                    });
+                if let Some(percent) = percent {
+                    let fraction = (percent as f64 / 100.0).clamp(0.0, 1.0);
+                    usage = Some(usage.map_or(fraction, |u| u.max(fraction)));
+                }
+            }
+            IOObjectRelease(iterator);
+            usage
+        }
+    }
+
+    pub fn network_bytes() -> Option<u64> {
+        None
+    }
+}
@@ -0,0 +1,188 @@
+use crate::interface::controller::Color;
+use crate::interface::effect::{
+    DependencyBehavior, Effect, EffectLayout, EffectMetadata, EffectParam, EffectParamDependency,
+    EffectParamKind,
+};
+use crate::resource::color::parse_color;
+use inventory;
+use serde_json::Value;
+use std::time::Duration;
+
+const DEFAULT_COLOR: &str = "#ffaa00";
+const DEFAULT_SECOND_COLOR: &str = "#0055ff";
+
+pub struct TheaterChaseEffect {
+    color: Color,
+    second_color: Color,
+    two_color: bool,
+    spacing: usize,
+    /// Steps per second.
+    speed: f32,
+}
+
+const THEATER_CHASE_PARAMS: [EffectParam; 5] = [
+    EffectParam {
+        key: "color",
+        label: "Color",
+        kind: EffectParamKind::Color {
+            default: DEFAULT_COLOR,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "twoColor",
+        label: "Alternate colors",
+        kind: EffectParamKind::Toggle { default: false },
+        dependency: None,
+    },
+    EffectParam {
+        key: "secondColor",
+        label: "Second color",
+        kind: EffectParamKind::Color {
+            default: DEFAULT_SECOND_COLOR,
+        },
+        dependency: Some(EffectParamDependency::Dependency {
+            key: "twoColor",
+            equals: Some(1.0),
+            not_equals: None,
+            behavior: DependencyBehavior::Hide,
+        }),
+    },
+    EffectParam {
+        key: "spacing",
+        label: "Spacing",
+        kind: EffectParamKind::Slider {
+            min: 2.0,
+            max: 10.0,
+            step: 1.0,
+            default: 3.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "speed",
+        label: "Speed",
+        kind: EffectParamKind::Slider {
+            min: 1.0,
+            max: 30.0,
+            step: 1.0,
+            default: 8.0,
+        },
+        dependency: None,
+    },
+];
+
+impl Effect for TheaterChaseEffect {
+    fn id(&self) -> String {
+        "theater_chase".to_string()
+    }
+
+    fn name(&self) -> String {
+        "Theater Chase".to_string()
+    }
+
+    fn tick(&mut self, time: Duration, buffer: &mut [Color]) {
+        // The phase comes straight from `time`, so pausing and resuming picks up where the
+        // marquee would have been. Matrices chase in row-major order, i.e. buffer order.
+        let step = (time.as_secs_f64() * self.speed as f64) as u64;
+        let phase = (step % self.spacing as u64) as usize;
+
+        for (i, led) in buffer.iter_mut().enumerate() {
+            *led = if i % self.spacing != phase {
+                Color::default()
+            } else if self.two_color && (i / self.spacing) % 2 == 1 {
+                self.second_color
+            } else {
+                self.color
+            };
+        }
+    }
+
+    fn update_params(&mut self, params: Value) {
+        if let Some(color) = params
+            .get("color")
+            .and_then(|v| v.as_str())
+            .and_then(parse_color)
+        {
+            self.color = color;
+        }
+
+        if let Some(color) = params
+            .get("secondColor")
+            .and_then(|v| v.as_str())
+            .and_then(parse_color)
+        {
+            self.second_color = color;
+        }
+
+        if let Some(two_color) = params.get("twoColor").and_then(|v| v.as_bool()) {
+            self.two_color = two_color;
+        }
+
+        if let Some(spacing) = params.get("spacing").and_then(|v| v.as_f64()) {
+            self.spacing = (spacing as usize).max(2);
+        }
+
+        if let Some(speed) = params.get("speed").and_then(|v| v.as_f64()) {
+            self.speed = (speed as f32).max(0.0);
+        }
+    }
+}
+
+fn factory() -> Box<dyn Effect> {
+    Box::new(TheaterChaseEffect {
+        color: parse_color(DEFAULT_COLOR).unwrap_or_default(),
+        second_color: parse_color(DEFAULT_SECOND_COLOR).unwrap_or_default(),
+        two_color: false,
+        spacing: 3,
+        speed: 8.0,
+    })
+}
+
+inventory::submit!(EffectMetadata {
+    id: "theater_chase",
+    name: "Theater Chase",
+    description: Some("Marquee of evenly spaced lights stepping along"),
+    group: Some("Dynamic"),
+    icon: Some("Lightbulb"),
+    layout: EffectLayout::Any,
+    params: &THEATER_CHASE_PARAMS,
+    factory,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn lit(buffer: &[Color]) -> Vec<usize> {
+        buffer
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.r > 0 || c.g > 0 || c.b > 0)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn spacing_three_lights_a_third_and_shifts_by_one() {
+        let mut effect = factory();
+        effect.update_params(json!({ "spacing": 3.0, "speed": 10.0 }));
+
+        let mut buffer = vec![Color::default(); 30];
+        let mut previous: Option<Vec<usize>> = None;
+        for step in 0..7u64 {
+            // Sample mid-step so float rounding never lands on a boundary.
+            effect.tick(Duration::from_millis(step * 100 + 50), &mut buffer);
+            let current = lit(&buffer);
+            assert_eq!(current.len(), buffer.len() / 3);
+
+            if let Some(previous) = previous {
+                let mut shifted: Vec<usize> = previous.iter().map(|i| (i + 1) % 30).collect();
+                shifted.sort_unstable();
+                assert_eq!(current, shifted);
+            }
+            previous = Some(current);
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use crate::interface::controller::Color;
-use crate::interface::effect::{Effect, EffectMetadata};
+use crate::interface::effect::{Effect, EffectLayout, EffectMetadata};
 use inventory;
 use std::time::Duration;
 
@@ -14,9 +14,13 @@ impl Effect for TurnOffEffect {
         "Turn Off".to_string()
     }
 
-    fn tick(&mut self, _elapsed: Duration, buffer: &mut [Color]) {
+    fn tick(&mut self, _time: Duration, buffer: &mut [Color]) {
         buffer.fill(Color::default());
     }
+
+    fn preferred_fps(&self) -> Option<u32> {
+        Some(1)
+    }
 }
 
 fn factory() -> Box<dyn Effect> {
@@ -29,6 +33,7 @@ inventory::submit!(EffectMetadata {
     description: Some("Turn off all LEDs"),
     group: Some("Basic"),
     icon: Some("Power"),
+    layout: EffectLayout::Any,
     params: &[],
     factory,
 });
@@ -0,0 +1,365 @@
+//! Twinkle Effect
+//!
+//! Random LEDs fade in and out over a dim background, like stars. Every LED is either idle
+//! or partway through a twinkle at its own rate; idle LEDs start one at random so that,
+//! on average, `density` percent of them are lit at any time. Starts are drawn per elapsed
+//! second rather than per tick, so the sky looks the same at any frame rate.
+
+use crate::interface::controller::Color;
+use crate::interface::effect::{
+    DependencyBehavior, Effect, EffectLayout, EffectMetadata, EffectParam, EffectParamDependency,
+    EffectParamKind,
+};
+use crate::resource::color::{hsv_to_rgb, parse_color};
+use inventory;
+use serde_json::Value;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DEFAULT_BACKGROUND: &str = "#02020a";
+const DEFAULT_TWINKLE: &str = "#ffffff";
+
+/// Longest gap between ticks that is simulated, so a stalled runner doesn't restart every
+/// star at once.
+const MAX_TICK: f32 = 0.25;
+
+/// Twinkles run at `fadeSpeed` times a random factor from this range.
+const RATE_SPREAD: (f32, f32) = (0.5, 1.5);
+
+/// Saturation of random-hue stars; fully saturated ones look like RGB test lights.
+const RANDOM_HUE_SATURATION: f32 = 0.6;
+
+/// Tints at either end of the color temperature slider, roughly candlelight and a clear sky.
+const WARM_TINT: [f32; 3] = [1.0, 0.58, 0.16];
+const COOL_TINT: [f32; 3] = [0.79, 0.89, 1.0];
+
+const TWINKLE_PARAMS: [EffectParam; 6] = [
+    // Percent of LEDs lit at once, on average.
+    EffectParam {
+        key: "density",
+        label: "Density",
+        kind: EffectParamKind::Slider {
+            min: 0.0,
+            max: 100.0,
+            step: 1.0,
+            default: 20.0,
+        },
+        dependency: None,
+    },
+    // Twinkles per second of a single star, before its random spread.
+    EffectParam {
+        key: "fadeSpeed",
+        label: "Fade speed",
+        kind: EffectParamKind::Slider {
+            min: 0.1,
+            max: 5.0,
+            step: 0.1,
+            default: 0.8,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "backgroundColor",
+        label: "Background",
+        kind: EffectParamKind::Color {
+            default: DEFAULT_BACKGROUND,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "randomHue",
+        label: "Random hue",
+        kind: EffectParamKind::Toggle { default: false },
+        dependency: None,
+    },
+    EffectParam {
+        key: "twinkleColor",
+        label: "Twinkle color",
+        kind: EffectParamKind::Color {
+            default: DEFAULT_TWINKLE,
+        },
+        dependency: Some(EffectParamDependency::Dependency {
+            key: "randomHue",
+            equals: Some(0.0),
+            not_equals: None,
+            behavior: DependencyBehavior::Hide,
+        }),
+    },
+    // -100 is warm, 100 is cool; applied to the twinkles only.
+    EffectParam {
+        key: "colorTemperature",
+        label: "Color temperature",
+        kind: EffectParamKind::Slider {
+            min: -100.0,
+            max: 100.0,
+            step: 1.0,
+            default: 0.0,
+        },
+        dependency: None,
+    },
+];
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Star {
+    /// Progress through the current twinkle, 0.0..1.0; `None` while idle.
+    phase: Option<f32>,
+    /// Twinkles per second.
+    rate: f32,
+    color: Color,
+}
+
+impl Star {
+    /// Brightness over a twinkle: in and out again along half a sine.
+    fn level(&self) -> f32 {
+        self.phase
+            .map_or(0.0, |phase| (phase * std::f32::consts::PI).sin())
+    }
+}
+
+pub struct TwinkleEffect {
+    /// One per LED, sized in `resize`.
+    stars: Vec<Star>,
+    /// 0.0..=1.0
+    density: f32,
+    fade_speed: f32,
+    background: Color,
+    twinkle_color: Color,
+    random_hue: bool,
+    /// -1.0 (warm) ..= 1.0 (cool).
+    temperature: f32,
+    last_time: Option<Duration>,
+    rng: u64,
+}
+
+impl TwinkleEffect {
+    pub fn new() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+
+        Self {
+            stars: Vec::new(),
+            density: 0.2,
+            fade_speed: 0.8,
+            background: parse_color(DEFAULT_BACKGROUND).unwrap_or_default(),
+            twinkle_color: parse_color(DEFAULT_TWINKLE).unwrap_or_default(),
+            random_hue: false,
+            temperature: 0.0,
+            last_time: None,
+            rng: seed | 1,
+        }
+    }
+
+    /// Uniform value in `[0, 1)` (xorshift64).
+    fn next_random(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// How long an idle star waits on average before the next twinkle, in units of
+    /// `1 / fadeSpeed`, so that lit / (lit + idle) comes out at `density`. Infinite at
+    /// density 0.
+    fn mean_idle_twinkles(&self) -> f32 {
+        // Mean of 1 / spread over the spread's range: the average twinkle length.
+        let (low, high) = RATE_SPREAD;
+        let mean_twinkle = (high / low).ln() / (high - low);
+        mean_twinkle * (1.0 - self.density) / self.density
+    }
+
+    /// A new twinkle at `phase` with a fresh rate and color.
+    fn start(&mut self, index: usize, phase: f32) {
+        let spread = RATE_SPREAD.0 + (RATE_SPREAD.1 - RATE_SPREAD.0) * self.next_random();
+        let color = if self.random_hue {
+            let (r, g, b) = hsv_to_rgb(self.next_random() * 360.0, RANDOM_HUE_SATURATION, 1.0);
+            Color { r, g, b }
+        } else {
+            self.twinkle_color
+        };
+        self.stars[index] = Star {
+            phase: Some(phase),
+            rate: self.fade_speed * spread,
+            color: tint(color, self.temperature),
+        };
+    }
+
+    /// Light stars as if the effect had been running all along, instead of a dark first
+    /// second while every star waits to start.
+    fn scatter(&mut self) {
+        for index in 0..self.stars.len() {
+            if self.next_random() < self.density {
+                let phase = self.next_random();
+                self.start(index, phase);
+            } else {
+                self.stars[index].phase = None;
+            }
+        }
+    }
+
+    fn advance(&mut self, dt: f32) {
+        let idle = self.mean_idle_twinkles();
+        for index in 0..self.stars.len() {
+            let star = self.stars[index];
+            match star.phase {
+                Some(phase) => {
+                    let phase = phase + dt * star.rate;
+                    self.stars[index].phase = (phase < 1.0).then_some(phase);
+                }
+                // Starting is a Poisson process, so its chance only depends on `dt`.
+                None if idle.is_finite() => {
+                    let twinkles = dt * self.fade_speed;
+                    let chance = if idle > 0.0 {
+                        1.0 - (-twinkles / idle).exp()
+                    } else {
+                        1.0
+                    };
+                    if self.next_random() < chance {
+                        self.start(index, 0.0);
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+impl Default for TwinkleEffect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `color` shifted towards warm (negative `temperature`) or cool (positive) white.
+fn tint(color: Color, temperature: f32) -> Color {
+    let target = if temperature < 0.0 { WARM_TINT } else { COOL_TINT };
+    let amount = temperature.abs().min(1.0);
+    let scale = |c: u8, t: f32| (c as f32 * (1.0 + (t - 1.0) * amount)).round() as u8;
+    Color {
+        r: scale(color.r, target[0]),
+        g: scale(color.g, target[1]),
+        b: scale(color.b, target[2]),
+    }
+}
+
+impl Effect for TwinkleEffect {
+    fn id(&self) -> String {
+        "twinkle".to_string()
+    }
+
+    fn name(&self) -> String {
+        "Twinkle".to_string()
+    }
+
+    fn tick(&mut self, time: Duration, buffer: &mut [Color]) {
+        match self.last_time.and_then(|last| time.checked_sub(last)) {
+            Some(elapsed) => self.advance(elapsed.as_secs_f32().min(MAX_TICK)),
+            None => self.scatter(),
+        }
+        self.last_time = Some(time);
+
+        let background = self.background;
+        let leds = buffer.len().min(self.stars.len());
+        for (led, star) in buffer[..leds].iter_mut().zip(&self.stars) {
+            let level = star.level();
+            let mix = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * level).round() as u8;
+            *led = Color {
+                r: mix(background.r, star.color.r),
+                g: mix(background.g, star.color.g),
+                b: mix(background.b, star.color.b),
+            };
+        }
+        buffer[leds..].fill(background);
+    }
+
+    fn resize(&mut self, width: usize, height: usize) {
+        let len = width.saturating_mul(height.max(1));
+        if len != self.stars.len() {
+            self.stars = vec![Star::default(); len];
+            // Scattered again on the next tick.
+            self.last_time = None;
+        }
+    }
+
+    fn update_params(&mut self, params: Value) {
+        if let Some(density) = params.get("density").and_then(|v| v.as_f64()) {
+            self.density = (density as f32).clamp(0.0, 100.0) / 100.0;
+        }
+        if let Some(fade_speed) = params.get("fadeSpeed").and_then(|v| v.as_f64()) {
+            self.fade_speed = (fade_speed as f32).clamp(0.1, 5.0);
+        }
+        if let Some(color) = params
+            .get("backgroundColor")
+            .and_then(|v| v.as_str())
+            .and_then(parse_color)
+        {
+            self.background = color;
+        }
+        if let Some(color) = params
+            .get("twinkleColor")
+            .and_then(|v| v.as_str())
+            .and_then(parse_color)
+        {
+            self.twinkle_color = color;
+        }
+        if let Some(random_hue) = params.get("randomHue").and_then(|v| v.as_bool()) {
+            self.random_hue = random_hue;
+        }
+        if let Some(temperature) = params.get("colorTemperature").and_then(|v| v.as_f64()) {
+            self.temperature = (temperature as f32).clamp(-100.0, 100.0) / 100.0;
+        }
+    }
+}
+
+fn factory() -> Box<dyn Effect> {
+    Box::new(TwinkleEffect::new())
+}
+
+inventory::submit!(EffectMetadata {
+    id: "twinkle",
+    name: "Twinkle",
+    description: Some("Stars fading in and out over a dim background"),
+    group: Some("Dynamic"),
+    icon: Some("Star"),
+    layout: EffectLayout::Any,
+    params: &TWINKLE_PARAMS,
+    factory,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Share of LEDs that differ from the background, averaged over `seconds` at `fps`.
+    fn lit_share(density: u32, fps: u64, seconds: u64) -> f32 {
+        let mut effect = TwinkleEffect::new();
+        effect.update_params(json!({ "density": density, "backgroundColor": "#000000" }));
+        effect.resize(500, 1);
+        let mut buffer = vec![Color::default(); 500];
+        let mut lit = 0;
+        let frames = fps * seconds;
+        for frame in 0..frames {
+            effect.tick(Duration::from_millis(frame * 1000 / fps), &mut buffer);
+            lit += effect.stars.iter().filter(|star| star.phase.is_some()).count();
+        }
+        lit as f32 / (frames * 500) as f32
+    }
+
+    #[test]
+    fn density_holds_at_any_frame_rate() {
+        for fps in [10, 120] {
+            let share = lit_share(30, fps, 20);
+            assert!((share - 0.3).abs() < 0.05, "{} fps: {}", fps, share);
+        }
+        assert_eq!(lit_share(0, 30, 2), 0.0);
+
+        // Idle LEDs show the background, here and past the end of the layout.
+        let mut effect = TwinkleEffect::new();
+        effect.update_params(json!({ "density": 0, "backgroundColor": "#102030" }));
+        effect.resize(4, 1);
+        let mut buffer = vec![Color::default(); 6];
+        effect.tick(Duration::ZERO, &mut buffer);
+        assert!(buffer.iter().all(|c| (c.r, c.g, c.b) == (0x10, 0x20, 0x30)));
+    }
+}
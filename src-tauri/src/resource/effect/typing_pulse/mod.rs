@@ -0,0 +1,217 @@
+//! Typing Pulse Effect
+//!
+//! Lights up in one color on every key press or mouse click and fades out while input is
+//! idle, so the glow follows how busy the keyboard is. Activity comes from
+//! [`crate::resource::input`].
+
+use crate::interface::controller::Color;
+use crate::interface::effect::{
+    Effect, EffectLayout, EffectMetadata, EffectParam, EffectParamKind, SelectOptions,
+    StaticSelectOption,
+};
+use crate::resource::color::parse_color;
+use crate::resource::input::{self, InputKind};
+use inventory;
+use serde_json::Value;
+use std::time::Duration;
+
+const DEFAULT_COLOR: &str = "#ff6a00";
+
+/// Brightness added per event; four quick presses reach full brightness.
+const PULSE_PER_EVENT: f32 = 0.25;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Source {
+    Keyboard,
+    Mouse,
+    Both,
+}
+
+impl Source {
+    fn from_param(value: f64) -> Self {
+        match value.round() as i64 {
+            1 => Source::Mouse,
+            2 => Source::Both,
+            _ => Source::Keyboard,
+        }
+    }
+
+    fn event_count(self) -> u64 {
+        let keys = || input::event_count(InputKind::Key);
+        let clicks = || input::event_count(InputKind::Mouse);
+        match self {
+            Source::Keyboard => keys(),
+            Source::Mouse => clicks(),
+            Source::Both => keys() + clicks(),
+        }
+    }
+}
+
+pub struct TypingPulseEffect {
+    color: Color,
+    /// Time constant of the fade, in seconds.
+    decay: f32,
+    source: Source,
+    /// Current brightness, 0.0–1.0.
+    level: f32,
+    /// `source`'s event count at the previous tick.
+    seen: u64,
+    last_time: Option<Duration>,
+}
+
+const SOURCE_OPTIONS: [StaticSelectOption; 3] = [
+    StaticSelectOption {
+        label: "Keyboard",
+        value: 0.0,
+    },
+    StaticSelectOption {
+        label: "Mouse",
+        value: 1.0,
+    },
+    StaticSelectOption {
+        label: "Keyboard and mouse",
+        value: 2.0,
+    },
+];
+
+const TYPING_PULSE_PARAMS: [EffectParam; 3] = [
+    EffectParam {
+        key: "color",
+        label: "Color",
+        kind: EffectParamKind::Color {
+            default: DEFAULT_COLOR,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "decay",
+        label: "Fade (ms)",
+        kind: EffectParamKind::Slider {
+            min: 100.0,
+            max: 5000.0,
+            step: 100.0,
+            default: 800.0,
+        },
+        dependency: None,
+    },
+    EffectParam {
+        key: "source",
+        label: "Source",
+        kind: EffectParamKind::Select {
+            default: 0.0,
+            options: SelectOptions::Static(&SOURCE_OPTIONS),
+        },
+        dependency: None,
+    },
+];
+
+impl Effect for TypingPulseEffect {
+    fn id(&self) -> String {
+        "typing_pulse".to_string()
+    }
+
+    fn name(&self) -> String {
+        "Typing Pulse".to_string()
+    }
+
+    fn tick(&mut self, time: Duration, buffer: &mut [Color]) {
+        let dt = self
+            .last_time
+            .map_or(0.0, |last| time.saturating_sub(last).as_secs_f32());
+        self.last_time = Some(time);
+
+        let count = self.source.event_count();
+        let events = count.saturating_sub(self.seen);
+        self.seen = count;
+
+        self.level *= (-dt / self.decay).exp();
+        self.level = (self.level + events as f32 * PULSE_PER_EVENT).min(1.0);
+
+        let scale = |c: u8| (c as f32 * self.level).round() as u8;
+        buffer.fill(Color {
+            r: scale(self.color.r),
+            g: scale(self.color.g),
+            b: scale(self.color.b),
+        });
+    }
+
+    fn update_params(&mut self, params: Value) {
+        if let Some(color) = params
+            .get("color")
+            .and_then(|v| v.as_str())
+            .and_then(parse_color)
+        {
+            self.color = color;
+        }
+
+        if let Some(decay) = params.get("decay").and_then(|v| v.as_f64()) {
+            self.decay = (decay as f32 / 1000.0).max(0.01);
+        }
+
+        if let Some(source) = params.get("source").and_then(|v| v.as_f64()) {
+            self.source = Source::from_param(source);
+            // Don't flash for events the previous source didn't include.
+            self.seen = self.source.event_count();
+        }
+    }
+}
+
+impl Drop for TypingPulseEffect {
+    fn drop(&mut self) {
+        input::stop_listening();
+    }
+}
+
+fn factory() -> Box<dyn Effect> {
+    input::start_listening();
+    Box::new(TypingPulseEffect {
+        color: parse_color(DEFAULT_COLOR).unwrap_or_default(),
+        decay: 0.8,
+        source: Source::Keyboard,
+        level: 0.0,
+        seen: Source::Keyboard.event_count(),
+        last_time: None,
+    })
+}
+
+inventory::submit!(EffectMetadata {
+    id: "typing_pulse",
+    name: "Typing Pulse",
+    description: Some("Glow on key presses and clicks, fading while idle"),
+    group: Some("Dynamic"),
+    icon: Some("Keyboard"),
+    layout: EffectLayout::Any,
+    params: &TYPING_PULSE_PARAMS,
+    factory,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn brightness(effect: &mut dyn Effect, elapsed_ms: u64) -> u8 {
+        let mut buffer = vec![Color::default(); 4];
+        effect.tick(Duration::from_millis(elapsed_ms), &mut buffer);
+        buffer[0].r
+    }
+
+    #[test]
+    fn key_presses_light_it_up_and_idle_fades_it_out() {
+        let mut effect = factory();
+        effect.update_params(json!({ "color": "#ffffff", "decay": 500, "source": 0 }));
+        assert_eq!(brightness(effect.as_mut(), 0), 0);
+
+        input::record(InputKind::Key, 1);
+        let one = brightness(effect.as_mut(), 16);
+        input::record(InputKind::Key, 2);
+        let three = brightness(effect.as_mut(), 32);
+        assert!(one > 0 && three > one, "{} then {}", one, three);
+
+        // Clicks aren't part of the keyboard source.
+        input::record(InputKind::Mouse, 5);
+        let idle = brightness(effect.as_mut(), 500);
+        assert!(idle < three);
+        assert!(brightness(effect.as_mut(), 3_000) < 5);
+    }
+}
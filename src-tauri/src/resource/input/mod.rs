@@ -0,0 +1,157 @@
+//! Keyboard and mouse activity, for effects that react to it.
+//!
+//! Only counts are kept: how many key presses and mouse clicks happened, never which keys
+//! or where. Effects poll [`event_count`] and react to the difference since their last tick.
+//!
+//! On Windows low-level hooks feed the counters while at least one [`start_listening`] is
+//! not yet matched by [`stop_listening`]. Elsewhere there is no system-wide source, so activity has to be reported through
+//! [`record`] (the `report_input_activity` command), e.g. by the frontend or a script.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InputKind {
+    Key,
+    Mouse,
+}
+
+static KEY_EVENTS: AtomicU64 = AtomicU64::new(0);
+static MOUSE_EVENTS: AtomicU64 = AtomicU64::new(0);
+
+/// Listeners that haven't stopped yet; the platform source runs while there are any.
+static LISTENERS: Mutex<usize> = Mutex::new(0);
+
+fn counter(kind: InputKind) -> &'static AtomicU64 {
+    match kind {
+        InputKind::Key => &KEY_EVENTS,
+        InputKind::Mouse => &MOUSE_EVENTS,
+    }
+}
+
+/// Count `count` events of `kind`.
+pub fn record(kind: InputKind, count: u64) {
+    counter(kind).fetch_add(count, Ordering::Relaxed);
+}
+
+/// Events of `kind` counted since the app started. Only ever grows.
+pub fn event_count(kind: InputKind) -> u64 {
+    counter(kind).load(Ordering::Relaxed)
+}
+
+/// Start the platform input source, if there is one, for one more listener. Each call
+/// must be paired with a [`stop_listening`].
+pub fn start_listening() {
+    let mut listeners = LISTENERS.lock().unwrap();
+    *listeners += 1;
+    if *listeners == 1 {
+        platform::start();
+    }
+}
+
+/// Release one [`start_listening`]; the last listener to stop removes the platform source.
+pub fn stop_listening() {
+    let mut listeners = LISTENERS.lock().unwrap();
+    match *listeners {
+        0 => {}
+        1 => {
+            *listeners = 0;
+            platform::stop();
+        }
+        _ => *listeners -= 1,
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{record, InputKind};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::mpsc;
+    use windows::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
+    use windows::Win32::System::Threading::GetCurrentThreadId;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CallNextHookEx, GetMessageW, PeekMessageW, PostThreadMessageW, SetWindowsHookExW,
+        UnhookWindowsHookEx, HC_ACTION, MSG, PM_NOREMOVE, WH_KEYBOARD_LL, WH_MOUSE_LL,
+        WM_KEYDOWN, WM_LBUTTONDOWN, WM_MBUTTONDOWN, WM_MOUSEWHEEL, WM_QUIT, WM_RBUTTONDOWN,
+        WM_SYSKEYDOWN,
+    };
+
+    /// Thread pumping messages for the hooks, 0 while there is none.
+    static HOOK_THREAD: AtomicU32 = AtomicU32::new(0);
+
+    unsafe extern "system" fn keyboard_hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code == HC_ACTION as i32 && matches!(wparam.0 as u32, WM_KEYDOWN | WM_SYSKEYDOWN) {
+            record(InputKind::Key, 1);
+        }
+        unsafe { CallNextHookEx(None, code, wparam, lparam) }
+    }
+
+    /// Clicks and wheel steps; plain movement would drown everything else out.
+    unsafe extern "system" fn mouse_hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if code == HC_ACTION as i32
+            && matches!(
+                wparam.0 as u32,
+                WM_LBUTTONDOWN | WM_RBUTTONDOWN | WM_MBUTTONDOWN | WM_MOUSEWHEEL
+            )
+        {
+            record(InputKind::Mouse, 1);
+        }
+        unsafe { CallNextHookEx(None, code, wparam, lparam) }
+    }
+
+    pub fn start() {
+        let (ready_tx, ready_rx) = mpsc::channel();
+        // Low-level hooks are called on the installing thread, which must pump messages.
+        let spawned = std::thread::Builder::new()
+            .name("input-hook".to_string())
+            .spawn(move || unsafe {
+                let mut msg = MSG::default();
+                // Create the thread's message queue before `stop` can post to it.
+                let _ = PeekMessageW(&mut msg, None, 0, 0, PM_NOREMOVE);
+                let _ = ready_tx.send(GetCurrentThreadId());
+
+                let keyboard = SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_hook), None, 0);
+                let mouse = SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_hook), None, 0);
+                if let (Err(err), Err(_)) = (&keyboard, &mouse) {
+                    log::warn!(err:display = err; "[input] Failed to install input hooks");
+                    return;
+                }
+                // 0 is WM_QUIT from `stop`, -1 an error.
+                while GetMessageW(&mut msg, None, 0, 0).0 > 0 {}
+                for hook in [keyboard, mouse].into_iter().flatten() {
+                    let _ = UnhookWindowsHookEx(hook);
+                }
+            });
+        match spawned {
+            Ok(_) => {
+                if let Ok(thread) = ready_rx.recv() {
+                    HOOK_THREAD.store(thread, Ordering::Relaxed);
+                }
+            }
+            Err(err) => log::warn!(err:display = err; "[input] Failed to start input hook thread"),
+        }
+    }
+
+    /// End the hook thread's message loop; it removes the hooks on its way out.
+    pub fn stop() {
+        let thread = HOOK_THREAD.swap(0, Ordering::Relaxed);
+        if thread == 0 {
+            return;
+        }
+        // SAFETY: posting to a thread id has no memory effects; a thread that already gave
+        // up on installing its hooks just makes the call fail.
+        if let Err(err) = unsafe { PostThreadMessageW(thread, WM_QUIT, WPARAM(0), LPARAM(0)) } {
+            log::debug!(err:display = err; "[input] Input hook thread had already exited");
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    pub fn start() {
+        log::info!("[input] No system-wide input source on this platform; waiting for reported activity");
+    }
+
+    pub fn stop() {}
+}
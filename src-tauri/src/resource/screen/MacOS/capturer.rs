@@ -1,10 +1,12 @@
 use std::sync::{Arc, RwLock};
 use std::sync::atomic::Ordering;
+use std::time::Instant;
 use screencapturekit::prelude::*;
 
-use crate::resource::screen::{ScreenCaptureError, ScreenCapturer, ScreenFrame};
+use crate::resource::screen::{CropRect, ScreenCaptureError, ScreenCapturer, ScreenFrame};
 use super::frame::{FrameHandler, SharedFrameBuffer};
-use super::config::{CAPTURE_FPS, CAPTURE_MAX_PIXELS};
+use super::config::{get_capture_crop, CAPTURE_FPS, CAPTURE_MAX_PIXELS, SHOW_CURSOR};
+use super::permission::shareable_content;
 
 // ============================================================================
 // ScreenCaptureKit Capturer
@@ -13,6 +15,7 @@ use super::config::{CAPTURE_FPS, CAPTURE_MAX_PIXELS};
 /// Screen capturer using ScreenCaptureKit framework.
 pub(crate) struct Capturer {
     display_index: usize,
+    crop: Option<CropRect>,
     stream: Option<SCStream>,
     frame_buffer: Arc<RwLock<SharedFrameBuffer>>,
     /// Local copy of frame for returning references
@@ -22,6 +25,7 @@ pub(crate) struct Capturer {
     local_stride: usize,
     #[allow(dead_code)] // Useful for debugging or future extension
     last_frame_id: u64,
+    captured_at: Instant,
 }
 
 impl Capturer {
@@ -30,8 +34,17 @@ impl Capturer {
     }
 
     pub(crate) fn with_output(output_index: usize) -> Result<Self, ScreenCaptureError> {
+        Self::with_output_crop(output_index, get_capture_crop())
+    }
+
+    /// Capturer of `output_index` cropped to `crop` rather than the global setting.
+    pub(crate) fn with_output_crop(
+        output_index: usize,
+        crop: Option<CropRect>,
+    ) -> Result<Self, ScreenCaptureError> {
         let mut capturer = Self {
             display_index: output_index,
+            crop,
             stream: None,
             frame_buffer: Arc::new(RwLock::new(SharedFrameBuffer::new())),
             local_buffer: Vec::new(),
@@ -39,6 +52,7 @@ impl Capturer {
             local_height: 0,
             local_stride: 0,
             last_frame_id: 0,
+            captured_at: Instant::now(),
         };
 
         capturer.start_stream()?;
@@ -52,10 +66,7 @@ impl Capturer {
         }
 
         // Get display
-        let content = SCShareableContent::get().map_err(|e| ScreenCaptureError::OsError {
-            context: "SCShareableContent::get",
-            code: format!("{:?}", e).len() as u32,
-        })?;
+        let content = shareable_content()?;
 
         let displays = content.displays();
         if self.display_index >= displays.len() {
@@ -72,17 +83,7 @@ impl Capturer {
             .exclude_windows(&[])
             .build();
 
-        // Get FPS and create frame interval
-        let fps = CAPTURE_FPS.load(Ordering::Relaxed).max(1) as i32;
-        let frame_interval = CMTime::new(1, fps);
-
-        // Configure stream with BGRA format (matches our expected format)
-        let config = SCStreamConfiguration::new()
-            .with_width(display.width())
-            .with_height(display.height())
-            .with_pixel_format(PixelFormat::BGRA)
-            .with_shows_cursor(true)
-            .with_minimum_frame_interval(&frame_interval);
+        let config = stream_configuration(display.width(), display.height(), self.crop);
 
         // Create stream
         let mut stream = SCStream::new(&filter, &config);
@@ -135,6 +136,7 @@ impl Capturer {
                 self.local_height = frame_buffer.height;
                 self.local_stride = frame_buffer.stride;
                 self.last_frame_id = frame_buffer.frame_id;
+                self.captured_at = frame_buffer.current_since();
                 return Ok(());
             }
         }
@@ -147,7 +149,7 @@ impl Capturer {
         })?;
 
         if frame_buffer.buffer.is_empty() {
-            return Err(ScreenCaptureError::InvalidState("No frame available yet"));
+            return Err(ScreenCaptureError::NoFrame);
         }
 
         // Copy to local buffer
@@ -157,6 +159,7 @@ impl Capturer {
         self.local_height = frame_buffer.height;
         self.local_stride = frame_buffer.stride;
         self.last_frame_id = frame_buffer.frame_id;
+        self.captured_at = frame_buffer.current_since();
 
         Ok(())
     }
@@ -180,6 +183,8 @@ impl ScreenCapturer for Capturer {
             stride: self.local_stride,
             pixels: &self.local_buffer,
             dirty_regions: &[],
+            frame_id: self.last_frame_id,
+            captured_at: self.captured_at,
         })
     }
 
@@ -187,3 +192,43 @@ impl ScreenCapturer for Capturer {
         (self.local_width, self.local_height)
     }
 }
+
+/// Stream settings for a `width` x `height` display from the current capture settings.
+/// A crop becomes the stream's source rect, so only that part is delivered.
+fn stream_configuration(width: u32, height: u32, crop: Option<CropRect>) -> SCStreamConfiguration {
+    let fps = CAPTURE_FPS.load(Ordering::Relaxed).max(1) as i32;
+    let frame_interval = CMTime::new(1, fps);
+
+    let (x, y, width, height) = crop.map_or((0, 0, width, height), |crop| crop.to_pixels(width, height));
+    // BGRA matches the frame format the rest of the pipeline expects.
+    let config = SCStreamConfiguration::new()
+        .with_width(width)
+        .with_height(height)
+        .with_pixel_format(PixelFormat::BGRA)
+        .with_shows_cursor(SHOW_CURSOR.load(Ordering::Relaxed))
+        .with_minimum_frame_interval(&frame_interval);
+    if crop.is_some() {
+        config.with_source_rect(CGRect::new(x as f64, y as f64, width as f64, height as f64))
+    } else {
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::screen::{get_show_cursor, set_show_cursor};
+
+    #[test]
+    fn stream_configuration_follows_the_cursor_setting() {
+        assert!(!get_show_cursor(), "the cursor is left out by default");
+        assert!(!stream_configuration(64, 36, None).shows_cursor());
+
+        set_show_cursor(true);
+        assert!(get_show_cursor());
+        assert!(stream_configuration(64, 36, None).shows_cursor());
+
+        set_show_cursor(false);
+        assert!(!stream_configuration(64, 36, None).shows_cursor());
+    }
+}
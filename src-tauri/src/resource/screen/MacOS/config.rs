@@ -1,7 +1,11 @@
-use std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU8, Ordering};
+use std::sync::RwLock;
 use serde::{Deserialize, Serialize};
 
-use crate::resource::screen::{normalize_capture_max_pixels, DEFAULT_CAPTURE_MAX_PIXELS};
+use crate::resource::screen::{
+    normalize_capture_fps, normalize_capture_max_pixels, CaptureSettings, CropRect,
+    DEFAULT_CAPTURE_MAX_PIXELS, DEFAULT_TARGET_NITS, ToneMapping,
+};
 use super::manager::global_manager;
 
 // ============================================================================
@@ -18,9 +22,15 @@ pub(crate) const DEFAULT_CAPTURE_FPS: u8 = 30;
 /// Max pixel budget for capture resolution. 0 means "no limit".
 pub(crate) static CAPTURE_MAX_PIXELS: AtomicU32 = AtomicU32::new(DEFAULT_CAPTURE_MAX_PIXELS);
 pub(crate) static CAPTURE_FPS: AtomicU8 = AtomicU8::new(DEFAULT_CAPTURE_FPS);
+static SAMPLE_RATIO: AtomicU8 = AtomicU8::new(100);
+/// Include the mouse pointer in the stream. Off by default: on ambient lights it only
+/// shows up as a stray spot of color.
+pub(crate) static SHOW_CURSOR: AtomicBool = AtomicBool::new(false);
+/// Part of each display to stream; `None` streams all of it.
+static CAPTURE_CROP: RwLock<Option<CropRect>> = RwLock::new(None);
 
-/// Generation counter for capture state; bump when settings change.
-pub(crate) static CAPTURE_GEN: AtomicU64 = AtomicU64::new(0);
+/// Set by `pause_capture`; only written with the manager lock held.
+static CAPTURE_PAUSED: AtomicBool = AtomicBool::new(false);
 
 // ============================================================================
 // Public Types
@@ -62,11 +72,11 @@ pub fn set_capture_max_pixels(max_pixels: u32) {
     let normalized = normalize_capture_max_pixels(max_pixels);
     let previous = CAPTURE_MAX_PIXELS.swap(normalized, Ordering::Relaxed);
 
+    // Subscriptions rebuild their stream at the new size on their next capture.
     if previous != normalized {
         if let Ok(mut manager) = global_manager().lock() {
             manager.clear();
         }
-        CAPTURE_GEN.fetch_add(1, Ordering::Relaxed);
     }
 }
 
@@ -75,7 +85,7 @@ pub fn get_capture_max_pixels() -> u32 {
 }
 
 pub fn set_capture_fps(fps: u8) {
-    CAPTURE_FPS.store(fps.clamp(1, 60), Ordering::Relaxed);
+    CAPTURE_FPS.store(normalize_capture_fps(fps), Ordering::Relaxed);
 }
 
 pub fn get_capture_fps() -> u8 {
@@ -90,6 +100,43 @@ pub fn get_hardware_acceleration() -> bool {
     true // ScreenCaptureKit uses GPU acceleration
 }
 
+pub fn set_high_bit_depth(_enabled: bool) {
+    // The ScreenCaptureKit stream is configured for 8-bit BGRA
+}
+
+pub fn get_high_bit_depth() -> bool {
+    false
+}
+
+pub fn set_tone_mapping(_tone_mapping: ToneMapping) {
+    // ScreenCaptureKit delivers SDR frames; there are no HDR highlights to map
+}
+
+pub fn get_tone_mapping() -> ToneMapping {
+    ToneMapping::default()
+}
+
+pub fn set_capture_target_nits(_nits: u32) {
+    // No HDR capture, so nothing is mapped to a target brightness
+}
+
+pub fn get_capture_target_nits() -> u32 {
+    DEFAULT_TARGET_NITS
+}
+
+pub fn set_show_cursor(enabled: bool) {
+    // The stream configuration is fixed once started, so restart the streams.
+    if SHOW_CURSOR.swap(enabled, Ordering::Relaxed) != enabled {
+        if let Ok(mut manager) = global_manager().lock() {
+            manager.clear();
+        }
+    }
+}
+
+pub fn get_show_cursor() -> bool {
+    SHOW_CURSOR.load(Ordering::Relaxed)
+}
+
 pub fn set_capture_method(_method: CaptureMethod) {
     // Only one method available for ScreenCaptureKit
 }
@@ -98,10 +145,72 @@ pub fn get_capture_method() -> CaptureMethod {
     CaptureMethod::ScreenCaptureKit
 }
 
-#[allow(dead_code)]
-pub fn set_sample_ratio(_percent: u8) {}
+/// Apply the pixel budget and frame rate together, rebuilding capturers at most once.
+/// There is only one capture method here, so `method` is ignored.
+pub fn configure_capture(max_pixels: u32, fps: u8, _method: CaptureMethod) -> CaptureSettings {
+    let max_pixels = normalize_capture_max_pixels(max_pixels);
+    let fps = normalize_capture_fps(fps);
+    CAPTURE_FPS.store(fps, Ordering::Relaxed);
+
+    let rebuilt = CAPTURE_MAX_PIXELS.swap(max_pixels, Ordering::Relaxed) != max_pixels;
+    if rebuilt {
+        if let Ok(mut manager) = global_manager().lock() {
+            manager.clear();
+        }
+    }
+
+    CaptureSettings {
+        max_pixels,
+        fps,
+        method: get_capture_method(),
+        rebuilt,
+    }
+}
+
+/// Percentage of each zone's pixels that screen effects average (1–100).
+pub fn set_sample_ratio(percent: u8) {
+    SAMPLE_RATIO.store(percent.clamp(1, 100), Ordering::Relaxed);
+}
 
-#[allow(dead_code)]
 pub fn get_sample_ratio() -> u8 {
-    100
+    SAMPLE_RATIO.load(Ordering::Relaxed)
+}
+
+/// Capture only part of each display, or all of it with `None`. Spanning all displays
+/// ignores the crop.
+pub fn set_capture_crop(crop: Option<CropRect>) {
+    let crop = crop.and_then(CropRect::normalized);
+    let changed = match CAPTURE_CROP.write() {
+        Ok(mut guard) => std::mem::replace(&mut *guard, crop) != crop,
+        Err(_) => false,
+    };
+    // The source rect is part of the stream configuration, so restart the streams.
+    if changed {
+        if let Ok(mut manager) = global_manager().lock() {
+            manager.clear();
+        }
+    }
+}
+
+pub fn get_capture_crop() -> Option<CropRect> {
+    CAPTURE_CROP.read().map(|guard| *guard).unwrap_or_default()
+}
+
+/// Stop every capture stream while keeping subscriptions alive; they report no frame
+/// until `resume_capture`.
+pub fn pause_capture() {
+    if let Ok(mut manager) = global_manager().lock() {
+        CAPTURE_PAUSED.store(true, Ordering::Relaxed);
+        manager.clear();
+    }
+}
+
+/// Subscriptions restart their stream on their next capture.
+pub fn resume_capture() {
+    let _manager = global_manager().lock();
+    CAPTURE_PAUSED.store(false, Ordering::Relaxed);
+}
+
+pub fn is_capture_paused() -> bool {
+    CAPTURE_PAUSED.load(Ordering::Relaxed)
 }
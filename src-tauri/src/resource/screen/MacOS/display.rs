@@ -1,6 +1,7 @@
-use screencapturekit::prelude::*;
 use serde::Serialize;
+use core_graphics::display::CGDisplay;
 use crate::resource::screen::ScreenCaptureError;
+use super::permission::shareable_content;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct DisplayInfo {
@@ -8,7 +9,28 @@ pub struct DisplayInfo {
     pub name: String,
     pub width: u32,
     pub height: u32,
+    /// Top-left corner on the virtual desktop, in points like `width` and `height`.
+    pub x: i32,
+    pub y: i32,
     pub is_hdr: bool,
+    /// Current refresh rate in Hz, from the display mode. Unset for displays that report
+    /// 0, which Core Graphics does for most built-in panels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_rate_hz: Option<f32>,
+    /// Unset: Core Graphics only reports it through the deprecated pixel encoding string.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bits_per_color: Option<u32>,
+    /// Clockwise rotation in degrees.
+    pub rotation: u32,
+    /// Backing pixels per point of the current mode (2.0 on Retina).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale_factor: Option<f32>,
+    /// Unset: neither ScreenCaptureKit nor Core Graphics names the GPU driving a display.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adapter_name: Option<String>,
+    /// Unset: macOS only exposes the EDR headroom through AppKit, not a luminance in nits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_luminance_nits: Option<f32>,
 }
 
 // ============================================================================
@@ -16,21 +38,35 @@ pub struct DisplayInfo {
 // ============================================================================
 
 pub fn list_displays() -> Result<Vec<DisplayInfo>, ScreenCaptureError> {
-    let content = SCShareableContent::get().map_err(|e| ScreenCaptureError::OsError {
-        context: "SCShareableContent::get",
-        code: format!("{:?}", e).len() as u32,
-    })?;
+    let content = shareable_content()?;
 
     let displays = content.displays();
     let mut result = Vec::with_capacity(displays.len());
 
     for (index, display) in displays.iter().enumerate() {
+        let frame = display.frame();
+        let cg_display = CGDisplay::new(display.display_id());
+        let mode = cg_display.display_mode();
         result.push(DisplayInfo {
             index,
             name: format!("Display {}", display.display_id()),
             width: display.width(),
             height: display.height(),
+            x: frame.x.round() as i32,
+            y: frame.y.round() as i32,
             is_hdr: false, // Could be extended to detect HDR
+            refresh_rate_hz: mode
+                .as_ref()
+                .map(|mode| mode.refresh_rate() as f32)
+                .filter(|&hz| hz > 0.0),
+            bits_per_color: None,
+            rotation: (cg_display.rotation().round() as i64).rem_euclid(360) as u32,
+            scale_factor: mode
+                .as_ref()
+                .filter(|mode| mode.width() > 0)
+                .map(|mode| mode.pixel_width() as f32 / mode.width() as f32),
+            adapter_name: None,
+            max_luminance_nits: None,
         });
     }
 
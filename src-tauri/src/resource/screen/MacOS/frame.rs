@@ -1,9 +1,11 @@
 use std::sync::{Arc, RwLock};
+use std::time::Instant;
 use screencapturekit::cv::CVPixelBufferLockFlags;
 use screencapturekit::prelude::*;
+use screencapturekit::SCFrameStatus;
 
 use super::config::BYTES_PER_PIXEL;
-use crate::resource::screen::compute_scaled_dimensions_by_max_pixels;
+use crate::resource::screen::{compute_scaled_dimensions_by_max_pixels, next_frame_id};
 
 // ============================================================================
 // Frame Buffer for Stream Output
@@ -18,6 +20,11 @@ pub(crate) struct SharedFrameBuffer {
     pub(crate) stride: usize,
     /// Frame counter for detecting new frames
     pub(crate) frame_id: u64,
+    /// When the stream handler stored this frame
+    pub(crate) captured_at: Instant,
+    /// Whether the stream is still running. It sends idle frames instead of repeating an
+    /// unchanged screen, so while this holds the stored frame is current.
+    pub(crate) live: bool,
 }
 
 impl SharedFrameBuffer {
@@ -28,6 +35,18 @@ impl SharedFrameBuffer {
             height: 0,
             stride: 0,
             frame_id: 0,
+            captured_at: Instant::now(),
+            live: false,
+        }
+    }
+
+    /// When the stored frame was last known to match the screen: now while the stream
+    /// runs, else when it arrived.
+    pub(crate) fn current_since(&self) -> Instant {
+        if self.live {
+            Instant::now()
+        } else {
+            self.captured_at
         }
     }
 }
@@ -48,6 +67,19 @@ impl SCStreamOutputTrait for FrameHandler {
             return;
         }
 
+        // Frames without new content only tell whether the stream is still running.
+        let live = match sample.frame_status() {
+            Some(SCFrameStatus::Stopped | SCFrameStatus::Suspended) => Some(false),
+            Some(SCFrameStatus::Idle | SCFrameStatus::Blank) => Some(true),
+            _ => None,
+        };
+        if let Some(live) = live {
+            if let Ok(mut frame_buffer) = self.frame_buffer.write() {
+                frame_buffer.live = live;
+            }
+            return;
+        }
+
         // Get pixel buffer from sample
         let Some(pixel_buffer) = sample.image_buffer() else {
             return;
@@ -129,6 +161,8 @@ impl SCStreamOutputTrait for FrameHandler {
         frame_buffer.width = target_width;
         frame_buffer.height = target_height;
         frame_buffer.stride = (target_width as usize) * BYTES_PER_PIXEL;
-        frame_buffer.frame_id += 1;
+        frame_buffer.frame_id = next_frame_id();
+        frame_buffer.captured_at = Instant::now();
+        frame_buffer.live = true;
     }
 }
@@ -1,31 +1,34 @@
-use std::collections::HashMap;
-use std::sync::{Mutex, OnceLock};
 use std::sync::atomic::Ordering;
+use std::sync::{Mutex, OnceLock};
 
+use crate::resource::screen::registry::{CaptureLease, CaptureRegistry};
+use crate::resource::screen::spanning::{DisplayRect, SpanningCapturer, ALL_DISPLAYS};
 use crate::resource::screen::{ScreenCaptureError, ScreenCapturer, ScreenFrame};
 use super::capturer::Capturer;
-use super::config::{CAPTURE_GEN, CaptureMethod};
+use super::config::{is_capture_paused, CaptureMethod, CAPTURE_MAX_PIXELS};
+use super::display::list_displays;
 
 // ============================================================================
 // Unified Capturer Wrapper (API compatibility with Windows)
 // ============================================================================
 
 /// Unified screen capturer wrapper for ScreenCaptureKit backend.
-pub struct DesktopDuplicator {
-    capturer: Capturer,
+pub enum DesktopDuplicator {
+    Display(Capturer),
+    /// [`ALL_DISPLAYS`]: one stream per display, composited into one frame.
+    Spanning(Box<SpanningCapturer<Capturer>>),
 }
 
 impl DesktopDuplicator {
     pub fn new() -> Result<Self, ScreenCaptureError> {
-        Ok(Self {
-            capturer: Capturer::new()?,
-        })
+        Self::with_output(0)
     }
 
     pub fn with_output(output_index: usize) -> Result<Self, ScreenCaptureError> {
-        Ok(Self {
-            capturer: Capturer::with_output(output_index)?,
-        })
+        if output_index == ALL_DISPLAYS {
+            return Self::spanning();
+        }
+        Ok(Self::Display(Capturer::with_output(output_index)?))
     }
 
     pub fn with_method_output(
@@ -35,22 +38,56 @@ impl DesktopDuplicator {
         Self::with_output(output_index)
     }
 
+    fn spanning() -> Result<Self, ScreenCaptureError> {
+        let displays = list_displays()?
+            .into_iter()
+            .map(|display| {
+                let rect = DisplayRect {
+                    x: display.x,
+                    y: display.y,
+                    width: display.width,
+                    height: display.height,
+                };
+                Capturer::with_output_crop(display.index, None).map(|capturer| (rect, capturer))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let max_pixels = CAPTURE_MAX_PIXELS.load(Ordering::Relaxed);
+        Ok(Self::Spanning(Box::new(SpanningCapturer::new(displays, max_pixels)?)))
+    }
+
     pub fn set_output_index(&mut self, output_index: usize) -> Result<(), ScreenCaptureError> {
-        self.capturer.set_output_index(output_index)
+        match self {
+            Self::Display(capturer) if output_index != ALL_DISPLAYS => {
+                capturer.set_output_index(output_index)
+            }
+            _ => {
+                *self = Self::with_output(output_index)?;
+                Ok(())
+            }
+        }
     }
 
     pub fn output_index(&self) -> usize {
-        self.capturer.output_index()
+        match self {
+            Self::Display(capturer) => capturer.output_index(),
+            Self::Spanning(_) => ALL_DISPLAYS,
+        }
     }
 }
 
 impl ScreenCapturer for DesktopDuplicator {
     fn capture(&mut self) -> Result<ScreenFrame<'_>, ScreenCaptureError> {
-        self.capturer.capture()
+        match self {
+            Self::Display(capturer) => capturer.capture(),
+            Self::Spanning(capturer) => capturer.capture(),
+        }
     }
 
     fn size(&self) -> (u32, u32) {
-        self.capturer.size()
+        match self {
+            Self::Display(capturer) => capturer.size(),
+            Self::Spanning(capturer) => capturer.size(),
+        }
     }
 }
 
@@ -58,74 +95,13 @@ impl ScreenCapturer for DesktopDuplicator {
 // Screen Capture Manager
 // ============================================================================
 
-pub(crate) struct ScreenCaptureManager {
-    outputs: HashMap<usize, ManagedOutput>,
-}
+pub(crate) type ScreenCaptureManager = CaptureRegistry<usize, DesktopDuplicator>;
 
-struct ManagedOutput {
-    duplicator: DesktopDuplicator,
-    ref_count: usize,
-}
-
-impl ScreenCaptureManager {
-    fn new() -> Self {
-        Self {
-            outputs: HashMap::new(),
-        }
-    }
-
-    pub(crate) fn acquire(&mut self, output_index: usize) -> Result<(), ScreenCaptureError> {
-        if let Some(entry) = self.outputs.get_mut(&output_index) {
-            entry.ref_count += 1;
-            return Ok(());
-        }
-
-        let duplicator = DesktopDuplicator::with_output(output_index)?;
-        self.outputs.insert(
-            output_index,
-            ManagedOutput {
-                duplicator,
-                ref_count: 1,
-            },
-        );
-        Ok(())
-    }
-
-    pub(crate) fn release(&mut self, output_index: usize) {
-        if let Some(entry) = self.outputs.get_mut(&output_index) {
-            if entry.ref_count > 1 {
-                entry.ref_count -= 1;
-                return;
-            }
-        }
-        self.outputs.remove(&output_index);
-    }
-
-    pub(crate) fn capture_with<F>(&mut self, output_index: usize, f: F) -> Result<bool, ScreenCaptureError>
-    where
-        F: FnOnce(&ScreenFrame<'_>),
-    {
-        let Some(entry) = self.outputs.get_mut(&output_index) else {
-            return Ok(false);
-        };
-
-        match entry.duplicator.capture() {
-            Ok(frame) => {
-                f(&frame);
-                Ok(true)
-            }
-            Err(err) => {
-                if matches!(err, ScreenCaptureError::InvalidState(_)) {
-                    self.outputs.remove(&output_index);
-                }
-                Err(err)
-            }
-        }
-    }
-
-    pub(crate) fn clear(&mut self) {
-        self.outputs.clear();
-    }
+fn acquire(
+    manager: &mut ScreenCaptureManager,
+    output_index: usize,
+) -> Result<CaptureLease<usize>, ScreenCaptureError> {
+    manager.acquire(output_index, || DesktopDuplicator::with_output(output_index))
 }
 
 static SCREEN_CAPTURE_MANAGER: OnceLock<Mutex<ScreenCaptureManager>> = OnceLock::new();
@@ -142,18 +118,24 @@ pub(crate) fn global_manager() -> &'static Mutex<ScreenCaptureManager> {
 #[derive(Debug)]
 pub struct ScreenSubscription {
     display_index: usize,
-    generation: u64,
+    /// `None` while paused, or once the shared stream was rebuilt; re-acquired lazily.
+    lease: Option<CaptureLease<usize>>,
+    paused: bool,
 }
 
 impl ScreenSubscription {
     pub fn new(display_index: usize) -> Result<Self, ScreenCaptureError> {
         let manager = global_manager();
         let mut guard = manager.lock().unwrap();
-        let generation = CAPTURE_GEN.load(Ordering::Relaxed);
-        guard.acquire(display_index)?;
+        let lease = if is_capture_paused() {
+            None
+        } else {
+            Some(acquire(&mut guard, display_index)?)
+        };
         Ok(Self {
             display_index,
-            generation,
+            lease,
+            paused: false,
         })
     }
 
@@ -161,28 +143,57 @@ impl ScreenSubscription {
         self.display_index
     }
 
+    /// Give up this subscription's share of the stream (stopping it if this was the last
+    /// one) without dropping the subscription.
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.release();
+    }
+
+    /// The stream is re-acquired on the next capture.
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// `Ok(false)` while paused (this subscription or globally) or without a stream.
     pub fn capture_with<F>(&mut self, f: F) -> Result<bool, ScreenCaptureError>
     where
         F: FnOnce(&ScreenFrame<'_>),
     {
+        if self.paused {
+            return Ok(false);
+        }
+
         let manager = global_manager();
         let mut guard = manager.lock().unwrap();
-
-        let current_generation = CAPTURE_GEN.load(Ordering::Relaxed);
-        if current_generation != self.generation {
-            guard.acquire(self.display_index)?;
-            self.generation = current_generation;
+        if is_capture_paused() {
+            return Ok(false);
         }
 
-        guard.capture_with(self.display_index, f)
+        // Settings changes and lost streams rebuild the entry, leaving this lease stale.
+        let lease = match self.lease.filter(|lease| guard.holds(lease)) {
+            Some(lease) => lease,
+            None => *self.lease.insert(acquire(&mut guard, self.display_index)?),
+        };
+
+        guard.capture_with(&lease, f)
+    }
+
+    fn release(&mut self) {
+        if let Some(lease) = self.lease.take() {
+            if let Ok(mut guard) = global_manager().lock() {
+                guard.release(lease);
+            }
+        }
     }
 }
 
 impl Drop for ScreenSubscription {
     fn drop(&mut self) {
-        let manager = global_manager();
-        if let Ok(mut guard) = manager.lock() {
-            guard.release(self.display_index);
-        }
+        self.release();
     }
 }
@@ -8,16 +8,25 @@ pub mod display;
 pub(crate) mod frame;
 pub(crate) mod capturer;
 pub mod manager;
+pub mod permission;
 
 pub use config::{
     CaptureMethod,
     get_capture_fps, set_capture_fps,
     get_capture_max_pixels, set_capture_max_pixels,
     get_hardware_acceleration, set_hardware_acceleration,
+    get_high_bit_depth, set_high_bit_depth,
+    get_tone_mapping, set_tone_mapping,
+    get_capture_target_nits, set_capture_target_nits,
+    get_show_cursor, set_show_cursor,
     get_sample_ratio, set_sample_ratio,
-    get_capture_method, set_capture_method,
+    get_capture_crop, set_capture_crop,
+    get_capture_method, set_capture_method, configure_capture,
+    is_capture_paused, pause_capture, resume_capture,
 };
 
 pub use display::{DisplayInfo, list_displays};
 
 pub use manager::{DesktopDuplicator, ScreenSubscription};
+
+pub use permission::{has_screen_permission, open_screen_permission_settings};
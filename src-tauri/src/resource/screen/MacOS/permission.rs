@@ -0,0 +1,48 @@
+use std::process::Command;
+use screencapturekit::prelude::*;
+
+use crate::resource::screen::{ScreenCaptureError, SCREEN_RECORDING_PERMISSION};
+
+const SCREEN_RECORDING_SETTINGS_URL: &str =
+    "x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture";
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGPreflightScreenCaptureAccess() -> bool;
+    fn CGRequestScreenCaptureAccess() -> bool;
+}
+
+// ============================================================================
+// Public API - Screen Recording Permission
+// ============================================================================
+
+/// Whether the app may record the screen. Doesn't prompt.
+pub fn has_screen_permission() -> bool {
+    unsafe { CGPreflightScreenCaptureAccess() }
+}
+
+/// Ask for Screen Recording permission and open the System Settings pane where it is
+/// granted. macOS only shows its own prompt once per app, so the pane is opened regardless;
+/// a new grant takes effect after the app restarts.
+pub fn open_screen_permission_settings() -> Result<(), String> {
+    if unsafe { CGRequestScreenCaptureAccess() } {
+        return Ok(());
+    }
+    Command::new("open")
+        .arg(SCREEN_RECORDING_SETTINGS_URL)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open System Settings: {}", e))
+}
+
+/// `SCShareableContent::get`, except that a missing permission is reported as such instead
+/// of as the opaque error ScreenCaptureKit returns for it.
+pub(crate) fn shareable_content() -> Result<SCShareableContent, ScreenCaptureError> {
+    if !has_screen_permission() {
+        return Err(ScreenCaptureError::Unsupported(SCREEN_RECORDING_PERMISSION));
+    }
+    SCShareableContent::get().map_err(|e| ScreenCaptureError::OsError {
+        context: "SCShareableContent::get",
+        code: format!("{:?}", e).len() as u32,
+    })
+}
@@ -2,19 +2,23 @@
 //!
 //! This module implements a high-performance screen capture pipeline that:
 //! 1. Captures desktop frames using DXGI Output Duplication
-//! 2. Optionally requests HDR formats (R16G16B16A16_FLOAT, R10G10B10A2_UNORM)
+//! 2. Optionally requests HDR formats (R16G16B16A16_FLOAT, R10G10B10A2_UNORM), or 10-bit
+//!    SDR (R10G10B10A2_UNORM) on wide-gamut SDR displays to avoid banding
 //! 3. Uses GPU shaders for HDR to SDR tone mapping
 //! 4. Performs hardware-accelerated downsampling via GenerateMips
 //! 5. Only transfers the final small BGRA8 buffer to CPU
 
 mod shaders;
 
-use std::{mem, slice, time::Instant};
+use std::{
+    mem, slice,
+    time::{Duration, Instant},
+};
 
 use windows::{
     core::Interface,
     Win32::{
-        Foundation::HMODULE,
+        Foundation::{HMODULE, RECT},
         Graphics::{
             Direct3D::{
                 D3D11_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP, D3D11_SRV_DIMENSION_TEXTURE2D,
@@ -29,7 +33,7 @@ use windows::{
                 D3D11_COMPARISON_NEVER, D3D11_CPU_ACCESS_READ, D3D11_CPU_ACCESS_WRITE,
                 D3D11_CREATE_DEVICE_BGRA_SUPPORT, D3D11_FILTER_MIN_MAG_MIP_LINEAR,
                 D3D11_INPUT_ELEMENT_DESC, D3D11_INPUT_PER_VERTEX_DATA, D3D11_MAPPED_SUBRESOURCE,
-                D3D11_MAP_READ, D3D11_RENDER_TARGET_VIEW_DESC,
+                D3D11_MAP_READ, D3D11_MAP_WRITE_DISCARD, D3D11_RENDER_TARGET_VIEW_DESC,
                 D3D11_RESOURCE_MISC_GENERATE_MIPS, D3D11_RTV_DIMENSION_TEXTURE2D,
                 D3D11_SAMPLER_DESC, D3D11_SDK_VERSION, D3D11_SHADER_RESOURCE_VIEW_DESC,
                 D3D11_SHADER_RESOURCE_VIEW_DESC_0,
@@ -47,22 +51,48 @@ use windows::{
                 IDXGIOutput6, IDXGIOutputDuplication, IDXGIResource, IDXGISurface1,
                 DXGI_ERROR_ACCESS_DENIED, DXGI_ERROR_ACCESS_LOST, DXGI_ERROR_NOT_FOUND,
                 DXGI_ERROR_WAIT_TIMEOUT, DXGI_MAPPED_RECT, DXGI_MAP_READ, DXGI_OUTDUPL_DESC,
-                DXGI_OUTDUPL_FRAME_INFO, DXGI_OUTPUT_DESC, DXGI_OUTPUT_DESC1,
+                DXGI_OUTDUPL_FRAME_INFO, DXGI_OUTDUPL_MOVE_RECT, DXGI_OUTPUT_DESC, DXGI_OUTPUT_DESC1,
             },
         },
     },
 };
 
 use crate::resource::screen::{
-    compute_scaled_dimensions_by_max_pixels, DirtyRegion, ScreenCaptureError, ScreenCapturer,
-    ScreenFrame,
+    compute_scaled_dimensions_by_max_pixels, next_frame_id, CropRect, DirtyRegion,
+    normalize_target_nits, ScreenCaptureError, ScreenCapturer, ScreenFrame, ToneMapOperator,
+    ToneMapping,
 };
 use rayon::prelude::*;
 use super::{
-    CAPTURE_MAX_PIXELS, CAPTURE_FPS, HARDWARE_ACCELERATION, HDR_COLOR_SPACE,
-    BYTES_PER_PIXEL, DEFAULT_TIMEOUT_MS, DEFAULT_TARGET_NITS,
+    capture_crop_for, get_capture_target_nits, get_tone_mapping, rotation_degrees, CAPTURE_MAX_PIXELS, CAPTURE_FPS, HARDWARE_ACCELERATION, HDR_COLOR_SPACE,
+    HIGH_BIT_DEPTH, BYTES_PER_PIXEL, DEFAULT_TIMEOUT_MS,
 };
 
+/// Rebuilds tried after the duplication is lost before the error reaches the caller.
+const MAX_REBUILD_ATTEMPTS: u32 = 5;
+/// Wait after a failed rebuild, times the attempts so far (about half a second in all).
+const REBUILD_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Desktop surface format granted by the duplication API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SurfaceFormat {
+    Sdr8,
+    /// sRGB-encoded 10-bit SDR; decoded to BGRA8 at readback.
+    Sdr10,
+    /// R16G16B16A16_FLOAT or PQ R10G10B10A2, tone mapped to BGRA8 by the pixel shader.
+    Hdr,
+}
+
+impl SurfaceFormat {
+    /// Format of the GPU working textures (mips, convert target, staging).
+    fn working_format(self) -> DXGI_FORMAT {
+        match self {
+            Self::Sdr10 => DXGI_FORMAT_R10G10B10A2_UNORM,
+            Self::Sdr8 | Self::Hdr => DXGI_FORMAT_B8G8R8A8_UNORM,
+        }
+    }
+}
+
 /// GPU resources for HDR processing pipeline.
 struct GpuPipeline {
     // Shader resources
@@ -82,6 +112,16 @@ struct GpuPipeline {
     mip_levels: u32,
 }
 
+/// The last frame of a capturer that was rebuilt, served until the new one has its own.
+struct HeldFrame {
+    buffer: Vec<u8>,
+    width: u32,
+    height: u32,
+    stride: usize,
+    frame_id: u64,
+    captured_at: Instant,
+}
+
 #[allow(dead_code)]
 pub struct DxgiCapturer {
     device: ID3D11Device,
@@ -95,17 +135,31 @@ pub struct DxgiCapturer {
     height: u32,
     stride: usize,
     has_frame: bool,
+    frame_id: u64,
+    captured_at: Instant,
     last_capture_time: Option<Instant>,
     dirty_regions: Vec<DirtyRegion>,
 
     // HDR state
     is_hdr: bool,
     target_nits: u32,
+    /// Pixel format of `staging_texture` and the crop texture; anything but BGRA8 is
+    /// decoded on readback.
+    working_format: DXGI_FORMAT,
+    /// Applied when HDR surfaces are decoded on the CPU. Changing the settings rebuilds
+    /// the capturer, like the GPU pipeline's shader.
+    tone_mapping: ToneMapping,
 
     // Staging texture for CPU readback
     staging_texture: ID3D11Texture2D,
     actual_width: u32,
     actual_height: u32,
+    crop: Option<CropRect>,
+    /// `crop` in desktop texture pixels as (x, y, width, height).
+    source_region: Option<(u32, u32, u32, u32)>,
+    /// Size of the desktop textures the duplication delivers, learned from the first frame.
+    /// Any later frame of another size means the display mode changed under us.
+    source_size: Option<(u32, u32)>,
 
     // GPU pipeline (only for HDR or hardware acceleration)
     gpu_pipeline: Option<GpuPipeline>,
@@ -115,6 +169,12 @@ pub struct DxgiCapturer {
     crop_size: Option<(u32, u32)>,
     crop_buffer: Vec<u8>,
     crop_dirty_regions: Vec<DirtyRegion>,
+
+    // Recovery from a lost duplication (HDR toggle, resolution change, secure desktop)
+    held_frame: Option<HeldFrame>,
+    rebuild_attempts: u32,
+    /// Set while recovering: when the next rebuild may be tried.
+    retry_at: Option<Instant>,
 }
 
 impl DxgiCapturer {
@@ -123,14 +183,28 @@ impl DxgiCapturer {
     }
 
     pub fn with_output(output_index: usize) -> Result<Self, ScreenCaptureError> {
+        Self::with_output_crop(output_index, capture_crop_for(output_index))
+    }
+
+    /// Capturer of `output_index` cropped to `crop` rather than the global setting.
+    pub fn with_output_crop(
+        output_index: usize,
+        crop: Option<CropRect>,
+    ) -> Result<Self, ScreenCaptureError> {
         let hardware = HARDWARE_ACCELERATION.load(std::sync::atomic::Ordering::Relaxed);
-        let (device, device_context, duplication, dupl_desc, desc, _desc1, is_hdr) =
-            create_duplication(output_index, hardware)?;
+        let high_bit_depth = HIGH_BIT_DEPTH.load(std::sync::atomic::Ordering::Relaxed);
+        let (device, device_context, duplication, dupl_desc, desc, _desc1, surface_format) =
+            create_duplication(output_index, hardware, high_bit_depth)?;
+        let is_hdr = surface_format == SurfaceFormat::Hdr;
+        let working_format = surface_format.working_format();
 
         let (width, height) = output_dimensions(&desc);
+        let source_region = crop.map(|crop| crop.to_pixels(width, height));
 
-        // Actual capture dimensions
-        let (actual_width, actual_height) = (width, height);
+        // Actual capture dimensions: everything downstream of the desktop texture only
+        // ever sees the cropped part.
+        let (actual_width, actual_height) =
+            source_region.map_or((width, height), |(_, _, w, h)| (w, h));
 
         // Calculate scaled dimensions
         let max_pixels = CAPTURE_MAX_PIXELS.load(std::sync::atomic::Ordering::Relaxed);
@@ -144,7 +218,7 @@ impl DxgiCapturer {
                 Height: scaled_height,
                 MipLevels: 1,
                 ArraySize: 1,
-                Format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                Format: working_format,
                 SampleDesc: DXGI_SAMPLE_DESC {
                     Count: 1,
                     Quality: 0,
@@ -161,16 +235,20 @@ impl DxgiCapturer {
             staging.unwrap()
         };
 
+        let tone_mapping = get_tone_mapping();
+        let target_nits = get_capture_target_nits();
+
         // Create GPU pipeline for hardware acceleration
         let gpu_pipeline = if hardware {
             Some(create_gpu_pipeline(
                 &device,
-                width,
-                height,
+                actual_width,
+                actual_height,
                 scaled_width,
                 scaled_height,
-                is_hdr,
-                DEFAULT_TARGET_NITS,
+                surface_format,
+                target_nits,
+                tone_mapping,
             )?)
         } else {
             None
@@ -188,18 +266,28 @@ impl DxgiCapturer {
             height: scaled_height,
             stride: scaled_width as usize * BYTES_PER_PIXEL,
             has_frame: false,
+            frame_id: 0,
+            captured_at: Instant::now(),
             last_capture_time: None,
             dirty_regions: Vec::new(),
             is_hdr,
-            target_nits: DEFAULT_TARGET_NITS,
+            target_nits,
+            working_format,
+            tone_mapping,
             staging_texture,
             actual_width,
             actual_height,
+            crop,
+            source_region,
+            source_size: None,
             gpu_pipeline,
             crop_texture: None,
             crop_size: None,
             crop_buffer: Vec::new(),
             crop_dirty_regions: Vec::new(),
+            held_frame: None,
+            rebuild_attempts: 0,
+            retry_at: None,
         })
     }
 
@@ -209,11 +297,44 @@ impl DxgiCapturer {
         }
 
         // Recreate with new output
-        let new = Self::with_output(output_index)?;
+        let new = Self::with_output_crop(output_index, capture_crop_for(output_index))?;
         *self = new;
         Ok(())
     }
 
+    /// Re-upload the shader constants for a new HDR target brightness, keeping the rest of
+    /// the pipeline. SDR surfaces don't use it, so for them this does nothing.
+    pub fn update_target_nits(&mut self, nits: u32) -> Result<(), ScreenCaptureError> {
+        let nits = normalize_target_nits(nits);
+        if !self.is_hdr || nits == self.target_nits {
+            return Ok(());
+        }
+
+        if let Some(pipeline) = &self.gpu_pipeline {
+            let params = shader_constants(nits, &self.tone_mapping);
+            unsafe {
+                let mut mapped = D3D11_MAPPED_SUBRESOURCE::default();
+                self.device_context
+                    .Map(
+                        &pipeline.constant_buffer,
+                        0,
+                        D3D11_MAP_WRITE_DISCARD,
+                        0,
+                        Some(&mut mapped),
+                    )
+                    .map_err(|err| os_error("Map(constant_buffer)", err))?;
+                std::ptr::copy_nonoverlapping(
+                    params.as_ptr(),
+                    mapped.pData as *mut f32,
+                    params.len(),
+                );
+                self.device_context.Unmap(&pipeline.constant_buffer, 0);
+            }
+        }
+        self.target_nits = nits;
+        Ok(())
+    }
+
     pub fn output_index(&self) -> usize {
         self.output_index
     }
@@ -235,7 +356,9 @@ impl DxgiCapturer {
             None => true,
         };
         if should_capture || !self.has_frame {
-            let _ = self.capture_internal()?;
+            if let CaptureStatus::ModeChanged(_) = self.capture_internal()? {
+                self.rebuild_and_retry(now)?;
+            }
             self.last_capture_time = Some(now);
         }
 
@@ -261,7 +384,7 @@ impl DxgiCapturer {
                     Height: crop_height,
                     MipLevels: 1,
                     ArraySize: 1,
-                    Format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                    Format: self.working_format,
                     SampleDesc: DXGI_SAMPLE_DESC { Count: 1, Quality: 0 },
                     Usage: D3D11_USAGE_STAGING,
                     BindFlags: 0,
@@ -316,11 +439,16 @@ impl DxgiCapturer {
             self.crop_buffer.resize(dst_stride * height_usize, 0);
 
             let src = unsafe { slice::from_raw_parts(mapped.pData as *const u8, src_pitch * height_usize) };
-            for y in 0..height_usize {
-                let src_row = &src[y * src_pitch..y * src_pitch + dst_stride];
-                let dst_row = &mut self.crop_buffer[y * dst_stride..(y + 1) * dst_stride];
-                dst_row.copy_from_slice(src_row);
-            }
+            copy_rows_to_bgra8(
+                src,
+                src_pitch,
+                &mut self.crop_buffer,
+                crop_width as usize,
+                height_usize,
+                self.working_format,
+                &self.tone_mapping,
+                self.target_nits,
+            );
 
             unsafe {
                 self.device_context.Unmap(crop_tex, 0);
@@ -340,6 +468,9 @@ impl DxgiCapturer {
                 stride: dst_stride,
                 pixels: &self.crop_buffer,
                 dirty_regions: &self.crop_dirty_regions,
+                // Crops are derived per call and never shared.
+                frame_id: 0,
+                captured_at: self.captured_at,
             });
         }
 
@@ -366,6 +497,8 @@ impl DxgiCapturer {
             stride: dst_stride,
             pixels: &self.crop_buffer,
             dirty_regions: &self.crop_dirty_regions,
+            frame_id: 0,
+            captured_at: self.captured_at,
         })
     }
 
@@ -380,13 +513,21 @@ impl DxgiCapturer {
             {
                 let code = err.code();
                 if code == DXGI_ERROR_WAIT_TIMEOUT {
+                    // The duplication is alive and the desktop simply hasn't changed.
+                    self.captured_at = Instant::now();
                     return Ok(CaptureStatus::NoFrame);
                 }
-                if code == DXGI_ERROR_ACCESS_LOST || code == DXGI_ERROR_ACCESS_DENIED {
-                    return Err(ScreenCaptureError::InvalidState(
+                if code == DXGI_ERROR_ACCESS_LOST {
+                    return Err(ScreenCaptureError::AccessLost(
                         "DXGI output duplication became unavailable",
                     ));
                 }
+                if code == DXGI_ERROR_ACCESS_DENIED {
+                    // Secure desktop (UAC prompt, lock screen) or a protected fullscreen app.
+                    return Err(ScreenCaptureError::PermissionDenied(
+                        "desktop duplication is blocked by the secure desktop",
+                    ));
+                }
                 return Err(os_error("AcquireNextFrame", err));
             }
 
@@ -397,7 +538,15 @@ impl DxgiCapturer {
                 .cast()
                 .map_err(|err| os_error("IDXGIResource::cast<ID3D11Texture2D>", err))?;
 
-            collect_dirty_regions(&self.duplication, &mut self.dirty_regions)?;
+            // A mode change doesn't always cost us the duplication, but the staging texture
+            // and GPU pipeline are sized for the old mode.
+            let mut texture_desc = D3D11_TEXTURE2D_DESC::default();
+            desktop_texture.GetDesc(&mut texture_desc);
+            let source_size = (texture_desc.Width, texture_desc.Height);
+            if *self.source_size.get_or_insert(source_size) != source_size {
+                let _ = self.duplication.ReleaseFrame();
+                return Ok(CaptureStatus::ModeChanged(source_size));
+            }
 
             // Process frame based on pipeline type
             let has_gpu_pipeline = self.gpu_pipeline.is_some();
@@ -407,10 +556,27 @@ impl DxgiCapturer {
                 self.process_cpu_fallback(&desktop_texture)?;
             }
 
+            // After processing, so the buffer size the regions are scaled to is final. Rects
+            // of a cropped capture would need clipping to the crop, so it repaints in full.
+            if frame_info.TotalMetadataBufferSize == 0 || self.source_region.is_some() {
+                self.dirty_regions.clear();
+            } else {
+                collect_dirty_regions(
+                    &self.duplication,
+                    rotation_degrees(self.dupl_desc.Rotation),
+                    source_size,
+                    (self.width, self.height),
+                    &mut self.dirty_regions,
+                )?;
+            }
+
             // Release frame after processing
             let _ = self.duplication.ReleaseFrame();
 
             self.has_frame = true;
+            self.held_frame = None;
+            self.frame_id = next_frame_id();
+            self.captured_at = Instant::now();
             Ok(CaptureStatus::Updated)
         }
     }
@@ -451,11 +617,16 @@ impl DxgiCapturer {
             self.buffer.resize(dst_stride * height, 0);
 
             let src = slice::from_raw_parts(mapped.pData as *const u8, src_pitch * height);
-            for y in 0..height {
-                let src_row = &src[y * src_pitch..y * src_pitch + dst_stride];
-                let dst_row = &mut self.buffer[y * dst_stride..(y + 1) * dst_stride];
-                dst_row.copy_from_slice(src_row);
-            }
+            copy_rows_to_bgra8(
+                src,
+                src_pitch,
+                &mut self.buffer,
+                self.width as usize,
+                height,
+                self.working_format,
+                &self.tone_mapping,
+                self.target_nits,
+            );
 
             ctx.Unmap(&self.staging_texture, 0);
 
@@ -505,14 +676,22 @@ impl DxgiCapturer {
             ctx.PSSetShaderResources(0, Some(&[Some(srv.clone())]));
             ctx.PSSetSamplers(0, Some(&[Some(pipeline.sampler.clone())]));
             ctx.VSSetConstantBuffers(0, Some(&[Some(pipeline.constant_buffer.clone())]));
+            ctx.PSSetConstantBuffers(0, Some(&[Some(pipeline.constant_buffer.clone())]));
             ctx.IASetInputLayout(&pipeline.input_layout);
             ctx.IASetPrimitiveTopology(D3D11_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP);
 
+            // The quad always covers the whole desktop texture. With a crop the viewport is
+            // scaled up and shifted so only the cropped part lands on the render target.
+            let (x, y, w, h) = self
+                .source_region
+                .unwrap_or((0, 0, tex_desc.Width, tex_desc.Height));
+            let scale_x = self.width as f32 / w as f32;
+            let scale_y = self.height as f32 / h as f32;
             let viewport = D3D11_VIEWPORT {
-                TopLeftX: 0.0,
-                TopLeftY: 0.0,
-                Width: self.width as f32,
-                Height: self.height as f32,
+                TopLeftX: -(x as f32) * scale_x,
+                TopLeftY: -(y as f32) * scale_y,
+                Width: tex_desc.Width as f32 * scale_x,
+                Height: tex_desc.Height as f32 * scale_y,
                 MinDepth: 0.0,
                 MaxDepth: 1.0,
             };
@@ -532,13 +711,15 @@ impl DxgiCapturer {
         desktop_texture: &ID3D11Texture2D,
         pipeline: &GpuPipeline,
     ) -> Result<(), ScreenCaptureError> {
+        let source_box = self.source_box();
+        let source_box = source_box.as_ref().map(std::ptr::from_ref);
         unsafe {
             let ctx = &self.device_context;
 
             if let (Some(ref mip_texture), Some(ref mip_srv)) =
                 (&pipeline.mip_texture, &pipeline.mip_srv)
             {
-                ctx.CopySubresourceRegion(mip_texture, 0, 0, 0, 0, desktop_texture, 0, None);
+                ctx.CopySubresourceRegion(mip_texture, 0, 0, 0, 0, desktop_texture, 0, source_box);
                 ctx.GenerateMips(mip_srv);
 
                 let target_mip = pipeline.mip_levels.saturating_sub(1);
@@ -552,6 +733,17 @@ impl DxgiCapturer {
                     target_mip,
                     None,
                 );
+            } else if source_box.is_some() {
+                ctx.CopySubresourceRegion(
+                    &pipeline.convert_texture,
+                    0,
+                    0,
+                    0,
+                    0,
+                    desktop_texture,
+                    0,
+                    source_box,
+                );
             } else {
                 ctx.CopyResource(&pipeline.convert_texture, desktop_texture);
             }
@@ -590,10 +782,17 @@ impl DxgiCapturer {
                 .Map(&mut mapped, DXGI_MAP_READ)
                 .map_err(|err| os_error("IDXGISurface1::Map", err))?;
 
+            // Rotated outputs hand over textures in panel orientation, which a crop taken
+            // from the desktop size may not fit.
+            let (x, y, width, height) = self
+                .source_region
+                .filter(|&(x, y, w, h)| x + w <= desc.Width && y + h <= desc.Height)
+                .unwrap_or((0, 0, desc.Width, desc.Height));
             self.copy_surface_cpu(
                 &mapped,
-                desc.Width as usize,
-                desc.Height as usize,
+                (x as usize, y as usize),
+                width as usize,
+                height as usize,
                 DXGI_FORMAT(desc.Format.0),
             );
 
@@ -605,17 +804,19 @@ impl DxgiCapturer {
         }
     }
 
-    /// CPU-based surface copy with format conversion and downsampling.
+    /// CPU-based surface copy with format conversion and downsampling, of the
+    /// `width` x `height` region at `origin`.
     fn copy_surface_cpu(
         &mut self,
         mapped: &DXGI_MAPPED_RECT,
+        origin: (usize, usize),
         width: usize,
         height: usize,
         format: DXGI_FORMAT,
     ) {
         unsafe {
             let pitch = mapped.Pitch as usize;
-            let data = slice::from_raw_parts(mapped.pBits as *const u8, pitch * height);
+            let data = slice::from_raw_parts(mapped.pBits as *const u8, pitch * (origin.1 + height));
 
             let max_pixels =
                 CAPTURE_MAX_PIXELS.load(std::sync::atomic::Ordering::Relaxed);
@@ -630,20 +831,27 @@ impl DxgiCapturer {
             let mut scaled = vec![0u8; scaled_width * scaled_height * BYTES_PER_PIXEL];
             let src_bpp = bytes_per_pixel_for_format(format);
             let dst_stride = scaled_width * BYTES_PER_PIXEL;
+            let tone_mapping = self.tone_mapping;
+            let target_nits = self.target_nits;
 
             scaled
                 .par_chunks_mut(dst_stride)
                 .enumerate()
                 .for_each(|(y, row)| {
-                    let src_y = y * height / scaled_height;
+                    let src_y = origin.1 + y * height / scaled_height;
 
                     for x in 0..scaled_width {
-                        let src_x = x * width / scaled_width;
+                        let src_x = origin.0 + x * width / scaled_width;
 
                         let src_idx = src_y * pitch + src_x * src_bpp;
                         let dst_idx = x * BYTES_PER_PIXEL;
 
-                        let bgra = decode_pixel_to_bgra8(&data[src_idx..], format);
+                        let bgra = decode_pixel_to_bgra8(
+                            &data[src_idx..],
+                            format,
+                            &tone_mapping,
+                            target_nits,
+                        );
                         row[dst_idx..dst_idx + BYTES_PER_PIXEL].copy_from_slice(&bgra);
                     }
                 });
@@ -656,6 +864,118 @@ impl DxgiCapturer {
     }
 }
 
+impl DxgiCapturer {
+    /// Box of the desktop texture that is captured, when cropped.
+    fn source_box(&self) -> Option<D3D11_BOX> {
+        self.source_region.map(|(x, y, width, height)| D3D11_BOX {
+            left: x,
+            top: y,
+            front: 0,
+            right: x + width,
+            bottom: y + height,
+            back: 1,
+        })
+    }
+
+    /// Replaces the duplicator (and everything sized from it, including the HDR check) for
+    /// the same output, then tries one capture with it. The last frame is kept to serve
+    /// until the new duplication delivers one.
+    fn rebuild_and_retry(&mut self, now: Instant) -> Result<(), ScreenCaptureError> {
+        let Ok(mut rebuilt) = DxgiCapturer::with_output_crop(self.output_index, self.crop) else {
+            return Err(ScreenCaptureError::AccessLost(
+                "DXGI duplication lost and recovery failed",
+            ));
+        };
+        if rebuilt.is_hdr != self.is_hdr {
+            log::info!(
+                output_index = self.output_index,
+                hdr = rebuilt.is_hdr;
+                "[dxgi] Output HDR mode changed"
+            );
+        }
+        rebuilt.held_frame = self.take_last_frame();
+        rebuilt.rebuild_attempts = self.rebuild_attempts;
+        *self = rebuilt;
+        if let CaptureStatus::Updated = self.capture_internal()? {
+            self.last_capture_time = Some(now);
+        }
+        Ok(())
+    }
+
+    /// Rebuild after the duplication became unusable. A failed attempt schedules the next
+    /// one after a backoff, and the last frame keeps being served meanwhile; the error is
+    /// returned once `MAX_REBUILD_ATTEMPTS` in a row have failed.
+    fn recover(&mut self, now: Instant) -> Result<(), ScreenCaptureError> {
+        self.rebuild_attempts += 1;
+        log::warn!(
+            output_index = self.output_index,
+            attempt = self.rebuild_attempts;
+            "[dxgi] Rebuilding output duplication"
+        );
+        match self.rebuild_and_retry(now) {
+            Ok(()) => {
+                self.rebuild_attempts = 0;
+                self.retry_at = None;
+                Ok(())
+            }
+            Err(err) if self.rebuild_attempts >= MAX_REBUILD_ATTEMPTS => {
+                self.rebuild_attempts = 0;
+                self.retry_at = None;
+                Err(err)
+            }
+            Err(err) => {
+                log::warn!(
+                    output_index = self.output_index,
+                    attempt = self.rebuild_attempts,
+                    err:display = err;
+                    "[dxgi] Rebuild failed, retrying"
+                );
+                self.retry_at = Some(now + REBUILD_BACKOFF * self.rebuild_attempts);
+                Ok(())
+            }
+        }
+    }
+
+    fn take_last_frame(&mut self) -> Option<HeldFrame> {
+        if !self.has_frame {
+            return self.held_frame.take();
+        }
+        Some(HeldFrame {
+            buffer: mem::take(&mut self.buffer),
+            width: self.width,
+            height: self.height,
+            stride: self.stride,
+            frame_id: self.frame_id,
+            captured_at: self.captured_at,
+        })
+    }
+
+    /// The current frame, or the one held over from before a rebuild.
+    fn latest_frame(&self) -> Result<ScreenFrame<'_>, ScreenCaptureError> {
+        if self.has_frame {
+            return Ok(ScreenFrame {
+                width: self.width,
+                height: self.height,
+                stride: self.stride,
+                pixels: &self.buffer,
+                dirty_regions: &self.dirty_regions,
+                frame_id: self.frame_id,
+                captured_at: self.captured_at,
+            });
+        }
+        let held = self.held_frame.as_ref().ok_or(ScreenCaptureError::NoFrame)?;
+        Ok(ScreenFrame {
+            width: held.width,
+            height: held.height,
+            stride: held.stride,
+            pixels: &held.buffer,
+            dirty_regions: &[],
+            frame_id: held.frame_id,
+            captured_at: held.captured_at,
+        })
+    }
+}
+
 impl ScreenCapturer for DxgiCapturer {
     fn capture(&mut self) -> Result<ScreenFrame<'_>, ScreenCaptureError> {
         let fps = CAPTURE_FPS.load(std::sync::atomic::Ordering::Relaxed).clamp(1, 60) as u64;
@@ -667,42 +987,41 @@ impl ScreenCapturer for DxgiCapturer {
             None => true,
         };
 
-        if should_capture || !self.has_frame {
+        if let Some(retry_at) = self.retry_at {
+            if now >= retry_at {
+                self.recover(now)?;
+            }
+        } else if should_capture || !self.has_frame {
+            self.update_target_nits(get_capture_target_nits())?;
             match self.capture_internal() {
                 Ok(CaptureStatus::Updated) => {
                     self.last_capture_time = Some(now);
                 }
                 Ok(CaptureStatus::NoFrame) => {
                     self.dirty_regions.clear();
-                    if !self.has_frame {
-                        return Err(ScreenCaptureError::InvalidState("No frame available yet"));
-                    }
                 }
-                Err(ScreenCaptureError::InvalidState(_)) => {
-                    // Attempt auto-recovery once by rebuilding the duplicator for the same output.
-                    if let Ok(rebuilt) = DxgiCapturer::with_output(self.output_index) {
-                        *self = rebuilt;
-                        // retry once
-                        if let CaptureStatus::Updated = self.capture_internal()? {
-                            self.last_capture_time = Some(now);
-                        }
-                    } else {
-                        return Err(ScreenCaptureError::InvalidState(
-                            "DXGI duplication lost and recovery failed",
-                        ));
-                    }
+                Ok(CaptureStatus::ModeChanged((width, height))) => {
+                    log::info!(
+                        output_index = self.output_index,
+                        width = width,
+                        height = height;
+                        "[dxgi] Display mode changed, rebuilding capturer"
+                    );
+                    self.recover(now)?;
+                }
+                Err(err) if err.invalidates_capturer() => {
+                    log::warn!(
+                        output_index = self.output_index,
+                        err:display = err;
+                        "[dxgi] Output duplication lost"
+                    );
+                    self.recover(now)?;
                 }
                 Err(err) => return Err(err),
             };
         }
 
-        Ok(ScreenFrame {
-            width: self.width,
-            height: self.height,
-            stride: self.stride,
-            pixels: &self.buffer,
-            dirty_regions: &self.dirty_regions,
-        })
+        self.latest_frame()
     }
 
     fn size(&self) -> (u32, u32) {
@@ -712,16 +1031,63 @@ impl ScreenCapturer for DxgiCapturer {
 
 unsafe impl Send for DxgiCapturer {}
 
+/// Shader constants: `params` (target nits and the constant derived from it) followed by
+/// `toneMap` (exposure, knee, operator). Two `float4`s, as constant buffers come in
+/// 16-byte registers.
+fn shader_constants(target_nits: u32, tone_mapping: &ToneMapping) -> [f32; 8] {
+    let operator = match tone_mapping.operator {
+        ToneMapOperator::Linear => 0.0,
+        ToneMapOperator::Reinhard => 1.0,
+        ToneMapOperator::Aces => 2.0,
+        ToneMapOperator::Hable => 3.0,
+    };
+    [
+        target_nits as f32,
+        18.851_563 - 18.6875 * target_nits as f32,
+        0.0,
+        0.0,
+        tone_mapping.exposure,
+        tone_mapping.knee,
+        operator,
+        0.0,
+    ]
+}
+
+/// The precompiled shader when the settings only scale and clip, else the tone-mapping
+/// variant. If that doesn't compile, the capture keeps working with the plain conversion.
+fn pixel_shader_bytecode(
+    is_hdr: bool,
+    tone_mapping: &ToneMapping,
+) -> std::borrow::Cow<'static, [u8]> {
+    if !is_hdr || tone_mapping.is_linear_passthrough() {
+        return shaders::PIXEL_SHADER_BYTECODE.into();
+    }
+    match shaders::compile_pixel_shader(shaders::TONE_MAP_PIXEL_SHADER_HLSL) {
+        Ok(bytecode) => bytecode.into(),
+        Err(err) => {
+            log::warn!(
+                err:display = err;
+                "[dxgi] Tone-mapping shader failed to compile, using the plain curve"
+            );
+            shaders::PIXEL_SHADER_BYTECODE.into()
+        }
+    }
+}
+
 /// Create GPU pipeline for HDR/SDR processing.
+#[allow(clippy::too_many_arguments)]
 fn create_gpu_pipeline(
     device: &ID3D11Device,
     src_width: u32,
     src_height: u32,
     dst_width: u32,
     dst_height: u32,
-    is_hdr: bool,
+    surface_format: SurfaceFormat,
     target_nits: u32,
+    tone_mapping: ToneMapping,
 ) -> Result<GpuPipeline, ScreenCaptureError> {
+    let is_hdr = surface_format == SurfaceFormat::Hdr;
+    let working_format = surface_format.working_format();
     unsafe {
         let mut vertex_shader: Option<ID3D11VertexShader> = None;
         device
@@ -731,7 +1097,11 @@ fn create_gpu_pipeline(
 
         let mut pixel_shader: Option<ID3D11PixelShader> = None;
         device
-            .CreatePixelShader(shaders::PIXEL_SHADER_BYTECODE, None, Some(&mut pixel_shader))
+            .CreatePixelShader(
+                &pixel_shader_bytecode(is_hdr, &tone_mapping),
+                None,
+                Some(&mut pixel_shader),
+            )
             .map_err(|err| os_error("CreatePixelShader", err))?;
         let pixel_shader = pixel_shader.unwrap();
 
@@ -772,14 +1142,9 @@ fn create_gpu_pipeline(
             .map_err(|err| os_error("CreateSamplerState", err))?;
         let sampler = sampler.unwrap();
 
-        let params: [f32; 4] = [
-            target_nits as f32,
-            18.851_563 - 18.6875 * target_nits as f32,
-            0.0,
-            0.0,
-        ];
+        let params = shader_constants(target_nits, &tone_mapping);
         let buffer_desc = D3D11_BUFFER_DESC {
-            ByteWidth: 16,
+            ByteWidth: mem::size_of_val(&params) as u32,
             Usage: D3D11_USAGE_DYNAMIC,
             BindFlags: windows::Win32::Graphics::Direct3D11::D3D11_BIND_CONSTANT_BUFFER.0 as u32,
             CPUAccessFlags: D3D11_CPU_ACCESS_WRITE.0 as u32,
@@ -802,7 +1167,7 @@ fn create_gpu_pipeline(
             Height: dst_height,
             MipLevels: 1,
             ArraySize: 1,
-            Format: DXGI_FORMAT_B8G8R8A8_UNORM,
+            Format: working_format,
             SampleDesc: DXGI_SAMPLE_DESC {
                 Count: 1,
                 Quality: 0,
@@ -819,7 +1184,7 @@ fn create_gpu_pipeline(
         let convert_texture = convert_texture.unwrap();
 
         let rtv_desc = D3D11_RENDER_TARGET_VIEW_DESC {
-            Format: DXGI_FORMAT_B8G8R8A8_UNORM,
+            Format: working_format,
             ViewDimension: D3D11_RTV_DIMENSION_TEXTURE2D,
             Anonymous: windows::Win32::Graphics::Direct3D11::D3D11_RENDER_TARGET_VIEW_DESC_0 {
                 Texture2D: D3D11_TEX2D_RTV { MipSlice: 0 },
@@ -845,7 +1210,7 @@ fn create_gpu_pipeline(
                 Height: src_height,
                 MipLevels: levels,
                 ArraySize: 1,
-                Format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                Format: working_format,
                 SampleDesc: DXGI_SAMPLE_DESC {
                     Count: 1,
                     Quality: 0,
@@ -862,7 +1227,7 @@ fn create_gpu_pipeline(
             let mip_tex = mip_tex.unwrap();
 
             let srv_desc = D3D11_SHADER_RESOURCE_VIEW_DESC {
-                Format: DXGI_FORMAT_B8G8R8A8_UNORM,
+                Format: working_format,
                 ViewDimension: D3D11_SRV_DIMENSION_TEXTURE2D,
                 Anonymous: D3D11_SHADER_RESOURCE_VIEW_DESC_0 {
                     Texture2D: D3D11_TEX2D_SRV {
@@ -903,12 +1268,13 @@ type DxgiDuplicationInit = (
     DXGI_OUTDUPL_DESC,
     DXGI_OUTPUT_DESC,
     Option<DXGI_OUTPUT_DESC1>,
-    bool,
+    SurfaceFormat,
 );
 
 fn create_duplication(
     target_output_index: usize,
     try_hdr: bool,
+    try_10bit: bool,
 ) -> Result<DxgiDuplicationInit, ScreenCaptureError> {
     unsafe {
         let factory: IDXGIFactory1 =
@@ -927,6 +1293,7 @@ fn create_duplication(
                 target_output_index,
                 &mut current_index,
                 try_hdr,
+                try_10bit,
             )? {
                 return Ok(result);
             }
@@ -943,6 +1310,7 @@ fn try_initialize_on_adapter(
     target_output_index: usize,
     current_index: &mut usize,
     try_hdr: bool,
+    try_10bit: bool,
 ) -> Result<Option<DxgiDuplicationInit>, ScreenCaptureError> {
     unsafe {
         let base_adapter: IDXGIAdapter = adapter
@@ -989,14 +1357,31 @@ fn try_initialize_on_adapter(
                                 dupl_desc,
                                 desc,
                                 desc1,
-                                true,
+                                SurfaceFormat::Hdr,
                             )));
                         }
                     }
 
-                    let sdr_format = DXGI_FORMAT_B8G8R8A8_UNORM;
-                    if let Ok(duplication) = output6.DuplicateOutput1(&device, 0, &[sdr_format]) {
+                    // The list is a preference order; DWM hands out 8-bit when it can't
+                    // provide 10-bit, so the granted format decides the decode path.
+                    let sdr_formats: &[DXGI_FORMAT] = if try_10bit {
+                        &[DXGI_FORMAT_R10G10B10A2_UNORM, DXGI_FORMAT_B8G8R8A8_UNORM]
+                    } else {
+                        &[DXGI_FORMAT_B8G8R8A8_UNORM]
+                    };
+                    if let Ok(duplication) = output6.DuplicateOutput1(&device, 0, sdr_formats) {
                         let dupl_desc = duplication.GetDesc();
+                        let format = if dupl_desc.ModeDesc.Format == DXGI_FORMAT_R10G10B10A2_UNORM {
+                            SurfaceFormat::Sdr10
+                        } else {
+                            if try_10bit {
+                                log::info!(
+                                    output_index = target_output_index;
+                                    "[dxgi] 10-bit capture not granted, using 8-bit"
+                                );
+                            }
+                            SurfaceFormat::Sdr8
+                        };
                         return Ok(Some((
                             device,
                             device_context,
@@ -1004,7 +1389,7 @@ fn try_initialize_on_adapter(
                             dupl_desc,
                             desc,
                             desc1,
-                            false,
+                            format,
                         )));
                     }
                 }
@@ -1023,7 +1408,7 @@ fn try_initialize_on_adapter(
                             dupl_desc,
                             desc,
                             None,
-                            false,
+                            SurfaceFormat::Sdr8,
                         )));
                     }
                     Err(err) => return Err(os_error("IDXGIOutput1::DuplicateOutput", err)),
@@ -1101,6 +1486,8 @@ fn os_error(context: &'static str, err: windows::core::Error) -> ScreenCaptureEr
 enum CaptureStatus {
     Updated,
     NoFrame,
+    /// The desktop texture no longer has the size the capturer was built for.
+    ModeChanged((u32, u32)),
 }
 
 fn bytes_per_pixel_for_format(format: DXGI_FORMAT) -> usize {
@@ -1113,22 +1500,27 @@ fn bytes_per_pixel_for_format(format: DXGI_FORMAT) -> usize {
 }
 
 #[inline]
-fn decode_pixel_to_bgra8(src: &[u8], format: DXGI_FORMAT) -> [u8; 4] {
+fn decode_pixel_to_bgra8(
+    src: &[u8],
+    format: DXGI_FORMAT,
+    tone_mapping: &ToneMapping,
+    target_nits: u32,
+) -> [u8; 4] {
     match format {
         DXGI_FORMAT_R10G10B10A2_UNORM => {
             if src.len() < 4 {
                 return [0, 0, 0, 255];
             }
             let packed = u32::from_le_bytes([src[0], src[1], src[2], src[3]]);
-            let r10 = (packed & 0x3FF) as u16;
-            let g10 = ((packed >> 10) & 0x3FF) as u16;
-            let b10 = ((packed >> 20) & 0x3FF) as u16;
-            let a2 = ((packed >> 30) & 0x3) as u8;
-
-            let r8 = (r10 >> 2) as u8;
-            let g8 = (g10 >> 2) as u8;
-            let b8 = (b10 >> 2) as u8;
-            let a8 = a2 * 85;
+            // Only SDR surfaces reach this path (HDR10 goes through the PQ shader), so the
+            // channels are already sRGB-encoded in the desktop's sRGB gamut and just need
+            // rescaling. Rounding instead of dropping the low bits keeps 1023 -> 255 and
+            // spreads the error evenly.
+            let unorm10_to_u8 = |v: u32| ((v * 255 + 511) / 1023) as u8;
+            let r8 = unorm10_to_u8(packed & 0x3FF);
+            let g8 = unorm10_to_u8((packed >> 10) & 0x3FF);
+            let b8 = unorm10_to_u8((packed >> 20) & 0x3FF);
+            let a8 = ((packed >> 30) & 0x3) as u8 * 85;
 
             [b8, g8, r8, a8]
         }
@@ -1141,10 +1533,11 @@ fn decode_pixel_to_bgra8(src: &[u8], format: DXGI_FORMAT) -> [u8; 4] {
             let b_half = u16::from_le_bytes([src[4], src[5]]);
             let a_half = u16::from_le_bytes([src[6], src[7]]);
 
-            let r8 = half_to_u8_tonemapped(r_half);
-            let g8 = half_to_u8_tonemapped(g_half);
-            let b8 = half_to_u8_tonemapped(b_half);
-            let a8 = half_to_u8_tonemapped(a_half);
+            let r8 = half_to_u8_tonemapped(r_half, tone_mapping, target_nits);
+            let g8 = half_to_u8_tonemapped(g_half, tone_mapping, target_nits);
+            let b8 = half_to_u8_tonemapped(b_half, tone_mapping, target_nits);
+            // Alpha isn't light; it always takes the default curve at scRGB white.
+            let a8 = half_to_u8_tonemapped(a_half, &ToneMapping::default(), SCRGB_WHITE_NITS);
 
             [b8, g8, r8, a8]
         }
@@ -1157,49 +1550,176 @@ fn decode_pixel_to_bgra8(src: &[u8], format: DXGI_FORMAT) -> [u8; 4] {
     }
 }
 
+/// Copy `rows` rows of `width` pixels from a mapped surface into a tightly packed BGRA8
+/// buffer, decoding when the surface isn't BGRA8 already.
+fn copy_rows_to_bgra8(
+    src: &[u8],
+    src_pitch: usize,
+    dst: &mut [u8],
+    width: usize,
+    rows: usize,
+    format: DXGI_FORMAT,
+    tone_mapping: &ToneMapping,
+    target_nits: u32,
+) {
+    let dst_stride = width * BYTES_PER_PIXEL;
+    let src_bpp = bytes_per_pixel_for_format(format);
+    for y in 0..rows {
+        let src_row = &src[y * src_pitch..y * src_pitch + width * src_bpp];
+        let dst_row = &mut dst[y * dst_stride..(y + 1) * dst_stride];
+        if format == DXGI_FORMAT_B8G8R8A8_UNORM {
+            dst_row.copy_from_slice(src_row);
+        } else {
+            for (dst_px, src_px) in dst_row
+                .chunks_exact_mut(BYTES_PER_PIXEL)
+                .zip(src_row.chunks_exact(src_bpp))
+            {
+                dst_px.copy_from_slice(&decode_pixel_to_bgra8(
+                    src_px,
+                    format,
+                    tone_mapping,
+                    target_nits,
+                ));
+            }
+        }
+    }
+}
+
+/// Changed areas of the frame just acquired, in buffer coordinates. Moved areas count as
+/// changed at their destination; what they uncover is reported as dirty by DXGI itself.
+/// Leaves `target` empty (the whole frame) if DXGI reports nothing.
 fn collect_dirty_regions(
     duplication: &IDXGIOutputDuplication,
+    rotation: u32,
+    texture_size: (u32, u32),
+    buffer_size: (u32, u32),
     target: &mut Vec<DirtyRegion>,
 ) -> Result<(), ScreenCaptureError> {
     target.clear();
 
-    let mut buffer_size = 0u32;
+    let mut rects = Vec::new();
+
+    let rect_size = mem::size_of::<RECT>() as u32;
+    let mut required = 0u32;
     unsafe {
         // First call to retrieve required buffer size; expected to return MORE_DATA.
-        let _ = duplication.GetFrameDirtyRects(0, std::ptr::null_mut(), &mut buffer_size);
+        let _ = duplication.GetFrameDirtyRects(0, std::ptr::null_mut(), &mut required);
     }
-
-    let rect_size = std::mem::size_of::<windows::Win32::Foundation::RECT>() as u32;
-    let count = buffer_size.div_ceil(rect_size);
-    if count == 0 {
-        return Ok(());
+    let count = required.div_ceil(rect_size);
+    if count > 0 {
+        let mut dirty = vec![RECT::default(); count as usize];
+        unsafe {
+            duplication
+                .GetFrameDirtyRects(required, dirty.as_mut_ptr(), &mut required)
+                .map_err(|err| os_error("IDXGIOutputDuplication::GetFrameDirtyRects", err))?;
+        }
+        dirty.truncate((required / rect_size) as usize);
+        rects.extend(dirty);
     }
 
-    let mut rects = vec![windows::Win32::Foundation::RECT::default(); count as usize];
+    let move_size = mem::size_of::<DXGI_OUTDUPL_MOVE_RECT>() as u32;
+    let mut required = 0u32;
     unsafe {
-        duplication
-            .GetFrameDirtyRects(buffer_size, rects.as_mut_ptr(), &mut buffer_size)
-            .map_err(|err| os_error("IDXGIOutputDuplication::GetFrameDirtyRects", err))?;
+        let _ = duplication.GetFrameMoveRects(0, std::ptr::null_mut(), &mut required);
+    }
+    let count = required.div_ceil(move_size);
+    if count > 0 {
+        let mut moves = vec![DXGI_OUTDUPL_MOVE_RECT::default(); count as usize];
+        unsafe {
+            duplication
+                .GetFrameMoveRects(required, moves.as_mut_ptr(), &mut required)
+                .map_err(|err| os_error("IDXGIOutputDuplication::GetFrameMoveRects", err))?;
+        }
+        moves.truncate((required / move_size) as usize);
+        rects.extend(moves.iter().map(|m| m.DestinationRect));
     }
 
-    target.extend(rects.iter().map(|r| DirtyRegion {
-        x: r.left,
-        y: r.top,
-        width: (r.right - r.left).max(0),
-        height: (r.bottom - r.top).max(0),
-    }));
-
+    target.extend(
+        rects
+            .iter()
+            .filter_map(|rect| desktop_rect_to_buffer(rect, rotation, texture_size, buffer_size)),
+    );
     Ok(())
 }
 
+/// Map a rectangle from desktop coordinates (as the rotated output shows it) into the
+/// captured buffer, which keeps the desktop texture's unrotated orientation and is scaled
+/// from `texture_size` down to `buffer_size`. Rounds outwards so no changed pixel is lost;
+/// `None` if nothing of it is on screen.
+fn desktop_rect_to_buffer(
+    rect: &RECT,
+    rotation: u32,
+    texture_size: (u32, u32),
+    buffer_size: (u32, u32),
+) -> Option<DirtyRegion> {
+    let (tw, th) = (texture_size.0 as i64, texture_size.1 as i64);
+    let (l, t, r, b) = (rect.left as i64, rect.top as i64, rect.right as i64, rect.bottom as i64);
+    // Clockwise output rotation: undo it to get back to texture coordinates.
+    let (left, top, right, bottom) = match rotation {
+        90 => (tw - b, l, tw - t, r),
+        180 => (tw - r, th - b, tw - l, th - t),
+        270 => (t, th - r, b, th - l),
+        _ => (l, t, r, b),
+    };
+
+    let (bw, bh) = (buffer_size.0 as i64, buffer_size.1 as i64);
+    let scale_down = |v: i64, from: i64, to: i64| (v.clamp(0, from) * to) / from.max(1);
+    let scale_up = |v: i64, from: i64, to: i64| (v.clamp(0, from) * to + from - 1) / from.max(1);
+    let left = scale_down(left, tw, bw);
+    let top = scale_down(top, th, bh);
+    let right = scale_up(right, tw, bw);
+    let bottom = scale_up(bottom, th, bh);
+    (right > left && bottom > top).then(|| DirtyRegion {
+        x: left as i32,
+        y: top as i32,
+        width: (right - left) as i32,
+        height: (bottom - top) as i32,
+    })
+}
+
+/// Brightness of scRGB 1.0.
+const SCRGB_WHITE_NITS: u32 = 80;
+
+/// scRGB to 8-bit with a 2.2 gamma, through the same curves as the tone-map shader.
+/// Like the shader, light is first scaled so `target_nits` lands on 1.0.
 #[inline]
-fn half_to_u8_tonemapped(half: u16) -> u8 {
-    let f = half_to_f32(half);
-    let tonemapped = f / (1.0 + f);
+fn half_to_u8_tonemapped(half: u16, tone_mapping: &ToneMapping, target_nits: u32) -> u8 {
+    let scale = SCRGB_WHITE_NITS as f32 / target_nits.max(1) as f32;
+    let tonemapped = tone_map(half_to_f32(half) * scale, tone_mapping);
     let gamma_corrected = tonemapped.powf(1.0 / 2.2);
     (gamma_corrected.clamp(0.0, 1.0) * 255.0) as u8
 }
 
+/// `TONE_MAP_PIXEL_SHADER_HLSL`'s exposure, operator and shoulder for one channel.
+fn tone_map(f: f32, tone_mapping: &ToneMapping) -> f32 {
+    fn hable_partial(c: f32) -> f32 {
+        const A: f32 = 0.15;
+        const B: f32 = 0.50;
+        const C: f32 = 0.10;
+        const D: f32 = 0.20;
+        const E: f32 = 0.02;
+        const F: f32 = 0.30;
+        ((c * (A * c + C * B) + D * E) / (c * (A * c + B) + D * F)) - E / F
+    }
+
+    let c = (f * tone_mapping.exposure).max(0.0);
+    let c = match tone_mapping.operator {
+        ToneMapOperator::Linear => c,
+        ToneMapOperator::Reinhard => c / (1.0 + c),
+        ToneMapOperator::Aces => {
+            ((c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14)).clamp(0.0, 1.0)
+        }
+        ToneMapOperator::Hable => hable_partial(c * 2.0) / hable_partial(11.2),
+    };
+
+    let knee = tone_mapping.knee;
+    if knee >= 1.0 {
+        return c;
+    }
+    let range = 1.0 - knee;
+    c.min(knee) + range * (1.0 - (-(c - knee).max(0.0) / range).exp())
+}
+
 #[inline]
 fn half_to_f32(half: u16) -> f32 {
     let sign = ((half >> 15) & 1) as u32;
@@ -1231,3 +1751,104 @@ fn half_to_f32(half: u16) -> f32 {
         f32::from_bits((sign << 31) | (new_exp << 23) | (mantissa << 13))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::screen::DEFAULT_TARGET_NITS;
+
+    fn pack_r10(r: u32, g: u32, b: u32, a: u32) -> [u8; 4] {
+        (r | (g << 10) | (b << 20) | (a << 30)).to_le_bytes()
+    }
+
+    #[test]
+    fn tone_mapping_constants_follow_the_original_params() {
+        let constants = shader_constants(200, &ToneMapping::default());
+        assert_eq!(mem::size_of_val(&constants) % 16, 0);
+        // The first register is what the precompiled shaders have always read.
+        assert_eq!(constants[..4], [200.0, 18.851_563 - 18.6875 * 200.0, 0.0, 0.0]);
+        assert_eq!(constants[4..], [1.0, 1.0, 1.0, 0.0]);
+
+        let tone_mapping = ToneMapping {
+            exposure: 2.5,
+            knee: 0.75,
+            operator: ToneMapOperator::Hable,
+        };
+        assert_eq!(shader_constants(200, &tone_mapping)[4..], [2.5, 0.75, 3.0, 0.0]);
+    }
+
+    #[test]
+    fn cpu_tone_mapping_follows_the_selected_operator() {
+        // scRGB 1.0 and 4.0 as half floats.
+        let (one, four) = (0x3C00, 0x4400);
+        let with = |operator, exposure| ToneMapping {
+            exposure,
+            knee: 1.0,
+            operator,
+        };
+
+        let nits = DEFAULT_TARGET_NITS;
+
+        // Reinhard is the default curve.
+        let reinhard = with(ToneMapOperator::Reinhard, 1.0);
+        assert_eq!(half_to_u8_tonemapped(one, &ToneMapping::default(), nits), 144);
+        assert_eq!(half_to_u8_tonemapped(one, &reinhard, nits), 144);
+        // A brighter target leaves less of the same light for the LEDs.
+        assert_eq!(half_to_u8_tonemapped(one, &reinhard, 100), 176);
+        assert_eq!(half_to_u8_tonemapped(one, &reinhard, 4000), 42);
+
+        // Linear clips what the others roll off; ACES and Hable stay below white.
+        assert_eq!(half_to_u8_tonemapped(four, &with(ToneMapOperator::Linear, 2.0), nits), 255);
+        for operator in [ToneMapOperator::Aces, ToneMapOperator::Hable] {
+            let level = half_to_u8_tonemapped(four, &with(operator, 1.0), nits);
+            assert!((200..255).contains(&level), "{:?}: {}", operator, level);
+        }
+    }
+
+    #[test]
+    fn dirty_rects_are_unrotated_and_scaled_into_the_buffer() {
+        // 1920x1080 panel mounted in portrait; the texture stays 1920x1080, scaled to half.
+        let rect = |left, top, right, bottom| RECT { left, top, right, bottom };
+        let map = |r: RECT, rotation| {
+            desktop_rect_to_buffer(&r, rotation, (1920, 1080), (960, 540))
+                .map(|d| (d.x, d.y, d.width, d.height))
+        };
+
+        assert_eq!(map(rect(100, 200, 300, 250), 0), Some((50, 100, 100, 25)));
+        // Top-left corner of the portrait desktop (1080 wide) is the texture's top-right.
+        assert_eq!(map(rect(0, 0, 100, 200), 90), Some((860, 0, 100, 50)));
+        assert_eq!(map(rect(0, 0, 100, 200), 180), Some((910, 440, 50, 100)));
+        assert_eq!(map(rect(0, 0, 100, 200), 270), Some((0, 490, 100, 50)));
+
+        // Odd edges round outwards; off-screen parts are dropped.
+        assert_eq!(map(rect(1, 1, 2, 2), 0), Some((0, 0, 1, 1)));
+        assert_eq!(map(rect(1900, 1000, 2500, 1500), 0), Some((950, 500, 10, 40)));
+        assert_eq!(map(rect(2000, 0, 2100, 10), 0), None);
+    }
+
+    #[test]
+    fn r10_decodes_to_rounded_bgra8() {
+        let cases = [
+            (pack_r10(0, 0, 0, 0), [0, 0, 0, 0]),
+            (pack_r10(1023, 1023, 1023, 3), [255, 255, 255, 255]),
+            // BGRA order out; R in the low bits in.
+            (pack_r10(1023, 0, 0, 3), [0, 0, 255, 255]),
+            (pack_r10(512, 256, 3, 1), [1, 64, 128, 85]),
+            // 3/1023 is 0.75 in 8-bit: rounds to 1 where dropping the low bits gives 0.
+            (pack_r10(3, 2, 1, 2), [0, 0, 1, 170]),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                decode_pixel_to_bgra8(
+                    &input,
+                    DXGI_FORMAT_R10G10B10A2_UNORM,
+                    &ToneMapping::default(),
+                    DEFAULT_TARGET_NITS,
+                ),
+                expected,
+                "input {:?}",
+                input
+            );
+        }
+    }
+}
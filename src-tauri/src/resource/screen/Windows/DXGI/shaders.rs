@@ -1,6 +1,7 @@
 // Pre-compiled HLSL shaders for HDR to SDR conversion
 // These shaders implement PQ (Perceptual Quantizer) EOTF decoding for HDR10 content
-// and convert from BT.2020 color space to sRGB for display.
+// and convert from BT.2020 color space to sRGB for display. A variant with adjustable
+// tone mapping is compiled at runtime from source when the user changes those settings.
 
 /// Vertex shader bytecode (compiled from HLSL)
 /// 
@@ -165,3 +166,124 @@ pub const PIXEL_SHADER_BYTECODE: &[u8] = &[
     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
     0, 0, 0, 0, 0, 0, 0,
 ];
+
+/// Pixel shader with adjustable tone mapping, compiled at runtime when the tone-mapping
+/// settings differ from the defaults (which `PIXEL_SHADER_BYTECODE` reproduces exactly).
+///
+/// Reads the second `float4` of the constant buffer: x = exposure, y = knee,
+/// z = operator (0 linear, 1 Reinhard, 2 ACES, 3 Hable).
+pub const TONE_MAP_PIXEL_SHADER_HLSL: &str = r#"
+cbuffer Params : register(b0) {
+    float4 params;
+    float4 toneMap;
+};
+
+SamplerState GenericSampler : register(s0);
+Texture2D txInput : register(t0);
+
+struct PS_INPUT {
+    float4 pos : SV_POSITION;
+    float3 tex : TEXCOORD0;
+};
+
+static const float m1 = 0.1593017578125;
+static const float m2 = 78.84375;
+static const float c1 = 0.8359375;
+static const float c2 = 18.8515625;
+static const float c3 = 18.6875;
+
+float3 reinhard(float3 c) {
+    return c / (1.0 + c);
+}
+
+// Narkowicz's fit of the ACES filmic curve.
+float3 aces(float3 c) {
+    return saturate((c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14));
+}
+
+float3 hablePartial(float3 c) {
+    const float A = 0.15, B = 0.50, C = 0.10, D = 0.20, E = 0.02, F = 0.30;
+    return ((c * (A * c + C * B) + D * E) / (c * (A * c + B) + D * F)) - E / F;
+}
+
+float3 hable(float3 c) {
+    return hablePartial(c * 2.0) / hablePartial(float3(11.2, 11.2, 11.2));
+}
+
+// Linear up to the knee, then an exponential roll-off that approaches (never reaches) 1.
+float3 shoulder(float3 c, float knee) {
+    if (knee >= 1.0) {
+        return c;
+    }
+    float range = 1.0 - knee;
+    return min(c, knee) + range * (1.0 - exp(-max(c - knee, 0.0) / range));
+}
+
+float4 main(PS_INPUT input) : SV_Target {
+    float3 color = txInput.Sample(GenericSampler, input.tex.xy).rgb;
+    color = max(color * input.tex.z * toneMap.x, 0.0);
+
+    uint op = (uint)toneMap.z;
+    if (op == 1) {
+        color = reinhard(color);
+    } else if (op == 2) {
+        color = aces(color);
+    } else if (op == 3) {
+        color = hable(color);
+    }
+    color = shoulder(color, toneMap.y);
+
+    float3 linearColor = pow(color, 1.0 / m1);
+    linearColor = (c1 + c2 * linearColor) / (1.0 + c3 * linearColor);
+    linearColor = pow(linearColor, 1.0 / m2);
+
+    float3 srgb = select(linearColor < 0.0031308,
+                         linearColor * 12.92,
+                         1.055 * pow(linearColor, 1.0 / 2.4) - 0.055);
+
+    return float4(srgb, 0.0);
+}
+"#;
+
+/// Compile a `ps_5_0` shader with the system HLSL compiler (d3dcompiler_47, part of
+/// Windows 10 and later).
+pub fn compile_pixel_shader(source: &str) -> Result<Vec<u8>, String> {
+    use windows::core::s;
+    use windows::Win32::Graphics::Direct3D::Fxc::{D3DCompile, D3DCOMPILE_OPTIMIZATION_LEVEL3};
+    use windows::Win32::Graphics::Direct3D::{ID3DBlob, ID3DInclude};
+
+    let blob_bytes = |blob: &ID3DBlob| unsafe {
+        std::slice::from_raw_parts(blob.GetBufferPointer() as *const u8, blob.GetBufferSize())
+            .to_vec()
+    };
+
+    let mut code: Option<ID3DBlob> = None;
+    let mut errors: Option<ID3DBlob> = None;
+    let result = unsafe {
+        D3DCompile(
+            source.as_ptr() as *const _,
+            source.len(),
+            s!("tone_map.hlsl"),
+            None,
+            None::<&ID3DInclude>,
+            s!("main"),
+            s!("ps_5_0"),
+            D3DCOMPILE_OPTIMIZATION_LEVEL3,
+            0,
+            &mut code,
+            Some(&mut errors),
+        )
+    };
+    match (result, code) {
+        (Ok(()), Some(code)) => Ok(blob_bytes(&code)),
+        (result, _) => {
+            let message = errors
+                .map(|errors| String::from_utf8_lossy(&blob_bytes(&errors)).trim().to_string())
+                .filter(|message| !message.is_empty());
+            Err(message.unwrap_or_else(|| match result {
+                Err(err) => err.message(),
+                Ok(()) => "compiler returned no bytecode".to_string(),
+            }))
+        }
+    }
+}
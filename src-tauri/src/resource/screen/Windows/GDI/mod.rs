@@ -16,14 +16,16 @@ use windows::Win32::Graphics::Gdi::{
     COLORONCOLOR, DIB_RGB_COLORS, HBITMAP, HDC, HGDIOBJ, RGBQUAD, SRCCOPY,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
-    GetDesktopWindow, GetSystemMetrics, SM_CXSCREEN, SM_CXVIRTUALSCREEN, SM_CYSCREEN,
+    DrawIconEx, GetCursorInfo, GetDesktopWindow, GetSystemMetrics, CURSORINFO, CURSOR_SHOWING,
+    DI_NORMAL, HICON, SM_CXCURSOR, SM_CXSCREEN, SM_CXVIRTUALSCREEN, SM_CYCURSOR, SM_CYSCREEN,
     SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN,
 };
 
 use crate::resource::screen::{
-    compute_scaled_dimensions_by_max_pixels, ScreenCaptureError, ScreenCapturer, ScreenFrame,
+    compute_scaled_dimensions_by_max_pixels, next_frame_id, CropRect, ScreenCaptureError,
+    ScreenCapturer, ScreenFrame, ALL_DISPLAYS,
 };
-use super::{CAPTURE_FPS, CAPTURE_MAX_PIXELS};
+use super::{capture_crop_for, CAPTURE_FPS, CAPTURE_MAX_PIXELS, SHOW_CURSOR};
 
 const BYTES_PER_PIXEL: usize = 4;
 
@@ -35,6 +37,19 @@ struct CaptureRegion {
     height: i32,
 }
 
+impl CaptureRegion {
+    /// The part of this region `crop` covers, so the blit reads from an offset origin.
+    fn cropped(self, crop: CropRect) -> Self {
+        let (x, y, width, height) = crop.to_pixels(self.width as u32, self.height as u32);
+        Self {
+            origin_x: self.origin_x + x as i32,
+            origin_y: self.origin_y + y as i32,
+            width: width as i32,
+            height: height as i32,
+        }
+    }
+}
+
 struct ScreenDcGuard {
     hwnd: HWND,
     dc: HDC,
@@ -208,6 +223,8 @@ pub struct GdiCapturer {
     // Frame rate control
     last_capture_time: Option<Instant>,
     has_frame: bool,
+    frame_id: u64,
+    captured_at: Instant,
 }
 
 impl GdiCapturer {
@@ -216,12 +233,18 @@ impl GdiCapturer {
     }
 
     pub fn with_output(output_index: usize) -> Result<Self, ScreenCaptureError> {
+        Self::with_output_crop(output_index, capture_crop_for(output_index))
+    }
+
+    /// Capturer of `output_index` cropped to `crop` rather than the global setting.
+    pub fn with_output_crop(output_index: usize, crop: Option<CropRect>) -> Result<Self, ScreenCaptureError> {
         unsafe {
             let desktop_hwnd = GetDesktopWindow();
             let screen_dc_guard = ScreenDcGuard::new(desktop_hwnd)?;
             let memory_dc_guard = MemoryDcGuard::new(screen_dc_guard.handle())?;
 
             let region = detect_region(output_index);
+            let region = crop.map_or(region, |crop| region.cropped(crop));
             let max_pixels = CAPTURE_MAX_PIXELS.load(Ordering::Relaxed);
             let (target_width, target_height) = compute_scaled_dimensions_by_max_pixels(
                 region.width as u32,
@@ -280,6 +303,8 @@ impl GdiCapturer {
                 bitmap_info,
                 last_capture_time: None,
                 has_frame: false,
+                frame_id: 0,
+                captured_at: Instant::now(),
             })
         }
     }
@@ -311,6 +336,11 @@ impl GdiCapturer {
                 });
             }
 
+            // BitBlt never copies the pointer; draw it in ourselves when wanted.
+            if SHOW_CURSOR.load(Ordering::Relaxed) {
+                self.draw_cursor();
+            }
+
             let scan_lines = GetDIBits(
                 self.memory_dc,
                 self.bitmap,
@@ -329,6 +359,38 @@ impl GdiCapturer {
         }
         Ok(())
     }
+
+    /// Paint the current pointer onto the scaled bitmap. Best effort: a hidden pointer or a
+    /// failed call just leaves the frame without one.
+    unsafe fn draw_cursor(&self) {
+        let mut info = CURSORINFO {
+            cbSize: size_of::<CURSORINFO>() as u32,
+            ..Default::default()
+        };
+        if GetCursorInfo(&mut info).is_err() || info.flags.0 & CURSOR_SHOWING.0 == 0 {
+            return;
+        }
+
+        let region = self.region;
+        let scale_x = self.target_width as f32 / region.width.max(1) as f32;
+        let scale_y = self.target_height as f32 / region.height.max(1) as f32;
+        let x = ((info.ptScreenPos.x - region.origin_x) as f32 * scale_x) as i32;
+        let y = ((info.ptScreenPos.y - region.origin_y) as f32 * scale_y) as i32;
+        let width = ((GetSystemMetrics(SM_CXCURSOR) as f32 * scale_x) as i32).max(1);
+        let height = ((GetSystemMetrics(SM_CYCURSOR) as f32 * scale_y) as i32).max(1);
+
+        let _ = DrawIconEx(
+            self.memory_dc,
+            x,
+            y,
+            HICON(info.hCursor.0),
+            width,
+            height,
+            0,
+            None,
+            DI_NORMAL,
+        );
+    }
 }
 
 impl ScreenCapturer for GdiCapturer {
@@ -346,6 +408,8 @@ impl ScreenCapturer for GdiCapturer {
             self.capture_internal()?;
             self.last_capture_time = Some(now);
             self.has_frame = true;
+            self.frame_id = next_frame_id();
+            self.captured_at = now;
         }
 
         Ok(ScreenFrame {
@@ -354,6 +418,8 @@ impl ScreenCapturer for GdiCapturer {
             stride: self.stride,
             pixels: &self.buffer,
             dirty_regions: &[],
+            frame_id: self.frame_id,
+            captured_at: self.captured_at,
         })
     }
 
@@ -407,6 +473,10 @@ fn detect_virtual_region() -> CaptureRegion {
 }
 
 fn detect_region(output_index: usize) -> CaptureRegion {
+    // One blit of the virtual screen spans every display; areas no monitor covers read black.
+    if output_index == ALL_DISPLAYS {
+        return detect_virtual_region();
+    }
     if let Ok(factory) = unsafe { CreateDXGIFactory1::<IDXGIFactory1>() } {
         let mut current_index = 0usize;
         for adapter_index in 0.. {
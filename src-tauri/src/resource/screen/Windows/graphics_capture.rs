@@ -28,7 +28,7 @@ use windows::Win32::Graphics::Direct3D::{
     D3D_FEATURE_LEVEL_9_3,
 };
 use windows::Win32::Graphics::Direct3D11::{
-    D3D11CreateDevice, D3D11_CPU_ACCESS_READ, D3D11_CREATE_DEVICE_BGRA_SUPPORT,
+    D3D11CreateDevice, D3D11_BOX, D3D11_CPU_ACCESS_READ, D3D11_CREATE_DEVICE_BGRA_SUPPORT,
     D3D11_MAP_READ, D3D11_MAPPED_SUBRESOURCE, D3D11_SDK_VERSION, D3D11_TEXTURE2D_DESC,
     D3D11_USAGE_STAGING, ID3D11Device, ID3D11DeviceContext, ID3D11Texture2D,
 };
@@ -39,10 +39,10 @@ use windows::Win32::System::WinRT::Direct3D11::CreateDirect3D11DeviceFromDXGIDev
 use windows::Win32::System::WinRT::Graphics::Capture::IGraphicsCaptureItemInterop;
 
 use crate::resource::screen::{
-    compute_scaled_dimensions_by_max_pixels, DirtyRegion, ScreenCaptureError, ScreenCapturer,
-    ScreenFrame,
+    compute_scaled_dimensions_by_max_pixels, next_frame_id, CropRect, DirtyRegion,
+    ScreenCaptureError, ScreenCapturer, ScreenFrame,
 };
-use super::{BYTES_PER_PIXEL, CAPTURE_FPS, CAPTURE_MAX_PIXELS};
+use super::{capture_crop_for, BYTES_PER_PIXEL, CAPTURE_FPS, CAPTURE_MAX_PIXELS, SHOW_CURSOR};
 
 /// WinRT Graphics Capture backend for fullscreen monitor capture.
 ///
@@ -59,8 +59,12 @@ pub struct GraphicsCapturer {
     stride: usize,
     dirty_regions: Vec<DirtyRegion>,
     output_index: usize,
+    /// Part of each frame copied out; `None` copies all of it.
+    crop: Option<CropRect>,
     last_capture_time: Option<Instant>,
     has_frame: bool,
+    frame_id: u64,
+    captured_at: Instant,
     // Reusable staging texture for CPU readback
     staging_texture: Option<ID3D11Texture2D>,
     staging_width: u32,
@@ -74,6 +78,14 @@ unsafe impl Send for GraphicsCapturer {}
 impl GraphicsCapturer {
     /// Creates a new Graphics Capture session for the specified monitor output.
     pub fn with_output(output_index: usize) -> Result<Self, ScreenCaptureError> {
+        Self::with_output_crop(output_index, capture_crop_for(output_index))
+    }
+
+    /// Capturer of `output_index` cropped to `crop` rather than the global setting.
+    pub fn with_output_crop(
+        output_index: usize,
+        crop: Option<CropRect>,
+    ) -> Result<Self, ScreenCaptureError> {
         // Check if Graphics Capture API is supported
         if !Self::is_supported() {
             return Err(ScreenCaptureError::Unsupported(
@@ -123,8 +135,11 @@ impl GraphicsCapturer {
             stride,
             dirty_regions: Vec::new(),
             output_index,
+            crop,
             last_capture_time: None,
             has_frame: false,
+            frame_id: 0,
+            captured_at: Instant::now(),
             staging_texture: None,
             staging_width: 0,
             staging_height: 0,
@@ -155,7 +170,8 @@ impl GraphicsCapturer {
     fn configure_session(session: &GraphicsCaptureSession) {
         // Try to disable the capture border (available on Windows 10 2004+)
         let _ = session.SetIsBorderRequired(false);
-        // Cursor capture is enabled by default
+        // Cursor capture is on unless turned off; the setter needs Windows 10 2004+ too.
+        let _ = session.SetIsCursorCaptureEnabled(SHOW_CURSOR.load(Ordering::Relaxed));
     }
 
     /// Ensure staging texture is properly sized.
@@ -218,8 +234,12 @@ impl GraphicsCapturer {
         let mut desc = D3D11_TEXTURE2D_DESC::default();
         unsafe { texture.GetDesc(&mut desc) };
 
-        let width = desc.Width;
-        let height = desc.Height;
+        // Frames always show the whole monitor; a crop is cut out while copying to staging.
+        let (crop_x, crop_y, width, height) = self
+            .crop
+            .map_or((0, 0, desc.Width, desc.Height), |crop| {
+                crop.to_pixels(desc.Width, desc.Height)
+            });
 
         // Check if frame pool needs recreation due to size change
         let content_size = frame
@@ -244,7 +264,20 @@ impl GraphicsCapturer {
 
         // Copy texture to staging
         unsafe {
-            self.context.CopyResource(staging, &texture);
+            if self.crop.is_some() {
+                let src_box = D3D11_BOX {
+                    left: crop_x,
+                    top: crop_y,
+                    front: 0,
+                    right: crop_x + width,
+                    bottom: crop_y + height,
+                    back: 1,
+                };
+                self.context
+                    .CopySubresourceRegion(staging, 0, 0, 0, 0, &texture, 0, Some(&src_box));
+            } else {
+                self.context.CopyResource(staging, &texture);
+            }
         }
 
         // Map staging texture for CPU read
@@ -321,6 +354,8 @@ impl GraphicsCapturer {
         // it requires additional API checks.
 
         self.has_frame = true;
+        self.frame_id = next_frame_id();
+        self.captured_at = Instant::now();
         Ok(true)
     }
 }
@@ -356,7 +391,12 @@ impl ScreenCapturer for GraphicsCapturer {
 
             if got_frame {
                 self.last_capture_time = Some(now);
-            } else if !self.has_frame {
+            } else if self.has_frame {
+                // Frames only arrive when the content changes; an empty poll of the running
+                // session means the desktop still shows the last one.
+                self.last_capture_time = Some(now);
+                self.captured_at = now;
+            } else {
                 // First capture: need to wait briefly for initial frame
                 let deadline = Instant::now() + Duration::from_millis(50);
                 while Instant::now() < deadline && !self.has_frame {
@@ -369,7 +409,7 @@ impl ScreenCapturer for GraphicsCapturer {
             }
 
             if !self.has_frame {
-                return Err(ScreenCaptureError::InvalidState("No frame available yet"));
+                return Err(ScreenCaptureError::NoFrame);
             }
         }
 
@@ -382,6 +422,8 @@ impl ScreenCapturer for GraphicsCapturer {
             stride: self.stride,
             pixels: &self.buffer,
             dirty_regions: &self.dirty_regions,
+            frame_id: self.frame_id,
+            captured_at: self.captured_at,
         })
     }
 
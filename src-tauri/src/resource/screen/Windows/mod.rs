@@ -11,24 +11,31 @@ pub mod gdi;
 #[path = "graphics_capture.rs"]
 pub mod graphics_capture;
 
-use std::collections::HashMap;
 use std::sync::{
-    atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering},
+    atomic::{AtomicBool, AtomicU32, AtomicU8, Ordering},
     Mutex, OnceLock, RwLock,
 };
 
 use serde::{Deserialize, Serialize};
 
-use windows::core::Interface;
+use windows::core::{Interface, PCWSTR};
 use windows::Win32::Graphics::Dxgi::{
-    Common::DXGI_COLOR_SPACE_TYPE,
+    Common::{
+        DXGI_COLOR_SPACE_TYPE, DXGI_MODE_ROTATION, DXGI_MODE_ROTATION_ROTATE180,
+        DXGI_MODE_ROTATION_ROTATE270, DXGI_MODE_ROTATION_ROTATE90,
+    },
     CreateDXGIFactory1, IDXGIFactory1, IDXGIOutput6,
     DXGI_ERROR_NOT_FOUND, DXGI_OUTPUT_DESC,
 };
+use windows::Win32::Graphics::Gdi::{EnumDisplaySettingsW, DEVMODEW, ENUM_CURRENT_SETTINGS, HMONITOR};
+use windows::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
 
+use super::registry::{CaptureLease, CaptureRegistry};
+use super::spanning::{DisplayRect, SpanningCapturer, ALL_DISPLAYS};
 use super::{
-    normalize_capture_max_pixels, DEFAULT_CAPTURE_MAX_PIXELS, ScreenCaptureError,
-    ScreenCapturer, ScreenFrame,
+    normalize_capture_fps, normalize_capture_max_pixels, normalize_target_nits, CaptureSettings,
+    CropRect, DEFAULT_CAPTURE_MAX_PIXELS, DEFAULT_TARGET_NITS, ScreenCaptureError, ScreenCapturer,
+    ScreenFrame, ToneMapOperator, ToneMapping,
 };
 use dxgi::DxgiCapturer;
 use gdi::GdiCapturer;
@@ -44,7 +51,6 @@ pub(crate) const HDR_COLOR_SPACE: DXGI_COLOR_SPACE_TYPE = DXGI_COLOR_SPACE_TYPE(
 pub(crate) const BYTES_PER_PIXEL: usize = 4;
 pub(crate) const DEFAULT_TIMEOUT_MS: u32 = 16;
 pub(crate) const DEFAULT_CAPTURE_FPS: u8 = 30;
-pub(crate) const DEFAULT_TARGET_NITS: u32 = 200;
 
 // ============================================================================
 // Global Settings
@@ -53,13 +59,30 @@ pub(crate) const DEFAULT_TARGET_NITS: u32 = 200;
 /// Max pixel budget for capture resolution. 0 means "no limit".
 pub(crate) static CAPTURE_MAX_PIXELS: AtomicU32 = AtomicU32::new(DEFAULT_CAPTURE_MAX_PIXELS);
 pub(crate) static CAPTURE_FPS: AtomicU8 = AtomicU8::new(DEFAULT_CAPTURE_FPS);
+static SAMPLE_RATIO: AtomicU8 = AtomicU8::new(100);
 pub(crate) static HARDWARE_ACCELERATION: AtomicBool = AtomicBool::new(true);
+/// Request 10-bit surfaces on SDR displays (HDR displays always get their wide formats).
+pub(crate) static HIGH_BIT_DEPTH: AtomicBool = AtomicBool::new(false);
+/// Draw the mouse pointer into captured frames. Off by default: on ambient lights it only
+/// shows up as a stray spot of color.
+pub(crate) static SHOW_CURSOR: AtomicBool = AtomicBool::new(false);
+/// HDR to SDR curve of the DXGI shader path.
+pub(crate) static TONE_MAPPING: RwLock<ToneMapping> = RwLock::new(ToneMapping {
+    exposure: 1.0,
+    knee: 1.0,
+    operator: ToneMapOperator::Reinhard,
+});
+/// HDR brightness the DXGI shader maps to full output; capturers pick changes up on their
+/// next frame.
+pub(crate) static CAPTURE_TARGET_NITS: AtomicU32 = AtomicU32::new(DEFAULT_TARGET_NITS);
+
+/// Part of each display to capture; `None` captures all of it.
+static CAPTURE_CROP: RwLock<Option<CropRect>> = RwLock::new(None);
 
 /// Screen capture method selection
 static CAPTURE_METHOD: RwLock<CaptureMethod> = RwLock::new(CaptureMethod::Dxgi);
-/// Generation counter for capture state; bump when method changes to help
-/// existing subscriptions re-sync without manual toggles.
-static CAPTURE_GEN: AtomicU64 = AtomicU64::new(0);
+/// Set by `pause_capture`; only written with the manager lock held.
+static CAPTURE_PAUSED: AtomicBool = AtomicBool::new(false);
 
 // ============================================================================
 // Public Types
@@ -107,7 +130,27 @@ pub struct DisplayInfo {
     pub name: String,
     pub width: u32,
     pub height: u32,
+    /// Top-left corner on the virtual desktop.
+    pub x: i32,
+    pub y: i32,
     pub is_hdr: bool,
+    /// Current refresh rate in Hz.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_rate_hz: Option<f32>,
+    /// Bits per color channel the output is driven at.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bits_per_color: Option<u32>,
+    /// Clockwise rotation of the desktop on this display, in degrees (0, 90, 180 or 270).
+    pub rotation: u32,
+    /// Desktop scaling, 1.0 being 96 DPI.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale_factor: Option<f32>,
+    /// GPU the display is connected to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adapter_name: Option<String>,
+    /// Peak luminance reported by the display (EDID), in nits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_luminance_nits: Option<f32>,
 }
 
 // ============================================================================
@@ -118,14 +161,12 @@ pub fn set_capture_max_pixels(max_pixels: u32) {
     let normalized = normalize_capture_max_pixels(max_pixels);
     let previous = CAPTURE_MAX_PIXELS.swap(normalized, Ordering::Relaxed);
 
-    // Only rebuild capture pipelines when the effective value changes.
+    // Only rebuild capture pipelines when the effective value changes. Existing
+    // subscriptions re-acquire a fresh duplicator with the new scale on their next capture.
     if previous != normalized {
         if let Ok(mut manager) = global_manager().lock() {
             manager.clear();
         }
-        // Bump generation so existing subscriptions will re-acquire a fresh duplicator
-        // with the new resolution scale applied.
-        CAPTURE_GEN.fetch_add(1, Ordering::Relaxed);
     }
 }
 
@@ -134,7 +175,7 @@ pub fn get_capture_max_pixels() -> u32 {
 }
 
 pub fn set_capture_fps(fps: u8) {
-    CAPTURE_FPS.store(fps.clamp(1, 60), Ordering::Relaxed);
+    CAPTURE_FPS.store(normalize_capture_fps(fps), Ordering::Relaxed);
 }
 
 pub fn get_capture_fps() -> u8 {
@@ -149,28 +190,165 @@ pub fn get_hardware_acceleration() -> bool {
     HARDWARE_ACCELERATION.load(Ordering::Relaxed)
 }
 
+pub fn set_high_bit_depth(enabled: bool) {
+    // The surface format is negotiated when a duplicator is created, so rebuild them.
+    if HIGH_BIT_DEPTH.swap(enabled, Ordering::Relaxed) != enabled {
+        if let Ok(mut manager) = global_manager().lock() {
+            manager.clear();
+        }
+    }
+}
+
+pub fn get_high_bit_depth() -> bool {
+    HIGH_BIT_DEPTH.load(Ordering::Relaxed)
+}
+
+pub fn set_tone_mapping(tone_mapping: ToneMapping) {
+    let tone_mapping = tone_mapping.normalized();
+    let changed = match TONE_MAPPING.write() {
+        Ok(mut guard) => std::mem::replace(&mut *guard, tone_mapping) != tone_mapping,
+        Err(_) => false,
+    };
+    // The shader and its constants are set up with the duplicator, so rebuild them.
+    if changed {
+        if let Ok(mut manager) = global_manager().lock() {
+            manager.clear();
+        }
+    }
+}
+
+pub fn get_tone_mapping() -> ToneMapping {
+    TONE_MAPPING.read().map(|guard| *guard).unwrap_or_default()
+}
+
+pub fn set_capture_target_nits(nits: u32) {
+    CAPTURE_TARGET_NITS.store(normalize_target_nits(nits), Ordering::Relaxed);
+}
+
+pub fn get_capture_target_nits() -> u32 {
+    CAPTURE_TARGET_NITS.load(Ordering::Relaxed)
+}
+
+/// Graphics Capture and GDI honor this; Desktop Duplication delivers the pointer separately
+/// from the desktop image and it is never drawn in.
+pub fn set_show_cursor(enabled: bool) {
+    // Cursor capture is a session/capturer option, so rebuild them.
+    if SHOW_CURSOR.swap(enabled, Ordering::Relaxed) != enabled {
+        if let Ok(mut manager) = global_manager().lock() {
+            manager.clear();
+        }
+    }
+}
+
+pub fn get_show_cursor() -> bool {
+    SHOW_CURSOR.load(Ordering::Relaxed)
+}
+
 pub fn set_capture_method(method: CaptureMethod) {
     if let Ok(mut guard) = CAPTURE_METHOD.write() {
         *guard = method;
     }
-    // Clear existing captures when method changes
+    // Clear existing captures when method changes; subscriptions re-sync on next capture.
     if let Ok(mut manager) = global_manager().lock() {
         manager.clear();
     }
-    // Bump generation so existing subscriptions can re-sync.
-    CAPTURE_GEN.fetch_add(1, Ordering::Relaxed);
 }
 
 pub fn get_capture_method() -> CaptureMethod {
     CAPTURE_METHOD.read().map(|g| *g).unwrap_or_default()
 }
 
-#[allow(dead_code)]
-pub fn set_sample_ratio(_percent: u8) {}
+/// Apply the pixel budget, frame rate and method together, rebuilding capturers at most
+/// once instead of once per setting.
+pub fn configure_capture(max_pixels: u32, fps: u8, method: CaptureMethod) -> CaptureSettings {
+    let max_pixels = normalize_capture_max_pixels(max_pixels);
+    let fps = normalize_capture_fps(fps);
+    CAPTURE_FPS.store(fps, Ordering::Relaxed);
+
+    let scale_changed = CAPTURE_MAX_PIXELS.swap(max_pixels, Ordering::Relaxed) != max_pixels;
+    let method_changed = CAPTURE_METHOD
+        .write()
+        .map(|mut guard| std::mem::replace(&mut *guard, method) != method)
+        .unwrap_or(false);
+    let rebuilt = scale_changed || method_changed;
+    if rebuilt {
+        if let Ok(mut manager) = global_manager().lock() {
+            manager.clear();
+        }
+    }
+
+    CaptureSettings {
+        max_pixels,
+        fps,
+        method: get_capture_method(),
+        rebuilt,
+    }
+}
+
+/// Percentage of each zone's pixels that screen effects average (1–100).
+pub fn set_sample_ratio(percent: u8) {
+    SAMPLE_RATIO.store(percent.clamp(1, 100), Ordering::Relaxed);
+}
 
-#[allow(dead_code)]
 pub fn get_sample_ratio() -> u8 {
-    100
+    SAMPLE_RATIO.load(Ordering::Relaxed)
+}
+
+/// Capture only part of each display, or all of it with `None`. Spanning all displays
+/// ignores the crop.
+pub fn set_capture_crop(crop: Option<CropRect>) {
+    let crop = crop.and_then(CropRect::normalized);
+    let changed = match CAPTURE_CROP.write() {
+        Ok(mut guard) => std::mem::replace(&mut *guard, crop) != crop,
+        Err(_) => false,
+    };
+    // Textures, viewports and bitmaps are all sized from the crop, so rebuild them.
+    if changed {
+        if let Ok(mut manager) = global_manager().lock() {
+            manager.clear();
+        }
+    }
+}
+
+pub fn get_capture_crop() -> Option<CropRect> {
+    CAPTURE_CROP.read().map(|guard| *guard).unwrap_or_default()
+}
+
+/// The crop a capturer of `output_index` applies.
+pub(crate) fn capture_crop_for(output_index: usize) -> Option<CropRect> {
+    if output_index == ALL_DISPLAYS {
+        None
+    } else {
+        get_capture_crop()
+    }
+}
+
+/// Free every duplicator (and its D3D device) while keeping subscriptions alive; they
+/// report no frame until `resume_capture`.
+pub fn pause_capture() {
+    if let Ok(mut manager) = global_manager().lock() {
+        CAPTURE_PAUSED.store(true, Ordering::Relaxed);
+        manager.clear();
+    }
+}
+
+/// Subscriptions re-acquire their duplicator on their next capture.
+pub fn resume_capture() {
+    let _manager = global_manager().lock();
+    CAPTURE_PAUSED.store(false, Ordering::Relaxed);
+}
+
+pub fn is_capture_paused() -> bool {
+    CAPTURE_PAUSED.load(Ordering::Relaxed)
+}
+
+/// Screen capture needs no permission on this platform.
+pub fn has_screen_permission() -> bool {
+    true
+}
+
+pub fn open_screen_permission_settings() -> Result<(), String> {
+    Ok(())
 }
 
 // ============================================================================
@@ -190,6 +368,11 @@ pub fn list_displays() -> Result<Vec<DisplayInfo>, ScreenCaptureError> {
                 Err(err) if err.code() == DXGI_ERROR_NOT_FOUND => break,
                 Err(err) => return Err(os_error("EnumAdapters1", err)),
             };
+            let adapter_name = adapter
+                .GetDesc1()
+                .ok()
+                .map(|desc| wide_to_string(&desc.Description).trim().to_string())
+                .filter(|name| !name.is_empty());
 
             for output_index in 0.. {
                 let output = match adapter.EnumOutputs(output_index) {
@@ -205,16 +388,14 @@ pub fn list_displays() -> Result<Vec<DisplayInfo>, ScreenCaptureError> {
                     continue;
                 }
 
-                // Check HDR support via IDXGIOutput6
-                let is_hdr = if let Ok(output6) = output.cast::<IDXGIOutput6>() {
-                    if let Ok(desc1) = output6.GetDesc1() {
-                        desc1.ColorSpace == HDR_COLOR_SPACE
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                };
+                // HDR state, bit depth and luminance need IDXGIOutput6 (Windows 10 1703+).
+                let desc1 = output
+                    .cast::<IDXGIOutput6>()
+                    .ok()
+                    .and_then(|output6| output6.GetDesc1().ok());
+                let is_hdr = desc1
+                    .as_ref()
+                    .is_some_and(|desc1| desc1.ColorSpace == HDR_COLOR_SPACE);
 
                 let (width, height) = output_dimensions(&desc);
                 let raw_name = wide_to_string(&desc.DeviceName);
@@ -230,7 +411,21 @@ pub fn list_displays() -> Result<Vec<DisplayInfo>, ScreenCaptureError> {
                     name,
                     width,
                     height,
+                    x: desc.DesktopCoordinates.left,
+                    y: desc.DesktopCoordinates.top,
                     is_hdr,
+                    refresh_rate_hz: current_refresh_rate(&desc.DeviceName),
+                    bits_per_color: desc1
+                        .as_ref()
+                        .map(|desc1| desc1.BitsPerColor)
+                        .filter(|&bits| bits > 0),
+                    rotation: rotation_degrees(desc.Rotation),
+                    scale_factor: monitor_scale_factor(desc.Monitor),
+                    adapter_name: adapter_name.clone(),
+                    max_luminance_nits: desc1
+                        .as_ref()
+                        .map(|desc1| desc1.MaxLuminance)
+                        .filter(|&nits| nits > 0.0),
                 });
 
                 current_index += 1;
@@ -250,6 +445,9 @@ pub enum DesktopDuplicator {
     Dxgi(DxgiCapturer),
     Gdi(GdiCapturer),
     Graphics(GraphicsCapturer),
+    /// [`ALL_DISPLAYS`] with DXGI or Graphics Capture, which only see one output per
+    /// capturer: one per display, composited. GDI blits the virtual screen directly.
+    Spanning(Box<SpanningCapturer<DesktopDuplicator>>),
 }
 
 impl DesktopDuplicator {
@@ -266,6 +464,9 @@ impl DesktopDuplicator {
         method: CaptureMethod,
         output_index: usize,
     ) -> Result<Self, ScreenCaptureError> {
+        if output_index == ALL_DISPLAYS && method != CaptureMethod::Gdi {
+            return Self::spanning(method);
+        }
         match method {
             CaptureMethod::Dxgi => Ok(Self::Dxgi(DxgiCapturer::with_output(output_index)?)),
             CaptureMethod::Gdi => Ok(Self::Gdi(GdiCapturer::with_output(output_index)?)),
@@ -273,14 +474,46 @@ impl DesktopDuplicator {
         }
     }
 
+    fn spanning(method: CaptureMethod) -> Result<Self, ScreenCaptureError> {
+        let displays = list_displays()?
+            .into_iter()
+            .map(|display| {
+                let rect = DisplayRect {
+                    x: display.x,
+                    y: display.y,
+                    width: display.width,
+                    height: display.height,
+                };
+                Self::display_uncropped(method, display.index).map(|capturer| (rect, capturer))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let max_pixels = CAPTURE_MAX_PIXELS.load(Ordering::Relaxed);
+        Ok(Self::Spanning(Box::new(SpanningCapturer::new(displays, max_pixels)?)))
+    }
+
+    /// One display of a spanning capture, which the crop doesn't apply to.
+    fn display_uncropped(method: CaptureMethod, output_index: usize) -> Result<Self, ScreenCaptureError> {
+        match method {
+            CaptureMethod::Dxgi => Ok(Self::Dxgi(DxgiCapturer::with_output_crop(output_index, None)?)),
+            CaptureMethod::Gdi => Ok(Self::Gdi(GdiCapturer::with_output_crop(output_index, None)?)),
+            CaptureMethod::Graphics => Ok(Self::Graphics(GraphicsCapturer::with_output_crop(output_index, None)?)),
+        }
+    }
+
     pub fn set_output_index(&mut self, output_index: usize) -> Result<(), ScreenCaptureError> {
         match self {
-            Self::Dxgi(capturer) => capturer.set_output_index(output_index),
+            Self::Dxgi(capturer) if output_index != ALL_DISPLAYS => {
+                capturer.set_output_index(output_index)
+            }
             Self::Gdi(capturer) => GdiCapturer::with_output(output_index).map(|c| *capturer = c),
-            Self::Graphics(capturer) => {
+            Self::Graphics(capturer) if output_index != ALL_DISPLAYS => {
                 *capturer = GraphicsCapturer::with_output(output_index)?;
                 Ok(())
             }
+            _ => {
+                *self = Self::with_output(output_index)?;
+                Ok(())
+            }
         }
     }
 
@@ -289,6 +522,7 @@ impl DesktopDuplicator {
             Self::Dxgi(capturer) => capturer.output_index(),
             Self::Gdi(capturer) => capturer.output_index(),
             Self::Graphics(capturer) => capturer.output_index(),
+            Self::Spanning(_) => ALL_DISPLAYS,
         }
     }
 }
@@ -299,6 +533,7 @@ impl ScreenCapturer for DesktopDuplicator {
             Self::Dxgi(capturer) => capturer.capture(),
             Self::Gdi(capturer) => capturer.capture(),
             Self::Graphics(capturer) => capturer.capture(),
+            Self::Spanning(capturer) => capturer.capture(),
         }
     }
 
@@ -307,6 +542,7 @@ impl ScreenCapturer for DesktopDuplicator {
             Self::Dxgi(capturer) => capturer.size(),
             Self::Gdi(capturer) => capturer.size(),
             Self::Graphics(capturer) => capturer.size(),
+            Self::Spanning(capturer) => capturer.size(),
         }
     }
 }
@@ -316,14 +552,7 @@ impl ScreenCapturer for DesktopDuplicator {
 // ============================================================================
 
 /// Shares one `DesktopDuplicator` per display and frees it when unused.
-struct ScreenCaptureManager {
-    outputs: HashMap<CaptureKey, ManagedOutput>,
-}
-
-struct ManagedOutput {
-    duplicator: DesktopDuplicator,
-    ref_count: usize,
-}
+type ScreenCaptureManager = CaptureRegistry<CaptureKey, DesktopDuplicator>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct CaptureKey {
@@ -331,106 +560,56 @@ struct CaptureKey {
     output: usize,
 }
 
-impl ScreenCaptureManager {
-    fn new() -> Self {
-        Self {
-            outputs: HashMap::new(),
-        }
+fn acquire(
+    manager: &mut ScreenCaptureManager,
+    output_index: usize,
+) -> Result<CaptureLease<CaptureKey>, ScreenCaptureError> {
+    let method = get_capture_method();
+    if let Some(lease) = manager.retain(CaptureKey {
+        method,
+        output: output_index,
+    }) {
+        return Ok(lease);
     }
 
-    fn acquire(
-        &mut self,
-        method: CaptureMethod,
-        output_index: usize,
-    ) -> Result<(), ScreenCaptureError> {
-        let key = CaptureKey {
-            method,
-            output: output_index,
-        };
-
-        if let Some(entry) = self.outputs.get_mut(&key) {
-            entry.ref_count += 1;
-            return Ok(());
-        }
-
-        // Build duplicator; if the requested backend is unavailable, fall back and
-        // update global method so the UI/config can observe the effective backend.
-        let (effective_method, duplicator) = match method {
-            CaptureMethod::Dxgi => match DesktopDuplicator::with_method_output(CaptureMethod::Dxgi, output_index) {
-                Ok(dup) => (CaptureMethod::Dxgi, dup),
-                Err(_dxgi_err) => {
-                    // Prefer the modern WinRT Graphics Capture API if available.
-                    match DesktopDuplicator::with_method_output(CaptureMethod::Graphics, output_index) {
-                        Ok(dup) => (CaptureMethod::Graphics, dup),
-                        Err(_graphics_err) => {
-                            // Both DXGI and Graphics Capture failed; fall back to GDI.
-                            (CaptureMethod::Gdi, DesktopDuplicator::with_method_output(CaptureMethod::Gdi, output_index)?)
-                        }
+    // Build duplicator; if the requested backend is unavailable, fall back and
+    // update global method so the UI/config can observe the effective backend.
+    let (effective_method, duplicator) = match method {
+        CaptureMethod::Dxgi => match DesktopDuplicator::with_method_output(CaptureMethod::Dxgi, output_index) {
+            Ok(dup) => (CaptureMethod::Dxgi, dup),
+            Err(dxgi_err) => {
+                log::warn!(output_index, code = dxgi_err.code(), err:display = dxgi_err; "[capture] DXGI unavailable, trying Graphics Capture");
+                // Prefer the modern WinRT Graphics Capture API if available.
+                match DesktopDuplicator::with_method_output(CaptureMethod::Graphics, output_index) {
+                    Ok(dup) => (CaptureMethod::Graphics, dup),
+                    Err(graphics_err) => {
+                        log::warn!(output_index, code = graphics_err.code(), err:display = graphics_err; "[capture] Graphics Capture unavailable, falling back to GDI");
+                        // Both DXGI and Graphics Capture failed; fall back to GDI.
+                        (CaptureMethod::Gdi, DesktopDuplicator::with_method_output(CaptureMethod::Gdi, output_index)?)
                     }
                 }
-            },
-            CaptureMethod::Graphics => (CaptureMethod::Graphics, DesktopDuplicator::with_method_output(CaptureMethod::Graphics, output_index)?),
-            CaptureMethod::Gdi => (CaptureMethod::Gdi, DesktopDuplicator::with_method_output(CaptureMethod::Gdi, output_index)?),
-        };
-
-        // If we had to fall back, rebind globally and clear existing outputs so
-        // subsequent subscriptions align with the effective backend.
-        if effective_method != method {
-            self.clear();
-            if let Ok(mut guard) = CAPTURE_METHOD.write() {
-                *guard = effective_method;
-            }
-            CAPTURE_GEN.fetch_add(1, Ordering::Relaxed);
-        }
-
-        self.outputs.insert(
-            CaptureKey {
-                method: effective_method,
-                output: output_index,
-            },
-            ManagedOutput {
-                duplicator,
-                ref_count: 1,
-            },
-        );
-        Ok(())
-    }
-
-    fn release(&mut self, key: CaptureKey) {
-        if let Some(entry) = self.outputs.get_mut(&key) {
-            if entry.ref_count > 1 {
-                entry.ref_count -= 1;
-                return;
-            }
-        }
-        self.outputs.remove(&key);
-    }
-
-    fn capture_with<F>(&mut self, key: CaptureKey, f: F) -> Result<bool, ScreenCaptureError>
-    where
-        F: FnOnce(&ScreenFrame<'_>),
-    {
-        let Some(entry) = self.outputs.get_mut(&key) else {
-            return Ok(false);
-        };
-
-        match entry.duplicator.capture() {
-            Ok(frame) => {
-                f(&frame);
-                Ok(true)
-            }
-            Err(err) => {
-                if matches!(err, ScreenCaptureError::InvalidState(_)) {
-                    self.outputs.remove(&key);
-                }
-                Err(err)
             }
+        },
+        CaptureMethod::Graphics => (CaptureMethod::Graphics, DesktopDuplicator::with_method_output(CaptureMethod::Graphics, output_index)?),
+        CaptureMethod::Gdi => (CaptureMethod::Gdi, DesktopDuplicator::with_method_output(CaptureMethod::Gdi, output_index)?),
+    };
+
+    // If we had to fall back, rebind globally and clear existing outputs so
+    // subsequent subscriptions align with the effective backend.
+    if effective_method != method {
+        manager.clear();
+        if let Ok(mut guard) = CAPTURE_METHOD.write() {
+            *guard = effective_method;
         }
     }
 
-    fn clear(&mut self) {
-        self.outputs.clear();
-    }
+    Ok(manager.insert(
+        CaptureKey {
+            method: effective_method,
+            output: output_index,
+        },
+        duplicator,
+    ))
 }
 
 static SCREEN_CAPTURE_MANAGER: OnceLock<Mutex<ScreenCaptureManager>> = OnceLock::new();
@@ -447,25 +626,24 @@ fn global_manager() -> &'static Mutex<ScreenCaptureManager> {
 #[derive(Debug)]
 pub struct ScreenSubscription {
     display_index: usize,
-    method: CaptureMethod,
-    generation: u64,
+    /// `None` while paused, or once the shared duplicator was rebuilt; re-acquired lazily.
+    lease: Option<CaptureLease<CaptureKey>>,
+    paused: bool,
 }
 
 impl ScreenSubscription {
     pub fn new(display_index: usize) -> Result<Self, ScreenCaptureError> {
         let manager = global_manager();
         let mut guard = manager.lock().unwrap();
-        let requested = get_capture_method();
-        guard.acquire(requested, display_index)?;
-
-        // `acquire` may have changed the global method (fallback). Re-read after acquire so
-        // the subscription key matches what is actually stored in the manager.
-        let method = get_capture_method();
-        let generation = CAPTURE_GEN.load(Ordering::Relaxed);
+        let lease = if is_capture_paused() {
+            None
+        } else {
+            Some(acquire(&mut guard, display_index)?)
+        };
         Ok(Self {
             display_index,
-            method,
-            generation,
+            lease,
+            paused: false,
         })
     }
 
@@ -473,42 +651,59 @@ impl ScreenSubscription {
         self.display_index
     }
 
+    /// Give up this subscription's share of the duplicator (freeing it if this was the
+    /// last one) without dropping the subscription.
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.release();
+    }
+
+    /// The duplicator is re-acquired on the next capture.
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// `Ok(false)` while paused (this subscription or globally) or without a duplicator.
     pub fn capture_with<F>(&mut self, f: F) -> Result<bool, ScreenCaptureError>
     where
         F: FnOnce(&ScreenFrame<'_>),
     {
+        if self.paused {
+            return Ok(false);
+        }
+
         let manager = global_manager();
         let mut guard = manager.lock().unwrap();
-
-        // Refresh backend binding if generation or method changed.
-        let current_generation = CAPTURE_GEN.load(Ordering::Relaxed);
-        let current_method = get_capture_method();
-        if current_generation != self.generation || current_method != self.method {
-            guard.acquire(current_method, self.display_index)?;
-            // `acquire` may have fallen back and updated globals; re-sync after it.
-            self.generation = CAPTURE_GEN.load(Ordering::Relaxed);
-            self.method = get_capture_method();
+        if is_capture_paused() {
+            return Ok(false);
         }
 
-        let key = CaptureKey {
-            method: self.method,
-            output: self.display_index,
+        // Method/settings changes, backend fallback and lost devices all rebuild the
+        // entry, leaving this lease stale; re-acquire under the current method.
+        let lease = match self.lease.filter(|lease| guard.holds(lease)) {
+            Some(lease) => lease,
+            None => *self.lease.insert(acquire(&mut guard, self.display_index)?),
         };
 
-        guard.capture_with(key, f)
+        guard.capture_with(&lease, f)
+    }
+
+    fn release(&mut self) {
+        if let Some(lease) = self.lease.take() {
+            if let Ok(mut guard) = global_manager().lock() {
+                guard.release(lease);
+            }
+        }
     }
 }
 
 impl Drop for ScreenSubscription {
     fn drop(&mut self) {
-        let manager = global_manager();
-        if let Ok(mut guard) = manager.lock() {
-            let key = CaptureKey {
-                method: self.method,
-                output: self.display_index,
-            };
-            guard.release(key);
-        }
+        self.release();
     }
 }
 
@@ -522,6 +717,34 @@ fn output_dimensions(desc: &DXGI_OUTPUT_DESC) -> (u32, u32) {
     (width, height)
 }
 
+fn rotation_degrees(rotation: DXGI_MODE_ROTATION) -> u32 {
+    match rotation {
+        DXGI_MODE_ROTATION_ROTATE90 => 90,
+        DXGI_MODE_ROTATION_ROTATE180 => 180,
+        DXGI_MODE_ROTATION_ROTATE270 => 270,
+        _ => 0,
+    }
+}
+
+/// DXGI has no current-mode query, so ask GDI for the display's active settings.
+fn current_refresh_rate(device_name: &[u16; 32]) -> Option<f32> {
+    let mut mode = DEVMODEW {
+        dmSize: std::mem::size_of::<DEVMODEW>() as u16,
+        ..Default::default()
+    };
+    let ok = unsafe {
+        EnumDisplaySettingsW(PCWSTR(device_name.as_ptr()), ENUM_CURRENT_SETTINGS, &mut mode)
+    };
+    // 0 and 1 both mean "hardware default".
+    (ok.as_bool() && mode.dmDisplayFrequency > 1).then_some(mode.dmDisplayFrequency as f32)
+}
+
+fn monitor_scale_factor(monitor: HMONITOR) -> Option<f32> {
+    let (mut dpi_x, mut dpi_y) = (0u32, 0u32);
+    unsafe { GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) }.ok()?;
+    (dpi_x > 0).then(|| dpi_x as f32 / 96.0)
+}
+
 fn wide_to_string(buffer: &[u16]) -> String {
     let end = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
     String::from_utf16_lossy(&buffer[..end])
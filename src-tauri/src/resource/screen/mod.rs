@@ -1,4 +1,6 @@
 use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
 
 /// Represents a BGRA screen frame stored in contiguous memory.
 pub struct ScreenFrame<'a> {
@@ -8,6 +10,23 @@ pub struct ScreenFrame<'a> {
     pub pixels: &'a [u8],
     /// Optional dirty regions reported by the backend. Empty means "unknown/entire frame".
     pub dirty_regions: &'a [DirtyRegion],
+    /// Identifies the image content. Backends that re-serve their last frame between
+    /// captures return the same id again; 0 means "unknown" and must not be cached.
+    pub frame_id: u64,
+    /// When the backend last confirmed these pixels are current: a new frame arrived, or
+    /// the OS reported that nothing changed. Event-driven backends (Graphics Capture,
+    /// ScreenCaptureKit) only hear about changes, so they count every check of a running
+    /// session as confirmation; a static desktop doesn't age.
+    pub captured_at: Instant,
+}
+
+static NEXT_FRAME_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Allocate an id for a freshly captured frame. Ids are unique across displays, backends
+/// and capturer rebuilds, so a cache keyed by frame id can never serve stale results after
+/// a resolution or method change.
+pub(crate) fn next_frame_id() -> u64 {
+    NEXT_FRAME_ID.fetch_add(1, Ordering::Relaxed)
 }
 
 /// A rectangular dirty region within a captured frame.
@@ -25,11 +44,61 @@ pub enum ScreenCaptureError {
     Unsupported(&'static str),
     OsError { context: &'static str, code: u32 },
     InvalidState(&'static str),
+    /// The OS refused to let us capture (missing permission, secure desktop).
+    PermissionDenied(&'static str),
+    /// A working capture stopped (mode change, display sleep, GPU reset).
+    AccessLost(&'static str),
+    /// The capturer is alive but no first frame has arrived yet.
+    NoFrame,
+}
+
+/// `Unsupported` context for macOS capture without the Screen Recording permission.
+pub(crate) const SCREEN_RECORDING_PERMISSION: &str = "screen-recording-permission";
+
+// HRESULTs with a more specific meaning than "the OS call failed".
+const E_ACCESSDENIED: u32 = 0x8007_0005;
+const DXGI_ERROR_UNSUPPORTED: u32 = 0x887A_0004;
+const DXGI_ERROR_ACCESS_LOST: u32 = 0x887A_0026;
+const DXGI_ERROR_SESSION_DISCONNECTED: u32 = 0x887A_0028;
+const DXGI_ERROR_ACCESS_DENIED: u32 = 0x887A_002B;
+
+impl ScreenCaptureError {
+    /// Stable identifier for the frontend; unlike the message it never changes wording.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ScreenCaptureError::Unsupported(SCREEN_RECORDING_PERMISSION) => "permission_denied",
+            ScreenCaptureError::Unsupported(_) => "unsupported",
+            ScreenCaptureError::OsError { code, .. } => match *code {
+                E_ACCESSDENIED | DXGI_ERROR_ACCESS_DENIED => "permission_denied",
+                DXGI_ERROR_ACCESS_LOST | DXGI_ERROR_SESSION_DISCONNECTED => "access_lost",
+                DXGI_ERROR_UNSUPPORTED => "unsupported",
+                _ => "os_error",
+            },
+            ScreenCaptureError::InvalidState(_) => "invalid_state",
+            ScreenCaptureError::PermissionDenied(_) => "permission_denied",
+            ScreenCaptureError::AccessLost(_) => "access_lost",
+            ScreenCaptureError::NoFrame => "no_frame",
+        }
+    }
+
+    /// Whether the capturer that returned this error is unusable and has to be rebuilt.
+    pub(crate) fn invalidates_capturer(&self) -> bool {
+        matches!(
+            self,
+            ScreenCaptureError::InvalidState(_)
+                | ScreenCaptureError::PermissionDenied(_)
+                | ScreenCaptureError::AccessLost(_)
+        )
+    }
 }
 
 impl Display for ScreenCaptureError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
+            ScreenCaptureError::Unsupported(SCREEN_RECORDING_PERMISSION) => write!(
+                f,
+                "Screen Recording permission is required (System Settings > Privacy & Security > Screen Recording)"
+            ),
             ScreenCaptureError::Unsupported(ctx) => {
                 write!(f, "Screen capture unsupported: {}", ctx)
             }
@@ -39,6 +108,13 @@ impl Display for ScreenCaptureError {
             ScreenCaptureError::InvalidState(ctx) => {
                 write!(f, "Screen capture invalid state: {}", ctx)
             }
+            ScreenCaptureError::PermissionDenied(ctx) => {
+                write!(f, "Screen capture permission denied: {}", ctx)
+            }
+            ScreenCaptureError::AccessLost(ctx) => {
+                write!(f, "Screen capture lost: {}", ctx)
+            }
+            ScreenCaptureError::NoFrame => write!(f, "No frame available yet"),
         }
     }
 }
@@ -71,6 +147,24 @@ pub(crate) const CAPTURE_PIXEL_PRESETS: &[u32] = &[
 
 pub(crate) const DEFAULT_CAPTURE_MAX_PIXELS: u32 = 2_304; // 36p
 
+pub(crate) const MIN_CAPTURE_FPS: u8 = 1;
+pub(crate) const MAX_CAPTURE_FPS: u8 = 60;
+
+pub(crate) fn normalize_capture_fps(fps: u8) -> u8 {
+    fps.clamp(MIN_CAPTURE_FPS, MAX_CAPTURE_FPS)
+}
+
+/// Capture settings as `configure_capture` applied them, after clamping.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureSettings {
+    pub max_pixels: u32,
+    pub fps: u8,
+    pub method: CaptureMethod,
+    /// Whether live capturers were dropped to pick up the change.
+    pub rebuilt: bool,
+}
+
 pub(crate) fn normalize_capture_max_pixels(value: u32) -> u32 {
     if value == 0 {
         return 0;
@@ -113,10 +207,153 @@ pub(crate) fn compute_scaled_dimensions_by_max_pixels(
     (scaled_width, scaled_height)
 }
 
+// ============================================================================
+// HDR tone mapping
+// ============================================================================
+
+/// Brightness in nits HDR capture maps to full LED output.
+pub(crate) const DEFAULT_TARGET_NITS: u32 = 200;
+const MIN_TARGET_NITS: u32 = 100;
+const MAX_TARGET_NITS: u32 = 4000;
+
+pub(crate) fn normalize_target_nits(nits: u32) -> u32 {
+    nits.clamp(MIN_TARGET_NITS, MAX_TARGET_NITS)
+}
+
+pub(crate) const MIN_TONE_MAP_EXPOSURE: f32 = 0.1;
+pub(crate) const MAX_TONE_MAP_EXPOSURE: f32 = 8.0;
+/// Lowest knee accepted; below this almost the whole range would be compressed.
+pub(crate) const MIN_TONE_MAP_KNEE: f32 = 0.1;
+
+/// Curve mapping HDR light (1.0 = the target brightness) onto the SDR range.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToneMapOperator {
+    /// Straight scaling with highlights clipped.
+    Linear,
+    /// `c / (1 + c)`: rolls highlights off without ever clipping.
+    #[default]
+    Reinhard,
+    Aces,
+    Hable,
+}
+
+/// How HDR highlights are brought down to LED range. Only the DXGI HDR path uses this.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ToneMapping {
+    /// Multiplier on the captured light before the curve.
+    pub exposure: f32,
+    /// Output level where a soft shoulder starts rolling highlights off; 1.0 clips hard.
+    pub knee: f32,
+    pub operator: ToneMapOperator,
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        Self {
+            exposure: 1.0,
+            knee: 1.0,
+            operator: ToneMapOperator::Reinhard,
+        }
+    }
+}
+
+impl ToneMapping {
+    /// Clamp to the supported ranges; non-finite values fall back to the defaults.
+    pub fn normalized(self) -> Self {
+        let defaults = Self::default();
+        let clamp = |value: f32, min: f32, max: f32, fallback: f32| {
+            if value.is_finite() {
+                value.clamp(min, max)
+            } else {
+                fallback
+            }
+        };
+        Self {
+            exposure: clamp(
+                self.exposure,
+                MIN_TONE_MAP_EXPOSURE,
+                MAX_TONE_MAP_EXPOSURE,
+                defaults.exposure,
+            ),
+            knee: clamp(self.knee, MIN_TONE_MAP_KNEE, 1.0, defaults.knee),
+            operator: self.operator,
+        }
+    }
+
+    /// True when the settings only scale and clip, which the precompiled HDR shader does
+    /// without compiling the tone-mapping variant.
+    pub fn is_linear_passthrough(&self) -> bool {
+        self.operator == ToneMapOperator::Linear && self.exposure == 1.0 && self.knee >= 1.0
+    }
+}
+
+// ============================================================================
+// Capture crop
+// ============================================================================
+
+/// Smallest side a crop may have, as a fraction of the display.
+pub(crate) const MIN_CROP_SIZE: f32 = 0.05;
+
+/// Part of a display to capture, in fractions (0.0–1.0) of its width and height so it
+/// still fits after a resolution change.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CropRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl CropRect {
+    /// Clamp into the display with at least `MIN_CROP_SIZE` on each side. `None` when the
+    /// values aren't finite or the crop covers the whole display, both meaning "no crop".
+    pub fn normalized(self) -> Option<Self> {
+        if ![self.x, self.y, self.width, self.height]
+            .iter()
+            .all(|value| value.is_finite())
+        {
+            return None;
+        }
+        let axis = |start: f32, size: f32| {
+            let start = start.clamp(0.0, 1.0 - MIN_CROP_SIZE);
+            (start, size.clamp(MIN_CROP_SIZE, 1.0 - start))
+        };
+        let (x, width) = axis(self.x, self.width);
+        let (y, height) = axis(self.y, self.height);
+        let whole = x == 0.0 && y == 0.0 && width == 1.0 && height == 1.0;
+        (!whole).then_some(Self {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    /// The crop in pixels of a `width` x `height` image, as `(x, y, width, height)`; never
+    /// empty.
+    pub(crate) fn to_pixels(self, width: u32, height: u32) -> (u32, u32, u32, u32) {
+        let axis = |start: f32, size: f32, len: u32| {
+            let first = ((start * len as f32).round() as u32).min(len.saturating_sub(1));
+            let end = (((start + size) * len as f32).round() as u32).clamp(first + 1, len.max(1));
+            (first, end - first)
+        };
+        let (x, crop_width) = axis(self.x, self.width, width);
+        let (y, crop_height) = axis(self.y, self.height, height);
+        (x, y, crop_width, crop_height)
+    }
+}
+
 // ============================================================================
 // Platform-specific modules
 // ============================================================================
 
+mod registry;
+mod spanning;
+
+pub use spanning::ALL_DISPLAYS;
+
 // Windows: Use native DXGI/GDI implementation
 #[cfg(target_os = "windows")]
 #[path = "Windows/mod.rs"]
@@ -125,11 +362,14 @@ mod screen;
 
 #[cfg(target_os = "windows")]
 pub use screen::{
-    CaptureMethod, DesktopDuplicator, DisplayInfo, ScreenSubscription,
-    get_capture_fps, get_capture_method, get_capture_max_pixels,
-    get_hardware_acceleration, get_sample_ratio, list_displays,
-    set_capture_fps, set_capture_method, set_capture_max_pixels,
-    set_hardware_acceleration, set_sample_ratio,
+    CaptureMethod, DesktopDuplicator, DisplayInfo, ScreenSubscription, configure_capture,
+    get_capture_crop, get_capture_fps, get_capture_method, get_capture_max_pixels,
+    get_hardware_acceleration, get_high_bit_depth, get_sample_ratio, get_show_cursor,
+    has_screen_permission, is_capture_paused, list_displays, open_screen_permission_settings,
+    pause_capture, resume_capture, set_capture_crop, set_capture_fps, set_capture_method,
+    set_capture_max_pixels,
+    set_hardware_acceleration, set_high_bit_depth, set_sample_ratio, set_show_cursor,
+    get_tone_mapping, set_tone_mapping, get_capture_target_nits, set_capture_target_nits,
 };
 
 // macOS: Use ScreenCaptureKit backend (native Apple framework)
@@ -140,11 +380,14 @@ mod screen;
 
 #[cfg(target_os = "macos")]
 pub use screen::{
-    CaptureMethod, DesktopDuplicator, DisplayInfo, ScreenSubscription,
-    get_capture_fps, get_capture_method, get_capture_max_pixels,
-    get_hardware_acceleration, get_sample_ratio, list_displays,
-    set_capture_fps, set_capture_method, set_capture_max_pixels,
-    set_hardware_acceleration, set_sample_ratio,
+    CaptureMethod, DesktopDuplicator, DisplayInfo, ScreenSubscription, configure_capture,
+    get_capture_crop, get_capture_fps, get_capture_method, get_capture_max_pixels,
+    get_hardware_acceleration, get_high_bit_depth, get_sample_ratio, get_show_cursor,
+    has_screen_permission, is_capture_paused, list_displays, open_screen_permission_settings,
+    pause_capture, resume_capture, set_capture_crop, set_capture_fps, set_capture_method,
+    set_capture_max_pixels,
+    set_hardware_acceleration, set_high_bit_depth, set_sample_ratio, set_show_cursor,
+    get_tone_mapping, set_tone_mapping, get_capture_target_nits, set_capture_target_nits,
 };
 
 // Linux: Use xcap backend
@@ -155,9 +398,89 @@ mod screen;
 
 #[cfg(target_os = "linux")]
 pub use screen::{
-    CaptureMethod, DesktopDuplicator, DisplayInfo, ScreenSubscription,
-    get_capture_fps, get_capture_method, get_capture_max_pixels,
-    get_hardware_acceleration, get_sample_ratio, list_displays,
-    set_capture_fps, set_capture_method, set_capture_max_pixels,
-    set_hardware_acceleration, set_sample_ratio,
+    CaptureMethod, DesktopDuplicator, DisplayInfo, ScreenSubscription, configure_capture,
+    get_capture_crop, get_capture_fps, get_capture_method, get_capture_max_pixels,
+    get_hardware_acceleration, get_high_bit_depth, get_sample_ratio, get_show_cursor,
+    has_screen_permission, is_capture_paused, list_displays, open_screen_permission_settings,
+    pause_capture, resume_capture, set_capture_crop, set_capture_fps, set_capture_method,
+    set_capture_max_pixels,
+    set_hardware_acceleration, set_high_bit_depth, set_sample_ratio, set_show_cursor,
+    get_tone_mapping, set_tone_mapping, get_capture_target_nits, set_capture_target_nits,
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaled_dimensions_follow_a_display_mode_change() {
+        let budget = 230_400;
+        let before = compute_scaled_dimensions_by_max_pixels(1920, 1080, budget);
+        let after = compute_scaled_dimensions_by_max_pixels(2560, 1440, budget);
+        assert_eq!(before, (480, 270));
+        assert_eq!(after, (640, 360));
+
+        // Dropping to a 4:3 mode changes the shape, not only the size.
+        assert_eq!(compute_scaled_dimensions_by_max_pixels(1280, 1024, budget), (320, 256));
+        assert_eq!(compute_scaled_dimensions_by_max_pixels(1280, 1024, 0), (1280, 1024));
+    }
+
+    #[test]
+    fn crops_are_clamped_and_scale_with_the_display() {
+        let centre = CropRect {
+            x: 0.25,
+            y: 0.0,
+            width: 0.5,
+            height: 1.0,
+        };
+        assert_eq!(centre.normalized(), Some(centre));
+        assert_eq!(centre.to_pixels(3440, 1440), (860, 0, 1720, 1440));
+        assert_eq!(centre.to_pixels(2560, 1080), (640, 0, 1280, 1080));
+
+        // Past the edge it is pulled back in; a sliver still keeps a pixel.
+        let edge = CropRect {
+            x: 0.9,
+            y: -1.0,
+            width: 0.5,
+            height: 0.0,
+        };
+        let clamped = edge.normalized().unwrap();
+        assert_eq!((clamped.x, clamped.y), (0.9, 0.0));
+        assert!((clamped.width - 0.1).abs() < 1e-6);
+        assert_eq!(clamped.height, MIN_CROP_SIZE);
+        assert_eq!(clamped.to_pixels(10, 10), (9, 0, 1, 1));
+
+        let whole = CropRect {
+            x: 0.0,
+            y: 0.0,
+            width: 2.0,
+            height: 1.0,
+        };
+        assert_eq!(whole.normalized(), None);
+        assert_eq!(CropRect { x: f32::NAN, ..centre }.normalized(), None);
+    }
+
+    #[test]
+    fn target_nits_are_held_to_the_supported_range() {
+        assert_eq!(normalize_target_nits(0), 100);
+        assert_eq!(normalize_target_nits(99), 100);
+        assert_eq!(normalize_target_nits(100), 100);
+        assert_eq!(normalize_target_nits(DEFAULT_TARGET_NITS), DEFAULT_TARGET_NITS);
+        assert_eq!(normalize_target_nits(4000), 4000);
+        assert_eq!(normalize_target_nits(4001), 4000);
+        assert_eq!(normalize_target_nits(u32::MAX), 4000);
+    }
+
+    #[test]
+    fn tone_mapping_defaults_to_reinhard() {
+        let missing: ToneMapping = serde_json::from_str(r#"{"exposure": 2.0}"#).unwrap();
+        assert_eq!(missing.operator, ToneMapOperator::Reinhard);
+        assert_eq!(ToneMapping::default().operator, ToneMapOperator::Reinhard);
+        assert!(!ToneMapping::default().is_linear_passthrough());
+        let linear = ToneMapping {
+            operator: ToneMapOperator::Linear,
+            ..ToneMapping::default()
+        };
+        assert!(linear.is_linear_passthrough());
+    }
+}
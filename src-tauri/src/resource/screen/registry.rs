@@ -0,0 +1,222 @@
+//! Reference-counted capturer sharing used by every platform's subscription manager.
+//!
+//! Subscriptions to the same display share one capturer, which is dropped (together with
+//! its GPU device and duplication/stream resources) as soon as the last holder lets go.
+//! Each holder gets a [`CaptureLease`] naming the exact entry it is counted in, so after an
+//! entry is rebuilt (settings change, lost device) an old lease can't release someone
+//! else's reference.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::{ScreenCaptureError, ScreenCapturer, ScreenFrame};
+
+/// One reference to a registry entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CaptureLease<K> {
+    key: K,
+    incarnation: u64,
+}
+
+struct ManagedOutput<D> {
+    capturer: D,
+    incarnation: u64,
+    ref_count: usize,
+}
+
+pub(crate) struct CaptureRegistry<K, D> {
+    outputs: HashMap<K, ManagedOutput<D>>,
+    next_incarnation: u64,
+}
+
+impl<K: Copy + Eq + Hash, D: ScreenCapturer> CaptureRegistry<K, D> {
+    pub(crate) fn new() -> Self {
+        Self {
+            outputs: HashMap::new(),
+            next_incarnation: 0,
+        }
+    }
+
+    /// Take another reference to the capturer for `key`, if one exists.
+    pub(crate) fn retain(&mut self, key: K) -> Option<CaptureLease<K>> {
+        let entry = self.outputs.get_mut(&key)?;
+        entry.ref_count += 1;
+        Some(CaptureLease {
+            key,
+            incarnation: entry.incarnation,
+        })
+    }
+
+    /// Register a freshly built capturer with one reference, replacing any old entry.
+    pub(crate) fn insert(&mut self, key: K, capturer: D) -> CaptureLease<K> {
+        let incarnation = self.next_incarnation;
+        self.next_incarnation += 1;
+        self.outputs.insert(
+            key,
+            ManagedOutput {
+                capturer,
+                incarnation,
+                ref_count: 1,
+            },
+        );
+        CaptureLease { key, incarnation }
+    }
+
+    /// Share the capturer for `key`, building it with `create` if there is none.
+    pub(crate) fn acquire<E>(
+        &mut self,
+        key: K,
+        create: impl FnOnce() -> Result<D, E>,
+    ) -> Result<CaptureLease<K>, E> {
+        match self.retain(key) {
+            Some(lease) => Ok(lease),
+            None => Ok(self.insert(key, create()?)),
+        }
+    }
+
+    /// Whether `lease` still counts towards a live entry.
+    pub(crate) fn holds(&self, lease: &CaptureLease<K>) -> bool {
+        self.outputs
+            .get(&lease.key)
+            .is_some_and(|entry| entry.incarnation == lease.incarnation)
+    }
+
+    /// Drop one reference, freeing the capturer with the last one. Stale leases are ignored.
+    pub(crate) fn release(&mut self, lease: CaptureLease<K>) {
+        if !self.holds(&lease) {
+            return;
+        }
+        if let Some(entry) = self.outputs.get_mut(&lease.key) {
+            entry.ref_count -= 1;
+            if entry.ref_count == 0 {
+                self.outputs.remove(&lease.key);
+            }
+        }
+    }
+
+    /// Capture through the lease's entry. `Ok(false)` means the lease is stale.
+    /// A capturer whose error invalidates it is dropped; its holders re-acquire.
+    pub(crate) fn capture_with<F>(
+        &mut self,
+        lease: &CaptureLease<K>,
+        f: F,
+    ) -> Result<bool, ScreenCaptureError>
+    where
+        F: FnOnce(&ScreenFrame<'_>),
+    {
+        let Some(entry) = self
+            .outputs
+            .get_mut(&lease.key)
+            .filter(|entry| entry.incarnation == lease.incarnation)
+        else {
+            return Ok(false);
+        };
+
+        match entry.capturer.capture() {
+            Ok(frame) => {
+                f(&frame);
+                Ok(true)
+            }
+            Err(err) => {
+                if err.invalidates_capturer() {
+                    self.outputs.remove(&lease.key);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Free every capturer. Outstanding leases go stale.
+    pub(crate) fn clear(&mut self) {
+        self.outputs.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    /// Counts live instances so tests can see when the registry frees one.
+    struct MockCapturer {
+        live: Arc<AtomicUsize>,
+        pixels: Vec<u8>,
+    }
+
+    impl MockCapturer {
+        fn new(live: &Arc<AtomicUsize>) -> Self {
+            live.fetch_add(1, Ordering::SeqCst);
+            Self {
+                live: live.clone(),
+                pixels: vec![0; 4],
+            }
+        }
+    }
+
+    impl Drop for MockCapturer {
+        fn drop(&mut self) {
+            self.live.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    impl ScreenCapturer for MockCapturer {
+        fn capture(&mut self) -> Result<ScreenFrame<'_>, ScreenCaptureError> {
+            Ok(ScreenFrame {
+                width: 1,
+                height: 1,
+                stride: 4,
+                pixels: &self.pixels,
+                dirty_regions: &[],
+                frame_id: 0,
+                captured_at: Instant::now(),
+            })
+        }
+
+        fn size(&self) -> (u32, u32) {
+            (1, 1)
+        }
+    }
+
+    #[test]
+    fn releasing_the_last_lease_frees_the_capturer() {
+        let live = Arc::new(AtomicUsize::new(0));
+        let mut registry = CaptureRegistry::new();
+
+        let first = registry
+            .acquire(0usize, || Ok::<_, ()>(MockCapturer::new(&live)))
+            .unwrap();
+        let second = registry
+            .acquire(0usize, || Ok::<_, ()>(MockCapturer::new(&live)))
+            .unwrap();
+        assert_eq!(live.load(Ordering::SeqCst), 1, "subscribers share one capturer");
+
+        registry.release(first);
+        assert_eq!(live.load(Ordering::SeqCst), 1);
+        assert!(matches!(registry.capture_with(&second, |_| {}), Ok(true)));
+
+        registry.release(second);
+        assert_eq!(live.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn stale_leases_do_not_release_a_rebuilt_capturer() {
+        let live = Arc::new(AtomicUsize::new(0));
+        let mut registry = CaptureRegistry::new();
+
+        let old = registry
+            .acquire(0usize, || Ok::<_, ()>(MockCapturer::new(&live)))
+            .unwrap();
+        registry.clear();
+        assert_eq!(live.load(Ordering::SeqCst), 0);
+
+        let fresh = registry
+            .acquire(0usize, || Ok::<_, ()>(MockCapturer::new(&live)))
+            .unwrap();
+        registry.release(old);
+        assert!(registry.holds(&fresh));
+        assert!(matches!(registry.capture_with(&old, |_| {}), Ok(false)));
+        assert_eq!(live.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,273 @@
+//! Capture of the whole virtual desktop as one frame ([`ALL_DISPLAYS`]).
+//!
+//! The frame covers the bounding box of every display in desktop coordinates. Each display
+//! keeps its own capturer and is drawn into its place on a shared canvas; whatever no display
+//! covers (gaps between monitors of different sizes or offsets) stays black, so the layout
+//! effects see the same letterboxing the user sees on their desk.
+//!
+//! This costs one capturer per display plus a CPU composite of every frame, so it is roughly
+//! N times the work of mirroring a single display. The canvas obeys the capture pixel budget
+//! as a whole, which keeps the composite cheap at the default budgets.
+
+use std::time::Instant;
+
+use super::{
+    compute_scaled_dimensions_by_max_pixels, next_frame_id, ScreenCaptureError, ScreenCapturer,
+    ScreenFrame,
+};
+
+/// Display index that stands for every display at once.
+pub const ALL_DISPLAYS: usize = usize::MAX;
+
+const BYTES_PER_PIXEL: usize = 4;
+
+/// A display's area on the virtual desktop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DisplayRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl DisplayRect {
+    fn right(&self) -> i64 {
+        self.x as i64 + self.width as i64
+    }
+
+    fn bottom(&self) -> i64 {
+        self.y as i64 + self.height as i64
+    }
+}
+
+/// Smallest rectangle containing every display, `None` without displays.
+pub(crate) fn virtual_bounds(rects: &[DisplayRect]) -> Option<DisplayRect> {
+    let left = rects.iter().map(|r| r.x as i64).min()?;
+    let top = rects.iter().map(|r| r.y as i64).min()?;
+    let right = rects.iter().map(DisplayRect::right).max()?;
+    let bottom = rects.iter().map(DisplayRect::bottom).max()?;
+    Some(DisplayRect {
+        x: left as i32,
+        y: top as i32,
+        width: (right - left).max(1) as u32,
+        height: (bottom - top).max(1) as u32,
+    })
+}
+
+/// Where a display lands on the canvas, in canvas pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Placement {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+fn place(rect: &DisplayRect, bounds: &DisplayRect, canvas: (u32, u32)) -> Placement {
+    let scale = |offset: i64, extent: u32, canvas: u32| -> usize {
+        (offset.max(0) as u64 * canvas as u64 / extent.max(1) as u64) as usize
+    };
+    let x0 = scale(rect.x as i64 - bounds.x as i64, bounds.width, canvas.0);
+    let y0 = scale(rect.y as i64 - bounds.y as i64, bounds.height, canvas.1);
+    let x1 = scale(rect.right() - bounds.x as i64, bounds.width, canvas.0);
+    let y1 = scale(rect.bottom() - bounds.y as i64, bounds.height, canvas.1);
+    Placement {
+        x: x0.min(canvas.0 as usize - 1),
+        y: y0.min(canvas.1 as usize - 1),
+        width: x1.saturating_sub(x0).max(1),
+        height: y1.saturating_sub(y0).max(1),
+    }
+}
+
+/// Nearest-neighbour copy of `frame` into its placement on the canvas.
+fn blit(frame: &ScreenFrame<'_>, target: Placement, canvas: &mut [u8], canvas_width: usize) {
+    if frame.width == 0 || frame.height == 0 {
+        return;
+    }
+    let canvas_height = canvas.len() / (canvas_width * BYTES_PER_PIXEL);
+    let rows = target.height.min(canvas_height - target.y);
+    let cols = target.width.min(canvas_width - target.x);
+
+    for row in 0..rows {
+        let src_y = row * frame.height as usize / target.height;
+        let src_row = src_y * frame.stride;
+        let dst_row = ((target.y + row) * canvas_width + target.x) * BYTES_PER_PIXEL;
+        for col in 0..cols {
+            let src = src_row + (col * frame.width as usize / target.width) * BYTES_PER_PIXEL;
+            let dst = dst_row + col * BYTES_PER_PIXEL;
+            if let Some(pixel) = frame.pixels.get(src..src + BYTES_PER_PIXEL) {
+                canvas[dst..dst + BYTES_PER_PIXEL].copy_from_slice(pixel);
+            }
+        }
+    }
+}
+
+struct SpannedDisplay<C> {
+    capturer: C,
+    target: Placement,
+}
+
+/// Composites one capturer per display into a frame of the whole virtual desktop.
+pub struct SpanningCapturer<C> {
+    displays: Vec<SpannedDisplay<C>>,
+    width: u32,
+    height: u32,
+    canvas: Vec<u8>,
+    frame_id: u64,
+    captured_at: Instant,
+}
+
+impl<C: ScreenCapturer> SpanningCapturer<C> {
+    /// `displays` pairs each display's desktop area with its capturer. `max_pixels` bounds
+    /// the composited frame, 0 meaning the full virtual desktop resolution.
+    pub(crate) fn new(
+        displays: Vec<(DisplayRect, C)>,
+        max_pixels: u32,
+    ) -> Result<Self, ScreenCaptureError> {
+        let rects: Vec<DisplayRect> = displays.iter().map(|(rect, _)| *rect).collect();
+        let bounds = virtual_bounds(&rects)
+            .ok_or(ScreenCaptureError::InvalidState("No displays to span"))?;
+        let (width, height) =
+            compute_scaled_dimensions_by_max_pixels(bounds.width, bounds.height, max_pixels);
+
+        let displays = displays
+            .into_iter()
+            .map(|(rect, capturer)| SpannedDisplay {
+                capturer,
+                target: place(&rect, &bounds, (width, height)),
+            })
+            .collect();
+
+        Ok(Self {
+            displays,
+            width,
+            height,
+            canvas: [0, 0, 0, 255].repeat(width as usize * height as usize),
+            frame_id: 0,
+            captured_at: Instant::now(),
+        })
+    }
+}
+
+impl<C: ScreenCapturer> ScreenCapturer for SpanningCapturer<C> {
+    /// A display without a new frame keeps its previous picture on the canvas; the call only
+    /// reports `NoFrame` until some display has delivered one.
+    fn capture(&mut self) -> Result<ScreenFrame<'_>, ScreenCaptureError> {
+        let canvas_width = self.width as usize;
+        let mut updated = false;
+        for display in &mut self.displays {
+            match display.capturer.capture() {
+                Ok(frame) => {
+                    blit(&frame, display.target, &mut self.canvas, canvas_width);
+                    updated = true;
+                }
+                Err(ScreenCaptureError::NoFrame) => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        if updated {
+            self.frame_id = next_frame_id();
+            self.captured_at = Instant::now();
+        } else if self.frame_id == 0 {
+            return Err(ScreenCaptureError::NoFrame);
+        }
+
+        Ok(ScreenFrame {
+            width: self.width,
+            height: self.height,
+            stride: canvas_width * BYTES_PER_PIXEL,
+            pixels: &self.canvas,
+            dirty_regions: &[],
+            frame_id: self.frame_id,
+            captured_at: self.captured_at,
+        })
+    }
+
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A display showing one solid color.
+    struct SolidCapturer {
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+    }
+
+    impl SolidCapturer {
+        fn new(width: u32, height: u32, bgra: [u8; 4]) -> Self {
+            Self {
+                width,
+                height,
+                pixels: bgra.repeat((width * height) as usize),
+            }
+        }
+    }
+
+    impl ScreenCapturer for SolidCapturer {
+        fn capture(&mut self) -> Result<ScreenFrame<'_>, ScreenCaptureError> {
+            Ok(ScreenFrame {
+                width: self.width,
+                height: self.height,
+                stride: self.width as usize * BYTES_PER_PIXEL,
+                pixels: &self.pixels,
+                dirty_regions: &[],
+                frame_id: 0,
+                captured_at: Instant::now(),
+            })
+        }
+
+        fn size(&self) -> (u32, u32) {
+            (self.width, self.height)
+        }
+    }
+
+    #[test]
+    fn spanning_frame_covers_every_display_and_letterboxes_the_gaps() {
+        // A 4x2 display with a 2x1 display to its right, aligned at the top.
+        let left = DisplayRect { x: 0, y: 0, width: 4, height: 2 };
+        let right = DisplayRect { x: 4, y: 0, width: 2, height: 1 };
+        assert_eq!(
+            virtual_bounds(&[left, right]),
+            Some(DisplayRect { x: 0, y: 0, width: left.width + right.width, height: 2 })
+        );
+
+        let red = [0, 0, 255, 255];
+        let blue = [255, 0, 0, 255];
+        let mut capturer = SpanningCapturer::new(
+            vec![
+                (left, SolidCapturer::new(4, 2, red)),
+                (right, SolidCapturer::new(2, 1, blue)),
+            ],
+            0,
+        )
+        .unwrap();
+        assert_eq!(capturer.size(), (6, 2));
+
+        let frame = capturer.capture().unwrap();
+        let pixel = |x: usize, y: usize| {
+            let offset = y * frame.stride + x * BYTES_PER_PIXEL;
+            &frame.pixels[offset..offset + BYTES_PER_PIXEL]
+        };
+        assert_eq!(pixel(0, 1), red);
+        assert_eq!(pixel(5, 0), blue);
+        assert_eq!(pixel(5, 1), [0, 0, 0, 255], "area below the small display stays black");
+    }
+
+    #[test]
+    fn virtual_bounds_include_displays_left_of_and_above_the_primary() {
+        let primary = DisplayRect { x: 0, y: 0, width: 1920, height: 1080 };
+        let above_left = DisplayRect { x: -1280, y: -1024, width: 1280, height: 1024 };
+        assert_eq!(
+            virtual_bounds(&[primary, above_left]),
+            Some(DisplayRect { x: -1280, y: -1024, width: 3200, height: 2104 })
+        );
+        assert_eq!(virtual_bounds(&[]), None);
+    }
+}
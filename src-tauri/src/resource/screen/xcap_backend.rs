@@ -1,20 +1,24 @@
-//! Cross-platform screen capture backend using xcap library.
+//! Linux screen capture backend using the xcap library.
 //!
-//! This module provides screen capture functionality for macOS and Linux
-//! using the xcap library. Windows uses its native DXGI/GDI implementation.
+//! xcap captures through XRandR/XShm on X11. Under Wayland it depends on the compositor,
+//! and failures there are reported as `Unsupported` with a hint instead of an OS error.
+//! Windows and macOS use their native implementations.
 
-use std::collections::HashMap;
 use std::sync::{
-    atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering},
-    Mutex, OnceLock,
+    atomic::{AtomicBool, AtomicU32, AtomicU8, Ordering},
+    Mutex, OnceLock, RwLock,
 };
+use std::time::{Duration, Instant};
 
 use serde::{Deserialize, Serialize};
-use xcap::Monitor;
+use xcap::{Monitor, XCapError};
 
+use super::registry::{CaptureLease, CaptureRegistry};
+use super::spanning::{DisplayRect, SpanningCapturer, ALL_DISPLAYS};
 use super::{
-    compute_scaled_dimensions_by_max_pixels, normalize_capture_max_pixels,
-    DEFAULT_CAPTURE_MAX_PIXELS, ScreenCaptureError, ScreenCapturer, ScreenFrame,
+    compute_scaled_dimensions_by_max_pixels, normalize_capture_fps, normalize_capture_max_pixels,
+    next_frame_id, CaptureSettings, CropRect, DEFAULT_CAPTURE_MAX_PIXELS, DEFAULT_TARGET_NITS,
+    ScreenCaptureError, ScreenCapturer, ScreenFrame, ToneMapping,
 };
 
 // ============================================================================
@@ -24,6 +28,10 @@ use super::{
 pub(crate) const BYTES_PER_PIXEL: usize = 4;
 pub(crate) const DEFAULT_CAPTURE_FPS: u8 = 30;
 
+/// `Unsupported` context when xcap fails in a Wayland session.
+const WAYLAND_UNSUPPORTED: &str = "Wayland session: the compositor didn't allow xcap to capture \
+    the screen. Log in with an X11 session, or use a compositor that supports wlr-screencopy";
+
 // ============================================================================
 // Global Settings
 // ============================================================================
@@ -31,9 +39,12 @@ pub(crate) const DEFAULT_CAPTURE_FPS: u8 = 30;
 /// Max pixel budget for capture resolution. 0 means "no limit".
 pub(crate) static CAPTURE_MAX_PIXELS: AtomicU32 = AtomicU32::new(DEFAULT_CAPTURE_MAX_PIXELS);
 pub(crate) static CAPTURE_FPS: AtomicU8 = AtomicU8::new(DEFAULT_CAPTURE_FPS);
+static SAMPLE_RATIO: AtomicU8 = AtomicU8::new(100);
+/// Part of each display to capture; `None` captures all of it.
+static CAPTURE_CROP: RwLock<Option<CropRect>> = RwLock::new(None);
 
-/// Generation counter for capture state; bump when settings change.
-static CAPTURE_GEN: AtomicU64 = AtomicU64::new(0);
+/// Set by `pause_capture`; only written with the manager lock held.
+static CAPTURE_PAUSED: AtomicBool = AtomicBool::new(false);
 
 // ============================================================================
 // Public Types
@@ -73,7 +84,26 @@ pub struct DisplayInfo {
     pub name: String,
     pub width: u32,
     pub height: u32,
+    /// Top-left corner on the virtual desktop.
+    pub x: i32,
+    pub y: i32,
     pub is_hdr: bool,
+    /// Current refresh rate in Hz.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_rate_hz: Option<f32>,
+    /// Not exposed by xcap.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bits_per_color: Option<u32>,
+    /// Clockwise rotation in degrees (0, 90, 180 or 270).
+    pub rotation: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale_factor: Option<f32>,
+    /// Not exposed by xcap.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adapter_name: Option<String>,
+    /// Not exposed by xcap.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_luminance_nits: Option<f32>,
 }
 
 // ============================================================================
@@ -84,11 +114,11 @@ pub fn set_capture_max_pixels(max_pixels: u32) {
     let normalized = normalize_capture_max_pixels(max_pixels);
     let previous = CAPTURE_MAX_PIXELS.swap(normalized, Ordering::Relaxed);
 
+    // Subscriptions rebuild their capturer at the new size on their next capture.
     if previous != normalized {
         if let Ok(mut manager) = global_manager().lock() {
             manager.clear();
         }
-        CAPTURE_GEN.fetch_add(1, Ordering::Relaxed);
     }
 }
 
@@ -97,7 +127,7 @@ pub fn get_capture_max_pixels() -> u32 {
 }
 
 pub fn set_capture_fps(fps: u8) {
-    CAPTURE_FPS.store(fps.clamp(1, 60), Ordering::Relaxed);
+    CAPTURE_FPS.store(normalize_capture_fps(fps), Ordering::Relaxed);
 }
 
 pub fn get_capture_fps() -> u8 {
@@ -112,6 +142,38 @@ pub fn get_hardware_acceleration() -> bool {
     false
 }
 
+pub fn set_high_bit_depth(_enabled: bool) {
+    // xcap only delivers 8-bit frames
+}
+
+pub fn get_high_bit_depth() -> bool {
+    false
+}
+
+pub fn set_tone_mapping(_tone_mapping: ToneMapping) {
+    // xcap frames are SDR; there are no HDR highlights to map
+}
+
+pub fn get_tone_mapping() -> ToneMapping {
+    ToneMapping::default()
+}
+
+pub fn set_capture_target_nits(_nits: u32) {
+    // xcap frames are SDR
+}
+
+pub fn get_capture_target_nits() -> u32 {
+    DEFAULT_TARGET_NITS
+}
+
+pub fn set_show_cursor(_enabled: bool) {
+    // xcap frames never include the pointer
+}
+
+pub fn get_show_cursor() -> bool {
+    false
+}
+
 pub fn set_capture_method(_method: CaptureMethod) {
     // Only one method available for xcap
 }
@@ -120,25 +182,119 @@ pub fn get_capture_method() -> CaptureMethod {
     CaptureMethod::Xcap
 }
 
-#[allow(dead_code)]
-pub fn set_sample_ratio(_percent: u8) {}
+/// Apply the pixel budget and frame rate together, rebuilding capturers at most once.
+/// There is only one capture method here, so `method` is ignored.
+pub fn configure_capture(max_pixels: u32, fps: u8, _method: CaptureMethod) -> CaptureSettings {
+    let max_pixels = normalize_capture_max_pixels(max_pixels);
+    let fps = normalize_capture_fps(fps);
+    CAPTURE_FPS.store(fps, Ordering::Relaxed);
+
+    let rebuilt = CAPTURE_MAX_PIXELS.swap(max_pixels, Ordering::Relaxed) != max_pixels;
+    if rebuilt {
+        if let Ok(mut manager) = global_manager().lock() {
+            manager.clear();
+        }
+    }
+
+    CaptureSettings {
+        max_pixels,
+        fps,
+        method: get_capture_method(),
+        rebuilt,
+    }
+}
+
+/// Percentage of each zone's pixels that screen effects average (1–100).
+pub fn set_sample_ratio(percent: u8) {
+    SAMPLE_RATIO.store(percent.clamp(1, 100), Ordering::Relaxed);
+}
 
-#[allow(dead_code)]
 pub fn get_sample_ratio() -> u8 {
-    100
+    SAMPLE_RATIO.load(Ordering::Relaxed)
+}
+
+/// Capture only part of each display, or all of it with `None`. Spanning all displays
+/// ignores the crop.
+pub fn set_capture_crop(crop: Option<CropRect>) {
+    let crop = crop.and_then(CropRect::normalized);
+    let changed = match CAPTURE_CROP.write() {
+        Ok(mut guard) => std::mem::replace(&mut *guard, crop) != crop,
+        Err(_) => false,
+    };
+    if changed {
+        if let Ok(mut manager) = global_manager().lock() {
+            manager.clear();
+        }
+    }
+}
+
+pub fn get_capture_crop() -> Option<CropRect> {
+    CAPTURE_CROP.read().map(|guard| *guard).unwrap_or_default()
+}
+
+/// Free every capturer while keeping subscriptions alive; they report no frame until
+/// `resume_capture`.
+pub fn pause_capture() {
+    if let Ok(mut manager) = global_manager().lock() {
+        CAPTURE_PAUSED.store(true, Ordering::Relaxed);
+        manager.clear();
+    }
+}
+
+/// Subscriptions re-acquire their capturer on their next capture.
+pub fn resume_capture() {
+    let _manager = global_manager().lock();
+    CAPTURE_PAUSED.store(false, Ordering::Relaxed);
+}
+
+pub fn is_capture_paused() -> bool {
+    CAPTURE_PAUSED.load(Ordering::Relaxed)
+}
+
+/// Screen capture needs no permission on this platform.
+pub fn has_screen_permission() -> bool {
+    true
+}
+
+pub fn open_screen_permission_settings() -> Result<(), String> {
+    Ok(())
 }
 
 // ============================================================================
 // Public API - Display Enumeration
 // ============================================================================
 
+fn is_wayland_session() -> bool {
+    std::env::var("XDG_SESSION_TYPE").is_ok_and(|session| session.eq_ignore_ascii_case("wayland"))
+        || std::env::var_os("WAYLAND_DISPLAY").is_some_and(|display| !display.is_empty())
+}
+
+/// xcap only reports errors as text, so the code is a stand-in; the message is logged.
+fn xcap_error(context: &'static str, err: XCapError) -> ScreenCaptureError {
+    if is_wayland_session() {
+        log::warn!(context = context, err:display = err; "xcap failed in a Wayland session");
+        return ScreenCaptureError::Unsupported(WAYLAND_UNSUPPORTED);
+    }
+    log::debug!(context = context, err:display = err; "xcap call failed");
+    ScreenCaptureError::OsError {
+        context,
+        code: err.to_string().len() as u32,
+    }
+}
+
+fn all_monitors() -> Result<Vec<Monitor>, ScreenCaptureError> {
+    Monitor::all().map_err(|e| xcap_error("Monitor::all", e))
+}
+
+fn monitor_at(index: usize) -> Result<Monitor, ScreenCaptureError> {
+    all_monitors()?
+        .into_iter()
+        .nth(index)
+        .ok_or(ScreenCaptureError::InvalidState("Monitor index out of range"))
+}
+
 pub fn list_displays() -> Result<Vec<DisplayInfo>, ScreenCaptureError> {
-    let monitors = Monitor::all().map_err(|e| {
-        ScreenCaptureError::OsError {
-            context: "Monitor::all",
-            code: e.to_string().len() as u32,
-        }
-    })?;
+    let monitors = all_monitors()?;
 
     let mut displays = Vec::new();
     for (index, monitor) in monitors.iter().enumerate() {
@@ -151,7 +307,18 @@ pub fn list_displays() -> Result<Vec<DisplayInfo>, ScreenCaptureError> {
             name,
             width,
             height,
+            x: monitor.x().unwrap_or(0),
+            y: monitor.y().unwrap_or(0),
             is_hdr: false, // xcap doesn't expose HDR info
+            refresh_rate_hz: monitor.frequency().ok().filter(|&hz| hz > 0.0),
+            bits_per_color: None,
+            rotation: monitor
+                .rotation()
+                .map(|degrees| (degrees.round() as i32).rem_euclid(360) as u32)
+                .unwrap_or(0),
+            scale_factor: monitor.scale_factor().ok().filter(|&scale| scale > 0.0),
+            adapter_name: None,
+            max_luminance_nits: None,
         });
     }
 
@@ -163,13 +330,21 @@ pub fn list_displays() -> Result<Vec<DisplayInfo>, ScreenCaptureError> {
 // ============================================================================
 
 /// Screen capturer using xcap library.
+///
+/// Pinned to the monitor it was opened on, even if enumeration order changes later, and
+/// grabs a new frame at most `CAPTURE_FPS` times a second.
 pub struct XcapCapturer {
     monitor_index: usize,
+    monitor: Monitor,
+    crop: Option<CropRect>,
     // Cached frame buffer in BGRA format
     buffer: Vec<u8>,
     width: u32,
     height: u32,
     stride: usize,
+    frame_id: u64,
+    captured_at: Instant,
+    last_capture_time: Option<Instant>,
 }
 
 impl XcapCapturer {
@@ -178,47 +353,41 @@ impl XcapCapturer {
     }
 
     pub fn with_output(output_index: usize) -> Result<Self, ScreenCaptureError> {
-        let monitors = Monitor::all().map_err(|e| {
-            ScreenCaptureError::OsError {
-                context: "Monitor::all",
-                code: e.to_string().len() as u32,
-            }
-        })?;
-
-        if output_index >= monitors.len() {
-            return Err(ScreenCaptureError::InvalidState("Monitor index out of range"));
-        }
+        Self::with_output_crop(output_index, get_capture_crop())
+    }
 
-        let monitor = &monitors[output_index];
+    /// Capturer of `output_index` cropped to `crop` rather than the global setting.
+    pub fn with_output_crop(
+        output_index: usize,
+        crop: Option<CropRect>,
+    ) -> Result<Self, ScreenCaptureError> {
+        let monitor = monitor_at(output_index)?;
         let width = monitor.width().unwrap_or(1920);
         let height = monitor.height().unwrap_or(1080);
 
         Ok(Self {
             monitor_index: output_index,
+            monitor,
+            crop,
             buffer: Vec::new(),
             width,
             height,
             stride: (width as usize) * BYTES_PER_PIXEL,
+            frame_id: 0,
+            captured_at: Instant::now(),
+            last_capture_time: None,
         })
     }
 
     pub fn set_output_index(&mut self, output_index: usize) -> Result<(), ScreenCaptureError> {
-        let monitors = Monitor::all().map_err(|e| {
-            ScreenCaptureError::OsError {
-                context: "Monitor::all",
-                code: e.to_string().len() as u32,
-            }
-        })?;
-
-        if output_index >= monitors.len() {
-            return Err(ScreenCaptureError::InvalidState("Monitor index out of range"));
-        }
-
-        let monitor = &monitors[output_index];
+        let monitor = monitor_at(output_index)?;
         self.monitor_index = output_index;
         self.width = monitor.width().unwrap_or(1920);
         self.height = monitor.height().unwrap_or(1080);
         self.stride = (self.width as usize) * BYTES_PER_PIXEL;
+        self.monitor = monitor;
+        self.buffer.clear();
+        self.last_capture_time = None;
 
         Ok(())
     }
@@ -227,27 +396,34 @@ impl XcapCapturer {
         self.monitor_index
     }
 
-    fn do_capture(&mut self) -> Result<(), ScreenCaptureError> {
-        let monitors = Monitor::all().map_err(|e| {
-            ScreenCaptureError::OsError {
-                context: "Monitor::all",
-                code: e.to_string().len() as u32,
-            }
-        })?;
-
-        if self.monitor_index >= monitors.len() {
-            return Err(ScreenCaptureError::InvalidState("Monitor index out of range"));
+    /// Capture failed: if the monitor is gone the capturer has to be rebuilt, otherwise it
+    /// is an ordinary failed grab.
+    fn capture_error(&self, err: XCapError) -> ScreenCaptureError {
+        let id = self.monitor.id().ok();
+        let present = Monitor::all()
+            .map(|monitors| monitors.iter().any(|monitor| monitor.id().ok() == id))
+            .unwrap_or(true);
+        if present {
+            xcap_error("capture_image", err)
+        } else {
+            ScreenCaptureError::InvalidState("Monitor disconnected")
         }
+    }
 
-        let monitor = &monitors[self.monitor_index];
-
+    fn do_capture(&mut self) -> Result<(), ScreenCaptureError> {
         // Capture the screen
-        let image = monitor.capture_image().map_err(|e| {
-            ScreenCaptureError::OsError {
-                context: "capture_image",
-                code: e.to_string().len() as u32,
+        let image = self
+            .monitor
+            .capture_image()
+            .map_err(|e| self.capture_error(e))?;
+
+        let image = match self.crop {
+            Some(crop) => {
+                let (x, y, width, height) = crop.to_pixels(image.width(), image.height());
+                image::imageops::crop_imm(&image, x, y, width, height).to_image()
             }
-        })?;
+            None => image,
+        };
 
         // Apply max pixel budget
         let max_pixels = CAPTURE_MAX_PIXELS.load(Ordering::Relaxed);
@@ -291,6 +467,8 @@ impl XcapCapturer {
             self.buffer.push(chunk[0]); // R
             self.buffer.push(chunk[3]); // A
         }
+        self.frame_id = next_frame_id();
+        self.captured_at = Instant::now();
 
         Ok(())
     }
@@ -298,7 +476,18 @@ impl XcapCapturer {
 
 impl ScreenCapturer for XcapCapturer {
     fn capture(&mut self) -> Result<ScreenFrame<'_>, ScreenCaptureError> {
-        self.do_capture()?;
+        let fps = CAPTURE_FPS.load(Ordering::Relaxed).max(1) as u64;
+        let interval = Duration::from_micros(1_000_000 / fps);
+        let now = Instant::now();
+        let due = self
+            .last_capture_time
+            .is_none_or(|last| now.duration_since(last) >= interval);
+
+        // Between grabs the last frame is handed out again with its old id.
+        if due || self.buffer.is_empty() {
+            self.do_capture()?;
+            self.last_capture_time = Some(now);
+        }
 
         Ok(ScreenFrame {
             width: self.width,
@@ -306,6 +495,8 @@ impl ScreenCapturer for XcapCapturer {
             stride: self.stride,
             pixels: &self.buffer,
             dirty_regions: &[],
+            frame_id: self.frame_id,
+            captured_at: self.captured_at,
         })
     }
 
@@ -319,21 +510,22 @@ impl ScreenCapturer for XcapCapturer {
 // ============================================================================
 
 /// Unified screen capturer wrapper for xcap backend.
-pub struct DesktopDuplicator {
-    capturer: XcapCapturer,
+pub enum DesktopDuplicator {
+    Display(XcapCapturer),
+    /// [`ALL_DISPLAYS`]: every monitor composited into one frame.
+    Spanning(Box<SpanningCapturer<XcapCapturer>>),
 }
 
 impl DesktopDuplicator {
     pub fn new() -> Result<Self, ScreenCaptureError> {
-        Ok(Self {
-            capturer: XcapCapturer::new()?,
-        })
+        Self::with_output(0)
     }
 
     pub fn with_output(output_index: usize) -> Result<Self, ScreenCaptureError> {
-        Ok(Self {
-            capturer: XcapCapturer::with_output(output_index)?,
-        })
+        if output_index == ALL_DISPLAYS {
+            return Self::spanning();
+        }
+        Ok(Self::Display(XcapCapturer::with_output(output_index)?))
     }
 
     pub fn with_method_output(
@@ -343,22 +535,56 @@ impl DesktopDuplicator {
         Self::with_output(output_index)
     }
 
+    fn spanning() -> Result<Self, ScreenCaptureError> {
+        let displays = list_displays()?
+            .into_iter()
+            .map(|display| {
+                let rect = DisplayRect {
+                    x: display.x,
+                    y: display.y,
+                    width: display.width,
+                    height: display.height,
+                };
+                XcapCapturer::with_output_crop(display.index, None).map(|capturer| (rect, capturer))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let max_pixels = CAPTURE_MAX_PIXELS.load(Ordering::Relaxed);
+        Ok(Self::Spanning(Box::new(SpanningCapturer::new(displays, max_pixels)?)))
+    }
+
     pub fn set_output_index(&mut self, output_index: usize) -> Result<(), ScreenCaptureError> {
-        self.capturer.set_output_index(output_index)
+        match self {
+            Self::Display(capturer) if output_index != ALL_DISPLAYS => {
+                capturer.set_output_index(output_index)
+            }
+            _ => {
+                *self = Self::with_output(output_index)?;
+                Ok(())
+            }
+        }
     }
 
     pub fn output_index(&self) -> usize {
-        self.capturer.output_index()
+        match self {
+            Self::Display(capturer) => capturer.output_index(),
+            Self::Spanning(_) => ALL_DISPLAYS,
+        }
     }
 }
 
 impl ScreenCapturer for DesktopDuplicator {
     fn capture(&mut self) -> Result<ScreenFrame<'_>, ScreenCaptureError> {
-        self.capturer.capture()
+        match self {
+            Self::Display(capturer) => capturer.capture(),
+            Self::Spanning(capturer) => capturer.capture(),
+        }
     }
 
     fn size(&self) -> (u32, u32) {
-        self.capturer.size()
+        match self {
+            Self::Display(capturer) => capturer.size(),
+            Self::Spanning(capturer) => capturer.size(),
+        }
     }
 }
 
@@ -366,14 +592,7 @@ impl ScreenCapturer for DesktopDuplicator {
 // Screen Capture Manager
 // ============================================================================
 
-struct ScreenCaptureManager {
-    outputs: HashMap<CaptureKey, ManagedOutput>,
-}
-
-struct ManagedOutput {
-    duplicator: DesktopDuplicator,
-    ref_count: usize,
-}
+type ScreenCaptureManager = CaptureRegistry<CaptureKey, DesktopDuplicator>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct CaptureKey {
@@ -381,74 +600,16 @@ struct CaptureKey {
     output: usize,
 }
 
-impl ScreenCaptureManager {
-    fn new() -> Self {
-        Self {
-            outputs: HashMap::new(),
-        }
-    }
-
-    fn acquire(
-        &mut self,
-        method: CaptureMethod,
-        output_index: usize,
-    ) -> Result<(), ScreenCaptureError> {
-        let key = CaptureKey {
-            method,
-            output: output_index,
-        };
-
-        if let Some(entry) = self.outputs.get_mut(&key) {
-            entry.ref_count += 1;
-            return Ok(());
-        }
-
-        let duplicator = DesktopDuplicator::with_method_output(method, output_index)?;
-        self.outputs.insert(
-            key,
-            ManagedOutput {
-                duplicator,
-                ref_count: 1,
-            },
-        );
-        Ok(())
-    }
-
-    fn release(&mut self, key: CaptureKey) {
-        if let Some(entry) = self.outputs.get_mut(&key) {
-            if entry.ref_count > 1 {
-                entry.ref_count -= 1;
-                return;
-            }
-        }
-        self.outputs.remove(&key);
-    }
-
-    fn capture_with<F>(&mut self, key: CaptureKey, f: F) -> Result<bool, ScreenCaptureError>
-    where
-        F: FnOnce(&ScreenFrame<'_>),
-    {
-        let Some(entry) = self.outputs.get_mut(&key) else {
-            return Ok(false);
-        };
-
-        match entry.duplicator.capture() {
-            Ok(frame) => {
-                f(&frame);
-                Ok(true)
-            }
-            Err(err) => {
-                if matches!(err, ScreenCaptureError::InvalidState(_)) {
-                    self.outputs.remove(&key);
-                }
-                Err(err)
-            }
-        }
-    }
-
-    fn clear(&mut self) {
-        self.outputs.clear();
-    }
+fn acquire(
+    manager: &mut ScreenCaptureManager,
+    output_index: usize,
+) -> Result<CaptureLease<CaptureKey>, ScreenCaptureError> {
+    let method = get_capture_method();
+    let key = CaptureKey {
+        method,
+        output: output_index,
+    };
+    manager.acquire(key, || DesktopDuplicator::with_method_output(method, output_index))
 }
 
 static SCREEN_CAPTURE_MANAGER: OnceLock<Mutex<ScreenCaptureManager>> = OnceLock::new();
@@ -465,21 +626,24 @@ fn global_manager() -> &'static Mutex<ScreenCaptureManager> {
 #[derive(Debug)]
 pub struct ScreenSubscription {
     display_index: usize,
-    method: CaptureMethod,
-    generation: u64,
+    /// `None` while paused, or once the shared capturer was rebuilt; re-acquired lazily.
+    lease: Option<CaptureLease<CaptureKey>>,
+    paused: bool,
 }
 
 impl ScreenSubscription {
     pub fn new(display_index: usize) -> Result<Self, ScreenCaptureError> {
         let manager = global_manager();
         let mut guard = manager.lock().unwrap();
-        let method = get_capture_method();
-        let generation = CAPTURE_GEN.load(Ordering::Relaxed);
-        guard.acquire(method, display_index)?;
+        let lease = if is_capture_paused() {
+            None
+        } else {
+            Some(acquire(&mut guard, display_index)?)
+        };
         Ok(Self {
             display_index,
-            method,
-            generation,
+            lease,
+            paused: false,
         })
     }
 
@@ -487,39 +651,57 @@ impl ScreenSubscription {
         self.display_index
     }
 
+    /// Give up this subscription's share of the capturer (freeing it if this was the last
+    /// one) without dropping the subscription.
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.release();
+    }
+
+    /// The capturer is re-acquired on the next capture.
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// `Ok(false)` while paused (this subscription or globally) or without a capturer.
     pub fn capture_with<F>(&mut self, f: F) -> Result<bool, ScreenCaptureError>
     where
         F: FnOnce(&ScreenFrame<'_>),
     {
+        if self.paused {
+            return Ok(false);
+        }
+
         let manager = global_manager();
         let mut guard = manager.lock().unwrap();
-
-        let current_generation = CAPTURE_GEN.load(Ordering::Relaxed);
-        let current_method = get_capture_method();
-        if current_generation != self.generation || current_method != self.method {
-            guard.acquire(current_method, self.display_index)?;
-            self.generation = current_generation;
-            self.method = current_method;
+        if is_capture_paused() {
+            return Ok(false);
         }
 
-        let key = CaptureKey {
-            method: self.method,
-            output: self.display_index,
+        // Settings changes and lost capturers rebuild the entry, leaving this lease stale.
+        let lease = match self.lease.filter(|lease| guard.holds(lease)) {
+            Some(lease) => lease,
+            None => *self.lease.insert(acquire(&mut guard, self.display_index)?),
         };
 
-        guard.capture_with(key, f)
+        guard.capture_with(&lease, f)
+    }
+
+    fn release(&mut self) {
+        if let Some(lease) = self.lease.take() {
+            if let Ok(mut guard) = global_manager().lock() {
+                guard.release(lease);
+            }
+        }
     }
 }
 
 impl Drop for ScreenSubscription {
     fn drop(&mut self) {
-        let manager = global_manager();
-        if let Ok(mut guard) = manager.lock() {
-            let key = CaptureKey {
-                method: self.method,
-                output: self.display_index,
-            };
-            guard.release(key);
-        }
+        self.release();
     }
 }
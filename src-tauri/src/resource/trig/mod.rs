@@ -0,0 +1,174 @@
+//! Table-driven trigonometry for per-LED effect math.
+//!
+//! Effects evaluate sin/cos/atan2 for every LED on every tick. The libm versions are
+//! accurate far beyond what an 8-bit color channel can show, so hot paths use a
+//! precomputed sine table with linear interpolation instead (max error ~5e-6) and a
+//! polynomial `atan2` (max error ~1e-5).
+
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+/// Entries per full turn. Must be a power of two so the index can be masked.
+pub const SIN_TABLE_SIZE: usize = 1024;
+
+const STEPS_PER_RADIAN: f32 = SIN_TABLE_SIZE as f32 / TAU;
+
+const SIN_TABLE_MASK: usize = SIN_TABLE_SIZE - 1;
+
+/// One extra entry so interpolation at the last step never needs to wrap.
+static SIN_TABLE: [f32; SIN_TABLE_SIZE + 1] = build_sin_table();
+
+const fn build_sin_table() -> [f32; SIN_TABLE_SIZE + 1] {
+    let mut table = [0.0; SIN_TABLE_SIZE + 1];
+    let mut i = 0;
+    while i <= SIN_TABLE_SIZE {
+        table[i] = const_sin(i as f64 * std::f64::consts::TAU / SIN_TABLE_SIZE as f64) as f32;
+        i += 1;
+    }
+    table
+}
+
+/// Taylor series, good to well below f32 precision after reducing to `[-PI, PI]`.
+const fn const_sin(x: f64) -> f64 {
+    let x = if x > std::f64::consts::PI {
+        x - std::f64::consts::TAU
+    } else {
+        x
+    };
+    let x2 = x * x;
+    let mut term = x;
+    let mut sum = x;
+    let mut n = 1;
+    while n < 15 {
+        term = -term * x2 / ((2 * n) as f64 * (2 * n + 1) as f64);
+        sum += term;
+        n += 1;
+    }
+    sum
+}
+
+/// Sine of `x` radians. Accuracy drops once `|x|` reaches ~1e5, so callers should keep
+/// phases wrapped (e.g. `time % TAU`) rather than letting them grow forever.
+#[inline]
+pub fn sin(x: f32) -> f32 {
+    let pos = x * STEPS_PER_RADIAN;
+    // Floor without a libm call. At exact negative integers this lands one step low with
+    // `frac == 1.0`, which interpolates to the same value.
+    let step = pos as i32 - (pos < 0.0) as i32;
+    let frac = pos - step as f32;
+    let index = step as u32 as usize & SIN_TABLE_MASK;
+
+    let a = SIN_TABLE[index];
+    let b = SIN_TABLE[index + 1];
+    a + (b - a) * frac
+}
+
+/// Cosine of `x` radians.
+#[inline]
+pub fn cos(x: f32) -> f32 {
+    sin(x + FRAC_PI_2)
+}
+
+/// Four-quadrant arctangent of `y / x`, in `[-PI, PI]`, matching `f32::atan2` signs.
+#[inline]
+pub fn atan2(y: f32, x: f32) -> f32 {
+    if x == 0.0 && y == 0.0 {
+        return 0.0;
+    }
+
+    let ax = x.abs();
+    let ay = y.abs();
+    let swap = ay > ax;
+    let z = if swap { ax / ay } else { ay / ax };
+
+    let mut angle = atan_unit(z);
+    if swap {
+        angle = FRAC_PI_2 - angle;
+    }
+    if x < 0.0 {
+        angle = PI - angle;
+    }
+    if y < 0.0 {
+        -angle
+    } else {
+        angle
+    }
+}
+
+/// `atan(z)` for `z` in `[0, 1]` (minimax polynomial, 11th order).
+#[inline]
+fn atan_unit(z: f32) -> f32 {
+    let z2 = z * z;
+    z * (0.999_977_26
+        + z2 * (-0.332_623_47
+            + z2 * (0.193_543_46 + z2 * (-0.116_432_87 + z2 * (0.052_653_32 + z2 * -0.011_721_2)))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_ERROR: f32 = 0.001;
+
+    #[test]
+    fn sin_cos_match_libm() {
+        let mut max_err = 0.0f32;
+        for i in -20_000..=20_000 {
+            let x = i as f32 * 0.001_3;
+            max_err = max_err.max((sin(x) - x.sin()).abs());
+            max_err = max_err.max((cos(x) - x.cos()).abs());
+        }
+        assert!(max_err < MAX_ERROR, "max error {max_err}");
+    }
+
+    #[test]
+    fn atan2_matches_libm() {
+        let mut max_err = 0.0f32;
+        for yi in -100..=100 {
+            for xi in -100..=100 {
+                let (y, x) = (yi as f32 * 0.37, xi as f32 * 0.41);
+                if x == 0.0 && y == 0.0 {
+                    continue;
+                }
+                max_err = max_err.max((atan2(y, x) - y.atan2(x)).abs());
+            }
+        }
+        assert!(max_err < MAX_ERROR, "max error {max_err}");
+        assert_eq!(atan2(0.0, 0.0), 0.0);
+    }
+
+    /// Rough speed comparison on a 1024-LED tick. Run with
+    /// `cargo test --release trig -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_1024_led_tick() {
+        use std::hint::black_box;
+        use std::time::Instant;
+
+        const LEDS: usize = 1024;
+        const TICKS: usize = 2_000;
+
+        let run = |f: &dyn Fn(f32, f32) -> f32| {
+            let started = Instant::now();
+            let mut acc = 0.0;
+            for t in 0..TICKS {
+                let time = t as f32 * 0.016;
+                for i in 0..LEDS {
+                    let x = black_box((i % 32) as f32 - 16.0);
+                    let y = black_box((i / 32) as f32 - 16.0);
+                    acc += f(x * 0.2 + time, y);
+                }
+            }
+            black_box(acc);
+            started.elapsed()
+        };
+
+        let libm = run(&|x, y| x.sin() + (x * 0.5).cos() + y.atan2(x));
+        let table = run(&|x, y| sin(x) + cos(x * 0.5) + atan2(y, x));
+        println!(
+            "libm {:?}/tick, table {:?}/tick, speedup {:.2}x",
+            libm / TICKS as u32,
+            table / TICKS as u32,
+            libm.as_secs_f64() / table.as_secs_f64()
+        );
+    }
+}
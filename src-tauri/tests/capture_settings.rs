@@ -0,0 +1,20 @@
+//! `configure_capture` must report the values it actually applied.
+
+use light_lib::resource::screen::{configure_capture, get_capture_fps, get_capture_method};
+
+#[test]
+fn configure_capture_reports_clamped_values_and_rebuilds_once() {
+    let method = get_capture_method();
+
+    let applied = configure_capture(200_000, 200, method);
+    assert_eq!(applied.fps, 60);
+    assert_eq!(get_capture_fps(), 60);
+    assert_eq!(applied.max_pixels, 230_400, "snapped to the nearest preset");
+    assert_eq!(applied.method, method);
+
+    let again = configure_capture(230_400, 0, method);
+    assert_eq!(again.fps, 1);
+    assert!(!again.rebuilt, "nothing that needs a rebuild changed");
+
+    assert!(configure_capture(14_400, 30, method).rebuilt);
+}
@@ -0,0 +1,159 @@
+//! Hardware-free harness for effect and pipeline tests.
+//!
+//! `EffectHarness` builds any inventory-registered effect the way the manager does (factory,
+//! resize, default params), ticks it on a fixed clock and pushes every frame through a
+//! `TestController`, which records what a device would have received.
+
+#![allow(dead_code)]
+
+use light_lib::interface::controller::{
+    Color, Controller, OutputCapabilities, OutputPortDefinition, SegmentType,
+};
+use light_lib::interface::effect::Effect;
+use light_lib::manager::inventory::{create_effect, default_params_for_effect};
+use serde_json::Value;
+use std::time::Duration;
+
+/// A controller with one linear output that keeps every frame it is sent.
+pub struct TestController {
+    leds: usize,
+    pub frames: Vec<Vec<Color>>,
+}
+
+impl TestController {
+    pub fn new(leds: usize) -> Self {
+        Self {
+            leds,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn last_frame(&self) -> Option<&[Color]> {
+        self.frames.last().map(Vec::as_slice)
+    }
+}
+
+impl Controller for TestController {
+    fn port_name(&self) -> String {
+        "test".to_string()
+    }
+
+    fn model(&self) -> String {
+        "Test Controller".to_string()
+    }
+
+    fn description(&self) -> String {
+        "In-memory controller for tests".to_string()
+    }
+
+    fn serial_id(&self) -> String {
+        "test-0".to_string()
+    }
+
+    fn outputs(&self) -> Vec<OutputPortDefinition> {
+        vec![OutputPortDefinition {
+            id: "out".to_string(),
+            name: "Output".to_string(),
+            output_type: SegmentType::Linear,
+            leds_count: self.leds,
+            matrix: None,
+            capabilities: OutputCapabilities {
+                editable: false,
+                min_total_leds: self.leds,
+                max_total_leds: self.leds,
+                allowed_total_leds: None,
+                allowed_segment_types: vec![SegmentType::Linear],
+            },
+        }]
+    }
+
+    fn update(&mut self, colors: &[Color]) -> Result<(), String> {
+        if colors.len() != self.leds {
+            return Err(format!(
+                "Expected {} LEDs, got {}",
+                self.leds,
+                colors.len()
+            ));
+        }
+        self.frames.push(colors.to_vec());
+        Ok(())
+    }
+}
+
+/// Drives one effect on a `width` x `height` layout with a fixed time step.
+pub struct EffectHarness {
+    effect: Box<dyn Effect>,
+    buffer: Vec<Color>,
+    time: Duration,
+    step: Duration,
+    pub controller: TestController,
+}
+
+impl EffectHarness {
+    /// Panics if no effect with `id` is registered.
+    pub fn new(id: &str, width: usize, height: usize) -> Self {
+        let mut effect =
+            create_effect(id).unwrap_or_else(|| panic!("effect '{}' is not registered", id));
+        effect.resize(width, height);
+        if let Some(defaults) = default_params_for_effect(id) {
+            effect.update_params(Value::Object(defaults));
+        }
+
+        let leds = width * height;
+        Self {
+            effect,
+            buffer: vec![Color::default(); leds],
+            time: Duration::ZERO,
+            step: Duration::from_millis(100),
+            controller: TestController::new(leds),
+        }
+    }
+
+    /// Parameters applied on top of the defaults.
+    pub fn with_params(mut self, params: Value) -> Self {
+        self.effect.update_params(params);
+        self
+    }
+
+    /// Clock advance between ticks; the first tick is always at time zero.
+    pub fn with_step(mut self, step: Duration) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Start from a buffer filled with `color` instead of black, like a device that still
+    /// shows the previous effect.
+    pub fn with_buffer(mut self, color: Color) -> Self {
+        self.buffer.fill(color);
+        self
+    }
+
+    /// Tick `count` times, sending each frame to the controller. Returns all frames so far.
+    pub fn run(&mut self, count: usize) -> &[Vec<Color>] {
+        for _ in 0..count {
+            self.effect.tick(self.time, &mut self.buffer);
+            self.controller
+                .update(&self.buffer)
+                .expect("effect changed the buffer length");
+            self.time += self.step;
+        }
+        &self.controller.frames
+    }
+}
+
+/// `rrggbb` per LED, which keeps golden frames readable in assertions.
+pub fn hex(frame: &[Color]) -> Vec<String> {
+    frame
+        .iter()
+        .map(|c| format!("{:02x}{:02x}{:02x}", c.r, c.g, c.b))
+        .collect()
+}
+
+pub fn assert_frames(frames: &[Vec<Color>], expected: &[&[&str]]) {
+    let actual: Vec<Vec<String>> = frames.iter().map(|frame| hex(frame)).collect();
+    let expected: Vec<Vec<String>> = expected
+        .iter()
+        .map(|frame| frame.iter().map(|c| c.to_string()).collect())
+        .collect();
+    assert_eq!(actual, expected);
+}
@@ -0,0 +1,80 @@
+//! Golden frames for the deterministic built-in effects. A failure here means an effect's
+//! output changed; update the expectation only if that change is intended.
+
+mod common;
+
+use common::{assert_frames, EffectHarness};
+use light_lib::interface::controller::Color;
+use serde_json::json;
+use std::time::Duration;
+
+#[test]
+fn rainbow_scrolls_across_a_strip() {
+    let mut harness = EffectHarness::new("rainbow", 6, 1);
+    assert_frames(
+        harness.run(3),
+        &[
+            &["ff0000", "ffff00", "00ff00", "00ffff", "0000ff", "ff00ff"],
+            &["ff6a00", "94ff00", "00ff6a", "0094ff", "6a00ff", "ff0094"],
+            &["ffd400", "2aff00", "00ffd4", "002aff", "d400ff", "ff002a"],
+        ],
+    );
+}
+
+#[test]
+fn rainbow_shifts_each_matrix_row() {
+    let mut harness = EffectHarness::new("rainbow", 3, 2);
+    assert_frames(
+        harness.run(1),
+        &[&["ff0000", "00ff00", "0000ff", "ff5500", "00ff54", "5500ff"]],
+    );
+}
+
+#[test]
+fn monochrome_fills_with_the_configured_color() {
+    let mut white = EffectHarness::new("monochrome", 3, 1);
+    assert_frames(white.run(1), &[&["ffffff", "ffffff", "ffffff"]]);
+
+    let mut orange =
+        EffectHarness::new("monochrome", 3, 1).with_params(json!({ "color": "#ff8000" }));
+    assert_frames(orange.run(2), &[&["ff8000"; 3], &["ff8000"; 3]]);
+}
+
+#[test]
+fn matrix_test_draws_quadrants_with_a_moving_scan_line() {
+    let mut harness =
+        EffectHarness::new("matrix_test", 4, 4).with_step(Duration::from_millis(50));
+    assert_frames(
+        harness.run(3),
+        &[
+            &[
+                "ffffff", "ffffff", "ffffff", "ffffff", //
+                "ff0000", "ff0000", "00ff00", "00ff00", //
+                "0000ff", "0000ff", "ffffff", "ffffff", //
+                "0000ff", "0000ff", "ffffff", "ffffff",
+            ],
+            &[
+                "ff0000", "ff0000", "00ff00", "00ff00", //
+                "ffffff", "ffffff", "ffffff", "ffffff", //
+                "0000ff", "0000ff", "ffffff", "ffffff", //
+                "0000ff", "0000ff", "ffffff", "ffffff",
+            ],
+            &[
+                "ff0000", "ff0000", "00ff00", "00ff00", //
+                "ff0000", "ff0000", "00ff00", "00ff00", //
+                "ffffff", "ffffff", "ffffff", "ffffff", //
+                "0000ff", "0000ff", "ffffff", "ffffff",
+            ],
+        ],
+    );
+}
+
+#[test]
+fn turn_off_blanks_whatever_was_shown() {
+    let mut harness = EffectHarness::new("turn_off", 4, 1).with_buffer(Color {
+        r: 12,
+        g: 200,
+        b: 7,
+    });
+    assert_frames(harness.run(2), &[&["000000"; 4], &["000000"; 4]]);
+}
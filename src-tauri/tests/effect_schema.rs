@@ -0,0 +1,83 @@
+//! The generated param schemas must accept what the effects themselves use as params.
+
+use light_lib::api::schema::{effect_schema, SCHEMA_DIALECT};
+use light_lib::manager::inventory::{default_params_for_effect, get_effect_metadata, list_effects};
+use serde_json::{json, Map, Value};
+
+/// Checks `params` against the keywords `effect_schema` emits. Returns the first violation.
+fn validate(schema: &Value, params: &Value) -> Result<(), String> {
+    let properties = schema["properties"].as_object().expect("schema has properties");
+    let params = params.as_object().ok_or("params must be an object")?;
+
+    for (key, value) in params {
+        let property = properties
+            .get(key)
+            .ok_or_else(|| format!("unknown param '{}'", key))?;
+        let type_ok = match property["type"].as_str() {
+            Some("number") => value.is_number(),
+            Some("boolean") => value.is_boolean(),
+            Some("string") => value.is_string(),
+            other => return Err(format!("'{}' has unexpected type {:?}", key, other)),
+        };
+        if !type_ok {
+            return Err(format!("'{}' should be a {}", key, property["type"]));
+        }
+        if let (Some(min), Some(value)) = (property["minimum"].as_f64(), value.as_f64()) {
+            if value < min {
+                return Err(format!("'{}' is below {}", key, min));
+            }
+        }
+        if let (Some(max), Some(value)) = (property["maximum"].as_f64(), value.as_f64()) {
+            if value > max {
+                return Err(format!("'{}' is above {}", key, max));
+            }
+        }
+        if let Some(choices) = property["oneOf"].as_array() {
+            if !choices.iter().any(|choice| choice["const"].as_f64() == value.as_f64()) {
+                return Err(format!("'{}' is not one of the options", key));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn every_schema_accepts_its_own_defaults() {
+    for meta in list_effects() {
+        let schema = effect_schema(meta, None);
+        assert_eq!(schema["$schema"], SCHEMA_DIALECT);
+        let defaults: Map<String, Value> = schema["properties"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(key, property)| (key.clone(), property["default"].clone()))
+            .collect();
+        assert_eq!(defaults.len(), meta.params.len());
+        if let Err(err) = validate(&schema, &Value::Object(defaults)) {
+            panic!("effect '{}': {}", meta.id, err);
+        }
+    }
+}
+
+#[test]
+fn screen_mirror_schema_describes_kinds_ranges_and_dependencies() {
+    let schema = effect_schema(get_effect_metadata("screen_mirror").unwrap(), None);
+    let properties = &schema["properties"];
+
+    assert_eq!(properties["smoothness"]["x-kind"], "slider");
+    assert_eq!(properties["smoothness"]["minimum"], 0.0);
+    assert_eq!(properties["smoothness"]["maximum"], 100.0);
+    assert_eq!(properties["displayIndex"]["x-kind"], "select");
+    assert!(properties
+        .as_object()
+        .unwrap()
+        .values()
+        .any(|property| property["x-dependency"]["key"].is_string()));
+
+    let defaults = Value::Object(default_params_for_effect("screen_mirror").unwrap());
+    assert_eq!(validate(&schema, &defaults), Ok(()));
+    assert!(validate(&schema, &json!({ "smoothness": 40 })).is_ok());
+    assert!(validate(&schema, &json!({ "smoothness": 140 })).is_err());
+    assert!(validate(&schema, &json!({ "smoothness": "fast" })).is_err());
+    assert!(validate(&schema, &json!({ "noSuchParam": 1 })).is_err());
+}
@@ -2,10 +2,21 @@ import { ComponentType, useMemo } from 'react';
 import {
   // Used by effects (from backend)
   AudioLines,
+  ChartColumn,
   Monitor,
   Power,
   Waves,
   LayoutGrid,
+  Image,
+  Radar,
+  Lightbulb,
+  Activity,
+  Keyboard,
+  Blend,
+  Cloudy,
+  Flame,
+  Sparkles,
+  Star,
   // Fallback
   Component,
   // Other commonly used icons in the app
@@ -30,10 +41,21 @@ import {
 const ICON_MAP: Record<string, ComponentType<LucideProps>> = {
   // Effect icons (from backend)
   AudioLines,
+  ChartColumn,
   Monitor,
   Power,
   Waves,
   LayoutGrid,
+  Image,
+  Radar,
+  Lightbulb,
+  Activity,
+  Keyboard,
+  Blend,
+  Cloudy,
+  Flame,
+  Sparkles,
+  Star,
   // UI icons
   Component,
   Sun,
@@ -2,6 +2,7 @@ import { CloseButton, Dialog, HStack, Portal, Separator, Spinner, Stack, Text }
 import { useEffect, useState } from "react";
 import type { Device, OutputPort } from "../../../types";
 import { api } from "../../../services/api";
+import { OutputLedCountInput } from "./OutputLedCountInput";
 
 export interface DeviceConfigDialogProps {
   open: boolean;
@@ -31,6 +32,22 @@ export function DeviceConfigDialog({ open, onOpenChange, port, deviceName }: Dev
 
   // 筛选可编辑 segment 的 output
   const editableOutputs: OutputPort[] = device?.outputs.filter((o) => o.capabilities.editable) ?? [];
+  // 灯珠数量可调的 output（如 DRGB 各通道长度）
+  const resizableOutputs = editableOutputs.filter(
+    (o) => o.capabilities.min_total_leds !== o.capabilities.max_total_leds,
+  );
+
+  const handleLedsCountCommit = (outputId: string, ledsCount: number) => {
+    if (!port) return;
+    api
+      .setOutputLedsCount({ port, outputId, ledsCount })
+      .then(() => api.getDevice(port))
+      .then(setDevice)
+      .catch(() => {
+        // 失败时重新拉取，恢复为后端的实际值
+        api.getDevice(port).then(setDevice).catch(() => undefined);
+      });
+  };
 
   // 标题：有设备名时显示 "设备名 · 配置"，否则只显示 "配置"
   const title = deviceName ? `${deviceName} · 配置` : "配置";
@@ -51,6 +68,23 @@ export function DeviceConfigDialog({ open, onOpenChange, port, deviceName }: Dev
               <Dialog.Title>{title}</Dialog.Title>
             </Dialog.Header>
             <Dialog.Body>
+              {!loading && resizableOutputs.length > 0 && (
+                <>
+                  <HStack mb="3">
+                    <Text flexShrink="0" fontWeight="medium">灯珠数量</Text>
+                    <Separator flex="1" />
+                  </HStack>
+                  <Stack gap="2" mb="4">
+                    {resizableOutputs.map((output) => (
+                      <OutputLedCountInput
+                        key={output.id}
+                        output={output}
+                        onCommit={(count) => handleLedsCountCommit(output.id, count)}
+                      />
+                    ))}
+                  </Stack>
+                </>
+              )}
               <HStack mb="3">
                 <Text flexShrink="0" fontWeight="medium">Segment 编辑</Text>
                 <Separator flex="1" />
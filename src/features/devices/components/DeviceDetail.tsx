@@ -7,6 +7,7 @@ import {
   EffectInfo,
   EffectParam,
   EffectParamValue,
+  HsvValue,
   ScopeBrightnessState,
   ScopeModeState,
 } from "../../../types";
@@ -48,11 +49,22 @@ interface DisplayMode extends EffectInfo {
   category: ModeCategory;
 }
 
+function isHsvValue(value: unknown): value is HsvValue {
+  if (typeof value !== "object" || value === null) return false;
+  const { h, s, v } = value as Record<string, unknown>;
+  return typeof h === "number" && typeof s === "number" && typeof v === "number";
+}
+
 function buildParamState(effectId?: string, params?: Record<string, EffectParamValue>) {
   const initial: Record<string, EffectParamValue> = {};
   if (!effectId || !params) return initial;
   for (const [key, value] of Object.entries(params)) {
-    if (typeof value === "number" || typeof value === "boolean" || typeof value === "string") {
+    if (
+      typeof value === "number" ||
+      typeof value === "boolean" ||
+      typeof value === "string" ||
+      isHsvValue(value)
+    ) {
       initial[`${effectId}:${key}`] = value;
     }
   }
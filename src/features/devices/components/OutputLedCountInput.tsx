@@ -0,0 +1,60 @@
+import { useEffect, useState } from "react";
+import { HStack, Input, Text } from "@chakra-ui/react";
+import type { OutputPort } from "../../../types";
+
+interface OutputLedCountInputProps {
+  output: OutputPort;
+  disabled?: boolean;
+  onCommit: (ledsCount: number) => void;
+}
+
+/**
+ * 输出口灯珠数量输入：失焦或回车时才提交，超出能力范围的值会被钳制。
+ */
+export function OutputLedCountInput({ output, disabled, onCommit }: OutputLedCountInputProps) {
+  const { min_total_leds, max_total_leds } = output.capabilities;
+  const min = Math.max(1, min_total_leds);
+  const [text, setText] = useState(String(output.leds_count));
+
+  useEffect(() => {
+    setText(String(output.leds_count));
+  }, [output.leds_count]);
+
+  const commit = () => {
+    const parsed = Number.parseInt(text, 10);
+    if (Number.isNaN(parsed)) {
+      setText(String(output.leds_count));
+      return;
+    }
+    const next = Math.min(max_total_leds, Math.max(min, parsed));
+    setText(String(next));
+    if (next !== output.leds_count) onCommit(next);
+  };
+
+  return (
+    <HStack justify="space-between" gap="3">
+      <Text fontSize="sm" color="var(--text-secondary)">
+        {output.name}
+      </Text>
+      <HStack gap="2">
+        <Input
+          size="xs"
+          width="20"
+          type="number"
+          min={min}
+          max={max_total_leds}
+          value={text}
+          disabled={disabled}
+          onChange={(e) => setText(e.target.value)}
+          onBlur={commit}
+          onKeyDown={(e) => {
+            if (e.key === "Enter") commit();
+          }}
+        />
+        <Text fontSize="xs" color="var(--text-tertiary)">
+          LED
+        </Text>
+      </HStack>
+    </HStack>
+  );
+}
@@ -0,0 +1,69 @@
+import { Box, HStack, Slider, Stack, Text } from "@chakra-ui/react";
+import { Palette } from "lucide-react";
+import { HsvParam, HsvValue } from "../../../../types";
+
+interface HsvRendererProps {
+  param: HsvParam;
+  value: HsvValue;
+  disabled: boolean;
+  onChange: (value: HsvValue) => void;
+  onCommit: (value: HsvValue) => void;
+}
+
+const CHANNELS: { key: keyof HsvValue; label: string; max: number; step: number }[] = [
+  { key: "h", label: "H", max: 360, step: 1 },
+  { key: "s", label: "S", max: 1, step: 0.01 },
+  { key: "v", label: "V", max: 1, step: 0.01 },
+];
+
+/**
+ * 纯渲染组件：H/S/V 三个滑条，每次变更都回传完整的 HSV 对象。
+ * draft 状态由上层 ParamRenderer 统一管理。
+ */
+export function HsvRenderer({ param, value, disabled, onChange, onCommit }: HsvRendererProps) {
+  // HSV -> HSL，仅用于预览色块
+  const l = value.v * (1 - value.s / 2);
+  const sl = l === 0 || l === 1 ? 0 : (value.v - l) / Math.min(l, 1 - l);
+  const preview = `hsl(${value.h}, ${Math.round(sl * 100)}%, ${Math.round(l * 100)}%)`;
+
+  return (
+    <Stack gap="2">
+      <HStack justify="space-between">
+        <HStack gap="1.5">
+          <Palette size={16} />
+          <Text>{param.label}</Text>
+        </HStack>
+        <Box w="4" h="4" borderRadius="sm" borderWidth="1px" style={{ background: preview }} />
+      </HStack>
+      {CHANNELS.map((channel) => (
+        <Slider.Root
+          key={channel.key}
+          min={0}
+          max={channel.max}
+          step={channel.step}
+          value={[value[channel.key]]}
+          onValueChange={(d) => onChange({ ...value, [channel.key]: d.value[0] })}
+          onValueChangeEnd={(d) => onCommit({ ...value, [channel.key]: d.value[0] })}
+          disabled={disabled}
+        >
+          <HStack justify="space-between">
+            <Slider.Label>
+              <Text fontSize="sm">{channel.label}</Text>
+            </Slider.Label>
+            <Slider.ValueText>
+              {channel.step < 1
+                ? value[channel.key].toFixed(2)
+                : Math.round(value[channel.key]).toString()}
+            </Slider.ValueText>
+          </HStack>
+          <Slider.Control>
+            <Slider.Track>
+              <Slider.Range />
+            </Slider.Track>
+            <Slider.Thumbs />
+          </Slider.Control>
+        </Slider.Root>
+      ))}
+    </Stack>
+  );
+}
@@ -1,8 +1,11 @@
 import { useEffect, useState } from "react";
-import { EffectParam, EffectParamValue } from "../../../../types";
+import { EffectParam, EffectParamValue, HsvValue } from "../../../../types";
 import { ColorRenderer } from "./ColorRenderer";
+import { HsvRenderer } from "./HsvRenderer";
 import { SelectRenderer } from "./SelectRenderer";
 import { SliderRenderer } from "./SliderRenderer";
+import { TemperatureRenderer } from "./TemperatureRenderer";
+import { TextRenderer } from "./TextRenderer";
 import { ToggleRenderer } from "./ToggleRenderer";
 
 interface ParamRendererProps {
@@ -84,6 +87,35 @@ export function ParamRenderer({ param, value, disabled, onChange, onCommit }: Pa
           onCommit={handleCommit as (v: string) => void}
         />
       );
+    case "text":
+      return (
+        <TextRenderer
+          param={param}
+          value={draft as string}
+          disabled={disabled}
+          onCommit={handleCommit as (v: string) => void}
+        />
+      );
+    case "hsv":
+      return (
+        <HsvRenderer
+          param={param}
+          value={draft as HsvValue}
+          disabled={disabled}
+          onChange={handleChange as (v: HsvValue) => void}
+          onCommit={handleCommit as (v: HsvValue) => void}
+        />
+      );
+    case "temperature":
+      return (
+        <TemperatureRenderer
+          param={param}
+          value={draft as number}
+          disabled={disabled}
+          onChange={handleChange as (v: number) => void}
+          onCommit={handleCommit as (v: number) => void}
+        />
+      );
     default:
       console.warn(`No renderer found for param type: ${(param as EffectParam).type}`);
       return null;
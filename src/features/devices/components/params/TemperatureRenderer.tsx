@@ -0,0 +1,54 @@
+import { HStack, Slider, Text } from "@chakra-ui/react";
+import { Thermometer } from "lucide-react";
+import { TemperatureParam } from "../../../../types";
+
+interface TemperatureRendererProps {
+  param: TemperatureParam;
+  value: number;
+  disabled: boolean;
+  onChange: (value: number) => void;
+  onCommit: (value: number) => void;
+}
+
+/** 每格 100K；色温本身不需要更细的精度 */
+const KELVIN_STEP = 100;
+
+/**
+ * 纯渲染组件：色温滑条，单位 K。
+ * draft 状态由上层 ParamRenderer 统一管理。
+ */
+export function TemperatureRenderer({
+  param,
+  value,
+  disabled,
+  onChange,
+  onCommit,
+}: TemperatureRendererProps) {
+  return (
+    <Slider.Root
+      min={param.min}
+      max={param.max}
+      step={KELVIN_STEP}
+      value={[value]}
+      onValueChange={(d) => onChange(d.value[0])}
+      onValueChangeEnd={(d) => onCommit(d.value[0])}
+      disabled={disabled}
+    >
+      <HStack justify="space-between">
+        <Slider.Label>
+          <HStack gap="1.5">
+            <Thermometer size={16} />
+            <Text>{param.label}</Text>
+          </HStack>
+        </Slider.Label>
+        <Slider.ValueText>{Math.round(value)}K</Slider.ValueText>
+      </HStack>
+      <Slider.Control>
+        <Slider.Track>
+          <Slider.Range />
+        </Slider.Track>
+        <Slider.Thumbs />
+      </Slider.Control>
+    </Slider.Root>
+  );
+}
@@ -0,0 +1,47 @@
+import { useEffect, useState } from "react";
+import { Field, Input } from "@chakra-ui/react";
+import { Type } from "lucide-react";
+import { TextParam } from "../../../../types";
+
+interface TextRendererProps {
+  param: TextParam;
+  value: string;
+  disabled: boolean;
+  onCommit: (value: string) => void;
+}
+
+/**
+ * 纯渲染组件：输入过程只更新本地文本，失焦或回车时才 commit，
+ * 避免每个按键都触发后端（例如 URL 重新抓取）。
+ */
+export function TextRenderer({ param, value, disabled, onCommit }: TextRendererProps) {
+  const [text, setText] = useState(value);
+
+  useEffect(() => {
+    setText(value);
+  }, [value]);
+
+  const commit = () => {
+    if (text !== value) onCommit(text);
+  };
+
+  return (
+    <Field.Root disabled={disabled}>
+      <Field.Label>
+        <span style={{ display: "flex", alignItems: "center", gap: "6px" }}>
+          <Type size={16} /> {param.label}
+        </span>
+      </Field.Label>
+      <Input
+        size="sm"
+        value={text}
+        placeholder={param.placeholder}
+        onChange={(e) => setText(e.target.value)}
+        onBlur={commit}
+        onKeyDown={(e) => {
+          if (e.key === "Enter") commit();
+        }}
+      />
+    </Field.Root>
+  );
+}
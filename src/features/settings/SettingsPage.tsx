@@ -138,6 +138,9 @@ export function SettingsPage() {
   const [captureFps, setCaptureFps] = useState<number>(30);
   const [captureMethod, setCaptureMethod] = useState<CaptureMethod>(isWindows ? "dxgi" : "xcap");
   const [loading, setLoading] = useState(true);
+  const [clearLedsOnExit, setClearLedsOnExit] = useState(true);
+  const [partialUpdates, setPartialUpdates] = useState(true);
+  const [adaptiveFrameRate, setAdaptiveFrameRate] = useState(true);
   const [windowEffect, setWindowEffect] = useState<WindowEffectId | "">("");
   const [availableWindowEffects, setAvailableWindowEffects] = useState<WindowEffectId[]>([]);
   const [systemInfo, setSystemInfo] = useState<SystemInfo | null>(null);
@@ -204,6 +207,9 @@ export function SettingsPage() {
       setCaptureFps(fps);
       lastSyncedFpsRef.current = fps;
       setCaptureMethod(method);
+      setClearLedsOnExit(cfg.clearLedsOnExit);
+      setPartialUpdates(cfg.partialUpdates);
+      setAdaptiveFrameRate(cfg.adaptiveFrameRate);
       setAvailableWindowEffects(windowEffects);
 
       if (windowEffects.length > 0) {
@@ -253,6 +259,27 @@ export function SettingsPage() {
     };
   }, []);
 
+  const handleClearLedsOnExitChange = (enabled: boolean) => {
+    setClearLedsOnExit(enabled);
+    configManager.setClearLedsOnExit(enabled).catch((err) => {
+      logger.error("settings.clearLedsOnExit.set_failed", { enabled }, err);
+    });
+  };
+
+  const handlePartialUpdatesChange = (enabled: boolean) => {
+    setPartialUpdates(enabled);
+    configManager.setPartialUpdates(enabled).catch((err) => {
+      logger.error("settings.partialUpdates.set_failed", { enabled }, err);
+    });
+  };
+
+  const handleAdaptiveFrameRateChange = (enabled: boolean) => {
+    setAdaptiveFrameRate(enabled);
+    configManager.setAdaptiveFrameRate(enabled).catch((err) => {
+      logger.error("settings.adaptiveFrameRate.set_failed", { enabled }, err);
+    });
+  };
+
   const handleMethodChange = (value: CaptureMethod) => {
     qualityLive.cancel();
     fpsLive.cancel();
@@ -396,6 +423,33 @@ export function SettingsPage() {
               description="启用后，最小化/关闭将隐藏窗口并保留托盘入口；点击托盘图标可恢复窗口。"
             />
           </div>
+
+          <div className="setting-section">
+            <Switch
+              checked={clearLedsOnExit}
+              onChange={handleClearLedsOnExitChange}
+              label="退出时关闭灯光"
+              description="启用后，退出应用时会熄灭所有设备的灯光；关闭则保留最后一帧。"
+            />
+          </div>
+
+          <div className="setting-section">
+            <Switch
+              checked={partialUpdates}
+              onChange={handlePartialUpdatesChange}
+              label="增量刷新"
+              description="仅向支持的设备发送变化的灯珠，降低带宽占用；若出现残影可关闭以始终发送整帧。"
+            />
+          </div>
+
+          <div className="setting-section">
+            <Switch
+              checked={adaptiveFrameRate}
+              onChange={handleAdaptiveFrameRateChange}
+              label="自适应帧率"
+              description="设备连接跟不上时自动降低其刷新帧率，避免计算注定被丢弃的帧。"
+            />
+          </div>
         </Card>
 
         <Card className="settings-card">
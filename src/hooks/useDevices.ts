@@ -1,5 +1,6 @@
 import { useState, useEffect, useCallback } from "react";
-import type { Device, SelectedScope } from "../types";
+import { listen } from "@tauri-apps/api/event";
+import type { CaptureErrorEvent, CaptureFailure, Device, DeviceStatus, SelectedScope } from "../types";
 import { api } from "../services/api";
 import { logger } from "../services/logger";
 import { normalizeSelectedScope } from "../utils/scope";
@@ -9,6 +10,8 @@ export function useDevices() {
   const [selectedScope, setSelectedScope] = useState<SelectedScope | null>(null);
   const [isScanning, setIsScanning] = useState(false);
   const [statusMsg, setStatusMsg] = useState("Ready");
+  // Keyed by `port/outputId/segmentId`; only failing targets have an entry.
+  const [captureErrors, setCaptureErrors] = useState<Record<string, CaptureFailure>>({});
 
   const selectScope = useCallback(
     (scope: SelectedScope | null) => {
@@ -88,6 +91,120 @@ export function useDevices() {
     }
   }, []);
 
+  // Devices are announced one by one while a scan is still running.
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    let mounted = true;
+
+    listen<Device>("device-discovered", (event) => {
+      const found = event.payload;
+      setDevices((prev) => {
+        const idx = prev.findIndex((d) => d.port === found.port);
+        if (idx < 0) return [...prev, found];
+        const next = prev.slice();
+        next[idx] = found;
+        return next;
+      });
+      setSelectedScope((prev) => prev ?? { port: found.port });
+    })
+      .then((fn) => {
+        if (mounted) unlisten = fn;
+        else fn();
+      })
+      .catch((err) => logger.error("devices.listener.init_failed", {}, err));
+
+    return () => {
+      mounted = false;
+      if (unlisten) unlisten();
+    };
+  }, []);
+
+  // Runner reports hardware write failures / recoveries.
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    let mounted = true;
+
+    listen<{ port: string; status: DeviceStatus }>("device-status-changed", (event) => {
+      const { port, status } = event.payload;
+      setDevices((prev) => {
+        const idx = prev.findIndex((d) => d.port === port);
+        if (idx < 0) return prev;
+        const next = prev.slice();
+        next[idx] = { ...prev[idx], status };
+        return next;
+      });
+    })
+      .then((fn) => {
+        if (mounted) unlisten = fn;
+        else fn();
+      })
+      .catch((err) => logger.error("devices.status_listener.init_failed", {}, err));
+
+    return () => {
+      mounted = false;
+      if (unlisten) unlisten();
+    };
+  }, []);
+
+  // A runner lost a device and the manager found it again (e.g. a strip was replugged); its
+  // outputs may have been resynced from the new handle, and it may be back under a new port.
+  useEffect(() => {
+    let unlisteners: (() => void)[] = [];
+    let mounted = true;
+
+    Promise.all([
+      listen<{ port: string }>("device-lost", (event) => {
+        logger.warn("devices.lost", { port: event.payload.port });
+      }),
+      listen<{ port: string; previousPort: string | null }>("device-reconnected", (event) => {
+        const { port, previousPort } = event.payload;
+        if (!previousPort) {
+          void refreshDevice(port);
+          return;
+        }
+        setSelectedScope((prev) => (prev?.port === previousPort ? { ...prev, port } : prev));
+        void refreshDevices();
+      }),
+    ])
+      .then((fns) => {
+        if (mounted) unlisteners = fns;
+        else fns.forEach((fn) => fn());
+      })
+      .catch((err) => logger.error("devices.connection_listener.init_failed", {}, err));
+
+    return () => {
+      mounted = false;
+      unlisteners.forEach((fn) => fn());
+    };
+  }, [refreshDevice, refreshDevices]);
+
+  // Runner reports screen capture failures / recoveries per target.
+  useEffect(() => {
+    let unlisten: (() => void) | null = null;
+    let mounted = true;
+
+    listen<CaptureErrorEvent>("capture-error", (event) => {
+      const { port, outputId, segmentId, error } = event.payload;
+      const key = `${port}/${outputId}/${segmentId ?? ""}`;
+      setCaptureErrors((prev) => {
+        const next = { ...prev };
+        if (error) next[key] = error;
+        else delete next[key];
+        return next;
+      });
+    })
+      .then((fn) => {
+        if (mounted) unlisten = fn;
+        else fn();
+      })
+      .catch((err) => logger.error("devices.capture_listener.init_failed", {}, err));
+
+    return () => {
+      mounted = false;
+      if (unlisten) unlisten();
+    };
+  }, []);
+
   // Initial scan
   useEffect(() => {
     scanDevices();
@@ -99,6 +216,7 @@ export function useDevices() {
     selectScope,
     isScanning,
     statusMsg,
+    captureErrors,
     scanDevices,
     refreshDevices,
     refreshDevice,
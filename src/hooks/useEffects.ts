@@ -10,7 +10,7 @@ export function useEffects() {
   const [error, setError] = useState<Error | null>(null);
 
   useEffect(() => {
-    api.getEffects()
+    api.getEffects(navigator.language)
       .then((data) => {
         setEffects(sortEffects(data));
         setLoading(false);
@@ -1,5 +1,25 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { AppConfig, Device, DeviceConfigResponse, EffectInfo } from "../types";
+import type {
+  AppConfig,
+  AudioBrightnessSettings,
+  BlendMode,
+  ColorOrder,
+  ColorVisionMode,
+  CropRect,
+  Device,
+  DeviceConfigResponse,
+  DrgbKeepaliveConfig,
+  EffectGroupInfo,
+  EffectInfo,
+  HotkeyBinding,
+  HotkeyRegistrationFailure,
+  LedColor,
+  LedPosition,
+  OutputFilter,
+  Rotation,
+  SceneSummary,
+  ToneMapping,
+} from "../types";
 import { logger } from "./logger";
 
 export type CaptureMethod = "dxgi" | "gdi" | "graphics" | "xcap" | "screencapturekit";
@@ -12,6 +32,40 @@ export interface SystemInfo {
   arch: string;
 }
 
+/** Capture settings as the backend applied them, after clamping. */
+export interface CaptureSettings {
+  maxPixels: number;
+  fps: number;
+  method: CaptureMethod;
+  /** Whether live capturers were rebuilt to pick up the change. */
+  rebuilt: boolean;
+}
+
+export interface CaptureBenchmarkReport {
+  displayIndex: number;
+  backend: CaptureMethod;
+  hardwareAcceleration: boolean;
+  durationMs: number;
+  frames: number;
+  missedFrames: number;
+  fps: number;
+  avgFrameMs: number;
+  avgCaptureMs: number;
+  avgRenderMs: number | null;
+  frameWidth: number;
+  frameHeight: number;
+}
+
+export interface PreviewImage {
+  /** Base64-encoded PNG, e.g. for a `data:image/png;base64,` URL. */
+  pngBase64: string;
+  width: number;
+  height: number;
+  /** Size of the captured frame before it was scaled down. */
+  frameWidth: number;
+  frameHeight: number;
+}
+
 async function invokeWithLog<T>(
   command: string,
   args?: Record<string, unknown>,
@@ -30,6 +84,28 @@ export const api = {
     return await invokeWithLog<Device[]>("scan_devices");
   },
 
+  listControllerKinds: async (): Promise<string[]> => {
+    return await invokeWithLog<string[]>("list_controller_kinds");
+  },
+
+  rescanControllerKind: async (name: string): Promise<Device[]> => {
+    return await invokeWithLog<Device[]>("rescan_controller_kind", { name }, { name });
+  },
+
+  /** Add an E1.31 (sACN) node; a blank `ip` sends to each universe's multicast group. */
+  addSacnDevice: async (ip: string | null, universe: number, ledCount: number): Promise<Device> => {
+    return await invokeWithLog<Device>(
+      "add_sacn_device",
+      { ip, universe, ledCount },
+      { ip, universe, ledCount },
+    );
+  },
+
+  /** Remove an sACN node added with `addSacnDevice`; scans no longer set it up. */
+  removeSacnDevice: async (port: string): Promise<void> => {
+    return await invokeWithLog("remove_sacn_device", { port }, { port });
+  },
+
   getDevices: async (): Promise<Device[]> => {
     return await invokeWithLog<Device[]>("get_devices");
   },
@@ -38,8 +114,88 @@ export const api = {
     return await invokeWithLog<Device>("get_device", { port }, { port });
   },
 
-  getEffects: async (): Promise<EffectInfo[]> => {
-    return await invokeWithLog<EffectInfo[]>("get_effects");
+  getMdnsTimeoutMs: async (): Promise<number> => {
+    return await invokeWithLog<number>("get_mdns_timeout_ms");
+  },
+
+  setMdnsTimeoutMs: async (timeoutMs: number): Promise<void> => {
+    return await invokeWithLog("set_mdns_timeout_ms", { timeoutMs }, { timeoutMs });
+  },
+
+  getFrameAcks: async (): Promise<boolean> => {
+    return await invokeWithLog<boolean>("get_frame_acks");
+  },
+
+  setFrameAcks: async (enabled: boolean): Promise<void> => {
+    return await invokeWithLog("set_frame_acks", { enabled }, { enabled });
+  },
+
+  getUdpPayloadBytes: async (): Promise<number> => {
+    return await invokeWithLog<number>("get_udp_payload_bytes");
+  },
+
+  setUdpPayloadBytes: async (bytes: number): Promise<void> => {
+    return await invokeWithLog("set_udp_payload_bytes", { bytes }, { bytes });
+  },
+
+  getNetworkSourceAddress: async (): Promise<string | null> => {
+    return await invokeWithLog<string | null>("get_network_source_address");
+  },
+
+  /** Applies from the next scan; `null` restores OS routing. */
+  setNetworkSourceAddress: async (address: string | null): Promise<void> => {
+    return await invokeWithLog("set_network_source_address", { address }, { address });
+  },
+
+  getDrgbKeepalive: async (): Promise<DrgbKeepaliveConfig> => {
+    return await invokeWithLog<DrgbKeepaliveConfig>("get_drgb_keepalive");
+  },
+
+  setDrgbKeepalive: async (intervalMs: number, idleThresholdMs: number): Promise<void> => {
+    return await invokeWithLog(
+      "set_drgb_keepalive",
+      { intervalMs, idleThresholdMs },
+      { intervalMs, idleThresholdMs },
+    );
+  },
+
+  getEffects: async (locale?: string): Promise<EffectInfo[]> => {
+    return await invokeWithLog<EffectInfo[]>("get_effects", { locale }, { locale });
+  },
+
+  /** JSON Schema (draft 2020-12) of an effect's params. */
+  getEffectSchema: async (effectId: string, locale?: string): Promise<Record<string, unknown>> => {
+    return await invokeWithLog<Record<string, unknown>>(
+      "get_effect_schema",
+      { effectId, locale },
+      { effectId, locale },
+    );
+  },
+
+  getEffectGroups: async (args?: { query?: string; locale?: string }): Promise<EffectGroupInfo[]> => {
+    const { query, locale } = args ?? {};
+    return await invokeWithLog<EffectGroupInfo[]>("get_effect_groups", { query, locale }, { query, locale });
+  },
+
+  runCaptureBenchmark: async (args: {
+    displayIndex: number;
+    seconds: number;
+    includeRender?: boolean;
+  }): Promise<CaptureBenchmarkReport> => {
+    const { displayIndex, seconds, includeRender } = args;
+    return await invokeWithLog<CaptureBenchmarkReport>(
+      "run_capture_benchmark",
+      { displayIndex, seconds, includeRender },
+      { displayIndex, seconds, includeRender }
+    );
+  },
+
+  capturePreview: async (displayIndex: number): Promise<PreviewImage> => {
+    return await invokeWithLog<PreviewImage>(
+      "capture_preview",
+      { displayIndex },
+      { displayIndex }
+    );
   },
 
   setEffect: async (port: string, effectId: string): Promise<void> => {
@@ -78,6 +234,235 @@ export const api = {
     );
   },
 
+  /** Fill the whole device with one color; resolves to the updated device. */
+  setDeviceSolidColor: async (port: string, color: LedColor): Promise<Device> => {
+    return await invokeWithLog<Device>("set_device_solid_color", { port, color }, { port, color });
+  },
+
+  /** Turn the whole device off; resolves to the updated device. */
+  clearDevice: async (port: string): Promise<Device> => {
+    return await invokeWithLog<Device>("clear_device", { port }, { port });
+  },
+
+  /** Select one effect on every matching output; resolves to the ids that changed. */
+  applyEffectToOutputs: async (args: {
+    port: string;
+    filter: OutputFilter;
+    effectId: string;
+    params?: Record<string, unknown>;
+  }): Promise<string[]> => {
+    const { port, filter, effectId, params } = args;
+    return await invokeWithLog<string[]>(
+      "apply_effect_to_outputs",
+      { port, filter, effectId, params },
+      { port, filter, effectId }
+    );
+  },
+
+  /** Pass `overlay: null` to remove the scope's overlay. */
+  setScopeOverlay: async (args: {
+    port: string;
+    outputId?: string;
+    segmentId?: string;
+    overlay: { effectId: string; opacity: number; blend: BlendMode } | null;
+  }): Promise<void> => {
+    const { port, outputId, segmentId, overlay } = args;
+    return await invokeWithLog(
+      "set_scope_overlay",
+      { port, outputId, segmentId, overlay },
+      { port, outputId, segmentId, overlay }
+    );
+  },
+
+  updateScopeOverlayParams: async (args: {
+    port: string;
+    outputId?: string;
+    segmentId?: string;
+    params: Record<string, unknown>;
+  }): Promise<void> => {
+    const { port, outputId, segmentId, params } = args;
+    return await invokeWithLog(
+      "update_scope_overlay_params",
+      { port, outputId, segmentId, params },
+      { port, outputId, segmentId }
+    );
+  },
+
+  /** Shift a scope's effect on the shared animation clock (0..=60000 ms). */
+  setScopePhaseOffset: async (args: {
+    port: string;
+    outputId?: string;
+    segmentId?: string;
+    offsetMs: number;
+  }): Promise<void> => {
+    const { port, outputId, segmentId, offsetMs } = args;
+    return await invokeWithLog(
+      "set_scope_phase_offset",
+      { port, outputId, segmentId, offsetMs },
+      { port, outputId, segmentId, offsetMs }
+    );
+  },
+
+  /** Modulate a scope's brightness with audio loudness; `settings: null` turns it off. */
+  setScopeAudioBrightness: async (args: {
+    port: string;
+    outputId?: string;
+    segmentId?: string;
+    settings: AudioBrightnessSettings | null;
+  }): Promise<void> => {
+    const { port, outputId, segmentId, settings } = args;
+    return await invokeWithLog(
+      "set_scope_audio_brightness",
+      { port, outputId, segmentId, settings },
+      { port, outputId, segmentId, settings }
+    );
+  },
+
+  setOutputLedsCount: async (args: {
+    port: string;
+    outputId: string;
+    ledsCount: number;
+  }): Promise<void> => {
+    const { port, outputId, ledsCount } = args;
+    return await invokeWithLog(
+      "set_output_leds_count",
+      { port, outputId, ledsCount },
+      { port, outputId, ledsCount },
+    );
+  },
+
+  setOutputLedPositions: async (args: {
+    port: string;
+    outputId: string;
+    /** `null` clears the layout and returns to index order. */
+    positions: LedPosition[] | null;
+  }): Promise<void> => {
+    const { port, outputId, positions } = args;
+    return await invokeWithLog(
+      "set_output_led_positions",
+      { port, outputId, positions },
+      { port, outputId, count: positions?.length ?? null },
+    );
+  },
+
+  /** `null` makes the output follow the app-wide soft-start setting again. */
+  setOutputSoftStart: async (args: {
+    port: string;
+    outputId: string;
+    softStartMs: number | null;
+  }): Promise<void> => {
+    const { port, outputId, softStartMs } = args;
+    return await invokeWithLog(
+      "set_output_soft_start",
+      { port, outputId, softStartMs },
+      { port, outputId, softStartMs },
+    );
+  },
+
+  /** 导出设备布局（分段、变换、各层级效果与参数），用于分享给相同硬件 */
+  exportDeviceLayout: async (port: string): Promise<Record<string, unknown>> => {
+    return await invokeWithLog<Record<string, unknown>>("export_device_layout", { port }, { port });
+  },
+
+  /** 布局与设备输出不匹配时整体拒绝，不会部分应用 */
+  importDeviceLayout: async (port: string, layout: Record<string, unknown>): Promise<void> => {
+    return await invokeWithLog("import_device_layout", { port, layout }, { port });
+  },
+
+  setOutputTransform: async (args: {
+    port: string;
+    outputId: string;
+    rotation: Rotation;
+    flipH: boolean;
+    flipV: boolean;
+  }): Promise<void> => {
+    const { port, outputId, rotation, flipH, flipV } = args;
+    return await invokeWithLog(
+      "set_output_transform",
+      { port, outputId, rotation, flipH, flipV },
+      { port, outputId, rotation, flipH, flipV },
+    );
+  },
+
+  /** Snapshot every connected device's config; resolves to how many were saved. */
+  persistState: async (): Promise<number> => {
+    return await invokeWithLog<number>("persist_state");
+  },
+
+  /** Apply the last snapshot to the connected devices; resolves to the restored ports. */
+  restoreState: async (): Promise<string[]> => {
+    return await invokeWithLog<string[]>("restore_state");
+  },
+
+  /** Record a device's frames to a file in the log dir; resolves to its path. */
+  startFrameRecording: async (port: string): Promise<string> => {
+    return await invokeWithLog<string>("start_frame_recording", { port }, { port });
+  },
+
+  /** Resolves to the last file written, or null if the device wasn't recording. */
+  stopFrameRecording: async (port: string): Promise<string | null> => {
+    return await invokeWithLog<string | null>("stop_frame_recording", { port }, { port });
+  },
+
+  setOutputEnabled: async (args: {
+    port: string;
+    outputId: string;
+    enabled: boolean;
+  }): Promise<void> => {
+    const { port, outputId, enabled } = args;
+    return await invokeWithLog(
+      "set_output_enabled",
+      { port, outputId, enabled },
+      { port, outputId, enabled },
+    );
+  },
+
+  /** `null` makes the device follow the app-wide color-blindness correction again. */
+  setDeviceColorVision: async (port: string, mode: ColorVisionMode | null): Promise<void> => {
+    return await invokeWithLog("set_device_color_vision", { port, mode }, { port, mode });
+  },
+
+  /** `smoothing` is the share of the previous frame kept per 60 Hz frame, 0 to 0.98. */
+  setDeviceSmoothing: async (port: string, smoothing: number): Promise<void> => {
+    return await invokeWithLog("set_device_smoothing", { port, smoothing }, { port, smoothing });
+  },
+
+  /** Cap the current the device's LEDs may draw; `null` removes the cap. */
+  setDevicePowerLimit: async (port: string, maxMilliamps: number | null): Promise<void> => {
+    return await invokeWithLog(
+      "set_device_power_limit",
+      { port, maxMilliamps },
+      { port, maxMilliamps }
+    );
+  },
+
+  /** Fails on devices whose driver fixes the order, or doesn't support `order`. */
+  setDeviceColorOrder: async (port: string, order: ColorOrder): Promise<void> => {
+    return await invokeWithLog("set_device_color_order", { port, order }, { port, order });
+  },
+
+  getColorVision: async (): Promise<ColorVisionMode> => {
+    return await invokeWithLog<ColorVisionMode>("get_color_vision");
+  },
+
+  setColorVision: async (mode: ColorVisionMode): Promise<void> => {
+    return await invokeWithLog("set_color_vision", { mode }, { mode });
+  },
+
+  /** Moves where each segment's LEDs start; `newOrder` must list every segment id once. */
+  reorderSegments: async (port: string, outputId: string, newOrder: string[]): Promise<void> => {
+    return await invokeWithLog(
+      "reorder_segments",
+      { port, outputId, newOrder },
+      { port, outputId, newOrder },
+    );
+  },
+
+  /** Presentation order only; `order` must list every output id of the device once. */
+  setOutputOrder: async (port: string, order: string[]): Promise<void> => {
+    return await invokeWithLog("set_output_order", { port, order }, { port, order });
+  },
+
   setBrightness: async (port: string, brightness: number): Promise<void> => {
     // Legacy device-level brightness.
     return await invokeWithLog("set_brightness", { port, brightness }, { port, brightness });
@@ -125,6 +510,85 @@ export const api = {
     return await invokeWithLog("set_capture_method", { method }, { method });
   },
 
+  configureCapture: async (
+    maxPixels: number,
+    fps: number,
+    method?: CaptureMethod,
+  ): Promise<CaptureSettings> => {
+    return await invokeWithLog<CaptureSettings>(
+      "configure_capture",
+      { maxPixels, fps, method },
+      { maxPixels, fps, method },
+    );
+  },
+
+  getCaptureHighBitDepth: async (): Promise<boolean> => {
+    return await invokeWithLog<boolean>("get_capture_high_bit_depth");
+  },
+
+  setCaptureHighBitDepth: async (enabled: boolean): Promise<void> => {
+    return await invokeWithLog("set_capture_high_bit_depth", { enabled }, { enabled });
+  },
+
+  getCaptureShowCursor: async (): Promise<boolean> => {
+    return await invokeWithLog<boolean>("get_capture_show_cursor");
+  },
+
+  setCaptureShowCursor: async (enabled: boolean): Promise<void> => {
+    return await invokeWithLog("set_capture_show_cursor", { enabled }, { enabled });
+  },
+
+  getCaptureSampleRatio: async (): Promise<number> => {
+    return await invokeWithLog<number>("get_capture_sample_ratio");
+  },
+
+  setCaptureSampleRatio: async (percent: number): Promise<void> => {
+    return await invokeWithLog("set_capture_sample_ratio", { percent }, { percent });
+  },
+
+  getCaptureToneMapping: async (): Promise<ToneMapping> => {
+    return await invokeWithLog<ToneMapping>("get_capture_tone_mapping");
+  },
+
+  /** DXGI HDR capture only; rebuilds the capture pipeline when the settings change. */
+  setCaptureToneMapping: async (toneMapping: ToneMapping): Promise<void> => {
+    return await invokeWithLog("set_capture_tone_mapping", { toneMapping }, { ...toneMapping });
+  },
+  getCaptureTargetNits: async (): Promise<number> => {
+    return await invokeWithLog<number>("get_capture_target_nits");
+  },
+  /** 返回钳制（100–4000）后实际生效的值 */
+  setCaptureTargetNits: async (nits: number): Promise<number> => {
+    return await invokeWithLog<number>("set_capture_target_nits", { nits }, { nits });
+  },
+
+  getCaptureCrop: async (): Promise<CropRect | null> => {
+    return await invokeWithLog<CropRect | null>("get_capture_crop");
+  },
+
+  /** `null` captures the whole display; resolves to the crop as clamped by the backend. */
+  setCaptureCrop: async (crop: CropRect | null): Promise<CropRect | null> => {
+    return await invokeWithLog<CropRect | null>("set_capture_crop", { crop }, { crop });
+  },
+
+  getCapturePaused: async (): Promise<boolean> => {
+    return await invokeWithLog<boolean>("get_capture_paused");
+  },
+
+  /** Frees screen capture resources (e.g. while minimized) without removing effects. */
+  setCapturePaused: async (paused: boolean): Promise<void> => {
+    return await invokeWithLog("set_capture_paused", { paused }, { paused });
+  },
+
+  /** False on macOS until Screen Recording permission is granted; always true elsewhere. */
+  checkScreenPermission: async (): Promise<boolean> => {
+    return await invokeWithLog<boolean>("check_screen_permission");
+  },
+
+  openScreenPermissionSettings: async (): Promise<void> => {
+    return await invokeWithLog("open_screen_permission_settings");
+  },
+
   getWindowEffects: async (): Promise<WindowEffectId[]> => {
     return await invokeWithLog<WindowEffectId[]>("get_window_effects");
   },
@@ -149,6 +613,41 @@ export const api = {
     return await invokeWithLog("set_minimize_to_tray", { enabled }, { enabled });
   },
 
+  getControllerPreferences: async (): Promise<Record<string, string>> => {
+    return await invokeWithLog<Record<string, string>>("get_controller_preferences");
+  },
+
+  /** Pass `null` to clear the preference; applies on the next scan. */
+  setControllerPreference: async (serialId: string, driver: string | null): Promise<void> => {
+    return await invokeWithLog(
+      "set_controller_preference",
+      { serialId, driver },
+      { serialId, driver },
+    );
+  },
+
+  /** Counts key presses or clicks for input-reactive effects such as Typing Pulse. */
+  reportInputActivity: async (kind: "key" | "mouse", count?: number): Promise<void> => {
+    return await invokeWithLog("report_input_activity", { kind, count }, { kind, count });
+  },
+
+  getHotkeys: async (): Promise<HotkeyBinding[]> => {
+    return await invokeWithLog<HotkeyBinding[]>("get_hotkeys");
+  },
+
+  /** Adds or replaces a binding by id; returns the bindings the OS refused. */
+  setHotkey: async (binding: HotkeyBinding): Promise<HotkeyRegistrationFailure[]> => {
+    return await invokeWithLog<HotkeyRegistrationFailure[]>(
+      "set_hotkey",
+      { binding },
+      { id: binding.id, shortcut: binding.shortcut },
+    );
+  },
+
+  deleteHotkey: async (id: string): Promise<HotkeyRegistrationFailure[]> => {
+    return await invokeWithLog<HotkeyRegistrationFailure[]>("delete_hotkey", { id }, { id });
+  },
+
   // --- Persisted configuration
 
   getAppConfig: async (): Promise<AppConfig> => {
@@ -162,4 +661,23 @@ export const api = {
   getDeviceConfig: async (port: string): Promise<DeviceConfigResponse> => {
     return await invokeWithLog<DeviceConfigResponse>("get_device_config", { port }, { port });
   },
+
+  // --- Scenes
+
+  saveScene: async (name: string): Promise<void> => {
+    return await invokeWithLog("save_scene", { name }, { name });
+  },
+
+  listScenes: async (): Promise<SceneSummary[]> => {
+    return await invokeWithLog<SceneSummary[]>("list_scenes");
+  },
+
+  /** Returns the device list after the scene has been applied. */
+  applyScene: async (name: string): Promise<Device[]> => {
+    return await invokeWithLog<Device[]>("apply_scene", { name }, { name });
+  },
+
+  deleteScene: async (name: string): Promise<void> => {
+    return await invokeWithLog("delete_scene", { name }, { name });
+  },
 };
@@ -1,4 +1,4 @@
-import type { AppConfig, ScreenCaptureConfig } from "../types";
+import type { AppConfig, DeviceDiscoveryConfig, DrgbKeepaliveConfig, ScreenCaptureConfig } from "../types";
 import type { CaptureMethod, WindowEffectId } from "./api";
 import { api } from "./api";
 import { normalizeCaptureMaxPixels } from "../utils/captureQuality";
@@ -57,6 +57,14 @@ export const configManager = {
         ...current.screenCapture,
         ...(patch.screenCapture ?? {}),
       } as ScreenCaptureConfig,
+      deviceDiscovery: {
+        ...current.deviceDiscovery,
+        ...(patch.deviceDiscovery ?? {}),
+      } as DeviceDiscoveryConfig,
+      drgbKeepalive: {
+        ...current.drgbKeepalive,
+        ...(patch.drgbKeepalive ?? {}),
+      } as DrgbKeepaliveConfig,
     };
 
     return await configManager.setAppConfig(merged);
@@ -66,6 +74,30 @@ export const configManager = {
     return await configManager.updateAppConfig({ minimizeToTray: enabled });
   },
 
+  setClearLedsOnExit: async (enabled: boolean): Promise<AppConfig> => {
+    return await configManager.updateAppConfig({ clearLedsOnExit: enabled });
+  },
+
+  setPartialUpdates: async (enabled: boolean): Promise<AppConfig> => {
+    return await configManager.updateAppConfig({ partialUpdates: enabled });
+  },
+
+  setAdaptiveFrameRate: async (enabled: boolean): Promise<AppConfig> => {
+    return await configManager.updateAppConfig({ adaptiveFrameRate: enabled });
+  },
+
+  setSoftStartMs: async (ms: number): Promise<AppConfig> => {
+    return await configManager.updateAppConfig({ softStartMs: ms });
+  },
+
+  setWriteRetries: async (retries: number): Promise<AppConfig> => {
+    return await configManager.updateAppConfig({ writeRetries: retries });
+  },
+
+  setDrgbKeepalive: async (patch: Partial<DrgbKeepaliveConfig>): Promise<AppConfig> => {
+    return await configManager.updateAppConfig({ drgbKeepalive: patch as DrgbKeepaliveConfig });
+  },
+
   setWindowEffect: async (effect: WindowEffectId): Promise<AppConfig> => {
     return await configManager.updateAppConfig({ windowEffect: effect });
   },
@@ -84,6 +116,30 @@ export const configManager = {
     return await configManager.updateAppConfig({ screenCapture: { fps } as ScreenCaptureConfig });
   },
 
+  setCaptureHighBitDepth: async (highBitDepth: boolean): Promise<AppConfig> => {
+    return await configManager.updateAppConfig({
+      screenCapture: { highBitDepth } as ScreenCaptureConfig,
+    });
+  },
+
+  setCaptureShowCursor: async (showCursor: boolean): Promise<AppConfig> => {
+    return await configManager.updateAppConfig({
+      screenCapture: { showCursor } as ScreenCaptureConfig,
+    });
+  },
+
+  setMdnsTimeoutMs: async (mdnsTimeoutMs: number): Promise<AppConfig> => {
+    return await configManager.updateAppConfig({
+      deviceDiscovery: { mdnsTimeoutMs } as DeviceDiscoveryConfig,
+    });
+  },
+
+  setNetworkSourceAddress: async (sourceAddress: string | null): Promise<AppConfig> => {
+    return await configManager.updateAppConfig({
+      deviceDiscovery: { sourceAddress: sourceAddress ?? undefined } as DeviceDiscoveryConfig,
+    });
+  },
+
   // Useful in dev / debug pages.
   getDeviceConfig: async (port: string) => {
     return await api.getDeviceConfig(port);
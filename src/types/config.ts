@@ -1,10 +1,93 @@
 import type { CaptureMethod, WindowEffectId } from "../services/api";
-import type { SegmentType } from "./device";
+import type {
+  AudioBrightnessSettings,
+  BlendMode,
+  ColorVisionMode,
+  LedPosition,
+  SegmentType,
+} from "./device";
 
 export interface ScreenCaptureConfig {
   maxPixels: number;
   fps: number;
   method: CaptureMethod;
+  /** Request 10-bit capture on SDR displays (Windows DXGI). */
+  highBitDepth: boolean;
+  /** Include the mouse pointer in captured frames (not available with DXGI or xcap). */
+  showCursor: boolean;
+  /** Percentage of each zone's pixels screen effects average (1–100). */
+  sampleRatio?: number;
+  /** HDR to LED tone mapping (DXGI HDR capture only). */
+  toneMapping?: ToneMapping;
+  /** HDR brightness in nits that lights the LEDs fully (100–4000, DXGI HDR capture only). */
+  targetNits?: number;
+  /** Part of the display captured; absent captures all of it. */
+  crop?: CropRect;
+}
+
+/** Rectangle in fractions (0–1) of the display's size, from its top-left corner. */
+export interface CropRect {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+export type ToneMapOperator = "linear" | "reinhard" | "aces" | "hable";
+
+export interface ToneMapping {
+  /** Multiplier on captured light (0.1–8). */
+  exposure: number;
+  /** Output level where highlights start rolling off (0.1–1); 1 clips hard. */
+  knee: number;
+  operator: ToneMapOperator;
+}
+
+export interface SacnNode {
+  /** Unicast target; absent for multicast. */
+  ip?: string;
+  /** First universe (1–63999); LEDs fill 170 per universe from here. */
+  universe: number;
+  ledCount: number;
+}
+
+export interface DeviceDiscoveryConfig {
+  mdnsTimeoutMs: number;
+  /** WiZ bulb addresses remembered from earlier scans. */
+  wizBulbs: string[];
+  /** E1.31 (sACN) nodes added by hand. */
+  sacnNodes: SacnNode[];
+  /** Local IPv4/IPv6 address whose adapter network controllers send from; unset lets the OS route. */
+  sourceAddress?: string;
+  /** Ask UDP LED matrices to confirm frames; applies to devices found by later scans. */
+  frameAcks?: boolean;
+  /** Largest UDP payload sent to LED matrices, in bytes (548–8952); applies to later scans. */
+  udpPayload?: number;
+}
+
+export interface DrgbKeepaliveConfig {
+  intervalMs: number;
+  idleThresholdMs: number;
+}
+
+export type HotkeyAction =
+  | { type: "applyScene"; name: string }
+  | { type: "cycleEffect" }
+  /** Change every device's brightness by `delta` percentage points. */
+  | { type: "adjustBrightness"; delta: number }
+  | { type: "toggleAll" };
+
+export interface HotkeyBinding {
+  id: string;
+  /** Accelerator such as `CommandOrControl+Shift+L`. */
+  shortcut: string;
+  action: HotkeyAction;
+}
+
+export interface HotkeyRegistrationFailure {
+  id: string;
+  shortcut: string;
+  error: string;
 }
 
 export interface AppConfig {
@@ -12,6 +95,21 @@ export interface AppConfig {
   windowEffect: WindowEffectId;
   minimizeToTray: boolean;
   screenCapture: ScreenCaptureConfig;
+  deviceDiscovery: DeviceDiscoveryConfig;
+  drgbKeepalive: DrgbKeepaliveConfig;
+  clearLedsOnExit: boolean;
+  partialUpdates: boolean;
+  /** Slow a device's frame rate down to what its connection accepts. */
+  adaptiveFrameRate: boolean;
+  /** Brightness ramp in ms when an effect lights an output up from dark; 0 disables it. */
+  softStartMs: number;
+  /** Color-blindness correction for devices without their own setting. */
+  colorVision?: ColorVisionMode;
+  /** Extra attempts for serial/HID writes that fail transiently (0..=5). */
+  writeRetries: number;
+  /** Driver name to keep, keyed by device serial id, when several drivers detect one device. */
+  controllerPreferences: Record<string, string>;
+  hotkeys: HotkeyBinding[];
 }
 
 // --- Device config persistence (devices/<deviceId>.json)
@@ -19,6 +117,16 @@ export interface AppConfig {
 export interface PersistedModeConfig {
   selected: string | null;
   params: Record<string, Record<string, unknown>>;
+  overlay?: PersistedOverlayConfig;
+  phaseOffsetMs?: number;
+  audioBrightness?: AudioBrightnessSettings;
+}
+
+export interface PersistedOverlayConfig {
+  effectId: string;
+  params: Record<string, unknown>;
+  opacity: number;
+  blend: BlendMode;
 }
 
 export interface SegmentDefinition {
@@ -31,6 +139,8 @@ export interface SegmentDefinition {
     height: number;
     map: Array<number | null>;
   };
+  /** Wired end to start; defaults to false. */
+  reversed?: boolean;
 }
 
 export interface PersistedDeviceSection {
@@ -43,9 +153,16 @@ export interface PersistedDeviceSection {
   layout: Record<
     string,
     {
+      /** User-configured LED count for outputs whose length is chosen by the host. */
+      ledsCount?: number;
       segments: SegmentDefinition[];
+      /** Free-form LED coordinates, one per LED. */
+      positions?: LedPosition[];
+      /** Only present (as false) when the output is disabled. */
+      enabled?: boolean;
     }
   >;
+  colorVision?: ColorVisionMode;
 }
 
 export interface PersistedSegmentEffectsConfig {
@@ -53,6 +170,9 @@ export interface PersistedSegmentEffectsConfig {
   brightness?: number;
   selected: string | null;
   params: Record<string, Record<string, unknown>>;
+  overlay?: PersistedOverlayConfig;
+  phaseOffsetMs?: number;
+  audioBrightness?: AudioBrightnessSettings;
 }
 
 export interface PersistedOutputEffectsConfig {
@@ -60,6 +180,9 @@ export interface PersistedOutputEffectsConfig {
   brightness?: number;
   selected: string | null;
   params: Record<string, Record<string, unknown>>;
+  overlay?: PersistedOverlayConfig;
+  phaseOffsetMs?: number;
+  audioBrightness?: AudioBrightnessSettings;
   segments: PersistedSegmentEffectsConfig[];
 }
 
@@ -67,6 +190,9 @@ export interface PersistedEffectsSection {
   // device-level
   selected: string | null;
   params: Record<string, Record<string, unknown>>;
+  overlay?: PersistedOverlayConfig;
+  phaseOffsetMs?: number;
+  audioBrightness?: AudioBrightnessSettings;
   brightness: number;
   // output / segment-level
   outputs: PersistedOutputEffectsConfig[];
@@ -77,6 +203,14 @@ export interface PersistedDeviceConfig {
   effects: PersistedEffectsSection;
 }
 
+// --- Scenes (scenes.json)
+
+export interface SceneSummary {
+  name: string;
+  /** Serial ids of the devices the scene covers. */
+  device_ids: string[];
+}
+
 export interface DeviceConfigResponse {
   deviceId: string;
   port: string;
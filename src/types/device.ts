@@ -6,6 +6,22 @@ export interface LedColor {
 
 export type SegmentType = 'Single' | 'Linear' | 'Matrix';
 
+/** Color-blindness correction applied to what a device shows. */
+export type ColorVisionMode = 'off' | 'protanopia' | 'deuteranopia' | 'tritanopia';
+
+/** Channel order a strip is wired in; the `w` orders have white channels. */
+export type ColorOrder =
+  | 'Rgb'
+  | 'Rbg'
+  | 'Grb'
+  | 'Gbr'
+  | 'Brg'
+  | 'Bgr'
+  | 'Rgbw'
+  | 'Grbw'
+  | 'Bgrw'
+  | 'Rgbww';
+
 export type DeviceType =
   | 'Motherboard'
   | 'Dram'
@@ -36,6 +52,21 @@ export interface MatrixMap {
   map: (number | null)[];
 }
 
+/** 顺时针旋转 */
+export type Rotation = 'deg0' | 'deg90' | 'deg180' | 'deg270';
+
+/** 矩阵面板的安装方向：先旋转再镜像 */
+export interface MatrixTransform {
+  rotation: Rotation;
+  flip_h: boolean;
+  flip_v: boolean;
+}
+
+export interface LedPosition {
+  x: number;
+  y: number;
+}
+
 export interface ScopeRef {
   port: string;
   output_id?: string;
@@ -51,6 +82,40 @@ export interface ScopeModeState {
   effective_params?: Record<string, number | boolean | string>;
   /** Where the effective effect comes from */
   effective_from?: ScopeRef;
+  /** Overlay composited over the effective effect (from the same origin scope) */
+  effective_overlay?: ScopeOverlayState;
+  /** Phase offset of the effective effect on the shared animation clock */
+  effective_phase_offset_ms: number;
+  /** Audio-driven brightness of the effective effect, if enabled at its origin scope */
+  effective_audio_brightness?: AudioBrightnessSettings;
+  /** Brightness (0–100) this scope renders at, inherited along with the mode */
+  effective_brightness: number;
+}
+
+export type BlendMode = "normal" | "add" | "screen" | "multiply";
+
+export type AudioBand = "full" | "bass" | "mid" | "treble";
+
+export interface AudioBrightnessSettings {
+  /** Capture device index, as listed by getAudioDevices */
+  deviceIndex: number;
+  band: AudioBand;
+  /** Loudness gain in percent (0..=1000) */
+  sensitivity: number;
+  /** Envelope rise time in ms (0..=5000) */
+  attackMs: number;
+  /** Envelope fall time in ms (0..=5000) */
+  releaseMs: number;
+  /** Brightness kept during silence, in percent */
+  floor: number;
+}
+
+export interface ScopeOverlayState {
+  effect_id: string;
+  params: Record<string, number | boolean | string>;
+  /** 0..=1 */
+  opacity: number;
+  blend: BlendMode;
 }
 
 export interface ScopeBrightnessState {
@@ -78,6 +143,7 @@ export interface Segment {
   segment_type: SegmentType;
   leds_count: number;
   matrix?: MatrixMap;
+  reversed: boolean;
   brightness: ScopeBrightnessState;
   mode: ScopeModeState;
 }
@@ -87,11 +153,63 @@ export interface OutputPort {
   name: string;
   output_type: SegmentType;
   leds_count: number;
+  /** Already rotated/mirrored by `transform`. */
   matrix?: MatrixMap;
+  /** Absent when the panel is mounted as mapped. */
+  transform?: MatrixTransform;
+  /** Physical position of each LED, in output order. */
+  positions?: LedPosition[];
   capabilities: OutputCapabilities;
   segments: Segment[];
   brightness: ScopeBrightnessState;
   mode: ScopeModeState;
+  /** Frames per second the device received over the last second; absent while idle. */
+  frame_rate?: number;
+  /** Per-output soft-start in ms; absent follows the app-wide setting. */
+  soft_start_ms?: number;
+  /** Disabled outputs stay black but keep their effects and layout. */
+  enabled: boolean;
+}
+
+export type DeviceHealth = "ok" | "error" | "disconnected";
+
+export interface DeviceStatus {
+  health: DeviceHealth;
+  last_error: string | null;
+  consecutive_failures: number;
+  /** Recent frames the device reported missing, in percent; only with frame acknowledgements. */
+  frame_loss_percent?: number;
+  /** Brightness in percent frames are dimmed to for the controller's power budget; absent
+   * while no limiting is needed. */
+  power_limit_percent?: number;
+}
+
+/** Outputs a bulk operation applies to; an empty filter selects every output of the device. */
+export interface OutputFilter {
+  outputType?: SegmentType;
+  /** Case-insensitive substring of the output name. */
+  nameContains?: string;
+}
+
+/** Why a screen-capturing effect can't get frames. `code` is stable; `message` is for display. */
+export interface CaptureFailure {
+  displayIndex: number;
+  code:
+    | "unsupported"
+    | "permission_denied"
+    | "access_lost"
+    | "os_error"
+    | "invalid_state"
+    | "no_frame";
+  message: string;
+}
+
+/** Payload of the `capture-error` event; `error` is null once the capture recovers. */
+export interface CaptureErrorEvent {
+  port: string;
+  outputId: string;
+  segmentId: string | null;
+  error: CaptureFailure | null;
 }
 
 export interface Device {
@@ -100,8 +218,17 @@ export interface Device {
   description: string;
   id: string;
   device_type: DeviceType;
+  status: DeviceStatus;
   brightness: ScopeBrightnessState;
   outputs: OutputPort[];
   mode: ScopeModeState;
+  /** Color-blindness correction for this device; absent follows the app-wide setting. */
+  color_vision?: ColorVisionMode;
+  /** Share of the previous frame kept per 60 Hz frame; 0 is off. */
+  smoothing: number;
+  /** User supply limit in mA; absent when unset. */
+  power_limit_ma?: number;
+  /** Channel order the device's frames are encoded in. */
+  color_order: ColorOrder;
 }
 
@@ -7,7 +7,14 @@ export interface ParamDependency {
   behavior?: ParamDependencyBehavior;
 }
 
-export type EffectParamValue = number | boolean | string;
+/** 色相（度，0–360）、饱和度与明度（0–1） */
+export interface HsvValue {
+  h: number;
+  s: number;
+  v: number;
+}
+
+export type EffectParamValue = number | boolean | string | HsvValue;
 
 interface EffectParamBase {
   key: string;
@@ -45,7 +52,35 @@ export interface ColorParam extends EffectParamBase {
   default: string;
 }
 
-export type EffectParam = SliderParam | SelectParam | ToggleParam | ColorParam;
+export interface TextParam extends EffectParamBase {
+  type: 'text';
+  default: string;
+  placeholder?: string;
+}
+
+export interface HsvParam extends EffectParamBase {
+  type: 'hsv';
+  default: HsvValue;
+}
+
+/** 色温，单位 K */
+export interface TemperatureParam extends EffectParamBase {
+  type: 'temperature';
+  default: number;
+  min: number;
+  max: number;
+}
+
+export type EffectParam =
+  | SliderParam
+  | SelectParam
+  | ToggleParam
+  | ColorParam
+  | TextParam
+  | HsvParam
+  | TemperatureParam;
+
+export type EffectLayout = 'any' | 'matrix';
 
 export interface EffectInfo {
   id: string;
@@ -53,5 +88,12 @@ export interface EffectInfo {
   description?: string;
   group?: string;
   icon?: string;
+  layout: EffectLayout;
   params?: EffectParam[];
 }
+
+export interface EffectGroupInfo {
+  id: string | null;
+  name: string;
+  effects: EffectInfo[];
+}